@@ -0,0 +1,45 @@
+-- Persistent Background Job Queue
+-- Replaces the in-memory job list so queued work survives restarts and can be shared by replicas
+
+-- Enable UUID extension if not already enabled
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- Background Jobs Table (leased by workers with FOR UPDATE SKIP LOCKED)
+CREATE TABLE background_jobs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    job_type VARCHAR(50) NOT NULL, -- process_training_file, calculate_pmc, cleanup_old_files
+    payload JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending', -- pending, running, completed, failed, retrying, dead_letter
+    retries INTEGER NOT NULL DEFAULT 0,
+    max_retries INTEGER NOT NULL DEFAULT 5,
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_by VARCHAR(255),
+    locked_at TIMESTAMPTZ,
+    heartbeat_at TIMESTAMPTZ,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    CONSTRAINT valid_job_status CHECK (status IN ('pending', 'running', 'completed', 'failed', 'retrying', 'dead_letter'))
+);
+
+-- Indexes for efficient querying
+CREATE INDEX idx_background_jobs_ready ON background_jobs(run_at) WHERE status IN ('pending', 'retrying');
+CREATE INDEX idx_background_jobs_running ON background_jobs(heartbeat_at) WHERE status = 'running';
+CREATE INDEX idx_background_jobs_user ON background_jobs(user_id, created_at DESC);
+
+-- Add trigger to update updated_at timestamp
+CREATE TRIGGER update_background_jobs_updated_at BEFORE UPDATE ON background_jobs
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Comments for documentation
+COMMENT ON TABLE background_jobs IS 'Durable queue for background work such as training file processing and PMC calculation';
+
+COMMENT ON COLUMN background_jobs.payload IS 'Serialized JobType describing the work to perform';
+COMMENT ON COLUMN background_jobs.retries IS 'Number of failed attempts so far';
+COMMENT ON COLUMN background_jobs.run_at IS 'Earliest time the job may be leased (pushed back on retry)';
+COMMENT ON COLUMN background_jobs.locked_by IS 'Worker currently holding the lease';
+COMMENT ON COLUMN background_jobs.heartbeat_at IS 'Last heartbeat from the leasing worker; stale leases are requeued';
+COMMENT ON COLUMN background_jobs.status IS 'dead_letter means retries were exhausted and the job needs manual attention';
@@ -0,0 +1,41 @@
+-- Data Exports
+-- Tracks user-requested exports (GDPR requests, spreadsheets) generated by the background job queue
+
+-- Enable UUID extension if not already enabled
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- Data Exports Table
+CREATE TABLE data_exports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    format VARCHAR(20) NOT NULL, -- csv, jsonl, zip
+    datasets TEXT[] NOT NULL, -- sessions, pmc, goals, events, hrv, sleep, resting_hr, predictions
+    start_date DATE,
+    end_date DATE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending', -- pending, processing, completed, failed, expired
+    job_id UUID REFERENCES background_jobs(id) ON DELETE SET NULL,
+    storage_key TEXT,
+    file_name VARCHAR(255),
+    size_bytes BIGINT,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    CONSTRAINT valid_export_format CHECK (format IN ('csv', 'jsonl', 'zip')),
+    CONSTRAINT valid_export_status CHECK (status IN ('pending', 'processing', 'completed', 'failed', 'expired')),
+    CONSTRAINT valid_export_range CHECK (start_date IS NULL OR end_date IS NULL OR start_date <= end_date)
+);
+
+-- Indexes for efficient querying
+CREATE INDEX idx_data_exports_user ON data_exports(user_id, created_at DESC);
+CREATE INDEX idx_data_exports_expiry ON data_exports(expires_at) WHERE status = 'completed';
+
+-- Add trigger to update updated_at timestamp
+CREATE TRIGGER update_data_exports_updated_at BEFORE UPDATE ON data_exports
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Comments for documentation
+COMMENT ON TABLE data_exports IS 'User data exports rendered by background jobs and served until they expire';
+
+COMMENT ON COLUMN data_exports.storage_key IS 'Path of the rendered file relative to the export directory';
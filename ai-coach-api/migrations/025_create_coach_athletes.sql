@@ -0,0 +1,49 @@
+-- Coach-Athlete Relationships
+-- Links coaches to the athletes who accepted their invitation, with per-scope access, and audits every access
+
+-- Enable UUID extension if not already enabled
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- Coach-Athlete Table (one row per pair, reused when a coach re-invites)
+CREATE TABLE coach_athletes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    coach_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    athlete_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending', -- pending, active, declined, revoked
+    can_read_training BOOLEAN NOT NULL DEFAULT TRUE,
+    can_read_recovery BOOLEAN NOT NULL DEFAULT FALSE,
+    can_edit_plans BOOLEAN NOT NULL DEFAULT FALSE,
+    invited_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    responded_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    CONSTRAINT valid_coach_athlete_status CHECK (status IN ('pending', 'active', 'declined', 'revoked')),
+    CONSTRAINT no_self_coaching CHECK (coach_id <> athlete_id),
+    UNIQUE(coach_id, athlete_id)
+);
+
+-- Coach Access Audit Table
+CREATE TABLE coach_access_audit (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    coach_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    athlete_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    scope VARCHAR(30) NOT NULL, -- read_training, read_recovery, edit_plans
+    action VARCHAR(100) NOT NULL, -- e.g. list_sessions, create_plan
+    resource_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Indexes for efficient querying
+CREATE INDEX idx_coach_athletes_coach ON coach_athletes(coach_id, status);
+CREATE INDEX idx_coach_athletes_athlete ON coach_athletes(athlete_id, status);
+CREATE INDEX idx_coach_access_audit_athlete ON coach_access_audit(athlete_id, created_at DESC);
+CREATE INDEX idx_coach_access_audit_coach ON coach_access_audit(coach_id, created_at DESC);
+
+-- Add trigger to update updated_at timestamp
+CREATE TRIGGER update_coach_athletes_updated_at BEFORE UPDATE ON coach_athletes
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Comments for documentation
+COMMENT ON TABLE coach_athletes IS 'Coach rosters: which athletes a coach may act on behalf of, and for what';
+COMMENT ON TABLE coach_access_audit IS 'Every read or write a coach performs on an athlete''s data';
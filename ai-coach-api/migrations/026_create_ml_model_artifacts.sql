@@ -0,0 +1,33 @@
+-- ML Model Artifacts
+-- Serialized TSS prediction models with their scaler, feature schema and metrics, so they survive restarts
+
+-- Enable UUID extension if not already enabled
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- Model Artifacts Table (one row per trained model version per user)
+CREATE TABLE ml_model_artifacts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    model_version VARCHAR(50) NOT NULL,
+    model_type VARCHAR(30) NOT NULL, -- linear_regression, random_forest
+    format_version INTEGER NOT NULL,
+    feature_schema TEXT[] NOT NULL, -- feature names in training column order
+    metrics JSONB NOT NULL,
+    artifact BYTEA NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    activated_at TIMESTAMPTZ,
+    UNIQUE(user_id, model_version)
+);
+
+-- Indexes for efficient querying
+CREATE INDEX idx_ml_model_artifacts_user ON ml_model_artifacts(user_id, created_at DESC);
+
+-- At most one active model per user
+CREATE UNIQUE INDEX idx_ml_model_artifacts_active ON ml_model_artifacts(user_id) WHERE is_active;
+
+-- Comments for documentation
+COMMENT ON TABLE ml_model_artifacts IS 'Persisted ML model artifacts keyed per user and version';
+COMMENT ON COLUMN ml_model_artifacts.feature_schema IS 'TrainingFeatures column order the model was fitted on; loading fails if it no longer matches';
+COMMENT ON COLUMN ml_model_artifacts.is_active IS 'Model used for predictions; restored lazily on the first prediction after startup';
@@ -0,0 +1,98 @@
+-- ML Model Registry and Experiments
+-- Model versions, champion deployments, A/B experiments and the per-prediction log their analysis is based on
+
+-- Enable UUID extension if not already enabled
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- Model Versions Table
+CREATE TABLE ml_model_versions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    version VARCHAR(50) NOT NULL UNIQUE,
+    model_type VARCHAR(30) NOT NULL, -- linear_regression, random_forest
+    status VARCHAR(20) NOT NULL DEFAULT 'validation',
+    metrics JSONB NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deployed_at TIMESTAMPTZ,
+    retired_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    CONSTRAINT valid_model_version_status CHECK (status IN (
+        'training', 'validation', 'staging', 'production', 'champion', 'challenger', 'retired'
+    ))
+);
+
+-- Experiments Table
+CREATE TABLE ml_experiments (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(255) NOT NULL,
+    champion_version VARCHAR(50) NOT NULL REFERENCES ml_model_versions(version),
+    challenger_version VARCHAR(50) NOT NULL REFERENCES ml_model_versions(version),
+    traffic_split REAL NOT NULL, -- share of users assigned to the challenger
+    start_date TIMESTAMPTZ NOT NULL,
+    end_date TIMESTAMPTZ NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'planning',
+    target_metric VARCHAR(50) NOT NULL,
+    min_sample_size INTEGER NOT NULL,
+    significance_threshold REAL NOT NULL,
+    winner VARCHAR(50),
+    results JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    CONSTRAINT valid_experiment_status CHECK (status IN ('planning', 'running', 'analyzing', 'completed', 'cancelled')),
+    CONSTRAINT valid_traffic_split CHECK (traffic_split >= 0 AND traffic_split <= 1),
+    CONSTRAINT distinct_experiment_arms CHECK (champion_version <> challenger_version)
+);
+
+-- Deployment History Table (one row per champion change)
+CREATE TABLE ml_model_deployments (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    model_version_id UUID NOT NULL REFERENCES ml_model_versions(id) ON DELETE CASCADE,
+    version VARCHAR(50) NOT NULL,
+    previous_version VARCHAR(50),
+    experiment_id UUID REFERENCES ml_experiments(id) ON DELETE SET NULL, -- set when promoted from an A/B experiment
+    deployed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Prediction Log Table (one row per served prediction, with the experiment arm it was assigned to)
+CREATE TABLE ml_prediction_logs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    experiment_id UUID REFERENCES ml_experiments(id) ON DELETE SET NULL,
+    arm VARCHAR(20), -- champion, challenger; NULL outside experiments
+    model_version VARCHAR(50) NOT NULL, -- registry version for experiment arms, otherwise the served model
+    served_model_version VARCHAR(50) NOT NULL,
+    predicted_tss REAL NOT NULL,
+    confidence REAL NOT NULL,
+    latency_ms REAL NOT NULL,
+    prediction_date DATE NOT NULL, -- day the prediction is for, compared against that day's sessions
+    predicted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT valid_prediction_arm CHECK (arm IS NULL OR arm IN ('champion', 'challenger'))
+);
+
+-- Indexes for efficient querying
+CREATE INDEX idx_ml_model_versions_status ON ml_model_versions(status);
+CREATE INDEX idx_ml_model_deployments_deployed ON ml_model_deployments(deployed_at DESC);
+CREATE INDEX idx_ml_experiments_status ON ml_experiments(status);
+CREATE INDEX idx_ml_prediction_logs_experiment ON ml_prediction_logs(experiment_id, arm);
+CREATE INDEX idx_ml_prediction_logs_version ON ml_prediction_logs(model_version, predicted_at);
+CREATE INDEX idx_ml_prediction_logs_user_date ON ml_prediction_logs(user_id, prediction_date);
+
+-- Only one champion at a time
+CREATE UNIQUE INDEX idx_ml_model_versions_champion ON ml_model_versions(status) WHERE status = 'champion';
+
+-- Only one running experiment at a time
+CREATE UNIQUE INDEX idx_ml_experiments_running ON ml_experiments(status) WHERE status = 'running';
+
+-- Add triggers to update updated_at timestamp
+CREATE TRIGGER update_ml_model_versions_updated_at BEFORE UPDATE ON ml_model_versions
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_ml_experiments_updated_at BEFORE UPDATE ON ml_experiments
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Comments for documentation
+COMMENT ON TABLE ml_model_versions IS 'Registry of TSS prediction model versions and their lifecycle status';
+COMMENT ON TABLE ml_model_deployments IS 'History of champion deployments';
+COMMENT ON TABLE ml_experiments IS 'Champion/challenger A/B experiments';
+COMMENT ON TABLE ml_prediction_logs IS 'Served predictions, compared against realised session TSS to evaluate experiment arms';
@@ -0,0 +1,25 @@
+-- Calendar Feeds
+-- Revocable per-user tokens for the iCalendar subscription feed, and source UIDs for imported events
+
+-- Feed Tokens Table (only the hash of the token is stored)
+CREATE TABLE calendar_feed_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    include_events BOOLEAN NOT NULL DEFAULT TRUE,
+    include_workouts BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_accessed_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+
+-- At most one live feed per user; creating a new one revokes the old URL
+CREATE UNIQUE INDEX idx_calendar_feed_tokens_active ON calendar_feed_tokens(user_id) WHERE revoked_at IS NULL;
+
+-- UID of the VEVENT an event was imported from, so re-importing a calendar updates it in place
+ALTER TABLE events ADD COLUMN external_uid VARCHAR(255);
+CREATE UNIQUE INDEX idx_events_external_uid ON events(user_id, external_uid) WHERE external_uid IS NOT NULL;
+
+-- Comments for documentation
+COMMENT ON TABLE calendar_feed_tokens IS 'Token-authenticated .ics subscription feeds for events and plan workouts';
+COMMENT ON COLUMN events.external_uid IS 'iCalendar UID of the imported VEVENT';
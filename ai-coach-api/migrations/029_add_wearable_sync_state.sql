@@ -0,0 +1,20 @@
+-- Wearable Sync State
+-- Per-connection cursor, schedule and failure tracking for background wearable sync
+
+ALTER TABLE wearable_connections
+    ADD COLUMN sync_cursor DATE,
+    ADD COLUMN next_sync_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN auth_failures INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN needs_reauth BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN last_sync_error TEXT;
+
+-- Connections the scheduler should pick up, oldest due first
+CREATE INDEX idx_wearable_connections_due ON wearable_connections(next_sync_at)
+    WHERE is_active AND NOT needs_reauth;
+
+-- Comments for documentation
+COMMENT ON COLUMN wearable_connections.sync_cursor IS 'Last date fully synced; the next sync starts a few days before it';
+COMMENT ON COLUMN wearable_connections.next_sync_at IS 'When the scheduler should next sync this connection (pushed out by backoff after failures)';
+COMMENT ON COLUMN wearable_connections.auth_failures IS 'Consecutive syncs rejected by the provider as unauthorized';
+COMMENT ON COLUMN wearable_connections.needs_reauth IS 'Scheduled sync stopped until the user reconnects the provider';
@@ -0,0 +1,55 @@
+-- Health Imports
+-- Apple Health and Health Connect export uploads, parsed into recovery data by the background job queue
+
+-- Enable UUID extension if not already enabled
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- Health Connect becomes a recovery data source alongside Apple Health
+ALTER TABLE hrv_readings DROP CONSTRAINT hrv_readings_source_check,
+    ADD CONSTRAINT hrv_readings_source_check
+    CHECK (source IN ('oura', 'whoop', 'manual', 'apple_health', 'health_connect', 'garmin', 'polar', 'fitbit'));
+ALTER TABLE sleep_data DROP CONSTRAINT sleep_data_source_check,
+    ADD CONSTRAINT sleep_data_source_check
+    CHECK (source IN ('oura', 'whoop', 'manual', 'apple_health', 'health_connect', 'garmin', 'polar', 'fitbit'));
+ALTER TABLE resting_hr_data DROP CONSTRAINT resting_hr_data_source_check,
+    ADD CONSTRAINT resting_hr_data_source_check
+    CHECK (source IN ('oura', 'whoop', 'manual', 'apple_health', 'health_connect', 'garmin', 'polar', 'fitbit'));
+
+-- Health Imports Table
+CREATE TABLE health_imports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    format VARCHAR(20) NOT NULL, -- apple_health, health_connect
+    status VARCHAR(20) NOT NULL DEFAULT 'uploading', -- uploading, pending, parsing, importing, completed, failed
+    job_id UUID REFERENCES background_jobs(id) ON DELETE SET NULL,
+    file_name VARCHAR(255),
+    storage_key TEXT,
+    size_bytes BIGINT,
+    progress_percent SMALLINT NOT NULL DEFAULT 0,
+    records_read INTEGER NOT NULL DEFAULT 0,
+    hrv_imported INTEGER NOT NULL DEFAULT 0,
+    sleep_imported INTEGER NOT NULL DEFAULT 0,
+    resting_hr_imported INTEGER NOT NULL DEFAULT 0,
+    duplicates_skipped INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    CONSTRAINT valid_import_format CHECK (format IN ('apple_health', 'health_connect')),
+    CONSTRAINT valid_import_status CHECK (status IN ('uploading', 'pending', 'parsing', 'importing', 'completed', 'failed')),
+    CONSTRAINT valid_import_progress CHECK (progress_percent BETWEEN 0 AND 100)
+);
+
+-- Indexes for efficient querying
+CREATE INDEX idx_health_imports_user ON health_imports(user_id, created_at DESC);
+
+-- Add trigger to update updated_at timestamp
+CREATE TRIGGER update_health_imports_updated_at BEFORE UPDATE ON health_imports
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Comments for documentation
+COMMENT ON TABLE health_imports IS 'Uploaded Apple Health / Health Connect exports and the progress of importing them';
+
+COMMENT ON COLUMN health_imports.storage_key IS 'Path of the uploaded file relative to the import directory; cleared once imported';
+COMMENT ON COLUMN health_imports.duplicates_skipped IS 'Records already stored, or on a day another source already covers';
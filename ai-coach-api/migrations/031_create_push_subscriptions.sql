@@ -0,0 +1,47 @@
+-- Web Push
+-- Browser push subscriptions per device, and the per-channel outcome of every notification delivery
+
+-- Push Subscriptions Table (one row per browser PushSubscription)
+CREATE TABLE push_subscriptions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    endpoint TEXT NOT NULL UNIQUE,
+    p256dh_key VARCHAR(255) NOT NULL,
+    auth_secret VARCHAR(255) NOT NULL,
+    device_name VARCHAR(255),
+    user_agent TEXT,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    last_success_at TIMESTAMP WITH TIME ZONE,
+    last_failure_at TIMESTAMP WITH TIME ZONE,
+    failure_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_push_subscriptions_user ON push_subscriptions(user_id);
+
+CREATE TRIGGER update_push_subscriptions_updated_at
+    BEFORE UPDATE ON push_subscriptions
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Notification Deliveries Table (one row per notification and channel)
+CREATE TABLE notification_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    notification_id UUID NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel VARCHAR(20) NOT NULL CHECK (channel IN ('in_app', 'email', 'web_push', 'sms')),
+    status VARCHAR(20) NOT NULL CHECK (status IN ('scheduled', 'sent', 'delivered', 'failed', 'cancelled')),
+    error_message TEXT,
+    attempted_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE(notification_id, channel)
+);
+
+CREATE INDEX idx_notification_deliveries_user ON notification_deliveries(user_id, attempted_at DESC);
+
+-- Comments for documentation
+COMMENT ON TABLE push_subscriptions IS 'Browser Web Push subscriptions; removed when the push service reports them gone';
+COMMENT ON COLUMN push_subscriptions.p256dh_key IS 'Subscription ECDH public key (base64url), used to encrypt payloads';
+COMMENT ON COLUMN push_subscriptions.auth_secret IS 'Subscription authentication secret (base64url)';
+COMMENT ON TABLE notification_deliveries IS 'Per-channel delivery status of notifications, used for channel metrics';
+COMMENT ON COLUMN notification_deliveries.status IS 'cancelled means the channel had nowhere to deliver, e.g. no push subscriptions';
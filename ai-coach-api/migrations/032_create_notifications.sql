@@ -0,0 +1,114 @@
+-- Notifications and Digests
+-- Stored notifications, so delivery can be deferred past quiet hours or rolled into digests
+
+-- Notification type enum
+CREATE TYPE notification_type AS ENUM (
+    'workout_reminder',
+    'rest_day_reminder',
+    'ftp_test_suggestion',
+    'fitness_improvement',
+    'goal_achievement',
+    'performance_decline',
+    'overtraining_risk',
+    'negative_tsb_alert',
+    'injury_risk',
+    'weekly_progress_summary',
+    'achievement_badge',
+    'training_streak',
+    'system_maintenance',
+    'security_alert'
+);
+
+-- Notification category enum
+CREATE TYPE notification_category AS ENUM (
+    'training',
+    'performance',
+    'health',
+    'motivation',
+    'system'
+);
+
+-- Notification priority enum
+CREATE TYPE notification_priority AS ENUM (
+    'low',
+    'medium',
+    'high',
+    'critical'
+);
+
+-- Delivery channel enum
+CREATE TYPE delivery_channel AS ENUM (
+    'in_app',
+    'email',
+    'web_push',
+    'sms'
+);
+
+-- Delivery status enum
+CREATE TYPE delivery_status AS ENUM (
+    'scheduled',
+    'sent',
+    'delivered',
+    'failed',
+    'cancelled'
+);
+
+-- Digest cadence enum
+CREATE TYPE batch_type AS ENUM (
+    'hourly',
+    'daily',
+    'weekly',
+    'custom'
+);
+
+-- Notification Batches Table (digests collecting notifications until scheduled_at)
+CREATE TABLE notification_batches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    batch_type batch_type NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    summary TEXT NOT NULL DEFAULT '',
+    scheduled_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    sent_at TIMESTAMP WITH TIME ZONE,
+    delivery_channels delivery_channel[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- A user has at most one open digest; new notifications join it
+CREATE UNIQUE INDEX idx_notification_batches_open ON notification_batches(user_id) WHERE sent_at IS NULL;
+CREATE INDEX idx_notification_batches_due ON notification_batches(scheduled_at) WHERE sent_at IS NULL;
+
+-- Notifications Table
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    notification_type notification_type NOT NULL,
+    category notification_category NOT NULL,
+    priority notification_priority NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    message TEXT NOT NULL,
+    data JSONB,
+    scheduled_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    sent_at TIMESTAMP WITH TIME ZONE,
+    read_at TIMESTAMP WITH TIME ZONE,
+    delivery_channels delivery_channel[] NOT NULL,
+    delivery_status delivery_status NOT NULL DEFAULT 'scheduled',
+    batch_id UUID REFERENCES notification_batches(id) ON DELETE SET NULL,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_notifications_user ON notifications(user_id, created_at DESC);
+CREATE INDEX idx_notifications_due ON notifications(scheduled_at) WHERE delivery_status = 'scheduled' AND batch_id IS NULL;
+CREATE INDEX idx_notifications_batch ON notifications(batch_id) WHERE batch_id IS NOT NULL;
+
+CREATE TRIGGER update_notifications_updated_at
+    BEFORE UPDATE ON notifications
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Comments for documentation
+COMMENT ON TABLE notifications IS 'User notifications; scheduled_at is pushed past quiet hours when delivery is deferred';
+COMMENT ON COLUMN notifications.batch_id IS 'Digest the notification is delivered in instead of on its own';
+COMMENT ON TABLE notification_batches IS 'Hourly/daily/weekly notification digests sent by email and in-app';
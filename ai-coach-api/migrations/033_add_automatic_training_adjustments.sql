@@ -0,0 +1,32 @@
+-- Automatic Training Adjustments
+-- Recovery-driven changes the daily auto-adjust job applies to generated plans, with enough
+-- of the original workout kept to undo them
+
+ALTER TABLE training_adjustments
+    ADD COLUMN plan_id UUID REFERENCES generated_plans(id) ON DELETE CASCADE,
+    ADD COLUMN week_number INTEGER,
+    ADD COLUMN day_of_week INTEGER CHECK (day_of_week IS NULL OR (day_of_week >= 1 AND day_of_week <= 7)),
+    ADD COLUMN original_workout JSONB,
+    ADD COLUMN adjusted_workout JSONB,
+    ADD COLUMN reasoning TEXT,
+    ADD COLUMN automatic BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN applied_at TIMESTAMPTZ,
+    ADD COLUMN reverted_at TIMESTAMPTZ;
+
+-- The job decides at most once per user and day
+CREATE UNIQUE INDEX idx_training_adjustments_automatic_daily ON training_adjustments(user_id, adjustment_date) WHERE automatic;
+
+-- Users who opted in to automatic adjustments
+CREATE INDEX idx_training_recovery_settings_auto_adjust ON training_recovery_settings(user_id) WHERE auto_adjust_enabled;
+
+-- Athletes are told when their plan was changed for them
+ALTER TYPE notification_type ADD VALUE IF NOT EXISTS 'training_adjustment';
+
+-- Comments for documentation
+COMMENT ON COLUMN training_adjustments.plan_id IS 'Generated plan whose workout was adjusted';
+COMMENT ON COLUMN training_adjustments.week_number IS 'Plan week of the adjusted workout';
+COMMENT ON COLUMN training_adjustments.day_of_week IS 'Day of the adjusted workout (1=Monday)';
+COMMENT ON COLUMN training_adjustments.original_workout IS 'Planned workout before the adjustment, restored on revert';
+COMMENT ON COLUMN training_adjustments.adjusted_workout IS 'Workout after the adjustment; NULL when it became a rest day';
+COMMENT ON COLUMN training_adjustments.automatic IS 'Decided by the daily auto-adjust job rather than suggested on request';
+COMMENT ON COLUMN training_adjustments.reverted_at IS 'When the athlete undid the adjustment';
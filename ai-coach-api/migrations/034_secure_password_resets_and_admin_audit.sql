@@ -0,0 +1,27 @@
+-- Password Resets and Admin Audit
+-- Reset tokens are stored hashed and consumed once; admin changes to user accounts are recorded
+
+-- Only a hash of the emailed token is kept, so outstanding plaintext tokens are dropped
+DELETE FROM password_reset_tokens;
+ALTER TABLE password_reset_tokens RENAME COLUMN token TO token_hash;
+ALTER INDEX idx_password_reset_tokens_token RENAME TO idx_password_reset_tokens_token_hash;
+ALTER TABLE password_reset_tokens ADD COLUMN used_at TIMESTAMP WITH TIME ZONE;
+
+-- Admin Audit Log Table
+CREATE TABLE admin_audit_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    admin_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    target_user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    action VARCHAR(50) NOT NULL, -- e.g. update_role
+    old_value TEXT,
+    new_value TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_admin_audit_log_target ON admin_audit_log(target_user_id, created_at DESC);
+CREATE INDEX idx_admin_audit_log_admin ON admin_audit_log(admin_id, created_at DESC);
+
+-- Comments for documentation
+COMMENT ON COLUMN password_reset_tokens.token_hash IS 'SHA-256 of the token sent in the reset email';
+COMMENT ON COLUMN password_reset_tokens.used_at IS 'When the token was redeemed or superseded';
+COMMENT ON TABLE admin_audit_log IS 'Every change an admin makes to another user''s account';
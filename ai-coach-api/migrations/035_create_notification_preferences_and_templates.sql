@@ -0,0 +1,100 @@
+-- Notification Preferences, Templates and Alert Windows
+-- Per-user notification settings, editable message templates, and the open condition windows
+-- that keep scheduler alerts from repeating
+
+-- Notification Preferences Table (defaults match NotificationPreferences::default)
+CREATE TABLE notification_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    workout_reminders BOOLEAN NOT NULL DEFAULT TRUE,
+    workout_reminder_advance_minutes INTEGER NOT NULL DEFAULT 60,
+    rest_day_reminders BOOLEAN NOT NULL DEFAULT TRUE,
+    ftp_test_reminders BOOLEAN NOT NULL DEFAULT TRUE,
+    fitness_improvement_alerts BOOLEAN NOT NULL DEFAULT TRUE,
+    goal_achievement_alerts BOOLEAN NOT NULL DEFAULT TRUE,
+    performance_decline_alerts BOOLEAN NOT NULL DEFAULT TRUE,
+    overtraining_risk_alerts BOOLEAN NOT NULL DEFAULT TRUE,
+    negative_tsb_alerts BOOLEAN NOT NULL DEFAULT TRUE,
+    injury_risk_alerts BOOLEAN NOT NULL DEFAULT TRUE,
+    weekly_progress_summaries BOOLEAN NOT NULL DEFAULT TRUE,
+    achievement_badge_alerts BOOLEAN NOT NULL DEFAULT TRUE,
+    training_streak_alerts BOOLEAN NOT NULL DEFAULT TRUE,
+    email_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    web_push_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    in_app_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    quiet_hours_start VARCHAR(5) NOT NULL DEFAULT '22:00',
+    quiet_hours_end VARCHAR(5) NOT NULL DEFAULT '07:00',
+    timezone VARCHAR(64) NOT NULL DEFAULT 'UTC',
+    batch_notifications BOOLEAN NOT NULL DEFAULT TRUE,
+    batch_interval_minutes INTEGER NOT NULL DEFAULT 60 CHECK (batch_interval_minutes >= 1),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_notification_preferences_updated_at
+    BEFORE UPDATE ON notification_preferences
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Notification Templates Table (one per type; {{placeholders}} are filled from the notification data)
+CREATE TABLE notification_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    notification_type notification_type NOT NULL UNIQUE,
+    title_template VARCHAR(255) NOT NULL,
+    message_template TEXT NOT NULL,
+    default_channels delivery_channel[] NOT NULL DEFAULT '{in_app,email}',
+    default_priority notification_priority NOT NULL DEFAULT 'medium',
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_notification_templates_updated_at
+    BEFORE UPDATE ON notification_templates
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+INSERT INTO notification_templates (notification_type, title_template, message_template, default_channels, default_priority) VALUES
+    ('fitness_improvement', 'Fitness Improvement Detected!',
+     'Your fitness (CTL) is up {{improvement_percentage}}% over the last {{period_days}} days. Keep up the great work!',
+     '{in_app,email}', 'medium'),
+    ('goal_achievement', 'Goal Achieved: {{goal_title}}',
+     'Congratulations! You''ve achieved your goal "{{goal_title}}".',
+     '{in_app,email}', 'medium'),
+    ('performance_decline', 'Performance Decline Notice',
+     'Your fitness (CTL) is down {{decline_percentage}}% over the last {{period_days}} days. {{recommendation}}',
+     '{in_app,email}', 'medium'),
+    ('overtraining_risk', 'Overtraining Risk Detected',
+     'Your training load over the last week is {{training_load_ratio}}x your usual load. {{recommendation}}',
+     '{in_app,email,web_push}', 'high'),
+    ('negative_tsb_alert', 'High Fatigue Alert',
+     'Your Training Stress Balance has been below {{tsb_threshold}} for {{consecutive_days}} days (now {{current_tsb}}). {{recommendation}}',
+     '{in_app,email,web_push}', 'high'),
+    ('injury_risk', 'Injury Risk Warning',
+     'Your training load has spiked to {{training_load_ratio}}x your usual load while your recovery is poor. {{recommendation}}',
+     '{in_app,email,web_push}', 'critical'),
+    ('weekly_progress_summary', 'Your Weekly Progress',
+     'Last week: {{total_sessions}} sessions, {{total_duration_hours}} hours and {{total_tss}} TSS.',
+     '{in_app,email}', 'low'),
+    ('achievement_badge', 'New Achievement Unlocked: {{achievement_name}}',
+     '{{description}}',
+     '{in_app,email}', 'low'),
+    ('training_streak', '{{streak_length}}-Day Training Streak!',
+     'You''ve trained {{streak_length}} days in a row. Keep it going!',
+     '{in_app,email}', 'low');
+
+-- Notification Alert Windows Table (an alert fires when its window opens, and again only after it closed)
+CREATE TABLE notification_alert_windows (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    alert_key VARCHAR(100) NOT NULL, -- e.g. overtraining_risk, goal_achievement:<goal id>
+    opened_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    closed_at TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (user_id, alert_key)
+);
+
+-- Read notifications for metrics
+CREATE INDEX idx_notifications_read ON notifications(user_id, read_at) WHERE read_at IS NOT NULL;
+
+-- Comments for documentation
+COMMENT ON TABLE notification_preferences IS 'Per-user notification settings; users without a row get the defaults';
+COMMENT ON TABLE notification_templates IS 'Title and message templates for scheduler alerts, by notification type';
+COMMENT ON TABLE notification_alert_windows IS 'Conditions an alert has already fired for; closed when the condition clears';
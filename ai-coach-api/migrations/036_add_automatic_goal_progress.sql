@@ -0,0 +1,14 @@
+-- Automatic Goal Progress
+-- Progress the goal evaluator measures from training sessions and recovery scores, kept
+-- apart from entries athletes log themselves
+
+ALTER TABLE goal_progress ADD COLUMN automatic BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- The evaluator keeps one measurement per goal and day, updated as new data arrives
+CREATE UNIQUE INDEX idx_goal_progress_automatic_daily ON goal_progress(goal_id, date) WHERE automatic;
+
+-- Goals the evaluator still tracks
+CREATE INDEX idx_goals_in_progress ON goals(user_id, target_date) WHERE status IN ('active', 'on_track', 'at_risk');
+
+-- Comments for documentation
+COMMENT ON COLUMN goal_progress.automatic IS 'Measured by the goal evaluator rather than logged by the athlete';
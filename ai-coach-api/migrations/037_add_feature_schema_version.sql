@@ -0,0 +1,11 @@
+-- Feature Schema Versioning
+-- Records which TrainingFeatures layout each stored model was fitted on, so models trained
+-- before the recovery features were added keep predicting until they are retrained
+
+ALTER TABLE ml_model_artifacts ADD COLUMN feature_schema_version INTEGER NOT NULL DEFAULT 1;
+
+-- Active models the retraining job still has to move to the current schema
+CREATE INDEX idx_ml_model_artifacts_active_schema ON ml_model_artifacts(feature_schema_version) WHERE is_active;
+
+-- Comments for documentation
+COMMENT ON COLUMN ml_model_artifacts.feature_schema_version IS 'TrainingFeatures schema version the model was fitted on; older versions are fed the matching prefix of the feature vector';
@@ -0,0 +1,20 @@
+-- Notification Delivery Claims
+-- Lets several API replicas run the notification scheduler: due notifications and digests are
+-- claimed before they are sent, and a digest only gets sent_at once it has been delivered
+
+ALTER TYPE delivery_status ADD VALUE IF NOT EXISTS 'sending' AFTER 'scheduled';
+
+ALTER TABLE notifications ADD COLUMN claimed_at TIMESTAMP WITH TIME ZONE;
+
+ALTER TABLE notification_batches ADD COLUMN claimed_at TIMESTAMP WITH TIME ZONE;
+UPDATE notification_batches SET claimed_at = sent_at WHERE sent_at IS NOT NULL;
+
+-- A digest stops collecting notifications once it is claimed, not once it is sent
+DROP INDEX idx_notification_batches_open;
+DROP INDEX idx_notification_batches_due;
+CREATE UNIQUE INDEX idx_notification_batches_open ON notification_batches(user_id) WHERE claimed_at IS NULL;
+CREATE INDEX idx_notification_batches_due ON notification_batches(scheduled_at) WHERE claimed_at IS NULL;
+
+-- Comments for documentation
+COMMENT ON COLUMN notifications.claimed_at IS 'When a scheduler claimed the notification for sending; stale claims are picked up again';
+COMMENT ON COLUMN notification_batches.claimed_at IS 'When a scheduler claimed the digest; its notifications are released if it is not sent';
@@ -0,0 +1,13 @@
+-- Background Job Deduplication
+-- Scheduled jobs are enqueued by the cron scheduler of every replica; a dedupe key per job and
+-- schedule slot makes sure each slot is queued once
+
+ALTER TABLE background_jobs ADD COLUMN dedupe_key VARCHAR(255);
+
+-- At most one waiting job per key; finished jobs keep their key so a slot isn't queued again
+CREATE UNIQUE INDEX idx_background_jobs_dedupe ON background_jobs(dedupe_key)
+    WHERE dedupe_key IS NOT NULL AND status IN ('pending', 'retrying');
+CREATE INDEX idx_background_jobs_dedupe_key ON background_jobs(dedupe_key) WHERE dedupe_key IS NOT NULL;
+
+-- Comments for documentation
+COMMENT ON COLUMN background_jobs.dedupe_key IS 'Job type and schedule slot, e.g. cleanup_old_files@2024-05-01; duplicates are not enqueued';
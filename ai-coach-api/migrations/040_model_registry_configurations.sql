@@ -0,0 +1,17 @@
+-- Model Registry Configurations
+-- Registry entries describe a model configuration (model type and feature schema) shared by
+-- every user's models of that kind, instead of one user's trained model. Predictions record
+-- the stored artifact that actually served them.
+
+ALTER TABLE ml_model_versions ADD COLUMN feature_schema_version INTEGER; -- NULL for entries registered per trained model
+
+ALTER TABLE ml_prediction_logs
+    ADD COLUMN served_artifact_id UUID REFERENCES ml_model_artifacts(id) ON DELETE SET NULL;
+
+-- Predictions served by a given artifact
+CREATE INDEX idx_ml_prediction_logs_artifact ON ml_prediction_logs(served_artifact_id) WHERE served_artifact_id IS NOT NULL;
+
+-- Comments for documentation
+COMMENT ON TABLE ml_model_versions IS 'Registry of TSS prediction model configurations and their lifecycle status; each user is served their own latest model of the configuration';
+COMMENT ON COLUMN ml_model_versions.feature_schema_version IS 'TrainingFeatures schema version of the configuration; experiment arms are served the user''s latest model of this type and schema';
+COMMENT ON COLUMN ml_prediction_logs.served_artifact_id IS 'ml_model_artifacts row that served the prediction';
@@ -0,0 +1,28 @@
+-- HRV Readings Without RMSSD
+-- Apple Health only records SDNN. Those readings are stored in `sdnn` alone instead of standing
+-- in for RMSSD, so they no longer enter RMSSD baselines and averages.
+
+ALTER TABLE hrv_readings ALTER COLUMN rmssd DROP NOT NULL;
+
+ALTER TABLE hrv_readings
+    ADD CONSTRAINT hrv_readings_metric_present CHECK (rmssd IS NOT NULL OR sdnn IS NOT NULL);
+
+-- Apple Health imports stored SDNN in both columns
+UPDATE hrv_readings SET rmssd = NULL WHERE metadata->>'hrv_metric' = 'sdnn';
+
+-- Recompute the RMSSD baselines those readings were averaged into, over the same 30-day window
+UPDATE recovery_baselines b
+SET hrv_baseline_rmssd = (
+        SELECT AVG(h.rmssd)
+        FROM hrv_readings h
+        WHERE h.user_id = b.user_id AND h.measurement_date >= (b.calculated_at - INTERVAL '30 days')::date
+    ),
+    updated_at = NOW()
+WHERE EXISTS (
+    SELECT 1 FROM hrv_readings h
+    WHERE h.user_id = b.user_id AND h.metadata->>'hrv_metric' = 'sdnn'
+);
+
+-- Comments for documentation
+COMMENT ON COLUMN hrv_readings.rmssd IS 'Root Mean Square of Successive Differences (primary HRV metric, ms); NULL for sources that only record SDNN';
+COMMENT ON COLUMN hrv_readings.sdnn IS 'Standard Deviation of NN intervals (ms); the only metric of Apple Health readings';
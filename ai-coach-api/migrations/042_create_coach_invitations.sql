@@ -0,0 +1,22 @@
+-- Coach Invitations by Email
+-- Invitations to an email without an account are kept here and become pending coach_athletes
+-- rows when someone signs up with that email, so inviting answers the same either way
+
+CREATE TABLE coach_invitations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    coach_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    can_read_training BOOLEAN NOT NULL DEFAULT TRUE,
+    can_read_recovery BOOLEAN NOT NULL DEFAULT FALSE,
+    can_edit_plans BOOLEAN NOT NULL DEFAULT FALSE,
+    invited_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One open invitation per coach and email; also used to resolve invitations at signup
+CREATE UNIQUE INDEX idx_coach_invitations_coach_email ON coach_invitations(coach_id, LOWER(email));
+CREATE INDEX idx_coach_invitations_email ON coach_invitations(LOWER(email));
+
+-- Comments for documentation
+COMMENT ON TABLE coach_invitations IS 'Coach invitations to emails without an account, turned into pending coach_athletes rows at signup';
+COMMENT ON COLUMN coach_invitations.email IS 'Invited email as entered by the coach; matched case-insensitively';
@@ -0,0 +1,20 @@
+-- Wearable OAuth States
+-- The OAuth `state` sent to a wearable provider is a random single-use nonce. Only its hash is
+-- stored, mapped to the user who started the authorization, so a callback can't be pointed at
+-- another user's account.
+
+CREATE TABLE wearable_oauth_states (
+    state_hash VARCHAR(64) PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider VARCHAR(50) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Cleanup of expired states
+CREATE INDEX idx_wearable_oauth_states_expires ON wearable_oauth_states(expires_at);
+
+-- Comments for documentation
+COMMENT ON TABLE wearable_oauth_states IS 'Pending wearable OAuth authorizations; each state is accepted by one callback before it expires';
+COMMENT ON COLUMN wearable_oauth_states.state_hash IS 'SHA-256 hex digest of the state sent to the provider';
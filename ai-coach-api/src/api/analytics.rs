@@ -1,17 +1,23 @@
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::Json,
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::get,
     Router,
 };
 use axum_extra::extract::WithRejection;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::sync::Arc;
+use tokio_util::io::ReaderStream;
 use uuid::Uuid;
 use chrono::{NaiveDate, DateTime, Utc};
 
 use crate::auth::{AuthService, Claims};
+use crate::models::{CreateExportRequest, DataExport, DataExportResponse, ExportStatus};
+use crate::services::{BackgroundJobService, DataExportService};
+use crate::services::data_export_service::resolve_datasets;
 
 #[derive(Debug, Deserialize)]
 pub struct AnalyticsQuery {
@@ -251,12 +257,23 @@ impl ApiError {
 pub struct AnalyticsAppState {
     pub db: PgPool,
     pub auth_service: AuthService,
+    pub data_export_service: DataExportService,
+    pub background_job_service: Arc<BackgroundJobService>,
 }
 
-pub fn analytics_routes(db: PgPool, auth_service: AuthService) -> Router {
+pub fn analytics_routes(
+    db: PgPool,
+    auth_service: AuthService,
+    background_job_service: Arc<BackgroundJobService>,
+) -> Router {
+    let data_export_service = DataExportService::new(db.clone())
+        .expect("Failed to create DataExportService");
+
     let shared_state = AnalyticsAppState {
         db,
         auth_service,
+        data_export_service,
+        background_job_service,
     };
 
     Router::new()
@@ -268,7 +285,9 @@ pub fn analytics_routes(db: PgPool, auth_service: AuthService) -> Router {
         .route("/statistics/summary", get(get_summary_statistics))
         .route("/statistics/personal-records", get(get_personal_records))
         .route("/statistics/monthly", get(get_monthly_statistics))
-        .route("/export", get(export_analytics_data))
+        .route("/export", get(list_exports).post(export_analytics_data))
+        .route("/export/:export_id", get(get_export_status))
+        .route("/export/download/:export_id", get(download_export))
         .with_state(shared_state)
 }
 
@@ -688,20 +707,178 @@ pub async fn get_monthly_statistics(
     })))
 }
 
-/// Export analytics data
+/// Queue an export of sessions, PMC, goals, events, recovery data and predictions
 pub async fn export_analytics_data(
     State(state): State<AnalyticsAppState>,
     WithRejection(claims, _): WithRejection<Claims, StatusCode>,
-    Query(query): Query<AnalyticsQuery>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
-    let _user_id = claims.sub;
+    Json(request): Json<CreateExportRequest>,
+) -> Result<(StatusCode, Json<DataExportResponse>), (StatusCode, Json<ApiError>)> {
+    let user_id = claims.sub.parse::<Uuid>().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID format")),
+        )
+    })?;
 
-    Ok(Json(serde_json::json!({
-        "export_id": Uuid::new_v4(),
-        "export_format": "json",
-        "created_at": Utc::now(),
-        "download_url": "/api/v1/analytics/export/download/{export_id}",
-        "expires_at": Utc::now() + chrono::Duration::hours(24),
-        "success": true
-    })))
-}
\ No newline at end of file
+    resolve_datasets(&request).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_EXPORT_REQUEST", &e.to_string())),
+        )
+    })?;
+
+    let export = queue_export(&state.data_export_service, &state.background_job_service, user_id, &request)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to queue data export: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("EXPORT_FAILED", "Failed to queue data export")),
+            )
+        })?;
+
+    Ok((StatusCode::ACCEPTED, Json(export)))
+}
+
+/// Record an export and queue the job that renders it
+pub async fn queue_export(
+    data_export_service: &DataExportService,
+    background_job_service: &BackgroundJobService,
+    user_id: Uuid,
+    request: &CreateExportRequest,
+) -> anyhow::Result<DataExportResponse> {
+    let export = data_export_service.create_export(user_id, request).await?;
+    let job_id = background_job_service.queue_data_export(export.id, user_id).await?;
+    let export = data_export_service.set_job_id(export.id, job_id).await?;
+
+    Ok(export.into())
+}
+
+/// List the user's exports, newest first
+pub async fn list_exports(
+    State(state): State<AnalyticsAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+) -> Result<Json<Vec<DataExportResponse>>, (StatusCode, Json<ApiError>)> {
+    let user_id = claims.sub.parse::<Uuid>().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID format")),
+        )
+    })?;
+
+    let exports = state.data_export_service.list_user_exports(user_id).await.map_err(|e| {
+        tracing::error!("Failed to list data exports: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("DATABASE_ERROR", "Failed to list exports")),
+        )
+    })?;
+
+    Ok(Json(exports.into_iter().map(DataExportResponse::from).collect()))
+}
+
+/// Get the status of one of the user's exports
+pub async fn get_export_status(
+    State(state): State<AnalyticsAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+    Path(export_id): Path<Uuid>,
+) -> Result<Json<DataExportResponse>, (StatusCode, Json<ApiError>)> {
+    let user_id = claims.sub.parse::<Uuid>().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID format")),
+        )
+    })?;
+
+    let export = find_user_export(&state, user_id, export_id).await?;
+    Ok(Json(export.into()))
+}
+
+/// Stream a completed export; other users' exports are reported as missing
+pub async fn download_export(
+    State(state): State<AnalyticsAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+    Path(export_id): Path<Uuid>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    let user_id = claims.sub.parse::<Uuid>().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID format")),
+        )
+    })?;
+
+    let export = find_user_export(&state, user_id, export_id).await?;
+
+    if export.status == ExportStatus::Expired.as_str()
+        || (export.status == ExportStatus::Completed.as_str() && !export.is_downloadable(Utc::now()))
+    {
+        return Err((
+            StatusCode::GONE,
+            Json(ApiError::new("EXPORT_EXPIRED", "Export has expired, please request a new one")),
+        ));
+    }
+
+    if export.status != ExportStatus::Completed.as_str() {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiError::new("EXPORT_NOT_READY", &format!("Export is {}", export.status))),
+        ));
+    }
+
+    let file = match state.data_export_service.file_path(&export) {
+        Ok(path) => tokio::fs::File::open(path).await.map_err(anyhow::Error::from),
+        Err(e) => Err(e),
+    }
+    .map_err(|e| {
+        tracing::error!("Failed to open export {}: {}", export_id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("EXPORT_UNAVAILABLE", "Export file could not be read")),
+        )
+    })?;
+
+    let content_type = export
+        .export_format()
+        .map(|format| format.content_type())
+        .unwrap_or("application/octet-stream");
+    let file_name = export
+        .file_name
+        .clone()
+        .unwrap_or_else(|| format!("{}.{}", export.id, export.format));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        Body::from_stream(ReaderStream::new(file)),
+    )
+        .into_response())
+}
+
+async fn find_user_export(
+    state: &AnalyticsAppState,
+    user_id: Uuid,
+    export_id: Uuid,
+) -> Result<DataExport, (StatusCode, Json<ApiError>)> {
+    state
+        .data_export_service
+        .get_user_export(user_id, export_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load data export {}: {}", export_id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("DATABASE_ERROR", "Failed to load export")),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::new("EXPORT_NOT_FOUND", "Export not found")),
+            )
+        })
+}
@@ -1,17 +1,18 @@
 use axum::{
-    extract::{Query, Request, State},
+    extract::{Path, Query, Request, State},
     middleware,
     response::Json,
     routing::{get, post, put},
-    Router,
+    Extension, Router,
 };
 use serde::Deserialize;
+use uuid::Uuid;
 
 use crate::auth::{
-    extract_user_session, jwt_auth_middleware, AuthError, AuthResponse, AuthService,
+    admin_only_middleware, jwt_auth_middleware, AuthError, AuthResponse, AuthService,
     ChangePasswordRequest, ForgotPasswordRequest, LoginRequest, MessageResponse,
     RefreshTokenRequest, RegisterRequest, ResetPasswordRequest, TokenResponse,
-    UpdateProfileRequest, UserInfo, UserRole,
+    UpdateProfileRequest, UserInfo, UserListParams, UserListResponse, UserRole, UserSession,
 };
 
 /// Authentication routes
@@ -91,47 +92,35 @@ async fn logout(
 }
 
 /// Get user profile
-#[tracing::instrument(skip(request))]
-async fn get_profile(request: Request) -> Result<Json<UserInfo>, AuthError> {
-    let session = extract_user_session(&request)?;
-
-    let user_info = UserInfo {
-        id: session.user_id,
-        email: session.email.clone(),
-        role: session.role.clone(),
-        created_at: chrono::Utc::now(), // TODO: Get from database
-        updated_at: chrono::Utc::now(), // TODO: Get from database
-    };
-
+#[tracing::instrument(skip(auth_service, session))]
+async fn get_profile(
+    State(auth_service): State<AuthService>,
+    Extension(session): Extension<UserSession>,
+) -> Result<Json<UserInfo>, AuthError> {
+    let user_info = auth_service.get_user_info(session.user_id).await?;
     Ok(Json(user_info))
 }
 
 /// Update user profile
-#[tracing::instrument(skip(auth_service, update_request))]
+#[tracing::instrument(skip(auth_service, session, update_request))]
 async fn update_profile(
     State(auth_service): State<AuthService>,
+    Extension(session): Extension<UserSession>,
     Json(update_request): Json<UpdateProfileRequest>,
-) -> Result<Json<MessageResponse>, AuthError> {
-    // Placeholder implementation - profile update logic not yet implemented
-    // Future: Connect to user service and update profile data
-
-    Ok(Json(MessageResponse {
-        message: "Profile updated successfully".to_string(),
-    }))
+) -> Result<Json<UserInfo>, AuthError> {
+    let user_info = auth_service.update_profile(session.user_id, update_request).await?;
+    Ok(Json(user_info))
 }
 
 /// Change user password
-#[tracing::instrument(skip(auth_service, change_request))]
+#[tracing::instrument(skip(auth_service, session, change_request))]
 async fn change_password(
     State(auth_service): State<AuthService>,
+    Extension(session): Extension<UserSession>,
     Json(change_request): Json<ChangePasswordRequest>,
 ) -> Result<Json<MessageResponse>, AuthError> {
-    // Placeholder implementation - password change logic not yet implemented
-    // Future: Validate current password and update with new hash
-
-    Ok(Json(MessageResponse {
-        message: "Password changed successfully".to_string(),
-    }))
+    let response = auth_service.change_password(session.user_id, change_request).await?;
+    Ok(Json(response))
 }
 
 /// Forgot password
@@ -140,17 +129,8 @@ async fn forgot_password(
     State(auth_service): State<AuthService>,
     Json(request): Json<ForgotPasswordRequest>,
 ) -> Result<Json<MessageResponse>, AuthError> {
-    // Placeholder implementation - forgot password flow not yet implemented
-    // Future: Generate reset token and send email
-    // This should:
-    // 1. Check if user exists
-    // 2. Generate reset token
-    // 3. Send email with reset link
-    // 4. Store reset token in database
-
-    Ok(Json(MessageResponse {
-        message: "If an account with that email exists, a password reset link has been sent.".to_string(),
-    }))
+    let response = auth_service.forgot_password(request).await?;
+    Ok(Json(response))
 }
 
 /// Reset password
@@ -159,29 +139,21 @@ async fn reset_password(
     State(auth_service): State<AuthService>,
     Json(request): Json<ResetPasswordRequest>,
 ) -> Result<Json<MessageResponse>, AuthError> {
-    // Placeholder implementation - password reset flow not yet implemented
-    // Future: Validate reset token and update password
-    // This should:
-    // 1. Validate reset token
-    // 2. Check if token is not expired
-    // 3. Update user password
-    // 4. Mark token as used
-
-    Ok(Json(MessageResponse {
-        message: "Password reset successfully".to_string(),
-    }))
+    let response = auth_service.reset_password(request).await?;
+    Ok(Json(response))
 }
 
 /// Admin endpoints
 pub fn admin_routes(auth_service: AuthService) -> Router {
+    // The last layer runs first: authenticate, then check the role
     Router::new()
         .route("/users", get(list_users))
         .route("/users/:id/role", put(update_user_role))
+        .route_layer(middleware::from_fn(admin_only_middleware))
         .route_layer(middleware::from_fn_with_state(
             auth_service.clone(),
             jwt_auth_middleware,
         ))
-        .route_layer(middleware::from_fn(crate::auth::admin_only_middleware))
         .with_state(auth_service)
 }
 
@@ -189,15 +161,25 @@ pub fn admin_routes(auth_service: AuthService) -> Router {
 struct ListUsersQuery {
     page: Option<u32>,
     limit: Option<u32>,
+    search: Option<String>,
+    role: Option<UserRole>,
 }
 
-/// List all users (admin only)
+/// List users, paginated and optionally filtered by email or role (admin only)
+#[tracing::instrument(skip(auth_service, params))]
 async fn list_users(
+    State(auth_service): State<AuthService>,
     Query(params): Query<ListUsersQuery>,
-) -> Result<Json<Vec<UserInfo>>, AuthError> {
-    // Placeholder implementation - user listing not yet implemented
-    // Future: Return paginated list of users with proper admin authorization
-    Ok(Json(vec![]))
+) -> Result<Json<UserListResponse>, AuthError> {
+    let response = auth_service
+        .list_users(UserListParams {
+            page: params.page,
+            limit: params.limit,
+            search: params.search,
+            role: params.role,
+        })
+        .await?;
+    Ok(Json(response))
 }
 
 #[derive(Deserialize)]
@@ -206,13 +188,15 @@ struct UpdateRoleRequest {
 }
 
 /// Update user role (admin only)
+#[tracing::instrument(skip(auth_service, session, request))]
 async fn update_user_role(
-    axum::extract::Path(user_id): axum::extract::Path<uuid::Uuid>,
+    State(auth_service): State<AuthService>,
+    Extension(session): Extension<UserSession>,
+    Path(user_id): Path<Uuid>,
     Json(request): Json<UpdateRoleRequest>,
-) -> Result<Json<MessageResponse>, AuthError> {
-    // Placeholder implementation - role update not yet implemented
-    // Future: Update user role with proper admin authorization
-    Ok(Json(MessageResponse {
-        message: "User role updated successfully".to_string(),
-    }))
-}
\ No newline at end of file
+) -> Result<Json<UserInfo>, AuthError> {
+    let user_info = auth_service
+        .change_user_role(session.user_id, user_id, request.role)
+        .await?;
+    Ok(Json(user_info))
+}
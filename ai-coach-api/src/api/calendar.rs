@@ -0,0 +1,202 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    middleware,
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Extension,
+    Router,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, warn};
+
+use crate::{
+    auth::{jwt_auth_middleware, AuthService, UserSession},
+    models::{CalendarFeed, CalendarFeedCreatedResponse, CalendarImportSummary, CreateCalendarFeedRequest},
+    services::CalendarFeedService,
+};
+
+/// Shared state for calendar API handlers
+pub struct CalendarState {
+    pub calendar_feed_service: CalendarFeedService,
+    /// Prefix for feed URLs handed to calendar apps, from `PUBLIC_BASE_URL`
+    pub public_base_url: String,
+}
+
+impl CalendarState {
+    fn feed_url(&self, token: &str) -> String {
+        format!("{}/api/v1/calendar/feed/{}.ics", self.public_base_url, token)
+    }
+}
+
+/// Serve the `.ics` feed for a subscription token. Unauthenticated; the token is the credential.
+pub async fn get_calendar_feed(
+    State(state): State<Arc<CalendarState>>,
+    Path(token): Path<String>,
+) -> Result<Response, CalendarError> {
+    let token = token.strip_suffix(".ics").unwrap_or(&token);
+
+    let feed = state
+        .calendar_feed_service
+        .feed_for_token(token)
+        .await
+        .map_err(|e| {
+            error!("Failed to resolve calendar feed: {}", e);
+            CalendarError::DatabaseError
+        })?
+        .ok_or(CalendarError::NotFound("Calendar feed not found"))?;
+
+    let ics = state
+        .calendar_feed_service
+        .render_feed(&feed)
+        .await
+        .map_err(|e| {
+            error!("Failed to render calendar feed {}: {}", feed.id, e);
+            CalendarError::DatabaseError
+        })?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "inline; filename=\"ai-coach.ics\""),
+            (header::CACHE_CONTROL, "private, max-age=900"),
+        ],
+        ics,
+    )
+        .into_response())
+}
+
+/// The user's current calendar feed
+pub async fn get_feed_settings(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CalendarState>>,
+) -> Result<Json<CalendarFeed>, CalendarError> {
+    let feed = state
+        .calendar_feed_service
+        .get_feed(session.user_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to load calendar feed: {}", e);
+            CalendarError::DatabaseError
+        })?
+        .ok_or(CalendarError::NotFound("No calendar feed"))?;
+
+    Ok(Json(feed))
+}
+
+/// Create a calendar feed, or rotate its URL if one exists
+pub async fn create_feed(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CalendarState>>,
+    request: Option<Json<CreateCalendarFeedRequest>>,
+) -> Result<(StatusCode, Json<CalendarFeedCreatedResponse>), CalendarError> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+
+    let (feed, token) = state
+        .calendar_feed_service
+        .create_feed(session.user_id, request)
+        .await
+        .map_err(|e| {
+            error!("Failed to create calendar feed: {}", e);
+            CalendarError::DatabaseError
+        })?;
+
+    let feed_url = state.feed_url(&token);
+    Ok((StatusCode::CREATED, Json(CalendarFeedCreatedResponse { feed, feed_url })))
+}
+
+/// Revoke the user's calendar feed
+pub async fn revoke_feed(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CalendarState>>,
+) -> Result<StatusCode, CalendarError> {
+    let revoked = state
+        .calendar_feed_service
+        .revoke_feed(session.user_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to revoke calendar feed: {}", e);
+            CalendarError::DatabaseError
+        })?;
+
+    if !revoked {
+        return Err(CalendarError::NotFound("No calendar feed"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Import races from an uploaded `.ics` file
+pub async fn import_calendar(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CalendarState>>,
+    body: String,
+) -> Result<Json<CalendarImportSummary>, CalendarError> {
+    if body.trim().is_empty() {
+        return Err(CalendarError::InvalidRequest("Calendar file is empty".to_string()));
+    }
+
+    // Parse up front so malformed uploads are a client error rather than a server one
+    crate::services::calendar_feed_service::parse_ics(&body).map_err(|e| {
+        warn!("Rejected calendar import: {}", e);
+        CalendarError::InvalidRequest(e.to_string())
+    })?;
+
+    let summary = state
+        .calendar_feed_service
+        .import_calendar(session.user_id, &body)
+        .await
+        .map_err(|e| {
+            error!("Failed to import calendar: {}", e);
+            CalendarError::DatabaseError
+        })?;
+
+    Ok(Json(summary))
+}
+
+/// Error type for calendar API
+#[derive(Debug)]
+pub enum CalendarError {
+    InvalidRequest(String),
+    NotFound(&'static str),
+    DatabaseError,
+}
+
+impl IntoResponse for CalendarError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            CalendarError::InvalidRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            CalendarError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.to_string()),
+            CalendarError::DatabaseError => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database error".to_string(),
+            ),
+        };
+
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+/// Create calendar routes: the token-authenticated `.ics` feed, feed management and race imports
+pub fn calendar_routes(db: PgPool, auth_service: AuthService) -> Router {
+    let state = Arc::new(CalendarState {
+        calendar_feed_service: CalendarFeedService::new(db),
+        public_base_url: std::env::var("PUBLIC_BASE_URL")
+            .map(|url| url.trim_end_matches('/').to_string())
+            .unwrap_or_default(),
+    });
+
+    let authenticated = Router::new()
+        .route("/feed", get(get_feed_settings).post(create_feed).delete(revoke_feed))
+        .route("/import", post(import_calendar))
+        .layer(middleware::from_fn_with_state(
+            auth_service,
+            jwt_auth_middleware,
+        ));
+
+    Router::new()
+        .route("/feed/:token", get(get_calendar_feed))
+        .merge(authenticated)
+        .with_state(state)
+}
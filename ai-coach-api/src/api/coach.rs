@@ -0,0 +1,603 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Json, Response},
+    routing::{get, post, put},
+    Extension,
+    Router,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    auth::{coach_or_admin_middleware, jwt_auth_middleware, AuthService, UserSession},
+    models::{
+        CoachAccessAudit, CoachAthlete, CoachAthleteSummary, CoachInvitationResponse, CoachPermissions, CoachScope,
+        CreateTrainingPlan, Goal, HrvReadingsListResponse, InviteAthleteRequest,
+        RecoveryDataQuery, RestingHrListResponse, SleepDataListResponse, TrainingPlan,
+        TrainingSession, UpdateCoachScopesRequest, UpdateTrainingPlan,
+    },
+    services::{
+        training_analysis_service::PerformanceManagementChart, CoachAthleteService, GoalService,
+        RecoveryDataService, TrainingAnalysisService, TrainingPlanService, TrainingSessionService,
+    },
+};
+
+/// Shared state for coach API handlers
+pub struct CoachState {
+    pub coach_athlete_service: CoachAthleteService,
+    pub training_session_service: TrainingSessionService,
+    pub training_analysis_service: TrainingAnalysisService,
+    pub goal_service: GoalService,
+    pub recovery_data_service: RecoveryDataService,
+    pub training_plan_service: TrainingPlanService,
+}
+
+impl CoachState {
+    /// Check that the signed-in coach may act on behalf of `athlete_id` within `scope`,
+    /// record the access and return the athlete's user ID for use with the regular services
+    async fn act_for(
+        &self,
+        session: &UserSession,
+        athlete_id: Uuid,
+        scope: CoachScope,
+        action: &str,
+        resource_id: Option<Uuid>,
+    ) -> Result<Uuid, CoachError> {
+        let relationship = self
+            .coach_athlete_service
+            .get_relationship(session.user_id, athlete_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to load coach relationship: {}", e);
+                CoachError::DatabaseError
+            })?
+            .filter(CoachAthlete::is_active)
+            .ok_or(CoachError::NotFound("Athlete not found on your roster"))?;
+
+        if !relationship.has_scope(scope) {
+            return Err(CoachError::Forbidden(format!(
+                "Athlete has not granted the {} scope",
+                scope.as_str()
+            )));
+        }
+
+        self.coach_athlete_service
+            .record_access(session.user_id, athlete_id, scope, action, resource_id)
+            .await
+            .map_err(|e| {
+                // No audit record, no access
+                error!("Failed to record coach access: {}", e);
+                CoachError::DatabaseError
+            })?;
+
+        Ok(athlete_id)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl PageQuery {
+    fn limit(&self) -> i64 {
+        self.limit.unwrap_or(50).clamp(1, 100)
+    }
+
+    fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PmcQuery {
+    pub days: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecoveryQuery {
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub limit: Option<i64>,
+}
+
+impl RecoveryQuery {
+    fn data_query(&self) -> RecoveryDataQuery {
+        RecoveryDataQuery {
+            from_date: self.from_date,
+            to_date: self.to_date,
+            limit: Some(self.limit.unwrap_or(100).clamp(1, 1000)),
+            page: Some(1),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AthleteRecoveryResponse {
+    pub hrv: HrvReadingsListResponse,
+    pub sleep: SleepDataListResponse,
+    pub resting_hr: RestingHrListResponse,
+}
+
+/// Plan written by a coach for one of their athletes
+#[derive(Debug, Deserialize)]
+pub struct CoachPlanRequest {
+    pub goal: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub plan_data: serde_json::Value,
+}
+
+// Coach-facing handlers
+
+/// Invite an athlete to the coach's roster. The reply doesn't say whether the email has an
+/// account; invitations to new emails wait for someone to sign up with them.
+pub async fn invite_athlete(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Json(request): Json<InviteAthleteRequest>,
+) -> Result<(StatusCode, Json<CoachInvitationResponse>), CoachError> {
+    let permissions = request
+        .scopes
+        .as_deref()
+        .map(CoachPermissions::from_scopes)
+        .unwrap_or_default();
+
+    if session.email.eq_ignore_ascii_case(request.athlete_email.trim()) {
+        return Err(CoachError::InvalidRequest("You cannot coach yourself".to_string()));
+    }
+
+    state
+        .coach_athlete_service
+        .invite_athlete(session.user_id, &request.athlete_email, permissions)
+        .await
+        .map_err(|e| {
+            error!("Failed to invite athlete: {}", e);
+            CoachError::DatabaseError
+        })?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(CoachInvitationResponse {
+            athlete_email: request.athlete_email.trim().to_string(),
+            message: "Invitation sent. The athlete will see it once they sign in.".to_string(),
+        }),
+    ))
+}
+
+/// Withdraw an invitation the athlete hasn't answered, by its roster entry ID
+pub async fn cancel_invitation(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path(invitation_id): Path<Uuid>,
+) -> Result<StatusCode, CoachError> {
+    let cancelled = state
+        .coach_athlete_service
+        .cancel_invitation(session.user_id, invitation_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to cancel invitation: {}", e);
+            CoachError::DatabaseError
+        })?;
+
+    if !cancelled {
+        return Err(CoachError::NotFound("Invitation not found"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the coach's pending and active athletes
+pub async fn list_athletes(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+) -> Result<Json<Vec<CoachAthleteSummary>>, CoachError> {
+    let athletes = state
+        .coach_athlete_service
+        .list_athletes(session.user_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to list athletes: {}", e);
+            CoachError::DatabaseError
+        })?;
+
+    Ok(Json(athletes))
+}
+
+/// Remove an athlete from the coach's roster
+pub async fn remove_athlete(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path(athlete_id): Path<Uuid>,
+) -> Result<StatusCode, CoachError> {
+    state
+        .coach_athlete_service
+        .revoke_athlete(session.user_id, athlete_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to remove athlete: {}", e);
+            CoachError::DatabaseError
+        })?
+        .ok_or(CoachError::NotFound("Athlete not found on your roster"))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// An athlete's training sessions
+pub async fn get_athlete_sessions(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path(athlete_id): Path<Uuid>,
+    Query(page): Query<PageQuery>,
+) -> Result<Json<Vec<TrainingSession>>, CoachError> {
+    let athlete_id = state
+        .act_for(&session, athlete_id, CoachScope::ReadTraining, "list_sessions", None)
+        .await?;
+
+    let sessions = state
+        .training_session_service
+        .get_sessions_by_user_id(athlete_id, Some(page.limit()), Some(page.offset()))
+        .await
+        .map_err(|e| {
+            error!("Failed to load athlete sessions: {}", e);
+            CoachError::DatabaseError
+        })?;
+
+    Ok(Json(sessions))
+}
+
+/// An athlete's Performance Management Chart
+pub async fn get_athlete_pmc(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path(athlete_id): Path<Uuid>,
+    Query(query): Query<PmcQuery>,
+) -> Result<Json<Vec<PerformanceManagementChart>>, CoachError> {
+    let athlete_id = state
+        .act_for(&session, athlete_id, CoachScope::ReadTraining, "get_pmc", None)
+        .await?;
+
+    let pmc = state
+        .training_analysis_service
+        .calculate_pmc(athlete_id, query.days.unwrap_or(90).clamp(1, 730))
+        .await
+        .map_err(|e| {
+            error!("Failed to calculate athlete PMC: {}", e);
+            CoachError::DatabaseError
+        })?;
+
+    Ok(Json(pmc))
+}
+
+/// An athlete's goals
+pub async fn get_athlete_goals(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path(athlete_id): Path<Uuid>,
+    Query(page): Query<PageQuery>,
+) -> Result<Json<Vec<Goal>>, CoachError> {
+    let athlete_id = state
+        .act_for(&session, athlete_id, CoachScope::ReadTraining, "list_goals", None)
+        .await?;
+
+    let goals = state
+        .goal_service
+        .get_goals_by_user(athlete_id, None, None, None, Some(page.limit()), Some(page.offset()))
+        .await
+        .map_err(|e| {
+            error!("Failed to load athlete goals: {}", e);
+            CoachError::DatabaseError
+        })?;
+
+    Ok(Json(goals))
+}
+
+/// An athlete's HRV, sleep and resting heart rate data
+pub async fn get_athlete_recovery(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path(athlete_id): Path<Uuid>,
+    Query(query): Query<RecoveryQuery>,
+) -> Result<Json<AthleteRecoveryResponse>, CoachError> {
+    let athlete_id = state
+        .act_for(&session, athlete_id, CoachScope::ReadRecovery, "get_recovery", None)
+        .await?;
+
+    let service = &state.recovery_data_service;
+    let (hrv, sleep, resting_hr) = tokio::try_join!(
+        service.get_hrv_readings(athlete_id, query.data_query()),
+        service.get_sleep_data(athlete_id, query.data_query()),
+        service.get_resting_hr_data(athlete_id, query.data_query()),
+    )
+    .map_err(|e| {
+        error!("Failed to load athlete recovery data: {}", e);
+        CoachError::DatabaseError
+    })?;
+
+    Ok(Json(AthleteRecoveryResponse { hrv, sleep, resting_hr }))
+}
+
+/// An athlete's training plans
+pub async fn get_athlete_plans(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path(athlete_id): Path<Uuid>,
+) -> Result<Json<Vec<TrainingPlan>>, CoachError> {
+    let athlete_id = state
+        .act_for(&session, athlete_id, CoachScope::ReadTraining, "list_plans", None)
+        .await?;
+
+    let plans = state
+        .training_plan_service
+        .get_plans_by_user_id(athlete_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to load athlete plans: {}", e);
+            CoachError::DatabaseError
+        })?;
+
+    Ok(Json(plans))
+}
+
+/// Create a training plan for an athlete
+pub async fn create_athlete_plan(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path(athlete_id): Path<Uuid>,
+    Json(request): Json<CoachPlanRequest>,
+) -> Result<(StatusCode, Json<TrainingPlan>), CoachError> {
+    if request.start_date > request.end_date {
+        return Err(CoachError::InvalidRequest("start_date must not be after end_date".to_string()));
+    }
+
+    let athlete_id = state
+        .act_for(&session, athlete_id, CoachScope::EditPlans, "create_plan", None)
+        .await?;
+
+    let plan = state
+        .training_plan_service
+        .create_plan(CreateTrainingPlan {
+            user_id: athlete_id,
+            goal: request.goal,
+            start_date: request.start_date,
+            end_date: request.end_date,
+            plan_data: request.plan_data,
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to create athlete plan: {}", e);
+            CoachError::DatabaseError
+        })?;
+
+    info!("Coach {} created plan {} for athlete {}", session.user_id, plan.id, athlete_id);
+    Ok((StatusCode::CREATED, Json(plan)))
+}
+
+/// Update one of an athlete's training plans
+pub async fn update_athlete_plan(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path((athlete_id, plan_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<UpdateTrainingPlan>,
+) -> Result<Json<TrainingPlan>, CoachError> {
+    let athlete_id = state
+        .act_for(&session, athlete_id, CoachScope::EditPlans, "update_plan", Some(plan_id))
+        .await?;
+
+    let plan = state
+        .training_plan_service
+        .get_plan_by_id(plan_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to load plan: {}", e);
+            CoachError::DatabaseError
+        })?
+        .filter(|plan| plan.user_id == athlete_id)
+        .ok_or(CoachError::NotFound("Plan not found"))?;
+
+    let plan = state
+        .training_plan_service
+        .update_plan(plan.id, request)
+        .await
+        .map_err(|e| {
+            error!("Failed to update athlete plan: {}", e);
+            CoachError::DatabaseError
+        })?
+        .ok_or(CoachError::NotFound("Plan not found"))?;
+
+    Ok(Json(plan))
+}
+
+// Athlete-facing handlers
+
+/// List the signed-in athlete's coaches and pending invitations
+pub async fn list_coaches(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+) -> Result<Json<Vec<CoachAthleteSummary>>, CoachError> {
+    let coaches = state
+        .coach_athlete_service
+        .list_coaches(session.user_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to list coaches: {}", e);
+            CoachError::DatabaseError
+        })?;
+
+    Ok(Json(coaches))
+}
+
+/// Accept a coach's invitation
+pub async fn accept_invitation(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path(relationship_id): Path<Uuid>,
+) -> Result<Json<CoachAthlete>, CoachError> {
+    respond_to_invitation(&state, &session, relationship_id, true).await
+}
+
+/// Decline a coach's invitation
+pub async fn decline_invitation(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path(relationship_id): Path<Uuid>,
+) -> Result<Json<CoachAthlete>, CoachError> {
+    respond_to_invitation(&state, &session, relationship_id, false).await
+}
+
+async fn respond_to_invitation(
+    state: &CoachState,
+    session: &UserSession,
+    relationship_id: Uuid,
+    accept: bool,
+) -> Result<Json<CoachAthlete>, CoachError> {
+    let relationship = state
+        .coach_athlete_service
+        .respond_to_invitation(session.user_id, relationship_id, accept)
+        .await
+        .map_err(|e| {
+            error!("Failed to respond to invitation: {}", e);
+            CoachError::DatabaseError
+        })?
+        .ok_or(CoachError::NotFound("Invitation not found"))?;
+
+    Ok(Json(relationship))
+}
+
+/// Change which scopes a coach has
+pub async fn update_coach_scopes(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path(relationship_id): Path<Uuid>,
+    Json(request): Json<UpdateCoachScopesRequest>,
+) -> Result<Json<CoachAthlete>, CoachError> {
+    let relationship = state
+        .coach_athlete_service
+        .update_permissions(
+            session.user_id,
+            relationship_id,
+            CoachPermissions::from_scopes(&request.scopes),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to update coach scopes: {}", e);
+            CoachError::DatabaseError
+        })?
+        .ok_or(CoachError::NotFound("Coach not found"))?;
+
+    Ok(Json(relationship))
+}
+
+/// Remove a coach's access
+pub async fn remove_coach(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Path(relationship_id): Path<Uuid>,
+) -> Result<StatusCode, CoachError> {
+    state
+        .coach_athlete_service
+        .revoke(session.user_id, relationship_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to remove coach: {}", e);
+            CoachError::DatabaseError
+        })?
+        .ok_or(CoachError::NotFound("Coach not found"))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Every access coaches made to the signed-in athlete's data
+pub async fn get_access_log(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<CoachState>>,
+    Query(page): Query<PageQuery>,
+) -> Result<Json<Vec<CoachAccessAudit>>, CoachError> {
+    let entries = state
+        .coach_athlete_service
+        .get_access_log(session.user_id, page.limit(), page.offset())
+        .await
+        .map_err(|e| {
+            error!("Failed to load access log: {}", e);
+            CoachError::DatabaseError
+        })?;
+
+    Ok(Json(entries))
+}
+
+/// Coach API errors
+#[derive(Debug)]
+pub enum CoachError {
+    InvalidRequest(String),
+    NotFound(&'static str),
+    Forbidden(String),
+    DatabaseError,
+}
+
+impl IntoResponse for CoachError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            CoachError::InvalidRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            CoachError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.to_string()),
+            CoachError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            CoachError::DatabaseError => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database error".to_string(),
+            ),
+        };
+
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+/// Create coach routes: roster management and delegated athlete data for coaches,
+/// invitations, scopes and the access log for athletes
+pub fn coach_routes(db: PgPool, auth_service: AuthService) -> Router {
+    let training_analysis_service = TrainingAnalysisService::new(
+        db.clone(),
+        std::env::var("REDIS_URL").ok(),
+    ).expect("Failed to create TrainingAnalysisService");
+
+    let state = Arc::new(CoachState {
+        coach_athlete_service: CoachAthleteService::new(db.clone()),
+        training_session_service: TrainingSessionService::new(db.clone()),
+        training_analysis_service,
+        goal_service: GoalService::new(db.clone()),
+        recovery_data_service: RecoveryDataService::new(db.clone()),
+        training_plan_service: TrainingPlanService::new(db),
+    });
+
+    let coach_only = Router::new()
+        .route("/athletes", get(list_athletes))
+        .route("/athletes/invite", post(invite_athlete))
+        .route("/athletes/invitations/:invitation_id", axum::routing::delete(cancel_invitation))
+        .route("/athletes/:athlete_id", axum::routing::delete(remove_athlete))
+        .route("/athletes/:athlete_id/sessions", get(get_athlete_sessions))
+        .route("/athletes/:athlete_id/pmc", get(get_athlete_pmc))
+        .route("/athletes/:athlete_id/goals", get(get_athlete_goals))
+        .route("/athletes/:athlete_id/recovery", get(get_athlete_recovery))
+        .route("/athletes/:athlete_id/plans", get(get_athlete_plans).post(create_athlete_plan))
+        .route("/athletes/:athlete_id/plans/:plan_id", put(update_athlete_plan))
+        .route_layer(middleware::from_fn(coach_or_admin_middleware));
+
+    Router::new()
+        .merge(coach_only)
+        .route("/coaches", get(list_coaches))
+        .route("/coaches/:relationship_id", axum::routing::delete(remove_coach))
+        .route("/coaches/:relationship_id/scopes", put(update_coach_scopes))
+        .route("/invitations/:relationship_id/accept", post(accept_invitation))
+        .route("/invitations/:relationship_id/decline", post(decline_invitation))
+        .route("/access-log", get(get_access_log))
+        .layer(middleware::from_fn_with_state(
+            auth_service,
+            jwt_auth_middleware,
+        ))
+        .with_state(state)
+}
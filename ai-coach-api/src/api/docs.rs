@@ -97,7 +97,7 @@ pub async fn openapi_spec() -> Result<Json<serde_json::Value>, StatusCode> {
                 "post": {
                     "tags": ["Training"],
                     "summary": "Upload training file",
-                    "description": "Upload and process training data file (TCX, GPX, CSV)",
+                    "description": "Upload and process training data file (TCX, GPX, CSV, FIT)",
                     "security": [{"bearerAuth": []}],
                     "requestBody": {
                         "required": true,
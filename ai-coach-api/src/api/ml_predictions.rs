@@ -15,6 +15,7 @@ use crate::services::{
     TrainingRecommendationService, ModelTrainingService, ModelVersioningService,
     FeatureEngineeringService,
 };
+use crate::services::model_versioning_service::{ABTestConfig, ABTestResults, TestMetrics};
 use crate::models::TrainingFeatures;
 
 #[derive(Debug, Deserialize)]
@@ -49,6 +50,12 @@ pub struct ModelTrainingQuery {
     pub target_rmse_threshold: Option<f32>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CompleteABTestQuery {
+    /// Deploy the challenger if it significantly beat the champion (default false)
+    pub promote_winner: Option<bool>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ABTestQuery {
     /// Test name
@@ -449,7 +456,12 @@ pub async fn list_model_versions(
     State(state): State<MLAppState>,
     WithRejection(_claims, _): WithRejection<Claims, StatusCode>,
 ) -> Result<Json<Vec<serde_json::Value>>, (StatusCode, Json<ApiError>)> {
-    let versions = state.versioning_service.list_model_versions().await;
+    let versions = state.versioning_service.list_model_versions().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("MODEL_REGISTRY_ERROR", &format!("Failed to list model versions: {}", e))),
+        )
+    })?;
 
     let response: Vec<serde_json::Value> = versions
         .into_iter()
@@ -475,7 +487,12 @@ pub async fn get_champion_model(
     State(state): State<MLAppState>,
     WithRejection(_claims, _): WithRejection<Claims, StatusCode>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
-    let champion = state.versioning_service.get_champion_version().await;
+    let champion = state.versioning_service.get_champion_version().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("MODEL_REGISTRY_ERROR", &format!("Failed to get champion model: {}", e))),
+        )
+    })?;
 
     let response = serde_json::json!({
         "champion_version": champion,
@@ -509,22 +526,7 @@ pub async fn create_ab_test(
             )
         })?;
 
-    let response = ABTestResponse {
-        test_id: test_config.test_id,
-        test_config: ABTestInfo {
-            name: test_config.name,
-            champion_version: test_config.champion_version,
-            challenger_version: test_config.challenger_version,
-            traffic_split: test_config.traffic_split,
-            start_date: test_config.start_date,
-            end_date: test_config.end_date,
-            target_metric: test_config.target_metric,
-        },
-        status: format!("{:?}", test_config.status),
-        results: None,
-    };
-
-    Ok(Json(response))
+    Ok(Json(ab_test_response(test_config, None)))
 }
 
 /// List active A/B tests
@@ -532,24 +534,16 @@ pub async fn list_ab_tests(
     State(state): State<MLAppState>,
     WithRejection(_claims, _): WithRejection<Claims, StatusCode>,
 ) -> Result<Json<Vec<ABTestResponse>>, (StatusCode, Json<ApiError>)> {
-    let tests = state.versioning_service.list_active_ab_tests().await;
+    let tests = state.versioning_service.list_active_ab_tests().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("AB_TEST_ERROR", &format!("Failed to list A/B tests: {}", e))),
+        )
+    })?;
 
     let response: Vec<ABTestResponse> = tests
         .into_iter()
-        .map(|test| ABTestResponse {
-            test_id: test.test_id,
-            test_config: ABTestInfo {
-                name: test.name,
-                champion_version: test.champion_version,
-                challenger_version: test.challenger_version,
-                traffic_split: test.traffic_split,
-                start_date: test.start_date,
-                end_date: test.end_date,
-                target_metric: test.target_metric,
-            },
-            status: format!("{:?}", test.status),
-            results: None,
-        })
+        .map(|test| ab_test_response(test, None))
         .collect();
 
     Ok(Json(response))
@@ -561,6 +555,8 @@ pub async fn get_ab_test_results(
     WithRejection(_claims, _): WithRejection<Claims, StatusCode>,
     Path(test_id): Path<Uuid>,
 ) -> Result<Json<ABTestResponse>, (StatusCode, Json<ApiError>)> {
+    let test_config = find_ab_test(&state, test_id).await?;
+
     let results = state
         .versioning_service
         .analyze_ab_test(test_id)
@@ -572,41 +568,7 @@ pub async fn get_ab_test_results(
             )
         })?;
 
-    // This would typically also fetch the test config, but for brevity using dummy data
-    let response = ABTestResponse {
-        test_id: results.test_id,
-        test_config: ABTestInfo {
-            name: "Test".to_string(),
-            champion_version: results.champion_performance.version.clone(),
-            challenger_version: results.challenger_performance.version.clone(),
-            traffic_split: 0.5,
-            start_date: chrono::Utc::now() - chrono::Duration::days(7),
-            end_date: chrono::Utc::now(),
-            target_metric: "rmse".to_string(),
-        },
-        status: "Completed".to_string(),
-        results: Some(ABTestResultInfo {
-            champion_performance: TestPerformanceMetrics {
-                version: results.champion_performance.version,
-                sample_size: results.champion_performance.sample_size,
-                avg_rmse: results.champion_performance.avg_rmse,
-                avg_confidence: results.champion_performance.avg_confidence,
-                prediction_latency_ms: results.champion_performance.prediction_latency_ms,
-            },
-            challenger_performance: TestPerformanceMetrics {
-                version: results.challenger_performance.version,
-                sample_size: results.challenger_performance.sample_size,
-                avg_rmse: results.challenger_performance.avg_rmse,
-                avg_confidence: results.challenger_performance.avg_confidence,
-                prediction_latency_ms: results.challenger_performance.prediction_latency_ms,
-            },
-            statistical_significance: results.statistical_significance,
-            winner: results.winner,
-            recommendation: format!("{:?}", results.recommendation),
-        }),
-    };
-
-    Ok(Json(response))
+    Ok(Json(ab_test_response(test_config, Some(results))))
 }
 
 /// Start an A/B test
@@ -640,10 +602,13 @@ pub async fn complete_ab_test(
     State(state): State<MLAppState>,
     WithRejection(_claims, _): WithRejection<Claims, StatusCode>,
     Path(test_id): Path<Uuid>,
+    Query(query): Query<CompleteABTestQuery>,
 ) -> Result<Json<ABTestResponse>, (StatusCode, Json<ApiError>)> {
+    find_ab_test(&state, test_id).await?;
+
     let results = state
         .versioning_service
-        .complete_ab_test(test_id, false) // Don't auto-promote winner
+        .complete_ab_test(test_id, query.promote_winner.unwrap_or(false))
         .await
         .map_err(|e| {
             (
@@ -652,41 +617,64 @@ pub async fn complete_ab_test(
             )
         })?;
 
-    // Convert results to response format (similar to get_ab_test_results)
-    let response = ABTestResponse {
-        test_id: results.test_id,
+    let test_config = find_ab_test(&state, test_id).await?;
+
+    Ok(Json(ab_test_response(test_config, Some(results))))
+}
+
+async fn find_ab_test(
+    state: &MLAppState,
+    test_id: Uuid,
+) -> Result<ABTestConfig, (StatusCode, Json<ApiError>)> {
+    state
+        .versioning_service
+        .get_ab_test(test_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("AB_TEST_ERROR", &format!("Failed to load A/B test: {}", e))),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::new("AB_TEST_NOT_FOUND", "A/B test not found")),
+            )
+        })
+}
+
+fn test_performance(metrics: TestMetrics) -> TestPerformanceMetrics {
+    TestPerformanceMetrics {
+        version: metrics.version,
+        sample_size: metrics.sample_size,
+        avg_rmse: metrics.avg_rmse,
+        avg_confidence: metrics.avg_confidence,
+        prediction_latency_ms: metrics.prediction_latency_ms,
+    }
+}
+
+fn ab_test_response(test: ABTestConfig, results: Option<ABTestResults>) -> ABTestResponse {
+    ABTestResponse {
+        test_id: test.test_id,
         test_config: ABTestInfo {
-            name: "Test".to_string(),
-            champion_version: results.champion_performance.version.clone(),
-            challenger_version: results.challenger_performance.version.clone(),
-            traffic_split: 0.5,
-            start_date: chrono::Utc::now() - chrono::Duration::days(7),
-            end_date: chrono::Utc::now(),
-            target_metric: "rmse".to_string(),
+            name: test.name,
+            champion_version: test.champion_version,
+            challenger_version: test.challenger_version,
+            traffic_split: test.traffic_split,
+            start_date: test.start_date,
+            end_date: test.end_date,
+            target_metric: test.target_metric,
         },
-        status: "Completed".to_string(),
-        results: Some(ABTestResultInfo {
-            champion_performance: TestPerformanceMetrics {
-                version: results.champion_performance.version,
-                sample_size: results.champion_performance.sample_size,
-                avg_rmse: results.champion_performance.avg_rmse,
-                avg_confidence: results.champion_performance.avg_confidence,
-                prediction_latency_ms: results.champion_performance.prediction_latency_ms,
-            },
-            challenger_performance: TestPerformanceMetrics {
-                version: results.challenger_performance.version,
-                sample_size: results.challenger_performance.sample_size,
-                avg_rmse: results.challenger_performance.avg_rmse,
-                avg_confidence: results.challenger_performance.avg_confidence,
-                prediction_latency_ms: results.challenger_performance.prediction_latency_ms,
-            },
+        status: format!("{:?}", test.status),
+        results: results.map(|results| ABTestResultInfo {
+            champion_performance: test_performance(results.champion_performance),
+            challenger_performance: test_performance(results.challenger_performance),
             statistical_significance: results.statistical_significance,
             winner: results.winner,
             recommendation: format!("{:?}", results.recommendation),
         }),
-    };
-
-    Ok(Json(response))
+    }
 }
 
 /// Get data quality assessment for the user
@@ -18,5 +18,7 @@ pub mod vision;
 pub mod docs;
 pub mod recovery;
 pub mod recovery_analysis;
-pub mod oura_wearable;
-pub mod training_adjustment;
\ No newline at end of file
+pub mod wearables;
+pub mod training_adjustment;
+pub mod coach;
+pub mod calendar;
\ No newline at end of file
@@ -1,6 +1,6 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     response::Json,
     routing::{get, post, put, delete},
     Router,
@@ -8,17 +8,24 @@ use axum::{
 use axum_extra::extract::WithRejection;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::sync::Arc;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
 use crate::auth::{AuthService, Claims};
 use crate::models::{
-    Notification, NotificationType, NotificationCategory, NotificationPriority,
-    DeliveryChannel, DeliveryStatus, CreateNotificationRequest,
-    NotificationPreferences, UpdateNotificationPreferencesRequest,
-    NotificationMetrics,
+    Notification, NotificationType, DeliveryChannel, CreateNotificationRequest,
+    NotificationPreferences, UpdateNotificationPreferencesRequest, NotificationFilter,
+    NotificationMetrics, CreatePushSubscriptionRequest, PushSubscription,
+    VapidPublicKeyResponse,
 };
-use crate::services::{NotificationService, notification_service::{NotificationError, PerformanceAlertType, HealthAlertType, MotivationNotificationType}};
+use crate::services::{
+    NotificationScheduler, NotificationService, WebPushService,
+    notification_service::{NotificationError, PerformanceAlertType, HealthAlertType, MotivationNotificationType},
+};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
 
 #[derive(Debug, Deserialize)]
 pub struct NotificationQuery {
@@ -99,15 +106,21 @@ pub struct NotificationAppState {
     pub db: PgPool,
     pub auth_service: AuthService,
     pub notification_service: NotificationService,
+    pub notification_scheduler: Arc<NotificationScheduler>,
 }
 
-pub fn notification_routes(db: PgPool, auth_service: AuthService) -> Router {
+pub fn notification_routes(
+    db: PgPool,
+    auth_service: AuthService,
+    notification_scheduler: Arc<NotificationScheduler>,
+) -> Router {
     let notification_service = NotificationService::new(db.clone());
 
     let shared_state = NotificationAppState {
         db,
         auth_service,
         notification_service,
+        notification_scheduler,
     };
 
     Router::new()
@@ -119,6 +132,9 @@ pub fn notification_routes(db: PgPool, auth_service: AuthService) -> Router {
         .route("/preferences", get(get_notification_preferences).put(update_notification_preferences))
         .route("/test", post(send_test_notification))
         .route("/metrics", get(get_notification_metrics))
+        .route("/push/vapid-public-key", get(get_vapid_public_key))
+        .route("/push/subscriptions", get(list_push_subscriptions).post(create_push_subscription))
+        .route("/push/subscriptions/:subscription_id", delete(delete_push_subscription))
         .route("/schedule/training-reminders", post(schedule_training_reminders))
         .route("/alerts/performance", post(create_performance_alert))
         .route("/alerts/health", post(create_health_alert))
@@ -139,19 +155,15 @@ pub async fn get_notifications(
         )
     })?;
 
-    // Mock implementation - in real code, this would query the database
-    let notifications = vec![];
-    let total_count = 0;
-    let unread_count = 0;
-
-    tracing::info!("Retrieved {} notifications for user {}", notifications.len(), user_id);
+    let filter = NotificationFilter {
+        category: query.category,
+        status: query.status,
+        unread_only: query.unread_only.unwrap_or(false),
+        limit: query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE),
+        offset: query.offset.unwrap_or(0).max(0),
+    };
 
-    Ok(Json(NotificationsListResponse {
-        notifications,
-        total_count,
-        unread_count,
-        success: true,
-    }))
+    list_notifications(&state, user_id, &filter).await
 }
 
 /// Get unread notifications for the authenticated user
@@ -166,16 +178,38 @@ pub async fn get_unread_notifications(
         )
     })?;
 
-    // Mock implementation
-    let notifications = vec![];
-    let unread_count = notifications.len() as i64;
+    let filter = NotificationFilter {
+        unread_only: true,
+        limit: MAX_PAGE_SIZE,
+        ..Default::default()
+    };
 
-    Ok(Json(NotificationsListResponse {
-        notifications,
-        total_count: unread_count,
-        unread_count,
-        success: true,
-    }))
+    list_notifications(&state, user_id, &filter).await
+}
+
+async fn list_notifications(
+    state: &NotificationAppState,
+    user_id: Uuid,
+    filter: &NotificationFilter,
+) -> Result<Json<NotificationsListResponse>, (StatusCode, Json<ApiError>)> {
+    match state.notification_service.list_notifications(user_id, filter).await {
+        Ok((notifications, total_count, unread_count)) => {
+            tracing::info!("Retrieved {} notifications for user {}", notifications.len(), user_id);
+            Ok(Json(NotificationsListResponse {
+                notifications,
+                total_count,
+                unread_count,
+                success: true,
+            }))
+        },
+        Err(e) => {
+            tracing::error!("Failed to list notifications: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("NOTIFICATIONS_RETRIEVAL_FAILED", "Failed to get notifications")),
+            ))
+        }
+    }
 }
 
 /// Get a specific notification
@@ -191,30 +225,20 @@ pub async fn get_notification(
         )
     })?;
 
-    // Mock notification
-    let notification = Notification {
-        id: notification_id,
-        user_id,
-        notification_type: NotificationType::WorkoutReminder,
-        category: NotificationCategory::Training,
-        priority: NotificationPriority::Medium,
-        title: "Workout Reminder".to_string(),
-        message: "Your workout is scheduled in 60 minutes".to_string(),
-        data: None,
-        scheduled_at: Utc::now(),
-        sent_at: Some(Utc::now()),
-        read_at: None,
-        delivery_channels: vec![DeliveryChannel::InApp, DeliveryChannel::Email],
-        delivery_status: DeliveryStatus::Delivered,
-        expires_at: Some(Utc::now() + chrono::Duration::hours(1)),
-        created_at: Utc::now(),
-        updated_at: Utc::now(),
-    };
-
-    Ok(Json(NotificationResponse {
-        notification,
-        success: true,
-    }))
+    match state.notification_service.get_notification(user_id, notification_id).await {
+        Ok(Some(notification)) => Ok(Json(NotificationResponse {
+            notification,
+            success: true,
+        })),
+        Ok(None) => Err(notification_not_found()),
+        Err(e) => {
+            tracing::error!("Failed to get notification {}: {}", notification_id, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("NOTIFICATIONS_RETRIEVAL_FAILED", "Failed to get notification")),
+            ))
+        }
+    }
 }
 
 /// Create a new notification
@@ -272,12 +296,22 @@ pub async fn mark_notifications_as_read(
         )
     })?;
 
-    // Mock implementation
-    tracing::info!("Marked {} notifications as read for user {}", request.notification_ids.len(), user_id);
+    let marked_count = state.notification_service
+        .mark_as_read(user_id, &request.notification_ids)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to mark notifications as read: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("MARK_READ_FAILED", "Failed to mark notifications as read")),
+            )
+        })?;
+
+    tracing::info!("Marked {} notifications as read for user {}", marked_count, user_id);
 
     Ok(Json(serde_json::json!({
         "success": true,
-        "marked_count": request.notification_ids.len()
+        "marked_count": marked_count
     })))
 }
 
@@ -287,7 +321,37 @@ pub async fn mark_notification_as_read(
     WithRejection(claims, _): WithRejection<Claims, StatusCode>,
     Path(notification_id): Path<Uuid>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
-    let user_id = claims.sub;
+    let user_id = claims.sub.parse::<Uuid>().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID format")),
+        )
+    })?;
+
+    let notification = state.notification_service
+        .get_notification(user_id, notification_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get notification {}: {}", notification_id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("MARK_READ_FAILED", "Failed to mark notification as read")),
+            )
+        })?;
+    if notification.is_none() {
+        return Err(notification_not_found());
+    }
+
+    state.notification_service
+        .mark_as_read(user_id, &[notification_id])
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to mark notification {} as read: {}", notification_id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("MARK_READ_FAILED", "Failed to mark notification as read")),
+            )
+        })?;
 
     tracing::info!("Marked notification {} as read for user {}", notification_id, user_id);
 
@@ -303,14 +367,37 @@ pub async fn delete_notification(
     WithRejection(claims, _): WithRejection<Claims, StatusCode>,
     Path(notification_id): Path<Uuid>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
-    let user_id = claims.sub;
+    let user_id = claims.sub.parse::<Uuid>().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID format")),
+        )
+    })?;
 
-    tracing::info!("Deleted notification {} for user {}", notification_id, user_id);
+    match state.notification_service.delete_notification(user_id, notification_id).await {
+        Ok(true) => {
+            tracing::info!("Deleted notification {} for user {}", notification_id, user_id);
+            Ok(Json(serde_json::json!({
+                "success": true,
+                "message": "Notification deleted"
+            })))
+        },
+        Ok(false) => Err(notification_not_found()),
+        Err(e) => {
+            tracing::error!("Failed to delete notification {}: {}", notification_id, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("NOTIFICATION_DELETE_FAILED", "Failed to delete notification")),
+            ))
+        }
+    }
+}
 
-    Ok(Json(serde_json::json!({
-        "success": true,
-        "message": "Notification deleted"
-    })))
+fn notification_not_found() -> (StatusCode, Json<ApiError>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ApiError::new("NOTIFICATION_NOT_FOUND", "Notification not found")),
+    )
 }
 
 /// Get notification preferences
@@ -363,6 +450,10 @@ pub async fn update_notification_preferences(
                 success: true,
             }))
         },
+        Err(NotificationError::InvalidData(message)) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_PREFERENCES", &message)),
+        )),
         Err(e) => {
             tracing::error!("Failed to update notification preferences: {}", e);
             Err((
@@ -398,8 +489,21 @@ pub async fn send_test_notification(
     };
 
     match state.notification_service.create_notification(create_request).await {
-        Ok(notification) => {
+        Ok(mut notification) => {
             tracing::info!("Created test notification {} for user {}", notification.id, user_id);
+
+            notification.delivery_status = state.notification_service
+                .send_notification(&notification)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to deliver test notification: {}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiError::new("TEST_NOTIFICATION_FAILED", &format!("Failed to send test notification: {}", e))),
+                    )
+                })?;
+            notification.sent_at = Some(Utc::now());
+
             Ok(Json(NotificationResponse {
                 notification,
                 success: true,
@@ -427,27 +531,129 @@ pub async fn get_notification_metrics(
         )
     })?;
 
-    // Mock metrics
-    let metrics = NotificationMetrics {
-        user_id: Some(user_id),
-        period_start: Utc::now() - chrono::Duration::days(30),
-        period_end: Utc::now(),
-        total_sent: 45,
-        total_delivered: 42,
-        total_read: 38,
-        delivery_rate: 93.3,
-        read_rate: 84.4,
-        by_type: std::collections::HashMap::new(),
-        by_channel: std::collections::HashMap::new(),
-    };
+    let period_end = Utc::now();
+    let period_start = period_end - chrono::Duration::days(30);
 
-    Ok(Json(NotificationMetricsResponse {
-        metrics,
-        success: true,
+    match state.notification_service.get_notification_metrics(user_id, period_start, period_end).await {
+        Ok(metrics) => Ok(Json(NotificationMetricsResponse {
+            metrics,
+            success: true,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to get notification metrics: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("METRICS_RETRIEVAL_FAILED", &format!("Failed to get notification metrics: {}", e))),
+            ))
+        }
+    }
+}
+
+fn push_service(state: &NotificationAppState) -> Result<&WebPushService, (StatusCode, Json<ApiError>)> {
+    state.notification_service.push_service().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiError::new("WEB_PUSH_DISABLED", "Web Push notifications are not configured")),
+        )
+    })
+}
+
+/// Get the VAPID public key browsers subscribe with (`applicationServerKey`)
+pub async fn get_vapid_public_key(
+    State(state): State<NotificationAppState>,
+) -> Result<Json<VapidPublicKeyResponse>, (StatusCode, Json<ApiError>)> {
+    let push_service = push_service(&state)?;
+
+    Ok(Json(VapidPublicKeyResponse {
+        public_key: push_service.vapid_public_key().to_string(),
     }))
 }
 
-/// Schedule training reminders for the user
+/// Register this browser's push subscription
+pub async fn create_push_subscription(
+    State(state): State<NotificationAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+    headers: HeaderMap,
+    Json(request): Json<CreatePushSubscriptionRequest>,
+) -> Result<(StatusCode, Json<PushSubscription>), (StatusCode, Json<ApiError>)> {
+    let user_id = claims.sub.parse::<Uuid>().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID format")),
+        )
+    })?;
+    let push_service = push_service(&state)?;
+    let user_agent = headers.get(header::USER_AGENT).and_then(|value| value.to_str().ok());
+
+    match push_service.subscribe(user_id, request, user_agent).await {
+        Ok(subscription) => Ok((StatusCode::CREATED, Json(subscription))),
+        Err(e) => {
+            tracing::warn!("Rejected push subscription for user {}: {}", user_id, e);
+            Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("INVALID_PUSH_SUBSCRIPTION", &format!("Invalid push subscription: {}", e))),
+            ))
+        }
+    }
+}
+
+/// List the user's push subscriptions (one per browser/device)
+pub async fn list_push_subscriptions(
+    State(state): State<NotificationAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+) -> Result<Json<Vec<PushSubscription>>, (StatusCode, Json<ApiError>)> {
+    let user_id = claims.sub.parse::<Uuid>().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID format")),
+        )
+    })?;
+    let push_service = push_service(&state)?;
+
+    match push_service.list_subscriptions(user_id).await {
+        Ok(subscriptions) => Ok(Json(subscriptions)),
+        Err(e) => {
+            tracing::error!("Failed to list push subscriptions: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("PUSH_SUBSCRIPTIONS_FAILED", "Failed to list push subscriptions")),
+            ))
+        }
+    }
+}
+
+/// Remove a push subscription, e.g. when the user turns off notifications on a device
+pub async fn delete_push_subscription(
+    State(state): State<NotificationAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+    Path(subscription_id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, Json<ApiError>)> {
+    let user_id = claims.sub.parse::<Uuid>().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID format")),
+        )
+    })?;
+    let push_service = push_service(&state)?;
+
+    match push_service.unsubscribe(user_id, subscription_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiError::new("PUSH_SUBSCRIPTION_NOT_FOUND", "Push subscription not found")),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to delete push subscription: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("PUSH_SUBSCRIPTIONS_FAILED", "Failed to delete push subscription")),
+            ))
+        }
+    }
+}
+
+/// Schedule reminders for the user's workouts tomorrow now, instead of on the scheduler's next
+/// check. Reminders already scheduled are not repeated.
 pub async fn schedule_training_reminders(
     State(state): State<NotificationAppState>,
     WithRejection(claims, _): WithRejection<Claims, StatusCode>,
@@ -459,12 +665,12 @@ pub async fn schedule_training_reminders(
         )
     })?;
 
-    match state.notification_service.schedule_training_reminders(user_id).await {
-        Ok(notifications) => {
-            tracing::info!("Scheduled {} training reminders for user {}", notifications.len(), user_id);
+    match state.notification_scheduler.schedule_workout_reminders(user_id).await {
+        Ok(scheduled) => {
+            tracing::info!("Scheduled {} training reminders for user {}", scheduled, user_id);
             Ok(Json(serde_json::json!({
                 "success": true,
-                "scheduled_count": notifications.len(),
+                "scheduled_count": scheduled,
                 "message": "Training reminders scheduled successfully"
             })))
         },
@@ -1,7 +1,7 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::{get, post, put},
     Router,
 };
@@ -13,9 +13,10 @@ use uuid::Uuid;
 use crate::auth::{AuthService, Claims};
 use crate::models::{
     GeneratedPlan, PlanGenerationRequest, UserTrainingPreferences, TrainingConstraints,
-    PlanAdaptation, PlanAlternative, CoachingInsight, AdaptationType
+    PlanAdaptation, PlanAlternative, CoachingInsight, AdaptationType, WorkoutFileFormat
 };
-use crate::services::PlanGenerationService;
+use crate::services::{PlanGenerationService, WorkoutExportService};
+use crate::services::workout_export_service::WorkoutFile;
 
 #[derive(Debug, Deserialize)]
 pub struct PlanQuery {
@@ -31,6 +32,13 @@ pub struct AdaptPlanRequest {
     pub trigger_reason: String,
 }
 
+/// Workout file export options; `day` (1=Monday) exports a single session instead of the week
+#[derive(Debug, Deserialize)]
+pub struct WorkoutExportQuery {
+    pub format: String,
+    pub day: Option<i32>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct PlanResponse {
     pub plan: GeneratedPlan,
@@ -60,14 +68,18 @@ pub struct PlanGenerationAppState {
     pub db: PgPool,
     pub auth_service: AuthService,
     pub plan_generation_service: PlanGenerationService,
+    pub workout_export_service: WorkoutExportService,
 }
 
 pub fn plan_generation_routes(db: PgPool, auth_service: AuthService) -> Router {
     let plan_generation_service = PlanGenerationService::new(db.clone());
+    let workout_export_service = WorkoutExportService::new(db.clone())
+        .expect("Failed to create WorkoutExportService");
     let shared_state = PlanGenerationAppState {
         db,
         auth_service,
         plan_generation_service,
+        workout_export_service,
     };
 
     Router::new()
@@ -76,6 +88,7 @@ pub fn plan_generation_routes(db: PgPool, auth_service: AuthService) -> Router {
         .route("/:plan_id/adapt", post(adapt_plan))
         .route("/:plan_id/alternatives", get(get_alternatives).post(generate_alternatives))
         .route("/:plan_id/insights", get(get_insights).post(generate_insights))
+        .route("/:plan_id/weeks/:week_number/export", get(export_plan_week))
         .route("/preferences", get(get_preferences).put(update_preferences))
         .route("/constraints", get(get_constraints).put(update_constraints))
         .with_state(shared_state)
@@ -127,6 +140,62 @@ pub async fn get_plan(
     }))
 }
 
+/// Download a plan week as ZWO, ERG, MRC or FIT workout files (zipped), or a single day's file
+/// (zipped too when the day has more than one workout)
+pub async fn export_plan_week(
+    State(state): State<PlanGenerationAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+    Path((plan_id, week_number)): Path<(Uuid, i32)>,
+    Query(query): Query<WorkoutExportQuery>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| {
+        (StatusCode::BAD_REQUEST, Json(ApiError::new("INVALID_USER_ID", "Invalid user ID")))
+    })?;
+
+    let format = query.format.parse::<WorkoutFileFormat>().map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiError::new("INVALID_FORMAT", &e.to_string())))
+    })?;
+
+    let plan = state.plan_generation_service
+        .get_plan_by_id(plan_id, user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get plan: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiError::new("DATABASE_ERROR", "Failed to retrieve plan")))
+        })?
+        .ok_or_else(|| {
+            (StatusCode::NOT_FOUND, Json(ApiError::new("PLAN_NOT_FOUND", "Plan not found")))
+        })?;
+
+    let file = state.workout_export_service
+        .export_plan_week(user_id, &plan, week_number, query.day, format)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Failed to export plan {} week {}: {}", plan_id, week_number, e);
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(ApiError::new("EXPORT_FAILED", &e.to_string())))
+        })?
+        .ok_or_else(|| {
+            (StatusCode::NOT_FOUND, Json(ApiError::new("WORKOUT_NOT_FOUND", "No trainer workouts for that week or day")))
+        })?;
+
+    Ok(workout_file_response(file))
+}
+
+/// Attachment response with the file's content type
+pub fn workout_file_response(file: WorkoutFile) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, file.content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file.file_name),
+            ),
+        ],
+        file.data,
+    )
+        .into_response()
+}
+
 /// Generate a new training plan
 pub async fn generate_plan(
     State(state): State<PlanGenerationAppState>,
@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Query, State},
+    extract::{multipart::Field, DefaultBodyLimit, Multipart, Path, Query, State},
     http::StatusCode,
     response::Json,
     routing::{get, post},
@@ -8,17 +8,22 @@ use axum::{
 use axum_extra::extract::WithRejection;
 use serde::Serialize;
 use sqlx::PgPool;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::auth::{AuthService, Claims};
 use crate::models::{
-    CreateHrvReadingRequest, CreateRestingHrRequest, CreateSleepDataRequest,
-    HrvReadingResponse, HrvReadingsListResponse, RecoveryBaselineResponse,
-    RecoveryDataQuery, RestingHrListResponse, RestingHrResponse, SleepDataListResponse,
-    SleepDataResponse,
+    CreateHealthImportQuery, CreateHrvReadingRequest, CreateRestingHrRequest,
+    CreateSleepDataRequest, HealthImportResponse, HrvReadingResponse, HrvReadingsListResponse,
+    RecoveryBaselineResponse, RecoveryDataQuery, RestingHrListResponse, RestingHrResponse,
+    SleepDataListResponse, SleepDataResponse,
 };
-use crate::services::RecoveryDataService;
+use crate::services::{BackgroundJobService, HealthImportService, RecoveryDataService};
+
+/// Health exports span years of data, so uploads get a much larger body limit than the default
+const DEFAULT_MAX_IMPORT_MB: usize = 1024;
 
 #[derive(Debug, Serialize)]
 pub struct ApiError {
@@ -50,14 +55,31 @@ pub struct RecoveryAppState {
     pub db: PgPool,
     pub auth_service: AuthService,
     pub recovery_service: RecoveryDataService,
+    pub health_import_service: HealthImportService,
+    pub background_job_service: Arc<BackgroundJobService>,
 }
 
-pub fn recovery_routes(db: PgPool, auth_service: AuthService) -> Router {
+pub fn recovery_routes(
+    db: PgPool,
+    auth_service: AuthService,
+    background_job_service: Arc<BackgroundJobService>,
+) -> Router {
     let recovery_service = RecoveryDataService::new(db.clone());
+    let health_import_service = HealthImportService::new(db.clone());
+
+    let max_import_bytes = std::env::var("HEALTH_IMPORT_MAX_MB")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_IMPORT_MB)
+        * 1024
+        * 1024;
+
     let shared_state = RecoveryAppState {
         db,
         auth_service,
         recovery_service,
+        health_import_service,
+        background_job_service,
     };
 
     Router::new()
@@ -69,6 +91,14 @@ pub fn recovery_routes(db: PgPool, auth_service: AuthService) -> Router {
         .route("/resting-hr", post(create_resting_hr).get(get_resting_hr_data))
         // Baseline endpoint
         .route("/baseline", get(get_baseline))
+        // Apple Health / Health Connect import endpoints
+        .route(
+            "/import",
+            post(upload_health_export)
+                .get(list_health_imports)
+                .layer(DefaultBodyLimit::max(max_import_bytes)),
+        )
+        .route("/import/:import_id", get(get_health_import))
         .with_state(shared_state)
 }
 
@@ -94,6 +124,13 @@ pub async fn create_hrv_reading(
         )
     })?;
 
+    if request.rmssd.is_none() && request.sdnn.is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("VALIDATION_ERROR", "An HRV reading needs RMSSD or SDNN")),
+        ));
+    }
+
     let user_id = Uuid::parse_str(&claims.sub).map_err(|_| {
         (
             StatusCode::BAD_REQUEST,
@@ -384,3 +421,164 @@ pub async fn get_baseline(
         )),
     }
 }
+
+// ============================================================================
+// Health Import Endpoints
+// ============================================================================
+
+/// Upload an Apple Health or Health Connect export as the multipart field `file`. The file is
+/// streamed to disk and imported by a background job; poll the returned import for progress.
+pub async fn upload_health_export(
+    State(state): State<RecoveryAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+    Query(query): Query<CreateHealthImportQuery>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<HealthImportResponse>), (StatusCode, Json<ApiError>)> {
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID")),
+        )
+    })?;
+
+    let internal_error = |e: anyhow::Error| {
+        tracing::error!("Failed to start health import: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("IMPORT_FAILED", "Failed to start health import")),
+        )
+    };
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        (
+            e.status(),
+            Json(ApiError::new("INVALID_UPLOAD", &e.body_text())),
+        )
+    })? {
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let service = &state.health_import_service;
+        let import = service
+            .create_import(user_id, query.format, field.file_name())
+            .await
+            .map_err(internal_error)?;
+        let path = service.upload_path(&import).map_err(internal_error)?;
+
+        let size_bytes = match save_upload(field, &path).await {
+            Ok(size_bytes) => size_bytes,
+            Err(e) => {
+                let status = e
+                    .downcast_ref::<axum::extract::multipart::MultipartError>()
+                    .map_or(StatusCode::INTERNAL_SERVER_ERROR, |e| e.status());
+                tracing::warn!("Health import upload {} failed: {}", import.id, e);
+                service
+                    .fail_import(&import, "Upload was interrupted")
+                    .await
+                    .map_err(internal_error)?;
+                return Err((
+                    status,
+                    Json(ApiError::new("UPLOAD_FAILED", "Failed to receive the export file")),
+                ));
+            }
+        };
+
+        let import = service
+            .finish_upload(import.id, size_bytes)
+            .await
+            .map_err(internal_error)?;
+        let job_id = state
+            .background_job_service
+            .queue_health_import(import.id, user_id)
+            .await
+            .map_err(internal_error)?;
+        let import = service
+            .set_job_id(import.id, job_id)
+            .await
+            .map_err(internal_error)?;
+
+        return Ok((StatusCode::ACCEPTED, Json(import.into())));
+    }
+
+    Err((
+        StatusCode::BAD_REQUEST,
+        Json(ApiError::new("MISSING_FILE", "Multipart field 'file' is required")),
+    ))
+}
+
+/// Stream a multipart field to `path`, returning the bytes written
+async fn save_upload(mut field: Field<'_>, path: &std::path::Path) -> anyhow::Result<i64> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut size_bytes = 0;
+    while let Some(chunk) = field.chunk().await? {
+        file.write_all(&chunk).await?;
+        size_bytes += chunk.len() as i64;
+    }
+    file.flush().await?;
+
+    Ok(size_bytes)
+}
+
+/// List the user's health imports, newest first
+pub async fn list_health_imports(
+    State(state): State<RecoveryAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+) -> Result<Json<Vec<HealthImportResponse>>, (StatusCode, Json<ApiError>)> {
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID")),
+        )
+    })?;
+
+    let imports = state
+        .health_import_service
+        .list_user_imports(user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list health imports: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("DATABASE_ERROR", "Failed to list imports")),
+            )
+        })?;
+
+    Ok(Json(imports.into_iter().map(Into::into).collect()))
+}
+
+/// Get the status and progress of one of the user's health imports
+pub async fn get_health_import(
+    State(state): State<RecoveryAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+    Path(import_id): Path<Uuid>,
+) -> Result<Json<HealthImportResponse>, (StatusCode, Json<ApiError>)> {
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID")),
+        )
+    })?;
+
+    let import = state
+        .health_import_service
+        .get_user_import(user_id, import_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get health import: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("DATABASE_ERROR", "Failed to retrieve import")),
+            )
+        })?
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            Json(ApiError::new("IMPORT_NOT_FOUND", "Import not found")),
+        ))?;
+
+    Ok(Json(import.into()))
+}
@@ -8,6 +8,7 @@ use axum::{
 use axum_extra::extract::WithRejection;
 use serde::Serialize;
 use sqlx::PgPool;
+use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::auth::{AuthService, Claims};
@@ -15,7 +16,9 @@ use crate::models::{
     RecoveryAlert, RecoveryAlertPreferences, RecoveryHistoryQuery, RecoveryInsightsResponse,
     RecoveryStatusResponse, RecoveryTrendsResponse,
 };
-use crate::services::{NotificationService, RecoveryAlertService, RecoveryAnalysisService};
+use crate::services::{
+    BackgroundJobService, NotificationService, RecoveryAlertService, RecoveryAnalysisService,
+};
 
 #[derive(Debug, Serialize)]
 pub struct ApiError {
@@ -42,8 +45,12 @@ pub struct RecoveryAnalysisAppState {
     pub alert_service: RecoveryAlertService,
 }
 
-pub fn recovery_analysis_routes(db: PgPool, auth_service: AuthService) -> Router {
-    let analysis_service = RecoveryAnalysisService::new(db.clone());
+pub fn recovery_analysis_routes(
+    db: PgPool,
+    auth_service: AuthService,
+    background_job_service: Arc<BackgroundJobService>,
+) -> Router {
+    let analysis_service = RecoveryAnalysisService::new(db.clone(), background_job_service);
     let notification_service = NotificationService::new(db.clone());
     let alert_service = RecoveryAlertService::new(db.clone(), notification_service);
 
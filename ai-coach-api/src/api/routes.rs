@@ -18,51 +18,46 @@ use super::vision::vision_routes;
 use super::docs::docs_routes;
 use super::recovery::recovery_routes;
 use super::recovery_analysis::recovery_analysis_routes;
-use super::oura_wearable::oura_wearable_routes;
+use super::wearables::wearable_routes;
 use super::training_adjustment::training_adjustment_routes;
+use super::coach::coach_routes;
+use super::calendar::calendar_routes;
 use crate::auth::AuthService;
 use crate::config::AppConfig;
+use crate::middleware::{rate_limit_middleware, RateLimiter};
+use crate::services::{BackgroundJobService, NotificationScheduler, WearableIntegrationService};
+use std::sync::Arc;
 
-pub fn create_routes(db: PgPool, jwt_secret: &str, app_config: &AppConfig) -> Router {
+pub fn create_routes(
+    db: PgPool,
+    jwt_secret: &str,
+    app_config: &AppConfig,
+    background_job_service: Arc<BackgroundJobService>,
+    notification_scheduler: Arc<NotificationScheduler>,
+    wearable_integration_service: WearableIntegrationService,
+) -> Router {
     let auth_service = AuthService::new(db.clone(), jwt_secret);
 
     // Create v1 API routes
     let mut api_v1 = Router::new()
         .nest("/auth", auth_routes(auth_service.clone()))
         .nest("/admin", admin_routes(auth_service.clone()))
-        .nest("/training", training_routes(db.clone(), auth_service.clone()))
+        .nest("/training", training_routes(db.clone(), auth_service.clone(), background_job_service.clone()))
         .nest("/coaching", coaching_routes(db.clone(), auth_service.clone()))
         .nest("/goals", goals_routes(db.clone(), auth_service.clone()))
-        .nest("/analytics", analytics_routes(db.clone(), auth_service.clone()))
-        .nest("/user", user_profile_routes(db.clone(), auth_service.clone()))
-        .nest("/notifications", notification_routes(db.clone(), auth_service.clone()))
+        .nest("/analytics", analytics_routes(db.clone(), auth_service.clone(), background_job_service.clone()))
+        .nest("/user", user_profile_routes(db.clone(), auth_service.clone(), background_job_service.clone()))
+        .nest("/notifications", notification_routes(db.clone(), auth_service.clone(), notification_scheduler))
         .nest("/events", events_routes(db.clone(), auth_service.clone()))
         .nest("/plans", plan_generation_routes(db.clone(), auth_service.clone()))
         .nest("/vision", vision_routes(db.clone(), auth_service.clone()))
-        .nest("/recovery", recovery_routes(db.clone(), auth_service.clone()))
-        .nest("/recovery/analysis", recovery_analysis_routes(db.clone(), auth_service.clone()))
-        .nest("/training/adjustment", training_adjustment_routes(db.clone(), auth_service.clone()));
-
-    // Add Oura wearable routes if credentials are configured
-    if let (Some(client_id), Some(client_secret), Some(redirect_uri)) = (
-        &app_config.oura_client_id,
-        &app_config.oura_client_secret,
-        &app_config.oura_redirect_uri,
-    ) {
-        api_v1 = api_v1.nest(
-            "/recovery/wearables/oura",
-            oura_wearable_routes(
-                db.clone(),
-                auth_service.clone(),
-                client_id.clone(),
-                client_secret.clone(),
-                redirect_uri.clone(),
-            ),
-        );
-        tracing::info!("Oura wearable integration enabled");
-    } else {
-        tracing::warn!("Oura wearable integration disabled (missing OAuth credentials)");
-    }
+        .nest("/recovery", recovery_routes(db.clone(), auth_service.clone(), background_job_service.clone()))
+        .nest("/recovery/analysis", recovery_analysis_routes(db.clone(), auth_service.clone(), background_job_service.clone()))
+        .nest("/training/adjustment", training_adjustment_routes(db.clone(), auth_service.clone()))
+        .nest("/coach", coach_routes(db.clone(), auth_service.clone()))
+        .nest("/calendar", calendar_routes(db.clone(), auth_service.clone()))
+        // Wearable routes for every provider whose OAuth credentials are configured
+        .nest("/recovery/wearables", wearable_routes(wearable_integration_service, auth_service.clone()));
 
     api_v1 = api_v1
         // Documentation routes
@@ -72,14 +67,30 @@ pub fn create_routes(db: PgPool, jwt_secret: &str, app_config: &AppConfig) -> Ro
         .nest("/workouts", workout_recommendation_routes(db.clone(), auth_service.clone()))
         .nest("/performance", performance_insights_routes(db.clone(), auth_service.clone()));
 
-    Router::new()
+    let router = Router::new()
         .route("/health", get(health_check))
         .nest("/api/v1", api_v1)
         // Maintain backward compatibility with existing routes
         .nest("/api/auth", auth_routes(auth_service.clone()))
         .nest("/api/admin", admin_routes(auth_service.clone()))
-        .nest("/api/training", training_routes(db.clone(), auth_service.clone()))
+        .nest("/api/training", training_routes(db.clone(), auth_service.clone(), background_job_service))
         .nest("/api/ml", ml_prediction_routes(db.clone(), auth_service.clone()))
         .nest("/api/workouts", workout_recommendation_routes(db.clone(), auth_service.clone()))
-        .nest("/api/performance", performance_insights_routes(db.clone(), auth_service.clone()))
+        .nest("/api/performance", performance_insights_routes(db.clone(), auth_service.clone()));
+
+    if !app_config.rate_limit_enabled {
+        tracing::warn!("Rate limiting disabled");
+        return router;
+    }
+
+    let rate_limiter = RateLimiter::from_redis_url(app_config.redis_url.as_deref(), jwt_secret)
+        .expect("Failed to create rate limiter")
+        .with_trusted_proxies(app_config.trusted_proxies.clone());
+    if app_config.redis_url.is_some() {
+        tracing::info!("Rate limiting enabled (shared through Redis)");
+    } else {
+        tracing::info!("Rate limiting enabled (per replica)");
+    }
+
+    router.layer(axum::middleware::from_fn_with_state(rate_limiter, rate_limit_middleware))
 }
\ No newline at end of file
@@ -14,7 +14,7 @@ use uuid::Uuid;
 
 use crate::auth::{AuthService, Claims};
 use crate::services::{TrainingAnalysisService, TrainingSessionService, BackgroundJobService};
-use crate::models::{TrainingSession, CreateTrainingSession};
+use crate::models::{TrainingSession, CreateTrainingSession, UpsertTrainingSession};
 
 #[derive(Debug, Deserialize)]
 pub struct UploadQuery {
@@ -54,6 +54,25 @@ impl PaginationQuery {
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SessionsQuery {
+    /// Maximum number of items to return (default: 50, max: 100)
+    pub limit: Option<i64>,
+    /// Number of items to skip (default: 0)
+    pub offset: Option<i64>,
+    /// Only return sessions changed after this time, oldest change first (for client sync)
+    pub updated_since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl SessionsQuery {
+    pub fn pagination(&self) -> PaginationQuery {
+        PaginationQuery {
+            limit: self.limit,
+            offset: self.offset,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct FileUploadResponse {
     /// Unique identifier for the uploaded file/training session
@@ -157,7 +176,11 @@ impl ApiError {
     }
 }
 
-pub fn training_routes(db: PgPool, auth_service: AuthService) -> Router {
+pub fn training_routes(
+    db: PgPool,
+    auth_service: AuthService,
+    background_job_service: Arc<BackgroundJobService>,
+) -> Router {
     let training_analysis_service = TrainingAnalysisService::new(
         db.clone(),
         std::env::var("REDIS_URL").ok(),
@@ -165,13 +188,6 @@ pub fn training_routes(db: PgPool, auth_service: AuthService) -> Router {
 
     let training_session_service = TrainingSessionService::new(db.clone());
 
-    let background_job_service = Arc::new(
-        BackgroundJobService::new(
-            db.clone(),
-            std::env::var("REDIS_URL").ok(),
-        ).expect("Failed to create BackgroundJobService")
-    );
-
     let shared_state = AppState {
         db,
         auth_service,
@@ -183,7 +199,7 @@ pub fn training_routes(db: PgPool, auth_service: AuthService) -> Router {
     Router::new()
         .route("/upload", post(upload_training_file))
         .route("/sessions/:session_id/metrics", get(get_training_metrics))
-        .route("/sessions", get(get_training_sessions))
+        .route("/sessions", get(get_training_sessions).post(upsert_training_session))
         .route("/pmc", get(get_performance_management_chart))
         .route("/process/:session_id", post(process_training_session))
         .route("/jobs/:job_id", get(get_job_status))
@@ -200,7 +216,7 @@ pub struct AppState {
     pub background_job_service: Arc<BackgroundJobService>,
 }
 
-/// Upload a training file (TCX, GPX, CSV)
+/// Upload a training file (TCX, GPX, CSV, FIT)
 pub async fn upload_training_file(
     State(state): State<AppState>,
     WithRejection(claims, _): WithRejection<Claims, StatusCode>,
@@ -282,10 +298,14 @@ pub async fn upload_training_file(
                             distance_meters: metrics.distance_meters,
                         };
 
-                        let _ = state
+                        if state
                             .training_session_service
                             .update_session(session.id, update_data)
-                            .await;
+                            .await
+                            .is_ok()
+                        {
+                            queue_goal_evaluation(&state, user_id).await;
+                        }
 
                         response.processing_status = "processed".to_string();
                         response.metrics = Some(metrics_json);
@@ -342,12 +362,16 @@ pub async fn get_training_metrics(
 }
 
 /// Get training sessions for a user
+///
+/// With `updated_since`, returns sessions changed after that time ordered by
+/// `updated_at` so clients can page through changes and advance a sync cursor.
 pub async fn get_training_sessions(
     State(state): State<AppState>,
     WithRejection(claims, _): WithRejection<Claims, StatusCode>,
-    Query(pagination): Query<PaginationQuery>,
+    Query(query): Query<SessionsQuery>,
 ) -> Result<Json<Vec<TrainingSession>>, StatusCode> {
     let user_id = claims.sub;
+    let pagination = query.pagination();
 
     // Validate pagination parameters
     if let Err(_) = pagination.validate() {
@@ -357,13 +381,40 @@ pub async fn get_training_sessions(
     let limit = Some(pagination.get_limit());
     let offset = Some(pagination.get_offset());
 
-    let sessions = state
+    let sessions = match query.updated_since {
+        Some(since) => state
+            .training_session_service
+            .get_sessions_updated_since(user_id, since, limit, offset)
+            .await,
+        None => state
+            .training_session_service
+            .get_sessions_by_user_id(user_id, limit, offset)
+            .await,
+    }
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(sessions))
+}
+
+/// Create or update a manually logged session using a client-generated ID
+pub async fn upsert_training_session(
+    State(state): State<AppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+    Json(payload): Json<UpsertTrainingSession>,
+) -> Result<Json<TrainingSession>, StatusCode> {
+    let user_id = claims.sub;
+
+    let session = state
         .training_session_service
-        .get_sessions_by_user_id(user_id, limit, offset)
+        .upsert_session(user_id, payload)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        // The ID already belongs to another user's session
+        .ok_or(StatusCode::CONFLICT)?;
 
-    Ok(Json(sessions))
+    queue_goal_evaluation(&state, user_id).await;
+
+    Ok(Json(session))
 }
 
 /// Get Performance Management Chart data
@@ -394,7 +445,7 @@ pub async fn get_performance_management_chart(
             ctl: pmc.ctl,
             atl: pmc.atl,
             tsb: pmc.tsb,
-            tss_daily: 0.0, // This would come from the actual PMC calculation
+            tss_daily: pmc.tss_daily,
         })
         .collect();
 
@@ -463,10 +514,14 @@ pub async fn process_training_session(
         distance_meters: metrics.distance_meters,
     };
 
-    let _ = state
+    if state
         .training_session_service
         .update_session(session.id, update_data)
-        .await;
+        .await
+        .is_ok()
+    {
+        queue_goal_evaluation(&state, user_id).await;
+    }
 
     let response = TrainingMetricsResponse {
         session_id: session.id,
@@ -477,6 +532,13 @@ pub async fn process_training_session(
     Ok(Json(response))
 }
 
+/// Re-measure the user's goals in the background once session data is saved
+async fn queue_goal_evaluation(state: &AppState, user_id: Uuid) {
+    if let Err(e) = state.background_job_service.queue_goal_evaluation(user_id).await {
+        tracing::warn!("Failed to queue goal evaluation for user {}: {}", user_id, e);
+    }
+}
+
 /// Get background job status
 pub async fn get_job_status(
     State(state): State<AppState>,
@@ -489,6 +551,7 @@ pub async fn get_job_status(
         .background_job_service
         .get_job_status(job_id)
         .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
 
     let response = serde_json::json!({
@@ -515,7 +578,8 @@ pub async fn get_user_jobs(
     let jobs = state
         .background_job_service
         .get_user_jobs(user_id)
-        .await;
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     let response: Vec<serde_json::Value> = jobs
         .into_iter()
@@ -1,21 +1,22 @@
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
     http::StatusCode,
     response::Json,
     routing::{get, patch, post},
     Router,
 };
 use axum_extra::extract::WithRejection;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::auth::{AuthService, Claims};
 use crate::models::{
-    TrainingRecoverySettings, TrainingRecoverySettingsResponse,
+    TrainingAdjustmentResponse, TrainingRecoverySettings, TrainingRecoverySettingsResponse,
     UpdateTrainingRecoverySettingsRequest,
 };
+use crate::services::training_adjustment_service::RevertAdjustmentError;
 use crate::services::TrainingAdjustmentService;
 
 #[derive(Debug, Serialize)]
@@ -50,6 +51,7 @@ pub struct TrainingAdjustmentAppState {
     pub adjustment_service: TrainingAdjustmentService,
 }
 
+/// Create training adjustment routes
 pub fn training_adjustment_routes(db: PgPool, auth_service: AuthService) -> Router {
     let adjustment_service = TrainingAdjustmentService::new(db.clone());
     let shared_state = TrainingAdjustmentAppState {
@@ -64,6 +66,8 @@ pub fn training_adjustment_routes(db: PgPool, auth_service: AuthService) -> Rout
             get(get_recovery_settings).patch(update_recovery_settings),
         )
         .route("/recommended-adjustment", get(get_recommended_adjustment))
+        .route("/adjustments", get(list_adjustments))
+        .route("/adjustments/:adjustment_id/revert", post(revert_adjustment))
         .with_state(shared_state)
 }
 
@@ -397,3 +401,80 @@ pub async fn get_recommended_adjustment(
         rest_recommendation: rest_info,
     }))
 }
+
+// ============================================================================
+// Adjustment History Endpoints
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct AdjustmentHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+/// List recovery adjustments, most recent first
+pub async fn list_adjustments(
+    State(state): State<TrainingAdjustmentAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+    Query(query): Query<AdjustmentHistoryQuery>,
+) -> Result<Json<Vec<TrainingAdjustmentResponse>>, (StatusCode, Json<ApiError>)> {
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID")),
+        )
+    })?;
+
+    let limit = query.limit.unwrap_or(30).clamp(1, 200);
+    let adjustments = state
+        .adjustment_service
+        .list_adjustments(user_id, limit)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list training adjustments: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new(
+                    "DATABASE_ERROR",
+                    "Failed to retrieve adjustments",
+                )),
+            )
+        })?;
+
+    Ok(Json(adjustments.into_iter().map(Into::into).collect()))
+}
+
+/// Undo an automatic adjustment, restoring the originally planned workout
+pub async fn revert_adjustment(
+    State(state): State<TrainingAdjustmentAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+    Path(adjustment_id): Path<Uuid>,
+) -> Result<Json<TrainingAdjustmentResponse>, (StatusCode, Json<ApiError>)> {
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID")),
+        )
+    })?;
+
+    match state.adjustment_service.revert_adjustment(user_id, adjustment_id).await {
+        Ok(adjustment) => Ok(Json(adjustment.into())),
+        Err(RevertAdjustmentError::NotFound) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiError::new("ADJUSTMENT_NOT_FOUND", "Adjustment not found")),
+        )),
+        Err(e @ (RevertAdjustmentError::NotRevertible | RevertAdjustmentError::PlanChanged)) => Err((
+            StatusCode::CONFLICT,
+            Json(ApiError::new("ADJUSTMENT_NOT_REVERTIBLE", &e.to_string())),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to revert training adjustment {}: {}", adjustment_id, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new(
+                    "SERVICE_ERROR",
+                    "Failed to revert adjustment",
+                )),
+            ))
+        }
+    }
+}
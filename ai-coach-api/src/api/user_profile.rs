@@ -2,16 +2,20 @@ use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::Json,
-    routing::{get, put},
+    routing::{get, post, put},
     Router,
 };
 use axum_extra::extract::WithRejection;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::sync::Arc;
 use uuid::Uuid;
 use chrono::{NaiveDate, DateTime, Utc};
 
+use crate::api::analytics::queue_export;
 use crate::auth::{AuthService, Claims};
+use crate::models::{CreateExportRequest, DataExportResponse, ExportFormat};
+use crate::services::{BackgroundJobService, DataExportService};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserProfile {
@@ -172,12 +176,23 @@ impl ApiError {
 pub struct ProfileAppState {
     pub db: PgPool,
     pub auth_service: AuthService,
+    pub data_export_service: DataExportService,
+    pub background_job_service: Arc<BackgroundJobService>,
 }
 
-pub fn user_profile_routes(db: PgPool, auth_service: AuthService) -> Router {
+pub fn user_profile_routes(
+    db: PgPool,
+    auth_service: AuthService,
+    background_job_service: Arc<BackgroundJobService>,
+) -> Router {
+    let data_export_service = DataExportService::new(db.clone())
+        .expect("Failed to create DataExportService");
+
     let shared_state = ProfileAppState {
         db,
         auth_service,
+        data_export_service,
+        background_job_service,
     };
 
     Router::new()
@@ -187,7 +202,7 @@ pub fn user_profile_routes(db: PgPool, auth_service: AuthService) -> Router {
         .route("/profile/notifications", get(get_notification_settings).put(update_notification_settings))
         .route("/profile/privacy", get(get_privacy_settings).put(update_privacy_settings))
         .route("/profile/zones/calculate", get(calculate_training_zones))
-        .route("/profile/export", get(export_profile_data))
+        .route("/profile/export", post(export_profile_data))
         .with_state(shared_state)
 }
 
@@ -503,24 +518,34 @@ pub async fn calculate_training_zones(
     })))
 }
 
-/// Export user profile data
+/// Queue a full export of the user's data (GDPR data access request) as a zip bundle
 pub async fn export_profile_data(
     State(state): State<ProfileAppState>,
     WithRejection(claims, _): WithRejection<Claims, StatusCode>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
-    let user_id = claims.sub;
+) -> Result<(StatusCode, Json<DataExportResponse>), (StatusCode, Json<ApiError>)> {
+    let user_id = claims.sub.parse::<Uuid>().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", "Invalid user ID format")),
+        )
+    })?;
+
+    let request = CreateExportRequest {
+        format: ExportFormat::Zip,
+        datasets: None,
+        start_date: None,
+        end_date: None,
+    };
 
-    // This would export all user data in a structured format
-    Ok(Json(serde_json::json!({
-        "export_id": Uuid::new_v4(),
-        "created_at": Utc::now(),
-        "data": {
-            "profile": {},
-            "thresholds": {},
-            "preferences": {},
-            "training_history": [],
-            "goals": []
-        },
-        "success": true
-    })))
+    let export = queue_export(&state.data_export_service, &state.background_job_service, user_id, &request)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to queue profile export: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("EXPORT_FAILED", "Failed to queue data export")),
+            )
+        })?;
+
+    Ok((StatusCode::ACCEPTED, Json(export)))
 }
\ No newline at end of file
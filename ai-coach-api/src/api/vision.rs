@@ -4,36 +4,40 @@ use axum::{
     http::StatusCode,
     middleware,
     response::{IntoResponse, Json, Response},
-    routing::{delete, get, post},
+    routing::{get, post},
     Extension,
     Router,
 };
 use serde::Deserialize;
 use sqlx::PgPool;
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::{
-    auth::{middleware::auth_middleware, models::Claims, AuthService},
+    auth::{jwt_auth_middleware, AuthService, UserSession},
     models::vision_analysis::*,
-    services::{VisionAnalysisService, VideoProcessingService, VideoStorageService},
+    services::{
+        LocalVideoStorage, PoseEstimationService, VideoProcessingService, VideoStorage,
+        VisionAnalysisService, VisionPipelineService,
+    },
 };
 
 /// Shared state for vision API handlers
 pub struct VisionState {
     pub analysis_service: Arc<VisionAnalysisService>,
-    pub storage_service: Arc<VideoStorageService>,
+    pub storage_service: Arc<dyn VideoStorage>,
     pub processing_service: Arc<VideoProcessingService>,
+    pub pipeline: Arc<VisionPipelineService>,
 }
 
 /// Upload video for analysis
 pub async fn upload_video(
-    Extension(claims): Extension<Claims>,
+    Extension(session): Extension<UserSession>,
     State(state): State<Arc<VisionState>>,
     mut multipart: Multipart,
 ) -> Result<Response, VisionError> {
-    info!("Video upload request from user: {}", claims.sub);
+    info!("Video upload request from user: {}", session.user_id);
 
     let mut video_data: Option<Vec<u8>> = None;
     let mut content_type: Option<String> = None;
@@ -95,8 +99,7 @@ pub async fn upload_video(
         )));
     }
 
-    let user_id = Uuid::parse_str(&claims.sub)
-        .map_err(|_| VisionError::InvalidRequest("Invalid user ID".to_string()))?;
+    let user_id = session.user_id;
 
     // Create analysis record
     let analysis = state
@@ -128,11 +131,10 @@ pub async fn upload_video(
             VisionError::StorageError
         })?;
 
-    // Update analysis with video URL
-    sqlx::query("UPDATE vision_analyses SET video_url = $1 WHERE id = $2")
-        .bind(&video_url)
-        .bind(analysis.id)
-        .execute(&state.analysis_service.db)
+    // Update analysis with video URL and storage key
+    state
+        .analysis_service
+        .set_video_location(analysis.id, video_url.clone(), storage_key.clone())
         .await
         .map_err(|e| {
             error!("Failed to update analysis with video URL: {}", e);
@@ -141,7 +143,14 @@ pub async fn upload_video(
 
     info!("Video uploaded successfully: analysis_id={}", analysis.id);
 
-    // TODO: Trigger background processing job
+    // Process in the background; progress is visible through /:id/status
+    let pipeline = Arc::clone(&state.pipeline);
+    let analysis_id = analysis.id;
+    tokio::spawn(async move {
+        if let Err(e) = pipeline.process(analysis_id, &storage_key).await {
+            error!("Background processing failed for analysis {}: {}", analysis_id, e);
+        }
+    });
 
     let response = VisionAnalysisUploadResponse {
         id: analysis.id,
@@ -155,12 +164,11 @@ pub async fn upload_video(
 
 /// Get analysis result by ID
 pub async fn get_analysis(
-    Extension(claims): Extension<Claims>,
+    Extension(session): Extension<UserSession>,
     State(state): State<Arc<VisionState>>,
     Path(analysis_id): Path<Uuid>,
 ) -> Result<Response, VisionError> {
-    let user_id = Uuid::parse_str(&claims.sub)
-        .map_err(|_| VisionError::InvalidRequest("Invalid user ID".to_string()))?;
+    let user_id = session.user_id;
 
     // Check authorization
     let analysis = state
@@ -183,12 +191,11 @@ pub async fn get_analysis(
 
 /// Get analysis status
 pub async fn get_analysis_status(
-    Extension(claims): Extension<Claims>,
+    Extension(session): Extension<UserSession>,
     State(state): State<Arc<VisionState>>,
     Path(analysis_id): Path<Uuid>,
 ) -> Result<Response, VisionError> {
-    let user_id = Uuid::parse_str(&claims.sub)
-        .map_err(|_| VisionError::InvalidRequest("Invalid user ID".to_string()))?;
+    let user_id = session.user_id;
 
     let analysis = state
         .analysis_service
@@ -223,12 +230,11 @@ fn default_limit() -> i64 {
 }
 
 pub async fn list_analyses(
-    Extension(claims): Extension<Claims>,
+    Extension(session): Extension<UserSession>,
     State(state): State<Arc<VisionState>>,
     Query(query): Query<ListQuery>,
 ) -> Result<Response, VisionError> {
-    let user_id = Uuid::parse_str(&claims.sub)
-        .map_err(|_| VisionError::InvalidRequest("Invalid user ID".to_string()))?;
+    let user_id = session.user_id;
 
     let analyses = state
         .analysis_service
@@ -255,12 +261,11 @@ pub async fn list_analyses(
 
 /// Delete analysis
 pub async fn delete_analysis(
-    Extension(claims): Extension<Claims>,
+    Extension(session): Extension<UserSession>,
     State(state): State<Arc<VisionState>>,
     Path(analysis_id): Path<Uuid>,
 ) -> Result<Response, VisionError> {
-    let user_id = Uuid::parse_str(&claims.sub)
-        .map_err(|_| VisionError::InvalidRequest("Invalid user ID".to_string()))?;
+    let user_id = session.user_id;
 
     // Check authorization
     let analysis = state
@@ -270,9 +275,12 @@ pub async fn delete_analysis(
         .map_err(|_| VisionError::DatabaseError)?
         .ok_or(VisionError::NotFound)?;
 
-    // Delete from storage
-    // Extract storage key from video URL if possible
-    // TODO: Implement proper storage key extraction
+    // Delete from storage; a missing object should not block removing the record
+    if let Some(storage_key) = analysis.storage_key() {
+        if let Err(e) = state.storage_service.delete_video(storage_key).await {
+            warn!("Failed to delete video {} for analysis {}: {}", storage_key, analysis_id, e);
+        }
+    }
 
     // Delete from database (cascades to pose_detections and movement_scores)
     state
@@ -331,32 +339,83 @@ impl IntoResponse for VisionError {
     }
 }
 
-/// Create vision API routes
+/// Build the storage backend configured through the environment
 ///
-/// Note: This is a Phase 1 implementation stub. The vision routes are defined
-/// but the full S3 storage integration requires additional configuration:
-/// - AWS credentials and S3 bucket setup
-/// - FFmpeg installation for video processing
-/// - Background job queue for async processing
+/// `VIDEO_STORAGE_BACKEND=local` stores videos under `VIDEO_STORAGE_PATH`
+/// (default `uploads/videos`), optionally served from `VIDEO_STORAGE_BASE_URL`.
+/// S3 storage needs an async AWS client and is constructed with `S3VideoStorage::new`.
+fn storage_from_env() -> Option<Arc<dyn VideoStorage>> {
+    match std::env::var("VIDEO_STORAGE_BACKEND").ok()?.to_lowercase().as_str() {
+        "local" | "filesystem" => {
+            let path = std::env::var("VIDEO_STORAGE_PATH")
+                .unwrap_or_else(|_| "uploads/videos".to_string());
+
+            let mut storage = match LocalVideoStorage::new(&path) {
+                Ok(storage) => storage,
+                Err(e) => {
+                    error!("Failed to initialize local video storage at {}: {}", path, e);
+                    return None;
+                }
+            };
+            if let Ok(base_url) = std::env::var("VIDEO_STORAGE_BASE_URL") {
+                storage = storage.with_base_url(base_url);
+            }
+
+            info!("Using local video storage at {}", path);
+            Some(Arc::new(storage))
+        }
+        other => {
+            warn!("Unsupported VIDEO_STORAGE_BACKEND for vision routes: {}", other);
+            None
+        }
+    }
+}
+
+/// Create vision API routes
 ///
-/// TODO for Phase 2:
-/// - Initialize S3 client with proper AWS configuration
-/// - Set up video storage service with bucket details
-/// - Implement background processing pipeline
-/// - Add video validation and thumbnail generation
-pub fn vision_routes(_db: PgPool, auth_service: AuthService) -> Router {
-    // Placeholder router until S3 and FFmpeg infrastructure is set up
-    // The routes are commented out to prevent runtime errors
-    // Uncomment and configure when infrastructure is ready
+/// Routes are only mounted when a storage backend is configured and the pose
+/// model (`POSE_MODEL_PATH`, default `models/pose_v1.onnx`) loads. FFmpeg must
+/// be installed for video validation and frame extraction.
+pub fn vision_routes(db: PgPool, auth_service: AuthService) -> Router {
+    let Some(storage_service) = storage_from_env() else {
+        info!("Vision routes disabled: no video storage backend configured");
+        return Router::new();
+    };
+
+    let model_path = std::env::var("POSE_MODEL_PATH")
+        .unwrap_or_else(|_| "models/pose_v1.onnx".to_string());
+    let pose_service = match PoseEstimationService::new(&model_path) {
+        Ok(service) => Arc::new(service),
+        Err(e) => {
+            error!("Vision routes disabled: failed to load pose model {}: {}", model_path, e);
+            return Router::new();
+        }
+    };
+
+    let analysis_service = Arc::new(VisionAnalysisService::new(db));
+    let processing_service = Arc::new(VideoProcessingService::new());
+    let pipeline = Arc::new(VisionPipelineService::new(
+        Arc::clone(&analysis_service),
+        Arc::clone(&storage_service),
+        Arc::clone(&processing_service),
+        pose_service,
+    ));
+
+    let state = Arc::new(VisionState {
+        analysis_service,
+        storage_service,
+        processing_service,
+        pipeline,
+    });
 
     Router::new()
-        // .route("/upload", post(upload_video))
-        // .route("/history", get(list_analyses))
-        // .route("/:id", get(get_analysis))
-        // .route("/:id/status", get(get_analysis_status))
-        // .route("/:id", delete(delete_analysis))
+        .route("/upload", post(upload_video))
+        .route("/history", get(list_analyses))
+        .route("/:id", get(get_analysis).delete(delete_analysis))
+        .route("/:id/status", get(get_analysis_status))
         .layer(middleware::from_fn_with_state(
-            auth_service.clone(),
-            auth_middleware,
+            auth_service,
+            jwt_auth_middleware,
         ))
+        .with_state(state)
 }
@@ -0,0 +1,282 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Json, Redirect, Response},
+    routing::{delete, get, post},
+    Extension,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::auth::{jwt_auth_middleware, AuthService, UserSession};
+use crate::models::{DataSource, WearableConnectionResponse};
+use crate::services::WearableIntegrationService;
+
+/// Shared state for wearable API handlers
+pub struct WearableState {
+    pub integration_service: WearableIntegrationService,
+}
+
+impl WearableState {
+    /// Resolve the `:provider` path segment to a configured provider
+    fn configured_provider(&self, provider: &str) -> Result<DataSource, WearableError> {
+        provider
+            .parse::<DataSource>()
+            .ok()
+            .filter(|source| self.integration_service.providers().get(source).is_some())
+            .ok_or(WearableError::NotFound("Wearable provider not available"))
+    }
+}
+
+/// Create wearable routes. `/:provider/callback` is public since the provider redirects the
+/// browser there; everything else requires a signed-in user.
+pub fn wearable_routes(integration_service: WearableIntegrationService, auth_service: AuthService) -> Router {
+    let state = Arc::new(WearableState { integration_service });
+
+    let authenticated = Router::new()
+        .route("/", get(list_wearables))
+        .route("/:provider/authorize", get(authorize_provider))
+        .route("/:provider/sync", post(sync_provider_data))
+        .route("/:provider/disconnect", delete(disconnect_provider))
+        .layer(middleware::from_fn_with_state(
+            auth_service,
+            jwt_auth_middleware,
+        ));
+
+    Router::new()
+        .route("/:provider/callback", get(provider_callback))
+        .merge(authenticated)
+        .with_state(state)
+}
+
+#[derive(Debug, Serialize)]
+pub struct WearablesResponse {
+    /// Providers configured on this server that the user can connect
+    pub available_providers: Vec<DataSource>,
+    pub connections: Vec<WearableConnectionResponse>,
+}
+
+/// List connectable providers and the user's active connections
+pub async fn list_wearables(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<WearableState>>,
+) -> Result<Json<WearablesResponse>, WearableError> {
+    let connections = state
+        .integration_service
+        .list_connections(session.user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list wearable connections: {}", e);
+            WearableError::DatabaseError
+        })?;
+
+    Ok(Json(WearablesResponse {
+        available_providers: state.integration_service.providers().sources(),
+        connections: connections.into_iter().map(Into::into).collect(),
+    }))
+}
+
+// ============================================================================
+// OAuth Flow Endpoints
+// ============================================================================
+
+/// Start OAuth authorization with a provider
+pub async fn authorize_provider(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<WearableState>>,
+    Path(provider): Path<String>,
+) -> Result<Redirect, WearableError> {
+    let source = state.configured_provider(&provider)?;
+
+    let auth_url = state
+        .integration_service
+        .get_authorization_url(session.user_id, source)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to start {} authorization: {:#}", source, e);
+            WearableError::DatabaseError
+        })?;
+
+    Ok(Redirect::to(&auth_url))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConnectionResponse {
+    pub success: bool,
+    pub message: String,
+    pub provider: String,
+    pub connected_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Handle a provider's OAuth callback
+pub async fn provider_callback(
+    State(state): State<Arc<WearableState>>,
+    Path(provider): Path<String>,
+    Query(params): Query<CallbackQuery>,
+) -> Result<Json<ConnectionResponse>, WearableError> {
+    let source = state.configured_provider(&provider)?;
+
+    // Check for OAuth errors
+    if let Some(error) = params.error {
+        let description = params.error_description.unwrap_or_default();
+        tracing::error!("{} OAuth error: {} - {}", source, error, description);
+        return Err(WearableError::InvalidRequest(format!(
+            "{} authorization failed: {}",
+            source, error
+        )));
+    }
+
+    let code = params
+        .code
+        .ok_or_else(|| WearableError::InvalidRequest("Authorization code is missing".to_string()))?;
+
+    let state_value = params
+        .state
+        .ok_or_else(|| WearableError::InvalidRequest("State parameter is missing".to_string()))?;
+
+    // The state must be one this server issued for this provider, not yet used or expired
+    let user_id = state
+        .integration_service
+        .consume_oauth_state(&state_value, source)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to check {} OAuth state: {:#}", source, e);
+            WearableError::DatabaseError
+        })?
+        .ok_or_else(|| WearableError::InvalidRequest("Invalid or expired state parameter".to_string()))?;
+
+    let connection = state
+        .integration_service
+        .handle_oauth_callback(user_id, source, &code)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to handle {} OAuth callback: {:#}", source, e);
+            WearableError::ProviderError(format!("Failed to complete {} authorization", source))
+        })?;
+
+    Ok(Json(ConnectionResponse {
+        success: true,
+        message: format!("{} successfully connected", source),
+        provider: connection.provider,
+        connected_at: connection.connected_at,
+    }))
+}
+
+// ============================================================================
+// Data Sync Endpoints
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct SyncQuery {
+    pub days_back: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncResponse {
+    pub success: bool,
+    pub sleep_records: usize,
+    pub hrv_readings: usize,
+    pub rhr_readings: usize,
+    pub errors: Vec<String>,
+}
+
+/// Manually trigger a data sync from a provider
+pub async fn sync_provider_data(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<WearableState>>,
+    Path(provider): Path<String>,
+    Query(query): Query<SyncQuery>,
+) -> Result<Json<SyncResponse>, WearableError> {
+    let source = state.configured_provider(&provider)?;
+    let days_back = query.days_back.unwrap_or(30).clamp(1, 90); // Max 90 days
+
+    let connected = state
+        .integration_service
+        .get_connection(session.user_id, source)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load {} connection: {}", source, e);
+            WearableError::DatabaseError
+        })?
+        .is_some();
+    if !connected {
+        return Err(WearableError::NotFound("Wearable provider is not connected"));
+    }
+
+    let result = state
+        .integration_service
+        .sync_user_data(session.user_id, source, days_back)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to sync {} data: {:#}", source, e);
+            WearableError::ProviderError(format!("Failed to sync {} data: {}", source, e))
+        })?;
+
+    Ok(Json(SyncResponse {
+        success: result.errors.is_empty(),
+        sleep_records: result.sleep_records,
+        hrv_readings: result.hrv_readings,
+        rhr_readings: result.rhr_readings,
+        errors: result.errors,
+    }))
+}
+
+// ============================================================================
+// Disconnect Endpoint
+// ============================================================================
+
+/// Disconnect a provider
+pub async fn disconnect_provider(
+    Extension(session): Extension<UserSession>,
+    State(state): State<Arc<WearableState>>,
+    Path(provider): Path<String>,
+) -> Result<StatusCode, WearableError> {
+    let source = state.configured_provider(&provider)?;
+
+    state
+        .integration_service
+        .disconnect(session.user_id, source)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to disconnect {}: {}", source, e);
+            WearableError::DatabaseError
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Error type for wearable API
+#[derive(Debug)]
+pub enum WearableError {
+    InvalidRequest(String),
+    NotFound(&'static str),
+    /// The provider rejected a request or returned something we couldn't use
+    ProviderError(String),
+    DatabaseError,
+}
+
+impl IntoResponse for WearableError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            WearableError::InvalidRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            WearableError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.to_string()),
+            WearableError::ProviderError(msg) => (StatusCode::BAD_GATEWAY, msg),
+            WearableError::DatabaseError => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database error".to_string(),
+            ),
+        };
+
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
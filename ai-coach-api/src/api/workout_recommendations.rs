@@ -1,7 +1,7 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    response::Json,
+    response::{Json, Response},
     routing::{get, post},
     Router,
 };
@@ -12,8 +12,10 @@ use uuid::Uuid;
 use chrono;
 
 use crate::auth::{AuthService, Claims};
-use crate::models::{StructuredWorkoutRecommendation, SportType, TrainingZone};
-use crate::services::WorkoutRecommendationService;
+use crate::api::plan_generation::workout_file_response;
+use crate::models::{StructuredWorkoutRecommendation, SportType, TrainingZone, WorkoutFileFormat};
+use crate::services::{WorkoutExportService, WorkoutRecommendationService};
+use crate::services::workout_recommendation_service::WorkoutRecommendationRequest;
 
 /// Query parameters for workout recommendations
 #[derive(Debug, Deserialize)]
@@ -27,6 +29,12 @@ pub struct WorkoutRecommendationQuery {
     pub recent_workouts: Option<String>, // Comma-separated workout types
 }
 
+/// Workout file format for recommendation exports
+#[derive(Debug, Deserialize)]
+pub struct WorkoutExportFormatQuery {
+    pub format: String,
+}
+
 /// Response wrapper for workout recommendations
 #[derive(Debug, Serialize)]
 pub struct WorkoutRecommendationResponse {
@@ -79,20 +87,25 @@ pub struct WorkoutAppState {
     pub db: PgPool,
     pub auth_service: AuthService,
     pub workout_service: WorkoutRecommendationService,
+    pub workout_export_service: WorkoutExportService,
 }
 
 /// Create workout recommendation routes
 pub fn workout_recommendation_routes(db: PgPool, auth_service: AuthService) -> Router {
     let workout_service = WorkoutRecommendationService::new(db.clone());
+    let workout_export_service = WorkoutExportService::new(db.clone())
+        .expect("Failed to create WorkoutExportService");
 
     let shared_state = WorkoutAppState {
         db,
         auth_service,
         workout_service,
+        workout_export_service,
     };
 
     Router::new()
         .route("/test", get(test_handler))
+        .route("/recommendation/export", get(export_workout_recommendation))
         // .route("/recommendation", get(get_workout_recommendation))
         // .route("/alternatives/:recommendation_id", get(get_workout_alternatives))
         // .route("/feedback", post(submit_workout_feedback))
@@ -114,6 +127,69 @@ async fn get_workout_recommendation(
         )
     })?;
 
+    let request = recommendation_request(user_id, query);
+
+    match state.workout_service.get_structured_workout_recommendation(request).await {
+        Ok(recommendation) => {
+            tracing::info!("Generated workout recommendation for user {}", user_id);
+            Ok(Json(WorkoutRecommendationResponse {
+                recommendation,
+                success: true,
+                message: "Workout recommendation generated successfully".to_string(),
+            }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to generate workout recommendation: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("WORKOUT_RECOMMENDATION_ERROR", &format!("Failed to generate workout recommendation: {}", e))),
+            ))
+        }
+    }
+}
+
+/// Generate a workout recommendation and download it as a ZWO, ERG, MRC or FIT workout file
+async fn export_workout_recommendation(
+    State(state): State<WorkoutAppState>,
+    WithRejection(claims, _): WithRejection<Claims, StatusCode>,
+    Query(query): Query<WorkoutRecommendationQuery>,
+    Query(export): Query<WorkoutExportFormatQuery>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    let user_id = claims.sub.parse::<Uuid>().map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("INVALID_USER_ID", &format!("Invalid user ID format: {}", e))),
+        )
+    })?;
+
+    let format = export.format.parse::<WorkoutFileFormat>().map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ApiError::new("INVALID_FORMAT", &e.to_string())))
+    })?;
+
+    let recommendation = state.workout_service
+        .get_structured_workout_recommendation(recommendation_request(user_id, query))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to generate workout recommendation: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("WORKOUT_RECOMMENDATION_ERROR", &format!("Failed to generate workout recommendation: {}", e))),
+            )
+        })?;
+
+    let file = state.workout_export_service
+        .export_recommendation(user_id, &recommendation, format)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Failed to export workout recommendation {}: {}", recommendation.id, e);
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(ApiError::new("EXPORT_FAILED", &e.to_string())))
+        })?;
+
+    Ok(workout_file_response(file))
+}
+
+/// Build a recommendation request from query parameters
+fn recommendation_request(user_id: Uuid, query: WorkoutRecommendationQuery) -> WorkoutRecommendationRequest {
     // Parse sport type
     let sport_type = match query.sport_type.as_deref() {
         Some("cycling") => SportType::Cycling,
@@ -141,7 +217,7 @@ async fn get_workout_recommendation(
     let recent_workouts = query.recent_workouts
         .map(|rw| rw.split(',').map(|s| s.trim().to_string()).collect());
 
-    let request = crate::services::workout_recommendation_service::WorkoutRecommendationRequest {
+    WorkoutRecommendationRequest {
         user_id,
         sport_type,
         target_date,
@@ -150,24 +226,6 @@ async fn get_workout_recommendation(
         available_equipment,
         goals,
         recent_workouts,
-    };
-
-    match state.workout_service.get_structured_workout_recommendation(request).await {
-        Ok(recommendation) => {
-            tracing::info!("Generated workout recommendation for user {}", user_id);
-            Ok(Json(WorkoutRecommendationResponse {
-                recommendation,
-                success: true,
-                message: "Workout recommendation generated successfully".to_string(),
-            }))
-        }
-        Err(e) => {
-            tracing::error!("Failed to generate workout recommendation: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiError::new("WORKOUT_RECOMMENDATION_ERROR", &format!("Failed to generate workout recommendation: {}", e))),
-            ))
-        }
     }
 }
 
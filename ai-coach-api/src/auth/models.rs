@@ -89,6 +89,8 @@ pub struct ChangePasswordRequest {
 #[derive(Debug, Deserialize)]
 pub struct UpdateProfileRequest {
     pub email: Option<String>,
+    /// Required to change the email
+    pub current_password: Option<String>,
 }
 
 /// Authentication response models
@@ -110,6 +112,15 @@ pub struct UserInfo {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Page of users for the admin user list
+#[derive(Debug, Serialize)]
+pub struct UserListResponse {
+    pub users: Vec<UserInfo>,
+    pub total: i64,
+    pub page: u32,
+    pub limit: u32,
+}
+
 #[derive(Debug, Serialize)]
 pub struct TokenResponse {
     pub access_token: String,
@@ -122,13 +133,15 @@ pub struct MessageResponse {
     pub message: String,
 }
 
-/// Password reset token model
-#[derive(Debug, Clone)]
+/// Password reset token model; only a hash of the emailed token is stored
+#[derive(Debug, Clone, sqlx::FromRow)]
 pub struct PasswordResetToken {
+    pub id: Uuid,
     pub user_id: Uuid,
-    pub token: String,
+    pub token_hash: String,
     pub expires_at: DateTime<Utc>,
-    pub used: bool,
+    pub used: Option<bool>,
+    pub used_at: Option<DateTime<Utc>>,
 }
 
 /// Refresh token model
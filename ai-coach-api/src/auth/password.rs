@@ -114,6 +114,13 @@ pub fn generate_reset_token() -> String {
         .collect()
 }
 
+/// Hash a password reset token for storage, so a leaked table can't be used to reset passwords
+pub fn hash_reset_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
 /// Check password strength and return a score (0-100)
 pub fn calculate_password_strength(password: &str) -> u8 {
     let mut score: u8 = 0;
@@ -224,4 +231,15 @@ mod tests {
         assert_eq!(token2.len(), 32);
         assert_ne!(token1, token2); // Should be different
     }
+
+    #[test]
+    fn test_reset_token_hashing() {
+        let token = generate_reset_token();
+        let hash = hash_reset_token(&token);
+
+        assert_eq!(hash.len(), 64);
+        assert_ne!(hash, token);
+        assert_eq!(hash, hash_reset_token(&token));
+        assert_ne!(hash, hash_reset_token(&generate_reset_token()));
+    }
 }
\ No newline at end of file
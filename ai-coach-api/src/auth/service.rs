@@ -3,10 +3,27 @@ use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
 use crate::auth::{
-    AuthError, AuthResponse, Claims, JwtService, LoginRequest, MessageResponse, RefreshTokenRequest,
-    RegisterRequest, TokenResponse, UserInfo, UserRole, UserSession,
+    AuthError, AuthResponse, ChangePasswordRequest, Claims, ForgotPasswordRequest, JwtService,
+    LoginRequest, MessageResponse, PasswordResetToken, RefreshTokenRequest, RegisterRequest,
+    ResetPasswordRequest, TokenResponse, UpdateProfileRequest, UserInfo, UserListResponse,
+    UserRole, UserSession,
 };
-use crate::auth::password::{hash_password, verify_password, generate_reset_token};
+use crate::auth::password::{
+    generate_reset_token, hash_password, hash_reset_token, validate_password_strength,
+    verify_password, PasswordPolicy,
+};
+use crate::models::validate_email;
+use crate::services::email_notification_service::{EmailNotificationService, SmtpConfig};
+use crate::services::CoachAthleteService;
+
+/// How long an emailed password reset link stays valid
+const RESET_TOKEN_EXPIRY_MINUTES: i64 = 60;
+const DEFAULT_USERS_PAGE_SIZE: u32 = 20;
+const MAX_USERS_PAGE_SIZE: u32 = 100;
+
+/// Reply to forgot-password whether or not the account exists, so emails can't be probed
+const RESET_REQUESTED_MESSAGE: &str =
+    "If an account with that email exists, a password reset link has been sent.";
 
 /// Simple user model for authentication
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -18,9 +35,47 @@ pub struct User {
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// User joined with their role, for profile and admin listings
+#[derive(Debug, sqlx::FromRow)]
+struct UserWithRole {
+    id: Uuid,
+    email: String,
+    role: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+    #[sqlx(default)]
+    total_count: Option<i64>,
+}
+
+impl From<UserWithRole> for UserInfo {
+    fn from(user: UserWithRole) -> Self {
+        Self {
+            id: user.id,
+            email: user.email,
+            role: UserRole::from_str(&user.role).unwrap_or(UserRole::Athlete),
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        }
+    }
+}
+
+/// Filters and paging for the admin user list
+#[derive(Debug, Default)]
+pub struct UserListParams {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+    /// Case-insensitive substring of the email address
+    pub search: Option<String>,
+    pub role: Option<UserRole>,
+}
+
 #[derive(Debug, Clone)]
 pub struct AuthService {
     jwt_service: JwtService,
+    email_service: EmailNotificationService,
+    password_policy: PasswordPolicy,
+    /// Prefix for links in account emails, from `PUBLIC_BASE_URL`
+    public_base_url: String,
     db: PgPool,
 }
 
@@ -28,6 +83,11 @@ impl AuthService {
     pub fn new(db: PgPool, jwt_secret: &str) -> Self {
         Self {
             jwt_service: JwtService::new(jwt_secret),
+            email_service: EmailNotificationService::new(SmtpConfig::default()),
+            password_policy: PasswordPolicy::default(),
+            public_base_url: std::env::var("PUBLIC_BASE_URL")
+                .map(|url| url.trim_end_matches('/').to_string())
+                .unwrap_or_else(|_| "https://ai-coach.app".to_string()),
             db,
         }
     }
@@ -63,6 +123,14 @@ impl AuthService {
         // Add role to user
         self.update_user_role(user.id, &role).await?;
 
+        // Invitations coaches sent to this email before the account existed
+        if let Err(e) = CoachAthleteService::new(self.db.clone())
+            .resolve_email_invitations(user.id, &user.email)
+            .await
+        {
+            tracing::warn!("Failed to resolve coach invitations for user {}: {}", user.id, e);
+        }
+
         // Generate tokens
         let (access_token, refresh_token) = self
             .jwt_service
@@ -185,8 +253,420 @@ impl AuthService {
         Ok(session)
     }
 
+    /// Get the user's account details
+    pub async fn get_user_info(&self, user_id: Uuid) -> Result<UserInfo, AuthError> {
+        let user = sqlx::query_as::<_, UserWithRole>(
+            "SELECT u.id, u.email, COALESCE(r.role, 'athlete') AS role, u.created_at, u.updated_at
+             FROM users u LEFT JOIN user_roles r ON r.user_id = u.id
+             WHERE u.id = $1"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(AuthError::Database)?
+        .ok_or(AuthError::UserNotFound)?;
+
+        Ok(user.into())
+    }
+
+    /// Update the user's profile. Changing the email requires the current password and a
+    /// valid, unused address, and revokes every refresh token like a password change.
+    pub async fn update_profile(
+        &self,
+        user_id: Uuid,
+        request: UpdateProfileRequest,
+    ) -> Result<UserInfo, AuthError> {
+        if let Some(email) = request.email {
+            let email = email.trim().to_string();
+            let user = self.get_user_by_id(user_id).await?;
+
+            if email == user.email {
+                return self.get_user_info(user_id).await;
+            }
+
+            let current_password = request.current_password.as_deref().ok_or_else(|| {
+                AuthError::PasswordValidation("Current password is required to change the email".to_string())
+            })?;
+            if !verify_password(current_password, &user.password_hash)? {
+                return Err(AuthError::InvalidCredentials);
+            }
+
+            validate_email(&email).map_err(|e| AuthError::EmailValidation(e.to_string()))?;
+
+            if let Some(existing) = self.get_user_by_email(&email).await? {
+                if existing.id != user_id {
+                    return Err(AuthError::EmailAlreadyExists);
+                }
+            }
+
+            let mut tx = self.db.begin().await.map_err(AuthError::Database)?;
+
+            let updated = sqlx::query("UPDATE users SET email = $2, updated_at = NOW() WHERE id = $1")
+                .bind(user_id)
+                .bind(&email)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| match e {
+                    sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+                        AuthError::EmailAlreadyExists
+                    }
+                    e => AuthError::Database(e),
+                })?;
+
+            if updated.rows_affected() == 0 {
+                return Err(AuthError::UserNotFound);
+            }
+
+            sqlx::query("UPDATE refresh_tokens SET revoked = true WHERE user_id = $1 AND NOT revoked")
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(AuthError::Database)?;
+
+            // Reset links were sent to the old address
+            sqlx::query(
+                "UPDATE password_reset_tokens SET used = true, used_at = NOW()
+                 WHERE user_id = $1 AND NOT used"
+            )
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AuthError::Database)?;
+
+            tx.commit().await.map_err(AuthError::Database)?;
+
+            tracing::info!("User {} changed their email", user_id);
+        }
+
+        self.get_user_info(user_id).await
+    }
+
+    /// Change the password of a signed-in user. Every refresh token is revoked, so other
+    /// devices have to sign in again with the new password.
+    pub async fn change_password(
+        &self,
+        user_id: Uuid,
+        request: ChangePasswordRequest,
+    ) -> Result<MessageResponse, AuthError> {
+        let user = self.get_user_by_id(user_id).await?;
+
+        if !verify_password(&request.current_password, &user.password_hash)? {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        self.validate_new_password(&request.new_password)?;
+        if verify_password(&request.new_password, &user.password_hash)? {
+            return Err(AuthError::PasswordValidation(
+                "New password must be different from the current password".to_string(),
+            ));
+        }
+
+        let password_hash = hash_password(&request.new_password)?;
+        let mut tx = self.db.begin().await.map_err(AuthError::Database)?;
+
+        sqlx::query("UPDATE users SET password_hash = $2, updated_at = NOW() WHERE id = $1")
+            .bind(user_id)
+            .bind(&password_hash)
+            .execute(&mut *tx)
+            .await
+            .map_err(AuthError::Database)?;
+
+        sqlx::query("UPDATE refresh_tokens SET revoked = true WHERE user_id = $1 AND NOT revoked")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AuthError::Database)?;
+
+        // A pending reset link must not undo the change
+        sqlx::query(
+            "UPDATE password_reset_tokens SET used = true, used_at = NOW()
+             WHERE user_id = $1 AND NOT used"
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(AuthError::Database)?;
+
+        tx.commit().await.map_err(AuthError::Database)?;
+
+        tracing::info!("User {} changed their password", user_id);
+
+        Ok(MessageResponse {
+            message: "Password changed successfully".to_string(),
+        })
+    }
+
+    /// Email a single-use, time-limited reset link. Earlier links stop working. The response
+    /// is the same whether or not the account exists, and is returned before the account is
+    /// looked up so its timing doesn't tell either.
+    pub async fn forgot_password(&self, request: ForgotPasswordRequest) -> Result<MessageResponse, AuthError> {
+        let service = self.clone();
+        let email = request.email.trim().to_string();
+
+        tokio::spawn(async move {
+            if let Err(e) = service.send_reset_link(&email).await {
+                tracing::error!("Failed to handle password reset request: {}", e);
+            }
+        });
+
+        Ok(MessageResponse {
+            message: RESET_REQUESTED_MESSAGE.to_string(),
+        })
+    }
+
+    async fn send_reset_link(&self, email: &str) -> Result<(), AuthError> {
+        let user = match self.get_user_by_email(email).await? {
+            Some(user) => user,
+            None => {
+                tracing::info!("Password reset requested for unknown email");
+                return Ok(());
+            }
+        };
+
+        let token = generate_reset_token();
+        let expires_at = Utc::now() + Duration::minutes(RESET_TOKEN_EXPIRY_MINUTES);
+        let mut tx = self.db.begin().await.map_err(AuthError::Database)?;
+
+        sqlx::query(
+            "UPDATE password_reset_tokens SET used = true, used_at = NOW()
+             WHERE user_id = $1 AND NOT used"
+        )
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(AuthError::Database)?;
+
+        sqlx::query(
+            "INSERT INTO password_reset_tokens (user_id, token_hash, expires_at)
+             VALUES ($1, $2, $3)"
+        )
+        .bind(user.id)
+        .bind(hash_reset_token(&token))
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(AuthError::Database)?;
+
+        tx.commit().await.map_err(AuthError::Database)?;
+
+        let name = user.email.split('@').next().unwrap_or_default();
+        let message = format!(
+            "We received a request to reset your password. Use this link within {} minutes to choose a new one: {}\n\nIf you didn't ask for this, you can ignore this email.",
+            RESET_TOKEN_EXPIRY_MINUTES,
+            reset_link(&self.public_base_url, &token)
+        );
+
+        if let Err(e) = self
+            .email_service
+            .send_message_email(&user.email, name, "Reset your AI Coach password", &message)
+            .await
+        {
+            tracing::error!("Failed to send password reset email to user {}: {}", user.id, e);
+        }
+
+        Ok(())
+    }
+
+    /// Set a new password with an emailed reset token. The token is consumed and every
+    /// refresh token is revoked.
+    pub async fn reset_password(&self, request: ResetPasswordRequest) -> Result<MessageResponse, AuthError> {
+        self.validate_new_password(&request.new_password)?;
+        let password_hash = hash_password(&request.new_password)?;
+
+        let mut tx = self.db.begin().await.map_err(AuthError::Database)?;
+
+        let reset_token = sqlx::query_as::<_, PasswordResetToken>(
+            "SELECT id, user_id, token_hash, expires_at, used, used_at
+             FROM password_reset_tokens
+             WHERE token_hash = $1
+             FOR UPDATE"
+        )
+        .bind(hash_reset_token(request.token.trim()))
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AuthError::Database)?
+        .ok_or(AuthError::InvalidToken)?;
+
+        if reset_token.used.unwrap_or(false) {
+            return Err(AuthError::InvalidToken);
+        }
+        if reset_token.expires_at <= Utc::now() {
+            return Err(AuthError::TokenExpired);
+        }
+
+        sqlx::query("UPDATE password_reset_tokens SET used = true, used_at = NOW() WHERE id = $1")
+            .bind(reset_token.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AuthError::Database)?;
+
+        sqlx::query("UPDATE users SET password_hash = $2, updated_at = NOW() WHERE id = $1")
+            .bind(reset_token.user_id)
+            .bind(&password_hash)
+            .execute(&mut *tx)
+            .await
+            .map_err(AuthError::Database)?;
+
+        sqlx::query("UPDATE refresh_tokens SET revoked = true WHERE user_id = $1 AND NOT revoked")
+            .bind(reset_token.user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AuthError::Database)?;
+
+        tx.commit().await.map_err(AuthError::Database)?;
+
+        tracing::info!("User {} reset their password", reset_token.user_id);
+
+        Ok(MessageResponse {
+            message: "Password reset successfully".to_string(),
+        })
+    }
+
+    /// List users for admins, newest first, optionally filtered by email and role
+    pub async fn list_users(&self, params: UserListParams) -> Result<UserListResponse, AuthError> {
+        let (page, limit) = page_bounds(params.page, params.limit);
+        let search = params
+            .search
+            .map(|search| search.trim().to_string())
+            .filter(|search| !search.is_empty())
+            .map(|search| format!("%{}%", escape_like(&search)));
+
+        let rows = sqlx::query_as::<_, UserWithRole>(
+            "SELECT u.id, u.email, COALESCE(r.role, 'athlete') AS role, u.created_at, u.updated_at,
+                    COUNT(*) OVER () AS total_count
+             FROM users u LEFT JOIN user_roles r ON r.user_id = u.id
+             WHERE ($1::TEXT IS NULL OR u.email ILIKE $1)
+               AND ($2::TEXT IS NULL OR COALESCE(r.role, 'athlete') = $2)
+             ORDER BY u.created_at DESC, u.id
+             LIMIT $3 OFFSET $4"
+        )
+        .bind(search.as_deref())
+        .bind(params.role.as_ref().map(UserRole::as_str))
+        .bind(i64::from(limit))
+        .bind(i64::from(page - 1) * i64::from(limit))
+        .fetch_all(&self.db)
+        .await
+        .map_err(AuthError::Database)?;
+
+        // A page past the end has no rows to carry the window count
+        let total = match rows.first() {
+            Some(row) => row.total_count.unwrap_or_default(),
+            None => sqlx::query_scalar::<_, i64>(
+                "SELECT COUNT(*)
+                 FROM users u LEFT JOIN user_roles r ON r.user_id = u.id
+                 WHERE ($1::TEXT IS NULL OR u.email ILIKE $1)
+                   AND ($2::TEXT IS NULL OR COALESCE(r.role, 'athlete') = $2)"
+            )
+            .bind(search.as_deref())
+            .bind(params.role.as_ref().map(UserRole::as_str))
+            .fetch_one(&self.db)
+            .await
+            .map_err(AuthError::Database)?,
+        };
+
+        Ok(UserListResponse {
+            users: rows.into_iter().map(UserInfo::from).collect(),
+            total,
+            page,
+            limit,
+        })
+    }
+
+    /// Change a user's role on behalf of an admin and record it in the audit log. The user's
+    /// refresh tokens are revoked, since they carry the old role. Admins can't demote
+    /// themselves or the last remaining admin.
+    pub async fn change_user_role(
+        &self,
+        admin_id: Uuid,
+        user_id: Uuid,
+        role: UserRole,
+    ) -> Result<UserInfo, AuthError> {
+        let current = self.get_user_info(user_id).await?;
+        if current.role == role {
+            return Ok(current);
+        }
+
+        let mut tx = self.db.begin().await.map_err(AuthError::Database)?;
+
+        // Lock the admin rows so concurrent demotions can't together remove every admin
+        let admins: Vec<Uuid> =
+            sqlx::query_scalar("SELECT user_id FROM user_roles WHERE role = 'admin' FOR UPDATE")
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(AuthError::Database)?;
+
+        if role != UserRole::Admin && admins.contains(&user_id) {
+            if admin_id == user_id {
+                return Err(AuthError::RoleChangeConflict(
+                    "admins can't remove their own admin role".to_string(),
+                ));
+            }
+            if admins.len() == 1 {
+                return Err(AuthError::RoleChangeConflict(
+                    "at least one admin must remain".to_string(),
+                ));
+            }
+        }
+
+        sqlx::query(
+            "INSERT INTO user_roles (user_id, role) VALUES ($1, $2)
+             ON CONFLICT (user_id) DO UPDATE SET role = $2, updated_at = NOW()"
+        )
+        .bind(user_id)
+        .bind(role.as_str())
+        .execute(&mut *tx)
+        .await
+        .map_err(AuthError::Database)?;
+
+        sqlx::query(
+            "INSERT INTO admin_audit_log (admin_id, target_user_id, action, old_value, new_value)
+             VALUES ($1, $2, 'update_role', $3, $4)"
+        )
+        .bind(admin_id)
+        .bind(user_id)
+        .bind(current.role.as_str())
+        .bind(role.as_str())
+        .execute(&mut *tx)
+        .await
+        .map_err(AuthError::Database)?;
+
+        sqlx::query("UPDATE refresh_tokens SET revoked = true WHERE user_id = $1 AND NOT revoked")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AuthError::Database)?;
+
+        tx.commit().await.map_err(AuthError::Database)?;
+
+        tracing::info!(
+            "Admin {} changed role of user {} from {} to {}",
+            admin_id,
+            user_id,
+            current.role.as_str(),
+            role.as_str()
+        );
+
+        self.get_user_info(user_id).await
+    }
+
     // Private helper methods
 
+    async fn get_user_by_id(&self, user_id: Uuid) -> Result<User, AuthError> {
+        sqlx::query_as::<_, User>(
+            "SELECT id, email, password_hash, created_at, updated_at FROM users WHERE id = $1"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(AuthError::Database)?
+        .ok_or(AuthError::UserNotFound)
+    }
+
+    fn validate_new_password(&self, password: &str) -> Result<(), AuthError> {
+        validate_password_strength(password, &self.password_policy)
+            .map_err(|e| AuthError::PasswordValidation(e.to_string()))
+    }
+
     async fn get_user_by_email(&self, email: &str) -> Result<Option<User>, AuthError> {
         let user = sqlx::query_as::<_, User>(
             "SELECT id, email, password_hash, created_at, updated_at FROM users WHERE email = $1"
@@ -301,4 +781,52 @@ impl AuthService {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Link in the reset email that opens the app's reset form
+fn reset_link(base_url: &str, token: &str) -> String {
+    format!("{}/reset-password?token={}", base_url, token)
+}
+
+/// Page number (from 1) and page size for the admin user list
+fn page_bounds(page: Option<u32>, limit: Option<u32>) -> (u32, u32) {
+    (
+        page.unwrap_or(1).max(1),
+        limit.unwrap_or(DEFAULT_USERS_PAGE_SIZE).clamp(1, MAX_USERS_PAGE_SIZE),
+    )
+}
+
+/// Escape LIKE wildcards so a search matches them literally
+fn escape_like(search: &str) -> String {
+    search
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_bounds() {
+        assert_eq!(page_bounds(None, None), (1, DEFAULT_USERS_PAGE_SIZE));
+        assert_eq!(page_bounds(Some(0), Some(0)), (1, 1));
+        assert_eq!(page_bounds(Some(3), Some(500)), (3, MAX_USERS_PAGE_SIZE));
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("jane.doe"), "jane.doe");
+        assert_eq!(escape_like("100%_sure"), "100\\%\\_sure");
+        assert_eq!(escape_like("a\\b"), "a\\\\b");
+    }
+
+    #[test]
+    fn test_reset_link() {
+        assert_eq!(
+            reset_link("https://ai-coach.app", "abc123"),
+            "https://ai-coach.app/reset-password?token=abc123"
+        );
+    }
+}
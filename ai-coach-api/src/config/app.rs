@@ -1,5 +1,9 @@
 use anyhow::Result;
 use std::env;
+use std::net::IpAddr;
+
+use crate::models::DataSource;
+use crate::services::wearable_provider::OAuthCredentials;
 
 #[derive(Debug, Clone)]
 pub struct AppConfig {
@@ -8,9 +12,19 @@ pub struct AppConfig {
     pub environment: String,
     pub log_level: String,
     pub jwt_secret: String,
+    pub redis_url: Option<String>,
+    pub rate_limit_enabled: bool,
+    /// Reverse proxies whose `X-Forwarded-For`/`X-Real-IP` headers are trusted
+    pub trusted_proxies: Vec<IpAddr>,
     pub oura_client_id: Option<String>,
     pub oura_client_secret: Option<String>,
     pub oura_redirect_uri: Option<String>,
+    pub whoop_client_id: Option<String>,
+    pub whoop_client_secret: Option<String>,
+    pub whoop_redirect_uri: Option<String>,
+    pub polar_client_id: Option<String>,
+    pub polar_client_secret: Option<String>,
+    pub polar_redirect_uri: Option<String>,
 }
 
 impl AppConfig {
@@ -24,21 +38,47 @@ impl AppConfig {
         let log_level = env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string());
         let jwt_secret = env::var("JWT_SECRET")
             .unwrap_or_else(|_| "your-secret-key-change-in-production".to_string());
+        let redis_url = env::var("REDIS_URL").ok();
+        let rate_limit_enabled = env::var("RATE_LIMIT_ENABLED")
+            .map(|v| v != "false" && v != "0")
+            .unwrap_or(true);
+        let trusted_proxies = env::var("TRUSTED_PROXIES")
+            .map(|v| parse_ip_list(&v))
+            .unwrap_or_default();
 
         // Oura OAuth configuration (optional)
         let oura_client_id = env::var("OURA_CLIENT_ID").ok();
         let oura_client_secret = env::var("OURA_CLIENT_SECRET").ok();
         let oura_redirect_uri = env::var("OURA_REDIRECT_URI").ok();
 
+        // Whoop OAuth configuration (optional)
+        let whoop_client_id = env::var("WHOOP_CLIENT_ID").ok();
+        let whoop_client_secret = env::var("WHOOP_CLIENT_SECRET").ok();
+        let whoop_redirect_uri = env::var("WHOOP_REDIRECT_URI").ok();
+
+        // Polar AccessLink OAuth configuration (optional)
+        let polar_client_id = env::var("POLAR_CLIENT_ID").ok();
+        let polar_client_secret = env::var("POLAR_CLIENT_SECRET").ok();
+        let polar_redirect_uri = env::var("POLAR_REDIRECT_URI").ok();
+
         Ok(AppConfig {
             host,
             port,
             environment,
             log_level,
             jwt_secret,
+            redis_url,
+            rate_limit_enabled,
+            trusted_proxies,
             oura_client_id,
             oura_client_secret,
             oura_redirect_uri,
+            whoop_client_id,
+            whoop_client_secret,
+            whoop_redirect_uri,
+            polar_client_id,
+            polar_client_secret,
+            polar_redirect_uri,
         })
     }
 
@@ -53,4 +93,36 @@ impl AppConfig {
     pub fn server_address(&self) -> String {
         format!("{}:{}", self.host, self.port)
     }
-}
\ No newline at end of file
+
+    /// OAuth credentials for a wearable provider, if all three settings are present
+    pub fn wearable_credentials(&self, source: DataSource) -> Option<OAuthCredentials> {
+        let (client_id, client_secret, redirect_uri) = match source {
+            DataSource::Oura => (&self.oura_client_id, &self.oura_client_secret, &self.oura_redirect_uri),
+            DataSource::Whoop => (&self.whoop_client_id, &self.whoop_client_secret, &self.whoop_redirect_uri),
+            DataSource::Polar => (&self.polar_client_id, &self.polar_client_secret, &self.polar_redirect_uri),
+            _ => return None,
+        };
+
+        Some(OAuthCredentials {
+            client_id: client_id.clone()?,
+            client_secret: client_secret.clone()?,
+            redirect_uri: redirect_uri.clone()?,
+        })
+    }
+}
+
+/// Parse a comma-separated list of IP addresses, skipping invalid entries
+fn parse_ip_list(value: &str) -> Vec<IpAddr> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| match entry.parse() {
+            Ok(ip) => Some(ip),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid TRUSTED_PROXIES entry: {}", entry);
+                None
+            }
+        })
+        .collect()
+}
@@ -1,5 +1,11 @@
 use ai_coach::api::routes::create_routes;
 use ai_coach::config::{AppConfig, DatabaseConfig, run_migrations};
+use ai_coach::services::{
+    BackgroundJobService, GoalService, NotificationScheduler, NotificationService, TrainingAdjustmentConfig,
+    TrainingAdjustmentScheduler, TrainingAnalysisService, WearableIntegrationService, WearableProviderRegistry, WearableSyncConfig, WearableSyncScheduler,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
 use tokio::net::TcpListener;
 use tracing::{info, instrument};
 use tracing_subscriber;
@@ -20,8 +26,45 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Run migrations
     run_migrations(&db).await?;
 
+    // Background schedulers run once per process; they claim work in Postgres so replicas don't overlap
+    let training_analysis_service = TrainingAnalysisService::new(db.clone(), app_config.redis_url.clone())?;
+    let notification_scheduler = Arc::new(NotificationScheduler::new(
+        Arc::new(NotificationService::new(db.clone())),
+        Arc::new(training_analysis_service),
+        Arc::new(GoalService::new(db.clone())),
+        db.clone(),
+    ));
+    notification_scheduler.clone().start();
+
+    // Job workers lease from the shared Postgres queue, so every replica runs one pool
+    let background_job_service = Arc::new(BackgroundJobService::new(db.clone(), app_config.redis_url.clone())?);
+    background_job_service.start().await?;
+
+    // Wearable sync for every provider whose OAuth credentials are configured
+    let wearable_integration_service = WearableIntegrationService::new(
+        db.clone(),
+        WearableProviderRegistry::from_config(&app_config)?,
+    );
+    Arc::new(WearableSyncScheduler::new(
+        db.clone(),
+        wearable_integration_service.clone(),
+        WearableSyncConfig::from_env(),
+        background_job_service.clone(),
+    ))
+    .start();
+
+    // Daily automatic training adjustments
+    Arc::new(TrainingAdjustmentScheduler::new(db.clone(), TrainingAdjustmentConfig::from_env())).start();
+
     // Create the application routes
-    let app = create_routes(db, &app_config.jwt_secret, &app_config);
+    let app = create_routes(
+        db,
+        &app_config.jwt_secret,
+        &app_config,
+        background_job_service,
+        notification_scheduler,
+        wearable_integration_service,
+    );
 
     // Start the server
     let listener = TcpListener::bind(&app_config.server_address()).await?;
@@ -29,7 +72,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Health check available at http://{}/health", app_config.server_address());
     info!("Authentication endpoints available at http://{}/api/auth", app_config.server_address());
 
-    axum::serve(listener, app).await?;
+    // Peer addresses are needed to rate limit anonymous clients
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
 
     Ok(())
 }
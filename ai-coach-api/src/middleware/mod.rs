@@ -1,11 +1,13 @@
 pub mod rate_limiting;
 
 pub use rate_limiting::{
+    MemoryRateLimitStore,
     RateLimitConfig,
-    RateLimiter,
+    RateLimitDecision,
     RateLimitProfiles,
-    create_rate_limiting_layer,
-    create_user_rate_limiting_layer,
+    RateLimitStore,
+    RateLimiter,
+    RedisRateLimitStore,
+    RouteClass,
     rate_limit_middleware,
-    user_rate_limit_middleware,
 };
\ No newline at end of file
@@ -1,16 +1,30 @@
+use anyhow::Result;
+use async_trait::async_trait;
 use axum::{
-    extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
     middleware::Next,
-    response::{Json, Response},
+    response::{IntoResponse, Json, Response},
 };
 use serde::Serialize;
 use std::collections::HashMap;
-use std::net::IpAddr;
-use std::sync::{Arc, RwLock};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use tower::ServiceBuilder;
-use tower_http::limit::RequestBodyLimitLayer;
+use tracing::warn;
+
+use crate::auth::{extract_bearer_token, JwtService};
+
+static RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+static RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+static RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+static RATELIMIT_POLICY: HeaderName = HeaderName::from_static("ratelimit-policy");
+
+/// Buckets idle for this long are full again and can be dropped
+const IDLE_BUCKET_TTL: Duration = Duration::from_secs(3600);
+
+/// Idle buckets are swept at most this often, once the map is large
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Serialize)]
 pub struct RateLimitError {
@@ -19,238 +33,657 @@ pub struct RateLimitError {
     pub retry_after: u64,
 }
 
-#[derive(Debug, Clone)]
+/// Token bucket parameters: `burst_size` tokens, refilled at `requests_per_minute`
+#[derive(Debug, Clone, PartialEq)]
 pub struct RateLimitConfig {
     pub requests_per_minute: u32,
-    pub requests_per_hour: u32,
     pub burst_size: u32,
-    pub window_size: Duration,
 }
 
 impl Default for RateLimitConfig {
     fn default() -> Self {
         Self {
             requests_per_minute: 60,
-            requests_per_hour: 1000,
             burst_size: 10,
-            window_size: Duration::from_secs(60),
         }
     }
 }
 
-#[derive(Debug)]
-struct RateLimitEntry {
-    requests: Vec<Instant>,
-    last_reset: Instant,
+impl RateLimitConfig {
+    fn refill_per_second(&self) -> f64 {
+        self.requests_per_minute as f64 / 60.0
+    }
+
+    /// Seconds needed to refill `tokens` tokens
+    fn seconds_to_refill(&self, tokens: f64) -> f64 {
+        if self.requests_per_minute == 0 {
+            return f64::INFINITY;
+        }
+        tokens.max(0.0) / self.refill_per_second()
+    }
 }
 
-impl RateLimitEntry {
-    fn new() -> Self {
-        Self {
-            requests: Vec::new(),
-            last_reset: Instant::now(),
+/// Budget a request is charged against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteClass {
+    Auth,
+    Upload,
+    MlTraining,
+    Read,
+    Write,
+}
+
+impl RouteClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteClass::Auth => "auth",
+            RouteClass::Upload => "upload",
+            RouteClass::MlTraining => "ml_training",
+            RouteClass::Read => "read",
+            RouteClass::Write => "write",
         }
     }
 
-    fn add_request(&mut self, now: Instant, window_size: Duration) {
-        // Remove old requests outside the window
-        self.requests.retain(|&request_time| now.duration_since(request_time) < window_size);
+    /// Classify a request by its full path, e.g. `/api/v1/training/upload`
+    pub fn classify(method: &Method, path: &str) -> Self {
+        let path = path.trim_end_matches('/');
+
+        if path.starts_with("/api/v1/auth/") || path.starts_with("/api/auth/") {
+            RouteClass::Auth
+        } else if *method == Method::POST && (path.ends_with("/upload") || path.ends_with("/recovery/import")) {
+            RouteClass::Upload
+        } else if *method == Method::POST && path.ends_with("/models/train") {
+            RouteClass::MlTraining
+        } else if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+            RouteClass::Read
+        } else {
+            RouteClass::Write
+        }
+    }
+}
+
+/// Outcome of charging one request to a bucket
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the bucket is full again
+    pub reset_after: u64,
+    /// Seconds until the next request would be allowed, when denied
+    pub retry_after: Option<u64>,
+}
+
+impl RateLimitDecision {
+    fn from_bucket(config: &RateLimitConfig, tokens: f64, allowed: bool) -> Self {
+        let capacity = config.burst_size as f64;
 
-        // Add current request
-        self.requests.push(now);
+        Self {
+            allowed,
+            limit: config.burst_size,
+            remaining: tokens.floor().max(0.0) as u32,
+            reset_after: finite_seconds(config.seconds_to_refill(capacity - tokens)),
+            retry_after: (!allowed).then(|| finite_seconds(config.seconds_to_refill(1.0 - tokens)).max(1)),
+        }
     }
+}
 
-    fn request_count(&self, now: Instant, window_size: Duration) -> usize {
-        self.requests.iter()
-            .filter(|&&request_time| now.duration_since(request_time) < window_size)
-            .count()
+fn finite_seconds(seconds: f64) -> u64 {
+    if seconds.is_finite() {
+        seconds.ceil() as u64
+    } else {
+        u64::MAX
     }
 }
 
-#[derive(Clone)]
-pub struct RateLimiter {
-    config: RateLimitConfig,
-    store: Arc<RwLock<HashMap<String, RateLimitEntry>>>,
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
 }
 
-impl RateLimiter {
-    pub fn new(config: RateLimitConfig) -> Self {
+impl TokenBucket {
+    fn new(config: &RateLimitConfig, now: Instant) -> Self {
         Self {
-            config,
-            store: Arc::new(RwLock::new(HashMap::new())),
+            tokens: config.burst_size as f64,
+            last_refill: now,
         }
     }
 
-    pub fn check_rate_limit(&self, key: &str) -> Result<(), (StatusCode, Json<RateLimitError>)> {
-        let now = Instant::now();
-        let mut store = self.store.write().unwrap();
-
-        let entry = store.entry(key.to_string()).or_insert_with(RateLimitEntry::new);
-
-        // Check minute limit
-        let minute_count = entry.request_count(now, Duration::from_secs(60));
-        if minute_count >= self.config.requests_per_minute as usize {
-            return Err((
-                StatusCode::TOO_MANY_REQUESTS,
-                Json(RateLimitError {
-                    error_code: "RATE_LIMIT_EXCEEDED".to_string(),
-                    message: "Too many requests per minute".to_string(),
-                    retry_after: 60,
-                }),
-            ));
+    fn try_acquire(&mut self, config: &RateLimitConfig, now: Instant) -> RateLimitDecision {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * config.refill_per_second()).min(config.burst_size as f64);
+        self.last_refill = now;
+
+        let allowed = self.tokens >= 1.0;
+        if allowed {
+            self.tokens -= 1.0;
         }
 
-        // Check hour limit
-        let hour_count = entry.request_count(now, Duration::from_secs(3600));
-        if hour_count >= self.config.requests_per_hour as usize {
-            return Err((
-                StatusCode::TOO_MANY_REQUESTS,
-                Json(RateLimitError {
-                    error_code: "RATE_LIMIT_EXCEEDED".to_string(),
-                    message: "Too many requests per hour".to_string(),
-                    retry_after: 3600,
-                }),
-            ));
+        RateLimitDecision::from_bucket(config, self.tokens, allowed)
+    }
+}
+
+/// Where bucket state lives
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Take one token from the bucket at `key`
+    async fn acquire(&self, key: &str, config: &RateLimitConfig) -> Result<RateLimitDecision>;
+}
+
+#[derive(Default)]
+struct MemoryBuckets {
+    buckets: HashMap<String, TokenBucket>,
+    last_sweep: Option<Instant>,
+}
+
+/// Per-process buckets, used when Redis is not configured
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    state: Mutex<MemoryBuckets>,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn acquire_at(&self, key: &str, config: &RateLimitConfig, now: Instant) -> RateLimitDecision {
+        let mut state = self.state.lock().unwrap();
+
+        // Keep the map from growing without bound, without scanning it on every request
+        let sweep_due = state
+            .last_sweep
+            .map_or(true, |last| now.saturating_duration_since(last) >= SWEEP_INTERVAL);
+        if state.buckets.len() > 10_000 && sweep_due {
+            state.buckets.retain(|_, bucket| now.saturating_duration_since(bucket.last_refill) < IDLE_BUCKET_TTL);
+            state.last_sweep = Some(now);
         }
 
-        // Add the current request
-        entry.add_request(now, self.config.window_size);
+        state
+            .buckets
+            .entry(key.to_string())
+            .or_insert_with(|| TokenBucket::new(config, now))
+            .try_acquire(config, now)
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn acquire(&self, key: &str, config: &RateLimitConfig) -> Result<RateLimitDecision> {
+        Ok(self.acquire_at(key, config, Instant::now()))
+    }
+}
+
+/// Refill and take a token atomically; returns {allowed, tokens * 1000}
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local refill_per_ms = tonumber(ARGV[2])
+local ttl_ms = tonumber(ARGV[3])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+local tokens = tonumber(state[1])
+local ts = tonumber(state[2])
+if tokens == nil then
+    tokens = capacity
+    ts = now
+end
+
+tokens = math.min(capacity, tokens + math.max(0, now - ts) * refill_per_ms)
+local allowed = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', now)
+redis.call('PEXPIRE', KEYS[1], ttl_ms)
+return {allowed, math.floor(tokens * 1000)}
+"#;
+
+/// Buckets shared by every replica through Redis
+pub struct RedisRateLimitStore {
+    client: redis::Client,
+    connection: tokio::sync::Mutex<Option<redis::aio::MultiplexedConnection>>,
+    script: redis::Script,
+}
 
-        Ok(())
+impl RedisRateLimitStore {
+    pub fn new(redis_url: &str) -> Result<Self> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+            connection: tokio::sync::Mutex::new(None),
+            script: redis::Script::new(TOKEN_BUCKET_SCRIPT),
+        })
     }
 
-    pub fn cleanup_old_entries(&self) {
-        let now = Instant::now();
-        let mut store = self.store.write().unwrap();
+    async fn connection(&self) -> Result<redis::aio::MultiplexedConnection> {
+        let mut connection = self.connection.lock().await;
+        if let Some(conn) = connection.as_ref() {
+            return Ok(conn.clone());
+        }
 
-        store.retain(|_, entry| {
-            now.duration_since(entry.last_reset) < Duration::from_secs(7200) // Keep entries for 2 hours
-        });
+        let conn = self.client.get_multiplexed_tokio_connection().await?;
+        *connection = Some(conn.clone());
+        Ok(conn)
     }
 }
 
-/// Extract client identifier for rate limiting
-fn get_client_key(headers: &HeaderMap, remote_addr: Option<std::net::SocketAddr>) -> String {
-    // Try to get real IP from headers (for reverse proxy scenarios)
-    if let Some(forwarded_for) = headers.get("x-forwarded-for") {
-        if let Ok(ip_str) = forwarded_for.to_str() {
-            // Take the first IP from the comma-separated list
-            if let Some(first_ip) = ip_str.split(',').next() {
-                return first_ip.trim().to_string();
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn acquire(&self, key: &str, config: &RateLimitConfig) -> Result<RateLimitDecision> {
+        let mut conn = self.connection().await?;
+        let ttl_ms = (config.seconds_to_refill(config.burst_size as f64).min(IDLE_BUCKET_TTL.as_secs_f64()) * 1000.0)
+            .ceil()
+            .max(1000.0) as u64;
+
+        let result: redis::RedisResult<(i64, i64)> = self
+            .script
+            .key(key)
+            .arg(config.burst_size)
+            .arg(config.refill_per_second() / 1000.0)
+            .arg(ttl_ms)
+            .invoke_async(&mut conn)
+            .await;
+
+        let (allowed, tokens_milli) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                // Reconnect on the next request
+                *self.connection.lock().await = None;
+                return Err(e.into());
             }
+        };
+
+        Ok(RateLimitDecision::from_bucket(config, tokens_milli as f64 / 1000.0, allowed == 1))
+    }
+}
+
+/// Different rate limiting configs for different endpoint types
+#[derive(Debug, Clone)]
+pub struct RateLimitProfiles {
+    pub auth: RateLimitConfig,
+    pub upload: RateLimitConfig,
+    pub ml_training: RateLimitConfig,
+    pub read: RateLimitConfig,
+    pub write: RateLimitConfig,
+}
+
+impl Default for RateLimitProfiles {
+    fn default() -> Self {
+        Self {
+            auth: Self::auth(),
+            upload: Self::upload(),
+            ml_training: Self::ml_training(),
+            read: Self::read(),
+            write: Self::write(),
+        }
+    }
+}
+
+impl RateLimitProfiles {
+    /// Conservative limits for auth endpoints, keyed by IP before login
+    pub fn auth() -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_minute: 10,
+            burst_size: 5,
+        }
+    }
+
+    /// File and video uploads
+    pub fn upload() -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_minute: 10,
+            burst_size: 5,
+        }
+    }
+
+    /// Model training is expensive, allow only a few runs per hour
+    pub fn ml_training() -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_minute: 1,
+            burst_size: 3,
+        }
+    }
+
+    /// Standard limits for reads
+    pub fn read() -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_minute: 120,
+            burst_size: 30,
         }
     }
 
-    if let Some(real_ip) = headers.get("x-real-ip") {
-        if let Ok(ip_str) = real_ip.to_str() {
-            return ip_str.to_string();
+    /// Standard limits for other writes
+    pub fn write() -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_minute: 60,
+            burst_size: 15,
         }
     }
 
-    // Fall back to remote address
-    if let Some(addr) = remote_addr {
-        return addr.ip().to_string();
+    pub fn for_class(&self, class: RouteClass) -> &RateLimitConfig {
+        match class {
+            RouteClass::Auth => &self.auth,
+            RouteClass::Upload => &self.upload,
+            RouteClass::MlTraining => &self.ml_training,
+            RouteClass::Read => &self.read,
+            RouteClass::Write => &self.write,
+        }
     }
+}
 
-    // Ultimate fallback
-    "unknown".to_string()
+/// Token bucket rate limiter keyed by user (JWT `sub`) or client IP, per route class
+#[derive(Clone)]
+pub struct RateLimiter {
+    profiles: RateLimitProfiles,
+    store: Arc<dyn RateLimitStore>,
+    fallback: Arc<MemoryRateLimitStore>,
+    jwt_service: JwtService,
+    trusted_proxies: Arc<Vec<IpAddr>>,
 }
 
-/// Rate limiting middleware
-pub async fn rate_limit_middleware(
-    State(rate_limiter): State<RateLimiter>,
-    headers: HeaderMap,
-    mut req: Request,
-    next: Next,
-) -> Result<Response, (StatusCode, Json<RateLimitError>)> {
-    let remote_addr = req.extensions().get::<std::net::SocketAddr>().copied();
-    let client_key = get_client_key(&headers, remote_addr);
+impl RateLimiter {
+    pub fn new(profiles: RateLimitProfiles, store: Arc<dyn RateLimitStore>, jwt_service: JwtService) -> Self {
+        Self {
+            profiles,
+            store,
+            fallback: Arc::new(MemoryRateLimitStore::new()),
+            jwt_service,
+            trusted_proxies: Arc::new(Vec::new()),
+        }
+    }
+
+    /// Honour forwarded client addresses only on connections from these proxies
+    pub fn with_trusted_proxies(mut self, trusted_proxies: Vec<IpAddr>) -> Self {
+        self.trusted_proxies = Arc::new(trusted_proxies);
+        self
+    }
+
+    /// Share limits through Redis when `redis_url` is set, otherwise keep them in memory
+    pub fn from_redis_url(redis_url: Option<&str>, jwt_secret: &str) -> Result<Self> {
+        let store: Arc<dyn RateLimitStore> = match redis_url {
+            Some(url) => Arc::new(RedisRateLimitStore::new(url)?),
+            None => Arc::new(MemoryRateLimitStore::new()),
+        };
 
-    // Check rate limit
-    rate_limiter.check_rate_limit(&client_key)?;
+        Ok(Self::new(RateLimitProfiles::default(), store, JwtService::new(jwt_secret)))
+    }
+
+    /// Charge a request to its bucket. If the shared store is unreachable, limit per replica instead of failing.
+    pub async fn check(&self, class: RouteClass, identity: &str) -> RateLimitDecision {
+        let config = self.profiles.for_class(class);
+        let key = format!("ratelimit:{}:{}", class.as_str(), identity);
+
+        match self.store.acquire(&key, config).await {
+            Ok(decision) => decision,
+            Err(e) => {
+                warn!("Rate limit store unavailable, using local buckets: {}", e);
+                self.fallback.acquire_at(&key, config, Instant::now())
+            }
+        }
+    }
 
-    // Continue to next middleware/handler
-    Ok(next.run(req).await)
+    /// `user:<sub>` for a valid access token, `ip:<address>` otherwise
+    pub fn identity(&self, headers: &HeaderMap, remote_addr: Option<SocketAddr>) -> String {
+        let user_id = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| extract_bearer_token(value).ok())
+            .and_then(|token| self.jwt_service.validate_token(token).ok())
+            .map(|claims| claims.sub);
+
+        match user_id {
+            Some(sub) => format!("user:{}", sub),
+            None => format!("ip:{}", get_client_ip(headers, remote_addr, &self.trusted_proxies)),
+        }
+    }
 }
 
-/// Per-user rate limiting middleware (requires authentication)
-pub async fn user_rate_limit_middleware(
-    State(rate_limiter): State<RateLimiter>,
-    headers: HeaderMap,
-    mut req: Request,
-    next: Next,
-) -> Result<Response, (StatusCode, Json<RateLimitError>)> {
-    // Try to extract user ID from headers/claims
-    let user_key = if let Some(auth_header) = headers.get("authorization") {
-        // In a real implementation, you'd decode the JWT here
-        // For now, use the full authorization header as key
-        format!("user:{}", auth_header.to_str().unwrap_or("unknown"))
-    } else {
-        // Fall back to IP-based limiting
-        let remote_addr = req.extensions().get::<std::net::SocketAddr>().copied();
-        get_client_key(&headers, remote_addr)
+/// Extract client IP for rate limiting. Forwarded headers are client-controlled,
+/// so they are only read when the connection comes from a trusted proxy.
+fn get_client_ip(headers: &HeaderMap, remote_addr: Option<SocketAddr>, trusted_proxies: &[IpAddr]) -> String {
+    let Some(peer) = remote_addr.map(|addr| addr.ip()) else {
+        return "unknown".to_string();
     };
 
-    // Check rate limit
-    rate_limiter.check_rate_limit(&user_key)?;
+    if !trusted_proxies.contains(&peer) {
+        return peer.to_string();
+    }
 
-    // Continue to next middleware/handler
-    Ok(next.run(req).await)
+    // Walk X-Forwarded-For from the nearest hop and take the first address not added by one of our proxies
+    if let Some(forwarded_for) = headers.get("x-forwarded-for").and_then(|value| value.to_str().ok()) {
+        let client = forwarded_for
+            .split(',')
+            .rev()
+            .filter_map(|hop| hop.trim().parse::<IpAddr>().ok())
+            .find(|ip| !trusted_proxies.contains(ip));
+        if let Some(client) = client {
+            return client.to_string();
+        }
+    }
+
+    if let Some(real_ip) = headers
+        .get("x-real-ip")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<IpAddr>().ok())
+    {
+        return real_ip.to_string();
+    }
+
+    peer.to_string()
 }
 
-/// Create rate limiting layer for specific routes
-pub fn create_rate_limiting_layer(config: RateLimitConfig) {
-    let _rate_limiter = RateLimiter::new(config);
-    // Implementation simplified for now to fix compilation
+/// `RateLimit-*` headers (IETF draft) describing the bucket the request was charged to
+fn rate_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision, config: &RateLimitConfig) {
+    let window = finite_seconds(config.seconds_to_refill(config.burst_size as f64));
+
+    headers.insert(RATELIMIT_LIMIT.clone(), HeaderValue::from(decision.limit));
+    headers.insert(RATELIMIT_REMAINING.clone(), HeaderValue::from(decision.remaining));
+    headers.insert(RATELIMIT_RESET.clone(), HeaderValue::from(decision.reset_after));
+    if let Ok(policy) = HeaderValue::from_str(&format!("{};w={}", decision.limit, window)) {
+        headers.insert(RATELIMIT_POLICY.clone(), policy);
+    }
+    if let Some(retry_after) = decision.retry_after {
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    }
 }
 
-/// Create user-specific rate limiting layer
-pub fn create_user_rate_limiting_layer(config: RateLimitConfig) {
-    let _rate_limiter = RateLimiter::new(config);
-    // Implementation simplified for now to fix compilation
+/// Rate limiting middleware; the health check is never limited
+pub async fn rate_limit_middleware(
+    State(rate_limiter): State<RateLimiter>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if req.uri().path() == "/health" {
+        return next.run(req).await;
+    }
+
+    let class = RouteClass::classify(req.method(), req.uri().path());
+    let remote_addr = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| *addr);
+    let identity = rate_limiter.identity(req.headers(), remote_addr);
+
+    let decision = rate_limiter.check(class, &identity).await;
+    let config = rate_limiter.profiles.for_class(class);
+
+    let mut response = if decision.allowed {
+        next.run(req).await
+    } else {
+        let retry_after = decision.retry_after.unwrap_or(1);
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(RateLimitError {
+                error_code: "RATE_LIMIT_EXCEEDED".to_string(),
+                message: format!("Too many {} requests", class.as_str().replace('_', " ")),
+                retry_after,
+            }),
+        )
+            .into_response()
+    };
+
+    rate_limit_headers(response.headers_mut(), &decision, config);
+    response
 }
 
-/// Different rate limiting configs for different endpoint types
-pub struct RateLimitProfiles;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl RateLimitProfiles {
-    /// Conservative limits for auth endpoints
-    pub fn auth() -> RateLimitConfig {
+    fn config() -> RateLimitConfig {
         RateLimitConfig {
-            requests_per_minute: 10,
-            requests_per_hour: 100,
+            requests_per_minute: 60,
             burst_size: 3,
-            window_size: Duration::from_secs(60),
         }
     }
 
-    /// Standard limits for API endpoints
-    pub fn api() -> RateLimitConfig {
-        RateLimitConfig {
-            requests_per_minute: 60,
-            requests_per_hour: 1000,
-            burst_size: 10,
-            window_size: Duration::from_secs(60),
+    #[test]
+    fn test_bucket_allows_burst_then_denies() {
+        let store = MemoryRateLimitStore::new();
+        let now = Instant::now();
+
+        for remaining in [2, 1, 0] {
+            let decision = store.acquire_at("key", &config(), now);
+            assert!(decision.allowed);
+            assert_eq!(decision.remaining, remaining);
         }
+
+        let denied = store.acquire_at("key", &config(), now);
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after, Some(1));
+        assert_eq!(denied.reset_after, 3);
+
+        // Other keys have their own bucket
+        assert!(store.acquire_at("other", &config(), now).allowed);
     }
 
-    /// Higher limits for file upload endpoints
-    pub fn upload() -> RateLimitConfig {
-        RateLimitConfig {
-            requests_per_minute: 30,
-            requests_per_hour: 200,
-            burst_size: 5,
-            window_size: Duration::from_secs(60),
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let store = MemoryRateLimitStore::new();
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            store.acquire_at("key", &config(), now);
         }
+        assert!(!store.acquire_at("key", &config(), now).allowed);
+
+        let later = now + Duration::from_millis(1500);
+        let decision = store.acquire_at("key", &config(), later);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+
+        // Refill never exceeds the burst size
+        let much_later = now + Duration::from_secs(600);
+        assert_eq!(store.acquire_at("key", &config(), much_later).remaining, 2);
     }
 
-    /// Very conservative limits for admin endpoints
-    pub fn admin() -> RateLimitConfig {
-        RateLimitConfig {
-            requests_per_minute: 5,
-            requests_per_hour: 50,
-            burst_size: 2,
-            window_size: Duration::from_secs(60),
+    #[test]
+    fn test_classify_routes() {
+        assert_eq!(RouteClass::classify(&Method::POST, "/api/v1/auth/login"), RouteClass::Auth);
+        assert_eq!(RouteClass::classify(&Method::POST, "/api/auth/refresh"), RouteClass::Auth);
+        assert_eq!(RouteClass::classify(&Method::POST, "/api/v1/training/upload"), RouteClass::Upload);
+        assert_eq!(RouteClass::classify(&Method::POST, "/api/v1/vision/upload/"), RouteClass::Upload);
+        assert_eq!(RouteClass::classify(&Method::POST, "/api/v1/recovery/import"), RouteClass::Upload);
+        assert_eq!(RouteClass::classify(&Method::POST, "/api/v1/ml/models/train"), RouteClass::MlTraining);
+        assert_eq!(RouteClass::classify(&Method::GET, "/api/v1/ml/models/versions"), RouteClass::Read);
+        assert_eq!(RouteClass::classify(&Method::PUT, "/api/v1/goals/1"), RouteClass::Write);
+    }
+
+    #[test]
+    fn test_identity_prefers_jwt_subject() {
+        let jwt_service = JwtService::new("test-secret");
+        let limiter = RateLimiter::new(
+            RateLimitProfiles::default(),
+            Arc::new(MemoryRateLimitStore::new()),
+            jwt_service.clone(),
+        );
+        let user_id = uuid::Uuid::new_v4();
+        let token = jwt_service
+            .create_access_token(user_id, "athlete@example.com", crate::auth::UserRole::Athlete)
+            .unwrap();
+
+        let peer: SocketAddr = "198.51.100.4:5000".parse().unwrap();
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(limiter.identity(&headers, Some(peer)), "ip:198.51.100.4");
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", token)).unwrap());
+        assert_eq!(limiter.identity(&headers, Some(peer)), format!("user:{}", user_id));
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer forged"));
+        assert_eq!(limiter.identity(&headers, Some(peer)), "ip:198.51.100.4");
+    }
+
+    #[test]
+    fn test_identity_ignores_spoofed_forwarded_headers() {
+        let limiter = RateLimiter::new(
+            RateLimitProfiles::default(),
+            Arc::new(MemoryRateLimitStore::new()),
+            JwtService::new("test-secret"),
+        )
+        .with_trusted_proxies(vec!["10.0.0.1".parse().unwrap()]);
+        let attacker: SocketAddr = "198.51.100.4:5000".parse().unwrap();
+        let proxy: SocketAddr = "10.0.0.1:443".parse().unwrap();
+
+        // An untrusted peer can't pick its own bucket
+        for spoofed in ["203.0.113.7", "203.0.113.8, 10.0.0.1"] {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-forwarded-for", HeaderValue::from_str(spoofed).unwrap());
+            headers.insert("x-real-ip", HeaderValue::from_static("192.0.2.1"));
+            assert_eq!(limiter.identity(&headers, Some(attacker)), "ip:198.51.100.4");
         }
+
+        // Behind a trusted proxy, the hop the proxy saw is used, not the client-supplied prefix
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.8, 198.51.100.4"));
+        assert_eq!(limiter.identity(&headers, Some(proxy)), "ip:198.51.100.4");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", HeaderValue::from_static("198.51.100.9"));
+        assert_eq!(limiter.identity(&headers, Some(proxy)), "ip:198.51.100.9");
+        assert_eq!(limiter.identity(&HeaderMap::new(), Some(proxy)), "ip:10.0.0.1");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_idle_buckets_swept_on_interval() {
+        let store = MemoryRateLimitStore::new();
+        let now = Instant::now();
+        let bucket_count = || store.state.lock().unwrap().buckets.len();
+
+        for i in 0..10_001 {
+            store.acquire_at(&format!("idle{}", i), &config(), now);
+        }
+
+        // A large map of idle buckets is swept
+        let later = now + IDLE_BUCKET_TTL;
+        store.acquire_at("fresh", &config(), later);
+        assert_eq!(bucket_count(), 1);
+
+        // ...but not again until the sweep interval has passed
+        for i in 0..10_001 {
+            store.acquire_at(&format!("stale{}", i), &config(), now);
+        }
+        store.acquire_at("next", &config(), later + Duration::from_secs(1));
+        assert_eq!(bucket_count(), 10_003);
+
+        store.acquire_at("last", &config(), later + SWEEP_INTERVAL);
+        assert_eq!(bucket_count(), 3);
+    }
+
+    #[test]
+    fn test_rate_limit_headers() {
+        let decision = RateLimitDecision::from_bucket(&config(), 0.25, false);
+        let mut headers = HeaderMap::new();
+        rate_limit_headers(&mut headers, &decision, &config());
+
+        assert_eq!(headers["ratelimit-limit"], "3");
+        assert_eq!(headers["ratelimit-remaining"], "0");
+        assert_eq!(headers["ratelimit-reset"], "3");
+        assert_eq!(headers["ratelimit-policy"], "3;w=3");
+        assert_eq!(headers[header::RETRY_AFTER], "1");
+    }
+}
@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Calendar subscription feed database model; the token itself is only returned on creation
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CalendarFeed {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub include_events: bool,
+    pub include_workouts: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_accessed_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Create (or rotate) the user's calendar feed
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateCalendarFeedRequest {
+    pub include_events: Option<bool>,
+    pub include_workouts: Option<bool>,
+}
+
+/// Newly created feed with its subscription URL
+#[derive(Debug, Serialize)]
+pub struct CalendarFeedCreatedResponse {
+    pub feed: CalendarFeed,
+    pub feed_url: String,
+}
+
+/// Outcome of importing an `.ics` race calendar
+#[derive(Debug, Default, Serialize)]
+pub struct CalendarImportSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
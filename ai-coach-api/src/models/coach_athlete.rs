@@ -0,0 +1,211 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// State of a coach-athlete relationship
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CoachAthleteStatus {
+    Pending,
+    Active,
+    Declined,
+    Revoked,
+}
+
+impl CoachAthleteStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CoachAthleteStatus::Pending => "pending",
+            CoachAthleteStatus::Active => "active",
+            CoachAthleteStatus::Declined => "declined",
+            CoachAthleteStatus::Revoked => "revoked",
+        }
+    }
+}
+
+/// What an athlete lets a coach do with their data
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CoachScope {
+    /// Sessions, PMC, goals and plans
+    ReadTraining,
+    /// HRV, sleep and resting heart rate
+    ReadRecovery,
+    /// Create and update training plans
+    EditPlans,
+}
+
+impl CoachScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CoachScope::ReadTraining => "read_training",
+            CoachScope::ReadRecovery => "read_recovery",
+            CoachScope::EditPlans => "edit_plans",
+        }
+    }
+}
+
+/// Coach-athlete relationship database model
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CoachAthlete {
+    pub id: Uuid,
+    pub coach_id: Uuid,
+    pub athlete_id: Uuid,
+    pub status: String,
+    pub can_read_training: bool,
+    pub can_read_recovery: bool,
+    pub can_edit_plans: bool,
+    pub invited_at: DateTime<Utc>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl CoachAthlete {
+    pub fn is_active(&self) -> bool {
+        self.status == CoachAthleteStatus::Active.as_str()
+    }
+
+    pub fn has_scope(&self, scope: CoachScope) -> bool {
+        self.is_active()
+            && match scope {
+                CoachScope::ReadTraining => self.can_read_training,
+                CoachScope::ReadRecovery => self.can_read_recovery,
+                CoachScope::EditPlans => self.can_edit_plans,
+            }
+    }
+
+    pub fn scopes(&self) -> Vec<CoachScope> {
+        [CoachScope::ReadTraining, CoachScope::ReadRecovery, CoachScope::EditPlans]
+            .into_iter()
+            .filter(|scope| match scope {
+                CoachScope::ReadTraining => self.can_read_training,
+                CoachScope::ReadRecovery => self.can_read_recovery,
+                CoachScope::EditPlans => self.can_edit_plans,
+            })
+            .collect()
+    }
+}
+
+/// Relationship as shown to either side, with the other party's email
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CoachAthleteSummary {
+    pub id: Uuid,
+    pub coach_id: Uuid,
+    pub coach_email: String,
+    /// Withheld from coaches until the athlete accepts, so the roster doesn't reveal which
+    /// invited emails have an account
+    pub athlete_id: Option<Uuid>,
+    pub athlete_email: String,
+    pub status: String,
+    pub can_read_training: bool,
+    pub can_read_recovery: bool,
+    pub can_edit_plans: bool,
+    pub invited_at: DateTime<Utc>,
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+/// Scope flags stored on a relationship
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoachPermissions {
+    pub can_read_training: bool,
+    pub can_read_recovery: bool,
+    pub can_edit_plans: bool,
+}
+
+impl CoachPermissions {
+    pub fn from_scopes(scopes: &[CoachScope]) -> Self {
+        Self {
+            can_read_training: scopes.contains(&CoachScope::ReadTraining),
+            can_read_recovery: scopes.contains(&CoachScope::ReadRecovery),
+            can_edit_plans: scopes.contains(&CoachScope::EditPlans),
+        }
+    }
+}
+
+impl Default for CoachPermissions {
+    /// Read-only access to training data
+    fn default() -> Self {
+        Self::from_scopes(&[CoachScope::ReadTraining])
+    }
+}
+
+/// Coach invites an athlete by email
+#[derive(Debug, Clone, Deserialize)]
+pub struct InviteAthleteRequest {
+    pub athlete_email: String,
+    /// Requested scopes, defaults to read_training
+    pub scopes: Option<Vec<CoachScope>>,
+}
+
+/// Reply to an invitation, the same whether or not the email has an account
+#[derive(Debug, Clone, Serialize)]
+pub struct CoachInvitationResponse {
+    pub athlete_email: String,
+    pub message: String,
+}
+
+/// Athlete changes what a coach may access
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateCoachScopesRequest {
+    pub scopes: Vec<CoachScope>,
+}
+
+/// Audit record of a coach acting on an athlete's data
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CoachAccessAudit {
+    pub id: Uuid,
+    pub coach_id: Uuid,
+    pub athlete_id: Uuid,
+    pub scope: String,
+    pub action: String,
+    pub resource_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relationship(status: CoachAthleteStatus, permissions: CoachPermissions) -> CoachAthlete {
+        CoachAthlete {
+            id: Uuid::new_v4(),
+            coach_id: Uuid::new_v4(),
+            athlete_id: Uuid::new_v4(),
+            status: status.as_str().to_string(),
+            can_read_training: permissions.can_read_training,
+            can_read_recovery: permissions.can_read_recovery,
+            can_edit_plans: permissions.can_edit_plans,
+            invited_at: Utc::now(),
+            responded_at: None,
+            revoked_at: None,
+        }
+    }
+
+    #[test]
+    fn test_scopes_require_active_relationship() {
+        let permissions = CoachPermissions::from_scopes(&[CoachScope::ReadTraining, CoachScope::EditPlans]);
+
+        let active = relationship(CoachAthleteStatus::Active, permissions);
+        assert!(active.has_scope(CoachScope::ReadTraining));
+        assert!(active.has_scope(CoachScope::EditPlans));
+        assert!(!active.has_scope(CoachScope::ReadRecovery));
+        assert_eq!(active.scopes(), vec![CoachScope::ReadTraining, CoachScope::EditPlans]);
+
+        for status in [CoachAthleteStatus::Pending, CoachAthleteStatus::Declined, CoachAthleteStatus::Revoked] {
+            assert!(!relationship(status, permissions).has_scope(CoachScope::ReadTraining));
+        }
+    }
+
+    #[test]
+    fn test_default_permissions_are_read_only() {
+        assert_eq!(
+            CoachPermissions::default(),
+            CoachPermissions {
+                can_read_training: true,
+                can_read_recovery: false,
+                can_edit_plans: false,
+            }
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// File format of a data export
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Single dataset as a spreadsheet-friendly CSV file
+    Csv,
+    /// One JSON object per line, tagged with its dataset
+    Jsonl,
+    /// Zip archive with one CSV per dataset and a manifest
+    Zip,
+}
+
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Zip => "zip",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Jsonl => "application/x-ndjson",
+            ExportFormat::Zip => "application/zip",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(format: &str) -> anyhow::Result<Self> {
+        match format {
+            "csv" => Ok(ExportFormat::Csv),
+            "jsonl" => Ok(ExportFormat::Jsonl),
+            "zip" => Ok(ExportFormat::Zip),
+            other => Err(anyhow::anyhow!("Unknown export format: {}", other)),
+        }
+    }
+}
+
+/// Data that can be included in an export; recovery data is split into HRV, sleep and resting HR
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportDataset {
+    Sessions,
+    Pmc,
+    Goals,
+    Events,
+    Hrv,
+    Sleep,
+    RestingHr,
+    Predictions,
+}
+
+impl ExportDataset {
+    pub const ALL: [ExportDataset; 8] = [
+        ExportDataset::Sessions,
+        ExportDataset::Pmc,
+        ExportDataset::Goals,
+        ExportDataset::Events,
+        ExportDataset::Hrv,
+        ExportDataset::Sleep,
+        ExportDataset::RestingHr,
+        ExportDataset::Predictions,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportDataset::Sessions => "sessions",
+            ExportDataset::Pmc => "pmc",
+            ExportDataset::Goals => "goals",
+            ExportDataset::Events => "events",
+            ExportDataset::Hrv => "hrv",
+            ExportDataset::Sleep => "sleep",
+            ExportDataset::RestingHr => "resting_hr",
+            ExportDataset::Predictions => "predictions",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportDataset {
+    type Err = anyhow::Error;
+
+    fn from_str(dataset: &str) -> anyhow::Result<Self> {
+        ExportDataset::ALL
+            .into_iter()
+            .find(|candidate| candidate.as_str() == dataset)
+            .ok_or_else(|| anyhow::anyhow!("Unknown export dataset: {}", dataset))
+    }
+}
+
+/// Lifecycle of a data export
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportStatus {
+    Pending,
+    Processing,
+    Completed,
+    Failed,
+    Expired,
+}
+
+impl ExportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportStatus::Pending => "pending",
+            ExportStatus::Processing => "processing",
+            ExportStatus::Completed => "completed",
+            ExportStatus::Failed => "failed",
+            ExportStatus::Expired => "expired",
+        }
+    }
+}
+
+/// Data export database model
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DataExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub format: String,
+    pub datasets: Vec<String>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub status: String,
+    pub job_id: Option<Uuid>,
+    pub storage_key: Option<String>,
+    pub file_name: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl DataExport {
+    pub fn export_format(&self) -> anyhow::Result<ExportFormat> {
+        self.format.parse()
+    }
+
+    pub fn export_datasets(&self) -> anyhow::Result<Vec<ExportDataset>> {
+        self.datasets.iter().map(|dataset| dataset.parse()).collect()
+    }
+
+    /// Completed and not yet past its expiry
+    pub fn is_downloadable(&self, now: DateTime<Utc>) -> bool {
+        self.status == ExportStatus::Completed.as_str()
+            && self.storage_key.is_some()
+            && self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+/// Request to export analytics data
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateExportRequest {
+    pub format: ExportFormat,
+    /// Defaults to every dataset
+    pub datasets: Option<Vec<ExportDataset>>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+}
+
+/// Export status returned to the client
+#[derive(Debug, Clone, Serialize)]
+pub struct DataExportResponse {
+    pub export_id: Uuid,
+    pub status: String,
+    pub format: String,
+    pub datasets: Vec<String>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub file_name: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub download_url: Option<String>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<DataExport> for DataExportResponse {
+    fn from(export: DataExport) -> Self {
+        let download_url = export
+            .is_downloadable(Utc::now())
+            .then(|| format!("/api/v1/analytics/export/download/{}", export.id));
+
+        Self {
+            export_id: export.id,
+            status: export.status,
+            format: export.format,
+            datasets: export.datasets,
+            start_date: export.start_date,
+            end_date: export.end_date,
+            file_name: export.file_name,
+            size_bytes: export.size_bytes,
+            download_url,
+            error_message: export.error_message,
+            created_at: export.created_at,
+            completed_at: export.completed_at,
+            expires_at: export.expires_at,
+        }
+    }
+}
@@ -121,6 +121,7 @@ pub struct GoalProgress {
     pub date: NaiveDate,
     pub note: Option<String>,
     pub milestone_achieved: Option<String>,
+    pub automatic: bool, // Measured from training and recovery data rather than logged
     pub created_at: DateTime<Utc>,
 }
 
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::DataSource;
+
+/// Kind of health platform export being imported
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthImportFormat {
+    /// Zipped `export.xml` from the Health app (or the bare XML file)
+    AppleHealth,
+    /// Zipped SQLite database exported from Android Health Connect
+    HealthConnect,
+}
+
+impl HealthImportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthImportFormat::AppleHealth => "apple_health",
+            HealthImportFormat::HealthConnect => "health_connect",
+        }
+    }
+
+    /// Source recorded on the imported rows
+    pub fn data_source(&self) -> DataSource {
+        match self {
+            HealthImportFormat::AppleHealth => DataSource::AppleHealth,
+            HealthImportFormat::HealthConnect => DataSource::HealthConnect,
+        }
+    }
+}
+
+impl std::str::FromStr for HealthImportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(format: &str) -> anyhow::Result<Self> {
+        match format {
+            "apple_health" => Ok(HealthImportFormat::AppleHealth),
+            "health_connect" => Ok(HealthImportFormat::HealthConnect),
+            other => Err(anyhow::anyhow!("Unknown import format: {}", other)),
+        }
+    }
+}
+
+/// Lifecycle of a health import
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthImportStatus {
+    /// The file is still being received
+    Uploading,
+    /// Uploaded and waiting for a worker
+    Pending,
+    Parsing,
+    Importing,
+    Completed,
+    Failed,
+}
+
+impl HealthImportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthImportStatus::Uploading => "uploading",
+            HealthImportStatus::Pending => "pending",
+            HealthImportStatus::Parsing => "parsing",
+            HealthImportStatus::Importing => "importing",
+            HealthImportStatus::Completed => "completed",
+            HealthImportStatus::Failed => "failed",
+        }
+    }
+}
+
+/// Health import database model
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct HealthImport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub format: String,
+    pub status: String,
+    pub job_id: Option<Uuid>,
+    pub file_name: Option<String>,
+    pub storage_key: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub progress_percent: i16,
+    pub records_read: i32,
+    pub hrv_imported: i32,
+    pub sleep_imported: i32,
+    pub resting_hr_imported: i32,
+    pub duplicates_skipped: i32,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl HealthImport {
+    pub fn import_format(&self) -> anyhow::Result<HealthImportFormat> {
+        self.format.parse()
+    }
+}
+
+/// Query parameters for starting an import
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateHealthImportQuery {
+    pub format: HealthImportFormat,
+}
+
+/// Import status returned to the client
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthImportResponse {
+    pub import_id: Uuid,
+    pub format: String,
+    pub status: String,
+    pub file_name: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub progress_percent: i16,
+    pub records_read: i32,
+    pub hrv_imported: i32,
+    pub sleep_imported: i32,
+    pub resting_hr_imported: i32,
+    pub duplicates_skipped: i32,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<HealthImport> for HealthImportResponse {
+    fn from(import: HealthImport) -> Self {
+        Self {
+            import_id: import.id,
+            format: import.format,
+            status: import.status,
+            file_name: import.file_name,
+            size_bytes: import.size_bytes,
+            progress_percent: import.progress_percent,
+            records_read: import.records_read,
+            hrv_imported: import.hrv_imported,
+            sleep_imported: import.sleep_imported,
+            resting_hr_imported: import.resting_hr_imported,
+            duplicates_skipped: import.duplicates_skipped,
+            error_message: import.error_message,
+            created_at: import.created_at,
+            started_at: import.started_at,
+            completed_at: import.completed_at,
+        }
+    }
+}
@@ -20,6 +20,11 @@ pub mod keypoint;
 pub mod recovery_data;
 pub mod recovery_analysis;
 pub mod training_recovery_settings;
+pub mod data_export;
+pub mod coach_athlete;
+pub mod calendar_feed;
+pub mod health_import;
+pub mod push_subscription;
 
 pub use user::*;
 pub use athlete_profile::*;
@@ -45,4 +50,9 @@ pub use keypoint::{
 };
 pub use recovery_data::*;
 pub use recovery_analysis::*;
-pub use training_recovery_settings::*;
\ No newline at end of file
+pub use training_recovery_settings::*;
+pub use data_export::*;
+pub use coach_athlete::*;
+pub use calendar_feed::*;
+pub use health_import::*;
+pub use push_subscription::*;
\ No newline at end of file
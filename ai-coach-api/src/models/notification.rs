@@ -30,6 +30,7 @@ pub enum NotificationType {
     WorkoutReminder,
     RestDayReminder,
     FtpTestSuggestion,
+    TrainingAdjustment,
 
     // Performance Alerts
     FitnessImprovement,
@@ -70,7 +71,7 @@ pub enum NotificationPriority {
     Critical,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, Hash)]
 #[sqlx(type_name = "delivery_channel", rename_all = "snake_case")]
 pub enum DeliveryChannel {
     InApp,
@@ -79,16 +80,74 @@ pub enum DeliveryChannel {
     Sms, // Future implementation
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+impl DeliveryChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryChannel::InApp => "in_app",
+            DeliveryChannel::Email => "email",
+            DeliveryChannel::WebPush => "web_push",
+            DeliveryChannel::Sms => "sms",
+        }
+    }
+}
+
+impl sqlx::postgres::PgHasArrayType for DeliveryChannel {
+    fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("_delivery_channel")
+    }
+}
+
+impl std::str::FromStr for DeliveryChannel {
+    type Err = anyhow::Error;
+
+    fn from_str(channel: &str) -> anyhow::Result<Self> {
+        match channel {
+            "in_app" => Ok(DeliveryChannel::InApp),
+            "email" => Ok(DeliveryChannel::Email),
+            "web_push" => Ok(DeliveryChannel::WebPush),
+            "sms" => Ok(DeliveryChannel::Sms),
+            other => Err(anyhow::anyhow!("Unknown delivery channel: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
 #[sqlx(type_name = "delivery_status", rename_all = "snake_case")]
 pub enum DeliveryStatus {
     Scheduled,
+    /// Claimed by a scheduler and being delivered
+    Sending,
     Sent,
     Delivered,
     Failed,
     Cancelled,
 }
 
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Scheduled => "scheduled",
+            DeliveryStatus::Sending => "sending",
+            DeliveryStatus::Sent => "sent",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed => "failed",
+            DeliveryStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Outcome of delivering a notification through one channel
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NotificationDelivery {
+    pub id: Uuid,
+    pub notification_id: Uuid,
+    pub user_id: Uuid,
+    pub channel: String,
+    pub status: String,
+    pub error_message: Option<String>,
+    pub attempted_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateNotificationRequest {
     pub user_id: Uuid,
@@ -101,7 +160,7 @@ pub struct CreateNotificationRequest {
     pub expires_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct NotificationPreferences {
     pub user_id: Uuid,
 
@@ -169,7 +228,7 @@ pub struct UpdateNotificationPreferencesRequest {
     pub batch_interval_minutes: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct NotificationTemplate {
     pub id: Uuid,
     pub notification_type: NotificationType,
@@ -182,7 +241,19 @@ pub struct NotificationTemplate {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Which of a user's sent notifications to list
+#[derive(Debug, Clone, Default)]
+pub struct NotificationFilter {
+    /// Category as stored, e.g. `health`
+    pub category: Option<String>,
+    /// Delivery status as stored, e.g. `delivered`
+    pub status: Option<String>,
+    pub unread_only: bool,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct NotificationBatch {
     pub id: Uuid,
     pub user_id: Uuid,
@@ -196,7 +267,7 @@ pub struct NotificationBatch {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
 #[sqlx(type_name = "batch_type", rename_all = "snake_case")]
 pub enum BatchType {
     Hourly,
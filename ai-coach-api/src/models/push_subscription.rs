@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Browser Web Push subscription database model
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PushSubscription {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub endpoint: String,
+    #[serde(skip_serializing)]
+    pub p256dh_key: String,
+    #[serde(skip_serializing)]
+    pub auth_secret: String,
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+    pub failure_count: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Keys of a `PushSubscription`, as serialized by `PushSubscription.toJSON()`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushSubscriptionKeys {
+    pub p256dh: String,
+    pub auth: String,
+}
+
+/// Register a browser's `PushSubscription` for the current device
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePushSubscriptionRequest {
+    pub endpoint: String,
+    /// Milliseconds since the epoch, when the browser knows it
+    pub expiration_time: Option<i64>,
+    pub keys: PushSubscriptionKeys,
+    pub device_name: Option<String>,
+}
+
+/// Public key browsers pass as `applicationServerKey` when subscribing
+#[derive(Debug, Serialize)]
+pub struct VapidPublicKeyResponse {
+    pub public_key: String,
+}
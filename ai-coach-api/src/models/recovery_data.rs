@@ -15,8 +15,9 @@ pub struct HrvReading {
     pub user_id: Uuid,
     pub measurement_date: NaiveDate,
     pub measurement_timestamp: DateTime<Utc>,
-    /// Root Mean Square of Successive Differences (ms) - primary HRV metric
-    pub rmssd: f64,
+    /// Root Mean Square of Successive Differences (ms) - primary HRV metric, `None` for
+    /// sources that only record SDNN
+    pub rmssd: Option<f64>,
     /// Standard Deviation of NN intervals (ms) - optional
     pub sdnn: Option<f64>,
     /// Percentage of successive NN intervals differing >50ms (%) - optional
@@ -104,6 +105,14 @@ pub struct WearableConnection {
     pub metadata: Option<sqlx::types::Json<serde_json::Value>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Last date fully synced by the background scheduler
+    pub sync_cursor: Option<NaiveDate>,
+    pub next_sync_at: DateTime<Utc>,
+    pub consecutive_failures: i32,
+    pub auth_failures: i32,
+    /// The provider keeps rejecting our tokens; the user must reconnect
+    pub needs_reauth: bool,
+    pub last_sync_error: Option<String>,
 }
 
 // ============================================================================
@@ -113,8 +122,9 @@ pub struct WearableConnection {
 /// Request to create HRV reading
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateHrvReadingRequest {
+    /// Required unless `sdnn` is given
     #[validate(range(min = 0.0, max = 200.0, message = "RMSSD must be between 0 and 200 ms"))]
-    pub rmssd: f64,
+    pub rmssd: Option<f64>,
 
     #[validate(range(min = 0.0, max = 200.0, message = "SDNN must be between 0 and 200 ms"))]
     pub sdnn: Option<f64>,
@@ -177,7 +187,7 @@ pub struct HrvReadingResponse {
     pub id: Uuid,
     pub measurement_date: NaiveDate,
     pub measurement_timestamp: DateTime<Utc>,
-    pub rmssd: f64,
+    pub rmssd: Option<f64>,
     pub sdnn: Option<f64>,
     pub pnn50: Option<f64>,
     pub source: String,
@@ -237,6 +247,8 @@ pub struct WearableConnectionResponse {
     pub connected_at: DateTime<Utc>,
     pub last_sync_at: Option<DateTime<Utc>>,
     pub is_active: bool,
+    pub needs_reauth: bool,
+    pub last_sync_error: Option<String>,
 }
 
 /// List of HRV readings with pagination info
@@ -344,6 +356,8 @@ impl From<WearableConnection> for WearableConnectionResponse {
             connected_at: conn.connected_at,
             last_sync_at: conn.last_sync_at,
             is_active: conn.is_active,
+            needs_reauth: conn.needs_reauth,
+            last_sync_error: conn.last_sync_error,
         }
     }
 }
@@ -380,13 +394,14 @@ impl Default for RecoveryDataQuery {
 // Data Source Enum
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum DataSource {
     Oura,
     Whoop,
     Manual,
     AppleHealth,
+    HealthConnect,
     Garmin,
     Polar,
     Fitbit,
@@ -399,6 +414,7 @@ impl DataSource {
             DataSource::Whoop => "whoop",
             DataSource::Manual => "manual",
             DataSource::AppleHealth => "apple_health",
+            DataSource::HealthConnect => "health_connect",
             DataSource::Garmin => "garmin",
             DataSource::Polar => "polar",
             DataSource::Fitbit => "fitbit",
@@ -406,6 +422,24 @@ impl DataSource {
     }
 }
 
+impl std::str::FromStr for DataSource {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> anyhow::Result<Self> {
+        match source {
+            "oura" => Ok(DataSource::Oura),
+            "whoop" => Ok(DataSource::Whoop),
+            "manual" => Ok(DataSource::Manual),
+            "apple_health" => Ok(DataSource::AppleHealth),
+            "health_connect" => Ok(DataSource::HealthConnect),
+            "garmin" => Ok(DataSource::Garmin),
+            "polar" => Ok(DataSource::Polar),
+            "fitbit" => Ok(DataSource::Fitbit),
+            other => Err(anyhow::anyhow!("Unknown data source: {}", other)),
+        }
+    }
+}
+
 impl std::fmt::Display for DataSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.as_str())
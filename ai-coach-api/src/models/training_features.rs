@@ -1,6 +1,17 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
+/// Version of the feature layout produced by [`TrainingFeatures::to_ndarray`].
+///
+/// Each version appends columns to the previous one, so a model trained on an
+/// older version can still be fed the prefix of the current feature vector.
+/// - 1: training load, goal event, seasonality and preferred workout types
+/// - 2: adds readiness score, HRV trend, sleep quality and RHR deviation
+pub const FEATURE_SCHEMA_VERSION: u32 = 2;
+
+/// Preferred workout types, one-hot encoded in this order
+const WORKOUT_TYPES: [&str; 5] = ["endurance", "threshold", "vo2max", "recovery", "strength"];
+
 /// Training features for machine learning model input
 /// Contains all relevant metrics for predicting optimal training load
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +42,22 @@ pub struct TrainingFeatures {
 
     /// Seasonal factors affecting training (0.0 to 1.0, considering weather, holidays, etc.)
     pub seasonal_factors: f32,
+
+    /// Recovery readiness score for the day (0-100, None if not calculated)
+    #[serde(default)]
+    pub readiness_score: Option<f32>,
+
+    /// HRV trend (1.0 = improving, 0.0 = stable, -1.0 = declining)
+    #[serde(default)]
+    pub hrv_trend: Option<f32>,
+
+    /// Sleep quality score of the previous night (0-100)
+    #[serde(default)]
+    pub sleep_quality: Option<f32>,
+
+    /// Resting heart rate deviation from baseline in percent (positive = elevated)
+    #[serde(default)]
+    pub rhr_deviation: Option<f32>,
 }
 
 /// Training load prediction from ML model
@@ -95,7 +122,7 @@ pub struct TrainingDataPoint {
     /// Performance outcome (subjective rating 1-10)
     pub performance_outcome: Option<f32>,
 
-    /// Recovery rating after workout (1-10)
+    /// Recovery rating after workout (1-10), taken from the next day's readiness score
     pub recovery_rating: Option<f32>,
 
     /// Date of the workout
@@ -115,11 +142,25 @@ impl TrainingFeatures {
             days_until_goal_event: None,
             preferred_workout_types: Vec::new(),
             seasonal_factors: 1.0,
+            readiness_score: None,
+            hrv_trend: None,
+            sleep_quality: None,
+            rhr_deviation: None,
         }
     }
 
     /// Convert features to ndarray for ML model input
     pub fn to_ndarray(&self) -> ndarray::Array1<f64> {
+        self.to_ndarray_for_schema(FEATURE_SCHEMA_VERSION)
+            .expect("current feature schema is always supported")
+    }
+
+    /// Convert features to ndarray using the layout of an older feature schema version
+    pub fn to_ndarray_for_schema(&self, version: u32) -> Option<ndarray::Array1<f64>> {
+        if !(1..=FEATURE_SCHEMA_VERSION).contains(&version) {
+            return None;
+        }
+
         let mut features = vec![
             self.current_ctl as f64,
             self.current_atl as f64,
@@ -132,16 +173,33 @@ impl TrainingFeatures {
         ];
 
         // Add preferred workout types as one-hot encoding
-        let workout_types = ["endurance", "threshold", "vo2max", "recovery", "strength"];
-        for workout_type in &workout_types {
+        for workout_type in &WORKOUT_TYPES {
             features.push(if self.preferred_workout_types.contains(&workout_type.to_string()) { 1.0 } else { 0.0 });
         }
 
-        ndarray::Array1::from(features)
+        if version >= 2 {
+            // Missing scores use -1 like days_until_goal_event; missing trends are neutral
+            features.push(self.readiness_score.unwrap_or(-1.0) as f64);
+            features.push(self.hrv_trend.unwrap_or(0.0) as f64);
+            features.push(self.sleep_quality.unwrap_or(-1.0) as f64);
+            features.push(self.rhr_deviation.unwrap_or(0.0) as f64);
+        }
+
+        Some(ndarray::Array1::from(features))
     }
 
     /// Get feature names for model interpretation
     pub fn feature_names() -> Vec<String> {
+        Self::feature_names_for_schema(FEATURE_SCHEMA_VERSION)
+            .expect("current feature schema is always supported")
+    }
+
+    /// Get feature names of a feature schema version, None if the version is unknown
+    pub fn feature_names_for_schema(version: u32) -> Option<Vec<String>> {
+        if !(1..=FEATURE_SCHEMA_VERSION).contains(&version) {
+            return None;
+        }
+
         let mut names = vec![
             "current_ctl".to_string(),
             "current_atl".to_string(),
@@ -154,12 +212,30 @@ impl TrainingFeatures {
         ];
 
         // Add preferred workout type feature names
-        let workout_types = ["endurance", "threshold", "vo2max", "recovery", "strength"];
-        for workout_type in &workout_types {
+        for workout_type in &WORKOUT_TYPES {
             names.push(format!("prefers_{}", workout_type));
         }
 
-        names
+        if version >= 2 {
+            names.extend([
+                "readiness_score".to_string(),
+                "hrv_trend".to_string(),
+                "sleep_quality".to_string(),
+                "rhr_deviation".to_string(),
+            ]);
+        }
+
+        Some(names)
+    }
+
+    /// Encode a recovery HRV trend label as a numeric feature, None without enough data
+    pub fn encode_hrv_trend(trend: &str) -> Option<f32> {
+        match trend {
+            "improving" => Some(1.0),
+            "stable" => Some(0.0),
+            "declining" => Some(-1.0),
+            _ => None,
+        }
     }
 }
 
@@ -161,7 +161,7 @@ pub enum Sport {
 impl Sport {
     pub fn from_string(s: &str) -> Self {
         match s.to_lowercase().as_str() {
-            "cycling" | "bike" | "bicycle" => Sport::Cycling,
+            "cycling" | "bike" | "bicycle" | "biking" | "ride" => Sport::Cycling,
             "running" | "run" => Sport::Running,
             "swimming" | "swim" => Sport::Swimming,
             "triathlon" | "tri" => Sport::Triathlon,
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
@@ -24,6 +24,54 @@ pub struct TrainingRecoverySettings {
     pub updated_at: DateTime<Utc>,
 }
 
+/// How strongly recovery changes planned workouts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdjustmentAggressiveness {
+    Conservative,
+    Moderate,
+    Aggressive,
+}
+
+impl TrainingRecoverySettings {
+    pub fn aggressiveness(&self) -> AdjustmentAggressiveness {
+        match self.adjustment_aggressiveness.as_str() {
+            "conservative" => AdjustmentAggressiveness::Conservative,
+            "aggressive" => AdjustmentAggressiveness::Aggressive,
+            _ => AdjustmentAggressiveness::Moderate,
+        }
+    }
+}
+
+/// Recovery-driven adjustment of a planned workout, suggested or applied automatically
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TrainingAdjustment {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub adjustment_date: NaiveDate,
+    pub recovery_score_id: Option<Uuid>,
+    pub plan_id: Option<Uuid>,
+    pub week_number: Option<i32>,
+    pub day_of_week: Option<i32>,
+    pub original_tss: Option<f64>,
+    pub recommended_tss: Option<f64>,
+    pub adjustment_applied: Option<bool>,
+    pub adjustment_type: Option<String>, // reduce_intensity, reduce_volume, swap_workout, rest_day, no_change
+    pub original_workout: Option<serde_json::Value>,
+    pub adjusted_workout: Option<serde_json::Value>,
+    pub reasoning: Option<String>,
+    pub automatic: bool,
+    pub applied_at: Option<DateTime<Utc>>,
+    pub reverted_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl TrainingAdjustment {
+    /// Applied and not yet undone
+    pub fn can_revert(&self) -> bool {
+        self.adjustment_applied.unwrap_or(false) && self.reverted_at.is_none() && self.plan_id.is_some()
+    }
+}
+
 // ============================================================================
 // Request DTOs
 // ============================================================================
@@ -71,6 +119,25 @@ pub struct TrainingRecoverySettingsResponse {
     pub allow_workout_swap: bool,
 }
 
+/// Entry in the athlete's adjustment history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingAdjustmentResponse {
+    pub id: Uuid,
+    pub adjustment_date: NaiveDate,
+    pub plan_id: Option<Uuid>,
+    pub adjustment_type: Option<String>,
+    pub applied: bool,
+    pub automatic: bool,
+    pub original_tss: Option<f64>,
+    pub recommended_tss: Option<f64>,
+    pub original_workout: Option<serde_json::Value>,
+    pub adjusted_workout: Option<serde_json::Value>,
+    pub reasoning: Option<String>,
+    pub applied_at: Option<DateTime<Utc>>,
+    pub reverted_at: Option<DateTime<Utc>>,
+    pub can_revert: bool,
+}
+
 // ============================================================================
 // Conversion Implementations
 // ============================================================================
@@ -90,6 +157,28 @@ impl From<TrainingRecoverySettings> for TrainingRecoverySettingsResponse {
     }
 }
 
+impl From<TrainingAdjustment> for TrainingAdjustmentResponse {
+    fn from(adjustment: TrainingAdjustment) -> Self {
+        let can_revert = adjustment.can_revert();
+        Self {
+            id: adjustment.id,
+            adjustment_date: adjustment.adjustment_date,
+            plan_id: adjustment.plan_id,
+            adjustment_type: adjustment.adjustment_type,
+            applied: adjustment.adjustment_applied.unwrap_or(false),
+            automatic: adjustment.automatic,
+            original_tss: adjustment.original_tss,
+            recommended_tss: adjustment.recommended_tss,
+            original_workout: adjustment.original_workout,
+            adjusted_workout: adjustment.adjusted_workout,
+            reasoning: adjustment.reasoning,
+            applied_at: adjustment.applied_at,
+            reverted_at: adjustment.reverted_at,
+            can_revert,
+        }
+    }
+}
+
 impl Default for TrainingRecoverySettings {
     fn default() -> Self {
         Self {
@@ -39,6 +39,17 @@ pub struct UpdateTrainingSession {
     pub distance_meters: Option<f64>,
 }
 
+/// Session logged on a client (e.g. the CLI), keyed by the client's ID so re-uploads update it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpsertTrainingSession {
+    pub id: Uuid,
+    pub date: NaiveDate,
+    pub session_type: Option<String>,
+    pub duration_seconds: Option<i32>,
+    pub distance_meters: Option<f64>,
+    pub notes: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SessionSummary {
     pub total_sessions: i64,
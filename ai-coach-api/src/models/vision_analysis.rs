@@ -62,6 +62,23 @@ impl std::fmt::Display for ExerciseType {
     }
 }
 
+impl From<&str> for ExerciseType {
+    fn from(value: &str) -> Self {
+        match value.trim().to_lowercase().replace('_', "-").as_str() {
+            "squat" => ExerciseType::Squat,
+            "deadlift" => ExerciseType::Deadlift,
+            "push-up" | "pushup" => ExerciseType::PushUp,
+            "running" => ExerciseType::Running,
+            "plank" => ExerciseType::Plank,
+            "lunge" => ExerciseType::Lunge,
+            "bench-press" | "benchpress" => ExerciseType::BenchPress,
+            "overhead-press" | "overheadpress" => ExerciseType::OverheadPress,
+            "pull-up" | "pullup" => ExerciseType::PullUp,
+            _ => ExerciseType::Other(value.to_string()),
+        }
+    }
+}
+
 /// Main vision analysis record
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct VisionAnalysis {
@@ -128,6 +145,8 @@ pub struct MovementIssue {
     pub issue_type: String,
     pub description: String,
     pub frames: Vec<i32>, // Frame numbers where issue occurs
+    #[serde(default)]
+    pub timestamps_ms: Vec<u64>, // Video timestamps matching `frames`
     pub confidence: f32,
 }
 
@@ -228,6 +247,11 @@ impl VisionAnalysis {
         }
     }
 
+    /// Key of the uploaded video in the storage backend
+    pub fn storage_key(&self) -> Option<&str> {
+        self.metadata.get("storage_key").and_then(|key| key.as_str())
+    }
+
     /// Convert to list item representation
     pub fn to_list_item(&self, overall_score: Option<f64>) -> VisionAnalysisListItem {
         VisionAnalysisListItem {
@@ -56,6 +56,53 @@ pub enum TestType {
     TimeTrial { distance_meters: Option<f64> },
 }
 
+/// Workout file formats structured sessions can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkoutFileFormat {
+    /// Zwift structured workout (XML, power as a fraction of FTP)
+    Zwo,
+    /// TrainerRoad/ERG course file with absolute watts
+    Erg,
+    /// TrainerRoad/ERG course file with percent of FTP
+    Mrc,
+    /// Garmin FIT workout file
+    Fit,
+}
+
+impl WorkoutFileFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkoutFileFormat::Zwo => "zwo",
+            WorkoutFileFormat::Erg => "erg",
+            WorkoutFileFormat::Mrc => "mrc",
+            WorkoutFileFormat::Fit => "fit",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            WorkoutFileFormat::Zwo => "application/xml",
+            WorkoutFileFormat::Erg | WorkoutFileFormat::Mrc => "text/plain; charset=utf-8",
+            WorkoutFileFormat::Fit => "application/vnd.ant.fit",
+        }
+    }
+}
+
+impl std::str::FromStr for WorkoutFileFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(format: &str) -> anyhow::Result<Self> {
+        match format.to_lowercase().as_str() {
+            "zwo" => Ok(WorkoutFileFormat::Zwo),
+            "erg" => Ok(WorkoutFileFormat::Erg),
+            "mrc" => Ok(WorkoutFileFormat::Mrc),
+            "fit" => Ok(WorkoutFileFormat::Fit),
+            other => Err(anyhow::anyhow!("Unknown workout file format: {}", other)),
+        }
+    }
+}
+
 /// Training zones with associated power/heart rate ranges
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrainingZone {
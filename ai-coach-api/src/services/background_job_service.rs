@@ -1,15 +1,19 @@
 use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{watch, RwLock};
 use tokio_cron_scheduler::{Job, JobScheduler};
-use tracing::{info, error};
+use tracing::{info, warn, error};
 use uuid::Uuid;
 
-use crate::services::{TrainingAnalysisService, TrainingSessionService};
-use crate::models::UpdateTrainingSession;
+use crate::services::{
+    DataExportService, GoalProgressEvaluator, HealthImportService, ModelTrainingService,
+    TrainingAnalysisService, TrainingSessionService,
+};
+use crate::models::{UpdateTrainingSession, FEATURE_SCHEMA_VERSION};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum JobType {
     ProcessTrainingFile {
         session_id: Uuid,
@@ -23,6 +27,52 @@ pub enum JobType {
     CleanupOldFiles {
         older_than_days: i32,
     },
+    GenerateDataExport {
+        export_id: Uuid,
+        user_id: Uuid,
+    },
+    ImportHealthData {
+        import_id: Uuid,
+        user_id: Uuid,
+    },
+    EvaluateGoals {
+        user_id: Uuid,
+    },
+    RetrainOutdatedModels,
+    RetrainUserModel {
+        user_id: Uuid,
+        schema_version: u32,
+    },
+}
+
+impl JobType {
+    /// Stable name stored alongside the serialized payload
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobType::ProcessTrainingFile { .. } => "process_training_file",
+            JobType::CalculatePMC { .. } => "calculate_pmc",
+            JobType::CleanupOldFiles { .. } => "cleanup_old_files",
+            JobType::GenerateDataExport { .. } => "generate_data_export",
+            JobType::ImportHealthData { .. } => "import_health_data",
+            JobType::EvaluateGoals { .. } => "evaluate_goals",
+            JobType::RetrainOutdatedModels => "retrain_outdated_models",
+            JobType::RetrainUserModel { .. } => "retrain_user_model",
+        }
+    }
+
+    /// Owner of the job, `None` for system jobs
+    pub fn user_id(&self) -> Option<Uuid> {
+        match self {
+            JobType::ProcessTrainingFile { user_id, .. } => Some(*user_id),
+            JobType::CalculatePMC { user_id, .. } => Some(*user_id),
+            JobType::CleanupOldFiles { .. } => None,
+            JobType::GenerateDataExport { user_id, .. } => Some(*user_id),
+            JobType::ImportHealthData { user_id, .. } => Some(*user_id),
+            JobType::EvaluateGoals { user_id } => Some(*user_id),
+            JobType::RetrainOutdatedModels => None,
+            JobType::RetrainUserModel { user_id, .. } => Some(*user_id),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -42,16 +92,144 @@ pub enum JobStatus {
     Pending,
     Running,
     Completed,
-    Failed,
+    Failed, // Permanent failure, e.g. a payload that can no longer be decoded
     Retrying,
+    DeadLetter, // Retries exhausted, needs manual attention
 }
 
-pub struct BackgroundJobService {
-    scheduler: Arc<RwLock<JobScheduler>>,
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Retrying => "retrying",
+            JobStatus::DeadLetter => "dead_letter",
+        }
+    }
+}
+
+impl std::str::FromStr for JobStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(status: &str) -> Result<Self> {
+        match status {
+            "pending" => Ok(JobStatus::Pending),
+            "running" => Ok(JobStatus::Running),
+            "completed" => Ok(JobStatus::Completed),
+            "failed" => Ok(JobStatus::Failed),
+            "retrying" => Ok(JobStatus::Retrying),
+            "dead_letter" => Ok(JobStatus::DeadLetter),
+            other => Err(anyhow!("Unknown job status: {}", other)),
+        }
+    }
+}
+
+/// Tuning for the persistent job queue
+#[derive(Debug, Clone)]
+pub struct JobQueueConfig {
+    pub worker_count: usize,
+    pub poll_interval: Duration,
+    pub heartbeat_interval: Duration,
+    pub lease_timeout: Duration, // Running jobs without a heartbeat for this long are requeued
+    pub max_retries: i32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for JobQueueConfig {
+    fn default() -> Self {
+        Self {
+            worker_count: 2,
+            poll_interval: Duration::from_secs(2),
+            heartbeat_interval: Duration::from_secs(15),
+            lease_timeout: Duration::from_secs(120),
+            max_retries: 5,
+            base_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl JobQueueConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            worker_count: std::env::var("BACKGROUND_JOB_WORKERS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.worker_count),
+            max_retries: std::env::var("BACKGROUND_JOB_MAX_RETRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_retries),
+            ..defaults
+        }
+    }
+
+    /// Delay before the next attempt after `retries` failed attempts: base * 2^retries, capped
+    pub fn retry_backoff(&self, retries: i32) -> Duration {
+        let factor = 2u32.saturating_pow(retries.clamp(0, 31) as u32);
+        self.base_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+}
+
+/// Row shape shared by the job queries
+struct JobRow {
+    id: Uuid,
+    payload: String,
+    status: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    started_at: Option<chrono::DateTime<chrono::Utc>>,
+    completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    error_message: Option<String>,
+    retries: i32,
+}
+
+impl TryFrom<JobRow> for BackgroundJob {
+    type Error = anyhow::Error;
+
+    fn try_from(row: JobRow) -> Result<Self> {
+        let job_type: JobType = serde_json::from_str(&row.payload)
+            .map_err(|e| anyhow!("Failed to decode payload of job {}: {}", row.id, e))?;
+
+        Ok(BackgroundJob {
+            id: row.id,
+            job_type,
+            status: row.status.parse()?,
+            created_at: row.created_at,
+            started_at: row.started_at,
+            completed_at: row.completed_at,
+            error_message: row.error_message,
+            retries: row.retries,
+        })
+    }
+}
+
+/// Everything a worker task needs to lease and execute jobs
+#[derive(Clone)]
+struct JobWorker {
+    db: PgPool,
     training_analysis_service: TrainingAnalysisService,
     training_session_service: TrainingSessionService,
-    db: PgPool,
-    jobs: Arc<RwLock<Vec<BackgroundJob>>>,
+    data_export_service: DataExportService,
+    health_import_service: HealthImportService,
+    goal_progress_evaluator: GoalProgressEvaluator,
+    model_training_service: ModelTrainingService,
+    config: JobQueueConfig,
+    worker_id: String,
+}
+
+/// Queue front end shared by the API routes. Routes only enqueue jobs; the workers and
+/// schedules are started by `start`, which the binary calls once per process.
+pub struct BackgroundJobService {
+    scheduler: RwLock<Option<JobScheduler>>,
+    worker: JobWorker,
+    shutdown: watch::Sender<bool>,
 }
 
 impl BackgroundJobService {
@@ -59,45 +237,75 @@ impl BackgroundJobService {
         db: PgPool,
         redis_url: Option<String>,
     ) -> Result<Self> {
-        let scheduler = tokio::runtime::Handle::current().block_on(async {
-            JobScheduler::new().await
-        }).map_err(|e| anyhow!("Failed to create job scheduler: {}", e))?;
-
         let training_analysis_service = TrainingAnalysisService::new(db.clone(), redis_url)?;
         let training_session_service = TrainingSessionService::new(db.clone());
+        let data_export_service = DataExportService::new(db.clone())?;
+        let health_import_service = HealthImportService::new(db.clone());
+        let goal_progress_evaluator = GoalProgressEvaluator::new(db.clone());
+        let model_training_service = ModelTrainingService::new(db.clone());
+
+        let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "ai-coach".to_string());
+        let (shutdown, _) = watch::channel(false);
 
         Ok(Self {
-            scheduler: Arc::new(RwLock::new(scheduler)),
-            training_analysis_service,
-            training_session_service,
-            db,
-            jobs: Arc::new(RwLock::new(Vec::new())),
+            scheduler: RwLock::new(None),
+            worker: JobWorker {
+                db,
+                training_analysis_service,
+                training_session_service,
+                data_export_service,
+                health_import_service,
+                goal_progress_evaluator,
+                model_training_service,
+                config: JobQueueConfig::from_env(),
+                worker_id: format!("{}-{}", hostname, Uuid::new_v4()),
+            },
+            shutdown,
         })
     }
 
-    /// Start the background job scheduler
+    /// Start the queue workers, the stale lease reaper and the periodic cleanup schedule
     pub async fn start(&self) -> Result<()> {
-        let scheduler = self.scheduler.read().await;
+        let scheduler = JobScheduler::new()
+            .await
+            .map_err(|e| anyhow!("Failed to create job scheduler: {}", e))?;
+
+        self.add_cleanup_job(&scheduler).await?;
+        self.add_model_retraining_job(&scheduler).await?;
+
         scheduler.start()
             .await
             .map_err(|e| anyhow!("Failed to start job scheduler: {}", e))?;
+        *self.scheduler.write().await = Some(scheduler);
 
-        info!("Background job scheduler started");
+        for index in 0..self.worker.config.worker_count {
+            let worker = JobWorker {
+                worker_id: format!("{}:{}", self.worker.worker_id, index),
+                ..self.worker.clone()
+            };
+            tokio::spawn(worker.run(self.shutdown.subscribe()));
+        }
 
-        // Add periodic cleanup job
-        self.add_cleanup_job().await?;
+        tokio::spawn(self.worker.clone().reap_stale_leases(self.shutdown.subscribe()));
 
+        info!(
+            "Background job service started with {} workers ({})",
+            self.worker.config.worker_count, self.worker.worker_id
+        );
         Ok(())
     }
 
-    /// Stop the background job scheduler
+    /// Stop the background job scheduler and workers
     pub async fn stop(&self) -> Result<()> {
-        let scheduler = self.scheduler.read().await;
-        scheduler.shutdown()
-            .await
-            .map_err(|e| anyhow!("Failed to stop job scheduler: {}", e))?;
+        let _ = self.shutdown.send(true);
+
+        if let Some(mut scheduler) = self.scheduler.write().await.take() {
+            scheduler.shutdown()
+                .await
+                .map_err(|e| anyhow!("Failed to stop job scheduler: {}", e))?;
+        }
 
-        info!("Background job scheduler stopped");
+        info!("Background job service stopped");
         Ok(())
     }
 
@@ -108,58 +316,11 @@ impl BackgroundJobService {
         user_id: Uuid,
         file_path: String,
     ) -> Result<Uuid> {
-        let job_id = Uuid::new_v4();
-
-        let background_job = BackgroundJob {
-            id: job_id,
-            job_type: JobType::ProcessTrainingFile {
-                session_id,
-                user_id,
-                file_path: file_path.clone(),
-            },
-            status: JobStatus::Pending,
-            created_at: chrono::Utc::now(),
-            started_at: None,
-            completed_at: None,
-            error_message: None,
-            retries: 0,
-        };
-
-        // Add job to internal tracking
-        {
-            let mut jobs = self.jobs.write().await;
-            jobs.push(background_job);
-        }
-
-        // Create and schedule the job
-        let training_analysis_service = self.training_analysis_service.clone();
-        let training_session_service = self.training_session_service.clone();
-        let jobs_ref = Arc::clone(&self.jobs);
-
-        let job = Job::new_async("0 0 * * * *", move |_uuid, _l| {
-            let training_analysis_service = training_analysis_service.clone();
-            let training_session_service = training_session_service.clone();
-            let jobs_ref = Arc::clone(&jobs_ref);
-            let file_path = file_path.clone();
-
-            Box::pin(async move {
-                Self::process_training_file_job(
-                    job_id,
-                    session_id,
-                    user_id,
-                    file_path,
-                    training_analysis_service,
-                    training_session_service,
-                    jobs_ref,
-                ).await;
-            })
-        })
-        .map_err(|e| anyhow!("Failed to create training file processing job: {}", e))?;
-
-        let mut scheduler = self.scheduler.write().await;
-        scheduler.add(job)
-            .await
-            .map_err(|e| anyhow!("Failed to add job to scheduler: {}", e))?;
+        let job_id = self.worker.enqueue(&JobType::ProcessTrainingFile {
+            session_id,
+            user_id,
+            file_path,
+        }).await?;
 
         info!("Queued training file processing job: {} for session: {}", job_id, session_id);
         Ok(job_id)
@@ -167,86 +328,97 @@ impl BackgroundJobService {
 
     /// Queue a PMC calculation job
     pub async fn queue_pmc_calculation(&self, user_id: Uuid, days: i32) -> Result<Uuid> {
-        let job_id = Uuid::new_v4();
-
-        let background_job = BackgroundJob {
-            id: job_id,
-            job_type: JobType::CalculatePMC { user_id, days },
-            status: JobStatus::Pending,
-            created_at: chrono::Utc::now(),
-            started_at: None,
-            completed_at: None,
-            error_message: None,
-            retries: 0,
-        };
+        let job_id = self.worker.enqueue(&JobType::CalculatePMC { user_id, days }).await?;
 
-        {
-            let mut jobs = self.jobs.write().await;
-            jobs.push(background_job);
-        }
-
-        let training_analysis_service = self.training_analysis_service.clone();
-        let jobs_ref = Arc::clone(&self.jobs);
+        info!("Queued PMC calculation job: {} for user: {}", job_id, user_id);
+        Ok(job_id)
+    }
 
-        let job = Job::new_async("0 0 * * * *", move |_uuid, _l| {
-            let training_analysis_service = training_analysis_service.clone();
-            let jobs_ref = Arc::clone(&jobs_ref);
+    /// Queue rendering of a data export
+    pub async fn queue_data_export(&self, export_id: Uuid, user_id: Uuid) -> Result<Uuid> {
+        let job_id = self.worker.enqueue(&JobType::GenerateDataExport { export_id, user_id }).await?;
 
-            Box::pin(async move {
-                Self::calculate_pmc_job(
-                    job_id,
-                    user_id,
-                    days,
-                    training_analysis_service,
-                    jobs_ref,
-                ).await;
-            })
-        })
-        .map_err(|e| anyhow!("Failed to create PMC calculation job: {}", e))?;
+        info!("Queued data export job: {} for export: {}", job_id, export_id);
+        Ok(job_id)
+    }
 
-        let mut scheduler = self.scheduler.write().await;
-        scheduler.add(job)
-            .await
-            .map_err(|e| anyhow!("Failed to add job to scheduler: {}", e))?;
+    /// Queue parsing of an uploaded health export
+    pub async fn queue_health_import(&self, import_id: Uuid, user_id: Uuid) -> Result<Uuid> {
+        let job_id = self.worker.enqueue(&JobType::ImportHealthData { import_id, user_id }).await?;
 
-        info!("Queued PMC calculation job: {} for user: {}", job_id, user_id);
+        info!("Queued health import job: {} for import: {}", job_id, import_id);
         Ok(job_id)
     }
 
+    /// Queue re-measuring a user's goals after new training or recovery data was written.
+    /// Returns `None` if an evaluation is already pending, which will see the new data too.
+    pub async fn queue_goal_evaluation(&self, user_id: Uuid) -> Result<Option<Uuid>> {
+        self.worker.queue_goal_evaluation(user_id).await
+    }
+
     /// Get job status
-    pub async fn get_job_status(&self, job_id: Uuid) -> Option<BackgroundJob> {
-        let jobs = self.jobs.read().await;
-        jobs.iter().find(|job| job.id == job_id).cloned()
+    pub async fn get_job_status(&self, job_id: Uuid) -> Result<Option<BackgroundJob>> {
+        let row = sqlx::query_as!(
+            JobRow,
+            r#"
+            SELECT id, payload::text AS "payload!", status, created_at, started_at, completed_at,
+                   error_message, retries
+            FROM background_jobs
+            WHERE id = $1
+            "#,
+            job_id
+        )
+        .fetch_optional(&self.worker.db)
+        .await?;
+
+        row.map(BackgroundJob::try_from).transpose()
     }
 
     /// Get all jobs for a user
-    pub async fn get_user_jobs(&self, user_id: Uuid) -> Vec<BackgroundJob> {
-        let jobs = self.jobs.read().await;
-        jobs.iter()
-            .filter(|job| match &job.job_type {
-                JobType::ProcessTrainingFile { user_id: uid, .. } => *uid == user_id,
-                JobType::CalculatePMC { user_id: uid, .. } => *uid == user_id,
-                JobType::CleanupOldFiles { .. } => false, // System job
+    pub async fn get_user_jobs(&self, user_id: Uuid) -> Result<Vec<BackgroundJob>> {
+        let rows = sqlx::query_as!(
+            JobRow,
+            r#"
+            SELECT id, payload::text AS "payload!", status, created_at, started_at, completed_at,
+                   error_message, retries
+            FROM background_jobs
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.worker.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| match BackgroundJob::try_from(row) {
+                Ok(job) => Some(job),
+                Err(e) => {
+                    warn!("Skipping undecodable job: {}", e);
+                    None
+                }
             })
-            .cloned()
-            .collect()
+            .collect())
     }
 
     /// Add periodic cleanup job
-    async fn add_cleanup_job(&self) -> Result<()> {
-        let jobs_ref = Arc::clone(&self.jobs);
+    async fn add_cleanup_job(&self, scheduler: &JobScheduler) -> Result<()> {
+        let worker = self.worker.clone();
 
-        // Run cleanup daily at 2 AM
+        // Run cleanup daily at 2 AM; every replica fires, the dedupe key queues the day's run once
         let job = Job::new_async("0 0 2 * * *", move |_uuid, _l| {
-            let jobs_ref = Arc::clone(&jobs_ref);
+            let worker = worker.clone();
 
             Box::pin(async move {
-                Self::cleanup_old_files_job(jobs_ref).await;
+                let slot = chrono::Utc::now().date_naive().to_string();
+                if let Err(e) = worker.enqueue_for_slot(&JobType::CleanupOldFiles { older_than_days: 30 }, &slot).await {
+                    error!("Failed to queue cleanup job: {}", e);
+                }
             })
         })
         .map_err(|e| anyhow!("Failed to create cleanup job: {}", e))?;
 
-        let mut scheduler = self.scheduler.write().await;
         scheduler.add(job)
             .await
             .map_err(|e| anyhow!("Failed to add cleanup job to scheduler: {}", e))?;
@@ -255,146 +427,547 @@ impl BackgroundJobService {
         Ok(())
     }
 
-    // Job execution methods
+    /// Add periodic retraining of models fitted on an older feature schema
+    async fn add_model_retraining_job(&self, scheduler: &JobScheduler) -> Result<()> {
+        let worker = self.worker.clone();
 
-    async fn process_training_file_job(
-        job_id: Uuid,
-        session_id: Uuid,
-        user_id: Uuid,
-        file_path: String,
-        training_analysis_service: TrainingAnalysisService,
-        training_session_service: TrainingSessionService,
-        jobs_ref: Arc<RwLock<Vec<BackgroundJob>>>,
-    ) {
-        info!("Starting training file processing job: {}", job_id);
+        // Run daily at 3 AM, after cleanup; outdated models keep predicting until then
+        let job = Job::new_async("0 0 3 * * *", move |_uuid, _l| {
+            let worker = worker.clone();
 
-        // Update job status to running
-        Self::update_job_status(&jobs_ref, job_id, JobStatus::Running, None).await;
+            Box::pin(async move {
+                let slot = chrono::Utc::now().date_naive().to_string();
+                if let Err(e) = worker.enqueue_for_slot(&JobType::RetrainOutdatedModels, &slot).await {
+                    error!("Failed to queue model retraining job: {}", e);
+                }
+            })
+        })
+        .map_err(|e| anyhow!("Failed to create model retraining job: {}", e))?;
 
-        // Process the file
-        let result = async {
-            // Read the file
-            let file_data = tokio::fs::read(&file_path).await
-                .map_err(|e| anyhow!("Failed to read file: {}", e))?;
-
-            let filename = std::path::Path::new(&file_path)
-                .file_name()
-                .and_then(|name| name.to_str())
-                .unwrap_or("unknown");
-
-            // Process the file
-            let metrics = training_analysis_service
-                .process_training_file(
-                    bytes::Bytes::from(file_data),
-                    filename,
-                    user_id,
-                    None,
-                )
-                .await?;
+        scheduler.add(job)
+            .await
+            .map_err(|e| anyhow!("Failed to add model retraining job to scheduler: {}", e))?;
+
+        info!("Added periodic model retraining job");
+        Ok(())
+    }
+}
+
+impl JobWorker {
+    async fn enqueue(&self, job_type: &JobType) -> Result<Uuid> {
+        let payload = serde_json::to_string(job_type)
+            .map_err(|e| anyhow!("Failed to serialize job: {}", e))?;
+
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO background_jobs (user_id, job_type, payload, max_retries)
+            VALUES ($1, $2, $3::text::jsonb, $4)
+            RETURNING id
+            "#,
+            job_type.user_id(),
+            job_type.name(),
+            payload,
+            self.config.max_retries
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(row.id)
+    }
+
+    /// Enqueue a scheduled job once per slot, however many replicas' schedulers fire for it.
+    /// Returns `None` if the slot was already queued.
+    async fn enqueue_for_slot(&self, job_type: &JobType, slot: &str) -> Result<Option<Uuid>> {
+        let payload = serde_json::to_string(job_type)
+            .map_err(|e| anyhow!("Failed to serialize job: {}", e))?;
+        let dedupe_key = slot_dedupe_key(job_type, slot);
+
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO background_jobs (user_id, job_type, payload, max_retries, dedupe_key)
+            SELECT $1, $2, $3::text::jsonb, $4, $5::text
+            WHERE NOT EXISTS (SELECT 1 FROM background_jobs WHERE dedupe_key = $5::text)
+            ON CONFLICT (dedupe_key) WHERE dedupe_key IS NOT NULL AND status IN ('pending', 'retrying')
+            DO NOTHING
+            RETURNING id
+            "#,
+            job_type.user_id(),
+            job_type.name(),
+            payload,
+            self.config.max_retries,
+            dedupe_key
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        match &row {
+            Some(row) => info!("Queued {} job {} for slot {}", job_type.name(), row.id, slot),
+            None => info!("{} job for slot {} is already queued", job_type.name(), slot),
+        }
+
+        Ok(row.map(|row| row.id))
+    }
+
+    /// Enqueue a job unless one with the same dedupe key is still waiting to run.
+    /// Returns `None` if the pending job already covers it.
+    async fn enqueue_coalesced(&self, job_type: &JobType, dedupe_key: &str) -> Result<Option<Uuid>> {
+        let payload = serde_json::to_string(job_type)
+            .map_err(|e| anyhow!("Failed to serialize job: {}", e))?;
+
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO background_jobs (user_id, job_type, payload, max_retries, dedupe_key)
+            VALUES ($1, $2, $3::text::jsonb, $4, $5)
+            ON CONFLICT (dedupe_key) WHERE dedupe_key IS NOT NULL AND status IN ('pending', 'retrying')
+            DO NOTHING
+            RETURNING id
+            "#,
+            job_type.user_id(),
+            job_type.name(),
+            payload,
+            self.config.max_retries,
+            dedupe_key
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(|row| row.id))
+    }
+
+    /// Queue a goal evaluation, coalescing with one that is still pending for the user
+    async fn queue_goal_evaluation(&self, user_id: Uuid) -> Result<Option<Uuid>> {
+        let job_type = JobType::EvaluateGoals { user_id };
+        let job_id = self.enqueue_coalesced(&job_type, &goal_evaluation_dedupe_key(user_id)).await?;
+
+        match job_id {
+            Some(job_id) => info!("Queued goal evaluation job: {} for user: {}", job_id, user_id),
+            None => info!("Goal evaluation for user {} is already pending", user_id),
+        }
+        Ok(job_id)
+    }
+
+    /// Fan the daily retrain out into one job per user whose active model is on an older
+    /// feature schema, skipping users who already have one pending for this schema version
+    async fn queue_outdated_model_retraining(&self) -> Result<()> {
+        let user_ids = self.model_training_service.users_with_outdated_models().await?;
+
+        let mut queued = 0;
+        for user_id in user_ids {
+            let job_type = JobType::RetrainUserModel { user_id, schema_version: FEATURE_SCHEMA_VERSION };
+            let dedupe_key = slot_dedupe_key(&job_type, &format!("v{}", FEATURE_SCHEMA_VERSION));
+
+            if self.enqueue_coalesced(&job_type, &dedupe_key).await?.is_some() {
+                queued += 1;
+            }
+        }
+
+        info!("Queued model retraining for {} users on feature schema v{}", queued, FEATURE_SCHEMA_VERSION);
+        Ok(())
+    }
 
-            // Update the training session with metrics
-            let metrics_json = serde_json::to_value(&metrics)
-                .map_err(|e| anyhow!("Failed to serialize metrics: {}", e))?;
-
-            let update_data = UpdateTrainingSession {
-                date: None,
-                trainrs_data: Some(metrics_json),
-                uploaded_file_path: None,
-                session_type: None,
-                duration_seconds: metrics.duration_seconds,
-                distance_meters: metrics.distance_meters,
+    /// Poll for and execute jobs until shutdown
+    async fn run(self, mut shutdown: watch::Receiver<bool>) {
+        while !*shutdown.borrow() {
+            let idle = match self.lease_next().await {
+                Ok(Some(job)) => {
+                    self.execute(job).await;
+                    false
+                }
+                Ok(None) => true,
+                Err(e) => {
+                    error!("Worker {} failed to lease job: {}", self.worker_id, e);
+                    true
+                }
             };
 
-            training_session_service
-                .update_session(session_id, update_data)
+            if idle {
+                tokio::select! {
+                    _ = tokio::time::sleep(self.config.poll_interval) => {}
+                    changed = shutdown.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        info!("Worker {} stopped", self.worker_id);
+    }
+
+    /// Lease the next runnable job; SKIP LOCKED lets replicas poll the same table without contention
+    async fn lease_next(&self) -> Result<Option<BackgroundJob>> {
+        let row = sqlx::query_as!(
+            JobRow,
+            r#"
+            UPDATE background_jobs
+            SET status = 'running', locked_by = $1, locked_at = NOW(), heartbeat_at = NOW(),
+                started_at = NOW()
+            WHERE id = (
+                SELECT id FROM background_jobs
+                WHERE status IN ('pending', 'retrying') AND run_at <= NOW()
+                ORDER BY run_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, payload::text AS "payload!", status, created_at, started_at, completed_at,
+                      error_message, retries
+            "#,
+            self.worker_id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let job_id = row.id;
+        match BackgroundJob::try_from(row) {
+            Ok(job) => Ok(Some(job)),
+            Err(e) => {
+                // A payload that no longer decodes will never succeed, so don't retry it
+                error!("{}", e);
+                sqlx::query!(
+                    r#"
+                    UPDATE background_jobs
+                    SET status = 'failed', error_message = $2, completed_at = NOW(),
+                        locked_by = NULL, locked_at = NULL, heartbeat_at = NULL
+                    WHERE id = $1
+                    "#,
+                    job_id,
+                    e.to_string()
+                )
+                .execute(&self.db)
                 .await?;
+                Ok(None)
+            }
+        }
+    }
+
+    async fn execute(&self, job: BackgroundJob) {
+        info!("Worker {} starting {} job: {}", self.worker_id, job.job_type.name(), job.id);
 
-            Ok::<(), anyhow::Error>(())
-        }.await;
+        let heartbeat = tokio::spawn(self.clone().heartbeat(job.id));
 
-        // Update job status based on result
-        match result {
-            Ok(()) => {
-                Self::update_job_status(&jobs_ref, job_id, JobStatus::Completed, None).await;
-                info!("Completed training file processing job: {}", job_id);
+        let result = match &job.job_type {
+            JobType::ProcessTrainingFile { session_id, user_id, file_path } => {
+                self.process_training_file(*session_id, *user_id, file_path).await
             }
-            Err(e) => {
-                let error_msg = format!("Training file processing failed: {}", e);
-                Self::update_job_status(&jobs_ref, job_id, JobStatus::Failed, Some(error_msg.clone())).await;
-                error!("Failed training file processing job {}: {}", job_id, error_msg);
+            JobType::CalculatePMC { user_id, days } => self
+                .training_analysis_service
+                .calculate_pmc(*user_id, *days)
+                .await
+                .map(|_| ()),
+            JobType::CleanupOldFiles { older_than_days } => self.cleanup_old_jobs(*older_than_days).await,
+            JobType::GenerateDataExport { export_id, .. } => {
+                self.data_export_service.generate_export(*export_id).await
+            }
+            JobType::ImportHealthData { import_id, .. } => {
+                self.health_import_service.run_import(*import_id).await
             }
+            JobType::EvaluateGoals { user_id } => self
+                .goal_progress_evaluator
+                .evaluate_user_goals(*user_id, chrono::Utc::now().date_naive())
+                .await
+                .map(|_| ()),
+            JobType::RetrainOutdatedModels => self.queue_outdated_model_retraining().await,
+            JobType::RetrainUserModel { user_id, .. } => self
+                .model_training_service
+                .retrain_outdated_model(*user_id)
+                .await
+                .map(|_| ()),
+        };
+
+        heartbeat.abort();
+
+        let outcome = match result {
+            Ok(()) => self.mark_completed(job.id).await,
+            Err(e) => self.mark_failed(&job, &e).await,
+        };
+
+        if let Err(e) = outcome {
+            error!("Failed to record outcome of job {}: {}", job.id, e);
         }
     }
 
-    async fn calculate_pmc_job(
-        job_id: Uuid,
-        user_id: Uuid,
-        days: i32,
-        training_analysis_service: TrainingAnalysisService,
-        jobs_ref: Arc<RwLock<Vec<BackgroundJob>>>,
-    ) {
-        info!("Starting PMC calculation job: {}", job_id);
+    async fn heartbeat(self, job_id: Uuid) {
+        let mut interval = tokio::time::interval(self.config.heartbeat_interval);
+        interval.tick().await; // The lease itself counts as the first heartbeat
 
-        Self::update_job_status(&jobs_ref, job_id, JobStatus::Running, None).await;
+        loop {
+            interval.tick().await;
 
-        let result = training_analysis_service
-            .calculate_pmc(user_id, days)
+            let result = sqlx::query!(
+                "UPDATE background_jobs SET heartbeat_at = NOW() WHERE id = $1 AND locked_by = $2",
+                job_id,
+                self.worker_id
+            )
+            .execute(&self.db)
             .await;
 
-        match result {
-            Ok(_) => {
-                Self::update_job_status(&jobs_ref, job_id, JobStatus::Completed, None).await;
-                info!("Completed PMC calculation job: {}", job_id);
+            match result {
+                Ok(done) if done.rows_affected() == 0 => {
+                    warn!("Worker {} lost the lease on job {}", self.worker_id, job_id);
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to send heartbeat for job {}: {}", job_id, e),
             }
-            Err(e) => {
-                let error_msg = format!("PMC calculation failed: {}", e);
-                Self::update_job_status(&jobs_ref, job_id, JobStatus::Failed, Some(error_msg.clone())).await;
-                error!("Failed PMC calculation job {}: {}", job_id, error_msg);
+        }
+    }
+
+    async fn mark_completed(&self, job_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE background_jobs
+            SET status = 'completed', completed_at = NOW(), error_message = NULL,
+                locked_by = NULL, locked_at = NULL, heartbeat_at = NULL
+            WHERE id = $1 AND locked_by = $2
+            "#,
+            job_id,
+            self.worker_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        info!("Completed job: {}", job_id);
+        Ok(())
+    }
+
+    /// Schedule a retry with exponential backoff, or dead-letter the job once retries are exhausted
+    async fn mark_failed(&self, job: &BackgroundJob, error: &anyhow::Error) -> Result<()> {
+        let error_msg = format!("{} failed: {}", job.job_type.name(), error);
+        let backoff = self.config.retry_backoff(job.retries);
+
+        let row = sqlx::query!(
+            r#"
+            UPDATE background_jobs
+            SET retries = retries + 1,
+                status = CASE WHEN retries >= max_retries THEN 'dead_letter' ELSE 'retrying' END,
+                completed_at = CASE WHEN retries >= max_retries THEN NOW() ELSE NULL END,
+                run_at = NOW() + make_interval(secs => $3),
+                error_message = $2,
+                locked_by = NULL, locked_at = NULL, heartbeat_at = NULL
+            WHERE id = $1 AND locked_by = $4
+            RETURNING status
+            "#,
+            job.id,
+            error_msg,
+            backoff.as_secs_f64(),
+            self.worker_id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        match row.map(|r| r.status) {
+            Some(status) if status == JobStatus::DeadLetter.as_str() => {
+                error!("Job {} moved to dead letter after {} attempts: {}", job.id, job.retries + 1, error_msg);
+            }
+            Some(_) => {
+                warn!("Job {} failed, retrying in {:?}: {}", job.id, backoff, error_msg);
             }
+            None => warn!("Job {} failed after its lease was lost: {}", job.id, error_msg),
         }
+
+        Ok(())
     }
 
-    async fn cleanup_old_files_job(jobs_ref: Arc<RwLock<Vec<BackgroundJob>>>) {
-        info!("Starting cleanup job");
+    /// Requeue running jobs whose worker stopped sending heartbeats
+    async fn reap_stale_leases(self, mut shutdown: watch::Receiver<bool>) {
+        let mut interval = tokio::time::interval(self.config.lease_timeout / 2);
+
+        while !*shutdown.borrow() {
+            tokio::select! {
+                _ = interval.tick() => {}
+                changed = shutdown.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+            }
 
-        // Clean up completed jobs older than 30 days
-        let cutoff_date = chrono::Utc::now() - chrono::Duration::days(30);
+            let result = sqlx::query!(
+                r#"
+                UPDATE background_jobs
+                SET retries = retries + 1,
+                    status = CASE WHEN retries >= max_retries THEN 'dead_letter' ELSE 'retrying' END,
+                    completed_at = CASE WHEN retries >= max_retries THEN NOW() ELSE NULL END,
+                    run_at = NOW(),
+                    error_message = 'Worker ' || locked_by || ' stopped sending heartbeats',
+                    locked_by = NULL, locked_at = NULL, heartbeat_at = NULL
+                WHERE status = 'running' AND heartbeat_at < NOW() - make_interval(secs => $1)
+                "#,
+                self.config.lease_timeout.as_secs_f64()
+            )
+            .execute(&self.db)
+            .await;
 
-        let mut jobs = jobs_ref.write().await;
-        let initial_count = jobs.len();
+            match result {
+                Ok(done) if done.rows_affected() > 0 => {
+                    warn!("Requeued {} jobs with stale leases", done.rows_affected());
+                }
+                Ok(_) => {}
+                Err(e) => error!("Failed to requeue stale jobs: {}", e),
+            }
+        }
+    }
 
-        jobs.retain(|job| {
-            job.status != JobStatus::Completed || job.created_at > cutoff_date
-        });
+    // Job execution methods
+
+    async fn process_training_file(&self, session_id: Uuid, user_id: Uuid, file_path: &str) -> Result<()> {
+        // Read the file
+        let file_data = tokio::fs::read(file_path).await
+            .map_err(|e| anyhow!("Failed to read file: {}", e))?;
 
-        let cleaned_count = initial_count - jobs.len();
+        let filename = std::path::Path::new(file_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("unknown");
 
-        if cleaned_count > 0 {
-            info!("Cleaned up {} old completed jobs", cleaned_count);
+        // Process the file
+        let metrics = self.training_analysis_service
+            .process_training_file(
+                bytes::Bytes::from(file_data),
+                filename,
+                user_id,
+                None,
+            )
+            .await?;
+
+        // Update the training session with metrics
+        let metrics_json = serde_json::to_value(&metrics)
+            .map_err(|e| anyhow!("Failed to serialize metrics: {}", e))?;
+
+        let update_data = UpdateTrainingSession {
+            date: None,
+            trainrs_data: Some(metrics_json),
+            uploaded_file_path: None,
+            session_type: None,
+            duration_seconds: metrics.duration_seconds,
+            distance_meters: metrics.distance_meters,
+        };
+
+        self.training_session_service
+            .update_session(session_id, update_data)
+            .await?;
+
+        // The session is stored either way, so don't reprocess the file if this fails
+        if let Err(e) = self.queue_goal_evaluation(user_id).await {
+            warn!("Failed to queue goal evaluation for user {}: {:#}", user_id, e);
         }
+
+        Ok(())
     }
 
-    async fn update_job_status(
-        jobs_ref: &Arc<RwLock<Vec<BackgroundJob>>>,
-        job_id: Uuid,
-        status: JobStatus,
-        error_message: Option<String>,
-    ) {
-        let mut jobs = jobs_ref.write().await;
+    async fn cleanup_old_jobs(&self, older_than_days: i32) -> Result<()> {
+        // Dead-lettered jobs are kept for inspection
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM background_jobs
+            WHERE status = 'completed' AND completed_at < NOW() - make_interval(days => $1)
+            "#,
+            older_than_days
+        )
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            info!("Cleaned up {} old completed jobs", result.rows_affected());
+        }
 
-        if let Some(job) = jobs.iter_mut().find(|job| job.id == job_id) {
-            job.status = status.clone();
-            job.error_message = error_message;
+        self.data_export_service.purge_expired().await?;
+        self.health_import_service.purge_stale_uploads(older_than_days).await?;
 
-            match status {
-                JobStatus::Running => {
-                    job.started_at = Some(chrono::Utc::now());
-                }
-                JobStatus::Completed | JobStatus::Failed => {
-                    job.completed_at = Some(chrono::Utc::now());
-                }
-                _ => {}
-            }
+        // Goals whose target date passed without new data still need settling
+        self.goal_progress_evaluator
+            .evaluate_overdue_goals(chrono::Utc::now().date_naive())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Dedupe key of a scheduled job, e.g. `cleanup_old_files@2024-05-01`
+fn slot_dedupe_key(job_type: &JobType, slot: &str) -> String {
+    match job_type.user_id() {
+        Some(user_id) => format!("{}:{}@{}", job_type.name(), user_id, slot),
+        None => format!("{}@{}", job_type.name(), slot),
+    }
+}
+
+/// Dedupe key shared by all pending goal evaluations of a user, e.g. `evaluate_goals:{user}`
+fn goal_evaluation_dedupe_key(user_id: Uuid) -> String {
+    format!("{}:{}", JobType::EvaluateGoals { user_id }.name(), user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_status_round_trip() {
+        for status in [
+            JobStatus::Pending,
+            JobStatus::Running,
+            JobStatus::Completed,
+            JobStatus::Failed,
+            JobStatus::Retrying,
+            JobStatus::DeadLetter,
+        ] {
+            assert_eq!(status.as_str().parse::<JobStatus>().unwrap(), status);
         }
+        assert!("unknown".parse::<JobStatus>().is_err());
+    }
+
+    #[test]
+    fn test_job_type_payload_round_trip() {
+        let user_id = Uuid::new_v4();
+        let job = JobType::CalculatePMC { user_id, days: 90 };
+
+        let payload = serde_json::to_value(&job).unwrap();
+        assert_eq!(payload, serde_json::json!({ "CalculatePMC": { "user_id": user_id, "days": 90 } }));
+
+        let decoded: JobType = serde_json::from_value(payload).unwrap();
+        assert!(matches!(decoded, JobType::CalculatePMC { days: 90, .. }));
+        assert_eq!(decoded.user_id(), Some(user_id));
+        assert_eq!(JobType::CleanupOldFiles { older_than_days: 30 }.user_id(), None);
+        assert_eq!(JobType::EvaluateGoals { user_id }.user_id(), Some(user_id));
+        assert_eq!(JobType::RetrainOutdatedModels.user_id(), None);
+
+        let payload = serde_json::to_value(JobType::RetrainOutdatedModels).unwrap();
+        assert_eq!(payload, serde_json::json!("RetrainOutdatedModels"));
+        assert!(matches!(serde_json::from_value(payload).unwrap(), JobType::RetrainOutdatedModels));
+
+        let retrain = JobType::RetrainUserModel { user_id, schema_version: 2 };
+        assert_eq!(retrain.user_id(), Some(user_id));
+        assert_eq!(slot_dedupe_key(&retrain, "v2"), format!("retrain_user_model:{}@v2", user_id));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_slot_dedupe_key() {
+        let user_id = Uuid::new_v4();
+
+        assert_eq!(
+            slot_dedupe_key(&JobType::CleanupOldFiles { older_than_days: 30 }, "2024-05-01"),
+            "cleanup_old_files@2024-05-01"
+        );
+        assert_eq!(
+            slot_dedupe_key(&JobType::EvaluateGoals { user_id }, "2024-05-01"),
+            format!("evaluate_goals:{}@2024-05-01", user_id)
+        );
+        assert_eq!(goal_evaluation_dedupe_key(user_id), format!("evaluate_goals:{}", user_id));
+    }
+
+    #[test]
+    fn test_retry_backoff_grows_exponentially_and_caps() {
+        let config = JobQueueConfig::default();
+
+        assert_eq!(config.retry_backoff(0), Duration::from_secs(30));
+        assert_eq!(config.retry_backoff(1), Duration::from_secs(60));
+        assert_eq!(config.retry_backoff(3), Duration::from_secs(240));
+        assert_eq!(config.retry_backoff(10), config.max_backoff);
+        assert_eq!(config.retry_backoff(i32::MAX), config.max_backoff);
+    }
+}
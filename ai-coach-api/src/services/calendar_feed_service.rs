@@ -0,0 +1,1029 @@
+/// Calendar Feed Service
+///
+/// Per-user iCalendar (RFC 5545) subscription feeds and `.ics` import:
+/// - Races and other events with priority, status and location
+/// - Planned workouts and rest days from generated plans, with duration and targets
+/// - Training phase markers (base, build, peak, taper) from event plans
+/// - Race calendar import, updating previously imported events by UID
+///
+/// UIDs are derived from database IDs, so calendar clients update entries in place.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::models::{
+    CalendarFeed, CalendarImportSummary, CreateCalendarFeedRequest, Event, EventPlan,
+    EventPriority, EventStatus, PlanWeekStructure, TrainingPhase,
+};
+use crate::services::workout_export_service::workout_type_label;
+use crate::services::EventService;
+
+/// Domain part of every UID we emit; imports skip these so a user's own feed isn't duplicated
+const UID_DOMAIN: &str = "ai-coach";
+const PRODID: &str = "-//AI Coach//Training Calendar//EN";
+const FEED_TOKEN_LEN: usize = 40;
+const DEFAULT_EVENT_MINUTES: i64 = 120;
+
+const CALENDAR_FEED_COLUMNS: &str =
+    "id, user_id, include_events, include_workouts, created_at, last_accessed_at, revoked_at";
+
+/// Generated plan fields needed for the feed
+#[derive(Debug, FromRow)]
+struct FeedPlanRow {
+    id: Uuid,
+    plan_name: String,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    plan_structure: serde_json::Value,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+/// When a calendar entry happens
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryTime {
+    /// All-day entry; `end` is exclusive
+    AllDay { start: NaiveDate, end: NaiveDate },
+    /// Floating local time, shown at the same clock time in any timezone
+    Timed { start: NaiveDateTime, end: NaiveDateTime },
+}
+
+/// One VEVENT
+#[derive(Debug, Clone)]
+pub struct CalendarEntry {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub url: Option<String>,
+    pub time: EntryTime,
+    pub priority: Option<u8>,
+    pub status: Option<&'static str>,
+    pub categories: Vec<String>,
+    /// Doesn't block time (workouts, rest days, phase markers)
+    pub transparent: bool,
+    pub last_modified: DateTime<Utc>,
+    pub sequence: i64,
+}
+
+/// VEVENT read from an imported calendar
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IcsEvent {
+    pub uid: Option<String>,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub url: Option<String>,
+    pub start_date: Option<NaiveDate>,
+    pub start_time: Option<NaiveTime>,
+    pub duration_minutes: Option<i32>,
+    pub priority: Option<u8>,
+    pub cancelled: bool,
+    pub categories: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct CalendarFeedService {
+    db: PgPool,
+    event_service: EventService,
+}
+
+impl CalendarFeedService {
+    pub fn new(db: PgPool) -> Self {
+        Self {
+            event_service: EventService::new(db.clone()),
+            db,
+        }
+    }
+
+    /// Create the user's feed, revoking any previous one. Returns the feed and its secret token.
+    pub async fn create_feed(
+        &self,
+        user_id: Uuid,
+        request: CreateCalendarFeedRequest,
+    ) -> Result<(CalendarFeed, String)> {
+        let token = generate_feed_token();
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query("UPDATE calendar_feed_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let feed = sqlx::query_as::<_, CalendarFeed>(&format!(
+            r#"
+            INSERT INTO calendar_feed_tokens (user_id, token_hash, include_events, include_workouts)
+            VALUES ($1, $2, $3, $4)
+            RETURNING {}
+            "#,
+            CALENDAR_FEED_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(hash_feed_token(&token))
+        .bind(request.include_events.unwrap_or(true))
+        .bind(request.include_workouts.unwrap_or(true))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        info!("Created calendar feed {} for user {}", feed.id, user_id);
+        Ok((feed, token))
+    }
+
+    /// The user's live feed, if any
+    pub async fn get_feed(&self, user_id: Uuid) -> Result<Option<CalendarFeed>> {
+        let feed = sqlx::query_as::<_, CalendarFeed>(&format!(
+            "SELECT {} FROM calendar_feed_tokens WHERE user_id = $1 AND revoked_at IS NULL",
+            CALENDAR_FEED_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(feed)
+    }
+
+    /// Revoke the user's feed; subscribed calendars stop updating. Returns false if there was none.
+    pub async fn revoke_feed(&self, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE calendar_feed_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Resolve a feed token, recording the access
+    pub async fn feed_for_token(&self, token: &str) -> Result<Option<CalendarFeed>> {
+        let feed = sqlx::query_as::<_, CalendarFeed>(&format!(
+            r#"
+            UPDATE calendar_feed_tokens SET last_accessed_at = NOW()
+            WHERE token_hash = $1 AND revoked_at IS NULL
+            RETURNING {}
+            "#,
+            CALENDAR_FEED_COLUMNS
+        ))
+        .bind(hash_feed_token(token))
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(feed)
+    }
+
+    /// Render the feed's `.ics` document
+    pub async fn render_feed(&self, feed: &CalendarFeed) -> Result<String> {
+        let today = Utc::now().date_naive();
+        let mut entries = Vec::new();
+
+        if feed.include_events {
+            let calendar = self
+                .event_service
+                .get_event_calendar(feed.user_id, today - Duration::days(90), today + Duration::days(730))
+                .await?;
+
+            for plan in &calendar.event_plans {
+                let Some(event) = calendar.events.iter().find(|event| event.id == plan.event_id) else {
+                    continue;
+                };
+                match serde_json::from_value::<Vec<TrainingPhase>>(plan.training_phases.clone()) {
+                    Ok(phases) => entries.extend(phase_entries(plan, &event.name, &phases)),
+                    Err(e) => warn!("Skipping phases of event plan {}: {}", plan.id, e),
+                }
+            }
+
+            entries.extend(calendar.events.iter().map(event_entry));
+        }
+
+        if feed.include_workouts {
+            let plans = sqlx::query_as::<_, FeedPlanRow>(
+                r#"
+                SELECT id, plan_name, start_date, end_date, plan_structure, created_at, updated_at
+                FROM generated_plans
+                WHERE user_id = $1 AND status IN ('draft', 'active') AND end_date >= $2
+                ORDER BY start_date
+                "#,
+            )
+            .bind(feed.user_id)
+            .bind(today - Duration::days(30))
+            .fetch_all(&self.db)
+            .await?;
+
+            for plan in &plans {
+                match serde_json::from_value::<Vec<PlanWeekStructure>>(plan.plan_structure.clone()) {
+                    Ok(weeks) => entries.extend(plan_entries(plan, &weeks)),
+                    Err(e) => warn!("Skipping workouts of plan {}: {}", plan.id, e),
+                }
+            }
+        }
+
+        Ok(render_calendar("AI Coach Training", &entries, Utc::now()))
+    }
+
+    /// Import the VEVENTs of an `.ics` race calendar as events. Events imported earlier are
+    /// matched by UID and updated; entries from our own feed are skipped.
+    pub async fn import_calendar(&self, user_id: Uuid, ics: &str) -> Result<CalendarImportSummary> {
+        let mut summary = CalendarImportSummary::default();
+
+        for ics_event in parse_ics(ics)? {
+            let (Some(name), Some(event_date)) = (ics_event.summary.clone(), ics_event.start_date) else {
+                summary.skipped += 1;
+                continue;
+            };
+
+            let uid = ics_event
+                .uid
+                .clone()
+                .unwrap_or_else(|| format!("{:x}", md5::compute(format!("{}|{}", name, event_date))));
+            if uid.ends_with(&format!("@{}", UID_DOMAIN)) {
+                summary.skipped += 1;
+                continue;
+            }
+
+            let inserted = sqlx::query_scalar::<_, bool>(
+                r#"
+                INSERT INTO events (
+                    user_id, external_uid, name, description, event_type, sport, event_date, event_time,
+                    location, expected_duration, website_url, priority, status
+                )
+                VALUES (
+                    $1, $2, $3, $4, 'race', $5::sport, $6, $7, $8, $9, $10,
+                    COALESCE($11::event_priority, 'medium'), $12::event_status
+                )
+                ON CONFLICT (user_id, external_uid) WHERE external_uid IS NOT NULL DO UPDATE
+                SET name = EXCLUDED.name,
+                    description = EXCLUDED.description,
+                    sport = EXCLUDED.sport,
+                    event_date = EXCLUDED.event_date,
+                    event_time = EXCLUDED.event_time,
+                    location = EXCLUDED.location,
+                    expected_duration = EXCLUDED.expected_duration,
+                    website_url = EXCLUDED.website_url,
+                    priority = COALESCE($11::event_priority, events.priority),
+                    status = CASE WHEN $12 = 'cancelled' THEN 'cancelled'::event_status ELSE events.status END,
+                    updated_at = NOW()
+                RETURNING (xmax = 0)
+                "#,
+            )
+            .bind(user_id)
+            .bind(&uid)
+            .bind(&name)
+            .bind(&ics_event.description)
+            .bind(guess_sport(&ics_event))
+            .bind(event_date)
+            .bind(ics_event.start_time)
+            .bind(&ics_event.location)
+            .bind(ics_event.duration_minutes)
+            .bind(&ics_event.url)
+            .bind(ics_event.priority.map(priority_from_ical))
+            .bind(if ics_event.cancelled { "cancelled" } else { "planned" })
+            .fetch_one(&self.db)
+            .await?;
+
+            if inserted {
+                summary.created += 1;
+            } else {
+                summary.updated += 1;
+            }
+        }
+
+        info!(
+            "Imported calendar for user {}: {} created, {} updated, {} skipped",
+            user_id, summary.created, summary.updated, summary.skipped
+        );
+        Ok(summary)
+    }
+}
+
+fn generate_feed_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(FEED_TOKEN_LEN)
+        .map(char::from)
+        .collect()
+}
+
+fn hash_feed_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// VEVENT for a race or other event; events with a start time are timed, others all-day
+pub fn event_entry(event: &Event) -> CalendarEntry {
+    let time = match event.event_time {
+        Some(start_time) => {
+            let start = event.event_date.and_time(start_time);
+            let minutes = event.expected_duration.map(i64::from).unwrap_or(DEFAULT_EVENT_MINUTES);
+            EntryTime::Timed { start, end: start + Duration::minutes(minutes.max(1)) }
+        }
+        None => EntryTime::AllDay { start: event.event_date, end: event.event_date + Duration::days(1) },
+    };
+
+    let mut details = Vec::new();
+    if let Some(description) = &event.description {
+        details.push(description.clone());
+    }
+    if let Some(distance) = event.distance {
+        details.push(format!("Distance: {} {}", distance, event.distance_unit.as_deref().unwrap_or("km")));
+    }
+    if let Some(elevation) = event.elevation_gain {
+        details.push(format!("Elevation gain: {} m", elevation));
+    }
+    details.push(format!("Priority: {:?}", event.priority));
+    if let Some(notes) = &event.notes {
+        details.push(notes.clone());
+    }
+
+    CalendarEntry {
+        uid: format!("event-{}@{}", event.id, UID_DOMAIN),
+        summary: event.name.clone(),
+        description: Some(details.join("\n")),
+        location: event.location.clone(),
+        url: event.website_url.clone(),
+        time,
+        priority: Some(priority_to_ical(&event.priority)),
+        status: Some(match event.status {
+            EventStatus::Cancelled => "CANCELLED",
+            EventStatus::Planned => "TENTATIVE",
+            _ => "CONFIRMED",
+        }),
+        categories: vec![format!("{:?}", event.event_type), format!("{:?}", event.sport)],
+        transparent: false,
+        last_modified: event.updated_at,
+        sequence: (event.updated_at - event.created_at).num_seconds().max(0),
+    }
+}
+
+/// All-day markers spanning each training phase leading up to an event
+pub fn phase_entries(plan: &EventPlan, event_name: &str, phases: &[TrainingPhase]) -> Vec<CalendarEntry> {
+    phases
+        .iter()
+        .enumerate()
+        .filter(|(_, phase)| phase.end_date >= phase.start_date)
+        .map(|(index, phase)| {
+            let mut details = vec![format!(
+                "{} weeks, {:.0}-{:.0} weekly volume",
+                phase.weeks, phase.weekly_volume_range.0, phase.weekly_volume_range.1
+            )];
+            if !phase.focus_areas.is_empty() {
+                details.push(format!("Focus: {}", phase.focus_areas.join(", ")));
+            }
+            if !phase.key_workouts.is_empty() {
+                details.push(format!("Key workouts: {}", phase.key_workouts.join(", ")));
+            }
+
+            CalendarEntry {
+                uid: format!("phase-{}-{}@{}", plan.id, index, UID_DOMAIN),
+                summary: format!("{:?} phase: {} ({})", phase.phase_type, phase.phase_name, event_name),
+                description: Some(details.join("\n")),
+                location: None,
+                url: None,
+                time: EntryTime::AllDay { start: phase.start_date, end: phase.end_date + Duration::days(1) },
+                priority: None,
+                status: None,
+                categories: vec!["Training phase".to_string()],
+                transparent: true,
+                last_modified: plan.updated_at,
+                sequence: (plan.updated_at - plan.created_at).num_seconds().max(0),
+            }
+        })
+        .collect()
+}
+
+/// Date of a plan day; weeks run from the plan start and `day_of_week` is 1=Monday
+pub fn plan_day_date(start_date: NaiveDate, week_number: i32, day_of_week: i32) -> NaiveDate {
+    let week_start = start_date + Duration::weeks((week_number - 1) as i64);
+    let monday = week_start - Duration::days(week_start.weekday().num_days_from_monday() as i64);
+    monday + Duration::days((day_of_week.clamp(1, 7) - 1) as i64)
+}
+
+/// All-day entries for a plan's workouts and rest days
+fn plan_entries(plan: &FeedPlanRow, weeks: &[PlanWeekStructure]) -> Vec<CalendarEntry> {
+    let sequence = (plan.updated_at - plan.created_at).num_seconds().max(0);
+    let in_plan = |date: &NaiveDate| *date >= plan.start_date && *date <= plan.end_date;
+    let all_day = |date: NaiveDate| EntryTime::AllDay { start: date, end: date + Duration::days(1) };
+    let mut entries = Vec::new();
+
+    for week in weeks {
+        for (index, day) in week.workout_days.iter().enumerate() {
+            let date = plan_day_date(plan.start_date, week.week_number, day.day_of_week);
+            if !in_plan(&date) {
+                continue;
+            }
+
+            let mut details = vec![
+                day.workout_description.clone(),
+                format!("Duration: {} min", day.duration_minutes),
+            ];
+            if let Some(power) = &day.power_targets {
+                details.push(format!("Power: {:.0}-{:.0}% FTP", power.ftp_percentage_low, power.ftp_percentage_high));
+            }
+            if let Some(hr) = &day.heart_rate_targets {
+                details.push(format!("Heart rate: {:.0}-{:.0}% LTHR", hr.hr_percentage_low, hr.hr_percentage_high));
+            }
+            if let Some(pace) = &day.pace_targets {
+                details.push(format!("Pace: {}-{} /km", pace.pace_per_km_low, pace.pace_per_km_high));
+            }
+            if let Some(notes) = &day.notes {
+                details.push(notes.clone());
+            }
+            details.push(format!("{} - week {} ({})", plan.plan_name, week.week_number, week.phase_name));
+
+            // Later workouts on the same day get their position in the day, so the first keeps
+            // the UID it had before the day had a second workout
+            let earlier_that_day = week.workout_days[..index]
+                .iter()
+                .filter(|other| other.day_of_week == day.day_of_week)
+                .count();
+            let session = match earlier_that_day {
+                0 => String::new(),
+                earlier => format!("-{}", earlier + 1),
+            };
+
+            entries.push(CalendarEntry {
+                uid: format!("plan-{}-w{}-d{}{}@{}", plan.id, week.week_number, day.day_of_week, session, UID_DOMAIN),
+                summary: format!("{} · {} min", workout_type_label(&day.workout_type), day.duration_minutes),
+                description: Some(details.join("\n")),
+                location: None,
+                url: None,
+                time: all_day(date),
+                priority: None,
+                status: None,
+                categories: vec!["Workout".to_string()],
+                transparent: true,
+                last_modified: plan.updated_at,
+                sequence,
+            });
+        }
+
+        for &rest_day in &week.rest_days {
+            let date = plan_day_date(plan.start_date, week.week_number, rest_day);
+            if !in_plan(&date) {
+                continue;
+            }
+
+            entries.push(CalendarEntry {
+                uid: format!("plan-{}-w{}-rest{}@{}", plan.id, week.week_number, rest_day, UID_DOMAIN),
+                summary: "Rest day".to_string(),
+                description: Some(format!("{} - week {} ({})", plan.plan_name, week.week_number, week.phase_name)),
+                location: None,
+                url: None,
+                time: all_day(date),
+                priority: None,
+                status: None,
+                categories: vec!["Rest".to_string()],
+                transparent: true,
+                last_modified: plan.updated_at,
+                sequence,
+            });
+        }
+    }
+
+    entries
+}
+
+/// iCalendar priority: 1 is highest, 9 lowest
+fn priority_to_ical(priority: &EventPriority) -> u8 {
+    match priority {
+        EventPriority::Critical => 1,
+        EventPriority::High => 3,
+        EventPriority::Medium => 5,
+        EventPriority::Low => 9,
+    }
+}
+
+fn priority_from_ical(priority: u8) -> &'static str {
+    match priority {
+        1 => "critical",
+        2..=4 => "high",
+        5 => "medium",
+        _ => "low",
+    }
+}
+
+/// Best guess of an imported event's sport from its title and categories
+fn guess_sport(event: &IcsEvent) -> &'static str {
+    let text = format!(
+        "{} {}",
+        event.summary.as_deref().unwrap_or(""),
+        event.categories.join(" ")
+    )
+    .to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|word| text.contains(word));
+
+    if has(&["triathlon", "ironman", "70.3", "sprint tri"]) {
+        "triathlon"
+    } else if has(&["duathlon"]) {
+        "duathlon"
+    } else if has(&["swim"]) {
+        "swimming"
+    } else if has(&["marathon", "run", "5k", "10k", "trail race", "parkrun"]) {
+        "running"
+    } else if has(&["cycl", "bike", "gran fondo", "gravel", "crit", "road race", "time trial", "sportive"]) {
+        "cycling"
+    } else {
+        "other"
+    }
+}
+
+/// Render a complete VCALENDAR document
+pub fn render_calendar(name: &str, entries: &[CalendarEntry], generated_at: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(name)),
+        "X-PUBLISHED-TTL:PT1H".to_string(),
+        "REFRESH-INTERVAL;VALUE=DURATION:PT1H".to_string(),
+    ];
+
+    for entry in entries {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", entry.uid));
+        lines.push(format!("DTSTAMP:{}", format_utc(generated_at)));
+        lines.push(format!("LAST-MODIFIED:{}", format_utc(entry.last_modified)));
+        lines.push(format!("SEQUENCE:{}", entry.sequence));
+        match &entry.time {
+            EntryTime::AllDay { start, end } => {
+                lines.push(format!("DTSTART;VALUE=DATE:{}", start.format("%Y%m%d")));
+                lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+            }
+            EntryTime::Timed { start, end } => {
+                lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")));
+                lines.push(format!("DTEND:{}", end.format("%Y%m%dT%H%M%S")));
+            }
+        }
+        lines.push(format!("SUMMARY:{}", escape_text(&entry.summary)));
+        if let Some(description) = &entry.description {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        if let Some(location) = &entry.location {
+            lines.push(format!("LOCATION:{}", escape_text(location)));
+        }
+        if let Some(url) = &entry.url {
+            lines.push(format!("URL:{}", url));
+        }
+        if let Some(priority) = entry.priority {
+            lines.push(format!("PRIORITY:{}", priority));
+        }
+        if let Some(status) = entry.status {
+            lines.push(format!("STATUS:{}", status));
+        }
+        if !entry.categories.is_empty() {
+            let categories: Vec<String> = entry.categories.iter().map(|c| escape_text(c)).collect();
+            lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        }
+        lines.push(format!("TRANSP:{}", if entry.transparent { "TRANSPARENT" } else { "OPAQUE" }));
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut output = String::new();
+    for line in &lines {
+        fold_line(line, &mut output);
+    }
+    output
+}
+
+fn format_utc(timestamp: DateTime<Utc>) -> String {
+    timestamp.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Append a content line, folded at 75 octets without splitting UTF-8 characters
+fn fold_line(line: &str, output: &mut String) {
+    let mut remaining = line;
+    let mut limit = 75;
+    while remaining.len() > limit {
+        let mut split = limit;
+        while !remaining.is_char_boundary(split) {
+            split -= 1;
+        }
+        output.push_str(&remaining[..split]);
+        output.push_str("\r\n ");
+        remaining = &remaining[split..];
+        // Continuation lines start with a space
+        limit = 74;
+    }
+    output.push_str(remaining);
+    output.push_str("\r\n");
+}
+
+/// Property name, parameters and raw value of one content line
+type ContentLine<'a> = (String, Vec<(String, String)>, &'a str);
+
+/// Split a content line into name, parameters and value; colons inside quoted parameters are kept
+fn split_content_line(line: &str) -> Option<ContentLine<'_>> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_uppercase();
+    let params = parts
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            Some((key.trim().to_uppercase(), value.trim_matches('"').to_string()))
+        })
+        .collect();
+
+    Some((name, params, value))
+}
+
+/// DATE or DATE-TIME value; UTC (`Z`) and TZID times are taken at face value
+fn parse_ical_datetime(value: &str) -> Option<(NaiveDate, Option<NaiveTime>)> {
+    let value = value.trim().trim_end_matches('Z');
+    match value.split_once('T') {
+        Some((date, time)) => Some((
+            NaiveDate::parse_from_str(date, "%Y%m%d").ok()?,
+            Some(NaiveTime::parse_from_str(time, "%H%M%S").ok()?),
+        )),
+        None => Some((NaiveDate::parse_from_str(value, "%Y%m%d").ok()?, None)),
+    }
+}
+
+/// ISO 8601 duration such as `PT2H30M` or `P1D`, in minutes
+fn parse_ical_duration(value: &str) -> Option<i64> {
+    let value = value.trim().trim_start_matches('+').strip_prefix('P')?;
+    let mut minutes = 0i64;
+    let mut number = String::new();
+    let mut in_time = false;
+
+    for c in value.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => number.push(c),
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                minutes += match (unit, in_time) {
+                    ('W', false) => amount * 7 * 24 * 60,
+                    ('D', false) => amount * 24 * 60,
+                    ('H', true) => amount * 60,
+                    ('M', true) => amount,
+                    ('S', true) => amount / 60,
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    Some(minutes)
+}
+
+/// Parse the VEVENTs of an iCalendar document
+pub fn parse_ics(text: &str) -> Result<Vec<IcsEvent>> {
+    // Unfold continuation lines
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        match (raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')), lines.last_mut()) {
+            (Some(continuation), Some(previous)) => previous.push_str(continuation),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+
+    if !lines.iter().any(|line| line.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err(anyhow!("Not an iCalendar file: missing BEGIN:VCALENDAR"));
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<IcsEvent> = None;
+    let mut end: Option<(NaiveDate, Option<NaiveTime>)> = None;
+    // Properties of nested components such as VALARM are ignored
+    let mut nested_depth = 0;
+
+    for line in &lines {
+        let Some((name, params, value)) = split_content_line(line) else {
+            continue;
+        };
+
+        match (name.as_str(), value.trim().to_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => {
+                current = Some(IcsEvent::default());
+                end = None;
+                continue;
+            }
+            ("END", "VEVENT") => {
+                if let Some(mut event) = current.take() {
+                    if event.duration_minutes.is_none() {
+                        event.duration_minutes = timed_duration(&event, end.take());
+                    }
+                    events.push(event);
+                }
+                continue;
+            }
+            ("BEGIN", _) if current.is_some() => {
+                nested_depth += 1;
+                continue;
+            }
+            ("END", _) if current.is_some() && nested_depth > 0 => {
+                nested_depth -= 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        let Some(event) = current.as_mut().filter(|_| nested_depth == 0) else {
+            continue;
+        };
+        let text = || Some(unescape_text(value)).filter(|text| !text.trim().is_empty());
+
+        match name.as_str() {
+            "UID" => event.uid = text(),
+            "SUMMARY" => event.summary = text(),
+            "DESCRIPTION" => event.description = text(),
+            "LOCATION" => event.location = text(),
+            "URL" => event.url = text(),
+            "PRIORITY" => event.priority = value.trim().parse().ok().filter(|p| *p > 0),
+            "STATUS" => event.cancelled = value.trim().eq_ignore_ascii_case("CANCELLED"),
+            "CATEGORIES" => event
+                .categories
+                .extend(value.split(',').map(|c| unescape_text(c.trim())).filter(|c| !c.is_empty())),
+            "DTSTART" => {
+                if let Some((date, time)) = parse_ical_datetime(value) {
+                    let all_day = params.iter().any(|(key, value)| key == "VALUE" && value.eq_ignore_ascii_case("DATE"));
+                    event.start_date = Some(date);
+                    event.start_time = if all_day { None } else { time };
+                }
+            }
+            "DTEND" => end = parse_ical_datetime(value),
+            "DURATION" => {
+                event.duration_minutes = parse_ical_duration(value)
+                    .filter(|_| event.start_time.is_some())
+                    .and_then(|minutes| i32::try_from(minutes).ok());
+            }
+            _ => {}
+        }
+    }
+
+    Ok(events)
+}
+
+/// Minutes between a timed event's start and DTEND
+fn timed_duration(event: &IcsEvent, end: Option<(NaiveDate, Option<NaiveTime>)>) -> Option<i32> {
+    let start = event.start_date?.and_time(event.start_time?);
+    let (end_date, end_time) = end?;
+    let minutes = (end_date.and_time(end_time?) - start).num_minutes();
+    i32::try_from(minutes).ok().filter(|minutes| *minutes > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EventType, Sport, WorkoutDay, WorkoutType, IntensityZone, PowerTargets, PhaseType, IntensityDistribution};
+
+    fn timestamp() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-03-01T12:00:00Z").unwrap().with_timezone(&Utc)
+    }
+
+    fn race() -> Event {
+        Event {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            name: "Spring Classic, 120km".to_string(),
+            description: Some("A-race".to_string()),
+            event_type: EventType::Race,
+            sport: Sport::Cycling,
+            event_date: NaiveDate::from_ymd_opt(2024, 5, 12).unwrap(),
+            event_time: Some(NaiveTime::from_hms_opt(8, 30, 0).unwrap()),
+            location: Some("Ghent; Belgium".to_string()),
+            distance: Some(120.0),
+            distance_unit: Some("km".to_string()),
+            elevation_gain: None,
+            expected_duration: Some(210),
+            registration_deadline: None,
+            cost: None,
+            website_url: None,
+            notes: None,
+            status: EventStatus::Registered,
+            priority: EventPriority::Critical,
+            created_at: timestamp(),
+            updated_at: timestamp() + Duration::seconds(42),
+        }
+    }
+
+    #[test]
+    fn test_event_entry_is_timed_with_priority() {
+        let entry = event_entry(&race());
+
+        assert_eq!(entry.uid, format!("event-{}@ai-coach", Uuid::nil()));
+        assert_eq!(entry.priority, Some(1));
+        assert_eq!(entry.status, Some("CONFIRMED"));
+        assert_eq!(entry.sequence, 42);
+        assert_eq!(
+            entry.time,
+            EntryTime::Timed {
+                start: NaiveDate::from_ymd_opt(2024, 5, 12).unwrap().and_hms_opt(8, 30, 0).unwrap(),
+                end: NaiveDate::from_ymd_opt(2024, 5, 12).unwrap().and_hms_opt(12, 0, 0).unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_render_escapes_and_folds() {
+        let mut entry = event_entry(&race());
+        entry.description = Some("x".repeat(200));
+        let ics = render_calendar("AI Coach Training", &[entry], timestamp());
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("SUMMARY:Spring Classic\\, 120km\r\n"));
+        assert!(ics.contains("LOCATION:Ghent\\; Belgium\r\n"));
+        assert!(ics.contains("DTSTART:20240512T083000\r\n"));
+        assert!(ics.contains("DTSTAMP:20240301T120000Z\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+    }
+
+    #[test]
+    fn test_plan_entries_dates_and_uids() {
+        let plan = FeedPlanRow {
+            id: Uuid::nil(),
+            plan_name: "Spring Build".to_string(),
+            // A Wednesday: week 1 covers Wed-Sun only
+            start_date: NaiveDate::from_ymd_opt(2024, 3, 6).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+            plan_structure: serde_json::json!([]),
+            created_at: timestamp(),
+            updated_at: timestamp(),
+        };
+        let day = |day_of_week| WorkoutDay {
+            day_of_week,
+            workout_type: WorkoutType::Threshold,
+            duration_minutes: 75,
+            intensity_zone: IntensityZone::Zone4,
+            workout_description: "Lactate threshold intervals".to_string(),
+            power_targets: Some(PowerTargets {
+                ftp_percentage_low: 95.0,
+                ftp_percentage_high: 105.0,
+                average_watts: None,
+                normalized_power: None,
+            }),
+            heart_rate_targets: None,
+            pace_targets: None,
+            equipment_needed: vec![],
+            notes: None,
+        };
+        let week = PlanWeekStructure {
+            week_number: 1,
+            phase_name: "Base Building".to_string(),
+            weekly_volume: 5.0,
+            weekly_intensity: 0.6,
+            workout_days: vec![day(1), day(5), day(5)],
+            rest_days: vec![6],
+            week_goals: vec![],
+            key_sessions: vec![],
+        };
+
+        let entries = plan_entries(&plan, &[week]);
+
+        // Monday falls before the plan starts
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].uid, format!("plan-{}-w1-d5@ai-coach", Uuid::nil()));
+        assert_eq!(entries[1].uid, format!("plan-{}-w1-d5-2@ai-coach", Uuid::nil()));
+        assert_eq!(entries[0].summary, "Threshold · 75 min");
+        assert!(entries[0].description.as_deref().unwrap().contains("Power: 95-105% FTP"));
+        assert_eq!(
+            entries[0].time,
+            EntryTime::AllDay {
+                start: NaiveDate::from_ymd_opt(2024, 3, 8).unwrap(),
+                end: NaiveDate::from_ymd_opt(2024, 3, 9).unwrap(),
+            }
+        );
+        assert_eq!(entries[2].summary, "Rest day");
+    }
+
+    #[test]
+    fn test_phase_entries_span_phase() {
+        let plan = EventPlan {
+            id: Uuid::nil(),
+            event_id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            training_phases: serde_json::json!([]),
+            peak_date: NaiveDate::from_ymd_opt(2024, 5, 12).unwrap(),
+            taper_start_date: NaiveDate::from_ymd_opt(2024, 4, 29).unwrap(),
+            base_training_weeks: 0,
+            build_training_weeks: 0,
+            peak_training_weeks: 0,
+            taper_weeks: 2,
+            recovery_weeks: 0,
+            travel_considerations: None,
+            logistics_notes: None,
+            equipment_checklist: None,
+            nutrition_plan: None,
+            created_at: timestamp(),
+            updated_at: timestamp(),
+        };
+        let taper = TrainingPhase {
+            phase_name: "Taper".to_string(),
+            phase_type: PhaseType::Taper,
+            start_date: NaiveDate::from_ymd_opt(2024, 4, 29).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2024, 5, 11).unwrap(),
+            weeks: 2,
+            weekly_volume_range: (4.0, 6.0),
+            intensity_distribution: IntensityDistribution {
+                zone1_percentage: 60.0,
+                zone2_percentage: 20.0,
+                zone3_percentage: 5.0,
+                zone4_percentage: 10.0,
+                zone5_percentage: 5.0,
+                zone6_percentage: 0.0,
+            },
+            focus_areas: vec!["Freshness".to_string()],
+            key_workouts: vec![],
+        };
+
+        let entries = phase_entries(&plan, "Spring Classic", &[taper]);
+
+        assert_eq!(entries[0].summary, "Taper phase: Taper (Spring Classic)");
+        assert_eq!(
+            entries[0].time,
+            EntryTime::AllDay {
+                start: NaiveDate::from_ymd_opt(2024, 4, 29).unwrap(),
+                end: NaiveDate::from_ymd_opt(2024, 5, 12).unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_ics_round_trips_rendered_feed() {
+        let ics = render_calendar("AI Coach Training", &[event_entry(&race())], timestamp());
+        let events = parse_ics(&ics).unwrap();
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary.as_deref(), Some("Spring Classic, 120km"));
+        assert_eq!(events[0].location.as_deref(), Some("Ghent; Belgium"));
+        assert_eq!(events[0].start_time, NaiveTime::from_hms_opt(8, 30, 0));
+        assert_eq!(events[0].duration_minutes, Some(210));
+        assert_eq!(events[0].priority, Some(1));
+    }
+
+    #[test]
+    fn test_parse_ics_race_calendar() {
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+            BEGIN:VEVENT\r\nUID:abc-123@races.example\r\nSUMMARY:City Marathon\r\n\
+            DTSTART;VALUE=DATE:20241020\r\nDTEND;VALUE=DATE:20241021\r\nLOCATION:Amsterdam\r\n\
+            DESCRIPTION:Line one\\nline two that is long enough to be folded across two content\r\n  lines\r\n\
+            BEGIN:VALARM\r\nDESCRIPTION:Reminder\r\nEND:VALARM\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nSUMMARY:Gravel Fondo\r\nDTSTART;TZID=Europe/Brussels:20240908T090000\r\n\
+            DURATION:PT4H30M\r\nSTATUS:CANCELLED\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        let events = parse_ics(ics).unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].uid.as_deref(), Some("abc-123@races.example"));
+        assert_eq!(events[0].start_time, None);
+        assert_eq!(events[0].duration_minutes, None);
+        assert_eq!(
+            events[0].description.as_deref(),
+            Some("Line one\nline two that is long enough to be folded across two content lines")
+        );
+        assert_eq!(guess_sport(&events[0]), "running");
+
+        assert_eq!(events[1].start_time, NaiveTime::from_hms_opt(9, 0, 0));
+        assert_eq!(events[1].duration_minutes, Some(270));
+        assert!(events[1].cancelled);
+        assert_eq!(guess_sport(&events[1]), "cycling");
+
+        assert!(parse_ics("not a calendar").is_err());
+    }
+}
@@ -0,0 +1,361 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::models::{
+    CoachAccessAudit, CoachAthlete, CoachAthleteSummary, CoachPermissions, CoachScope,
+};
+
+const RELATIONSHIP_COLUMNS: &str = r#"
+    id, coach_id, athlete_id, status, can_read_training, can_read_recovery, can_edit_plans,
+    invited_at, responded_at, revoked_at
+"#;
+
+const SUMMARY_QUERY: &str = r#"
+    SELECT ca.id, ca.coach_id, coach.email AS coach_email, ca.athlete_id, athlete.email AS athlete_email,
+           ca.status, ca.can_read_training, ca.can_read_recovery, ca.can_edit_plans,
+           ca.invited_at, ca.responded_at
+    FROM coach_athletes ca
+    JOIN users coach ON coach.id = ca.coach_id
+    JOIN users athlete ON athlete.id = ca.athlete_id
+"#;
+
+/// Coach rosters: invitations, per-scope permissions and the access audit trail
+#[derive(Clone)]
+pub struct CoachAthleteService {
+    db: PgPool,
+}
+
+impl CoachAthleteService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Invite an athlete by email. Re-inviting after a decline or revocation reopens the invitation;
+    /// an active relationship is left unchanged. Emails without an account get an invitation that
+    /// is resolved when someone signs up with them, so callers can't tell the two cases apart.
+    pub async fn invite_athlete(
+        &self,
+        coach_id: Uuid,
+        athlete_email: &str,
+        permissions: CoachPermissions,
+    ) -> Result<()> {
+        let athlete_email = athlete_email.trim();
+        let athlete_id = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE LOWER(email) = LOWER($1)")
+            .bind(athlete_email)
+            .fetch_optional(&self.db)
+            .await?;
+
+        let Some(athlete_id) = athlete_id else {
+            sqlx::query(
+                r#"
+                INSERT INTO coach_invitations (coach_id, email, can_read_training, can_read_recovery, can_edit_plans)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (coach_id, LOWER(email)) DO UPDATE
+                SET can_read_training = EXCLUDED.can_read_training,
+                    can_read_recovery = EXCLUDED.can_read_recovery,
+                    can_edit_plans = EXCLUDED.can_edit_plans,
+                    invited_at = NOW()
+                "#,
+            )
+            .bind(coach_id)
+            .bind(athlete_email)
+            .bind(permissions.can_read_training)
+            .bind(permissions.can_read_recovery)
+            .bind(permissions.can_edit_plans)
+            .execute(&self.db)
+            .await?;
+
+            info!("Coach {} invited an email without an account", coach_id);
+            return Ok(());
+        };
+
+        if athlete_id == coach_id {
+            return Err(anyhow::anyhow!("Coaches cannot invite themselves"));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO coach_athletes (coach_id, athlete_id, can_read_training, can_read_recovery, can_edit_plans)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (coach_id, athlete_id) DO UPDATE
+            SET status = CASE WHEN coach_athletes.status = 'active' THEN 'active' ELSE 'pending' END,
+                can_read_training = CASE WHEN coach_athletes.status = 'active'
+                    THEN coach_athletes.can_read_training ELSE EXCLUDED.can_read_training END,
+                can_read_recovery = CASE WHEN coach_athletes.status = 'active'
+                    THEN coach_athletes.can_read_recovery ELSE EXCLUDED.can_read_recovery END,
+                can_edit_plans = CASE WHEN coach_athletes.status = 'active'
+                    THEN coach_athletes.can_edit_plans ELSE EXCLUDED.can_edit_plans END,
+                invited_at = CASE WHEN coach_athletes.status = 'active'
+                    THEN coach_athletes.invited_at ELSE NOW() END,
+                responded_at = CASE WHEN coach_athletes.status = 'active'
+                    THEN coach_athletes.responded_at ELSE NULL END,
+                revoked_at = CASE WHEN coach_athletes.status = 'active'
+                    THEN coach_athletes.revoked_at ELSE NULL END
+            "#,
+        )
+        .bind(coach_id)
+        .bind(athlete_id)
+        .bind(permissions.can_read_training)
+        .bind(permissions.can_read_recovery)
+        .bind(permissions.can_edit_plans)
+        .execute(&self.db)
+        .await?;
+
+        info!("Coach {} invited athlete {}", coach_id, athlete_id);
+        Ok(())
+    }
+
+    /// Turn the invitations sent to a new user's email into pending invitations they can accept
+    /// or decline. Returns the invitations resolved.
+    pub async fn resolve_email_invitations(&self, user_id: Uuid, email: &str) -> Result<u64> {
+        let mut tx = self.db.begin().await?;
+
+        let resolved = sqlx::query(
+            r#"
+            INSERT INTO coach_athletes (
+                coach_id, athlete_id, can_read_training, can_read_recovery, can_edit_plans, invited_at
+            )
+            SELECT coach_id, $1, can_read_training, can_read_recovery, can_edit_plans, invited_at
+            FROM coach_invitations
+            WHERE LOWER(email) = LOWER($2) AND coach_id <> $1
+            ON CONFLICT (coach_id, athlete_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(email.trim())
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query("DELETE FROM coach_invitations WHERE LOWER(email) = LOWER($1)")
+            .bind(email.trim())
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        if resolved > 0 {
+            info!("Resolved {} coach invitations for new user {}", resolved, user_id);
+        }
+        Ok(resolved)
+    }
+
+    /// Withdraw an invitation the athlete hasn't answered yet, by its roster entry ID. Returns
+    /// false if the coach has no such pending invitation.
+    pub async fn cancel_invitation(&self, coach_id: Uuid, invitation_id: Uuid) -> Result<bool> {
+        let deleted = sqlx::query("DELETE FROM coach_invitations WHERE id = $1 AND coach_id = $2")
+            .bind(invitation_id)
+            .bind(coach_id)
+            .execute(&self.db)
+            .await?
+            .rows_affected();
+
+        if deleted > 0 {
+            return Ok(true);
+        }
+
+        let revoked = sqlx::query(
+            r#"
+            UPDATE coach_athletes
+            SET status = 'revoked', revoked_at = NOW()
+            WHERE id = $1 AND coach_id = $2 AND status = 'pending'
+            "#,
+        )
+        .bind(invitation_id)
+        .bind(coach_id)
+        .execute(&self.db)
+        .await?
+        .rows_affected();
+
+        Ok(revoked > 0)
+    }
+
+    /// Accept or decline a pending invitation addressed to `athlete_id`
+    pub async fn respond_to_invitation(
+        &self,
+        athlete_id: Uuid,
+        relationship_id: Uuid,
+        accept: bool,
+    ) -> Result<Option<CoachAthlete>> {
+        let relationship = sqlx::query_as::<_, CoachAthlete>(&format!(
+            r#"
+            UPDATE coach_athletes
+            SET status = $3, responded_at = NOW()
+            WHERE id = $1 AND athlete_id = $2 AND status = 'pending'
+            RETURNING {}
+            "#,
+            RELATIONSHIP_COLUMNS
+        ))
+        .bind(relationship_id)
+        .bind(athlete_id)
+        .bind(if accept { "active" } else { "declined" })
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(relationship)
+    }
+
+    /// Change what a coach may access; only the athlete can do this
+    pub async fn update_permissions(
+        &self,
+        athlete_id: Uuid,
+        relationship_id: Uuid,
+        permissions: CoachPermissions,
+    ) -> Result<Option<CoachAthlete>> {
+        let relationship = sqlx::query_as::<_, CoachAthlete>(&format!(
+            r#"
+            UPDATE coach_athletes
+            SET can_read_training = $3, can_read_recovery = $4, can_edit_plans = $5
+            WHERE id = $1 AND athlete_id = $2 AND status IN ('pending', 'active')
+            RETURNING {}
+            "#,
+            RELATIONSHIP_COLUMNS
+        ))
+        .bind(relationship_id)
+        .bind(athlete_id)
+        .bind(permissions.can_read_training)
+        .bind(permissions.can_read_recovery)
+        .bind(permissions.can_edit_plans)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(relationship)
+    }
+
+    /// End a relationship; either the coach or the athlete may revoke it
+    pub async fn revoke(&self, user_id: Uuid, relationship_id: Uuid) -> Result<Option<CoachAthlete>> {
+        let relationship = sqlx::query_as::<_, CoachAthlete>(&format!(
+            r#"
+            UPDATE coach_athletes
+            SET status = 'revoked', revoked_at = NOW()
+            WHERE id = $1 AND (coach_id = $2 OR athlete_id = $2) AND status IN ('pending', 'active')
+            RETURNING {}
+            "#,
+            RELATIONSHIP_COLUMNS
+        ))
+        .bind(relationship_id)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        if let Some(relationship) = &relationship {
+            info!(
+                "Coach relationship {} between coach {} and athlete {} revoked by {}",
+                relationship.id, relationship.coach_id, relationship.athlete_id, user_id
+            );
+        }
+
+        Ok(relationship)
+    }
+
+    /// Revoke by coach and athlete IDs, as used by the coach-facing endpoints
+    pub async fn revoke_athlete(&self, coach_id: Uuid, athlete_id: Uuid) -> Result<Option<CoachAthlete>> {
+        match self.get_relationship(coach_id, athlete_id).await? {
+            Some(relationship) => self.revoke(coach_id, relationship.id).await,
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_relationship(&self, coach_id: Uuid, athlete_id: Uuid) -> Result<Option<CoachAthlete>> {
+        let relationship = sqlx::query_as::<_, CoachAthlete>(&format!(
+            "SELECT {} FROM coach_athletes WHERE coach_id = $1 AND athlete_id = $2",
+            RELATIONSHIP_COLUMNS
+        ))
+        .bind(coach_id)
+        .bind(athlete_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(relationship)
+    }
+
+    /// A coach's active athletes and open invitations, including ones to emails without an
+    /// account. Athlete IDs are only shown once the invitation is accepted.
+    pub async fn list_athletes(&self, coach_id: Uuid) -> Result<Vec<CoachAthleteSummary>> {
+        let athletes = sqlx::query_as::<_, CoachAthleteSummary>(
+            r#"
+            SELECT ca.id, ca.coach_id, coach.email AS coach_email,
+                   CASE WHEN ca.status = 'active' THEN ca.athlete_id END AS athlete_id,
+                   athlete.email AS athlete_email, ca.status, ca.can_read_training,
+                   ca.can_read_recovery, ca.can_edit_plans, ca.invited_at, ca.responded_at
+            FROM coach_athletes ca
+            JOIN users coach ON coach.id = ca.coach_id
+            JOIN users athlete ON athlete.id = ca.athlete_id
+            WHERE ca.coach_id = $1 AND ca.status IN ('pending', 'active')
+            UNION ALL
+            SELECT ci.id, ci.coach_id, coach.email, NULL, ci.email, 'pending', ci.can_read_training,
+                   ci.can_read_recovery, ci.can_edit_plans, ci.invited_at, NULL
+            FROM coach_invitations ci
+            JOIN users coach ON coach.id = ci.coach_id
+            WHERE ci.coach_id = $1
+            ORDER BY athlete_email
+            "#,
+        )
+        .bind(coach_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(athletes)
+    }
+
+    /// An athlete's coaches and pending invitations
+    pub async fn list_coaches(&self, athlete_id: Uuid) -> Result<Vec<CoachAthleteSummary>> {
+        let coaches = sqlx::query_as::<_, CoachAthleteSummary>(&format!(
+            "{} WHERE ca.athlete_id = $1 AND ca.status IN ('pending', 'active') ORDER BY ca.invited_at DESC",
+            SUMMARY_QUERY
+        ))
+        .bind(athlete_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(coaches)
+    }
+
+    /// Record that a coach accessed an athlete's data
+    pub async fn record_access(
+        &self,
+        coach_id: Uuid,
+        athlete_id: Uuid,
+        scope: CoachScope,
+        action: &str,
+        resource_id: Option<Uuid>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO coach_access_audit (coach_id, athlete_id, scope, action, resource_id)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(coach_id)
+        .bind(athlete_id)
+        .bind(scope.as_str())
+        .bind(action)
+        .bind(resource_id)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Accesses to an athlete's data, newest first
+    pub async fn get_access_log(&self, athlete_id: Uuid, limit: i64, offset: i64) -> Result<Vec<CoachAccessAudit>> {
+        let entries = sqlx::query_as::<_, CoachAccessAudit>(
+            r#"
+            SELECT id, coach_id, athlete_id, scope, action, resource_id, created_at
+            FROM coach_access_audit
+            WHERE athlete_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(athlete_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(entries)
+    }
+}
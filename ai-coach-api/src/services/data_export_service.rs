@@ -0,0 +1,648 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{NaiveDate, Utc};
+use serde_json::{Map, Value};
+use sqlx::PgPool;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::models::{CreateExportRequest, DataExport, ExportDataset, ExportFormat, ExportStatus};
+use crate::services::TrainingAnalysisService;
+
+/// Days of load before the export range used to warm up CTL/ATL
+const PMC_WARMUP_DAYS: i64 = 42;
+const DEFAULT_RETENTION_HOURS: i64 = 72;
+
+const EXPORT_COLUMNS: &str = r#"
+    id, user_id, format, datasets, start_date, end_date, status, job_id, storage_key, file_name,
+    size_bytes, error_message, created_at, completed_at, expires_at
+"#;
+
+/// Records gathered for one dataset
+#[derive(Debug, Clone)]
+pub struct ExportedDataset {
+    pub dataset: ExportDataset,
+    pub records: Vec<Value>,
+}
+
+/// Gathers user data into CSV, JSON-lines or zip files and keeps them until they expire
+#[derive(Clone)]
+pub struct DataExportService {
+    db: PgPool,
+    training_analysis_service: TrainingAnalysisService,
+    export_dir: PathBuf,
+    retention: chrono::Duration,
+}
+
+impl DataExportService {
+    /// Create a new DataExportService storing files under `EXPORT_DIR` (default `exports`)
+    pub fn new(db: PgPool) -> Result<Self> {
+        let training_analysis_service = TrainingAnalysisService::new(db.clone(), None)?;
+        let export_dir = std::env::var("EXPORT_DIR").unwrap_or_else(|_| "exports".to_string());
+        let retention_hours = std::env::var("EXPORT_RETENTION_HOURS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_RETENTION_HOURS);
+
+        Ok(Self {
+            db,
+            training_analysis_service,
+            export_dir: PathBuf::from(export_dir),
+            retention: chrono::Duration::hours(retention_hours),
+        })
+    }
+
+    /// Record a pending export; the file is rendered by a background job
+    pub async fn create_export(
+        &self,
+        user_id: Uuid,
+        request: &CreateExportRequest,
+    ) -> Result<DataExport> {
+        let datasets = resolve_datasets(request)?;
+        let datasets: Vec<String> = datasets.iter().map(|d| d.as_str().to_string()).collect();
+
+        let export = sqlx::query_as::<_, DataExport>(&format!(
+            r#"
+            INSERT INTO data_exports (user_id, format, datasets, start_date, end_date)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING {}
+            "#,
+            EXPORT_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(request.format.as_str())
+        .bind(&datasets)
+        .bind(request.start_date)
+        .bind(request.end_date)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(export)
+    }
+
+    /// Link an export to the job that renders it
+    pub async fn set_job_id(&self, export_id: Uuid, job_id: Uuid) -> Result<DataExport> {
+        let export = sqlx::query_as::<_, DataExport>(&format!(
+            "UPDATE data_exports SET job_id = $2 WHERE id = $1 RETURNING {}",
+            EXPORT_COLUMNS
+        ))
+        .bind(export_id)
+        .bind(job_id)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(export)
+    }
+
+    pub async fn get_export(&self, export_id: Uuid) -> Result<Option<DataExport>> {
+        let export = sqlx::query_as::<_, DataExport>(&format!(
+            "SELECT {} FROM data_exports WHERE id = $1",
+            EXPORT_COLUMNS
+        ))
+        .bind(export_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(export)
+    }
+
+    /// Get an export only if it belongs to `user_id`
+    pub async fn get_user_export(&self, user_id: Uuid, export_id: Uuid) -> Result<Option<DataExport>> {
+        let export = sqlx::query_as::<_, DataExport>(&format!(
+            "SELECT {} FROM data_exports WHERE id = $1 AND user_id = $2",
+            EXPORT_COLUMNS
+        ))
+        .bind(export_id)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(export)
+    }
+
+    pub async fn list_user_exports(&self, user_id: Uuid) -> Result<Vec<DataExport>> {
+        let exports = sqlx::query_as::<_, DataExport>(&format!(
+            "SELECT {} FROM data_exports WHERE user_id = $1 ORDER BY created_at DESC",
+            EXPORT_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(exports)
+    }
+
+    /// Path of a completed export's file
+    pub fn file_path(&self, export: &DataExport) -> Result<PathBuf> {
+        let storage_key = export
+            .storage_key
+            .as_deref()
+            .ok_or_else(|| anyhow!("Export {} has no file", export.id))?;
+
+        self.resolve(storage_key)
+    }
+
+    /// Gather, render and store an export. Failures are recorded on the export and returned so the job retries.
+    pub async fn generate_export(&self, export_id: Uuid) -> Result<()> {
+        let export = self
+            .get_export(export_id)
+            .await?
+            .ok_or_else(|| anyhow!("Export {} not found", export_id))?;
+
+        if export.status == ExportStatus::Completed.as_str() {
+            info!("Export {} already completed", export_id);
+            return Ok(());
+        }
+
+        self.set_status(export_id, ExportStatus::Processing, None).await?;
+
+        match self.write_export(&export).await {
+            Ok((storage_key, file_name, size_bytes)) => {
+                sqlx::query(
+                    r#"
+                    UPDATE data_exports
+                    SET status = 'completed', storage_key = $2, file_name = $3, size_bytes = $4,
+                        error_message = NULL, completed_at = NOW(), expires_at = $5
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(export_id)
+                .bind(&storage_key)
+                .bind(&file_name)
+                .bind(size_bytes)
+                .bind(Utc::now() + self.retention)
+                .execute(&self.db)
+                .await?;
+
+                info!("Export {} completed: {} ({} bytes)", export_id, storage_key, size_bytes);
+                Ok(())
+            }
+            Err(e) => {
+                self.set_status(export_id, ExportStatus::Failed, Some(&e.to_string())).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete the files of expired exports and mark them expired
+    pub async fn purge_expired(&self) -> Result<u64> {
+        let expired = sqlx::query_as::<_, DataExport>(&format!(
+            "SELECT {} FROM data_exports WHERE status = 'completed' AND expires_at <= NOW()",
+            EXPORT_COLUMNS
+        ))
+        .fetch_all(&self.db)
+        .await?;
+
+        for export in &expired {
+            if let Ok(path) = self.file_path(export) {
+                match tokio::fs::remove_file(&path).await {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => {
+                        warn!("Failed to delete export file {}: {}", path.display(), e);
+                        continue;
+                    }
+                }
+            }
+
+            sqlx::query("UPDATE data_exports SET status = 'expired', storage_key = NULL WHERE id = $1")
+                .bind(export.id)
+                .execute(&self.db)
+                .await?;
+        }
+
+        if !expired.is_empty() {
+            info!("Purged {} expired data exports", expired.len());
+        }
+
+        Ok(expired.len() as u64)
+    }
+
+    async fn set_status(&self, export_id: Uuid, status: ExportStatus, error: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE data_exports SET status = $2, error_message = $3 WHERE id = $1")
+            .bind(export_id)
+            .bind(status.as_str())
+            .bind(error)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Render the export to disk, returning its storage key, download file name and size
+    async fn write_export(&self, export: &DataExport) -> Result<(String, String, i64)> {
+        let format = export.export_format()?;
+        let mut datasets = Vec::new();
+
+        for dataset in export.export_datasets()? {
+            let records = self
+                .gather(dataset, export.user_id, export.start_date, export.end_date)
+                .await
+                .with_context(|| format!("Failed to gather {} data", dataset.as_str()))?;
+            datasets.push(ExportedDataset { dataset, records });
+        }
+
+        let manifest = serde_json::json!({
+            "export_id": export.id,
+            "user_id": export.user_id,
+            "start_date": export.start_date,
+            "end_date": export.end_date,
+            "generated_at": Utc::now(),
+            "datasets": datasets
+                .iter()
+                .map(|d| serde_json::json!({ "name": d.dataset.as_str(), "records": d.records.len() }))
+                .collect::<Vec<_>>(),
+        });
+
+        let contents = render_export(format, &datasets, &manifest)?;
+
+        let storage_key = format!("{}/{}.{}", export.user_id, export.id, format.as_str());
+        let path = self.resolve(&storage_key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Failed to create export directory {}", parent.display()))?;
+        }
+        tokio::fs::write(&path, &contents)
+            .await
+            .with_context(|| format!("Failed to write export file {}", path.display()))?;
+
+        let file_name = export_file_name(format, &datasets, export.created_at.date_naive());
+        Ok((storage_key, file_name, contents.len() as i64))
+    }
+
+    async fn gather(
+        &self,
+        dataset: ExportDataset,
+        user_id: Uuid,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+    ) -> Result<Vec<Value>> {
+        let Some(query) = dataset_query(dataset) else {
+            return self.gather_pmc(user_id, start_date, end_date).await;
+        };
+
+        let rows = sqlx::query_scalar::<_, String>(&format!(
+            "SELECT row_to_json(t)::text FROM ({}) t",
+            query
+        ))
+        .bind(user_id)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.db)
+        .await?;
+
+        rows.iter()
+            .map(|row| serde_json::from_str(row).map_err(|e| anyhow!("Invalid row JSON: {}", e)))
+            .collect()
+    }
+
+    /// PMC is derived rather than stored, so compute it from the first session (or range start) with warm-up
+    async fn gather_pmc(
+        &self,
+        user_id: Uuid,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+    ) -> Result<Vec<Value>> {
+        let first_date = match start_date {
+            Some(date) => Some(date),
+            None => {
+                sqlx::query_scalar::<_, Option<NaiveDate>>(
+                    "SELECT MIN(date) FROM training_sessions WHERE user_id = $1",
+                )
+                .bind(user_id)
+                .fetch_one(&self.db)
+                .await?
+            }
+        };
+
+        let Some(first_date) = first_date else {
+            return Ok(Vec::new());
+        };
+
+        let today = Utc::now().date_naive();
+        if first_date > today {
+            return Ok(Vec::new());
+        }
+
+        let days = (today - first_date).num_days() + PMC_WARMUP_DAYS + 1;
+        let pmc = self
+            .training_analysis_service
+            .calculate_pmc(user_id, days.min(i32::MAX as i64) as i32)
+            .await?;
+
+        pmc.into_iter()
+            .filter(|point| point.date >= first_date && end_date.is_none_or(|end| point.date <= end))
+            .map(|point| serde_json::to_value(point).map_err(Into::into))
+            .collect()
+    }
+
+    /// Resolve a storage key to a path, refusing keys that escape the export directory
+    fn resolve(&self, storage_key: &str) -> Result<PathBuf> {
+        let relative = Path::new(storage_key);
+        if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(anyhow!("Invalid storage key: {}", storage_key));
+        }
+
+        Ok(self.export_dir.join(relative))
+    }
+}
+
+/// Datasets requested for an export; CSV holds a single table so it takes exactly one dataset
+pub fn resolve_datasets(request: &CreateExportRequest) -> Result<Vec<ExportDataset>> {
+    let mut datasets = match &request.datasets {
+        Some(datasets) if datasets.is_empty() => return Err(anyhow!("At least one dataset is required")),
+        Some(datasets) => datasets.clone(),
+        None => ExportDataset::ALL.to_vec(),
+    };
+    let mut seen = std::collections::HashSet::new();
+    datasets.retain(|dataset| seen.insert(*dataset));
+
+    if request.format == ExportFormat::Csv && datasets.len() != 1 {
+        return Err(anyhow!(
+            "CSV exports contain a single dataset; use the zip format to export several"
+        ));
+    }
+
+    if let (Some(start), Some(end)) = (request.start_date, request.end_date) {
+        if start > end {
+            return Err(anyhow!("start_date must not be after end_date"));
+        }
+    }
+
+    Ok(datasets)
+}
+
+/// Row query for a stored dataset, `None` for PMC. Binds: $1 user_id, $2 start date, $3 end date (both optional).
+fn dataset_query(dataset: ExportDataset) -> Option<&'static str> {
+    let query = match dataset {
+        ExportDataset::Sessions => {
+            r#"
+            SELECT id, date, session_type, duration_seconds, distance_meters, trainrs_data,
+                   uploaded_file_path, created_at, updated_at
+            FROM training_sessions
+            WHERE user_id = $1 AND ($2::date IS NULL OR date >= $2) AND ($3::date IS NULL OR date <= $3)
+            ORDER BY date, created_at
+            "#
+        }
+        ExportDataset::Pmc => return None,
+        ExportDataset::Goals => {
+            r#"
+            SELECT id, title, description, goal_type, goal_category, target_value, current_value, unit,
+                   target_date, status, priority, event_id, parent_goal_id, created_at, updated_at
+            FROM goals
+            WHERE user_id = $1
+              AND ($2::date IS NULL OR created_at::date >= $2) AND ($3::date IS NULL OR created_at::date <= $3)
+            ORDER BY created_at
+            "#
+        }
+        ExportDataset::Events => {
+            r#"
+            SELECT id, name, description, event_type, sport, event_date, event_time, location, distance,
+                   distance_unit, elevation_gain, expected_duration, registration_deadline, cost,
+                   website_url, notes, status, priority, created_at, updated_at
+            FROM events
+            WHERE user_id = $1
+              AND ($2::date IS NULL OR event_date >= $2) AND ($3::date IS NULL OR event_date <= $3)
+            ORDER BY event_date
+            "#
+        }
+        ExportDataset::Hrv => {
+            r#"
+            SELECT id, measurement_date, measurement_timestamp, rmssd, sdnn, pnn50, source, metadata, created_at
+            FROM hrv_readings
+            WHERE user_id = $1
+              AND ($2::date IS NULL OR measurement_date >= $2) AND ($3::date IS NULL OR measurement_date <= $3)
+            ORDER BY measurement_timestamp
+            "#
+        }
+        ExportDataset::Sleep => {
+            r#"
+            SELECT id, sleep_date, total_sleep_hours, deep_sleep_hours, rem_sleep_hours, light_sleep_hours,
+                   awake_hours, sleep_efficiency, sleep_latency_minutes, bedtime, wake_time, source,
+                   metadata, created_at
+            FROM sleep_data
+            WHERE user_id = $1
+              AND ($2::date IS NULL OR sleep_date >= $2) AND ($3::date IS NULL OR sleep_date <= $3)
+            ORDER BY sleep_date
+            "#
+        }
+        ExportDataset::RestingHr => {
+            r#"
+            SELECT id, measurement_date, measurement_timestamp, resting_hr, source, metadata, created_at
+            FROM resting_hr_data
+            WHERE user_id = $1
+              AND ($2::date IS NULL OR measurement_date >= $2) AND ($3::date IS NULL OR measurement_date <= $3)
+            ORDER BY measurement_timestamp
+            "#
+        }
+        ExportDataset::Predictions => {
+            r#"
+            SELECT id, prediction_type, data, confidence, model_version, created_at, updated_at
+            FROM model_predictions
+            WHERE user_id = $1
+              AND ($2::date IS NULL OR created_at::date >= $2) AND ($3::date IS NULL OR created_at::date <= $3)
+            ORDER BY created_at
+            "#
+        }
+    };
+
+    Some(query)
+}
+
+/// Render gathered datasets in the requested format
+pub fn render_export(format: ExportFormat, datasets: &[ExportedDataset], manifest: &Value) -> Result<Vec<u8>> {
+    match format {
+        ExportFormat::Csv => {
+            let [dataset] = datasets else {
+                return Err(anyhow!("CSV exports contain exactly one dataset, got {}", datasets.len()));
+            };
+            render_csv(&dataset.records)
+        }
+        ExportFormat::Jsonl => render_jsonl(datasets),
+        ExportFormat::Zip => render_zip(datasets, manifest),
+    }
+}
+
+/// One `{"dataset": ..., "data": ...}` object per line
+pub fn render_jsonl(datasets: &[ExportedDataset]) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+
+    for dataset in datasets {
+        for record in &dataset.records {
+            let line = serde_json::json!({ "dataset": dataset.dataset.as_str(), "data": record });
+            serde_json::to_writer(&mut output, &line)?;
+            output.push(b'\n');
+        }
+    }
+
+    Ok(output)
+}
+
+/// Flatten records into a CSV table. Columns are the union of top-level keys in first-seen order;
+/// nested values are written as JSON and nulls as empty cells.
+pub fn render_csv(records: &[Value]) -> Result<Vec<u8>> {
+    let mut columns: Vec<&str> = Vec::new();
+    for record in records {
+        for key in record.as_object().into_iter().flat_map(Map::keys) {
+            if !columns.contains(&key.as_str()) {
+                columns.push(key);
+            }
+        }
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    if columns.is_empty() {
+        return Ok(writer.into_inner()?);
+    }
+
+    writer.write_record(&columns)?;
+    for record in records {
+        let row = columns.iter().map(|column| csv_cell(record.get(*column)));
+        writer.write_record(row)?;
+    }
+
+    writer.into_inner().map_err(|e| anyhow!("Failed to finish CSV: {}", e))
+}
+
+fn csv_cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Zip archive with `manifest.json` and one CSV file per dataset
+pub fn render_zip(datasets: &[ExportedDataset], manifest: &Value) -> Result<Vec<u8>> {
+    let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    archive.start_file("manifest.json", options)?;
+    archive.write_all(&serde_json::to_vec_pretty(manifest)?)?;
+
+    for dataset in datasets {
+        archive.start_file(format!("{}.csv", dataset.dataset.as_str()), options)?;
+        archive.write_all(&render_csv(&dataset.records)?)?;
+    }
+
+    Ok(archive.finish()?.into_inner())
+}
+
+/// Download file name, e.g. `ai-coach-sessions-2024-03-01.csv` or `ai-coach-export-2024-03-01.zip`
+pub fn export_file_name(format: ExportFormat, datasets: &[ExportedDataset], date: NaiveDate) -> String {
+    let label = match datasets {
+        [single] => single.dataset.as_str(),
+        _ => "export",
+    };
+
+    format!("ai-coach-{}-{}.{}", label, date.format("%Y-%m-%d"), format.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn sessions() -> ExportedDataset {
+        ExportedDataset {
+            dataset: ExportDataset::Sessions,
+            records: vec![
+                serde_json::json!({ "date": "2024-03-01", "duration_seconds": 3600, "trainrs_data": { "tss": 55.0 } }),
+                serde_json::json!({ "date": "2024-03-02", "duration_seconds": null, "notes": "Easy, short" }),
+            ],
+        }
+    }
+
+    fn request(format: ExportFormat, datasets: Option<Vec<ExportDataset>>) -> CreateExportRequest {
+        CreateExportRequest {
+            format,
+            datasets,
+            start_date: None,
+            end_date: None,
+        }
+    }
+
+    #[test]
+    fn test_render_csv_unions_columns() {
+        let csv = String::from_utf8(render_csv(&sessions().records).unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "date,duration_seconds,trainrs_data,notes");
+        assert_eq!(lines[1], r#"2024-03-01,3600,"{""tss"":55.0}","#);
+        assert_eq!(lines[2], r#"2024-03-02,,,"Easy, short""#);
+    }
+
+    #[test]
+    fn test_render_csv_empty() {
+        assert!(render_csv(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_render_jsonl_tags_dataset() {
+        let output = String::from_utf8(render_jsonl(&[sessions()]).unwrap()).unwrap();
+        let lines: Vec<Value> = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["dataset"], "sessions");
+        assert_eq!(lines[1]["data"]["notes"], "Easy, short");
+    }
+
+    #[test]
+    fn test_render_zip_contains_manifest_and_datasets() {
+        let goals = ExportedDataset {
+            dataset: ExportDataset::Goals,
+            records: vec![serde_json::json!({ "title": "Sub-3 marathon" })],
+        };
+        let manifest = serde_json::json!({ "datasets": ["sessions", "goals"] });
+        let bytes = render_zip(&[sessions(), goals], &manifest).unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(String::from).collect();
+        names.sort();
+        assert_eq!(names, vec!["goals.csv", "manifest.json", "sessions.csv"]);
+
+        let mut goals_csv = String::new();
+        archive.by_name("goals.csv").unwrap().read_to_string(&mut goals_csv).unwrap();
+        assert_eq!(goals_csv, "title\nSub-3 marathon\n");
+    }
+
+    #[test]
+    fn test_resolve_datasets() {
+        assert_eq!(
+            resolve_datasets(&request(ExportFormat::Zip, None)).unwrap(),
+            ExportDataset::ALL.to_vec()
+        );
+        assert_eq!(
+            resolve_datasets(&request(
+                ExportFormat::Csv,
+                Some(vec![ExportDataset::Hrv, ExportDataset::Hrv])
+            ))
+            .unwrap(),
+            vec![ExportDataset::Hrv]
+        );
+        assert!(resolve_datasets(&request(ExportFormat::Csv, None)).is_err());
+        assert!(resolve_datasets(&request(ExportFormat::Jsonl, Some(vec![]))).is_err());
+
+        let mut reversed = request(ExportFormat::Jsonl, None);
+        reversed.start_date = NaiveDate::from_ymd_opt(2024, 3, 2);
+        reversed.end_date = NaiveDate::from_ymd_opt(2024, 3, 1);
+        assert!(resolve_datasets(&reversed).is_err());
+    }
+
+    #[test]
+    fn test_export_file_name() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+
+        assert_eq!(
+            export_file_name(ExportFormat::Csv, &[sessions()], date),
+            "ai-coach-sessions-2024-03-01.csv"
+        );
+        assert_eq!(
+            export_file_name(ExportFormat::Zip, &[sessions(), sessions()], date),
+            "ai-coach-export-2024-03-01.zip"
+        );
+    }
+}
@@ -5,7 +5,7 @@ use uuid::Uuid;
 
 use crate::models::{Notification, NotificationType, NotificationPreferences};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct EmailNotificationService {
     templates: HashMap<NotificationType, EmailTemplate>,
     smtp_config: SmtpConfig,
@@ -185,6 +185,26 @@ impl EmailNotificationService {
         Ok(())
     }
 
+    /// Email a one-off message that is not part of the notification feed
+    pub async fn send_message_email(
+        &self,
+        user_email: &str,
+        user_name: &str,
+        subject: &str,
+        message: &str,
+    ) -> Result<(), EmailError> {
+        let text_body = format!("Hi {},\n\n{}\n\nBest regards,\nAI Coach Team", user_name, message);
+        let html_body = format!(
+            r#"<html><body><h2>Hi {},</h2><p>{}</p><p>Best regards,<br>AI Coach Team</p></body></html>"#,
+            user_name, message
+        );
+
+        self.send_email(user_email, subject, &text_body, &html_body).await?;
+
+        tracing::info!("Sent email \"{}\" to {}", subject, user_email);
+        Ok(())
+    }
+
     pub async fn send_batch_email(
         &self,
         notifications: &[Notification],
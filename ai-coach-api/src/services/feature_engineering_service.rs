@@ -57,8 +57,11 @@ impl FeatureEngineeringService {
         // Calculate seasonal factors (simplified - could be enhanced with weather data, etc.)
         let seasonal_factors = self.calculate_seasonal_factors(today);
 
-        // Get days until goal event (would need to be implemented based on athlete goals)
-        let days_until_goal_event = self.get_days_until_goal_event(user_id).await?;
+        // Get days until the next high-priority event
+        let days_until_goal_event = self.get_days_until_goal_event(user_id, today).await?;
+
+        // Get today's recovery signals
+        let recovery = self.get_recovery_signals(user_id, today).await?;
 
         Ok(TrainingFeatures {
             current_ctl: pmc.ctl,
@@ -70,6 +73,10 @@ impl FeatureEngineeringService {
             days_until_goal_event,
             preferred_workout_types,
             seasonal_factors,
+            readiness_score: recovery.as_ref().map(|r| r.readiness_score as f32),
+            hrv_trend: recovery.as_ref().and_then(|r| TrainingFeatures::encode_hrv_trend(&r.hrv_trend)),
+            sleep_quality: recovery.as_ref().and_then(|r| r.sleep_quality_score.map(|v| v as f32)),
+            rhr_deviation: recovery.as_ref().and_then(|r| r.rhr_deviation.map(|v| v as f32)),
         })
     }
 
@@ -99,7 +106,7 @@ impl FeatureEngineeringService {
                         .clone()
                         .unwrap_or_else(|| "unknown".to_string()),
                     performance_outcome: None, // Would need to be collected from user feedback
-                    recovery_rating: None,     // Would need to be collected from user feedback
+                    recovery_rating: self.get_recovery_rating(user_id, session.date).await?,
                     workout_date: session.created_at,
                 };
                 data_points.push(data_point);
@@ -110,6 +117,9 @@ impl FeatureEngineeringService {
     }
 
     /// Extract features for a specific date
+    ///
+    /// Load features describe the end of `date`; recovery features come from the
+    /// morning score of the following day, the day being trained.
     async fn extract_features_for_date(&self, user_id: Uuid, date: NaiveDate) -> Result<TrainingFeatures> {
         // Get PMC data for the specific date
         let pmc = self.analysis_service.get_pmc_for_date(user_id, date).await?;
@@ -127,7 +137,9 @@ impl FeatureEngineeringService {
         let recent_performance_trend = self.calculate_performance_trend(&recent_sessions);
         let preferred_workout_types = self.extract_preferred_workout_types(&recent_sessions);
         let seasonal_factors = self.calculate_seasonal_factors(date);
-        let days_until_goal_event = self.get_days_until_goal_event(user_id).await?;
+        let workout_date = date + Duration::days(1);
+        let days_until_goal_event = self.get_days_until_goal_event(user_id, workout_date).await?;
+        let recovery = self.get_recovery_signals(user_id, workout_date).await?;
 
         Ok(TrainingFeatures {
             current_ctl: pmc.ctl,
@@ -139,6 +151,10 @@ impl FeatureEngineeringService {
             days_until_goal_event,
             preferred_workout_types,
             seasonal_factors,
+            readiness_score: recovery.as_ref().map(|r| r.readiness_score as f32),
+            hrv_trend: recovery.as_ref().and_then(|r| TrainingFeatures::encode_hrv_trend(&r.hrv_trend)),
+            sleep_quality: recovery.as_ref().and_then(|r| r.sleep_quality_score.map(|v| v as f32)),
+            rhr_deviation: recovery.as_ref().and_then(|r| r.rhr_deviation.map(|v| v as f32)),
         })
     }
 
@@ -242,11 +258,57 @@ impl FeatureEngineeringService {
         }
     }
 
-    /// Get days until goal event (placeholder implementation)
-    async fn get_days_until_goal_event(&self, _user_id: Uuid) -> Result<Option<i32>> {
-        // This would need to be implemented based on athlete goals/events system
-        // For now, returning None as placeholder
-        Ok(None)
+    /// Get days from `date` until the next high or critical priority event
+    async fn get_days_until_goal_event(&self, user_id: Uuid, date: NaiveDate) -> Result<Option<i32>> {
+        let event_date: Option<NaiveDate> = sqlx::query_scalar(
+            r#"
+            SELECT MIN(event_date) FROM events
+            WHERE user_id = $1
+              AND event_date >= $2
+              AND priority IN ('high', 'critical')
+              AND status NOT IN ('cancelled', 'missed')
+            "#,
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(event_date.map(|event_date| days_between(date, event_date)))
+    }
+
+    /// Get the recovery score for `date`, falling back to the previous day's score
+    /// when today's has not been calculated yet
+    async fn get_recovery_signals(&self, user_id: Uuid, date: NaiveDate) -> Result<Option<RecoverySignals>> {
+        let signals = sqlx::query_as::<_, RecoverySignals>(
+            r#"
+            SELECT readiness_score, hrv_trend, sleep_quality_score, rhr_deviation
+            FROM recovery_scores
+            WHERE user_id = $1 AND score_date BETWEEN $2 AND $3
+            ORDER BY score_date DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(date - Duration::days(1))
+        .bind(date)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(signals)
+    }
+
+    /// Rate recovery after a workout (1-10) from the next day's readiness score
+    async fn get_recovery_rating(&self, user_id: Uuid, workout_date: NaiveDate) -> Result<Option<f32>> {
+        let readiness: Option<f64> = sqlx::query_scalar(
+            "SELECT readiness_score FROM recovery_scores WHERE user_id = $1 AND score_date = $2",
+        )
+        .bind(user_id)
+        .bind(workout_date + Duration::days(1))
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(readiness.map(readiness_to_rating))
     }
 
     /// Batch extract features for multiple users and dates
@@ -304,6 +366,25 @@ impl FeatureEngineeringService {
     }
 }
 
+/// Recovery score columns used as model features
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct RecoverySignals {
+    readiness_score: f64,
+    hrv_trend: String,
+    sleep_quality_score: Option<f64>,
+    rhr_deviation: Option<f64>,
+}
+
+/// Whole days from `from` to `to`
+fn days_between(from: NaiveDate, to: NaiveDate) -> i32 {
+    (to - from).num_days() as i32
+}
+
+/// Map a 0-100 readiness score onto the 1-10 recovery rating scale
+fn readiness_to_rating(readiness: f64) -> f32 {
+    (1.0 + readiness.clamp(0.0, 100.0) * 9.0 / 100.0) as f32
+}
+
 /// Training load statistics for validation and normalization
 #[derive(Debug, Clone)]
 pub struct TrainingLoadStats {
@@ -317,6 +398,7 @@ pub struct TrainingLoadStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::FEATURE_SCHEMA_VERSION;
     use chrono::{Utc, Duration, NaiveDate};
     use sqlx::PgPool;
     use std::env;
@@ -471,12 +553,16 @@ mod tests {
             days_until_goal_event: Some(30),
             preferred_workout_types: vec!["endurance".to_string(), "threshold".to_string()],
             seasonal_factors: 0.8,
+            readiness_score: Some(72.0),
+            hrv_trend: Some(-1.0),
+            sleep_quality: None,
+            rhr_deviation: Some(4.5),
         };
 
         let array = features.to_ndarray();
 
         // Should have correct number of features
-        let expected_length = 8 + 5; // 8 numeric features + 5 workout type one-hot
+        let expected_length = 8 + 5 + 4; // 8 numeric features + 5 workout type one-hot + 4 recovery
         assert_eq!(array.len(), expected_length);
 
         // Check some specific values
@@ -488,6 +574,57 @@ mod tests {
         // Check one-hot encoding for workout types
         assert_eq!(array[8], 1.0);   // endurance (first workout type)
         assert_eq!(array[9], 1.0);   // threshold (second workout type)
+
+        // Check recovery features, missing sleep quality encoded as -1
+        assert_eq!(array[13], 72.0);
+        assert_eq!(array[14], -1.0);
+        assert_eq!(array[15], -1.0);
+        assert_eq!(array[16], 4.5);
+    }
+
+    #[test]
+    fn test_training_features_legacy_schema() {
+        let features = TrainingFeatures {
+            readiness_score: Some(80.0),
+            ..TrainingFeatures::default()
+        };
+
+        // Version 1 is the current vector without the recovery columns
+        let legacy = features.to_ndarray_for_schema(1).unwrap();
+        let current = features.to_ndarray();
+        assert_eq!(legacy.len(), 13);
+        assert_eq!(legacy.as_slice().unwrap(), &current.as_slice().unwrap()[..13]);
+
+        let legacy_names = TrainingFeatures::feature_names_for_schema(1).unwrap();
+        assert_eq!(legacy_names[..], TrainingFeatures::feature_names()[..13]);
+        assert!(!legacy_names.contains(&"readiness_score".to_string()));
+
+        // Unknown versions are rejected
+        assert!(features.to_ndarray_for_schema(0).is_none());
+        assert!(TrainingFeatures::feature_names_for_schema(FEATURE_SCHEMA_VERSION + 1).is_none());
+    }
+
+    #[test]
+    fn test_encode_hrv_trend() {
+        assert_eq!(TrainingFeatures::encode_hrv_trend("improving"), Some(1.0));
+        assert_eq!(TrainingFeatures::encode_hrv_trend("stable"), Some(0.0));
+        assert_eq!(TrainingFeatures::encode_hrv_trend("declining"), Some(-1.0));
+        assert_eq!(TrainingFeatures::encode_hrv_trend("insufficient_data"), None);
+    }
+
+    #[test]
+    fn test_days_between() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        assert_eq!(days_between(today, today), 0);
+        assert_eq!(days_between(today, NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()), 31);
+    }
+
+    #[test]
+    fn test_readiness_to_rating() {
+        assert_eq!(readiness_to_rating(0.0), 1.0);
+        assert_eq!(readiness_to_rating(100.0), 10.0);
+        assert_eq!(readiness_to_rating(50.0), 5.5);
+        assert_eq!(readiness_to_rating(120.0), 10.0);
     }
 
     #[test]
@@ -495,7 +632,7 @@ mod tests {
         let names = TrainingFeatures::feature_names();
 
         // Should have correct number of feature names
-        let expected_length = 8 + 5; // 8 numeric features + 5 workout type features
+        let expected_length = 8 + 5 + 4; // 8 numeric features + 5 workout type features + 4 recovery
         assert_eq!(names.len(), expected_length);
 
         // Check some specific names
@@ -519,5 +656,7 @@ mod tests {
         assert_eq!(features.days_until_goal_event, None);
         assert!(features.preferred_workout_types.is_empty());
         assert_eq!(features.seasonal_factors, 1.0);
+        assert_eq!(features.readiness_score, None);
+        assert_eq!(features.hrv_trend, None);
     }
 }
\ No newline at end of file
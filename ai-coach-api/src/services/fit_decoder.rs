@@ -0,0 +1,651 @@
+/// FIT File Decoder
+///
+/// Pure-Rust decoder for Garmin FIT activity files:
+/// - Header and file CRC validation
+/// - Definition/data messages, both architectures and compressed timestamp headers
+/// - Record, lap, session and device info messages
+/// - Developer fields (e.g. running power, core body temperature) via field descriptions
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::HashMap;
+use tracing::{debug, warn};
+
+use crate::models::training_metrics::Sport;
+use crate::services::training_file_parser::TrackPoint;
+
+/// Seconds between the Unix epoch and the FIT epoch (1989-12-31T00:00:00Z)
+pub(crate) const FIT_EPOCH_OFFSET: i64 = 631_065_600;
+const FIT_SIGNATURE: &[u8; 4] = b".FIT";
+const SEMICIRCLES_TO_DEGREES: f64 = 180.0 / 2_147_483_648.0;
+
+// Global message numbers
+const MESG_SESSION: u16 = 18;
+const MESG_LAP: u16 = 19;
+const MESG_RECORD: u16 = 20;
+const MESG_DEVICE_INFO: u16 = 23;
+const MESG_FIELD_DESCRIPTION: u16 = 206;
+
+const FIELD_TIMESTAMP: u8 = 253;
+
+/// Parsed FIT file header
+#[derive(Debug, Clone)]
+pub struct FitHeader {
+    pub header_size: usize,
+    pub protocol_version: u8,
+    pub profile_version: u16,
+    pub data_size: usize,
+}
+
+/// Lap summary message
+#[derive(Debug, Clone, Default)]
+pub struct FitLap {
+    pub start_time: Option<DateTime<Utc>>,
+    pub total_elapsed_time: Option<f64>, // seconds
+    pub total_timer_time: Option<f64>, // seconds
+    pub total_distance: Option<f64>, // meters
+    pub avg_heart_rate: Option<f64>,
+    pub avg_power: Option<f64>,
+    pub total_ascent: Option<f64>, // meters
+}
+
+/// Session summary message
+#[derive(Debug, Clone, Default)]
+pub struct FitSession {
+    pub start_time: Option<DateTime<Utc>>,
+    pub sport: Option<Sport>,
+    pub total_elapsed_time: Option<f64>, // seconds
+    pub total_timer_time: Option<f64>, // seconds
+    pub total_distance: Option<f64>, // meters
+    pub avg_heart_rate: Option<f64>,
+    pub avg_power: Option<f64>,
+    pub normalized_power: Option<f64>,
+    pub total_ascent: Option<f64>, // meters
+    pub training_stress_score: Option<f64>,
+}
+
+/// Device info message (head unit and connected sensors)
+#[derive(Debug, Clone, Default)]
+pub struct FitDeviceInfo {
+    pub device_index: Option<u8>,
+    pub manufacturer: Option<u16>,
+    pub product: Option<u16>,
+    pub serial_number: Option<u32>,
+    pub software_version: Option<f64>,
+    pub product_name: Option<String>,
+}
+
+/// Everything extracted from a FIT activity file
+#[derive(Debug, Clone, Default)]
+pub struct FitActivity {
+    pub trackpoints: Vec<TrackPoint>,
+    pub laps: Vec<FitLap>,
+    pub sessions: Vec<FitSession>,
+    pub devices: Vec<FitDeviceInfo>,
+}
+
+impl FitActivity {
+    /// Sport of the first session, if declared
+    pub fn sport(&self) -> Option<Sport> {
+        self.sessions.iter().find_map(|session| session.sport.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FieldDefinition {
+    number: u8,
+    size: usize,
+    base_type: u8,
+}
+
+#[derive(Debug, Clone)]
+struct DeveloperFieldDefinition {
+    number: u8,
+    size: usize,
+    developer_index: u8,
+}
+
+#[derive(Debug, Clone)]
+struct MessageDefinition {
+    global_number: u16,
+    big_endian: bool,
+    fields: Vec<FieldDefinition>,
+    developer_fields: Vec<DeveloperFieldDefinition>,
+}
+
+/// Developer field metadata announced by a field description message
+#[derive(Debug, Clone)]
+struct DeveloperFieldDescription {
+    base_type: u8,
+    name: String,
+    scale: Option<f64>,
+    offset: Option<f64>,
+    native_message: Option<u16>,
+    native_field: Option<u8>,
+}
+
+#[derive(Debug, Clone)]
+enum FieldValue {
+    Number(f64),
+    Text(String),
+}
+
+impl FieldValue {
+    fn number(&self) -> Option<f64> {
+        match self {
+            FieldValue::Number(value) => Some(*value),
+            FieldValue::Text(_) => None,
+        }
+    }
+}
+
+/// A decoded data message
+#[derive(Debug, Default)]
+struct DataMessage {
+    fields: HashMap<u8, FieldValue>,
+    developer_fields: Vec<(DeveloperFieldDescription, f64)>,
+}
+
+impl DataMessage {
+    fn number(&self, field: u8) -> Option<f64> {
+        self.fields.get(&field).and_then(FieldValue::number)
+    }
+
+    fn scaled(&self, field: u8, scale: f64, offset: f64) -> Option<f64> {
+        self.number(field).map(|value| value / scale - offset)
+    }
+
+    fn text(&self, field: u8) -> Option<String> {
+        match self.fields.get(&field) {
+            Some(FieldValue::Text(text)) => Some(text.clone()),
+            _ => None,
+        }
+    }
+
+    fn timestamp(&self, field: u8) -> Option<DateTime<Utc>> {
+        self.number(field).and_then(|value| fit_timestamp(value as u32))
+    }
+}
+
+/// Decoder for FIT activity files
+pub struct FitDecoder;
+
+impl FitDecoder {
+    /// Validate the FIT header, including the header CRC when present
+    pub fn validate_header(data: &[u8]) -> Result<FitHeader> {
+        let header_size = *data.first().ok_or_else(|| anyhow!("Invalid FIT file: empty file"))? as usize;
+
+        if header_size != 12 && header_size != 14 {
+            return Err(anyhow!("Invalid FIT file: unexpected header size {}", header_size));
+        }
+        if data.len() < header_size {
+            return Err(anyhow!("Invalid FIT file: truncated header"));
+        }
+        if &data[8..12] != FIT_SIGNATURE {
+            return Err(anyhow!("Invalid FIT file: missing .FIT signature"));
+        }
+
+        // A zero header CRC means the writer chose not to compute it
+        if header_size == 14 {
+            let expected = u16::from_le_bytes([data[12], data[13]]);
+            if expected != 0 && fit_crc(&data[..12]) != expected {
+                return Err(anyhow!("Invalid FIT file: header CRC mismatch"));
+            }
+        }
+
+        Ok(FitHeader {
+            header_size,
+            protocol_version: data[1],
+            profile_version: u16::from_le_bytes([data[2], data[3]]),
+            data_size: u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize,
+        })
+    }
+
+    /// Decode a FIT activity file into trackpoints and summary messages
+    pub fn decode(data: &[u8]) -> Result<FitActivity> {
+        let header = Self::validate_header(data)?;
+        let data_end = header.header_size + header.data_size;
+
+        if data.len() < data_end + 2 {
+            return Err(anyhow!(
+                "Invalid FIT file: expected {} bytes of data, found {}",
+                data_end + 2,
+                data.len()
+            ));
+        }
+
+        let expected_crc = u16::from_le_bytes([data[data_end], data[data_end + 1]]);
+        if fit_crc(&data[..data_end]) != expected_crc {
+            return Err(anyhow!("Invalid FIT file: file CRC mismatch"));
+        }
+
+        let mut activity = FitActivity::default();
+        let mut definitions: HashMap<u8, MessageDefinition> = HashMap::new();
+        let mut descriptions: HashMap<(u8, u8), DeveloperFieldDescription> = HashMap::new();
+        let mut last_timestamp: Option<u32> = None;
+        let mut position = header.header_size;
+
+        while position < data_end {
+            let record_header = data[position];
+            position += 1;
+
+            // Compressed timestamp header: 2-bit local type, 5-bit time offset
+            if record_header & 0x80 != 0 {
+                let local_type = (record_header >> 5) & 0x03;
+                let offset = (record_header & 0x1F) as u32;
+                let definition = definitions
+                    .get(&local_type)
+                    .ok_or_else(|| anyhow!("Invalid FIT file: undefined local message {}", local_type))?
+                    .clone();
+
+                let previous = last_timestamp
+                    .ok_or_else(|| anyhow!("Invalid FIT file: compressed timestamp before any timestamp"))?;
+                let mut timestamp = (previous & !0x1F) | offset;
+                if offset < (previous & 0x1F) {
+                    timestamp += 0x20;
+                }
+                last_timestamp = Some(timestamp);
+
+                let mut message = Self::read_data_message(data, &mut position, data_end, &definition, &descriptions)?;
+                message.fields.insert(FIELD_TIMESTAMP, FieldValue::Number(timestamp as f64));
+                Self::handle_message(&definition, message, &mut activity, &mut descriptions);
+                continue;
+            }
+
+            let local_type = record_header & 0x0F;
+
+            if record_header & 0x40 != 0 {
+                let has_developer_fields = record_header & 0x20 != 0;
+                let definition = Self::read_definition(data, &mut position, data_end, has_developer_fields)?;
+                definitions.insert(local_type, definition);
+                continue;
+            }
+
+            let definition = definitions
+                .get(&local_type)
+                .ok_or_else(|| anyhow!("Invalid FIT file: undefined local message {}", local_type))?
+                .clone();
+            let message = Self::read_data_message(data, &mut position, data_end, &definition, &descriptions)?;
+
+            if let Some(timestamp) = message.number(FIELD_TIMESTAMP) {
+                last_timestamp = Some(timestamp as u32);
+            }
+
+            Self::handle_message(&definition, message, &mut activity, &mut descriptions);
+        }
+
+        debug!(
+            "Decoded FIT file: {} records, {} laps, {} sessions, {} devices",
+            activity.trackpoints.len(),
+            activity.laps.len(),
+            activity.sessions.len(),
+            activity.devices.len()
+        );
+
+        Ok(activity)
+    }
+
+    fn read_definition(
+        data: &[u8],
+        position: &mut usize,
+        end: usize,
+        has_developer_fields: bool,
+    ) -> Result<MessageDefinition> {
+        let fixed = take(data, position, end, 5)?;
+        let big_endian = fixed[1] == 1;
+        let global_number = if big_endian {
+            u16::from_be_bytes([fixed[2], fixed[3]])
+        } else {
+            u16::from_le_bytes([fixed[2], fixed[3]])
+        };
+        let field_count = fixed[4] as usize;
+
+        let fields = take(data, position, end, field_count * 3)?
+            .chunks_exact(3)
+            .map(|chunk| FieldDefinition {
+                number: chunk[0],
+                size: chunk[1] as usize,
+                base_type: chunk[2],
+            })
+            .collect();
+
+        let developer_fields = if has_developer_fields {
+            let count = take(data, position, end, 1)?[0] as usize;
+            take(data, position, end, count * 3)?
+                .chunks_exact(3)
+                .map(|chunk| DeveloperFieldDefinition {
+                    number: chunk[0],
+                    size: chunk[1] as usize,
+                    developer_index: chunk[2],
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(MessageDefinition {
+            global_number,
+            big_endian,
+            fields,
+            developer_fields,
+        })
+    }
+
+    fn read_data_message(
+        data: &[u8],
+        position: &mut usize,
+        end: usize,
+        definition: &MessageDefinition,
+        descriptions: &HashMap<(u8, u8), DeveloperFieldDescription>,
+    ) -> Result<DataMessage> {
+        let mut message = DataMessage::default();
+
+        for field in &definition.fields {
+            let bytes = take(data, position, end, field.size)?;
+            if let Some(value) = decode_value(bytes, field.base_type, definition.big_endian) {
+                message.fields.insert(field.number, value);
+            }
+        }
+
+        for field in &definition.developer_fields {
+            let bytes = take(data, position, end, field.size)?;
+            let Some(description) = descriptions.get(&(field.developer_index, field.number)) else {
+                continue;
+            };
+
+            if let Some(FieldValue::Number(raw)) = decode_value(bytes, description.base_type, definition.big_endian) {
+                let value = raw / description.scale.unwrap_or(1.0) - description.offset.unwrap_or(0.0);
+                message.developer_fields.push((description.clone(), value));
+            }
+        }
+
+        Ok(message)
+    }
+
+    fn handle_message(
+        definition: &MessageDefinition,
+        message: DataMessage,
+        activity: &mut FitActivity,
+        descriptions: &mut HashMap<(u8, u8), DeveloperFieldDescription>,
+    ) {
+        match definition.global_number {
+            MESG_RECORD => {
+                if let Some(point) = Self::record_to_trackpoint(&message) {
+                    activity.trackpoints.push(point);
+                }
+            }
+            MESG_LAP => activity.laps.push(FitLap {
+                start_time: message.timestamp(2),
+                total_elapsed_time: message.scaled(7, 1000.0, 0.0),
+                total_timer_time: message.scaled(8, 1000.0, 0.0),
+                total_distance: message.scaled(9, 100.0, 0.0),
+                avg_heart_rate: message.number(15),
+                avg_power: message.number(19),
+                total_ascent: message.number(21),
+            }),
+            MESG_SESSION => activity.sessions.push(FitSession {
+                start_time: message.timestamp(2),
+                sport: message.number(5).and_then(|sport| fit_sport(sport as u8)),
+                total_elapsed_time: message.scaled(7, 1000.0, 0.0),
+                total_timer_time: message.scaled(8, 1000.0, 0.0),
+                total_distance: message.scaled(9, 100.0, 0.0),
+                avg_heart_rate: message.number(16),
+                avg_power: message.number(20),
+                normalized_power: message.number(34),
+                total_ascent: message.number(22),
+                training_stress_score: message.scaled(35, 10.0, 0.0),
+            }),
+            MESG_DEVICE_INFO => activity.devices.push(FitDeviceInfo {
+                device_index: message.number(0).map(|v| v as u8),
+                manufacturer: message.number(2).map(|v| v as u16),
+                product: message.number(4).map(|v| v as u16),
+                serial_number: message.number(3).map(|v| v as u32),
+                software_version: message.scaled(5, 100.0, 0.0),
+                product_name: message.text(27),
+            }),
+            MESG_FIELD_DESCRIPTION => {
+                let (Some(developer_index), Some(field_number), Some(base_type)) =
+                    (message.number(0), message.number(1), message.number(2))
+                else {
+                    warn!("Ignoring incomplete FIT developer field description");
+                    return;
+                };
+
+                descriptions.insert(
+                    (developer_index as u8, field_number as u8),
+                    DeveloperFieldDescription {
+                        base_type: base_type as u8,
+                        name: message.text(3).unwrap_or_default(),
+                        scale: message.number(6).filter(|scale| *scale != 0.0),
+                        offset: message.number(7),
+                        native_message: message.number(14).map(|v| v as u16),
+                        native_field: message.number(15).map(|v| v as u8),
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn record_to_trackpoint(message: &DataMessage) -> Option<TrackPoint> {
+        let timestamp = message.timestamp(FIELD_TIMESTAMP)?;
+
+        let cadence = message
+            .number(4)
+            .map(|cadence| cadence + message.scaled(53, 128.0, 0.0).unwrap_or(0.0));
+
+        let mut point = TrackPoint {
+            timestamp,
+            latitude: message.number(0).map(|v| v * SEMICIRCLES_TO_DEGREES),
+            longitude: message.number(1).map(|v| v * SEMICIRCLES_TO_DEGREES),
+            elevation: message.scaled(78, 5.0, 500.0).or_else(|| message.scaled(2, 5.0, 500.0)),
+            heart_rate: message.number(3),
+            power: message.number(7),
+            cadence,
+            speed: message.scaled(73, 1000.0, 0.0).or_else(|| message.scaled(6, 1000.0, 0.0)),
+            distance: message.scaled(5, 100.0, 0.0),
+            core_temperature: None,
+        };
+
+        for (description, value) in &message.developer_fields {
+            let name = description.name.to_lowercase();
+            let native_power = description.native_message == Some(MESG_RECORD) && description.native_field == Some(7);
+
+            if (native_power || name.contains("power")) && point.power.is_none() {
+                point.power = Some(*value);
+            } else if name.contains("core") && name.contains("temp") {
+                point.core_temperature = Some(*value);
+            }
+        }
+
+        Some(point)
+    }
+}
+
+/// CRC-16 used by the FIT protocol
+pub fn fit_crc(data: &[u8]) -> u16 {
+    const CRC_TABLE: [u16; 16] = [
+        0x0000, 0xCC01, 0xD801, 0x1400, 0xF001, 0x3C00, 0x2800, 0xE401,
+        0xA001, 0x6C00, 0x7800, 0xB401, 0x5000, 0x9C01, 0x8801, 0x4400,
+    ];
+
+    data.iter().fold(0u16, |mut crc, &byte| {
+        let tmp = CRC_TABLE[(crc & 0xF) as usize];
+        crc = (crc >> 4) & 0x0FFF;
+        crc = crc ^ tmp ^ CRC_TABLE[(byte & 0xF) as usize];
+
+        let tmp = CRC_TABLE[(crc & 0xF) as usize];
+        crc = (crc >> 4) & 0x0FFF;
+        crc ^ tmp ^ CRC_TABLE[((byte >> 4) & 0xF) as usize]
+    })
+}
+
+fn take<'a>(data: &'a [u8], position: &mut usize, end: usize, len: usize) -> Result<&'a [u8]> {
+    if *position + len > end {
+        return Err(anyhow!("Invalid FIT file: message runs past end of data at byte {}", position));
+    }
+    let bytes = &data[*position..*position + len];
+    *position += len;
+    Ok(bytes)
+}
+
+/// Decode the first element of a field, returning `None` for the base type's invalid value
+fn decode_value(bytes: &[u8], base_type: u8, big_endian: bool) -> Option<FieldValue> {
+    macro_rules! read {
+        ($ty:ty, $invalid:expr) => {{
+            const SIZE: usize = std::mem::size_of::<$ty>();
+            let raw: [u8; SIZE] = bytes.get(..SIZE)?.try_into().ok()?;
+            let value = if big_endian { <$ty>::from_be_bytes(raw) } else { <$ty>::from_le_bytes(raw) };
+            if value == $invalid {
+                return None;
+            }
+            value as f64
+        }};
+    }
+
+    let value = match base_type & 0x1F {
+        0x00 | 0x02 | 0x0D => read!(u8, u8::MAX), // enum, uint8, byte
+        0x01 => read!(i8, i8::MAX),
+        0x03 => read!(i16, i16::MAX),
+        0x04 => read!(u16, u16::MAX),
+        0x05 => read!(i32, i32::MAX),
+        0x06 => read!(u32, u32::MAX),
+        0x07 => {
+            let text: Vec<u8> = bytes.iter().copied().take_while(|b| *b != 0).collect();
+            if text.is_empty() {
+                return None;
+            }
+            return Some(FieldValue::Text(String::from_utf8_lossy(&text).into_owned()));
+        }
+        0x08 => {
+            let raw: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+            let value = if big_endian { f32::from_be_bytes(raw) } else { f32::from_le_bytes(raw) };
+            if !value.is_finite() {
+                return None;
+            }
+            value as f64
+        }
+        0x09 => {
+            let raw: [u8; 8] = bytes.get(..8)?.try_into().ok()?;
+            let value = if big_endian { f64::from_be_bytes(raw) } else { f64::from_le_bytes(raw) };
+            if !value.is_finite() {
+                return None;
+            }
+            value
+        }
+        0x0A => read!(u8, 0), // uint8z
+        0x0B => read!(u16, 0), // uint16z
+        0x0C => read!(u32, 0), // uint32z
+        0x0E => read!(i64, i64::MAX),
+        0x0F => read!(u64, u64::MAX),
+        0x10 => read!(u64, 0), // uint64z
+        _ => return None,
+    };
+
+    Some(FieldValue::Number(value))
+}
+
+fn fit_timestamp(value: u32) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(value as i64 + FIT_EPOCH_OFFSET, 0).single()
+}
+
+fn fit_sport(value: u8) -> Option<Sport> {
+    match value {
+        1 => Some(Sport::Running),
+        2 => Some(Sport::Cycling),
+        5 => Some(Sport::Swimming),
+        0 => None,
+        other => Some(Sport::Other(format!("fit_sport_{}", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Running activity: file_id, developer data id, two developer field descriptions
+    /// (Power on record.power, core_temperature with scale 100), one device_info,
+    /// five 1 Hz records (the last with a compressed timestamp header), one lap and one session
+    const SAMPLE_FIT: &[u8] = include_bytes!("../../tests/fixtures/training_files/sample_run.fit");
+
+    #[test]
+    fn test_fit_crc_matches_reference() {
+        // FIT uses CRC-16/ARC, whose check value for "123456789" is 0xBB3D
+        assert_eq!(fit_crc(b"123456789"), 0xBB3D);
+    }
+
+    #[test]
+    fn test_validate_header() {
+        let header = FitDecoder::validate_header(SAMPLE_FIT).unwrap();
+        assert_eq!(header.header_size, 14);
+        assert_eq!(header.data_size + 16, SAMPLE_FIT.len());
+
+        let mut corrupted = SAMPLE_FIT.to_vec();
+        corrupted[4] ^= 0xFF;
+        assert!(FitDecoder::validate_header(&corrupted).unwrap_err().to_string().contains("header CRC"));
+
+        assert!(FitDecoder::validate_header(b"not a fit file at all").is_err());
+    }
+
+    #[test]
+    fn test_decode_rejects_corrupted_data() {
+        let mut corrupted = SAMPLE_FIT.to_vec();
+        corrupted[40] ^= 0x01;
+        assert!(FitDecoder::decode(&corrupted).unwrap_err().to_string().contains("file CRC"));
+    }
+
+    #[test]
+    fn test_decode_records() {
+        let activity = FitDecoder::decode(SAMPLE_FIT).unwrap();
+
+        assert_eq!(activity.trackpoints.len(), 5);
+
+        let first = &activity.trackpoints[0];
+        assert_eq!(first.timestamp.to_rfc3339(), "2024-03-02T07:00:00+00:00");
+        assert!((first.latitude.unwrap() - 45.5017).abs() < 1e-6);
+        assert!((first.longitude.unwrap() + 73.5673).abs() < 1e-6);
+        assert!((first.elevation.unwrap() - 30.0).abs() < 1e-9);
+        assert_eq!(first.heart_rate, Some(140.0));
+        assert_eq!(first.cadence, Some(85.0));
+        assert_eq!(first.speed, Some(3.5));
+        assert_eq!(first.distance, Some(0.0));
+
+        // Compressed timestamp header on the final record
+        let last = &activity.trackpoints[4];
+        assert_eq!(last.timestamp.to_rfc3339(), "2024-03-02T07:00:04+00:00");
+        assert_eq!(last.distance, Some(14.0));
+    }
+
+    #[test]
+    fn test_decode_developer_fields() {
+        let activity = FitDecoder::decode(SAMPLE_FIT).unwrap();
+
+        let powers: Vec<Option<f64>> = activity.trackpoints.iter().map(|p| p.power).collect();
+        assert_eq!(powers, vec![Some(250.0), Some(255.0), Some(260.0), Some(265.0), Some(270.0)]);
+
+        let core = activity.trackpoints[4].core_temperature.unwrap();
+        assert!((core - 37.54).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_decode_summary_messages() {
+        let activity = FitDecoder::decode(SAMPLE_FIT).unwrap();
+
+        assert!(matches!(activity.sport(), Some(Sport::Running)));
+
+        let session = &activity.sessions[0];
+        assert_eq!(session.total_timer_time, Some(4.0));
+        assert_eq!(session.total_distance, Some(14.0));
+        assert_eq!(session.avg_heart_rate, Some(142.0));
+        assert_eq!(session.avg_power, Some(260.0));
+
+        assert_eq!(activity.laps.len(), 1);
+        assert_eq!(activity.laps[0].total_elapsed_time, Some(4.0));
+
+        let device = &activity.devices[0];
+        assert_eq!(device.manufacturer, Some(1));
+        assert_eq!(device.product, Some(3121));
+        assert_eq!(device.serial_number, Some(123456));
+        assert_eq!(device.software_version, Some(12.5));
+    }
+}
@@ -0,0 +1,343 @@
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::goal::{Goal, GoalProgress, GoalStatus, GoalType};
+
+const GOAL_COLUMNS: &str = r#"
+    id, user_id, title, COALESCE(description, '') AS description, goal_type, goal_category,
+    target_value::DOUBLE PRECISION AS target_value, current_value::DOUBLE PRECISION AS current_value,
+    unit, target_date, status, priority, event_id, parent_goal_id, created_at, updated_at
+"#;
+
+/// Days of training that count towards weekly TSS and volume goals
+const WEEK_DAYS: i64 = 7;
+/// Days over which training consistency is measured
+const CONSISTENCY_DAYS: i64 = 28;
+/// Days of sessions searched for the best 20-minute power
+const POWER_DAYS: i64 = 90;
+/// FTP is estimated as this share of the best 20-minute power
+const FTP_FROM_20_MIN_POWER: f64 = 0.95;
+/// Days of recovery scores averaged for recovery goals
+const RECOVERY_DAYS: i64 = 7;
+
+const TARGET_REACHED: &str = "Target reached";
+
+/// Measures progress on goals that training and recovery data can answer directly, records
+/// it as automatic progress entries and completes or fails goals on achievement or expiry.
+#[derive(Clone)]
+pub struct GoalProgressEvaluator {
+    db: PgPool,
+}
+
+impl GoalProgressEvaluator {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Re-measure the user's tracked goals as of `today`, returning the progress entries written
+    pub async fn evaluate_user_goals(&self, user_id: Uuid, today: NaiveDate) -> Result<Vec<GoalProgress>> {
+        let goals = sqlx::query_as::<_, Goal>(&format!(
+            r#"
+            SELECT {} FROM goals
+            WHERE user_id = $1 AND status IN ('active', 'on_track', 'at_risk')
+            "#,
+            GOAL_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to load goals")?;
+
+        let mut entries = Vec::new();
+
+        for goal in goals.iter().filter(|goal| is_measurable(&goal.goal_type)) {
+            if let Some(entry) = self.evaluate_goal(goal, today).await? {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Settle measurable goals whose target date has passed without new data arriving
+    pub async fn evaluate_overdue_goals(&self, today: NaiveDate) -> Result<u64> {
+        let user_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT user_id FROM goals
+            WHERE status IN ('active', 'on_track', 'at_risk') AND target_date < $1
+              AND goal_type IN ('power', 'distance', 'consistency', 'weekly_tss', 'weekly_volume', 'recovery_metrics')
+            "#,
+        )
+        .bind(today)
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to find overdue goals")?;
+
+        for user_id in &user_ids {
+            if let Err(e) = self.evaluate_user_goals(*user_id, today).await {
+                tracing::error!("Failed to evaluate overdue goals for user {}: {:#}", user_id, e);
+            }
+        }
+
+        Ok(user_ids.len() as u64)
+    }
+
+    async fn evaluate_goal(&self, goal: &Goal, today: NaiveDate) -> Result<Option<GoalProgress>> {
+        // A goal is judged on the data up to its target date
+        let as_of = goal.target_date.map_or(today, |target_date| target_date.min(today));
+        let value = self.measure(goal, as_of).await?;
+        let status = next_status(value, goal.target_value, goal.target_date, today);
+
+        let entry = match value {
+            Some(value) => {
+                let achieved = matches!(status, Some(GoalStatus::Completed));
+                Some(self.record_progress(goal, value, as_of, achieved).await?)
+            }
+            None => None,
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE goals
+            SET current_value = COALESCE($2::DECIMAL(10,2), current_value),
+                status = COALESCE($3, status),
+                updated_at = NOW()
+            WHERE id = $1
+              AND (current_value IS DISTINCT FROM COALESCE($2::DECIMAL(10,2), current_value)
+                   OR status <> COALESCE($3, status))
+            "#,
+        )
+        .bind(goal.id)
+        .bind(value)
+        .bind(status.clone())
+        .execute(&self.db)
+        .await
+        .context("Failed to update goal")?;
+
+        match status {
+            Some(GoalStatus::Completed) => tracing::info!("Goal {} completed with {:?}", goal.id, value),
+            Some(GoalStatus::Failed) => tracing::info!("Goal {} expired at {:?}", goal.id, value),
+            _ => {}
+        }
+
+        Ok(entry)
+    }
+
+    /// Write the day's automatic entry, replacing an earlier measurement from the same day
+    async fn record_progress(&self, goal: &Goal, value: f64, date: NaiveDate, achieved: bool) -> Result<GoalProgress> {
+        sqlx::query_as::<_, GoalProgress>(
+            r#"
+            INSERT INTO goal_progress (goal_id, value, date, milestone_achieved, automatic)
+            VALUES ($1, $2, $3, $4, TRUE)
+            ON CONFLICT (goal_id, date) WHERE automatic
+            DO UPDATE SET value = EXCLUDED.value,
+                          milestone_achieved = COALESCE(EXCLUDED.milestone_achieved, goal_progress.milestone_achieved),
+                          created_at = NOW()
+            RETURNING id, goal_id, value::DOUBLE PRECISION AS value, date, note, milestone_achieved,
+                      automatic, created_at
+            "#,
+        )
+        .bind(goal.id)
+        .bind(value)
+        .bind(date)
+        .bind(achieved.then(|| TARGET_REACHED.to_string()))
+        .fetch_one(&self.db)
+        .await
+        .context("Failed to record goal progress")
+    }
+
+    /// The goal's measured value as of `as_of`, in the goal's unit; None without data
+    async fn measure(&self, goal: &Goal, as_of: NaiveDate) -> Result<Option<f64>> {
+        let unit = goal.unit.as_deref();
+
+        let value = match goal.goal_type {
+            GoalType::WeeklyTss => {
+                let tss: f64 = sqlx::query_scalar(
+                    r#"
+                    SELECT COALESCE(SUM((trainrs_data->>'tss')::DOUBLE PRECISION), 0) FROM training_sessions
+                    WHERE user_id = $1 AND date > $2 AND date <= $3
+                    "#,
+                )
+                .bind(goal.user_id)
+                .bind(as_of - Duration::days(WEEK_DAYS))
+                .bind(as_of)
+                .fetch_one(&self.db)
+                .await?;
+                Some(tss)
+            }
+            GoalType::WeeklyVolume => {
+                let (seconds, meters): (i64, f64) = sqlx::query_as(
+                    r#"
+                    SELECT COALESCE(SUM(duration_seconds), 0)::BIGINT,
+                           COALESCE(SUM(distance_meters), 0)::DOUBLE PRECISION
+                    FROM training_sessions
+                    WHERE user_id = $1 AND date > $2 AND date <= $3
+                    "#,
+                )
+                .bind(goal.user_id)
+                .bind(as_of - Duration::days(WEEK_DAYS))
+                .bind(as_of)
+                .fetch_one(&self.db)
+                .await?;
+
+                Some(volume_in_unit(seconds, meters, unit))
+            }
+            GoalType::Consistency => {
+                let training_days: i64 = sqlx::query_scalar(
+                    r#"
+                    SELECT COUNT(DISTINCT date) FROM training_sessions
+                    WHERE user_id = $1 AND date > $2 AND date <= $3
+                    "#,
+                )
+                .bind(goal.user_id)
+                .bind(as_of - Duration::days(CONSISTENCY_DAYS))
+                .bind(as_of)
+                .fetch_one(&self.db)
+                .await?;
+                Some(training_days as f64 / CONSISTENCY_DAYS as f64 * 100.0)
+            }
+            GoalType::Distance => {
+                let meters: Option<f64> = sqlx::query_scalar(
+                    r#"
+                    SELECT MAX(distance_meters)::DOUBLE PRECISION FROM training_sessions
+                    WHERE user_id = $1 AND date >= $2 AND date <= $3
+                    "#,
+                )
+                .bind(goal.user_id)
+                .bind(goal.created_at.date_naive())
+                .bind(as_of)
+                .fetch_one(&self.db)
+                .await?;
+                meters.map(|meters| distance_in_unit(meters, unit))
+            }
+            GoalType::Power => {
+                let power: Option<f64> = sqlx::query_scalar(
+                    r#"
+                    SELECT MAX((trainrs_data->'peak_powers'->>'1200')::DOUBLE PRECISION) FROM training_sessions
+                    WHERE user_id = $1 AND date > $2 AND date <= $3
+                    "#,
+                )
+                .bind(goal.user_id)
+                .bind(as_of - Duration::days(POWER_DAYS))
+                .bind(as_of)
+                .fetch_one(&self.db)
+                .await?;
+                power.map(|power| power * FTP_FROM_20_MIN_POWER)
+            }
+            GoalType::RecoveryMetrics => {
+                let readiness: Option<f64> = sqlx::query_scalar(
+                    r#"
+                    SELECT AVG(readiness_score)::DOUBLE PRECISION FROM recovery_scores
+                    WHERE user_id = $1 AND score_date > $2 AND score_date <= $3
+                    "#,
+                )
+                .bind(goal.user_id)
+                .bind(as_of - Duration::days(RECOVERY_DAYS))
+                .bind(as_of)
+                .fetch_one(&self.db)
+                .await?;
+                readiness
+            }
+            _ => None,
+        };
+
+        Ok(value.map(round2))
+    }
+}
+
+/// Goal types the evaluator can measure from training and recovery data
+pub fn is_measurable(goal_type: &GoalType) -> bool {
+    matches!(
+        goal_type,
+        GoalType::Power
+            | GoalType::Distance
+            | GoalType::Consistency
+            | GoalType::WeeklyTss
+            | GoalType::WeeklyVolume
+            | GoalType::RecoveryMetrics
+    )
+}
+
+/// Completed once the target is reached, failed once the target date has passed without it;
+/// None leaves the status as it is
+pub fn next_status(
+    value: Option<f64>,
+    target_value: Option<f64>,
+    target_date: Option<NaiveDate>,
+    today: NaiveDate,
+) -> Option<GoalStatus> {
+    let target = target_value?;
+
+    if value.is_some_and(|value| value >= target) {
+        Some(GoalStatus::Completed)
+    } else if target_date.is_some_and(|target_date| target_date < today) {
+        Some(GoalStatus::Failed)
+    } else {
+        None
+    }
+}
+
+/// Training volume in the goal's unit: hours unless the unit asks for minutes or distance
+pub fn volume_in_unit(seconds: i64, meters: f64, unit: Option<&str>) -> f64 {
+    match unit.map(|unit| unit.trim().to_lowercase()).as_deref() {
+        Some("min" | "mins" | "minutes") => seconds as f64 / 60.0,
+        Some("km" | "m" | "mi" | "miles") => distance_in_unit(meters, unit),
+        _ => seconds as f64 / 3600.0,
+    }
+}
+
+/// Distance in the goal's unit: kilometres unless the unit is metres or miles
+pub fn distance_in_unit(meters: f64, unit: Option<&str>) -> f64 {
+    match unit.map(|unit| unit.trim().to_lowercase()).as_deref() {
+        Some("m" | "meters" | "metres") => meters,
+        Some("mi" | "miles") => meters / 1609.344,
+        _ => meters / 1000.0,
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_next_status() {
+        let today = date("2025-06-10");
+
+        assert!(matches!(next_status(Some(300.0), Some(300.0), Some(date("2025-07-01")), today), Some(GoalStatus::Completed)));
+        // Reaching the target on the last day still counts
+        assert!(matches!(next_status(Some(310.0), Some(300.0), Some(date("2025-06-01")), today), Some(GoalStatus::Completed)));
+        assert!(matches!(next_status(Some(250.0), Some(300.0), Some(date("2025-06-09")), today), Some(GoalStatus::Failed)));
+        assert!(matches!(next_status(None, Some(300.0), Some(date("2025-06-09")), today), Some(GoalStatus::Failed)));
+        assert!(next_status(Some(250.0), Some(300.0), Some(date("2025-06-10")), today).is_none());
+        assert!(next_status(Some(250.0), Some(300.0), None, today).is_none());
+        assert!(next_status(Some(250.0), None, Some(date("2025-06-01")), today).is_none());
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(volume_in_unit(5400, 40000.0, None), 1.5);
+        assert_eq!(volume_in_unit(5400, 40000.0, Some("minutes")), 90.0);
+        assert_eq!(volume_in_unit(5400, 40000.0, Some("km")), 40.0);
+        assert_eq!(distance_in_unit(42195.0, Some("KM")), 42.195);
+        assert_eq!(distance_in_unit(1609.344, Some("mi")), 1.0);
+        assert_eq!(distance_in_unit(5000.0, Some("m")), 5000.0);
+    }
+
+    #[test]
+    fn test_is_measurable() {
+        assert!(is_measurable(&GoalType::WeeklyTss));
+        assert!(is_measurable(&GoalType::RecoveryMetrics));
+        assert!(!is_measurable(&GoalType::RaceTime));
+        assert!(!is_measurable(&GoalType::Custom));
+    }
+}
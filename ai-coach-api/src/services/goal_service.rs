@@ -3,7 +3,7 @@ use chrono::{NaiveDate, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::models::{
+use crate::models::goal::{
     Goal, GoalProgress, CreateGoalRequest, UpdateGoalRequest, CreateGoalProgressRequest,
     GoalProgressSummary, GoalRecommendation, TrendDirection, GoalStatus, GoalType,
     GoalCategory, GoalPriority, RecommendationType
@@ -200,7 +200,7 @@ impl GoalService {
             r#"
             INSERT INTO goal_progress (goal_id, value, date, note, milestone_achieved, created_at)
             VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING id, goal_id, value, date, note, milestone_achieved, created_at
+            RETURNING id, goal_id, value, date, note, milestone_achieved, automatic, created_at
             "#,
             goal_id,
             request.value,
@@ -237,7 +237,7 @@ impl GoalService {
         let recent_entries = sqlx::query_as!(
             GoalProgress,
             r#"
-            SELECT id, goal_id, value, date, note, milestone_achieved, created_at
+            SELECT id, goal_id, value, date, note, milestone_achieved, automatic, created_at
             FROM goal_progress
             WHERE goal_id = $1
             ORDER BY date DESC, created_at DESC
@@ -256,7 +256,7 @@ impl GoalService {
         };
 
         // Calculate trend direction
-        let trend_direction = self.calculate_trend_direction(&recent_entries).await;
+        let trend_direction = trend_from_series(&recent_entries);
 
         // Get milestones achieved
         let milestones_achieved: Vec<String> = recent_entries
@@ -382,32 +382,6 @@ impl GoalService {
     }
 
     // Private helper methods
-    async fn calculate_trend_direction(&self, progress_entries: &[GoalProgress]) -> TrendDirection {
-        if progress_entries.len() < 2 {
-            return TrendDirection::Insufficient;
-        }
-
-        let recent_values: Vec<f64> = progress_entries.iter().take(5).map(|p| p.value).collect();
-        let earlier_values: Vec<f64> = progress_entries.iter().skip(5).take(5).map(|p| p.value).collect();
-
-        if earlier_values.is_empty() {
-            return TrendDirection::Insufficient;
-        }
-
-        let recent_avg = recent_values.iter().sum::<f64>() / recent_values.len() as f64;
-        let earlier_avg = earlier_values.iter().sum::<f64>() / earlier_values.len() as f64;
-
-        let change_pct = ((recent_avg - earlier_avg) / earlier_avg) * 100.0;
-
-        if change_pct > 5.0 {
-            TrendDirection::Improving
-        } else if change_pct < -5.0 {
-            TrendDirection::Declining
-        } else {
-            TrendDirection::Stable
-        }
-    }
-
     async fn calculate_success_probability(&self, goal: &Goal, progress_entries: &[GoalProgress]) -> Option<f64> {
         if let (Some(target), Some(current), Some(target_date)) = (goal.target_value, goal.current_value, goal.target_date) {
             let days_remaining = (target_date - chrono::Local::now().naive_local().date()).num_days() as f64;
@@ -455,4 +429,77 @@ impl GoalService {
 
         None
     }
-}
\ No newline at end of file
+}
+
+/// Trend of a progress series (in any order) from its least-squares slope: the change the
+/// slope implies over the series' span, relative to its average value
+pub fn trend_from_series(entries: &[GoalProgress]) -> TrendDirection {
+    let Some(first_date) = entries.iter().map(|entry| entry.date).min() else {
+        return TrendDirection::Insufficient;
+    };
+
+    let points: Vec<(f64, f64)> = entries
+        .iter()
+        .map(|entry| ((entry.date - first_date).num_days() as f64, entry.value))
+        .collect();
+    let span = points.iter().map(|(day, _)| *day).fold(0.0, f64::max);
+
+    if points.len() < 3 || span == 0.0 {
+        return TrendDirection::Insufficient;
+    }
+
+    let n = points.len() as f64;
+    let mean_day = points.iter().map(|(day, _)| day).sum::<f64>() / n;
+    let mean_value = points.iter().map(|(_, value)| value).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|(day, value)| (day - mean_day) * (value - mean_value)).sum();
+    let variance: f64 = points.iter().map(|(day, _)| (day - mean_day).powi(2)).sum();
+    let change = covariance / variance * span;
+
+    let change_pct = if mean_value.abs() > f64::EPSILON {
+        change / mean_value.abs() * 100.0
+    } else {
+        change.signum() * 100.0
+    };
+
+    if change_pct > 5.0 {
+        TrendDirection::Improving
+    } else if change_pct < -5.0 {
+        TrendDirection::Declining
+    } else {
+        TrendDirection::Stable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(day: u32, value: f64) -> GoalProgress {
+        GoalProgress {
+            id: Uuid::new_v4(),
+            goal_id: Uuid::nil(),
+            value,
+            date: NaiveDate::from_ymd_opt(2025, 5, day).unwrap(),
+            note: None,
+            milestone_achieved: None,
+            automatic: true,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_trend_from_series() {
+        let rising = vec![entry(10, 330.0), entry(8, 310.0), entry(5, 300.0), entry(1, 280.0)];
+        assert!(matches!(trend_from_series(&rising), TrendDirection::Improving));
+
+        let falling = vec![entry(1, 80.0), entry(4, 72.0), entry(7, 65.0)];
+        assert!(matches!(trend_from_series(&falling), TrendDirection::Declining));
+
+        // Day-to-day noise around a flat line
+        let flat = vec![entry(1, 300.0), entry(2, 306.0), entry(3, 297.0), entry(4, 302.0), entry(5, 299.0)];
+        assert!(matches!(trend_from_series(&flat), TrendDirection::Stable));
+
+        assert!(matches!(trend_from_series(&rising[..2]), TrendDirection::Insufficient));
+        assert!(matches!(trend_from_series(&[entry(3, 1.0), entry(3, 2.0), entry(3, 3.0)]), TrendDirection::Insufficient));
+    }
+}
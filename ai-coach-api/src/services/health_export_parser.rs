@@ -0,0 +1,872 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, TimeZone, Utc};
+use quick_xml::events::{BytesStart, Event};
+use serde_json::json;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+use sqlx::{ConnectOptions, Connection};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::models::{CreateHrvReadingRequest, CreateRestingHrRequest, CreateSleepDataRequest};
+
+const APPLE_HRV_TYPE: &str = "HKQuantityTypeIdentifierHeartRateVariabilitySDNN";
+const APPLE_RESTING_HR_TYPE: &str = "HKQuantityTypeIdentifierRestingHeartRate";
+const APPLE_SLEEP_TYPE: &str = "HKCategoryTypeIdentifierSleepAnalysis";
+const APPLE_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S %z";
+
+/// Samples ending after 18:00 local time count towards the next night
+const NIGHT_CUTOFF_HOURS: i64 = 6;
+
+/// Largest Health Connect database extracted from a zipped export, so a small archive can't
+/// decompress into an unbounded temporary file
+const MAX_HEALTH_CONNECT_DB_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Recovery data extracted from a health platform export
+#[derive(Debug, Default)]
+pub struct ParsedHealthData {
+    pub hrv: Vec<CreateHrvReadingRequest>,
+    pub sleep: Vec<CreateSleepDataRequest>,
+    pub resting_hr: Vec<CreateRestingHrRequest>,
+    /// Relevant records found in the export, including ones rejected as out of range
+    pub records_read: u64,
+}
+
+impl ParsedHealthData {
+    pub fn len(&self) -> usize {
+        self.hrv.len() + self.sleep.len() + self.resting_hr.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Bytes of the export read so far, polled by the import job to report progress
+#[derive(Debug, Default)]
+pub struct ParseProgress {
+    pub bytes_read: AtomicU64,
+    pub total_bytes: AtomicU64,
+}
+
+impl ParseProgress {
+    /// Fraction of the export read, between 0 and 1
+    pub fn fraction(&self) -> f64 {
+        let total = self.total_bytes.load(Ordering::Relaxed);
+        if total == 0 {
+            return 0.0;
+        }
+        (self.bytes_read.load(Ordering::Relaxed) as f64 / total as f64).min(1.0)
+    }
+}
+
+struct CountingReader<'a, R> {
+    inner: R,
+    progress: &'a ParseProgress,
+}
+
+impl<R: Read> Read for CountingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.progress
+            .bytes_read
+            .fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+// ============================================================================
+// Apple Health
+// ============================================================================
+
+/// Parse an Apple Health export, either the zip the Health app shares or its bare `export.xml`.
+/// Blocking; run it on a blocking thread.
+pub fn read_apple_health_export(path: &Path, progress: &ParseProgress) -> Result<ParsedHealthData> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open upload {}", path.display()))?;
+
+    if !is_zip(&mut file)? {
+        let size = file.metadata()?.len();
+        progress.total_bytes.store(size, Ordering::Relaxed);
+        let reader = CountingReader {
+            inner: file,
+            progress,
+        };
+        return parse_apple_health_xml(BufReader::with_capacity(1 << 16, reader));
+    }
+
+    let mut archive = zip::ZipArchive::new(file).context("Invalid zip archive")?;
+    let index = apple_export_entry(&mut archive)?;
+    let entry = archive.by_index(index)?;
+    progress.total_bytes.store(entry.size(), Ordering::Relaxed);
+
+    let reader = CountingReader {
+        inner: entry,
+        progress,
+    };
+    parse_apple_health_xml(BufReader::with_capacity(1 << 16, reader))
+}
+
+/// Index of the main export in the archive: `export.xml`, or the largest other XML file for
+/// localized exports. The clinical `export_cda.xml` is never it.
+fn apple_export_entry<R: Read + Seek>(archive: &mut zip::ZipArchive<R>) -> Result<usize> {
+    let mut best: Option<(usize, u64)> = None;
+
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index)?;
+        let name = entry.name().to_lowercase();
+        let file_name = name.rsplit('/').next().unwrap_or(&name);
+
+        if file_name == "export.xml" {
+            return Ok(index);
+        }
+        if file_name.ends_with(".xml")
+            && !file_name.ends_with("_cda.xml")
+            && best.is_none_or(|(_, size)| entry.size() > size)
+        {
+            best = Some((index, entry.size()));
+        }
+    }
+
+    best.map(|(index, _)| index)
+        .ok_or_else(|| anyhow!("No export.xml found in the Apple Health archive"))
+}
+
+/// Stream `<Record>` elements out of an Apple Health `export.xml`
+pub fn parse_apple_health_xml<R: BufRead>(reader: R) -> Result<ParsedHealthData> {
+    let mut reader = quick_xml::Reader::from_reader(reader);
+    let mut buf = Vec::new();
+    let mut data = ParsedHealthData::default();
+    let mut sleep_samples = Vec::new();
+
+    loop {
+        match reader
+            .read_event_into(&mut buf)
+            .context("Invalid Apple Health XML")?
+        {
+            Event::Start(element) | Event::Empty(element)
+                if element.name().as_ref() == b"Record" =>
+            {
+                if let Some(record) = AppleRecord::from_element(&element)? {
+                    data.records_read += 1;
+                    record.collect(&mut data, &mut sleep_samples);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    data.sleep = summarize_sleep(sleep_samples);
+    Ok(data)
+}
+
+#[derive(Debug, Default)]
+struct AppleRecord {
+    record_type: String,
+    source_name: String,
+    value: String,
+    start_date: String,
+    end_date: String,
+}
+
+impl AppleRecord {
+    /// The record's attributes, or `None` for record types we don't import
+    fn from_element(element: &BytesStart) -> Result<Option<Self>> {
+        let mut record = AppleRecord::default();
+
+        for attribute in element.attributes() {
+            let attribute = attribute.context("Invalid Record attribute")?;
+            let target = match attribute.key.as_ref() {
+                b"type" => &mut record.record_type,
+                b"sourceName" => &mut record.source_name,
+                b"value" => &mut record.value,
+                b"startDate" => &mut record.start_date,
+                b"endDate" => &mut record.end_date,
+                _ => continue,
+            };
+            *target = attribute.unescape_value()?.into_owned();
+        }
+
+        let wanted = matches!(
+            record.record_type.as_str(),
+            APPLE_HRV_TYPE | APPLE_RESTING_HR_TYPE | APPLE_SLEEP_TYPE
+        );
+        Ok(wanted.then_some(record))
+    }
+
+    fn collect(self, data: &mut ParsedHealthData, sleep_samples: &mut Vec<SleepSample>) {
+        let (Ok(start), Ok(end)) = (
+            DateTime::parse_from_str(&self.start_date, APPLE_DATE_FORMAT),
+            DateTime::parse_from_str(&self.end_date, APPLE_DATE_FORMAT),
+        ) else {
+            return;
+        };
+
+        match self.record_type.as_str() {
+            APPLE_HRV_TYPE => {
+                // Apple only records SDNN, which is not comparable with RMSSD baselines
+                let Some(sdnn) = self
+                    .value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|v| *v > 0.0 && *v <= 200.0)
+                else {
+                    return;
+                };
+                data.hrv.push(CreateHrvReadingRequest {
+                    rmssd: None,
+                    sdnn: Some(sdnn),
+                    pnn50: None,
+                    measurement_timestamp: Some(start.with_timezone(&Utc)),
+                    metadata: Some(
+                        json!({ "source_name": self.source_name, "hrv_metric": "sdnn" }),
+                    ),
+                });
+            }
+            APPLE_RESTING_HR_TYPE => {
+                let Some(resting_hr) = self
+                    .value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|v| (30.0..=150.0).contains(v))
+                else {
+                    return;
+                };
+                data.resting_hr.push(CreateRestingHrRequest {
+                    resting_hr,
+                    measurement_timestamp: Some(start.with_timezone(&Utc)),
+                    metadata: Some(json!({ "source_name": self.source_name })),
+                });
+            }
+            APPLE_SLEEP_TYPE => {
+                let stage = match self.value.as_str() {
+                    "HKCategoryValueSleepAnalysisInBed" => SleepStage::InBed,
+                    "HKCategoryValueSleepAnalysisAsleep"
+                    | "HKCategoryValueSleepAnalysisAsleepUnspecified" => SleepStage::Asleep,
+                    "HKCategoryValueSleepAnalysisAsleepCore" => SleepStage::Light,
+                    "HKCategoryValueSleepAnalysisAsleepDeep" => SleepStage::Deep,
+                    "HKCategoryValueSleepAnalysisAsleepREM" => SleepStage::Rem,
+                    "HKCategoryValueSleepAnalysisAwake" => SleepStage::Awake,
+                    _ => return,
+                };
+                sleep_samples.push(SleepSample {
+                    source: self.source_name,
+                    start,
+                    end,
+                    stage,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+// ============================================================================
+// Health Connect
+// ============================================================================
+
+/// Parse a Health Connect export: a zip holding the `.db` SQLite database, or the database itself
+pub async fn read_health_connect_export(path: &Path) -> Result<ParsedHealthData> {
+    let upload = path.to_path_buf();
+    let database =
+        tokio::task::spawn_blocking(move || extract_health_connect_db(&upload)).await??;
+
+    let db_path = database.as_ref().map_or(path, |extracted| extracted.path());
+    let mut conn = SqliteConnectOptions::new()
+        .filename(db_path)
+        .read_only(true)
+        .connect()
+        .await
+        .context("Failed to open the Health Connect database")?;
+
+    let result = parse_health_connect_db(&mut conn).await;
+    conn.close().await.ok();
+    result
+}
+
+/// Copy the database out of a zipped export into a temporary file; `None` if the upload is
+/// already the database
+fn extract_health_connect_db(path: &Path) -> Result<Option<tempfile::NamedTempFile>> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open upload {}", path.display()))?;
+    if !is_zip(&mut file)? {
+        return Ok(None);
+    }
+
+    let mut archive = zip::ZipArchive::new(file).context("Invalid zip archive")?;
+    let index = (0..archive.len())
+        .find(|&index| {
+            archive
+                .by_index(index)
+                .is_ok_and(|entry| entry.name().to_lowercase().ends_with(".db"))
+        })
+        .ok_or_else(|| anyhow!("No database found in the Health Connect archive"))?;
+
+    let entry = archive.by_index(index)?;
+    let mut extracted = tempfile::NamedTempFile::new()?;
+    copy_with_limit(entry, &mut extracted, MAX_HEALTH_CONNECT_DB_BYTES)?;
+
+    Ok(Some(extracted))
+}
+
+/// Copy at most `max_bytes`, failing instead of truncating when the reader has more
+fn copy_with_limit(
+    reader: impl Read,
+    writer: &mut impl std::io::Write,
+    max_bytes: u64,
+) -> Result<u64> {
+    let copied = std::io::copy(&mut reader.take(max_bytes + 1), writer)?;
+    if copied > max_bytes {
+        return Err(anyhow!(
+            "Health Connect database exceeds the {} MB limit",
+            max_bytes / (1024 * 1024)
+        ));
+    }
+    Ok(copied)
+}
+
+/// Read HRV (RMSSD), resting HR and sleep sessions from Health Connect's record tables.
+/// Tables missing from the export are treated as empty.
+pub async fn parse_health_connect_db(conn: &mut SqliteConnection) -> Result<ParsedHealthData> {
+    let tables: HashSet<String> =
+        sqlx::query_scalar::<_, String>("SELECT name FROM sqlite_master WHERE type = 'table'")
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .collect();
+    let mut data = ParsedHealthData::default();
+
+    if tables.contains("heart_rate_variability_rmssd_record_table") {
+        let rows = sqlx::query_as::<_, (i64, f64)>(
+            "SELECT time, heart_rate_variability_millis \
+             FROM heart_rate_variability_rmssd_record_table",
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .context("Failed to read Health Connect HRV records")?;
+
+        data.records_read += rows.len() as u64;
+        data.hrv = rows
+            .into_iter()
+            .filter(|(_, rmssd)| *rmssd > 0.0 && *rmssd <= 200.0)
+            .filter_map(|(time, rmssd)| {
+                Some(CreateHrvReadingRequest {
+                    rmssd: Some(rmssd),
+                    sdnn: None,
+                    pnn50: None,
+                    measurement_timestamp: Some(Utc.timestamp_millis_opt(time).single()?),
+                    metadata: None,
+                })
+            })
+            .collect();
+    }
+
+    if tables.contains("resting_heart_rate_record_table") {
+        let rows = sqlx::query_as::<_, (i64, i64)>(
+            "SELECT time, beats_per_minute FROM resting_heart_rate_record_table",
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .context("Failed to read Health Connect resting heart rate records")?;
+
+        data.records_read += rows.len() as u64;
+        data.resting_hr = rows
+            .into_iter()
+            .filter(|(_, bpm)| (30..=150).contains(bpm))
+            .filter_map(|(time, bpm)| {
+                Some(CreateRestingHrRequest {
+                    resting_hr: bpm as f64,
+                    measurement_timestamp: Some(Utc.timestamp_millis_opt(time).single()?),
+                    metadata: None,
+                })
+            })
+            .collect();
+    }
+
+    if tables.contains("sleep_session_record_table") {
+        let query = if tables.contains("sleep_stages_table") {
+            "SELECT session.row_id, session.start_time, session.start_zone_offset, \
+                    session.end_time, session.end_zone_offset, \
+                    stage.stage_start_time, stage.stage_end_time, stage.stage_type \
+             FROM sleep_session_record_table session \
+             LEFT JOIN sleep_stages_table stage ON stage.parent_key = session.row_id \
+             ORDER BY session.row_id, stage.stage_start_time"
+        } else {
+            "SELECT row_id, start_time, start_zone_offset, end_time, end_zone_offset, \
+                    NULL AS stage_start_time, NULL AS stage_end_time, NULL AS stage_type \
+             FROM sleep_session_record_table"
+        };
+        let rows = sqlx::query_as::<_, HealthConnectSleepRow>(query)
+            .fetch_all(&mut *conn)
+            .await
+            .context("Failed to read Health Connect sleep sessions")?;
+
+        let mut sessions: BTreeMap<i64, Vec<HealthConnectSleepRow>> = BTreeMap::new();
+        for row in rows {
+            sessions.entry(row.row_id).or_default().push(row);
+        }
+        data.records_read += sessions.len() as u64;
+
+        let mut samples = Vec::new();
+        for (row_id, rows) in sessions {
+            samples.extend(health_connect_session_samples(row_id, &rows));
+        }
+        data.sleep = summarize_sleep(samples);
+    }
+
+    Ok(data)
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct HealthConnectSleepRow {
+    row_id: i64,
+    start_time: i64,
+    start_zone_offset: Option<i32>,
+    end_time: i64,
+    end_zone_offset: Option<i32>,
+    stage_start_time: Option<i64>,
+    stage_end_time: Option<i64>,
+    stage_type: Option<i32>,
+}
+
+/// A session without stages counts as asleep throughout; a staged session counts its stages,
+/// with the session itself as time in bed. Each session is its own source, so overlapping
+/// sessions written by different apps aren't added together.
+fn health_connect_session_samples(row_id: i64, rows: &[HealthConnectSleepRow]) -> Vec<SleepSample> {
+    let Some(session) = rows.first() else {
+        return Vec::new();
+    };
+    let to_local = |millis: i64, offset: Option<i32>| {
+        let offset = FixedOffset::east_opt(offset.unwrap_or(0))?;
+        Some(
+            Utc.timestamp_millis_opt(millis)
+                .single()?
+                .with_timezone(&offset),
+        )
+    };
+    let (Some(start), Some(end)) = (
+        to_local(session.start_time, session.start_zone_offset),
+        to_local(session.end_time, session.end_zone_offset),
+    ) else {
+        return Vec::new();
+    };
+    let source = format!("session-{}", row_id);
+
+    let staged: Vec<SleepSample> = rows
+        .iter()
+        .filter_map(|row| {
+            let stage = match row.stage_type? {
+                1 | 3 | 7 => SleepStage::Awake,
+                4 => SleepStage::Light,
+                5 => SleepStage::Deep,
+                6 => SleepStage::Rem,
+                _ => SleepStage::Asleep,
+            };
+            Some(SleepSample {
+                source: source.clone(),
+                start: to_local(row.stage_start_time?, session.start_zone_offset)?,
+                end: to_local(row.stage_end_time?, session.end_zone_offset)?,
+                stage,
+            })
+        })
+        .collect();
+
+    let session_stage = if staged.is_empty() {
+        SleepStage::Asleep
+    } else {
+        SleepStage::InBed
+    };
+    let mut samples = vec![SleepSample {
+        source,
+        start,
+        end,
+        stage: session_stage,
+    }];
+    samples.extend(staged);
+    samples
+}
+
+// ============================================================================
+// Sleep Aggregation
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SleepStage {
+    InBed,
+    /// Asleep without stage information
+    Asleep,
+    Light,
+    Deep,
+    Rem,
+    Awake,
+}
+
+impl SleepStage {
+    fn is_asleep(&self) -> bool {
+        matches!(
+            self,
+            SleepStage::Asleep | SleepStage::Light | SleepStage::Deep | SleepStage::Rem
+        )
+    }
+
+    fn is_staged(&self) -> bool {
+        matches!(self, SleepStage::Light | SleepStage::Deep | SleepStage::Rem)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SleepSample {
+    /// Device or app that recorded the sample
+    source: String,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    stage: SleepStage,
+}
+
+impl SleepSample {
+    fn hours(&self) -> f64 {
+        (self.end - self.start).num_seconds().max(0) as f64 / 3600.0
+    }
+
+    /// Night the sample belongs to, named by the local wake-up date
+    fn night(&self) -> NaiveDate {
+        (self.end.naive_local() + Duration::hours(NIGHT_CUTOFF_HOURS)).date()
+    }
+}
+
+/// Roll sleep samples up into one record per night. When several sources recorded the same
+/// night (a watch and a phone app, say), only the source with the most sleep is used.
+fn summarize_sleep(samples: Vec<SleepSample>) -> Vec<CreateSleepDataRequest> {
+    let mut nights: BTreeMap<NaiveDate, HashMap<String, Vec<SleepSample>>> = BTreeMap::new();
+    for sample in samples {
+        nights
+            .entry(sample.night())
+            .or_default()
+            .entry(sample.source.clone())
+            .or_default()
+            .push(sample);
+    }
+
+    nights
+        .into_iter()
+        .filter_map(|(night, sources)| {
+            let (source, samples) = sources.into_iter().max_by(|(a_name, a), (b_name, b)| {
+                asleep_hours(a)
+                    .total_cmp(&asleep_hours(b))
+                    .then_with(|| b_name.cmp(a_name))
+            })?;
+            summarize_night(night, &source, &samples)
+        })
+        .collect()
+}
+
+fn asleep_hours(samples: &[SleepSample]) -> f64 {
+    samples
+        .iter()
+        .filter(|s| s.stage.is_asleep())
+        .map(SleepSample::hours)
+        .sum()
+}
+
+fn summarize_night(
+    night: NaiveDate,
+    source: &str,
+    samples: &[SleepSample],
+) -> Option<CreateSleepDataRequest> {
+    let total_sleep_hours = asleep_hours(samples);
+    if total_sleep_hours <= 0.0 || total_sleep_hours > 24.0 {
+        return None;
+    }
+
+    let stage_hours = |stage: SleepStage| -> f64 {
+        samples
+            .iter()
+            .filter(|s| s.stage == stage)
+            .map(SleepSample::hours)
+            .sum()
+    };
+    let staged = samples.iter().any(|s| s.stage.is_staged());
+    let has_awake = samples.iter().any(|s| s.stage == SleepStage::Awake);
+
+    let bedtime = samples.iter().map(|s| s.start).min()?;
+    let wake_time = samples.iter().map(|s| s.end).max()?;
+    let time_in_bed = (wake_time - bedtime).num_seconds() as f64 / 3600.0;
+    let sleep_efficiency =
+        (time_in_bed > 0.0).then(|| (total_sleep_hours / time_in_bed * 100.0).min(100.0));
+
+    let fell_asleep = samples
+        .iter()
+        .filter(|s| s.stage.is_asleep())
+        .map(|s| s.start)
+        .min()?;
+    let sleep_latency_minutes = samples
+        .iter()
+        .any(|s| s.stage == SleepStage::InBed)
+        .then(|| (fell_asleep - bedtime).num_minutes().max(0) as i32);
+
+    Some(CreateSleepDataRequest {
+        total_sleep_hours,
+        deep_sleep_hours: staged.then(|| stage_hours(SleepStage::Deep)),
+        rem_sleep_hours: staged.then(|| stage_hours(SleepStage::Rem)),
+        light_sleep_hours: staged.then(|| stage_hours(SleepStage::Light)),
+        awake_hours: has_awake.then(|| stage_hours(SleepStage::Awake).min(24.0)),
+        sleep_efficiency,
+        sleep_latency_minutes,
+        bedtime: Some(bedtime.with_timezone(&Utc)),
+        wake_time: Some(wake_time.with_timezone(&Utc)),
+        sleep_date: Some(night),
+        metadata: Some(json!({ "source_name": source })),
+    })
+}
+
+fn is_zip(file: &mut File) -> Result<bool> {
+    let mut magic = [0u8; 4];
+    let read = file.read(&mut magic)?;
+    file.rewind()?;
+    Ok(read == 4 && magic == *b"PK\x03\x04")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn apple_record(record_type: &str, value: &str, start: &str, end: &str) -> String {
+        format!(
+            r#"<Record type="{}" sourceName="Apple Watch" unit="ms" creationDate="{}" startDate="{}" endDate="{}" value="{}"/>"#,
+            record_type, end, start, end, value
+        )
+    }
+
+    fn apple_export(records: &[String]) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE HealthData [
+<!ELEMENT HealthData (ExportDate,Me,(Record|Workout)*)>
+]>
+<HealthData locale="en_US">
+ <ExportDate value="2024-03-05 08:00:00 -0800"/>
+ <Record type="HKQuantityTypeIdentifierStepCount" sourceName="iPhone" unit="count" startDate="2024-03-01 09:00:00 -0800" endDate="2024-03-01 09:10:00 -0800" value="500"/>
+ {}
+</HealthData>"#,
+            records.join("\n ")
+        )
+    }
+
+    #[test]
+    fn test_apple_hrv_and_resting_hr() {
+        let xml = apple_export(&[
+            format!(
+                r#"<Record type="{}" sourceName="Apple Watch" unit="ms" startDate="2024-03-01 06:30:00 -0800" endDate="2024-03-01 06:31:00 -0800" value="48.5">
+  <HeartRateVariabilityMetadataList>
+   <InstantaneousBeatsPerMinute bpm="58" time="6:30:01.00 AM"/>
+  </HeartRateVariabilityMetadataList>
+ </Record>"#,
+                APPLE_HRV_TYPE
+            ),
+            apple_record(
+                APPLE_HRV_TYPE,
+                "450",
+                "2024-03-01 07:00:00 -0800",
+                "2024-03-01 07:01:00 -0800",
+            ),
+            apple_record(
+                APPLE_RESTING_HR_TYPE,
+                "52",
+                "2024-03-01 00:00:00 -0800",
+                "2024-03-01 23:59:00 -0800",
+            ),
+        ]);
+
+        let data = parse_apple_health_xml(xml.as_bytes()).unwrap();
+
+        assert_eq!(data.records_read, 3);
+        assert_eq!(data.hrv.len(), 1, "out of range HRV is dropped");
+        assert_eq!(data.hrv[0].rmssd, None, "SDNN is not stored as RMSSD");
+        assert_eq!(data.hrv[0].sdnn, Some(48.5));
+        assert_eq!(
+            data.hrv[0].measurement_timestamp.unwrap().to_rfc3339(),
+            "2024-03-01T14:30:00+00:00"
+        );
+        assert_eq!(data.resting_hr.len(), 1);
+        assert_eq!(data.resting_hr[0].resting_hr, 52.0);
+    }
+
+    #[test]
+    fn test_apple_sleep_stages_roll_up_into_nights() {
+        let sleep = |value: &str, start: &str, end: &str| {
+            apple_record(
+                APPLE_SLEEP_TYPE,
+                &format!("HKCategoryValueSleepAnalysis{}", value),
+                start,
+                end,
+            )
+        };
+        let xml = apple_export(&[
+            sleep(
+                "InBed",
+                "2024-03-01 22:30:00 -0800",
+                "2024-03-02 06:30:00 -0800",
+            ),
+            sleep(
+                "AsleepCore",
+                "2024-03-01 23:00:00 -0800",
+                "2024-03-02 02:00:00 -0800",
+            ),
+            sleep(
+                "AsleepDeep",
+                "2024-03-02 02:00:00 -0800",
+                "2024-03-02 03:30:00 -0800",
+            ),
+            sleep(
+                "Awake",
+                "2024-03-02 03:30:00 -0800",
+                "2024-03-02 04:00:00 -0800",
+            ),
+            sleep(
+                "AsleepREM",
+                "2024-03-02 04:00:00 -0800",
+                "2024-03-02 06:00:00 -0800",
+            ),
+            // Next night
+            sleep(
+                "AsleepUnspecified",
+                "2024-03-02 23:00:00 -0800",
+                "2024-03-03 05:00:00 -0800",
+            ),
+        ]);
+
+        let data = parse_apple_health_xml(xml.as_bytes()).unwrap();
+
+        assert_eq!(data.sleep.len(), 2);
+        let first = &data.sleep[0];
+        assert_eq!(first.sleep_date, NaiveDate::from_ymd_opt(2024, 3, 2));
+        assert_eq!(first.total_sleep_hours, 6.5);
+        assert_eq!(first.light_sleep_hours, Some(3.0));
+        assert_eq!(first.deep_sleep_hours, Some(1.5));
+        assert_eq!(first.rem_sleep_hours, Some(2.0));
+        assert_eq!(first.awake_hours, Some(0.5));
+        assert_eq!(first.sleep_latency_minutes, Some(30));
+        assert_eq!(first.sleep_efficiency, Some(6.5 / 8.0 * 100.0));
+
+        let second = &data.sleep[1];
+        assert_eq!(second.sleep_date, NaiveDate::from_ymd_opt(2024, 3, 3));
+        assert_eq!(second.total_sleep_hours, 6.0);
+        assert_eq!(second.deep_sleep_hours, None);
+        assert_eq!(second.sleep_latency_minutes, None);
+    }
+
+    #[test]
+    fn test_overlapping_sources_are_not_double_counted() {
+        let watch = apple_record(
+            APPLE_SLEEP_TYPE,
+            "HKCategoryValueSleepAnalysisAsleepCore",
+            "2024-03-01 23:00:00 -0800",
+            "2024-03-02 06:00:00 -0800",
+        );
+        let phone = watch
+            .replace("Apple Watch", "Sleep Cycle")
+            .replace("2024-03-02 06:00:00", "2024-03-02 05:00:00");
+
+        let data = parse_apple_health_xml(apple_export(&[watch, phone]).as_bytes()).unwrap();
+
+        assert_eq!(data.sleep.len(), 1);
+        assert_eq!(data.sleep[0].total_sleep_hours, 7.0);
+        assert_eq!(
+            data.sleep[0].metadata,
+            Some(json!({ "source_name": "Apple Watch" }))
+        );
+    }
+
+    #[test]
+    fn test_reads_export_xml_from_zip_and_reports_progress() {
+        let xml = apple_export(&[apple_record(
+            APPLE_RESTING_HR_TYPE,
+            "55",
+            "2024-03-01 00:00:00 +0000",
+            "2024-03-01 23:59:00 +0000",
+        )]);
+
+        let mut upload = tempfile::NamedTempFile::new().unwrap();
+        {
+            let mut zip = zip::ZipWriter::new(upload.as_file_mut());
+            let options = zip::write::FileOptions::default();
+            zip.start_file("apple_health_export/export_cda.xml", options)
+                .unwrap();
+            zip.write_all(b"<ClinicalDocument/>").unwrap();
+            zip.start_file("apple_health_export/export.xml", options)
+                .unwrap();
+            zip.write_all(xml.as_bytes()).unwrap();
+            zip.finish().unwrap();
+        }
+
+        let progress = ParseProgress::default();
+        let data = read_apple_health_export(upload.path(), &progress).unwrap();
+
+        assert_eq!(data.resting_hr.len(), 1);
+        assert_eq!(
+            progress.total_bytes.load(Ordering::Relaxed),
+            xml.len() as u64
+        );
+        assert_eq!(progress.fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_copy_with_limit_rejects_oversized_entries() {
+        let mut out = Vec::new();
+        assert_eq!(copy_with_limit(&[0u8; 16][..], &mut out, 16).unwrap(), 16);
+        assert_eq!(out.len(), 16);
+
+        let mut out = Vec::new();
+        assert!(copy_with_limit(&[0u8; 17][..], &mut out, 16).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_health_connect_database() {
+        let mut conn = SqliteConnectOptions::new()
+            .filename(":memory:")
+            .connect()
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE heart_rate_variability_rmssd_record_table (row_id INTEGER PRIMARY KEY, time INTEGER, zone_offset INTEGER, heart_rate_variability_millis REAL)",
+            "CREATE TABLE resting_heart_rate_record_table (row_id INTEGER PRIMARY KEY, time INTEGER, zone_offset INTEGER, beats_per_minute INTEGER)",
+            "CREATE TABLE sleep_session_record_table (row_id INTEGER PRIMARY KEY, start_time INTEGER, start_zone_offset INTEGER, end_time INTEGER, end_zone_offset INTEGER)",
+            "CREATE TABLE sleep_stages_table (row_id INTEGER PRIMARY KEY, parent_key INTEGER, stage_start_time INTEGER, stage_end_time INTEGER, stage_type INTEGER)",
+            // 2024-03-02 06:00 UTC
+            "INSERT INTO heart_rate_variability_rmssd_record_table VALUES (1, 1709359200000, 3600, 62.0)",
+            "INSERT INTO resting_heart_rate_record_table VALUES (1, 1709359200000, 3600, 49), (2, 1709359200000, 3600, 12)",
+            // 22:00 to 06:00 UTC, +01:00 local
+            "INSERT INTO sleep_session_record_table VALUES (1, 1709330400000, 3600, 1709359200000, 3600)",
+            "INSERT INTO sleep_stages_table VALUES (1, 1, 1709330400000, 1709334000000, 1), (2, 1, 1709334000000, 1709348400000, 4), (3, 1, 1709348400000, 1709355600000, 5), (4, 1, 1709355600000, 1709359200000, 6)",
+            // An unstaged nap on another day
+            "INSERT INTO sleep_session_record_table VALUES (2, 1709470800000, 3600, 1709474400000, 3600)",
+        ] {
+            sqlx::query(statement).execute(&mut conn).await.unwrap();
+        }
+
+        let data = parse_health_connect_db(&mut conn).await.unwrap();
+
+        assert_eq!(data.hrv.len(), 1);
+        assert_eq!(data.hrv[0].rmssd, Some(62.0));
+        assert_eq!(data.hrv[0].sdnn, None);
+        assert_eq!(data.resting_hr.len(), 1);
+        assert_eq!(data.resting_hr[0].resting_hr, 49.0);
+
+        assert_eq!(data.sleep.len(), 2);
+        let night = &data.sleep[0];
+        assert_eq!(night.sleep_date, NaiveDate::from_ymd_opt(2024, 3, 2));
+        assert_eq!(night.total_sleep_hours, 7.0);
+        assert_eq!(night.light_sleep_hours, Some(4.0));
+        assert_eq!(night.deep_sleep_hours, Some(2.0));
+        assert_eq!(night.rem_sleep_hours, Some(1.0));
+        assert_eq!(night.awake_hours, Some(1.0));
+        assert_eq!(night.sleep_latency_minutes, Some(60));
+        assert_eq!(data.sleep[1].total_sleep_hours, 1.0);
+    }
+}
@@ -0,0 +1,425 @@
+use anyhow::{anyhow, Result};
+use sqlx::PgPool;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::models::{HealthImport, HealthImportFormat, HealthImportStatus};
+use crate::services::health_export_parser::{
+    read_apple_health_export, read_health_connect_export, ParseProgress, ParsedHealthData,
+};
+use crate::services::RecoveryDataService;
+
+const IMPORT_COLUMNS: &str = r#"
+    id, user_id, format, status, job_id, file_name, storage_key, size_bytes, progress_percent,
+    records_read, hrv_imported, sleep_imported, resting_hr_imported, duplicates_skipped,
+    error_message, created_at, started_at, completed_at
+"#;
+
+/// Rows inserted per statement
+const IMPORT_BATCH_SIZE: usize = 1000;
+/// Progress reached once the export is parsed; inserting takes the rest
+const PARSED_PERCENT: f64 = 70.0;
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Rows inserted by an import
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportCounts {
+    pub hrv: u64,
+    pub sleep: u64,
+    pub resting_hr: u64,
+    pub duplicates: u64,
+}
+
+/// Imports Apple Health and Health Connect exports into the recovery tables. Uploads are
+/// written to disk by the API and parsed by a background job, which records its progress.
+#[derive(Clone)]
+pub struct HealthImportService {
+    db: PgPool,
+    recovery_data_service: RecoveryDataService,
+    import_dir: PathBuf,
+}
+
+impl HealthImportService {
+    /// Create a new HealthImportService storing uploads under `HEALTH_IMPORT_DIR` (default `imports`)
+    pub fn new(db: PgPool) -> Self {
+        let import_dir =
+            std::env::var("HEALTH_IMPORT_DIR").unwrap_or_else(|_| "imports".to_string());
+
+        Self {
+            recovery_data_service: RecoveryDataService::new(db.clone()),
+            db,
+            import_dir: PathBuf::from(import_dir),
+        }
+    }
+
+    /// Record an import whose file is about to be uploaded
+    pub async fn create_import(
+        &self,
+        user_id: Uuid,
+        format: HealthImportFormat,
+        file_name: Option<&str>,
+    ) -> Result<HealthImport> {
+        let import_id = Uuid::new_v4();
+        let storage_key = format!("{}/{}.upload", user_id, import_id);
+
+        let import = sqlx::query_as::<_, HealthImport>(&format!(
+            r#"
+            INSERT INTO health_imports (id, user_id, format, file_name, storage_key)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING {}
+            "#,
+            IMPORT_COLUMNS
+        ))
+        .bind(import_id)
+        .bind(user_id)
+        .bind(format.as_str())
+        .bind(file_name)
+        .bind(&storage_key)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(import)
+    }
+
+    /// Where the import's upload is stored
+    pub fn upload_path(&self, import: &HealthImport) -> Result<PathBuf> {
+        let storage_key = import
+            .storage_key
+            .as_deref()
+            .ok_or_else(|| anyhow!("Import {} has no upload", import.id))?;
+
+        self.resolve(storage_key)
+    }
+
+    /// Mark the upload as received so a job can pick it up
+    pub async fn finish_upload(&self, import_id: Uuid, size_bytes: i64) -> Result<HealthImport> {
+        let import = sqlx::query_as::<_, HealthImport>(&format!(
+            "UPDATE health_imports SET status = 'pending', size_bytes = $2 WHERE id = $1 RETURNING {}",
+            IMPORT_COLUMNS
+        ))
+        .bind(import_id)
+        .bind(size_bytes)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(import)
+    }
+
+    /// Link an import to the job that processes it
+    pub async fn set_job_id(&self, import_id: Uuid, job_id: Uuid) -> Result<HealthImport> {
+        let import = sqlx::query_as::<_, HealthImport>(&format!(
+            "UPDATE health_imports SET job_id = $2 WHERE id = $1 RETURNING {}",
+            IMPORT_COLUMNS
+        ))
+        .bind(import_id)
+        .bind(job_id)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(import)
+    }
+
+    /// Record a failure and delete whatever was uploaded
+    pub async fn fail_import(&self, import: &HealthImport, error: &str) -> Result<()> {
+        self.remove_upload(import).await;
+
+        sqlx::query(
+            r#"
+            UPDATE health_imports
+            SET status = 'failed', error_message = $2, storage_key = NULL, completed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(import.id)
+        .bind(error)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_import(&self, import_id: Uuid) -> Result<Option<HealthImport>> {
+        let import = sqlx::query_as::<_, HealthImport>(&format!(
+            "SELECT {} FROM health_imports WHERE id = $1",
+            IMPORT_COLUMNS
+        ))
+        .bind(import_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(import)
+    }
+
+    /// Get an import only if it belongs to `user_id`
+    pub async fn get_user_import(
+        &self,
+        user_id: Uuid,
+        import_id: Uuid,
+    ) -> Result<Option<HealthImport>> {
+        let import = sqlx::query_as::<_, HealthImport>(&format!(
+            "SELECT {} FROM health_imports WHERE id = $1 AND user_id = $2",
+            IMPORT_COLUMNS
+        ))
+        .bind(import_id)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(import)
+    }
+
+    pub async fn list_user_imports(&self, user_id: Uuid) -> Result<Vec<HealthImport>> {
+        let imports = sqlx::query_as::<_, HealthImport>(&format!(
+            "SELECT {} FROM health_imports WHERE user_id = $1 ORDER BY created_at DESC",
+            IMPORT_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(imports)
+    }
+
+    /// Parse an uploaded export, insert its records and recompute the recovery baseline.
+    /// Failures are recorded on the import and returned so the job retries; the upload is kept
+    /// until the import completes.
+    pub async fn run_import(&self, import_id: Uuid) -> Result<()> {
+        let import = self
+            .get_import(import_id)
+            .await?
+            .ok_or_else(|| anyhow!("Import {} not found", import_id))?;
+
+        if import.status == HealthImportStatus::Completed.as_str() {
+            info!("Import {} already completed", import_id);
+            return Ok(());
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE health_imports
+            SET status = 'parsing', progress_percent = 0, error_message = NULL,
+                started_at = COALESCE(started_at, NOW())
+            WHERE id = $1
+            "#,
+        )
+        .bind(import_id)
+        .execute(&self.db)
+        .await?;
+
+        match self.import_upload(&import).await {
+            Ok(counts) => {
+                self.remove_upload(&import).await;
+
+                sqlx::query(
+                    r#"
+                    UPDATE health_imports
+                    SET status = 'completed', progress_percent = 100, storage_key = NULL,
+                        hrv_imported = $2, sleep_imported = $3, resting_hr_imported = $4,
+                        duplicates_skipped = $5, completed_at = NOW()
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(import_id)
+                .bind(counts.hrv as i32)
+                .bind(counts.sleep as i32)
+                .bind(counts.resting_hr as i32)
+                .bind(counts.duplicates as i32)
+                .execute(&self.db)
+                .await?;
+
+                info!(
+                    "Import {} completed: {} HRV, {} sleep, {} resting HR, {} duplicates skipped",
+                    import_id, counts.hrv, counts.sleep, counts.resting_hr, counts.duplicates
+                );
+                Ok(())
+            }
+            Err(e) => {
+                sqlx::query(
+                    "UPDATE health_imports SET status = 'failed', error_message = $2 WHERE id = $1",
+                )
+                .bind(import_id)
+                .bind(e.to_string())
+                .execute(&self.db)
+                .await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete uploads of imports that failed or never finished uploading
+    pub async fn purge_stale_uploads(&self, older_than_days: i32) -> Result<u64> {
+        let stale = sqlx::query_as::<_, HealthImport>(&format!(
+            r#"
+            SELECT {} FROM health_imports
+            WHERE status IN ('uploading', 'failed') AND storage_key IS NOT NULL
+              AND created_at < NOW() - make_interval(days => $1)
+            "#,
+            IMPORT_COLUMNS
+        ))
+        .bind(older_than_days)
+        .fetch_all(&self.db)
+        .await?;
+
+        for import in &stale {
+            self.remove_upload(import).await;
+
+            sqlx::query(
+                r#"
+                UPDATE health_imports
+                SET status = 'failed', storage_key = NULL,
+                    error_message = COALESCE(error_message, 'Upload was never completed')
+                WHERE id = $1
+                "#,
+            )
+            .bind(import.id)
+            .execute(&self.db)
+            .await?;
+        }
+
+        if !stale.is_empty() {
+            info!("Purged {} stale health import uploads", stale.len());
+        }
+
+        Ok(stale.len() as u64)
+    }
+
+    async fn import_upload(&self, import: &HealthImport) -> Result<ImportCounts> {
+        let path = self.upload_path(import)?;
+
+        let data = match import.import_format()? {
+            HealthImportFormat::AppleHealth => self.parse_apple_health(import.id, path).await?,
+            HealthImportFormat::HealthConnect => read_health_connect_export(&path).await?,
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE health_imports
+            SET status = 'importing', progress_percent = $2, records_read = $3
+            WHERE id = $1
+            "#,
+        )
+        .bind(import.id)
+        .bind(PARSED_PERCENT as i16)
+        .bind(data.records_read as i32)
+        .execute(&self.db)
+        .await?;
+
+        let counts = self.insert_records(import, &data).await?;
+
+        self.recovery_data_service
+            .calculate_baseline(import.user_id)
+            .await?;
+
+        Ok(counts)
+    }
+
+    /// Parse on a blocking thread, reporting how much of the XML has been read
+    async fn parse_apple_health(&self, import_id: Uuid, path: PathBuf) -> Result<ParsedHealthData> {
+        let progress = Arc::new(ParseProgress::default());
+        let mut parse = tokio::task::spawn_blocking({
+            let progress = progress.clone();
+            move || read_apple_health_export(&path, &progress)
+        });
+        let mut interval = tokio::time::interval(PROGRESS_INTERVAL);
+
+        loop {
+            tokio::select! {
+                result = &mut parse => return result?,
+                _ = interval.tick() => {
+                    let percent = (progress.fraction() * PARSED_PERCENT) as i16;
+                    if let Err(e) = self.set_progress(import_id, percent).await {
+                        warn!("Failed to record progress of import {}: {}", import_id, e);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn insert_records(
+        &self,
+        import: &HealthImport,
+        data: &ParsedHealthData,
+    ) -> Result<ImportCounts> {
+        let source = import.import_format()?.data_source();
+        let user_id = import.user_id;
+        let total = data.len().max(1) as f64;
+        let mut processed = 0;
+        let mut counts = ImportCounts::default();
+
+        for batch in data.hrv.chunks(IMPORT_BATCH_SIZE) {
+            counts.hrv += self
+                .recovery_data_service
+                .import_hrv_readings(user_id, source, batch)
+                .await?;
+            processed += batch.len();
+            self.set_progress(import.id, insert_percent(processed, total))
+                .await?;
+        }
+
+        for batch in data.sleep.chunks(IMPORT_BATCH_SIZE) {
+            counts.sleep += self
+                .recovery_data_service
+                .import_sleep_data(user_id, source, batch)
+                .await?;
+            processed += batch.len();
+            self.set_progress(import.id, insert_percent(processed, total))
+                .await?;
+        }
+
+        for batch in data.resting_hr.chunks(IMPORT_BATCH_SIZE) {
+            counts.resting_hr += self
+                .recovery_data_service
+                .import_resting_hr(user_id, source, batch)
+                .await?;
+            processed += batch.len();
+            self.set_progress(import.id, insert_percent(processed, total))
+                .await?;
+        }
+
+        counts.duplicates = data.len() as u64 - counts.hrv - counts.sleep - counts.resting_hr;
+        Ok(counts)
+    }
+
+    async fn set_progress(&self, import_id: Uuid, percent: i16) -> Result<()> {
+        sqlx::query("UPDATE health_imports SET progress_percent = $2 WHERE id = $1")
+            .bind(import_id)
+            .bind(percent.clamp(0, 99))
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove_upload(&self, import: &HealthImport) {
+        let Ok(path) = self.upload_path(import) else {
+            return;
+        };
+
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Failed to delete import upload {}: {}", path.display(), e),
+        }
+    }
+
+    /// Map a storage key to a path under the import directory, rejecting anything that could escape it
+    fn resolve(&self, storage_key: &str) -> Result<PathBuf> {
+        let relative = Path::new(storage_key);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(anyhow!("Invalid storage key: {}", storage_key));
+        }
+
+        Ok(self.import_dir.join(relative))
+    }
+}
+
+/// Progress while inserting, from the end of parsing up to 99%
+fn insert_percent(processed: usize, total: f64) -> i16 {
+    (PARSED_PERCENT + processed as f64 / total * (99.0 - PARSED_PERCENT)) as i16
+}
@@ -2,34 +2,96 @@ use anyhow::{Result, anyhow};
 use chrono::Utc;
 use ndarray::{Array1, Array2};
 use linfa::prelude::*;
-use linfa_linear::LinearRegression;
+use linfa_linear::{FittedLinearRegression, LinearRegression};
 use linfa_trees::RandomForest;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::info;
 use uuid::Uuid;
 
-use crate::models::{TrainingFeatures, TrainingLoadPrediction, ModelMetrics, TrainingDataPoint};
+use crate::models::{TrainingFeatures, TrainingLoadPrediction, ModelMetrics, TrainingDataPoint, FEATURE_SCHEMA_VERSION};
 use crate::services::FeatureEngineeringService;
+use crate::services::model_store_service::ModelStoreService;
 
 /// Machine Learning model types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ModelType {
     LinearRegression,
     RandomForest,
 }
 
+impl ModelType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelType::LinearRegression => "linear_regression",
+            ModelType::RandomForest => "random_forest",
+        }
+    }
+}
+
 /// Trained ML model for TSS prediction
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrainedModel {
     pub model_type: ModelType,
     pub model_version: String,
-    pub linear_model: Option<LinearRegression<f64>>,
+    pub linear_model: Option<FittedLinearRegression<f64>>,
     pub forest_model: Option<RandomForest<f64, usize>>,
     pub feature_scaler: FeatureScaler,
+    /// `TrainingFeatures` column order the model was fitted on
+    pub feature_names: Vec<String>,
+    /// `TrainingFeatures` schema version the model was fitted on; artifacts saved
+    /// before versioning were all fitted on version 1
+    #[serde(default = "legacy_feature_schema_version")]
+    pub feature_schema_version: u32,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
+fn legacy_feature_schema_version() -> u32 {
+    1
+}
+
+impl TrainedModel {
+    /// Ensure the model was fitted on the same feature columns, in the same order,
+    /// that `TrainingFeatures::to_ndarray_for_schema` produces for its schema version
+    pub fn check_feature_schema(&self) -> Result<()> {
+        let expected = TrainingFeatures::feature_names_for_schema(self.feature_schema_version)
+            .ok_or_else(|| anyhow!(
+                "Model {} uses unknown feature schema version {}; retrain required",
+                self.model_version,
+                self.feature_schema_version
+            ))?;
+
+        if self.feature_names != expected {
+            return Err(anyhow!(
+                "Model {} was trained on a different feature schema ({} features, expected {}); retrain required",
+                self.model_version,
+                self.feature_names.len(),
+                expected.len()
+            ));
+        }
+
+        if self.feature_scaler.means.len() != expected.len() || self.feature_scaler.stds.len() != expected.len() {
+            return Err(anyhow!(
+                "Model {} has a feature scaler of the wrong width; retrain required",
+                self.model_version
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the model was fitted on an older feature schema than the current one
+    pub fn is_outdated(&self) -> bool {
+        self.feature_schema_version < FEATURE_SCHEMA_VERSION
+    }
+}
+
 /// Feature scaling for normalization
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureScaler {
     pub means: Array1<f64>,
     pub stds: Array1<f64>,
@@ -60,18 +122,24 @@ impl FeatureScaler {
 pub struct MLModelService {
     db: PgPool,
     feature_service: FeatureEngineeringService,
+    model_store: ModelStoreService,
     current_model: Option<TrainedModel>,
+    // Active models restored from the model store, by user
+    loaded_models: Arc<RwLock<HashMap<Uuid, TrainedModel>>>,
 }
 
 impl MLModelService {
     /// Create a new MLModelService
     pub fn new(db: PgPool) -> Self {
         let feature_service = FeatureEngineeringService::new(db.clone());
+        let model_store = ModelStoreService::new(db.clone());
 
         Self {
             db,
             feature_service,
+            model_store,
             current_model: None,
+            loaded_models: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -111,6 +179,8 @@ impl MLModelService {
             linear_model: Some(linear_model),
             forest_model: None,
             feature_scaler: scaler,
+            feature_names: TrainingFeatures::feature_names(),
+            feature_schema_version: FEATURE_SCHEMA_VERSION,
             created_at: Utc::now(),
         };
 
@@ -166,6 +236,8 @@ impl MLModelService {
             linear_model: None,
             forest_model: Some(forest_model),
             feature_scaler: scaler,
+            feature_names: TrainingFeatures::feature_names(),
+            feature_schema_version: FEATURE_SCHEMA_VERSION,
             created_at: Utc::now(),
         };
 
@@ -183,8 +255,80 @@ impl MLModelService {
         let model = self.current_model.as_ref()
             .ok_or_else(|| anyhow!("No trained model available"))?;
 
-        // Convert features to ndarray
-        let feature_array = features.to_ndarray();
+        self.predict_with_model(model, features)
+    }
+
+    /// Make a TSS prediction with the user's active stored model, restoring it on first use
+    pub async fn predict_tss_for_user(
+        &self,
+        user_id: Uuid,
+        features: &TrainingFeatures,
+    ) -> Result<TrainingLoadPrediction> {
+        let active_version = self.model_store.active_version(user_id).await?
+            .ok_or_else(|| anyhow!("No trained model available for user {}", user_id))?;
+
+        self.predict_tss_with_version(user_id, &active_version, features).await
+    }
+
+    /// Make a TSS prediction with a specific stored model version of the user's
+    pub async fn predict_tss_with_version(
+        &self,
+        user_id: Uuid,
+        model_version: &str,
+        features: &TrainingFeatures,
+    ) -> Result<TrainingLoadPrediction> {
+        // Another instance may have activated a newer model since this one was loaded
+        if let Some(model) = self.loaded_models.read().await.get(&user_id) {
+            if model.model_version == model_version {
+                return self.predict_with_model(model, features);
+            }
+        }
+
+        let model = self.model_store.load_model(user_id, Some(model_version)).await?
+            .ok_or_else(|| anyhow!("Model {} not found for user {}", model_version, user_id))?;
+        info!("Restored model {} for user {}", model.model_version, user_id);
+
+        let prediction = self.predict_with_model(&model, features);
+        self.loaded_models.write().await.insert(user_id, model);
+
+        prediction
+    }
+
+    /// Persist the most recently trained model so it survives restarts
+    pub async fn save_current_model(&self, user_id: Uuid, metrics: &ModelMetrics) -> Result<()> {
+        let model = self.current_model.as_ref()
+            .ok_or_else(|| anyhow!("No trained model available"))?;
+
+        self.model_store.save_model(user_id, model, metrics).await?;
+        Ok(())
+    }
+
+    /// Newest stored model of the given type (and feature schema, if given) for the user,
+    /// e.g. to serve an A/B test arm
+    pub async fn latest_model_version(
+        &self,
+        user_id: Uuid,
+        model_type: &str,
+        feature_schema_version: Option<u32>,
+    ) -> Result<Option<String>> {
+        self.model_store.latest_version_of_type(user_id, model_type, feature_schema_version).await
+    }
+
+    /// Make a stored model version the one used for the user's predictions
+    pub async fn activate_model(&self, user_id: Uuid, model_version: &str) -> Result<bool> {
+        let activated = self.model_store.activate_model(user_id, model_version).await?;
+
+        if activated {
+            self.loaded_models.write().await.remove(&user_id);
+        }
+
+        Ok(activated)
+    }
+
+    fn predict_with_model(&self, model: &TrainedModel, features: &TrainingFeatures) -> Result<TrainingLoadPrediction> {
+        // Convert features to ndarray in the layout the model was fitted on
+        let feature_array = features.to_ndarray_for_schema(model.feature_schema_version)
+            .ok_or_else(|| anyhow!("Unsupported feature schema version {}", model.feature_schema_version))?;
         let scaled_features = model.feature_scaler.transform_single(&feature_array);
 
         let (prediction, confidence) = match &model.model_type {
@@ -408,6 +552,10 @@ mod tests {
                     days_until_goal_event: if i % 3 == 0 { Some(30 - i as i32) } else { None },
                     preferred_workout_types: vec!["endurance".to_string()],
                     seasonal_factors: 0.8 + (i as f32 * 0.01),
+                    readiness_score: if i % 4 == 0 { None } else { Some(50.0 + i as f32) },
+                    hrv_trend: Some(((i % 3) as f32) - 1.0),
+                    sleep_quality: Some(60.0 + (i % 5) as f32 * 5.0),
+                    rhr_deviation: Some((i % 6) as f32 - 2.0),
                 },
                 actual_tss: tss,
                 actual_workout_type: if i % 2 == 0 { "endurance".to_string() } else { "threshold".to_string() },
@@ -614,11 +762,15 @@ mod tests {
             days_until_goal_event: Some(45),
             preferred_workout_types: vec!["endurance".to_string(), "vo2max".to_string()],
             seasonal_factors: 0.9,
+            readiness_score: Some(65.0),
+            hrv_trend: Some(1.0),
+            sleep_quality: Some(80.0),
+            rhr_deviation: Some(-3.0),
         };
 
         // Test conversion to ndarray
         let array = features.to_ndarray();
-        assert_eq!(array.len(), 13); // 8 numeric + 5 workout type features
+        assert_eq!(array.len(), 17); // 8 numeric + 5 workout type + 4 recovery features
 
         // Verify numeric features
         assert_eq!(array[0], 100.0);  // current_ctl
@@ -636,6 +788,12 @@ mod tests {
         assert_eq!(array[10], 1.0);   // prefers_vo2max
         assert_eq!(array[11], 0.0);   // prefers_recovery
         assert_eq!(array[12], 0.0);   // prefers_strength
+
+        // Verify recovery features
+        assert_eq!(array[13], 65.0);  // readiness_score
+        assert_eq!(array[14], 1.0);   // hrv_trend
+        assert_eq!(array[15], 80.0);  // sleep_quality
+        assert_eq!(array[16], -3.0);  // rhr_deviation
     }
 
     #[test]
@@ -662,5 +820,92 @@ mod tests {
         };
         let array = features_with_none.to_ndarray();
         assert_eq!(array[6], -1.0); // None should be converted to -1
+        assert_eq!(array[13], -1.0); // Missing readiness score
+        assert_eq!(array[14], 0.0);  // Missing HRV trend is neutral
+    }
+
+    fn create_test_model(feature_names: Vec<String>) -> TrainedModel {
+        create_test_model_with_schema(feature_names, FEATURE_SCHEMA_VERSION)
+    }
+
+    fn create_test_model_with_schema(feature_names: Vec<String>, feature_schema_version: u32) -> TrainedModel {
+        let n_features = feature_names.len();
+        let data = Array2::from_shape_fn((4, n_features), |(i, j)| (i + j) as f64);
+
+        TrainedModel {
+            model_type: ModelType::LinearRegression,
+            model_version: "linear_v1".to_string(),
+            linear_model: None,
+            forest_model: None,
+            feature_scaler: FeatureScaler::fit(&data),
+            feature_names,
+            feature_schema_version,
+            created_at: Utc::now(),
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_model_artifact_round_trip() {
+        use crate::services::model_store_service::{decode_artifact, encode_artifact, ARTIFACT_FORMAT_VERSION};
+
+        let model = create_test_model(TrainingFeatures::feature_names());
+        let artifact = encode_artifact(&model).unwrap();
+        let restored = decode_artifact(ARTIFACT_FORMAT_VERSION, &artifact).unwrap();
+
+        assert_eq!(restored.model_version, model.model_version);
+        assert_eq!(restored.model_type.as_str(), "linear_regression");
+        assert_eq!(restored.feature_names, TrainingFeatures::feature_names());
+        assert_eq!(restored.feature_scaler.means, model.feature_scaler.means);
+        assert_eq!(restored.feature_scaler.stds, model.feature_scaler.stds);
+
+        // Artifacts from another format version are not loaded
+        assert!(decode_artifact(ARTIFACT_FORMAT_VERSION + 1, &artifact).is_err());
+    }
+
+    #[test]
+    fn test_stale_feature_schema_rejected() {
+        use crate::services::model_store_service::{decode_artifact, encode_artifact, ARTIFACT_FORMAT_VERSION};
+
+        // Same features, different column order
+        let mut reordered = TrainingFeatures::feature_names();
+        reordered.swap(0, 1);
+        let model = create_test_model(reordered);
+        assert!(model.check_feature_schema().is_err());
+
+        let artifact = encode_artifact(&model).unwrap();
+        let err = decode_artifact(ARTIFACT_FORMAT_VERSION, &artifact).unwrap_err();
+        assert!(err.to_string().contains("different feature schema"));
+
+        // Missing features
+        let mut truncated = TrainingFeatures::feature_names();
+        truncated.pop();
+        assert!(create_test_model(truncated).check_feature_schema().is_err());
+    }
+
+    #[test]
+    fn test_legacy_feature_schema_accepted() {
+        use crate::services::model_store_service::{decode_artifact, encode_artifact, ARTIFACT_FORMAT_VERSION};
+
+        let legacy_names = TrainingFeatures::feature_names_for_schema(1).unwrap();
+        let model = create_test_model_with_schema(legacy_names.clone(), 1);
+        assert!(model.check_feature_schema().is_ok());
+        assert!(model.is_outdated());
+
+        // Artifacts saved before versioning have no schema version and default to 1
+        let mut json: serde_json::Value = serde_json::from_slice(&encode_artifact(&model).unwrap()).unwrap();
+        json.as_object_mut().unwrap().remove("feature_schema_version");
+        let restored = decode_artifact(ARTIFACT_FORMAT_VERSION, &serde_json::to_vec(&json).unwrap()).unwrap();
+        assert_eq!(restored.feature_schema_version, 1);
+        assert_eq!(restored.feature_names, legacy_names);
+
+        // A legacy model is fed the legacy feature layout
+        let features = TrainingFeatures::default();
+        let array = features.to_ndarray_for_schema(restored.feature_schema_version).unwrap();
+        assert_eq!(array.len(), restored.feature_scaler.means.len());
+
+        // Legacy names with the current version, or an unknown version, are rejected
+        assert!(create_test_model_with_schema(legacy_names.clone(), FEATURE_SCHEMA_VERSION).check_feature_schema().is_err());
+        assert!(create_test_model_with_schema(legacy_names, FEATURE_SCHEMA_VERSION + 1).check_feature_schema().is_err());
+        assert!(!create_test_model(TrainingFeatures::feature_names()).is_outdated());
+    }
+}
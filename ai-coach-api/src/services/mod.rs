@@ -4,64 +4,104 @@ pub mod user_service;
 pub mod athlete_profile_service;
 pub mod training_session_service;
 pub mod training_analysis_service;
+pub mod training_file_parser;
+pub mod training_metrics_calculator;
+pub mod fit_decoder;
 pub mod background_job_service;
+pub mod data_export_service;
 pub mod coaching_recommendation_service;
 pub mod training_plan_service;
 pub mod model_prediction_service;
 pub mod feature_engineering_service;
 pub mod ml_model_service;
+pub mod model_store_service;
 pub mod model_training_service;
 pub mod training_recommendation_service;
 pub mod model_versioning_service;
 pub mod workout_recommendation_service;
+pub mod workout_export_service;
 pub mod performance_insights_service;
 pub mod notification_service;
 pub mod notification_scheduler;
+pub mod notification_timing;
 pub mod email_notification_service;
 pub mod goal_service;
+pub mod goal_progress_evaluator;
 pub mod event_service;
 pub mod plan_generation_service;
 pub mod vision_analysis_service;
 pub mod video_storage_service;
 pub mod video_processing_service;
+pub mod vision_pipeline_service;
 pub mod pose_estimation_service;
 pub mod keypoint_processor;
+pub mod movement_analysis_service;
 pub mod recovery_data_service;
 pub mod recovery_analysis_service;
 pub mod training_adjustment_service;
+pub mod training_adjustment_scheduler;
 pub mod recovery_alert_service;
 pub mod oura_api_client;
-pub mod oura_integration_service;
+pub mod whoop_api_client;
+pub mod polar_api_client;
+pub mod wearable_provider;
+pub mod wearable_integration_service;
+pub mod wearable_sync_scheduler;
+pub mod health_export_parser;
+pub mod health_import_service;
+pub mod coach_athlete_service;
+pub mod calendar_feed_service;
+pub mod web_push_client;
+pub mod web_push_service;
 
 pub use user_service::UserService;
 pub use athlete_profile_service::AthleteProfileService;
 pub use training_session_service::TrainingSessionService;
 pub use training_analysis_service::TrainingAnalysisService;
+pub use training_file_parser::TrainingFileParser;
+pub use training_metrics_calculator::TrainingMetricsCalculator;
+pub use fit_decoder::FitDecoder;
 pub use background_job_service::BackgroundJobService;
+pub use data_export_service::DataExportService;
 pub use coaching_recommendation_service::CoachingRecommendationService;
 pub use training_plan_service::TrainingPlanService;
 pub use model_prediction_service::ModelPredictionService;
 pub use feature_engineering_service::FeatureEngineeringService;
 pub use ml_model_service::MLModelService;
+pub use model_store_service::ModelStoreService;
 pub use model_training_service::ModelTrainingService;
 pub use training_recommendation_service::TrainingRecommendationService;
 pub use model_versioning_service::ModelVersioningService;
 pub use workout_recommendation_service::WorkoutRecommendationService;
+pub use workout_export_service::WorkoutExportService;
 pub use performance_insights_service::PerformanceInsightsService;
 pub use notification_service::NotificationService;
 pub use notification_scheduler::NotificationScheduler;
 pub use email_notification_service::EmailNotificationService;
 pub use goal_service::GoalService;
+pub use goal_progress_evaluator::GoalProgressEvaluator;
 pub use event_service::EventService;
 pub use plan_generation_service::PlanGenerationService;
 pub use vision_analysis_service::VisionAnalysisService;
-pub use video_storage_service::VideoStorageService;
+pub use video_storage_service::{LocalVideoStorage, S3VideoStorage, VideoStorage};
 pub use video_processing_service::VideoProcessingService;
+pub use vision_pipeline_service::VisionPipelineService;
 pub use pose_estimation_service::PoseEstimationService;
 pub use keypoint_processor::KeypointProcessor;
+pub use movement_analysis_service::MovementAnalysisService;
 pub use recovery_data_service::RecoveryDataService;
 pub use recovery_analysis_service::RecoveryAnalysisService;
 pub use training_adjustment_service::TrainingAdjustmentService;
+pub use training_adjustment_scheduler::{TrainingAdjustmentConfig, TrainingAdjustmentScheduler};
 pub use recovery_alert_service::RecoveryAlertService;
 pub use oura_api_client::OuraApiClient;
-pub use oura_integration_service::OuraIntegrationService;
\ No newline at end of file
+pub use whoop_api_client::WhoopApiClient;
+pub use polar_api_client::PolarApiClient;
+pub use wearable_provider::{WearableProvider, WearableProviderRegistry};
+pub use wearable_integration_service::WearableIntegrationService;
+pub use wearable_sync_scheduler::{WearableSyncConfig, WearableSyncScheduler};
+pub use health_import_service::HealthImportService;
+pub use coach_athlete_service::CoachAthleteService;
+pub use calendar_feed_service::CalendarFeedService;
+pub use web_push_client::WebPushClient;
+pub use web_push_service::WebPushService;
\ No newline at end of file
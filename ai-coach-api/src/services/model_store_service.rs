@@ -0,0 +1,263 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::models::ModelMetrics;
+use crate::services::ml_model_service::TrainedModel;
+
+/// Bumped whenever the serialized `TrainedModel` layout changes; older artifacts are not loaded
+pub const ARTIFACT_FORMAT_VERSION: i32 = 1;
+
+const ARTIFACT_INFO_COLUMNS: &str = r#"
+    id, user_id, model_version, model_type, format_version, feature_schema, feature_schema_version,
+    metrics, size_bytes, is_active, created_at, activated_at
+"#;
+
+/// Stored model metadata, without the artifact bytes
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ModelArtifactInfo {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub model_version: String,
+    pub model_type: String,
+    pub format_version: i32,
+    pub feature_schema: Vec<String>,
+    pub feature_schema_version: i32,
+    pub metrics: serde_json::Value,
+    pub size_bytes: i64,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub activated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow)]
+struct ArtifactRow {
+    format_version: i32,
+    artifact: Vec<u8>,
+}
+
+/// Postgres-backed store for trained model artifacts, keyed per user and version
+#[derive(Clone)]
+pub struct ModelStoreService {
+    db: PgPool,
+}
+
+impl ModelStoreService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Persist a trained model with its metrics. Saving does not make it the active model.
+    pub async fn save_model(
+        &self,
+        user_id: Uuid,
+        model: &TrainedModel,
+        metrics: &ModelMetrics,
+    ) -> Result<ModelArtifactInfo> {
+        let artifact = encode_artifact(model)?;
+
+        let info = sqlx::query_as::<_, ModelArtifactInfo>(&format!(
+            r#"
+            INSERT INTO ml_model_artifacts
+                (user_id, model_version, model_type, format_version, feature_schema, feature_schema_version,
+                 metrics, artifact, size_bytes)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (user_id, model_version) DO UPDATE
+            SET model_type = EXCLUDED.model_type,
+                format_version = EXCLUDED.format_version,
+                feature_schema = EXCLUDED.feature_schema,
+                feature_schema_version = EXCLUDED.feature_schema_version,
+                metrics = EXCLUDED.metrics,
+                artifact = EXCLUDED.artifact,
+                size_bytes = EXCLUDED.size_bytes
+            RETURNING {}
+            "#,
+            ARTIFACT_INFO_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(&model.model_version)
+        .bind(model.model_type.as_str())
+        .bind(ARTIFACT_FORMAT_VERSION)
+        .bind(&model.feature_names)
+        .bind(model.feature_schema_version as i32)
+        .bind(serde_json::to_value(metrics)?)
+        .bind(&artifact)
+        .bind(artifact.len() as i64)
+        .fetch_one(&self.db)
+        .await?;
+
+        info!(
+            "Stored model {} for user {} ({} bytes)",
+            info.model_version, user_id, info.size_bytes
+        );
+        Ok(info)
+    }
+
+    /// Load a model version, or the user's active model when `version` is `None`.
+    /// Fails if the artifact doesn't match the feature schema version it was saved with.
+    pub async fn load_model(&self, user_id: Uuid, version: Option<&str>) -> Result<Option<TrainedModel>> {
+        let row = match version {
+            Some(version) => {
+                sqlx::query_as::<_, ArtifactRow>(
+                    "SELECT format_version, artifact FROM ml_model_artifacts WHERE user_id = $1 AND model_version = $2",
+                )
+                .bind(user_id)
+                .bind(version)
+                .fetch_optional(&self.db)
+                .await?
+            }
+            None => {
+                sqlx::query_as::<_, ArtifactRow>(
+                    "SELECT format_version, artifact FROM ml_model_artifacts WHERE user_id = $1 AND is_active",
+                )
+                .bind(user_id)
+                .fetch_optional(&self.db)
+                .await?
+            }
+        };
+
+        row.map(|row| decode_artifact(row.format_version, &row.artifact))
+            .transpose()
+    }
+
+    /// Version of the user's active model
+    pub async fn active_version(&self, user_id: Uuid) -> Result<Option<String>> {
+        let version = sqlx::query_scalar::<_, String>(
+            "SELECT model_version FROM ml_model_artifacts WHERE user_id = $1 AND is_active",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(version)
+    }
+
+    /// Users whose active model was fitted on an older feature schema than `schema_version`
+    pub async fn users_with_outdated_models(&self, schema_version: u32) -> Result<Vec<Uuid>> {
+        let user_ids = sqlx::query_scalar::<_, Uuid>(
+            "SELECT user_id FROM ml_model_artifacts WHERE is_active AND feature_schema_version < $1",
+        )
+        .bind(schema_version as i32)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(user_ids)
+    }
+
+    /// Whether the user's active model was fitted on an older feature schema than `schema_version`
+    pub async fn has_outdated_model(&self, user_id: Uuid, schema_version: u32) -> Result<bool> {
+        let outdated = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM ml_model_artifacts
+                WHERE user_id = $1 AND is_active AND feature_schema_version < $2
+            )
+            "#,
+        )
+        .bind(user_id)
+        .bind(schema_version as i32)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(outdated)
+    }
+
+    /// Newest stored version of a model type for a user, optionally fitted on a given feature schema
+    pub async fn latest_version_of_type(
+        &self,
+        user_id: Uuid,
+        model_type: &str,
+        feature_schema_version: Option<u32>,
+    ) -> Result<Option<String>> {
+        let version = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT model_version FROM ml_model_artifacts
+            WHERE user_id = $1 AND model_type = $2 AND format_version = $3
+              AND ($4::INTEGER IS NULL OR feature_schema_version = $4)
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(model_type)
+        .bind(ARTIFACT_FORMAT_VERSION)
+        .bind(feature_schema_version.map(|version| version as i32))
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(version)
+    }
+
+    /// Make `version` the model used for the user's predictions. Returns false if it doesn't exist.
+    pub async fn activate_model(&self, user_id: Uuid, version: &str) -> Result<bool> {
+        let mut tx = self.db.begin().await?;
+
+        let exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM ml_model_artifacts WHERE user_id = $1 AND model_version = $2)",
+        )
+        .bind(user_id)
+        .bind(version)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if !exists {
+            return Ok(false);
+        }
+
+        sqlx::query("UPDATE ml_model_artifacts SET is_active = FALSE WHERE user_id = $1 AND is_active")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "UPDATE ml_model_artifacts SET is_active = TRUE, activated_at = NOW() WHERE user_id = $1 AND model_version = $2",
+        )
+        .bind(user_id)
+        .bind(version)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        info!("Activated model {} for user {}", version, user_id);
+        Ok(true)
+    }
+
+    /// All stored versions for a user, newest first
+    pub async fn list_models(&self, user_id: Uuid) -> Result<Vec<ModelArtifactInfo>> {
+        let models = sqlx::query_as::<_, ModelArtifactInfo>(&format!(
+            "SELECT {} FROM ml_model_artifacts WHERE user_id = $1 ORDER BY created_at DESC",
+            ARTIFACT_INFO_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(models)
+    }
+}
+
+/// Serialize a model together with its scaler and feature schema
+pub fn encode_artifact(model: &TrainedModel) -> Result<Vec<u8>> {
+    serde_json::to_vec(model).context("Failed to serialize model artifact")
+}
+
+/// Deserialize an artifact and check it can be fed `TrainingFeatures` in its schema version
+pub fn decode_artifact(format_version: i32, artifact: &[u8]) -> Result<TrainedModel> {
+    if format_version != ARTIFACT_FORMAT_VERSION {
+        return Err(anyhow!(
+            "Model artifact format {} is not supported (expected {}); retrain required",
+            format_version,
+            ARTIFACT_FORMAT_VERSION
+        ));
+    }
+
+    let model: TrainedModel =
+        serde_json::from_slice(artifact).context("Failed to deserialize model artifact")?;
+    model.check_feature_schema()?;
+
+    Ok(model)
+}
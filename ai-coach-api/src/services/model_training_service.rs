@@ -4,8 +4,11 @@ use sqlx::PgPool;
 use uuid::Uuid;
 use tracing::{info, warn, error};
 
-use crate::models::ModelMetrics;
-use crate::services::{FeatureEngineeringService, MLModelService, ModelPredictionService};
+use crate::models::{ModelMetrics, FEATURE_SCHEMA_VERSION};
+use crate::services::{
+    FeatureEngineeringService, MLModelService, ModelPredictionService, ModelStoreService, ModelVersioningService,
+};
+use crate::services::ml_model_service::ModelType;
 
 /// Configuration for model training
 #[derive(Debug, Clone)]
@@ -40,6 +43,8 @@ pub struct ModelTrainingService {
     db: PgPool,
     feature_service: FeatureEngineeringService,
     prediction_service: ModelPredictionService,
+    versioning_service: ModelVersioningService,
+    model_store: ModelStoreService,
 }
 
 impl ModelTrainingService {
@@ -47,11 +52,15 @@ impl ModelTrainingService {
     pub fn new(db: PgPool) -> Self {
         let feature_service = FeatureEngineeringService::new(db.clone());
         let prediction_service = ModelPredictionService::new(db.clone());
+        let versioning_service = ModelVersioningService::new(db.clone());
+        let model_store = ModelStoreService::new(db.clone());
 
         Self {
             db,
             feature_service,
             prediction_service,
+            versioning_service,
+            model_store,
         }
     }
 
@@ -98,6 +107,14 @@ impl ModelTrainingService {
                     warn!("Failed to store linear regression metrics: {}", e);
                 }
 
+                if let Err(e) = ml_service.save_current_model(user_id, &metrics).await {
+                    warn!("Failed to persist linear regression model: {}", e);
+                }
+
+                if let Err(e) = self.register_model_version(ModelType::LinearRegression, &metrics).await {
+                    warn!("Failed to register linear regression model version: {}", e);
+                }
+
                 model_metrics.push(metrics);
             }
             Err(e) => {
@@ -117,6 +134,14 @@ impl ModelTrainingService {
                         warn!("Failed to store Random Forest metrics: {}", e);
                     }
 
+                    if let Err(e) = ml_service.save_current_model(user_id, &metrics).await {
+                        warn!("Failed to persist Random Forest model: {}", e);
+                    }
+
+                    if let Err(e) = self.register_model_version(ModelType::RandomForest, &metrics).await {
+                        warn!("Failed to register Random Forest model version: {}", e);
+                    }
+
                     model_metrics.push(metrics);
                 }
                 Err(e) => {
@@ -140,6 +165,13 @@ impl ModelTrainingService {
         info!("Best model for user {}: {} (RMSE: {:.2})",
             user_id, best_model.model_version, best_model.rmse_tss);
 
+        // Serve predictions from the best model, including after restarts
+        match ml_service.activate_model(user_id, &best_model.model_version).await {
+            Ok(true) => {}
+            Ok(false) => warn!("Best model {} for user {} was not persisted", best_model.model_version, user_id),
+            Err(e) => warn!("Failed to activate model {} for user {}: {}", best_model.model_version, user_id, e),
+        }
+
         Ok(model_metrics)
     }
 
@@ -159,6 +191,31 @@ impl ModelTrainingService {
         Ok(results)
     }
 
+    /// Users whose active model was fitted on an older feature schema
+    pub async fn users_with_outdated_models(&self) -> Result<Vec<Uuid>> {
+        self.model_store.users_with_outdated_models(FEATURE_SCHEMA_VERSION).await
+    }
+
+    /// Retrain a user whose active model was fitted on an older feature schema.
+    /// Users without enough data keep their current model, which still predicts
+    /// from its own schema version. Returns whether a new model was trained.
+    pub async fn retrain_outdated_model(&self, user_id: Uuid) -> Result<bool> {
+        if !self.model_store.has_outdated_model(user_id, FEATURE_SCHEMA_VERSION).await? {
+            return Ok(false);
+        }
+
+        match self.train_user_models(user_id, None).await {
+            Ok(_) => {
+                info!("Retrained user {} on feature schema v{}", user_id, FEATURE_SCHEMA_VERSION);
+                Ok(true)
+            }
+            Err(e) => {
+                warn!("Keeping outdated model for user {}: {}", user_id, e);
+                Ok(false)
+            }
+        }
+    }
+
     /// Validate model performance using cross-validation
     pub async fn cross_validate_model(
         &self,
@@ -252,6 +309,25 @@ impl ModelTrainingService {
         })
     }
 
+    /// Register the trained model's configuration so it can be deployed or A/B tested
+    async fn register_model_version(&self, model_type: ModelType, metrics: &ModelMetrics) -> Result<()> {
+        self.versioning_service
+            .register_model_version(
+                model_type.as_str().to_string(),
+                FEATURE_SCHEMA_VERSION,
+                metrics.clone(),
+                format!(
+                    "{} on feature schema v{}, last fitted on {} samples",
+                    model_type.as_str(),
+                    FEATURE_SCHEMA_VERSION,
+                    metrics.sample_count
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
     /// Store model metrics in the database
     async fn store_model_metrics(&self, user_id: Uuid, metrics: &ModelMetrics) -> Result<()> {
         let prediction_data = serde_json::json!({
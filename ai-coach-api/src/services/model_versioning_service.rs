@@ -1,20 +1,21 @@
 use anyhow::{Result, anyhow};
-use chrono::{Utc, Duration};
-use sqlx::PgPool;
+use chrono::{Utc, Duration, NaiveDate};
+use sqlx::{FromRow, PgPool};
+use statrs::distribution::{ContinuousCDF, StudentsT};
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
-use tracing::{info, warn, error};
+use tracing::info;
 
-use crate::models::{ModelMetrics, TrainingFeatures, TrainingLoadPrediction};
-use crate::services::{MLModelService, ModelPredictionService};
+use crate::models::{ModelMetrics, TrainingLoadPrediction};
 
-/// Model version metadata
+/// Registered model configuration. Every user is fitted their own model of it, so the
+/// version identifies the configuration, not a trained model.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelVersion {
     pub id: Uuid,
     pub version: String,
     pub model_type: String,
+    pub feature_schema_version: Option<u32>,
     pub status: ModelStatus,
     pub metrics: ModelMetrics,
     pub created_at: chrono::DateTime<chrono::Utc>,
@@ -94,82 +95,279 @@ pub enum TestRecommendation {
     RequireMoreData,
 }
 
+impl ModelStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelStatus::Training => "training",
+            ModelStatus::Validation => "validation",
+            ModelStatus::Staging => "staging",
+            ModelStatus::Production => "production",
+            ModelStatus::Champion => "champion",
+            ModelStatus::Challenger => "challenger",
+            ModelStatus::Retired => "retired",
+        }
+    }
+
+    pub fn parse(status: &str) -> Result<Self> {
+        match status {
+            "training" => Ok(ModelStatus::Training),
+            "validation" => Ok(ModelStatus::Validation),
+            "staging" => Ok(ModelStatus::Staging),
+            "production" => Ok(ModelStatus::Production),
+            "champion" => Ok(ModelStatus::Champion),
+            "challenger" => Ok(ModelStatus::Challenger),
+            "retired" => Ok(ModelStatus::Retired),
+            other => Err(anyhow!("Unknown model status: {}", other)),
+        }
+    }
+}
+
+impl ABTestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ABTestStatus::Planning => "planning",
+            ABTestStatus::Running => "running",
+            ABTestStatus::Analyzing => "analyzing",
+            ABTestStatus::Completed => "completed",
+            ABTestStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(status: &str) -> Result<Self> {
+        match status {
+            "planning" => Ok(ABTestStatus::Planning),
+            "running" => Ok(ABTestStatus::Running),
+            "analyzing" => Ok(ABTestStatus::Analyzing),
+            "completed" => Ok(ABTestStatus::Completed),
+            "cancelled" => Ok(ABTestStatus::Cancelled),
+            other => Err(anyhow!("Unknown A/B test status: {}", other)),
+        }
+    }
+}
+
+/// Side of an A/B test a user is assigned to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExperimentArm {
+    Champion,
+    Challenger,
+}
+
+impl ExperimentArm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExperimentArm::Champion => "champion",
+            ExperimentArm::Challenger => "challenger",
+        }
+    }
+}
+
+/// A user's assignment within the running A/B test
+#[derive(Debug, Clone)]
+pub struct ModelAssignment {
+    pub test_id: Uuid,
+    pub arm: ExperimentArm,
+    pub version: String,
+    pub model_type: String,
+    pub feature_schema_version: Option<u32>,
+}
+
+/// A logged prediction paired with the TSS the athlete actually did that day
+#[derive(Debug, Clone, FromRow)]
+pub struct PredictionOutcome {
+    pub predicted_tss: f64,
+    pub actual_tss: f64,
+    pub confidence: f64,
+    pub latency_ms: f64,
+}
+
+impl PredictionOutcome {
+    pub fn squared_error(&self) -> f64 {
+        (self.predicted_tss - self.actual_tss).powi(2)
+    }
+
+    /// Off by more than 30% of the realised TSS (or 10 TSS on very easy days)
+    pub fn is_miss(&self) -> bool {
+        (self.predicted_tss - self.actual_tss).abs() > (self.actual_tss.abs() * 0.3).max(10.0)
+    }
+}
+
+const MODEL_VERSION_COLUMNS: &str =
+    "id, version, model_type, feature_schema_version, status, metrics, description, created_at, deployed_at, retired_at";
+
+const AB_TEST_COLUMNS: &str = r#"
+    id, name, champion_version, challenger_version, traffic_split, start_date, end_date, status,
+    target_metric, min_sample_size, significance_threshold
+"#;
+
+#[derive(FromRow)]
+struct ModelVersionRow {
+    id: Uuid,
+    version: String,
+    model_type: String,
+    feature_schema_version: Option<i32>,
+    status: String,
+    metrics: serde_json::Value,
+    description: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    deployed_at: Option<chrono::DateTime<chrono::Utc>>,
+    retired_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl TryFrom<ModelVersionRow> for ModelVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(row: ModelVersionRow) -> Result<Self> {
+        Ok(ModelVersion {
+            id: row.id,
+            version: row.version,
+            model_type: row.model_type,
+            feature_schema_version: row.feature_schema_version.map(|version| version as u32),
+            status: ModelStatus::parse(&row.status)?,
+            metrics: serde_json::from_value(row.metrics)?,
+            created_at: row.created_at,
+            deployed_at: row.deployed_at,
+            retired_at: row.retired_at,
+            description: row.description,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct ABTestRow {
+    id: Uuid,
+    name: String,
+    champion_version: String,
+    challenger_version: String,
+    traffic_split: f32,
+    start_date: chrono::DateTime<chrono::Utc>,
+    end_date: chrono::DateTime<chrono::Utc>,
+    status: String,
+    target_metric: String,
+    min_sample_size: i32,
+    significance_threshold: f32,
+}
+
+impl TryFrom<ABTestRow> for ABTestConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(row: ABTestRow) -> Result<Self> {
+        Ok(ABTestConfig {
+            test_id: row.id,
+            name: row.name,
+            champion_version: row.champion_version,
+            challenger_version: row.challenger_version,
+            traffic_split: row.traffic_split,
+            start_date: row.start_date,
+            end_date: row.end_date,
+            status: ABTestStatus::parse(&row.status)?,
+            target_metric: row.target_metric,
+            min_sample_size: row.min_sample_size.max(0) as usize,
+            significance_threshold: row.significance_threshold,
+        })
+    }
+}
+
 /// Model versioning and A/B testing service
 #[derive(Clone)]
 pub struct ModelVersioningService {
     db: PgPool,
-    prediction_service: ModelPredictionService,
-    // In production, this would be persisted storage
-    model_registry: std::sync::Arc<tokio::sync::RwLock<HashMap<String, ModelVersion>>>,
-    ab_tests: std::sync::Arc<tokio::sync::RwLock<HashMap<Uuid, ABTestConfig>>>,
 }
 
 impl ModelVersioningService {
     /// Create a new ModelVersioningService
     pub fn new(db: PgPool) -> Self {
-        let prediction_service = ModelPredictionService::new(db.clone());
-
-        Self {
-            db,
-            prediction_service,
-            model_registry: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
-            ab_tests: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
-        }
+        Self { db }
     }
 
-    /// Register a new model version
+    /// Register a model configuration, or refresh an existing one with the metrics of its latest fit
     pub async fn register_model_version(
         &self,
-        version: String,
         model_type: String,
+        feature_schema_version: u32,
         metrics: ModelMetrics,
         description: String,
     ) -> Result<ModelVersion> {
-        let model_version = ModelVersion {
-            id: Uuid::new_v4(),
-            version: version.clone(),
-            model_type,
-            status: ModelStatus::Validation,
-            metrics,
-            created_at: Utc::now(),
-            deployed_at: None,
-            retired_at: None,
-            description,
-        };
-
-        // Store in registry
-        let mut registry = self.model_registry.write().await;
-        registry.insert(version.clone(), model_version.clone());
-
-        // Store in database for persistence
-        self.store_model_version(&model_version).await?;
-
-        info!("Registered new model version: {}", version);
-        Ok(model_version)
+        let version = model_config_version(&model_type, feature_schema_version);
+
+        let row = sqlx::query_as::<_, ModelVersionRow>(&format!(
+            r#"
+            INSERT INTO ml_model_versions (version, model_type, feature_schema_version, status, metrics, description)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (version) DO UPDATE
+            SET metrics = EXCLUDED.metrics, description = EXCLUDED.description
+            RETURNING {}
+            "#,
+            MODEL_VERSION_COLUMNS
+        ))
+        .bind(&version)
+        .bind(&model_type)
+        .bind(feature_schema_version as i32)
+        .bind(ModelStatus::Validation.as_str())
+        .bind(serde_json::to_value(&metrics)?)
+        .bind(&description)
+        .fetch_one(&self.db)
+        .await?;
+
+        info!("Registered model configuration: {}", version);
+        row.try_into()
     }
 
     /// Deploy a model version to production
     pub async fn deploy_model_version(&self, version: &str) -> Result<()> {
-        let mut registry = self.model_registry.write().await;
-
-        if let Some(model) = registry.get_mut(version) {
-            // Retire current champion
-            for (_, existing_model) in registry.iter_mut() {
-                if existing_model.status == ModelStatus::Champion {
-                    existing_model.status = ModelStatus::Production;
-                }
-            }
-
-            // Promote this model to champion
-            model.status = ModelStatus::Champion;
-            model.deployed_at = Some(Utc::now());
+        self.deploy(version, None).await
+    }
 
-            self.store_model_version(model).await?;
-            info!("Deployed model version {} as champion", version);
-            Ok(())
-        } else {
-            Err(anyhow!("Model version {} not found", version))
+    /// Make `version` the champion, demoting the current one and recording the deployment
+    async fn deploy(&self, version: &str, test_id: Option<Uuid>) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        let model_id = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM ml_model_versions WHERE version = $1 FOR UPDATE",
+        )
+        .bind(version)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| anyhow!("Model version {} not found", version))?;
+
+        let previous_version = sqlx::query_scalar::<_, String>(
+            "SELECT version FROM ml_model_versions WHERE status = 'champion' FOR UPDATE",
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if previous_version.as_deref() == Some(version) {
+            return Ok(());
         }
+
+        sqlx::query("UPDATE ml_model_versions SET status = 'production' WHERE status = 'champion'")
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "UPDATE ml_model_versions SET status = 'champion', deployed_at = NOW(), retired_at = NULL WHERE id = $1",
+        )
+        .bind(model_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO ml_model_deployments (model_version_id, version, previous_version, experiment_id)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(model_id)
+        .bind(version)
+        .bind(&previous_version)
+        .bind(test_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        info!("Deployed model version {} as champion", version);
+        Ok(())
     }
 
     /// Create a new A/B test
@@ -182,107 +380,186 @@ impl ModelVersioningService {
         duration_days: i32,
         target_metric: String,
     ) -> Result<ABTestConfig> {
-        // Validate versions exist
-        let registry = self.model_registry.read().await;
-        if !registry.contains_key(&champion_version) {
-            return Err(anyhow!("Champion version {} not found", champion_version));
+        if champion_version == challenger_version {
+            return Err(anyhow!("Champion and challenger must be different versions"));
         }
-        if !registry.contains_key(&challenger_version) {
-            return Err(anyhow!("Challenger version {} not found", challenger_version));
-        }
-        drop(registry);
-
-        let test_config = ABTestConfig {
-            test_id: Uuid::new_v4(),
-            name,
-            champion_version: champion_version.clone(),
-            challenger_version: challenger_version.clone(),
-            traffic_split: traffic_split.clamp(0.0, 1.0),
-            start_date: Utc::now(),
-            end_date: Utc::now() + Duration::days(duration_days as i64),
-            status: ABTestStatus::Planning,
-            target_metric,
-            min_sample_size: 100,
-            significance_threshold: 0.05,
-        };
 
-        // Store test configuration
-        let mut tests = self.ab_tests.write().await;
-        tests.insert(test_config.test_id, test_config.clone());
-
-        // Update model statuses
-        let mut registry = self.model_registry.write().await;
-        if let Some(champion) = registry.get_mut(&champion_version) {
-            if champion.status != ModelStatus::Champion {
-                champion.status = ModelStatus::Champion;
+        // Validate versions exist
+        for version in [&champion_version, &challenger_version] {
+            if self.get_model_version(version).await?.is_none() {
+                return Err(anyhow!("Model version {} not found", version));
             }
         }
-        if let Some(challenger) = registry.get_mut(&challenger_version) {
-            challenger.status = ModelStatus::Challenger;
+
+        // The control arm becomes champion if nothing has been deployed yet
+        if self.get_champion_version().await?.is_none() {
+            self.deploy(&champion_version, None).await?;
         }
 
-        self.store_ab_test(&test_config).await?;
+        let row = sqlx::query_as::<_, ABTestRow>(&format!(
+            r#"
+            INSERT INTO ml_experiments
+                (name, champion_version, challenger_version, traffic_split, start_date, end_date, status,
+                 target_metric, min_sample_size, significance_threshold)
+            VALUES ($1, $2, $3, $4, NOW(), NOW() + make_interval(days => $5), 'planning', $6, $7, $8)
+            RETURNING {}
+            "#,
+            AB_TEST_COLUMNS
+        ))
+        .bind(&name)
+        .bind(&champion_version)
+        .bind(&challenger_version)
+        .bind(traffic_split.clamp(0.0, 1.0))
+        .bind(duration_days)
+        .bind(&target_metric)
+        .bind(100_i32)
+        .bind(0.05_f32)
+        .fetch_one(&self.db)
+        .await?;
+
+        sqlx::query("UPDATE ml_model_versions SET status = 'challenger' WHERE version = $1 AND status <> 'champion'")
+            .bind(&challenger_version)
+            .execute(&self.db)
+            .await?;
+
         info!("Created A/B test: {} vs {}", champion_version, challenger_version);
-        Ok(test_config)
+        row.try_into()
     }
 
-    /// Start an A/B test
+    /// Start an A/B test; its duration counts from now
     pub async fn start_ab_test(&self, test_id: Uuid) -> Result<()> {
-        let mut tests = self.ab_tests.write().await;
-        if let Some(test) = tests.get_mut(&test_id) {
-            test.status = ABTestStatus::Running;
-            self.store_ab_test(test).await?;
-            info!("Started A/B test: {}", test.name);
-            Ok(())
-        } else {
-            Err(anyhow!("A/B test {} not found", test_id))
+        let result = sqlx::query(
+            r#"
+            UPDATE ml_experiments
+            SET status = 'running', end_date = NOW() + (end_date - start_date), start_date = NOW()
+            WHERE id = $1 AND status = 'planning'
+            "#,
+        )
+        .bind(test_id)
+        .execute(&self.db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                anyhow!("Another A/B test is already running")
+            }
+            other => other.into(),
+        })?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("A/B test {} not found or already started", test_id));
         }
+
+        info!("Started A/B test: {}", test_id);
+        Ok(())
+    }
+
+    /// Assign a user to an arm of the running A/B test, if there is one
+    pub async fn assign_model(&self, user_id: Uuid) -> Result<Option<ModelAssignment>> {
+        let test = sqlx::query_as::<_, ABTestRow>(&format!(
+            "SELECT {} FROM ml_experiments WHERE status = 'running' AND end_date > NOW() LIMIT 1",
+            AB_TEST_COLUMNS
+        ))
+        .fetch_optional(&self.db)
+        .await?;
+
+        let Some(test) = test else {
+            return Ok(None);
+        };
+
+        // Use deterministic hash-based assignment for consistent user experience
+        let (arm, version) = if self.hash_user_id(user_id) < test.traffic_split {
+            (ExperimentArm::Challenger, test.challenger_version)
+        } else {
+            (ExperimentArm::Champion, test.champion_version)
+        };
+
+        let model = self.get_model_version(&version).await?
+            .ok_or_else(|| anyhow!("Model version {} not found", version))?;
+
+        Ok(Some(ModelAssignment {
+            test_id: test.id,
+            arm,
+            version,
+            model_type: model.model_type,
+            feature_schema_version: model.feature_schema_version,
+        }))
     }
 
     /// Determine which model version to use for a prediction (A/B testing logic)
-    pub async fn select_model_for_prediction(&self, user_id: Uuid) -> String {
-        // Check if there's an active A/B test
-        let tests = self.ab_tests.read().await;
-        for test in tests.values() {
-            if test.status == ABTestStatus::Running && Utc::now() < test.end_date {
-                // Use deterministic hash-based assignment for consistent user experience
-                let user_hash = self.hash_user_id(user_id);
-                if user_hash < test.traffic_split {
-                    return test.challenger_version.clone();
-                } else {
-                    return test.champion_version.clone();
-                }
-            }
+    pub async fn select_model_for_prediction(&self, user_id: Uuid) -> Result<String> {
+        if let Some(assignment) = self.assign_model(user_id).await? {
+            return Ok(assignment.version);
         }
 
         // No active test, use champion model
-        let registry = self.model_registry.read().await;
-        for model in registry.values() {
-            if model.status == ModelStatus::Champion {
-                return model.version.clone();
-            }
+        if let Some(champion) = self.get_champion_version().await? {
+            return Ok(champion);
         }
 
         // Fallback to latest production model
-        registry.values()
-            .filter(|m| m.status == ModelStatus::Production)
-            .max_by_key(|m| m.created_at)
-            .map(|m| m.version.clone())
-            .unwrap_or_else(|| "fallback_v1".to_string())
+        let latest = sqlx::query_scalar::<_, String>(
+            "SELECT version FROM ml_model_versions WHERE status = 'production' ORDER BY created_at DESC LIMIT 1",
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(latest.unwrap_or_else(|| "fallback_v1".to_string()))
+    }
+
+    /// Record a served prediction along with the stored model that served it. Experiment arms
+    /// are only credited when `assignment` is given.
+    pub async fn log_prediction(
+        &self,
+        user_id: Uuid,
+        assignment: Option<&ModelAssignment>,
+        prediction: &TrainingLoadPrediction,
+        latency_ms: f32,
+        prediction_date: NaiveDate,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO ml_prediction_logs
+                (user_id, experiment_id, arm, model_version, served_model_version, served_artifact_id,
+                 predicted_tss, confidence, latency_ms, prediction_date)
+            VALUES (
+                $1, $2, $3, $4, $5,
+                (SELECT id FROM ml_model_artifacts WHERE user_id = $1 AND model_version = $5),
+                $6, $7, $8, $9
+            )
+            "#,
+        )
+        .bind(user_id)
+        .bind(assignment.map(|a| a.test_id))
+        .bind(assignment.map(|a| a.arm.as_str()))
+        .bind(assignment.map_or(prediction.model_version.as_str(), |a| a.version.as_str()))
+        .bind(&prediction.model_version)
+        .bind(prediction.recommended_tss)
+        .bind(prediction.confidence)
+        .bind(latency_ms)
+        .bind(prediction_date)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
     }
 
     /// Analyze A/B test results
     pub async fn analyze_ab_test(&self, test_id: Uuid) -> Result<ABTestResults> {
-        let tests = self.ab_tests.read().await;
-        let test = tests.get(&test_id)
+        let test = self.get_ab_test(test_id).await?
             .ok_or_else(|| anyhow!("A/B test {} not found", test_id))?;
 
-        // Collect performance metrics for both models
-        let champion_metrics = self.collect_model_metrics(&test.champion_version, test.start_date, Utc::now()).await?;
-        let challenger_metrics = self.collect_model_metrics(&test.challenger_version, test.start_date, Utc::now()).await?;
+        // Collect performance metrics for both arms from their logged predictions
+        let champion_outcomes = self.collect_prediction_outcomes(test_id, ExperimentArm::Champion).await?;
+        let challenger_outcomes = self.collect_prediction_outcomes(test_id, ExperimentArm::Challenger).await?;
 
-        // Calculate statistical significance (simplified t-test)
-        let significance = self.calculate_statistical_significance(&champion_metrics, &challenger_metrics);
+        let champion_metrics = self.collect_model_metrics(&test.champion_version, &champion_outcomes);
+        let challenger_metrics = self.collect_model_metrics(&test.challenger_version, &challenger_outcomes);
+
+        // Welch's t-test on squared errors
+        let significance = self.calculate_statistical_significance(
+            &champion_outcomes.iter().map(PredictionOutcome::squared_error).collect::<Vec<_>>(),
+            &challenger_outcomes.iter().map(PredictionOutcome::squared_error).collect::<Vec<_>>(),
+        );
 
         // Determine winner based on target metric
         let winner = self.determine_winner(&champion_metrics, &challenger_metrics, &test.target_metric);
@@ -309,58 +586,104 @@ impl ModelVersioningService {
 
     /// Complete an A/B test and optionally promote the winner
     pub async fn complete_ab_test(&self, test_id: Uuid, promote_winner: bool) -> Result<ABTestResults> {
+        let test = self.get_ab_test(test_id).await?
+            .ok_or_else(|| anyhow!("A/B test {} not found", test_id))?;
+
+        if matches!(test.status, ABTestStatus::Completed | ABTestStatus::Cancelled) {
+            return Err(anyhow!("A/B test {} has already finished", test_id));
+        }
+
         let results = self.analyze_ab_test(test_id).await?;
 
         // Update test status
-        let mut tests = self.ab_tests.write().await;
-        if let Some(test) = tests.get_mut(&test_id) {
-            test.status = ABTestStatus::Completed;
-        }
+        sqlx::query(
+            r#"
+            UPDATE ml_experiments
+            SET status = 'completed', winner = $2, results = $3, completed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(test_id)
+        .bind(&results.winner)
+        .bind(serde_json::to_value(&results)?)
+        .execute(&self.db)
+        .await?;
 
         // Promote winner if requested and recommendation supports it
-        if promote_winner && matches!(results.recommendation, TestRecommendation::PromoteChallenger) {
-            if let Some(winner) = &results.winner {
-                self.deploy_model_version(winner).await?;
-                info!("Promoted A/B test winner: {}", winner);
-            }
-        }
+        let promote = promote_winner
+            && matches!(results.recommendation, TestRecommendation::PromoteChallenger)
+            && results.winner.as_deref() == Some(test.challenger_version.as_str());
 
-        // Update challenger status
-        let mut registry = self.model_registry.write().await;
-        for model in registry.values_mut() {
-            if model.status == ModelStatus::Challenger {
-                model.status = if promote_winner && results.winner.as_ref() == Some(&model.version) {
-                    ModelStatus::Champion
-                } else {
-                    ModelStatus::Production
-                };
-            }
+        if promote {
+            self.deploy(&test.challenger_version, Some(test_id)).await?;
+            info!("Promoted A/B test winner: {}", test.challenger_version);
+        } else {
+            sqlx::query("UPDATE ml_model_versions SET status = 'production' WHERE version = $1 AND status = 'challenger'")
+                .bind(&test.challenger_version)
+                .execute(&self.db)
+                .await?;
         }
 
         Ok(results)
     }
 
+    pub async fn get_model_version(&self, version: &str) -> Result<Option<ModelVersion>> {
+        let row = sqlx::query_as::<_, ModelVersionRow>(&format!(
+            "SELECT {} FROM ml_model_versions WHERE version = $1",
+            MODEL_VERSION_COLUMNS
+        ))
+        .bind(version)
+        .fetch_optional(&self.db)
+        .await?;
+
+        row.map(ModelVersion::try_from).transpose()
+    }
+
+    pub async fn get_ab_test(&self, test_id: Uuid) -> Result<Option<ABTestConfig>> {
+        let row = sqlx::query_as::<_, ABTestRow>(&format!(
+            "SELECT {} FROM ml_experiments WHERE id = $1",
+            AB_TEST_COLUMNS
+        ))
+        .bind(test_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        row.map(ABTestConfig::try_from).transpose()
+    }
+
     /// Get current champion model version
-    pub async fn get_champion_version(&self) -> Option<String> {
-        let registry = self.model_registry.read().await;
-        registry.values()
-            .find(|m| m.status == ModelStatus::Champion)
-            .map(|m| m.version.clone())
+    pub async fn get_champion_version(&self) -> Result<Option<String>> {
+        let champion = sqlx::query_scalar::<_, String>(
+            "SELECT version FROM ml_model_versions WHERE status = 'champion'",
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(champion)
     }
 
     /// List all model versions
-    pub async fn list_model_versions(&self) -> Vec<ModelVersion> {
-        let registry = self.model_registry.read().await;
-        registry.values().cloned().collect()
+    pub async fn list_model_versions(&self) -> Result<Vec<ModelVersion>> {
+        let rows = sqlx::query_as::<_, ModelVersionRow>(&format!(
+            "SELECT {} FROM ml_model_versions ORDER BY created_at DESC",
+            MODEL_VERSION_COLUMNS
+        ))
+        .fetch_all(&self.db)
+        .await?;
+
+        rows.into_iter().map(ModelVersion::try_from).collect()
     }
 
     /// List active A/B tests
-    pub async fn list_active_ab_tests(&self) -> Vec<ABTestConfig> {
-        let tests = self.ab_tests.read().await;
-        tests.values()
-            .filter(|t| matches!(t.status, ABTestStatus::Running | ABTestStatus::Planning))
-            .cloned()
-            .collect()
+    pub async fn list_active_ab_tests(&self) -> Result<Vec<ABTestConfig>> {
+        let rows = sqlx::query_as::<_, ABTestRow>(&format!(
+            "SELECT {} FROM ml_experiments WHERE status IN ('planning', 'running') ORDER BY created_at DESC",
+            AB_TEST_COLUMNS
+        ))
+        .fetch_all(&self.db)
+        .await?;
+
+        rows.into_iter().map(ABTestConfig::try_from).collect()
     }
 
     /// Hash user ID for consistent A/B test assignment
@@ -376,44 +699,85 @@ impl ModelVersioningService {
         (hash % 10000) as f32 / 10000.0
     }
 
-    /// Collect performance metrics for a model version
-    async fn collect_model_metrics(
-        &self,
-        version: &str,
-        start_date: chrono::DateTime<chrono::Utc>,
-        end_date: chrono::DateTime<chrono::Utc>,
-    ) -> Result<TestMetrics> {
-        // This would query actual prediction logs from the database
-        // For now, returning mock data based on model version
-        let base_rmse = if version.contains("linear") { 45.0 } else { 40.0 };
-        let base_confidence = if version.contains("forest") { 0.8 } else { 0.75 };
-
-        Ok(TestMetrics {
-            version: version.to_string(),
-            sample_size: 250, // Would be actual count from logs
-            avg_rmse: base_rmse + (rand::random::<f32>() - 0.5) * 10.0,
-            avg_confidence: base_confidence + (rand::random::<f32>() - 0.5) * 0.1,
-            user_satisfaction: Some(7.5 + (rand::random::<f32>() - 0.5) * 2.0),
-            prediction_latency_ms: 50.0 + rand::random::<f32>() * 20.0,
-            error_rate: rand::random::<f32>() * 0.05,
-        })
+    /// Logged predictions for one arm of a test, paired with the TSS of the sessions done that day.
+    /// Days without a session are skipped, as is all but the latest prediction per user and day.
+    async fn collect_prediction_outcomes(&self, test_id: Uuid, arm: ExperimentArm) -> Result<Vec<PredictionOutcome>> {
+        let outcomes = sqlx::query_as::<_, PredictionOutcome>(
+            r#"
+            SELECT l.predicted_tss::float8 AS predicted_tss, s.actual_tss,
+                   l.confidence::float8 AS confidence, l.latency_ms::float8 AS latency_ms
+            FROM (
+                SELECT DISTINCT ON (user_id, prediction_date) user_id, prediction_date,
+                       predicted_tss, confidence, latency_ms
+                FROM ml_prediction_logs
+                WHERE experiment_id = $1 AND arm = $2
+                ORDER BY user_id, prediction_date, predicted_at DESC
+            ) l
+            JOIN LATERAL (
+                SELECT SUM((ts.trainrs_data->>'tss')::float8) AS actual_tss
+                FROM training_sessions ts
+                WHERE ts.user_id = l.user_id AND ts.date = l.prediction_date AND ts.trainrs_data ? 'tss'
+            ) s ON s.actual_tss IS NOT NULL
+            "#,
+        )
+        .bind(test_id)
+        .bind(arm.as_str())
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(outcomes)
     }
 
-    /// Calculate statistical significance between two sets of metrics
-    fn calculate_statistical_significance(&self, champion: &TestMetrics, challenger: &TestMetrics) -> f32 {
-        // Simplified t-test calculation
-        // In production, this would use proper statistical libraries
-        let rmse_diff = (champion.avg_rmse - challenger.avg_rmse).abs();
-        let pooled_variance = ((champion.sample_size + challenger.sample_size) as f32) / 10.0;
+    /// Performance metrics for a model version from its prediction outcomes
+    fn collect_model_metrics(&self, version: &str, outcomes: &[PredictionOutcome]) -> TestMetrics {
+        let n = outcomes.len();
+        let mean = |f: &dyn Fn(&PredictionOutcome) -> f64| {
+            if n == 0 { 0.0 } else { outcomes.iter().map(f).sum::<f64>() / n as f64 }
+        };
 
-        if pooled_variance == 0.0 {
+        TestMetrics {
+            version: version.to_string(),
+            sample_size: n,
+            avg_rmse: mean(&PredictionOutcome::squared_error).sqrt() as f32,
+            avg_confidence: mean(&|o| o.confidence) as f32,
+            user_satisfaction: None, // No feedback is collected per prediction yet
+            prediction_latency_ms: mean(&|o| o.latency_ms) as f32,
+            error_rate: mean(&|o| if o.is_miss() { 1.0 } else { 0.0 }) as f32,
+        }
+    }
+
+    /// Two-sided p-value of Welch's t-test between the two arms' per-prediction errors
+    fn calculate_statistical_significance(&self, champion: &[f64], challenger: &[f64]) -> f32 {
+        if champion.len() < 2 || challenger.len() < 2 {
             return 1.0; // No significance
         }
 
-        let t_stat = rmse_diff / pooled_variance.sqrt();
+        let mean_var = |xs: &[f64]| {
+            let n = xs.len() as f64;
+            let mean = xs.iter().sum::<f64>() / n;
+            let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            (mean, var / n)
+        };
+
+        let (champion_mean, champion_se2) = mean_var(champion);
+        let (challenger_mean, challenger_se2) = mean_var(challenger);
+        let se2 = champion_se2 + challenger_se2;
+
+        if se2 == 0.0 {
+            return if champion_mean == challenger_mean { 1.0 } else { 0.0 };
+        }
+
+        let t_stat = (champion_mean - challenger_mean) / se2.sqrt();
+
+        // Welch–Satterthwaite degrees of freedom
+        let df = se2.powi(2)
+            / (champion_se2.powi(2) / (champion.len() as f64 - 1.0)
+                + challenger_se2.powi(2) / (challenger.len() as f64 - 1.0));
 
-        // Convert t-statistic to p-value (very simplified)
-        (1.0 - (t_stat / 5.0).min(1.0)).max(0.0)
+        match StudentsT::new(0.0, 1.0, df) {
+            Ok(dist) => (2.0 * (1.0 - dist.cdf(t_stat.abs()))).clamp(0.0, 1.0) as f32,
+            Err(_) => 1.0,
+        }
     }
 
     /// Determine winner based on target metric
@@ -463,6 +827,10 @@ impl ModelVersioningService {
             return TestRecommendation::ExtendTest;
         }
 
+        if champion.avg_rmse <= 0.0 {
+            return TestRecommendation::KeepChampion;
+        }
+
         // Check if challenger is meaningfully better
         let rmse_improvement = (champion.avg_rmse - challenger.avg_rmse) / champion.avg_rmse;
         if rmse_improvement > 0.05 { // 5% improvement
@@ -471,36 +839,109 @@ impl ModelVersioningService {
             TestRecommendation::KeepChampion
         }
     }
+}
 
-    /// Store model version in database
-    async fn store_model_version(&self, model: &ModelVersion) -> Result<()> {
-        let model_data = serde_json::to_value(model)?;
+/// Registry version of a model configuration, e.g. `random_forest@fs2`
+pub fn model_config_version(model_type: &str, feature_schema_version: u32) -> String {
+    format!("{}@fs{}", model_type, feature_schema_version)
+}
 
-        let prediction = crate::models::CreateModelPrediction {
-            user_id: Uuid::new_v4(), // System user ID
-            prediction_type: "ModelVersion".to_string(),
-            data: model_data,
-            confidence: Some(model.metrics.r_squared),
-            model_version: Some(model.version.clone()),
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        self.prediction_service.create_prediction(prediction).await?;
-        Ok(())
+    fn outcome(predicted_tss: f64, actual_tss: f64) -> PredictionOutcome {
+        PredictionOutcome {
+            predicted_tss,
+            actual_tss,
+            confidence: 0.8,
+            latency_ms: 12.0,
+        }
+    }
+
+    fn service() -> ModelVersioningService {
+        ModelVersioningService::new(PgPool::connect_lazy("postgres://localhost/unused").unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_collect_model_metrics_from_outcomes() {
+        let service = service();
+        let outcomes = vec![outcome(100.0, 110.0), outcome(150.0, 140.0), outcome(80.0, 40.0)];
+
+        let metrics = service.collect_model_metrics("linear_v1", &outcomes);
+        assert_eq!(metrics.sample_size, 3);
+        assert!((metrics.avg_rmse - (1800.0f32 / 3.0).sqrt()).abs() < 0.01);
+        assert!((metrics.avg_confidence - 0.8).abs() < 1e-6);
+        assert!((metrics.prediction_latency_ms - 12.0).abs() < 1e-6);
+        // Only the 80 vs 40 prediction misses by more than 30%
+        assert!((metrics.error_rate - 1.0 / 3.0).abs() < 1e-6);
+
+        let empty = service.collect_model_metrics("linear_v1", &[]);
+        assert_eq!(empty.sample_size, 0);
+        assert_eq!(empty.avg_rmse, 0.0);
     }
 
-    /// Store A/B test configuration in database
-    async fn store_ab_test(&self, test: &ABTestConfig) -> Result<()> {
-        let test_data = serde_json::to_value(test)?;
+    #[tokio::test]
+    async fn test_statistical_significance() {
+        let service = service();
 
-        let prediction = crate::models::CreateModelPrediction {
-            user_id: Uuid::new_v4(), // System user ID
-            prediction_type: "ABTest".to_string(),
-            data: test_data,
-            confidence: Some(test.traffic_split),
-            model_version: Some(format!("{}_{}", test.champion_version, test.challenger_version)),
+        let champion: Vec<f64> = (0..200).map(|i| 400.0 + (i % 10) as f64 * 20.0).collect();
+        let better: Vec<f64> = (0..200).map(|i| 100.0 + (i % 10) as f64 * 20.0).collect();
+        let same = champion.clone();
+
+        assert!(service.calculate_statistical_significance(&champion, &better) < 0.01);
+        assert!(service.calculate_statistical_significance(&champion, &same) > 0.99);
+        assert_eq!(service.calculate_statistical_significance(&champion, &better[..1]), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_recommendation_requires_data_and_significance() {
+        let service = service();
+        let metrics = |version: &str, sample_size, avg_rmse| TestMetrics {
+            version: version.to_string(),
+            sample_size,
+            avg_rmse,
+            avg_confidence: 0.8,
+            user_satisfaction: None,
+            prediction_latency_ms: 10.0,
+            error_rate: 0.1,
         };
 
-        self.prediction_service.create_prediction(prediction).await?;
-        Ok(())
+        let champion = metrics("linear_v1", 150, 40.0);
+        let challenger = metrics("forest_v1", 150, 30.0);
+
+        assert!(matches!(
+            service.generate_test_recommendation(&champion, &challenger, 0.01, 0.05, 100),
+            TestRecommendation::PromoteChallenger
+        ));
+        assert!(matches!(
+            service.generate_test_recommendation(&champion, &challenger, 0.2, 0.05, 100),
+            TestRecommendation::ExtendTest
+        ));
+        assert!(matches!(
+            service.generate_test_recommendation(&champion, &metrics("forest_v1", 50, 30.0), 0.01, 0.05, 100),
+            TestRecommendation::RequireMoreData
+        ));
+        assert_eq!(
+            service.determine_winner(&champion, &challenger, "rmse"),
+            Some("forest_v1".to_string())
+        );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_status_round_trip() {
+        for status in [ModelStatus::Training, ModelStatus::Champion, ModelStatus::Retired] {
+            assert_eq!(ModelStatus::parse(status.as_str()).unwrap(), status);
+        }
+        for status in [ABTestStatus::Planning, ABTestStatus::Running, ABTestStatus::Completed] {
+            assert_eq!(ABTestStatus::parse(status.as_str()).unwrap(), status);
+        }
+        assert!(ModelStatus::parse("unknown").is_err());
+    }
+
+    #[test]
+    fn test_model_config_version() {
+        assert_eq!(model_config_version("linear_regression", 2), "linear_regression@fs2");
+        assert_ne!(model_config_version("random_forest", 1), model_config_version("random_forest", 2));
+    }
+}
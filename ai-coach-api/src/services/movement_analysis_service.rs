@@ -0,0 +1,1204 @@
+/// Movement Analysis Service
+///
+/// Turns processed pose frames into exercise-specific movement scores:
+/// - Rep segmentation from joint-angle time series
+/// - Depth, tempo and range of motion measurement
+/// - Fault detection (knee valgus, butt wink, hip sag, asymmetric lockout, ...)
+/// - Issues and recommendations that point back to the offending frames
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::models::keypoint::PoseFrame;
+use crate::models::vision_analysis::{
+    ExerciseType, IssueSeverity, MovementIssue, MovementRecommendation, RecommendationPriority,
+};
+
+/// Body line (shoulder-hip-ankle) angle below which the trunk is no longer straight
+const BODY_LINE_THRESHOLD: f64 = 160.0;
+/// Left/right difference at lockout treated as asymmetric
+const ASYMMETRY_THRESHOLD: f64 = 15.0;
+/// Medial knee drift relative to leg length treated as valgus
+const VALGUS_THRESHOLD: f64 = 0.08;
+/// Hip flexion at the bottom, with the knees already settled, treated as butt wink
+const BUTT_WINK_THRESHOLD: f64 = 15.0;
+/// Coefficient of variation of rep durations treated as inconsistent tempo
+const TEMPO_CV_THRESHOLD: f64 = 0.25;
+
+/// Joint whose angle drives rep segmentation
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Joint {
+    Hip,
+    Knee,
+    Elbow,
+}
+
+impl Joint {
+    fn names(self) -> (&'static str, &'static str) {
+        match self {
+            Joint::Hip => ("left_hip", "right_hip"),
+            Joint::Knee => ("left_knee", "right_knee"),
+            Joint::Elbow => ("left_elbow", "right_elbow"),
+        }
+    }
+}
+
+/// How left and right angles are combined into one signal
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combine {
+    /// Average both sides (bilateral movements)
+    Mean,
+    /// Most flexed side (the working leg of a lunge)
+    Min,
+}
+
+/// Which way the joint moves away from the start position
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    /// Angle closes during the rep (squat, push-up, pull-up)
+    Flexion,
+    /// Angle opens during the rep (overhead press from the rack)
+    Extension,
+}
+
+impl Direction {
+    /// Distance travelled away from the start position, larger is further into the rep
+    fn progress(self, angle: f64) -> f64 {
+        match self {
+            Direction::Flexion => -angle,
+            Direction::Extension => angle,
+        }
+    }
+}
+
+/// Movement faults the rule engine can detect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Fault {
+    KneeValgus,
+    ButtWink,
+    HipSag,
+    HipPike,
+    AsymmetricLockout,
+    InsufficientDepth,
+    IncompleteLockout,
+    ForwardLean,
+    InconsistentTempo,
+}
+
+impl Fault {
+    fn issue_type(self) -> &'static str {
+        match self {
+            Fault::KneeValgus => "knee_valgus",
+            Fault::ButtWink => "butt_wink",
+            Fault::HipSag => "hip_sag",
+            Fault::HipPike => "hip_pike",
+            Fault::AsymmetricLockout => "asymmetric_lockout",
+            Fault::InsufficientDepth => "insufficient_depth",
+            Fault::IncompleteLockout => "incomplete_lockout",
+            Fault::ForwardLean => "forward_lean",
+            Fault::InconsistentTempo => "inconsistent_tempo",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Fault::KneeValgus => "Knees cave inward",
+            Fault::ButtWink => "Pelvis tucks under at the bottom",
+            Fault::HipSag => "Hips sag below the shoulder-ankle line",
+            Fault::HipPike => "Hips pike above the shoulder-ankle line",
+            Fault::AsymmetricLockout => "Left and right sides lock out unevenly",
+            Fault::InsufficientDepth => "Range of motion stops short of full depth",
+            Fault::IncompleteLockout => "Rep finishes short of full lockout",
+            Fault::ForwardLean => "Torso leans excessively forward",
+            Fault::InconsistentTempo => "Rep tempo varies noticeably",
+        }
+    }
+
+    /// Severity, escalated when the fault shows up in at least half the reps
+    fn severity(self, share: f64) -> IssueSeverity {
+        let frequent = share >= 0.5;
+        match self {
+            Fault::KneeValgus | Fault::HipSag if frequent => IssueSeverity::Critical,
+            Fault::KneeValgus | Fault::HipSag | Fault::ButtWink | Fault::AsymmetricLockout => {
+                IssueSeverity::Warning
+            }
+            Fault::InsufficientDepth | Fault::IncompleteLockout if frequent => IssueSeverity::Warning,
+            _ => IssueSeverity::Minor,
+        }
+    }
+
+    /// Contribution to the injury risk score when present in every rep
+    fn risk_weight(self) -> f64 {
+        match self {
+            Fault::KneeValgus => 60.0,
+            Fault::ButtWink | Fault::HipSag => 40.0,
+            Fault::AsymmetricLockout => 30.0,
+            Fault::ForwardLean => 20.0,
+            Fault::HipPike => 10.0,
+            Fault::IncompleteLockout => 5.0,
+            Fault::InsufficientDepth | Fault::InconsistentTempo => 0.0,
+        }
+    }
+
+    fn recommendation(self) -> (&'static str, &'static [&'static str], &'static str) {
+        match self {
+            Fault::KneeValgus => (
+                "Keep the knees tracking over the toes through the whole rep",
+                &["Banded squats", "Clamshells", "Lateral band walks"],
+                "Push your knees out",
+            ),
+            Fault::ButtWink => (
+                "Stop just above the depth where the pelvis tucks and build hip and ankle mobility",
+                &["Goblet squat holds", "Hip flexor stretch", "Ankle dorsiflexion drills"],
+                "Keep a neutral spine at the bottom",
+            ),
+            Fault::HipSag => (
+                "Brace the trunk so shoulders, hips and ankles stay in one line",
+                &["Dead bugs", "Hollow body holds", "RKC plank"],
+                "Squeeze your glutes and brace your abs",
+            ),
+            Fault::HipPike => (
+                "Lower the hips until the body forms a straight line",
+                &["Bird dogs", "Forearm plank against a mirror"],
+                "Hips down, long body",
+            ),
+            Fault::AsymmetricLockout => (
+                "Reduce the load and finish each rep evenly on both sides",
+                &["Unilateral accessory work", "Paused reps"],
+                "Finish both sides together",
+            ),
+            Fault::InsufficientDepth => (
+                "Work through the full range of motion, lowering the load if needed",
+                &["Paused reps", "Tempo reps", "Mobility work for the limiting joint"],
+                "Own the bottom position",
+            ),
+            Fault::IncompleteLockout => (
+                "Finish every rep at full extension before starting the next",
+                &["Lockout holds", "Paused reps at the top"],
+                "Finish tall and strong",
+            ),
+            Fault::ForwardLean => (
+                "Keep the torso more upright through the rep",
+                &["Front squats", "Goblet squats", "Thoracic extensions"],
+                "Chest proud",
+            ),
+            Fault::InconsistentTempo => (
+                "Use the same controlled tempo for every rep",
+                &["Tempo reps with a metronome"],
+                "Same speed every rep",
+            ),
+        }
+    }
+}
+
+/// Rep segmentation and scoring rules for a dynamic exercise
+#[derive(Debug, Clone)]
+struct RepRules {
+    joint: Joint,
+    combine: Combine,
+    direction: Direction,
+    /// Angle that marks the start (and end) position of a rep
+    start_threshold: f64,
+    /// Angle that must be passed for the movement to count as a rep
+    target_threshold: f64,
+    /// Angle the peak of each rep should reach
+    depth_angle: Option<f64>,
+    /// Fault reported when the peak misses `depth_angle`
+    depth_fault: Fault,
+    /// Angle the start position should reach between reps (flexion movements only)
+    lockout_angle: Option<f64>,
+    /// Expected range of motion in degrees for a full rep
+    full_range: f64,
+    /// Whether the lowering (eccentric) phase comes first
+    eccentric_first: bool,
+    faults: &'static [Fault],
+}
+
+/// Exercise-specific analysis rules
+#[derive(Debug, Clone)]
+enum ExerciseRules {
+    Reps(RepRules),
+    Hold,
+}
+
+impl ExerciseRules {
+    fn for_exercise(exercise: &ExerciseType) -> Option<Self> {
+        let rules = match exercise {
+            ExerciseType::Squat => RepRules {
+                joint: Joint::Knee,
+                combine: Combine::Mean,
+                direction: Direction::Flexion,
+                start_threshold: 160.0,
+                target_threshold: 130.0,
+                depth_angle: Some(100.0),
+                depth_fault: Fault::InsufficientDepth,
+                lockout_angle: Some(165.0),
+                full_range: 90.0,
+                eccentric_first: true,
+                faults: &[Fault::KneeValgus, Fault::ButtWink, Fault::ForwardLean],
+            },
+            ExerciseType::Deadlift => RepRules {
+                joint: Joint::Hip,
+                combine: Combine::Mean,
+                direction: Direction::Flexion,
+                start_threshold: 155.0,
+                target_threshold: 130.0,
+                depth_angle: None,
+                depth_fault: Fault::InsufficientDepth,
+                lockout_angle: Some(170.0),
+                full_range: 80.0,
+                eccentric_first: true,
+                faults: &[Fault::AsymmetricLockout],
+            },
+            ExerciseType::PushUp => RepRules {
+                joint: Joint::Elbow,
+                combine: Combine::Mean,
+                direction: Direction::Flexion,
+                start_threshold: 150.0,
+                target_threshold: 120.0,
+                depth_angle: Some(100.0),
+                depth_fault: Fault::InsufficientDepth,
+                lockout_angle: Some(160.0),
+                full_range: 80.0,
+                eccentric_first: true,
+                faults: &[Fault::HipSag],
+            },
+            ExerciseType::Lunge => RepRules {
+                joint: Joint::Knee,
+                combine: Combine::Min,
+                direction: Direction::Flexion,
+                start_threshold: 155.0,
+                target_threshold: 125.0,
+                depth_angle: Some(100.0),
+                depth_fault: Fault::InsufficientDepth,
+                lockout_angle: None,
+                full_range: 80.0,
+                eccentric_first: true,
+                faults: &[Fault::KneeValgus, Fault::ForwardLean],
+            },
+            ExerciseType::BenchPress => RepRules {
+                joint: Joint::Elbow,
+                combine: Combine::Mean,
+                direction: Direction::Flexion,
+                start_threshold: 150.0,
+                target_threshold: 120.0,
+                depth_angle: Some(95.0),
+                depth_fault: Fault::InsufficientDepth,
+                lockout_angle: Some(160.0),
+                full_range: 80.0,
+                eccentric_first: true,
+                faults: &[Fault::AsymmetricLockout],
+            },
+            ExerciseType::OverheadPress => RepRules {
+                joint: Joint::Elbow,
+                combine: Combine::Mean,
+                direction: Direction::Extension,
+                start_threshold: 110.0,
+                target_threshold: 145.0,
+                depth_angle: Some(165.0),
+                depth_fault: Fault::IncompleteLockout,
+                lockout_angle: None,
+                full_range: 90.0,
+                eccentric_first: false,
+                faults: &[Fault::AsymmetricLockout],
+            },
+            ExerciseType::PullUp => RepRules {
+                joint: Joint::Elbow,
+                combine: Combine::Mean,
+                direction: Direction::Flexion,
+                start_threshold: 145.0,
+                target_threshold: 115.0,
+                depth_angle: Some(90.0),
+                depth_fault: Fault::InsufficientDepth,
+                lockout_angle: Some(150.0),
+                full_range: 90.0,
+                eccentric_first: false,
+                faults: &[Fault::AsymmetricLockout],
+            },
+            ExerciseType::Plank => return Some(ExerciseRules::Hold),
+            ExerciseType::Running | ExerciseType::Other(_) => return None,
+        };
+
+        Some(ExerciseRules::Reps(rules))
+    }
+}
+
+/// A single segmented repetition
+#[derive(Debug, Clone, Serialize)]
+pub struct RepSegment {
+    pub rep_number: u32,
+    pub start_frame: u32,
+    pub start_ms: u64,
+    /// Deepest point of the rep (bottom of a squat, top of a pull-up)
+    pub peak_frame: u32,
+    pub peak_ms: u64,
+    pub end_frame: u32,
+    pub end_ms: u64,
+    /// Primary joint angle at the peak in degrees
+    pub peak_angle: f64,
+    /// Range of motion of the primary joint in degrees
+    pub range_of_motion: f64,
+    pub eccentric_ms: u64,
+    pub concentric_ms: u64,
+}
+
+impl RepSegment {
+    pub fn duration_ms(&self) -> u64 {
+        self.end_ms.saturating_sub(self.start_ms)
+    }
+}
+
+/// Result of analyzing an exercise set
+#[derive(Debug, Clone, Serialize)]
+pub struct MovementAnalysis {
+    pub exercise_type: ExerciseType,
+    /// Completed reps, `None` for isometric holds
+    pub rep_count: Option<i32>,
+    pub reps: Vec<RepSegment>,
+    /// Time spent in a correct position, isometric holds only
+    pub hold_duration_ms: Option<u64>,
+    pub overall_score: f64,
+    pub form_quality: f64,
+    pub injury_risk: f64,
+    pub range_of_motion: Option<f64>,
+    pub tempo_consistency: Option<f64>,
+    pub issues: Vec<MovementIssue>,
+    pub recommendations: Vec<MovementRecommendation>,
+}
+
+impl MovementAnalysis {
+    /// Per-rep metrics stored alongside the movement score
+    pub fn biomechanics_data(&self) -> serde_json::Value {
+        serde_json::json!({
+            "exercise_type": self.exercise_type.to_string(),
+            "reps": self.reps,
+            "hold_duration_ms": self.hold_duration_ms,
+        })
+    }
+}
+
+/// Primary joint angle for one frame
+#[derive(Debug, Clone)]
+struct Sample {
+    frame_index: usize,
+    frame_number: u32,
+    timestamp_ms: u64,
+    angle: f64,
+    confidence: f64,
+}
+
+/// Frame where a fault was observed
+#[derive(Debug, Clone)]
+struct Hit {
+    fault: Fault,
+    rep: Option<u32>,
+    frame_number: u32,
+    timestamp_ms: u64,
+    confidence: f64,
+}
+
+impl Hit {
+    fn new(fault: Fault, rep: Option<u32>, frame: &PoseFrame, confidence: f64) -> Self {
+        Self {
+            fault,
+            rep,
+            frame_number: frame.frame_number,
+            timestamp_ms: frame.timestamp_ms,
+            confidence,
+        }
+    }
+}
+
+/// Exercise rule engine producing movement scores from pose frames
+pub struct MovementAnalysisService;
+
+impl MovementAnalysisService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Whether rep counting and form scoring is available for an exercise
+    pub fn supports(exercise: &ExerciseType) -> bool {
+        ExerciseRules::for_exercise(exercise).is_some()
+    }
+
+    /// Analyze processed pose frames (with joint angles) for the given exercise
+    pub fn analyze(&self, exercise: &ExerciseType, frames: &[PoseFrame]) -> Result<MovementAnalysis> {
+        let Some(rules) = ExerciseRules::for_exercise(exercise) else {
+            anyhow::bail!("Movement analysis is not supported for {}", exercise);
+        };
+
+        match rules {
+            ExerciseRules::Reps(rules) => self.analyze_reps(exercise, &rules, frames),
+            ExerciseRules::Hold => self.analyze_hold(exercise, frames),
+        }
+    }
+
+    fn analyze_reps(
+        &self,
+        exercise: &ExerciseType,
+        rules: &RepRules,
+        frames: &[PoseFrame],
+    ) -> Result<MovementAnalysis> {
+        let samples: Vec<Sample> = frames
+            .iter()
+            .enumerate()
+            .filter_map(|(index, frame)| {
+                let (angle, confidence) = combined_angle(frame, rules.joint, rules.combine)?;
+                Some(Sample {
+                    frame_index: index,
+                    frame_number: frame.frame_number,
+                    timestamp_ms: frame.timestamp_ms,
+                    angle,
+                    confidence,
+                })
+            })
+            .collect();
+
+        if samples.is_empty() {
+            anyhow::bail!("No frames with a visible {:?} joint to analyze", rules.joint);
+        }
+
+        let segments = segment_reps(&samples, rules);
+        if segments.is_empty() {
+            return Ok(no_reps_analysis(exercise));
+        }
+
+        let mut reps = Vec::with_capacity(segments.len());
+        let mut hits = Vec::new();
+
+        for (i, &(start, peak, end)) in segments.iter().enumerate() {
+            let rep_number = i as u32 + 1;
+            let window = &samples[start..=end];
+            let peak_sample = &samples[peak];
+            let progress = |s: &Sample| rules.direction.progress(s.angle);
+
+            let range_of_motion = window
+                .iter()
+                .map(|s| (s.angle - peak_sample.angle).abs())
+                .fold(0.0, f64::max);
+
+            let first_phase_ms = peak_sample.timestamp_ms - samples[start].timestamp_ms;
+            let second_phase_ms = samples[end].timestamp_ms - peak_sample.timestamp_ms;
+            let (eccentric_ms, concentric_ms) = if rules.eccentric_first {
+                (first_phase_ms, second_phase_ms)
+            } else {
+                (second_phase_ms, first_phase_ms)
+            };
+
+            reps.push(RepSegment {
+                rep_number,
+                start_frame: samples[start].frame_number,
+                start_ms: samples[start].timestamp_ms,
+                peak_frame: peak_sample.frame_number,
+                peak_ms: peak_sample.timestamp_ms,
+                end_frame: samples[end].frame_number,
+                end_ms: samples[end].timestamp_ms,
+                peak_angle: peak_sample.angle,
+                range_of_motion,
+                eccentric_ms,
+                concentric_ms,
+            });
+
+            let peak_frame = &frames[peak_sample.frame_index];
+
+            if let Some(depth) = rules.depth_angle {
+                if progress(peak_sample) < rules.direction.progress(depth) {
+                    hits.push(Hit::new(rules.depth_fault, Some(rep_number), peak_frame, peak_sample.confidence));
+                }
+            }
+
+            // The lockout of a flexion movement is the most extended point before the next rep
+            let lockout = match rules.direction {
+                Direction::Extension => peak,
+                Direction::Flexion => {
+                    let next_peak = segments.get(i + 1).map_or(samples.len() - 1, |next| next.1);
+                    (peak..=next_peak)
+                        .max_by(|&a, &b| samples[a].angle.total_cmp(&samples[b].angle))
+                        .unwrap_or(end)
+                }
+            };
+            let lockout_sample = &samples[lockout];
+            let lockout_frame = &frames[lockout_sample.frame_index];
+
+            if let Some(lockout_angle) = rules.lockout_angle {
+                if lockout_sample.angle < lockout_angle {
+                    hits.push(Hit::new(Fault::IncompleteLockout, Some(rep_number), lockout_frame, lockout_sample.confidence));
+                }
+            }
+
+            for &fault in rules.faults {
+                match fault {
+                    Fault::KneeValgus => {
+                        if let Some(drift) = knee_valgus(peak_frame) {
+                            if drift > VALGUS_THRESHOLD {
+                                hits.push(Hit::new(fault, Some(rep_number), peak_frame, peak_sample.confidence));
+                            }
+                        }
+                    }
+                    Fault::ButtWink => {
+                        if let Some(index) = butt_wink(&samples[start..=end], frames) {
+                            let sample = &samples[start + index];
+                            hits.push(Hit::new(fault, Some(rep_number), &frames[sample.frame_index], sample.confidence));
+                        }
+                    }
+                    Fault::ForwardLean => {
+                        let limit = match exercise {
+                            ExerciseType::Lunge => 30.0,
+                            _ => 50.0,
+                        };
+                        if torso_lean(peak_frame).is_some_and(|lean| lean > limit) {
+                            hits.push(Hit::new(fault, Some(rep_number), peak_frame, peak_sample.confidence));
+                        }
+                    }
+                    Fault::HipSag => {
+                        if let Some(sample) = window.iter().find(|s| {
+                            body_line(&frames[s.frame_index])
+                                .is_some_and(|(angle, sagging)| sagging && angle < BODY_LINE_THRESHOLD)
+                        }) {
+                            hits.push(Hit::new(fault, Some(rep_number), &frames[sample.frame_index], sample.confidence));
+                        }
+                    }
+                    Fault::AsymmetricLockout => {
+                        let (left, right) = rules.joint.names();
+                        if let (Some(l), Some(r)) = (
+                            joint_angle(lockout_frame, left),
+                            joint_angle(lockout_frame, right),
+                        ) {
+                            if (l.0 - r.0).abs() > ASYMMETRY_THRESHOLD {
+                                hits.push(Hit::new(fault, Some(rep_number), lockout_frame, l.1.min(r.1)));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let tempo_consistency = tempo_consistency(&reps);
+        if tempo_consistency.is_some_and(|score| score < (1.0 - TEMPO_CV_THRESHOLD) * 100.0) {
+            let median = median_duration(&reps);
+            for rep in &reps {
+                let deviation = (rep.duration_ms() as f64 - median).abs() / median.max(1.0);
+                if deviation > TEMPO_CV_THRESHOLD {
+                    hits.push(Hit {
+                        fault: Fault::InconsistentTempo,
+                        rep: Some(rep.rep_number),
+                        frame_number: rep.start_frame,
+                        timestamp_ms: rep.start_ms,
+                        confidence: 1.0,
+                    });
+                }
+            }
+        }
+
+        let range_of_motion = reps
+            .iter()
+            .map(|rep| (rep.range_of_motion / rules.full_range).min(1.0))
+            .sum::<f64>()
+            / reps.len() as f64
+            * 100.0;
+
+        let rep_count = Some(reps.len() as i32);
+        let findings = summarize(hits, reps.len(), rep_count);
+
+        Ok(MovementAnalysis {
+            exercise_type: exercise.clone(),
+            rep_count,
+            reps,
+            hold_duration_ms: None,
+            overall_score: overall_score(findings.form_quality, Some(range_of_motion), tempo_consistency),
+            form_quality: findings.form_quality,
+            injury_risk: findings.injury_risk,
+            range_of_motion: Some(range_of_motion),
+            tempo_consistency,
+            issues: findings.issues,
+            recommendations: findings.recommendations,
+        })
+    }
+
+    fn analyze_hold(&self, exercise: &ExerciseType, frames: &[PoseFrame]) -> Result<MovementAnalysis> {
+        let samples: Vec<(&PoseFrame, f64, bool)> = frames
+            .iter()
+            .filter_map(|frame| body_line(frame).map(|(angle, sagging)| (frame, angle, sagging)))
+            .collect();
+
+        if samples.is_empty() {
+            anyhow::bail!("No frames with visible shoulders, hips and ankles to analyze");
+        }
+
+        let mut hits = Vec::new();
+        let mut hold_duration_ms = 0;
+
+        for (i, &(frame, angle, sagging)) in samples.iter().enumerate() {
+            if angle < BODY_LINE_THRESHOLD {
+                let fault = if sagging { Fault::HipSag } else { Fault::HipPike };
+                hits.push(Hit::new(fault, None, frame, average_confidence(frame)));
+            } else if let Some(&(next, next_angle, _)) = samples.get(i + 1) {
+                if next_angle >= BODY_LINE_THRESHOLD {
+                    hold_duration_ms += next.timestamp_ms.saturating_sub(frame.timestamp_ms);
+                }
+            }
+        }
+
+        // Stability is how little the body line wobbles over the hold
+        let angles: Vec<f64> = samples.iter().map(|s| s.1).collect();
+        let stability = (100.0 - std_dev(&angles) * 5.0).clamp(0.0, 100.0);
+
+        let findings = summarize(hits, samples.len(), None);
+
+        Ok(MovementAnalysis {
+            exercise_type: exercise.clone(),
+            rep_count: None,
+            reps: Vec::new(),
+            hold_duration_ms: Some(hold_duration_ms),
+            overall_score: overall_score(findings.form_quality, None, Some(stability)),
+            form_quality: findings.form_quality,
+            injury_risk: findings.injury_risk,
+            range_of_motion: None,
+            tempo_consistency: Some(stability),
+            issues: findings.issues,
+            recommendations: findings.recommendations,
+        })
+    }
+}
+
+impl Default for MovementAnalysisService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Split the angle series into reps: leave the start position, pass the target, return
+///
+/// Returns (start, peak, end) sample indices for each completed rep.
+fn segment_reps(samples: &[Sample], rules: &RepRules) -> Vec<(usize, usize, usize)> {
+    let direction = rules.direction;
+    let start_progress = direction.progress(rules.start_threshold);
+    let target_progress = direction.progress(rules.target_threshold);
+
+    let mut reps = Vec::new();
+    let mut last_start: Option<usize> = None;
+    let mut current: Option<(usize, usize)> = None;
+    let mut reached_target = false;
+
+    for (i, sample) in samples.iter().enumerate() {
+        let progress = direction.progress(sample.angle);
+
+        if progress <= start_progress {
+            if let Some((start, peak)) = current.take() {
+                if reached_target {
+                    reps.push((start, peak, i));
+                }
+            }
+            reached_target = false;
+            last_start = Some(i);
+        } else if let Some((_, peak)) = current.as_mut() {
+            if progress > direction.progress(samples[*peak].angle) {
+                *peak = i;
+            }
+            reached_target |= progress >= target_progress;
+        } else if let Some(start) = last_start {
+            // Only count reps that begin from the start position
+            current = Some((start, i));
+            reached_target = progress >= target_progress;
+        }
+    }
+
+    reps
+}
+
+/// Issues, recommendations and form scores derived from fault hits
+struct Findings {
+    issues: Vec<MovementIssue>,
+    recommendations: Vec<MovementRecommendation>,
+    form_quality: f64,
+    injury_risk: f64,
+}
+
+/// Result for a set in which no rep went through the full movement. Nothing is scored; the
+/// recommendation tells the athlete how to record a set that can be analyzed.
+fn no_reps_analysis(exercise: &ExerciseType) -> MovementAnalysis {
+    MovementAnalysis {
+        exercise_type: exercise.clone(),
+        rep_count: Some(0),
+        reps: vec![],
+        hold_duration_ms: None,
+        overall_score: 0.0,
+        form_quality: 0.0,
+        injury_risk: 0.0,
+        range_of_motion: None,
+        tempo_consistency: None,
+        issues: vec![],
+        recommendations: vec![MovementRecommendation {
+            priority: RecommendationPriority::High,
+            issue: "No complete repetitions detected".to_string(),
+            suggestion: format!(
+                "No {} rep went through the full range of motion. Record the whole set from the side with your full body in frame, and complete each rep from start to finish.",
+                exercise
+            ),
+            exercises: vec![],
+            cue: "Full range, full body in frame".to_string(),
+        }],
+    }
+}
+
+/// Group hits by fault and score them against the number of reps (or frames, for holds)
+fn summarize(mut hits: Vec<Hit>, units: usize, rep_count: Option<i32>) -> Findings {
+    hits.sort_by_key(|hit| (hit.fault, hit.frame_number));
+
+    let mut issues = Vec::new();
+    let mut recommendations = Vec::new();
+    let mut form_quality: f64 = 100.0;
+    let mut injury_risk: f64 = 0.0;
+
+    for group in hits.chunk_by(|a, b| a.fault == b.fault) {
+        let fault = group[0].fault;
+
+        let affected = if group[0].rep.is_some() {
+            let mut reps: Vec<u32> = group.iter().filter_map(|hit| hit.rep).collect();
+            reps.dedup();
+            reps.len()
+        } else {
+            group.len()
+        };
+        let share = (affected as f64 / units.max(1) as f64).min(1.0);
+
+        let severity = fault.severity(share);
+        let (penalty, priority) = match severity {
+            IssueSeverity::Critical => (30.0, RecommendationPriority::High),
+            IssueSeverity::Warning => (15.0, RecommendationPriority::Medium),
+            IssueSeverity::Minor => (5.0, RecommendationPriority::Low),
+        };
+        form_quality -= penalty * share.max(0.25);
+        injury_risk += fault.risk_weight() * share;
+
+        let description = match rep_count {
+            Some(total) => format!("{} in {} of {} reps", fault.description(), affected, total),
+            None => format!("{} for {:.0}% of the hold", fault.description(), share * 100.0),
+        };
+
+        let (suggestion, exercises, cue) = fault.recommendation();
+        recommendations.push(MovementRecommendation {
+            priority,
+            issue: fault.issue_type().to_string(),
+            suggestion: suggestion.to_string(),
+            exercises: exercises.iter().map(|e| e.to_string()).collect(),
+            cue: cue.to_string(),
+        });
+
+        issues.push(MovementIssue {
+            severity,
+            issue_type: fault.issue_type().to_string(),
+            description,
+            frames: group.iter().map(|hit| hit.frame_number as i32).collect(),
+            timestamps_ms: group.iter().map(|hit| hit.timestamp_ms).collect(),
+            confidence: (group.iter().map(|hit| hit.confidence).sum::<f64>() / group.len() as f64) as f32,
+        });
+    }
+
+    issues.sort_by_key(|issue| match issue.severity {
+        IssueSeverity::Critical => 0,
+        IssueSeverity::Warning => 1,
+        IssueSeverity::Minor => 2,
+    });
+    recommendations.sort_by_key(|rec| match rec.priority {
+        RecommendationPriority::High => 0,
+        RecommendationPriority::Medium => 1,
+        RecommendationPriority::Low => 2,
+    });
+
+    Findings {
+        issues,
+        recommendations,
+        form_quality: form_quality.clamp(0.0, 100.0),
+        injury_risk: injury_risk.clamp(0.0, 100.0),
+    }
+}
+
+/// Weighted blend of whichever component scores are available
+fn overall_score(form_quality: f64, range_of_motion: Option<f64>, tempo_consistency: Option<f64>) -> f64 {
+    let components = [
+        (Some(form_quality), 0.5),
+        (range_of_motion, 0.25),
+        (tempo_consistency, 0.25),
+    ];
+    let (weighted, weights) = components
+        .iter()
+        .filter_map(|(score, weight)| score.map(|s| (s * weight, *weight)))
+        .fold((0.0, 0.0), |(sum, total), (s, w)| (sum + s, total + w));
+
+    (weighted / weights).clamp(0.0, 100.0)
+}
+
+/// Angle (degrees) and confidence of a named joint
+fn joint_angle(frame: &PoseFrame, name: &str) -> Option<(f64, f64)> {
+    frame
+        .joint_angles
+        .iter()
+        .find(|angle| angle.name == name)
+        .map(|angle| (angle.angle_degrees as f64, angle.confidence as f64))
+}
+
+fn combined_angle(frame: &PoseFrame, joint: Joint, combine: Combine) -> Option<(f64, f64)> {
+    let (left, right) = joint.names();
+    match (joint_angle(frame, left), joint_angle(frame, right)) {
+        (Some(l), Some(r)) => Some(match combine {
+            Combine::Mean => ((l.0 + r.0) / 2.0, l.1.min(r.1)),
+            Combine::Min => if l.0 <= r.0 { l } else { r },
+        }),
+        (Some(side), None) | (None, Some(side)) => Some(side),
+        (None, None) => None,
+    }
+}
+
+fn point(frame: &PoseFrame, name: &str) -> Option<(f64, f64)> {
+    frame
+        .get_keypoint(name)
+        .filter(|kp| kp.visible)
+        .map(|kp| (kp.x as f64, kp.y as f64))
+}
+
+/// Midpoint of a left/right pair, or whichever side is visible
+fn pair_point(frame: &PoseFrame, left: &str, right: &str) -> Option<(f64, f64)> {
+    match (point(frame, left), point(frame, right)) {
+        (Some(l), Some(r)) => Some(((l.0 + r.0) / 2.0, (l.1 + r.1) / 2.0)),
+        (Some(p), None) | (None, Some(p)) => Some(p),
+        (None, None) => None,
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+fn average_confidence(frame: &PoseFrame) -> f64 {
+    if frame.keypoints.is_empty() {
+        return 0.0;
+    }
+    frame.keypoints.iter().map(|kp| kp.confidence as f64).sum::<f64>() / frame.keypoints.len() as f64
+}
+
+/// Largest medial knee drift relative to leg length, front-on views only
+fn knee_valgus(frame: &PoseFrame) -> Option<f64> {
+    let left_hip = point(frame, "left_hip")?;
+    let right_hip = point(frame, "right_hip")?;
+    let shoulders = pair_point(frame, "left_shoulder", "right_shoulder")?;
+    let midline = (left_hip.0 + right_hip.0) / 2.0;
+
+    // Side-on footage hides the frontal plane, hips must be clearly apart
+    let torso = distance(shoulders, (midline, (left_hip.1 + right_hip.1) / 2.0));
+    if (left_hip.0 - right_hip.0).abs() < torso * 0.3 {
+        return None;
+    }
+
+    [("left_hip", "left_knee", "left_ankle"), ("right_hip", "right_knee", "right_ankle")]
+        .iter()
+        .filter_map(|(hip, knee, ankle)| {
+            let hip = point(frame, hip)?;
+            let knee = point(frame, knee)?;
+            let ankle = point(frame, ankle)?;
+            if (ankle.1 - hip.1).abs() < f64::EPSILON {
+                return None;
+            }
+
+            // Where the knee would sit on a straight hip-ankle line
+            let t = (knee.1 - hip.1) / (ankle.1 - hip.1);
+            let line_x = hip.0 + t * (ankle.0 - hip.0);
+            let drift = (line_x - midline).abs() - (knee.0 - midline).abs();
+
+            Some(drift.max(0.0) / distance(hip, ankle))
+        })
+        .reduce(f64::max)
+}
+
+/// Index within the rep where the hips keep folding after the knees have settled
+fn butt_wink(rep: &[Sample], frames: &[PoseFrame]) -> Option<usize> {
+    let knee = |s: &Sample| combined_angle(&frames[s.frame_index], Joint::Knee, Combine::Mean);
+    let hip = |s: &Sample| combined_angle(&frames[s.frame_index], Joint::Hip, Combine::Mean);
+
+    let bottom_knee = rep.iter().filter_map(|s| knee(s).map(|k| k.0)).reduce(f64::min)?;
+
+    // Frames where the knees are within 10° of the bottom
+    let settled: Vec<(usize, f64)> = rep
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| knee(s).is_some_and(|k| k.0 <= bottom_knee + 10.0))
+        .filter_map(|(i, s)| hip(s).map(|h| (i, h.0)))
+        .collect();
+
+    let &(_, entry_hip) = settled.first()?;
+    let &(index, lowest_hip) = settled.iter().min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    (entry_hip - lowest_hip > BUTT_WINK_THRESHOLD).then_some(index)
+}
+
+/// Torso angle from vertical in degrees
+fn torso_lean(frame: &PoseFrame) -> Option<f64> {
+    let shoulders = pair_point(frame, "left_shoulder", "right_shoulder")?;
+    let hips = pair_point(frame, "left_hip", "right_hip")?;
+    let length = distance(shoulders, hips);
+    if length < f64::EPSILON {
+        return None;
+    }
+    // Image y grows downward, so upright is a negative y offset
+    Some(((hips.1 - shoulders.1) / length).clamp(-1.0, 1.0).acos().to_degrees())
+}
+
+/// Shoulder-hip-ankle angle and whether the hips sit below the shoulder-ankle line
+fn body_line(frame: &PoseFrame) -> Option<(f64, bool)> {
+    ["left", "right"]
+        .iter()
+        .filter_map(|side| {
+            let shoulder = point(frame, &format!("{}_shoulder", side))?;
+            let hip = point(frame, &format!("{}_hip", side))?;
+            let ankle = point(frame, &format!("{}_ankle", side))?;
+
+            let a = (shoulder.0 - hip.0, shoulder.1 - hip.1);
+            let b = (ankle.0 - hip.0, ankle.1 - hip.1);
+            let magnitude = distance(shoulder, hip) * distance(ankle, hip);
+            if magnitude < f64::EPSILON || (ankle.0 - shoulder.0).abs() < f64::EPSILON {
+                return None;
+            }
+            let angle = ((a.0 * b.0 + a.1 * b.1) / magnitude).clamp(-1.0, 1.0).acos().to_degrees();
+
+            let line_y = shoulder.1 + (hip.0 - shoulder.0) / (ankle.0 - shoulder.0) * (ankle.1 - shoulder.1);
+            Some((angle, hip.1 > line_y))
+        })
+        .next()
+}
+
+fn median_duration(reps: &[RepSegment]) -> f64 {
+    let mut durations: Vec<u64> = reps.iter().map(RepSegment::duration_ms).collect();
+    durations.sort_unstable();
+    durations[durations.len() / 2] as f64
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64).sqrt()
+}
+
+/// Tempo consistency from the coefficient of variation of rep durations
+fn tempo_consistency(reps: &[RepSegment]) -> Option<f64> {
+    if reps.len() < 2 {
+        return None;
+    }
+    let durations: Vec<f64> = reps.iter().map(|rep| rep.duration_ms() as f64).collect();
+    let mean = durations.iter().sum::<f64>() / durations.len() as f64;
+    if mean <= 0.0 {
+        return None;
+    }
+    Some(((1.0 - std_dev(&durations) / mean) * 100.0).clamp(0.0, 100.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::keypoint::{JointAngle, Keypoint};
+
+    fn frame(index: u32, angles: &[(&str, f64)], keypoints: &[(&str, f32, f32)]) -> PoseFrame {
+        let keypoints = keypoints
+            .iter()
+            .map(|(name, x, y)| Keypoint::new(*x, *y, 0.9, name.to_string()))
+            .collect();
+        let mut frame = PoseFrame::new(index as u64 * 100, index, keypoints);
+        frame.joint_angles = angles
+            .iter()
+            .map(|(name, degrees)| JointAngle {
+                name: name.to_string(),
+                angle_degrees: *degrees as f32,
+                angle_radians: degrees.to_radians() as f32,
+                confidence: 0.9,
+            })
+            .collect();
+        frame
+    }
+
+    /// Bilateral frames following a sequence of primary joint angles
+    fn series(joint: Joint, angles: &[f64]) -> Vec<PoseFrame> {
+        let (left, right) = joint.names();
+        angles
+            .iter()
+            .enumerate()
+            .map(|(i, &angle)| frame(i as u32, &[(left, angle), (right, angle)], &[]))
+            .collect()
+    }
+
+    fn rep(top: f64, bottom: f64, steps: usize) -> Vec<f64> {
+        let step = (top - bottom) / steps as f64;
+        let mut angles: Vec<f64> = (0..steps).map(|i| top - step * i as f64).collect();
+        angles.extend((0..steps).map(|i| bottom + step * i as f64));
+        angles
+    }
+
+    fn set(top: f64, bottom: f64, reps: usize) -> Vec<f64> {
+        let mut angles: Vec<f64> = (0..reps).flat_map(|_| rep(top, bottom, 5)).collect();
+        angles.push(top);
+        angles
+    }
+
+    #[test]
+    fn test_counts_squat_reps_with_timestamps() {
+        let service = MovementAnalysisService::new();
+        let frames = series(Joint::Knee, &set(170.0, 85.0, 3));
+        let analysis = service.analyze(&ExerciseType::Squat, &frames).unwrap();
+
+        assert_eq!(analysis.rep_count, Some(3));
+        let first = &analysis.reps[0];
+        assert_eq!(first.start_ms, 0);
+        assert_eq!(first.peak_ms, 500);
+        assert!((first.peak_angle - 85.0).abs() < 0.01);
+        assert!(first.range_of_motion > 80.0);
+        assert!(analysis.issues.is_empty());
+        assert!(analysis.tempo_consistency.unwrap() > 95.0);
+        assert!(analysis.overall_score > 90.0);
+    }
+
+    #[test]
+    fn test_shallow_squat_flags_depth() {
+        let service = MovementAnalysisService::new();
+        let frames = series(Joint::Knee, &set(170.0, 120.0, 2));
+        let analysis = service.analyze(&ExerciseType::Squat, &frames).unwrap();
+
+        assert_eq!(analysis.rep_count, Some(2));
+        let issue = analysis
+            .issues
+            .iter()
+            .find(|issue| issue.issue_type == "insufficient_depth")
+            .unwrap();
+        assert_eq!(issue.frames, vec![5, 15]);
+        assert_eq!(issue.timestamps_ms, vec![500, 1500]);
+        assert_eq!(issue.severity, IssueSeverity::Warning);
+        assert!(analysis.range_of_motion.unwrap() < 60.0);
+    }
+
+    #[test]
+    fn test_partial_movement_is_not_a_rep() {
+        let service = MovementAnalysisService::new();
+        let frames = series(Joint::Knee, &[170.0, 160.0, 150.0, 145.0, 150.0, 165.0, 170.0]);
+        let analysis = service.analyze(&ExerciseType::Squat, &frames).unwrap();
+
+        assert_eq!(analysis.rep_count, Some(0));
+        assert!(analysis.reps.is_empty());
+        assert!(analysis.issues.is_empty());
+        assert_eq!(analysis.recommendations[0].issue, "No complete repetitions detected");
+    }
+
+    #[test]
+    fn test_overhead_press_counts_extension_reps() {
+        let service = MovementAnalysisService::new();
+        let mut angles = Vec::new();
+        for _ in 0..2 {
+            angles.extend(rep(70.0, 170.0, 5));
+        }
+        angles.push(70.0);
+        let analysis = service
+            .analyze(&ExerciseType::OverheadPress, &series(Joint::Elbow, &angles))
+            .unwrap();
+
+        assert_eq!(analysis.rep_count, Some(2));
+        assert!(analysis.issues.iter().all(|issue| issue.issue_type != "incomplete_lockout"));
+        // Pressing up from the rack is the concentric phase
+        assert_eq!(analysis.reps[0].concentric_ms, 300);
+    }
+
+    #[test]
+    fn test_detects_asymmetric_lockout() {
+        let service = MovementAnalysisService::new();
+        let angles = set(165.0, 80.0, 2);
+        let frames: Vec<PoseFrame> = angles
+            .iter()
+            .enumerate()
+            .map(|(i, &angle)| {
+                // Right arm stops 25° short of the left at the top
+                let right = if angle > 150.0 { angle - 25.0 } else { angle };
+                frame(i as u32, &[("left_elbow", angle), ("right_elbow", right)], &[])
+            })
+            .collect();
+        let analysis = service.analyze(&ExerciseType::BenchPress, &frames).unwrap();
+
+        assert!(analysis
+            .issues
+            .iter()
+            .any(|issue| issue.issue_type == "asymmetric_lockout"));
+        assert!(analysis.injury_risk > 0.0);
+        assert!(analysis
+            .recommendations
+            .iter()
+            .any(|rec| rec.issue == "asymmetric_lockout"));
+    }
+
+    #[test]
+    fn test_detects_knee_valgus_from_front_view() {
+        let service = MovementAnalysisService::new();
+        let angles = set(170.0, 85.0, 1);
+        let frames: Vec<PoseFrame> = angles
+            .iter()
+            .enumerate()
+            .map(|(i, &angle)| {
+                // Knees collapse toward the midline at the bottom
+                let knee_offset = if angle < 100.0 { 0.02 } else { 0.1 };
+                frame(
+                    i as u32,
+                    &[("left_knee", angle), ("right_knee", angle)],
+                    &[
+                        ("left_shoulder", 0.4, 0.2),
+                        ("right_shoulder", 0.6, 0.2),
+                        ("left_hip", 0.4, 0.5),
+                        ("right_hip", 0.6, 0.5),
+                        ("left_knee", 0.5 - knee_offset, 0.65),
+                        ("right_knee", 0.5 + knee_offset, 0.65),
+                        ("left_ankle", 0.4, 0.8),
+                        ("right_ankle", 0.6, 0.8),
+                    ],
+                )
+            })
+            .collect();
+        let analysis = service.analyze(&ExerciseType::Squat, &frames).unwrap();
+
+        let issue = analysis
+            .issues
+            .iter()
+            .find(|issue| issue.issue_type == "knee_valgus")
+            .unwrap();
+        assert_eq!(issue.severity, IssueSeverity::Critical);
+        assert_eq!(issue.frames, vec![5]);
+    }
+
+    #[test]
+    fn test_plank_hip_sag_and_hold_time() {
+        let service = MovementAnalysisService::new();
+        let frames: Vec<PoseFrame> = (0..10)
+            .map(|i| {
+                // Hips drop below the shoulder-ankle line for the last three frames
+                let hip_y = if i >= 7 { 0.62 } else { 0.5 };
+                frame(
+                    i,
+                    &[],
+                    &[
+                        ("left_shoulder", 0.2, 0.5),
+                        ("left_hip", 0.5, hip_y),
+                        ("left_ankle", 0.8, 0.5),
+                    ],
+                )
+            })
+            .collect();
+        let analysis = service.analyze(&ExerciseType::Plank, &frames).unwrap();
+
+        assert_eq!(analysis.rep_count, None);
+        assert_eq!(analysis.hold_duration_ms, Some(600));
+        let issue = &analysis.issues[0];
+        assert_eq!(issue.issue_type, "hip_sag");
+        assert_eq!(issue.timestamps_ms, vec![700, 800, 900]);
+    }
+
+    #[test]
+    fn test_unsupported_exercise() {
+        let service = MovementAnalysisService::new();
+        assert!(!MovementAnalysisService::supports(&ExerciseType::Running));
+        assert!(service.analyze(&ExerciseType::Running, &[]).is_err());
+        assert_eq!(ExerciseType::from("Push-Up"), ExerciseType::PushUp);
+        assert_eq!(ExerciseType::from("bench_press"), ExerciseType::BenchPress);
+    }
+}
@@ -1,24 +1,59 @@
-use chrono::{DateTime, Utc, Duration};
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration, NaiveDate, Utc};
 use serde_json::json;
 use sqlx::PgPool;
+use std::future::Future;
 use std::sync::Arc;
 use tokio::time::{interval, Duration as TokioDuration};
 use uuid::Uuid;
 
-use crate::models::{
-    NotificationType, DeliveryChannel, CreateNotificationRequest,
-};
+use crate::models::{GoalStatus, Notification, RecoveryScore};
 use crate::services::{
-    NotificationService,
-    notification_service::{PerformanceAlertType, HealthAlertType, MotivationNotificationType},
-    TrainingAnalysisService, PerformanceInsightsService,
+    GoalService, NotificationService,
+    notification_service::{NotificationError, PerformanceAlertType, HealthAlertType, MotivationNotificationType},
+    training_analysis_service::PerformanceManagementChart,
+    TrainingAnalysisService,
 };
 
-#[derive(Debug)]
+/// Users with a session or recovery score this recently get scheduled checks
+const ACTIVE_USER_DAYS: i64 = 30;
+/// Days of PMC history loaded for the load checks
+const PMC_HISTORY_DAYS: i32 = 90;
+/// Load checks are skipped when the PMC hasn't been updated for this long
+const STALE_PMC_DAYS: i64 = 7;
+/// Fitness (CTL) trends are compared over this many days
+const FITNESS_TREND_DAYS: usize = 28;
+/// Fitness needs to be at least this high for a trend to mean anything
+const MIN_TREND_CTL: f64 = 20.0;
+/// Fitness trend alerts open at these changes and close only once the trend falls back
+/// below the clear thresholds, so a trend hovering at the line doesn't alert repeatedly
+const FITNESS_IMPROVEMENT_PERCENT: f64 = 10.0;
+const FITNESS_IMPROVEMENT_CLEAR_PERCENT: f64 = 5.0;
+const PERFORMANCE_DECLINE_PERCENT: f64 = 15.0;
+const PERFORMANCE_DECLINE_CLEAR_PERCENT: f64 = 7.5;
+/// Acute:chronic workload ratio treated as a load spike
+const LOAD_SPIKE_RATIO: f64 = 1.5;
+/// Chronic load below which the acute:chronic ratio is too noisy to use
+const MIN_RATIO_CTL: f64 = 10.0;
+/// TSB below this counts as a fatigued day
+const NEGATIVE_TSB_THRESHOLD: f64 = -20.0;
+const NEGATIVE_TSB_ALERT_DAYS: usize = 5;
+/// Recent recovery scores looked at for poor recovery
+const RECOVERY_WINDOW: usize = 3;
+/// Completed goals older than this are not announced
+const GOAL_ACHIEVEMENT_DAYS: i64 = 7;
+const SESSION_MILESTONES: [i64; 6] = [10, 25, 50, 100, 250, 500];
+const FITNESS_MILESTONES: [f64; 4] = [25.0, 50.0, 75.0, 100.0];
+
+/// Runs the periodic notification jobs: sending due notifications and digests, and checking
+/// each active user's training load, goals and streaks. Each alert fires once per condition
+/// window, tracked in `notification_alert_windows`, so restarts and repeated checks don't
+/// resend it.
+#[derive(Clone)]
 pub struct NotificationScheduler {
     notification_service: Arc<NotificationService>,
     training_analysis_service: Arc<TrainingAnalysisService>,
-    performance_insights_service: Arc<PerformanceInsightsService>,
+    goal_service: Arc<GoalService>,
     db: PgPool,
 }
 
@@ -26,51 +61,48 @@ impl NotificationScheduler {
     pub fn new(
         notification_service: Arc<NotificationService>,
         training_analysis_service: Arc<TrainingAnalysisService>,
-        performance_insights_service: Arc<PerformanceInsightsService>,
+        goal_service: Arc<GoalService>,
         db: PgPool,
     ) -> Self {
         Self {
             notification_service,
             training_analysis_service,
-            performance_insights_service,
+            goal_service,
             db,
         }
     }
 
     /// Start the notification scheduler
-    pub async fn start(&self) {
-        let scheduler = Arc::new(self.clone());
-
+    pub fn start(self: Arc<Self>) {
         // Spawn different scheduler tasks
-        let scheduler_clone = scheduler.clone();
+        let scheduler = self.clone();
         tokio::spawn(async move {
-            scheduler_clone.run_scheduled_notifications().await;
+            scheduler.run_scheduled_notifications().await;
         });
 
-        let scheduler_clone = scheduler.clone();
+        let scheduler = self.clone();
         tokio::spawn(async move {
-            scheduler_clone.run_training_reminder_check().await;
+            scheduler.run_training_reminder_check().await;
         });
 
-        let scheduler_clone = scheduler.clone();
+        let scheduler = self.clone();
         tokio::spawn(async move {
-            scheduler_clone.run_performance_monitoring().await;
+            scheduler.run_performance_monitoring().await;
         });
 
-        let scheduler_clone = scheduler.clone();
+        let scheduler = self.clone();
         tokio::spawn(async move {
-            scheduler_clone.run_health_monitoring().await;
+            scheduler.run_health_monitoring().await;
         });
 
-        let scheduler_clone = scheduler.clone();
         tokio::spawn(async move {
-            scheduler_clone.run_motivation_scheduler().await;
+            self.run_motivation_scheduler().await;
         });
 
         tracing::info!("Notification scheduler started");
     }
 
-    /// Run scheduled notifications every minute
+    /// Run scheduled notifications and due digests every minute
     async fn run_scheduled_notifications(&self) {
         let mut interval = interval(TokioDuration::from_secs(60)); // Every minute
 
@@ -87,6 +119,17 @@ impl NotificationScheduler {
                     tracing::error!("Failed to send scheduled notifications: {}", e);
                 }
             }
+
+            match self.notification_service.send_due_batches().await {
+                Ok(count) => {
+                    if count > 0 {
+                        tracing::info!("Sent {} notification digests", count);
+                    }
+                },
+                Err(e) => {
+                    tracing::error!("Failed to send notification digests: {}", e);
+                }
+            }
         }
     }
 
@@ -97,18 +140,9 @@ impl NotificationScheduler {
         loop {
             interval.tick().await;
 
-            let users = self.get_active_users().await.unwrap_or_default();
-
-            for user_id in users {
-                match self.notification_service.schedule_training_reminders(user_id).await {
-                    Ok(notifications) => {
-                        if !notifications.is_empty() {
-                            tracing::info!("Scheduled {} training reminders for user {}", notifications.len(), user_id);
-                        }
-                    },
-                    Err(e) => {
-                        tracing::error!("Failed to schedule training reminders for user {}: {}", user_id, e);
-                    }
+            for user_id in self.active_users().await {
+                if let Err(e) = self.schedule_workout_reminders(user_id).await {
+                    tracing::error!("Failed to schedule training reminders for user {}: {:#}", user_id, e);
                 }
             }
         }
@@ -121,61 +155,13 @@ impl NotificationScheduler {
         loop {
             interval.tick().await;
 
-            let users = self.get_active_users().await.unwrap_or_default();
-
-            for user_id in users {
-                // Check for fitness improvements
-                if let Ok(improvement_detected) = self.check_fitness_improvement(user_id).await {
-                    if improvement_detected {
-                        let data = json!({
-                            "improvement_percentage": 5.2,
-                            "period_days": 7,
-                            "metric": "FTP"
-                        });
-
-                        if let Err(e) = self.notification_service
-                            .create_performance_alert(user_id, PerformanceAlertType::FitnessImprovement, data)
-                            .await
-                        {
-                            tracing::error!("Failed to create fitness improvement alert for user {}: {}", user_id, e);
-                        }
-                    }
-                }
-
-                // Check for goal achievements
-                if let Ok(goals_achieved) = self.check_goal_achievements(user_id).await {
-                    for goal in goals_achieved {
-                        let data = json!({
-                            "goal_id": goal.id,
-                            "goal_title": goal.title,
-                            "achievement_date": Utc::now()
-                        });
-
-                        if let Err(e) = self.notification_service
-                            .create_performance_alert(user_id, PerformanceAlertType::GoalAchievement, data)
-                            .await
-                        {
-                            tracing::error!("Failed to create goal achievement alert for user {}: {}", user_id, e);
-                        }
-                    }
+            for user_id in self.active_users().await {
+                if let Err(e) = self.check_performance(user_id).await {
+                    tracing::error!("Failed to check performance for user {}: {:#}", user_id, e);
                 }
 
-                // Check for performance decline
-                if let Ok(decline_detected) = self.check_performance_decline(user_id).await {
-                    if decline_detected {
-                        let data = json!({
-                            "decline_percentage": -8.5,
-                            "period_days": 14,
-                            "recommendation": "Consider reducing training intensity"
-                        });
-
-                        if let Err(e) = self.notification_service
-                            .create_performance_alert(user_id, PerformanceAlertType::PerformanceDecline, data)
-                            .await
-                        {
-                            tracing::error!("Failed to create performance decline alert for user {}: {}", user_id, e);
-                        }
-                    }
+                if let Err(e) = self.check_goal_achievements(user_id).await {
+                    tracing::error!("Failed to check goal achievements for user {}: {:#}", user_id, e);
                 }
             }
         }
@@ -188,64 +174,9 @@ impl NotificationScheduler {
         loop {
             interval.tick().await;
 
-            let users = self.get_active_users().await.unwrap_or_default();
-
-            for user_id in users {
-                // Check for overtraining risk
-                if let Ok(overtraining_risk) = self.check_overtraining_risk(user_id).await {
-                    if overtraining_risk {
-                        let data = json!({
-                            "risk_level": "High",
-                            "training_load_ratio": 1.8,
-                            "recommendation": "Take 2-3 recovery days",
-                            "detected_at": Utc::now()
-                        });
-
-                        if let Err(e) = self.notification_service
-                            .create_health_alert(user_id, HealthAlertType::OvertrainingRisk, data)
-                            .await
-                        {
-                            tracing::error!("Failed to create overtraining risk alert for user {}: {}", user_id, e);
-                        }
-                    }
-                }
-
-                // Check for negative TSB
-                if let Ok(negative_tsb_days) = self.check_negative_tsb(user_id).await {
-                    if negative_tsb_days >= 5 {
-                        let data = json!({
-                            "consecutive_days": negative_tsb_days,
-                            "current_tsb": -35.2,
-                            "recommendation": "Focus on recovery activities",
-                            "detected_at": Utc::now()
-                        });
-
-                        if let Err(e) = self.notification_service
-                            .create_health_alert(user_id, HealthAlertType::NegativeTsb, data)
-                            .await
-                        {
-                            tracing::error!("Failed to create negative TSB alert for user {}: {}", user_id, e);
-                        }
-                    }
-                }
-
-                // Check for injury risk
-                if let Ok(injury_risk) = self.check_injury_risk(user_id).await {
-                    if injury_risk {
-                        let data = json!({
-                            "risk_factors": ["High training load", "Poor recovery", "Load spikes"],
-                            "risk_score": 0.78,
-                            "recommendation": "Consider medical evaluation",
-                            "detected_at": Utc::now()
-                        });
-
-                        if let Err(e) = self.notification_service
-                            .create_health_alert(user_id, HealthAlertType::InjuryRisk, data)
-                            .await
-                        {
-                            tracing::error!("Failed to create injury risk alert for user {}: {}", user_id, e);
-                        }
-                    }
+            for user_id in self.active_users().await {
+                if let Err(e) = self.check_health(user_id).await {
+                    tracing::error!("Failed to check training load for user {}: {:#}", user_id, e);
                 }
             }
         }
@@ -258,150 +189,659 @@ impl NotificationScheduler {
         loop {
             interval.tick().await;
 
-            let users = self.get_active_users().await.unwrap_or_default();
-
-            for user_id in users {
-                // Check if it's time for weekly progress summary (every Monday)
-                if self.is_monday() {
-                    let weekly_data = self.generate_weekly_progress_data(user_id).await.unwrap_or_default();
-                    let data = json!({
-                        "week_summary": weekly_data,
-                        "generated_at": Utc::now()
-                    });
-
-                    if let Err(e) = self.notification_service
-                        .create_motivation_notification(user_id, MotivationNotificationType::WeeklyProgressSummary, data)
-                        .await
-                    {
-                        tracing::error!("Failed to create weekly progress summary for user {}: {}", user_id, e);
-                    }
+            for user_id in self.active_users().await {
+                if let Err(e) = self.check_motivation(user_id).await {
+                    tracing::error!("Failed to check motivation notifications for user {}: {:#}", user_id, e);
                 }
+            }
+        }
+    }
 
-                // Check for achievement badges
-                if let Ok(new_achievements) = self.check_new_achievements(user_id).await {
-                    for achievement in new_achievements {
-                        let data = json!({
-                            "achievement_id": achievement.id,
-                            "achievement_name": achievement.name,
-                            "description": achievement.description,
-                            "earned_at": Utc::now()
-                        });
-
-                        if let Err(e) = self.notification_service
-                            .create_motivation_notification(user_id, MotivationNotificationType::AchievementBadge, data)
-                            .await
-                        {
-                            tracing::error!("Failed to create achievement badge notification for user {}: {}", user_id, e);
-                        }
-                    }
-                }
+    // Checks
+
+    /// One reminder per plan for tomorrow's workouts. Returns how many were newly scheduled.
+    pub async fn schedule_workout_reminders(&self, user_id: Uuid) -> Result<usize> {
+        let upcoming = self.notification_service
+            .get_upcoming_workouts(user_id)
+            .await
+            .context("Failed to load upcoming workouts")?;
+
+        let mut scheduled = 0;
+        for workouts in &upcoming {
+            let key = format!("workout_reminder:{}:{}", workouts.plan_id, workouts.date);
+            let sent = self.notify_once(user_id, &key, || {
+                self.notification_service.create_workout_reminder(user_id, workouts)
+            })
+            .await?;
+
+            if sent {
+                scheduled += 1;
+            }
+        }
+
+        Ok(scheduled)
+    }
+
+    /// Fitness improvement or decline over the last four weeks
+    async fn check_performance(&self, user_id: Uuid) -> Result<()> {
+        let pmc = self.current_pmc(user_id).await?;
+        let trend = pmc.as_deref().and_then(fitness_trend);
+
+        let improving = hysteresis_state(trend, FITNESS_IMPROVEMENT_PERCENT, FITNESS_IMPROVEMENT_CLEAR_PERCENT);
+        self.track_condition(user_id, "fitness_improvement", improving, || {
+            self.notification_service.create_performance_alert(
+                user_id,
+                PerformanceAlertType::FitnessImprovement,
+                json!({
+                    "improvement_percentage": round1(trend.unwrap_or_default()),
+                    "period_days": FITNESS_TREND_DAYS,
+                    "metric": "CTL"
+                }),
+            )
+        })
+        .await?;
+
+        let declining = hysteresis_state(
+            trend.map(|change| -change),
+            PERFORMANCE_DECLINE_PERCENT,
+            PERFORMANCE_DECLINE_CLEAR_PERCENT,
+        );
+        self.track_condition(user_id, "performance_decline", declining, || {
+            self.notification_service.create_performance_alert(
+                user_id,
+                PerformanceAlertType::PerformanceDecline,
+                json!({
+                    "decline_percentage": round1(-trend.unwrap_or_default()),
+                    "period_days": FITNESS_TREND_DAYS,
+                    "metric": "CTL",
+                    "recommendation": "Check whether missed sessions or illness are behind it, and rebuild gradually."
+                }),
+            )
+        })
+        .await
+    }
+
+    /// Goals completed in the last week, each announced once
+    async fn check_goal_achievements(&self, user_id: Uuid) -> Result<()> {
+        let since = Utc::now() - Duration::days(GOAL_ACHIEVEMENT_DAYS);
+        let goals = self.goal_service
+            .get_goals_by_user(user_id, None, None, None, Some(100), None)
+            .await?;
 
-                // Check for training streaks
-                if let Ok(streak_length) = self.check_training_streak(user_id).await {
-                    if streak_length > 0 && streak_length % 7 == 0 { // Every week
-                        let data = json!({
-                            "streak_length": streak_length,
+        for goal in goals {
+            if !matches!(goal.status, GoalStatus::Completed) || goal.updated_at < since {
+                continue;
+            }
+
+            self.notify_once(user_id, &format!("goal_achievement:{}", goal.id), || {
+                self.notification_service.create_performance_alert(
+                    user_id,
+                    PerformanceAlertType::GoalAchievement,
+                    json!({
+                        "goal_id": goal.id,
+                        "goal_title": goal.title,
+                        "achievement_date": goal.updated_at
+                    }),
+                )
+            })
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Load spikes, with or without poor recovery, and extended negative TSB
+    async fn check_health(&self, user_id: Uuid) -> Result<()> {
+        let pmc = self.current_pmc(user_id).await?;
+        let recovery_scores = self.recent_recovery_scores(user_id).await?;
+
+        let ratio = pmc.as_deref()
+            .and_then(|pmc| pmc.last())
+            .and_then(acute_chronic_ratio)
+            .filter(|ratio| *ratio >= LOAD_SPIKE_RATIO);
+        let poor_recovery = is_recovery_poor(&recovery_scores);
+
+        let injury_risk = ratio.is_some() && poor_recovery;
+        self.track_condition(user_id, "injury_risk", injury_risk, || {
+            self.notification_service.create_health_alert(
+                user_id,
+                HealthAlertType::InjuryRisk,
+                json!({
+                    "risk_factors": ["Training load spike", "Poor recovery"],
+                    "training_load_ratio": round1(ratio.unwrap_or_default()),
+                    "recommendation": "Cut back to easy sessions until your recovery improves, and see a professional if anything hurts.",
+                    "detected_at": Utc::now()
+                }),
+            )
+        })
+        .await?;
+
+        // The injury warning already covers a spike with poor recovery
+        let overtraining_risk = ratio.is_some() && !poor_recovery;
+        self.track_condition(user_id, "overtraining_risk", overtraining_risk, || {
+            self.notification_service.create_health_alert(
+                user_id,
+                HealthAlertType::OvertrainingRisk,
+                json!({
+                    "risk_level": "High",
+                    "training_load_ratio": round1(ratio.unwrap_or_default()),
+                    "recommendation": "Take 2-3 easier days to let your body absorb the recent load.",
+                    "detected_at": Utc::now()
+                }),
+            )
+        })
+        .await?;
+
+        let (streak, current_tsb) = pmc.as_deref().map_or((0, None), negative_tsb_streak);
+        self.track_condition(user_id, "negative_tsb", streak >= NEGATIVE_TSB_ALERT_DAYS, || {
+            self.notification_service.create_health_alert(
+                user_id,
+                HealthAlertType::NegativeTsb,
+                json!({
+                    "consecutive_days": streak,
+                    "current_tsb": round1(current_tsb.unwrap_or_default()),
+                    "tsb_threshold": NEGATIVE_TSB_THRESHOLD,
+                    "recommendation": "Focus on recovery activities.",
+                    "detected_at": Utc::now()
+                }),
+            )
+        })
+        .await
+    }
+
+    /// Weekly summary on Mondays, new badges and training streak milestones
+    async fn check_motivation(&self, user_id: Uuid) -> Result<()> {
+        let today = Utc::now().date_naive();
+
+        if today.weekday() == chrono::Weekday::Mon {
+            let week_start = today - Duration::days(7);
+            let week = week_start.iso_week();
+            let summary = self.weekly_progress_data(user_id, week_start, today).await?;
+
+            if summary.total_sessions > 0 {
+                let key = format!("weekly_progress_summary:{}-W{:02}", week.year(), week.week());
+                self.notify_once(user_id, &key, || {
+                    self.notification_service.create_motivation_notification(
+                        user_id,
+                        MotivationNotificationType::WeeklyProgressSummary,
+                        json!({
+                            "week_start": week_start,
+                            "total_sessions": summary.total_sessions,
+                            "total_duration_hours": round1(summary.total_duration_hours),
+                            "total_tss": summary.total_tss.round(),
+                            "generated_at": Utc::now()
+                        }),
+                    )
+                })
+                .await?;
+            }
+        }
+
+        let session_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM training_sessions WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.db)
+            .await
+            .context("Failed to count training sessions")?;
+        let ctl = self.current_pmc(user_id).await?
+            .and_then(|pmc| pmc.last().map(|day| day.ctl))
+            .unwrap_or_default();
+
+        for badge in earned_badges(session_count, ctl) {
+            self.notify_once(user_id, &format!("achievement_badge:{}", badge.id), || {
+                self.notification_service.create_motivation_notification(
+                    user_id,
+                    MotivationNotificationType::AchievementBadge,
+                    json!({
+                        "achievement_id": badge.id,
+                        "achievement_name": badge.name,
+                        "description": badge.description,
+                        "earned_at": Utc::now()
+                    }),
+                )
+            })
+            .await?;
+        }
+
+        let session_dates: Vec<NaiveDate> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT date FROM training_sessions
+            WHERE user_id = $1 AND date <= $2
+            ORDER BY date DESC
+            LIMIT 400
+            "#,
+        )
+        .bind(user_id)
+        .bind(today)
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to load training dates")?;
+
+        if let Some((start, length)) = training_streak(&session_dates, today) {
+            // Announce each full week once, even if a daily check was missed
+            let milestone = length / 7 * 7;
+            if milestone > 0 {
+                self.notify_once(user_id, &format!("training_streak:{}:{}", start, milestone), || {
+                    self.notification_service.create_motivation_notification(
+                        user_id,
+                        MotivationNotificationType::TrainingStreak,
+                        json!({
+                            "streak_length": milestone,
                             "streak_type": "daily_training",
-                            "achievement_level": if streak_length >= 30 { "gold" } else if streak_length >= 14 { "silver" } else { "bronze" },
+                            "achievement_level": if milestone >= 30 { "gold" } else if milestone >= 14 { "silver" } else { "bronze" },
                             "earned_at": Utc::now()
-                        });
-
-                        if let Err(e) = self.notification_service
-                            .create_motivation_notification(user_id, MotivationNotificationType::TrainingStreak, data)
-                            .await
-                        {
-                            tracing::error!("Failed to create training streak notification for user {}: {}", user_id, e);
-                        }
-                    }
-                }
+                        }),
+                    )
+                })
+                .await?;
             }
         }
-    }
 
-    // Helper methods for analysis and checks
+        Ok(())
+    }
 
-    async fn get_active_users(&self) -> Result<Vec<Uuid>, Box<dyn std::error::Error + Send + Sync>> {
-        // Mock implementation - in real code, this would query the database for active users
-        Ok(vec![])
+    // Alert windows
+
+    /// Send the alert when its condition starts to hold, and let it fire again only after
+    /// the condition has cleared
+    async fn track_condition<F, Fut>(
+        &self,
+        user_id: Uuid,
+        key: &str,
+        state: impl Into<ConditionState>,
+        send: F,
+    ) -> Result<()>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Notification, NotificationError>>,
+    {
+        match state.into() {
+            ConditionState::Active => self.notify_once(user_id, key, send).await.map(|_| ()),
+            ConditionState::Cleared => self.close_alert_window(user_id, key).await,
+            ConditionState::Unchanged => Ok(()),
+        }
     }
 
-    async fn check_fitness_improvement(&self, user_id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        // Mock implementation - would analyze training data for fitness improvements
-        Ok(false)
+    /// Send the alert unless its window is already open. Returns whether it was sent.
+    async fn notify_once<F, Fut>(&self, user_id: Uuid, key: &str, send: F) -> Result<bool>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Notification, NotificationError>>,
+    {
+        if !self.open_alert_window(user_id, key).await? {
+            return Ok(false);
+        }
+
+        match send().await {
+            Ok(notification) => {
+                tracing::info!("Sent {} alert {} to user {}", key, notification.id, user_id);
+                Ok(true)
+            },
+            // The user doesn't want it; leave the window open so it isn't retried
+            Err(NotificationError::NotificationDisabled | NotificationError::AllChannelsDisabled) => Ok(false),
+            Err(e) => {
+                // Retry on the next run
+                self.close_alert_window(user_id, key).await?;
+                Err(e).with_context(|| format!("Failed to send {} alert", key))
+            },
+        }
     }
 
-    async fn check_goal_achievements(&self, user_id: Uuid) -> Result<Vec<MockGoal>, Box<dyn std::error::Error + Send + Sync>> {
-        // Mock implementation - would check if any goals have been achieved
-        Ok(vec![])
+    /// Open the alert's window; returns whether it was closed before, i.e. the alert should fire
+    async fn open_alert_window(&self, user_id: Uuid, key: &str) -> Result<bool> {
+        let opened = sqlx::query(
+            r#"
+            INSERT INTO notification_alert_windows (user_id, alert_key)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id, alert_key) DO UPDATE SET opened_at = NOW(), closed_at = NULL
+            WHERE notification_alert_windows.closed_at IS NOT NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(key)
+        .execute(&self.db)
+        .await
+        .context("Failed to open alert window")?;
+
+        Ok(opened.rows_affected() > 0)
     }
 
-    async fn check_performance_decline(&self, user_id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        // Mock implementation - would analyze performance trends
-        Ok(false)
+    async fn close_alert_window(&self, user_id: Uuid, key: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE notification_alert_windows SET closed_at = NOW()
+            WHERE user_id = $1 AND alert_key = $2 AND closed_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(key)
+        .execute(&self.db)
+        .await
+        .context("Failed to close alert window")?;
+
+        Ok(())
     }
 
-    async fn check_overtraining_risk(&self, user_id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        // Mock implementation - would analyze training load and recovery metrics
-        Ok(false)
+    // Data
+
+    /// Users who trained or recorded recovery recently
+    async fn active_users(&self) -> Vec<Uuid> {
+        let since = Utc::now().date_naive() - Duration::days(ACTIVE_USER_DAYS);
+
+        sqlx::query_scalar(
+            r#"
+            SELECT user_id FROM training_sessions WHERE date >= $1
+            UNION
+            SELECT user_id FROM recovery_scores WHERE score_date >= $1
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.db)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!("Failed to load active users: {}", e);
+            vec![]
+        })
     }
 
-    async fn check_negative_tsb(&self, user_id: Uuid) -> Result<i32, Box<dyn std::error::Error + Send + Sync>> {
-        // Mock implementation - would check consecutive days of negative TSB
-        Ok(0)
+    /// The user's PMC, unless it is missing or too old to describe their current load
+    async fn current_pmc(&self, user_id: Uuid) -> Result<Option<Vec<PerformanceManagementChart>>> {
+        let pmc = self.training_analysis_service
+            .calculate_pmc(user_id, PMC_HISTORY_DAYS)
+            .await
+            .context("Failed to calculate PMC")?;
+        let stale_before = Utc::now().date_naive() - Duration::days(STALE_PMC_DAYS);
+
+        Ok(match pmc.last() {
+            Some(latest) if latest.date >= stale_before => Some(pmc),
+            _ => None,
+        })
     }
 
-    async fn check_injury_risk(&self, user_id: Uuid) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        // Mock implementation - would analyze injury risk factors
-        Ok(false)
+    /// Latest recovery scores, newest first
+    async fn recent_recovery_scores(&self, user_id: Uuid) -> Result<Vec<RecoveryScore>> {
+        let since = Utc::now().date_naive() - Duration::days(RECOVERY_WINDOW as i64);
+
+        sqlx::query_as::<_, RecoveryScore>(
+            r#"
+            SELECT id, user_id, score_date, readiness_score, hrv_trend, hrv_deviation,
+                   sleep_quality_score, recovery_adequacy, rhr_deviation, training_strain,
+                   recovery_status, recommended_tss_adjustment, calculated_at, model_version,
+                   created_at, updated_at
+            FROM recovery_scores
+            WHERE user_id = $1 AND score_date > $2
+            ORDER BY score_date DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(since)
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to load recovery scores")
     }
 
-    async fn generate_weekly_progress_data(&self, user_id: Uuid) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
-        // Mock implementation - would generate weekly progress summary
-        Ok(json!({
-            "total_sessions": 5,
-            "total_duration_hours": 8.5,
-            "total_tss": 420,
-            "average_intensity": 0.75,
-            "improvement_areas": ["Endurance", "Recovery"]
-        }))
+    async fn weekly_progress_data(&self, user_id: Uuid, start: NaiveDate, end: NaiveDate) -> Result<WeeklyProgress> {
+        let (total_sessions, total_seconds, total_tss): (i64, i64, f64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*),
+                   COALESCE(SUM(duration_seconds), 0)::BIGINT,
+                   COALESCE(SUM((trainrs_data->>'tss')::DOUBLE PRECISION), 0)
+            FROM training_sessions
+            WHERE user_id = $1 AND date >= $2 AND date < $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(start)
+        .bind(end)
+        .fetch_one(&self.db)
+        .await
+        .context("Failed to summarize the week's training")?;
+
+        Ok(WeeklyProgress {
+            total_sessions,
+            total_duration_hours: total_seconds as f64 / 3600.0,
+            total_tss,
+        })
     }
+}
+
+#[derive(Debug)]
+struct WeeklyProgress {
+    total_sessions: i64,
+    total_duration_hours: f64,
+    total_tss: f64,
+}
 
-    async fn check_new_achievements(&self, user_id: Uuid) -> Result<Vec<MockAchievement>, Box<dyn std::error::Error + Send + Sync>> {
-        // Mock implementation - would check for new achievements
-        Ok(vec![])
+/// Whether an alert's condition holds. Between the open and clear thresholds it is
+/// `Unchanged`, which keeps the alert window as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionState {
+    Active,
+    Cleared,
+    Unchanged,
+}
+
+impl From<bool> for ConditionState {
+    fn from(active: bool) -> Self {
+        if active {
+            ConditionState::Active
+        } else {
+            ConditionState::Cleared
+        }
     }
+}
 
-    async fn check_training_streak(&self, user_id: Uuid) -> Result<i32, Box<dyn std::error::Error + Send + Sync>> {
-        // Mock implementation - would calculate current training streak
-        Ok(0)
+/// Condition of a value that opens an alert at `open_at` and clears below `clear_below`.
+/// No value, e.g. too little history, clears it.
+pub fn hysteresis_state(value: Option<f64>, open_at: f64, clear_below: f64) -> ConditionState {
+    match value {
+        Some(value) if value >= open_at => ConditionState::Active,
+        Some(value) if value >= clear_below => ConditionState::Unchanged,
+        _ => ConditionState::Cleared,
     }
+}
+
+/// Badge earned from training history
+#[derive(Debug, Clone, PartialEq)]
+pub struct Badge {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
+/// Percentage change in fitness (CTL) over the trend period, when there is enough history
+/// and fitness to judge it
+pub fn fitness_trend(pmc: &[PerformanceManagementChart]) -> Option<f64> {
+    let latest = pmc.last()?;
+    let previous = pmc.get(pmc.len().checked_sub(FITNESS_TREND_DAYS + 1)?)?;
 
-    fn is_monday(&self) -> bool {
-        Utc::now().weekday() == chrono::Weekday::Mon
+    if previous.ctl <= 0.0 || previous.ctl.max(latest.ctl) < MIN_TREND_CTL {
+        return None;
     }
+
+    Some((latest.ctl - previous.ctl) / previous.ctl * 100.0)
 }
 
-impl Clone for NotificationScheduler {
-    fn clone(&self) -> Self {
-        Self {
-            notification_service: self.notification_service.clone(),
-            training_analysis_service: self.training_analysis_service.clone(),
-            performance_insights_service: self.performance_insights_service.clone(),
-            db: self.db.clone(),
+/// Acute:chronic workload ratio (ATL/CTL), when chronic load is high enough for it to be
+/// meaningful
+pub fn acute_chronic_ratio(day: &PerformanceManagementChart) -> Option<f64> {
+    (day.ctl >= MIN_RATIO_CTL).then(|| day.atl / day.ctl)
+}
+
+/// Consecutive most recent days with TSB below the fatigue threshold, and the latest TSB
+pub fn negative_tsb_streak(pmc: &[PerformanceManagementChart]) -> (usize, Option<f64>) {
+    let streak = pmc.iter()
+        .rev()
+        .take_while(|day| day.tsb < NEGATIVE_TSB_THRESHOLD)
+        .count();
+
+    (streak, pmc.last().map(|day| day.tsb))
+}
+
+/// Whether most of the latest recovery scores (newest first) are poor or critical
+pub fn is_recovery_poor(scores: &[RecoveryScore]) -> bool {
+    let recent = &scores[..scores.len().min(RECOVERY_WINDOW)];
+    let poor = recent.iter()
+        .filter(|score| matches!(score.recovery_status.as_str(), "poor" | "critical"))
+        .count();
+
+    !recent.is_empty() && poor * 2 > recent.len()
+}
+
+/// Current run of consecutive training days ending today or yesterday, as its first day and
+/// length. `dates` are distinct and newest first.
+pub fn training_streak(dates: &[NaiveDate], today: NaiveDate) -> Option<(NaiveDate, i64)> {
+    let latest = *dates.first()?;
+    if latest < today - Duration::days(1) {
+        return None;
+    }
+
+    let mut start = latest;
+    for date in &dates[1..] {
+        if *date != start - Duration::days(1) {
+            break;
         }
+        start = *date;
     }
+
+    Some((start, (latest - start).num_days() + 1))
 }
 
-// Mock types for demonstration
-#[derive(Debug)]
-struct MockGoal {
-    id: Uuid,
-    title: String,
+/// The highest session-count and fitness badges reached. Lower badges are left out so a
+/// long-time user isn't sent every badge at once.
+pub fn earned_badges(session_count: i64, ctl: f64) -> Vec<Badge> {
+    let mut badges = vec![];
+
+    if let Some(sessions) = SESSION_MILESTONES.iter().rev().find(|milestone| session_count >= **milestone) {
+        badges.push(Badge {
+            id: format!("sessions_{}", sessions),
+            name: format!("{} Sessions", sessions),
+            description: format!("You've logged {} training sessions.", sessions),
+        });
+    }
+
+    if let Some(fitness) = FITNESS_MILESTONES.iter().rev().find(|milestone| ctl >= **milestone) {
+        badges.push(Badge {
+            id: format!("fitness_{}", fitness),
+            name: format!("Fitness {}", fitness),
+            description: format!("Your fitness (CTL) has reached {}.", fitness),
+        });
+    }
+
+    badges
 }
 
-#[derive(Debug)]
-struct MockAchievement {
-    id: Uuid,
-    name: String,
-    description: String,
-}
\ No newline at end of file
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(offset: i64, ctl: f64, atl: f64) -> PerformanceManagementChart {
+        PerformanceManagementChart {
+            ctl,
+            atl,
+            tsb: ctl - atl,
+            tss_daily: 0.0,
+            date: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap() + Duration::days(offset),
+        }
+    }
+
+    fn score(status: &str) -> RecoveryScore {
+        RecoveryScore {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            score_date: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            readiness_score: 50.0,
+            hrv_trend: "stable".to_string(),
+            hrv_deviation: None,
+            sleep_quality_score: None,
+            recovery_adequacy: None,
+            rhr_deviation: None,
+            training_strain: None,
+            recovery_status: status.to_string(),
+            recommended_tss_adjustment: None,
+            calculated_at: Utc::now(),
+            model_version: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_fitness_trend() {
+        let rising: Vec<_> = (0..=28).map(|i| day(i, 40.0 + i as f64 * 0.5, 40.0)).collect();
+        assert_eq!(fitness_trend(&rising).map(round1), Some(35.0));
+
+        // Not enough history
+        assert_eq!(fitness_trend(&rising[1..]), None);
+
+        // Too little fitness to judge
+        let low: Vec<_> = (0..=28).map(|i| day(i, 5.0 + i as f64 * 0.1, 5.0)).collect();
+        assert_eq!(fitness_trend(&low), None);
+    }
+
+    #[test]
+    fn test_hysteresis_state() {
+        let state = |value| hysteresis_state(value, FITNESS_IMPROVEMENT_PERCENT, FITNESS_IMPROVEMENT_CLEAR_PERCENT);
+
+        assert_eq!(state(Some(12.0)), ConditionState::Active);
+        assert_eq!(state(Some(10.0)), ConditionState::Active);
+        // Dips just under the alert threshold keep the window open
+        assert_eq!(state(Some(8.0)), ConditionState::Unchanged);
+        assert_eq!(state(Some(4.9)), ConditionState::Cleared);
+        assert_eq!(state(None), ConditionState::Cleared);
+        assert_eq!(ConditionState::from(true), ConditionState::Active);
+    }
+
+    #[test]
+    fn test_acute_chronic_ratio() {
+        assert_eq!(acute_chronic_ratio(&day(0, 40.0, 60.0)), Some(1.5));
+        assert_eq!(acute_chronic_ratio(&day(0, 5.0, 20.0)), None);
+    }
+
+    #[test]
+    fn test_negative_tsb_streak() {
+        let pmc = vec![
+            day(0, 50.0, 80.0),
+            day(1, 50.0, 60.0),
+            day(2, 50.0, 75.0),
+            day(3, 50.0, 72.0),
+        ];
+
+        assert_eq!(negative_tsb_streak(&pmc), (2, Some(-22.0)));
+        assert_eq!(negative_tsb_streak(&[]), (0, None));
+    }
+
+    #[test]
+    fn test_is_recovery_poor() {
+        assert!(is_recovery_poor(&[score("poor"), score("critical"), score("good")]));
+        assert!(!is_recovery_poor(&[score("poor"), score("good"), score("optimal")]));
+        // Only the latest scores count
+        assert!(!is_recovery_poor(&[score("good"), score("fair"), score("good"), score("poor"), score("poor")]));
+        assert!(!is_recovery_poor(&[]));
+    }
+
+    #[test]
+    fn test_training_streak() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let dates: Vec<_> = [9, 8, 7, 5, 4].iter()
+            .map(|d| NaiveDate::from_ymd_opt(2025, 3, *d).unwrap())
+            .collect();
+
+        assert_eq!(training_streak(&dates, today), Some((NaiveDate::from_ymd_opt(2025, 3, 7).unwrap(), 3)));
+        // A streak that ended before yesterday is over
+        assert_eq!(training_streak(&dates[3..], today), None);
+        assert_eq!(training_streak(&[], today), None);
+    }
+
+    #[test]
+    fn test_earned_badges() {
+        let badges = earned_badges(60, 52.0);
+        let ids: Vec<_> = badges.iter().map(|badge| badge.id.as_str()).collect();
+        assert_eq!(ids, vec!["sessions_50", "fitness_50"]);
+
+        assert!(earned_badges(3, 10.0).is_empty());
+    }
+}
@@ -1,6 +1,6 @@
-use chrono::{DateTime, Utc, Duration, TimeZone};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc, Duration, TimeZone};
 use serde_json::json;
-use sqlx::PgPool;
+use sqlx::{FromRow, PgPool};
 use std::collections::HashMap;
 use uuid::Uuid;
 
@@ -9,26 +9,83 @@ use crate::models::{
     DeliveryChannel, DeliveryStatus, CreateNotificationRequest,
     NotificationPreferences, NotificationTemplate, NotificationBatch, BatchType,
     NotificationMetrics, NotificationTypeMetrics, ChannelMetrics,
-    UpdateNotificationPreferencesRequest,
+    UpdateNotificationPreferencesRequest, NotificationFilter, PlanWeekStructure, WorkoutDay,
 };
+use crate::services::calendar_feed_service::plan_day_date;
+use crate::services::email_notification_service::{EmailError, EmailNotificationService, SmtpConfig};
+use crate::services::notification_timing::{DeliveryPlan, NotificationSchedule};
+use crate::services::web_push_client::{PushMessage, PushUrgency};
+use crate::services::web_push_service::WebPushService;
+use crate::services::workout_export_service::workout_type_label;
+
+/// Plan workouts have a day but no time; reminders assume they start at this local hour
+const PLANNED_WORKOUT_HOUR: u32 = 7;
+
+const NOTIFICATION_COLUMNS: &str = r#"
+    id, user_id, notification_type, category, priority, title, message, data, scheduled_at,
+    sent_at, read_at, delivery_channels, delivery_status, expires_at, created_at, updated_at
+"#;
+
+const PREFERENCE_COLUMNS: &str = r#"
+    user_id, workout_reminders, workout_reminder_advance_minutes, rest_day_reminders,
+    ftp_test_reminders, fitness_improvement_alerts, goal_achievement_alerts,
+    performance_decline_alerts, overtraining_risk_alerts, negative_tsb_alerts, injury_risk_alerts,
+    weekly_progress_summaries, achievement_badge_alerts, training_streak_alerts, email_enabled,
+    web_push_enabled, in_app_enabled, quiet_hours_start, quiet_hours_end, timezone,
+    batch_notifications, batch_interval_minutes, created_at, updated_at
+"#;
+
+const TEMPLATE_COLUMNS: &str = r#"
+    id, notification_type, title_template, message_template, default_channels, default_priority,
+    is_active, created_at, updated_at
+"#;
+
+const BATCH_COLUMNS: &str =
+    "id, user_id, batch_type, title, summary, scheduled_at, sent_at, delivery_channels, created_at";
+
+/// Upper bound on notifications or digests handled per scheduler tick
+const SEND_BATCH_LIMIT: i64 = 500;
+
+/// Claims older than this belong to a scheduler that stopped, and are picked up again
+const CLAIM_TIMEOUT_SECONDS: f64 = 600.0;
+
+/// Per-channel delivery counts read back for metrics
+#[derive(Debug, FromRow)]
+struct ChannelDeliveryCounts {
+    channel: String,
+    sent: i64,
+    delivered: i64,
+    failed: i64,
+}
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NotificationService {
     db: PgPool,
-    email_service: Option<EmailService>,
-    push_service: Option<PushNotificationService>,
+    email_service: Option<EmailNotificationService>,
+    push_service: Option<WebPushService>,
 }
 
 impl NotificationService {
     pub fn new(db: PgPool) -> Self {
+        let push_service = WebPushService::from_env(db.clone());
+        if push_service.is_none() {
+            tracing::info!("Web Push notifications disabled (VAPID keys not configured)");
+        }
+
         Self {
             db,
-            email_service: Some(EmailService::new()),
-            push_service: Some(PushNotificationService::new()),
+            email_service: Some(EmailNotificationService::new(SmtpConfig::default())),
+            push_service,
         }
     }
 
-    /// Create a new notification
+    /// Web Push delivery, if VAPID keys are configured
+    pub fn push_service(&self) -> Option<&WebPushService> {
+        self.push_service.as_ref()
+    }
+
+    /// Create a new notification. It is stored for delivery at its scheduled time, pushed back
+    /// past the user's quiet hours, or added to their open digest when batching is on.
     pub async fn create_notification(
         &self,
         request: CreateNotificationRequest,
@@ -48,72 +105,190 @@ impl NotificationService {
             return Err(NotificationError::AllChannelsDisabled);
         }
 
-        let notification = Notification {
-            id: Uuid::new_v4(),
-            user_id: request.user_id,
-            notification_type: request.notification_type.clone(),
-            category: self.get_category_for_type(&request.notification_type),
-            priority: self.get_priority_for_type(&request.notification_type),
-            title: request.title,
-            message: request.message,
-            data: request.data,
+        let category = self.get_category_for_type(&request.notification_type);
+        let priority = self.get_priority_for_type(&request.notification_type);
+        let plan = self.delivery_schedule(&preferences).plan(
+            &request.notification_type,
+            &priority,
+            &filtered_channels,
             scheduled_at,
-            sent_at: None,
-            read_at: None,
-            delivery_channels: filtered_channels,
-            delivery_status: DeliveryStatus::Scheduled,
-            expires_at: request.expires_at,
-            created_at: now,
-            updated_at: now,
+        );
+
+        let mut tx = self.db.begin().await?;
+
+        let (deliver_at, batch_id) = match plan {
+            DeliveryPlan::At(deliver_at) => (deliver_at, None),
+            DeliveryPlan::Digest { batch_type, send_at } => {
+                let digest_channels: Vec<DeliveryChannel> = filtered_channels.iter()
+                    .copied()
+                    .filter(|channel| matches!(channel, DeliveryChannel::Email | DeliveryChannel::InApp))
+                    .collect();
+
+                // Join the open digest, or open one due at `send_at`
+                let (batch_id, batch_send_at): (Uuid, DateTime<Utc>) = sqlx::query_as(
+                    r#"
+                    INSERT INTO notification_batches (user_id, batch_type, title, scheduled_at, delivery_channels)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (user_id) WHERE claimed_at IS NULL DO UPDATE SET
+                        delivery_channels = ARRAY(
+                            SELECT DISTINCT unnest(notification_batches.delivery_channels || EXCLUDED.delivery_channels)
+                        )
+                    RETURNING id, scheduled_at
+                    "#,
+                )
+                .bind(request.user_id)
+                .bind(batch_type)
+                .bind(digest_title(batch_type))
+                .bind(send_at)
+                .bind(&digest_channels)
+                .fetch_one(&mut *tx)
+                .await?;
+
+                (batch_send_at, Some(batch_id))
+            },
         };
 
-        // Store in database (mock implementation)
-        // In real implementation, this would use sqlx to insert into notifications table
-        tracing::info!("Created notification {} for user {}", notification.id, notification.user_id);
+        let notification = sqlx::query_as::<_, Notification>(&format!(
+            r#"
+            INSERT INTO notifications
+                (user_id, notification_type, category, priority, title, message, data,
+                 scheduled_at, delivery_channels, batch_id, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING {}
+            "#,
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(request.user_id)
+        .bind(&request.notification_type)
+        .bind(&category)
+        .bind(&priority)
+        .bind(&request.title)
+        .bind(&request.message)
+        .bind(&request.data)
+        .bind(deliver_at)
+        .bind(&filtered_channels)
+        .bind(batch_id)
+        .bind(request.expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        match batch_id {
+            Some(batch_id) => tracing::info!(
+                "Created notification {} for user {} in digest {} due at {}",
+                notification.id, notification.user_id, batch_id, deliver_at
+            ),
+            None if deliver_at > scheduled_at => tracing::info!(
+                "Created notification {} for user {}, deferred past quiet hours to {}",
+                notification.id, notification.user_id, deliver_at
+            ),
+            None => tracing::info!("Created notification {} for user {}", notification.id, notification.user_id),
+        }
 
         Ok(notification)
     }
 
-    /// Schedule notifications based on user's training plan and preferences
-    pub async fn schedule_training_reminders(&self, user_id: Uuid) -> Result<Vec<Notification>, NotificationError> {
-        let preferences = self.get_user_preferences(user_id).await?;
-        if !preferences.workout_reminders {
-            return Ok(vec![]);
-        }
-
-        let mut notifications = vec![];
+    /// The user's quiet hours and digest settings; invalid preferences fall back to sending
+    /// immediately rather than holding notifications back
+    fn delivery_schedule(&self, preferences: &NotificationPreferences) -> NotificationSchedule {
+        NotificationSchedule::from_preferences(preferences).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring notification timing preferences for user {}: {}", preferences.user_id, e);
+            NotificationSchedule::default()
+        })
+    }
 
-        // Mock: Get upcoming workouts from training plan
-        let upcoming_workouts = self.get_upcoming_workouts(user_id).await?;
+    /// Delivery timing of a user's notifications, including the timezone their days run in
+    pub async fn user_schedule(&self, user_id: Uuid) -> Result<NotificationSchedule, NotificationError> {
+        let preferences = self.get_user_preferences(user_id).await?;
+        Ok(self.delivery_schedule(&preferences))
+    }
 
-        for workout in upcoming_workouts {
-            let reminder_time = workout.scheduled_at - Duration::minutes(preferences.workout_reminder_advance_minutes as i64);
+    /// Workouts planned for tomorrow, in the user's timezone, grouped by plan. Only active
+    /// plans count.
+    pub async fn get_upcoming_workouts(&self, user_id: Uuid) -> Result<Vec<UpcomingWorkouts>, NotificationError> {
+        let schedule = self.user_schedule(user_id).await?;
+        let tomorrow = Utc::now().with_timezone(&schedule.timezone).date_naive() + Duration::days(1);
+
+        let plans = sqlx::query_as::<_, (Uuid, String, NaiveDate, serde_json::Value)>(
+            r#"
+            SELECT id, plan_name, start_date, plan_structure
+            FROM generated_plans
+            WHERE user_id = $1 AND status = 'active' AND start_date <= $2 AND end_date >= $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(tomorrow)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut upcoming = vec![];
+        for (plan_id, plan_name, start_date, plan_structure) in plans {
+            let weeks = match serde_json::from_value::<Vec<PlanWeekStructure>>(plan_structure) {
+                Ok(weeks) => weeks,
+                Err(e) => {
+                    tracing::warn!("Skipping workouts of plan {}: {}", plan_id, e);
+                    continue;
+                }
+            };
 
-            // Check if reminder time is not in quiet hours
-            if self.is_in_quiet_hours(&reminder_time, &preferences) {
-                continue;
+            let workouts = workouts_on(&weeks, start_date, tomorrow);
+            if !workouts.is_empty() {
+                upcoming.push(UpcomingWorkouts { plan_id, plan_name, date: tomorrow, workouts });
             }
+        }
 
-            let notification = self.create_notification(CreateNotificationRequest {
-                user_id,
-                notification_type: NotificationType::WorkoutReminder,
-                title: format!("Workout Reminder: {}", workout.name),
-                message: format!("Your {} workout is scheduled in {} minutes",
-                    workout.workout_type, preferences.workout_reminder_advance_minutes),
-                data: Some(json!({
-                    "workout_id": workout.id,
-                    "workout_type": workout.workout_type,
-                    "duration_minutes": workout.duration_minutes
-                })),
-                scheduled_at: Some(reminder_time),
-                delivery_channels: vec![DeliveryChannel::InApp, DeliveryChannel::Email],
-                expires_at: Some(workout.scheduled_at + Duration::hours(1)),
-            }).await?;
-
-            notifications.push(notification);
+        Ok(upcoming)
+    }
+
+    /// Remind the user of a plan's workouts for a day, `workout_reminder_advance_minutes`
+    /// before the planned workout time
+    pub async fn create_workout_reminder(
+        &self,
+        user_id: Uuid,
+        upcoming: &UpcomingWorkouts,
+    ) -> Result<Notification, NotificationError> {
+        let preferences = self.get_user_preferences(user_id).await?;
+        if !preferences.workout_reminders {
+            return Err(NotificationError::NotificationDisabled);
         }
 
-        Ok(notifications)
+        let schedule = self.delivery_schedule(&preferences);
+        let workout_time = NaiveTime::from_hms_opt(PLANNED_WORKOUT_HOUR, 0, 0).unwrap();
+        let starts_at = schedule.resolve_local(upcoming.date.and_time(workout_time));
+        let day_ends_at = schedule.resolve_local((upcoming.date + Duration::days(1)).and_time(NaiveTime::MIN));
+
+        let summaries: Vec<String> = upcoming.workouts.iter()
+            .map(|workout| format!("{} ({} min)", workout_type_label(&workout.workout_type), workout.duration_minutes))
+            .collect();
+        let title = match upcoming.workouts.as_slice() {
+            [workout] => format!("Workout Reminder: {}", workout_type_label(&workout.workout_type)),
+            workouts => format!("Workout Reminder: {} workouts", workouts.len()),
+        };
+
+        self.create_notification(CreateNotificationRequest {
+            user_id,
+            notification_type: NotificationType::WorkoutReminder,
+            title,
+            message: format!(
+                "Planned for {} in {}: {}",
+                upcoming.date.format("%A"),
+                upcoming.plan_name,
+                summaries.join(", ")
+            ),
+            data: Some(json!({
+                "plan_id": upcoming.plan_id,
+                "date": upcoming.date,
+                "workouts": upcoming.workouts.iter().map(|workout| json!({
+                    "workout_type": workout_type_label(&workout.workout_type),
+                    "duration_minutes": workout.duration_minutes,
+                    "description": workout.workout_description
+                })).collect::<Vec<_>>()
+            })),
+            scheduled_at: Some(starts_at - Duration::minutes(preferences.workout_reminder_advance_minutes as i64)),
+            delivery_channels: vec![DeliveryChannel::InApp, DeliveryChannel::Email],
+            expires_at: Some(day_ends_at),
+        }).await
     }
 
     /// Create performance improvement notification
@@ -158,6 +333,9 @@ impl NotificationService {
             },
         };
 
+        let template = self.get_template(&notification_type).await?;
+        let (title, message) = template_text(template.as_ref(), &data).unwrap_or((title, message));
+
         self.create_notification(CreateNotificationRequest {
             user_id,
             notification_type,
@@ -165,7 +343,7 @@ impl NotificationService {
             message,
             data: Some(data),
             scheduled_at: Some(Utc::now()),
-            delivery_channels: vec![DeliveryChannel::InApp, DeliveryChannel::Email],
+            delivery_channels: template_channels(template, &[DeliveryChannel::InApp, DeliveryChannel::Email]),
             expires_at: Some(Utc::now() + Duration::days(7)),
         }).await
     }
@@ -215,6 +393,9 @@ impl NotificationService {
             },
         };
 
+        let template = self.get_template(&notification_type).await?;
+        let (title, message) = template_text(template.as_ref(), &data).unwrap_or((title, message));
+
         let mut notification = self.create_notification(CreateNotificationRequest {
             user_id,
             notification_type,
@@ -222,7 +403,10 @@ impl NotificationService {
             message,
             data: Some(data),
             scheduled_at: Some(Utc::now()),
-            delivery_channels: vec![DeliveryChannel::InApp, DeliveryChannel::Email, DeliveryChannel::WebPush],
+            delivery_channels: template_channels(
+                template,
+                &[DeliveryChannel::InApp, DeliveryChannel::Email, DeliveryChannel::WebPush],
+            ),
             expires_at: Some(Utc::now() + Duration::days(3)),
         }).await?;
 
@@ -272,6 +456,9 @@ impl NotificationService {
             },
         };
 
+        let template = self.get_template(&notification_type).await?;
+        let (title, message) = template_text(template.as_ref(), &data).unwrap_or((title, message));
+
         self.create_notification(CreateNotificationRequest {
             user_id,
             notification_type,
@@ -279,22 +466,130 @@ impl NotificationService {
             message,
             data: Some(data),
             scheduled_at: Some(Utc::now()),
-            delivery_channels: vec![DeliveryChannel::InApp, DeliveryChannel::Email],
+            delivery_channels: template_channels(template, &[DeliveryChannel::InApp, DeliveryChannel::Email]),
             expires_at: Some(Utc::now() + Duration::days(30)),
         }).await
     }
 
+    /// The active template for a notification type, if one is set up
+    async fn get_template(&self, notification_type: &NotificationType) -> Result<Option<NotificationTemplate>, NotificationError> {
+        let template = sqlx::query_as::<_, NotificationTemplate>(&format!(
+            "SELECT {} FROM notification_templates WHERE notification_type = $1 AND is_active",
+            TEMPLATE_COLUMNS
+        ))
+        .bind(notification_type)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(template)
+    }
+
+    /// The user's sent notifications, newest first, with the total matching the filter and
+    /// the overall unread count
+    pub async fn list_notifications(
+        &self,
+        user_id: Uuid,
+        filter: &NotificationFilter,
+    ) -> Result<(Vec<Notification>, i64, i64), NotificationError> {
+        let notifications = sqlx::query_as::<_, Notification>(&format!(
+            r#"
+            SELECT {} FROM notifications
+            WHERE user_id = $1 AND sent_at IS NOT NULL
+              AND ($2::TEXT IS NULL OR category::TEXT = $2)
+              AND ($3::TEXT IS NULL OR delivery_status::TEXT = $3)
+              AND (NOT $4 OR read_at IS NULL)
+            ORDER BY sent_at DESC, id
+            LIMIT $5 OFFSET $6
+            "#,
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(filter.category.as_deref())
+        .bind(filter.status.as_deref())
+        .bind(filter.unread_only)
+        .bind(filter.limit)
+        .bind(filter.offset)
+        .fetch_all(&self.db)
+        .await?;
+
+        let (total_count, unread_count): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT
+                COUNT(*) FILTER (
+                    WHERE ($2::TEXT IS NULL OR category::TEXT = $2)
+                      AND ($3::TEXT IS NULL OR delivery_status::TEXT = $3)
+                      AND (NOT $4 OR read_at IS NULL)
+                ),
+                COUNT(*) FILTER (WHERE read_at IS NULL)
+            FROM notifications
+            WHERE user_id = $1 AND sent_at IS NOT NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(filter.category.as_deref())
+        .bind(filter.status.as_deref())
+        .bind(filter.unread_only)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok((notifications, total_count, unread_count))
+    }
+
+    /// One of the user's notifications
+    pub async fn get_notification(&self, user_id: Uuid, notification_id: Uuid) -> Result<Option<Notification>, NotificationError> {
+        let notification = sqlx::query_as::<_, Notification>(&format!(
+            "SELECT {} FROM notifications WHERE id = $1 AND user_id = $2",
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(notification_id)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(notification)
+    }
+
+    /// Mark the user's notifications as read; returns how many were unread
+    pub async fn mark_as_read(&self, user_id: Uuid, notification_ids: &[Uuid]) -> Result<u64, NotificationError> {
+        let result = sqlx::query(
+            "UPDATE notifications SET read_at = NOW() WHERE user_id = $1 AND id = ANY($2) AND read_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(notification_ids)
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete one of the user's notifications; returns whether it existed
+    pub async fn delete_notification(&self, user_id: Uuid, notification_id: Uuid) -> Result<bool, NotificationError> {
+        let result = sqlx::query("DELETE FROM notifications WHERE id = $1 AND user_id = $2")
+            .bind(notification_id)
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Send scheduled notifications
     pub async fn send_scheduled_notifications(&self) -> Result<u32, NotificationError> {
         let now = Utc::now();
-        let notifications = self.get_scheduled_notifications(now).await?;
+        self.cancel_expired_notifications(now).await?;
+
+        let notifications = self.claim_due_notifications(now).await?;
         let mut sent_count = 0;
 
         for notification in notifications {
             match self.send_notification(&notification).await {
-                Ok(_) => {
+                Ok(DeliveryStatus::Failed) => {
+                    tracing::error!("Failed to deliver notification {} on any channel", notification.id);
+                    self.mark_notification_failed(notification.id).await?;
+                },
+                Ok(status) => {
                     sent_count += 1;
-                    self.mark_notification_sent(notification.id, now).await?;
+                    self.mark_notification_sent(notification.id, status, now).await?;
                 },
                 Err(e) => {
                     tracing::error!("Failed to send notification {}: {}", notification.id, e);
@@ -306,40 +601,421 @@ impl NotificationService {
         Ok(sent_count)
     }
 
-    /// Send a single notification through all its delivery channels
-    async fn send_notification(&self, notification: &Notification) -> Result<(), NotificationError> {
+    /// Send digests that are due: one email listing the batched notifications, and the
+    /// notifications themselves in-app
+    pub async fn send_due_batches(&self) -> Result<u32, NotificationError> {
+        let now = Utc::now();
+        let mut sent_count = 0;
+
+        self.release_stale_batches(now).await?;
+
+        for batch in self.claim_due_batches(now).await? {
+            let notifications = sqlx::query_as::<_, Notification>(&format!(
+                r#"
+                SELECT {} FROM notifications
+                WHERE id = ANY($1) AND (expires_at IS NULL OR expires_at > $2)
+                ORDER BY scheduled_at, created_at
+                "#,
+                NOTIFICATION_COLUMNS
+            ))
+            .bind(&batch.notification_ids)
+            .bind(now)
+            .fetch_all(&self.db)
+            .await?;
+
+            if notifications.is_empty() {
+                sqlx::query("UPDATE notification_batches SET sent_at = $2 WHERE id = $1")
+                    .bind(batch.id)
+                    .bind(now)
+                    .execute(&self.db)
+                    .await?;
+                continue;
+            }
+
+            match self.send_batch(&batch, &notifications).await {
+                Ok(()) => sent_count += 1,
+                Err(e) => {
+                    tracing::error!("Failed to send digest {} for user {}: {}", batch.id, batch.user_id, e);
+                    self.release_batch(batch.id).await?;
+                }
+            }
+        }
+
+        Ok(sent_count)
+    }
+
+    /// Claim due digests, so each is sent by one scheduler even with several replicas running.
+    /// A claimed digest is closed: new notifications open the next one.
+    async fn claim_due_batches(&self, now: DateTime<Utc>) -> Result<Vec<NotificationBatch>, NotificationError> {
+        let batches = sqlx::query_as::<_, NotificationBatch>(&format!(
+            r#"
+            UPDATE notification_batches SET claimed_at = $1
+            WHERE id IN (
+                SELECT id FROM notification_batches
+                WHERE claimed_at IS NULL AND scheduled_at <= $1
+                ORDER BY scheduled_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {},
+                ARRAY(
+                    SELECT n.id FROM notifications n
+                    WHERE n.batch_id = notification_batches.id AND n.delivery_status = 'scheduled'
+                ) AS notification_ids
+            "#,
+            BATCH_COLUMNS
+        ))
+        .bind(now)
+        .bind(SEND_BATCH_LIMIT)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(batches)
+    }
+
+    /// Send the unsent notifications of a digest that failed on their own instead
+    async fn release_batch(&self, batch_id: Uuid) -> Result<(), NotificationError> {
+        let result = sqlx::query(
+            "UPDATE notifications SET batch_id = NULL WHERE batch_id = $1 AND delivery_status = 'scheduled'",
+        )
+        .bind(batch_id)
+        .execute(&self.db)
+        .await?;
+
+        tracing::warn!("Released {} notifications from unsent digest {}", result.rows_affected(), batch_id);
+        Ok(())
+    }
+
+    /// Release the notifications of digests whose scheduler stopped before sending them
+    async fn release_stale_batches(&self, now: DateTime<Utc>) -> Result<(), NotificationError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE notifications SET batch_id = NULL
+            WHERE delivery_status = 'scheduled' AND batch_id IN (
+                SELECT id FROM notification_batches
+                WHERE sent_at IS NULL AND claimed_at < $1 - make_interval(secs => $2)
+            )
+            "#,
+        )
+        .bind(now)
+        .bind(CLAIM_TIMEOUT_SECONDS)
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            tracing::warn!("Released {} notifications from stale digests", result.rows_affected());
+        }
+        Ok(())
+    }
+
+    async fn send_batch(&self, batch: &NotificationBatch, notifications: &[Notification]) -> Result<(), NotificationError> {
+        let mut statuses: HashMap<Uuid, Vec<DeliveryStatus>> = HashMap::new();
+
+        if batch.delivery_channels.contains(&DeliveryChannel::Email) {
+            let email_notifications: Vec<Notification> = notifications.iter()
+                .filter(|notification| notification.delivery_channels.contains(&DeliveryChannel::Email))
+                .cloned()
+                .collect();
+
+            if !email_notifications.is_empty() {
+                let (status, error) = match self.send_digest_email(batch.user_id, &email_notifications).await {
+                    Ok(()) => (DeliveryStatus::Delivered, None),
+                    Err(e) => (DeliveryStatus::Failed, Some(e.to_string())),
+                };
+
+                for notification in &email_notifications {
+                    self.record_delivery(notification.id, notification.user_id, DeliveryChannel::Email, status, error.as_deref())
+                        .await?;
+                    statuses.entry(notification.id).or_default().push(status);
+                }
+            }
+        }
+
+        if batch.delivery_channels.contains(&DeliveryChannel::InApp) {
+            for notification in notifications.iter().filter(|n| n.delivery_channels.contains(&DeliveryChannel::InApp)) {
+                self.record_delivery(notification.id, notification.user_id, DeliveryChannel::InApp, DeliveryStatus::Delivered, None)
+                    .await?;
+                statuses.entry(notification.id).or_default().push(DeliveryStatus::Delivered);
+            }
+        }
+
+        let now = Utc::now();
+        for notification in notifications {
+            let status = overall_delivery_status(statuses.get(&notification.id).map_or(&[], |s| s.as_slice()));
+            match status {
+                DeliveryStatus::Failed => self.mark_notification_failed(notification.id).await?,
+                status => self.mark_notification_sent(notification.id, status, now).await?,
+            }
+        }
+
+        let (title, summary) = digest_summary(batch.batch_type, notifications);
+        sqlx::query("UPDATE notification_batches SET title = $2, summary = $3, sent_at = $4 WHERE id = $1")
+            .bind(batch.id)
+            .bind(title)
+            .bind(summary)
+            .bind(now)
+            .execute(&self.db)
+            .await?;
+
+        tracing::info!("Sent digest {} with {} notifications to user {}", batch.id, notifications.len(), batch.user_id);
+        Ok(())
+    }
+
+    async fn send_digest_email(&self, user_id: Uuid, notifications: &[Notification]) -> Result<(), NotificationError> {
+        let email_service = self.email_service.as_ref()
+            .ok_or_else(|| NotificationError::EmailService("Email is not configured".to_string()))?;
+        let (email, name) = self.get_user_contact(user_id).await?;
+        let preferences = self.get_user_preferences(user_id).await?;
+
+        email_service.send_batch_email(notifications, &email, &name, &preferences).await
+            .map_err(|e| NotificationError::EmailService(e.to_string()))
+    }
+
+    /// Email a single notification, using the generic digest layout for types without a template
+    async fn email_notification(&self, email_service: &EmailNotificationService, notification: &Notification) -> Result<(), NotificationError> {
+        let (email, name) = self.get_user_contact(notification.user_id).await?;
+        let preferences = self.get_user_preferences(notification.user_id).await?;
+
+        let result = match email_service.send_notification_email(notification, &email, &name, &preferences).await {
+            Err(EmailError::TemplateNotFound) => {
+                email_service.send_batch_email(std::slice::from_ref(notification), &email, &name, &preferences).await
+            },
+            result => result,
+        };
+
+        result.map_err(|e| NotificationError::EmailService(e.to_string()))
+    }
+
+    /// Email address and a display name for greetings
+    async fn get_user_contact(&self, user_id: Uuid) -> Result<(String, String), NotificationError> {
+        let email: String = sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&self.db)
+            .await?;
+        let name = email.split('@').next().unwrap_or_default().to_string();
+
+        Ok((email, name))
+    }
+
+    /// Send a single notification through all its delivery channels, recording the outcome of
+    /// each. The notification only counts as failed if no channel delivered it.
+    pub async fn send_notification(&self, notification: &Notification) -> Result<DeliveryStatus, NotificationError> {
+        let mut statuses = Vec::with_capacity(notification.delivery_channels.len());
+
         for channel in &notification.delivery_channels {
-            match channel {
-                DeliveryChannel::Email => {
-                    if let Some(ref email_service) = self.email_service {
-                        email_service.send_email_notification(notification).await?;
-                    }
+            let (status, error) = match channel {
+                DeliveryChannel::Email => match self.email_service {
+                    Some(ref email_service) => match self.email_notification(email_service, notification).await {
+                        Ok(()) => (DeliveryStatus::Delivered, None),
+                        Err(e) => (DeliveryStatus::Failed, Some(e.to_string())),
+                    },
+                    None => (DeliveryStatus::Cancelled, Some("Email is not configured".to_string())),
                 },
-                DeliveryChannel::WebPush => {
-                    if let Some(ref push_service) = self.push_service {
-                        push_service.send_push_notification(notification).await?;
-                    }
+                DeliveryChannel::WebPush => match self.push_service {
+                    Some(ref push_service) => match push_service.send_notification(notification).await {
+                        Ok(result) => (result.status(), result.error_message()),
+                        Err(e) => (DeliveryStatus::Failed, Some(format!("{:#}", e))),
+                    },
+                    None => (DeliveryStatus::Cancelled, Some("Web Push is not configured".to_string())),
                 },
                 DeliveryChannel::InApp => {
                     // In-app notifications are stored in database and displayed in UI
                     // No external service needed
+                    (DeliveryStatus::Delivered, None)
                 },
                 DeliveryChannel::Sms => {
                     // Future implementation
                     tracing::warn!("SMS notifications not yet implemented");
+                    (DeliveryStatus::Cancelled, Some("SMS is not supported".to_string()))
                 },
-            }
+            };
+
+            self.record_delivery(notification.id, notification.user_id, *channel, status, error.as_deref())
+                .await?;
+            statuses.push(status);
         }
+
+        Ok(overall_delivery_status(&statuses))
+    }
+
+    /// Push a one-off message to all of the user's devices, outside the notification feed
+    pub async fn send_push_notification(
+        &self,
+        user_id: Uuid,
+        title: String,
+        message: String,
+    ) -> Result<(), NotificationError> {
+        let push_service = self.push_service.as_ref()
+            .ok_or_else(|| NotificationError::PushService("Web Push is not configured".to_string()))?;
+
+        let delivery_id = Uuid::new_v4();
+        let message = PushMessage {
+            title,
+            body: message,
+            tag: Some(delivery_id.to_string()),
+            data: None,
+            urgency: PushUrgency::High,
+            ttl_seconds: 24 * 60 * 60,
+        };
+
+        let result = push_service.send_message(user_id, &message).await
+            .map_err(|e| NotificationError::PushService(format!("{:#}", e)))?;
+        self.record_delivery(delivery_id, user_id, DeliveryChannel::WebPush, result.status(), result.error_message().as_deref())
+            .await?;
+
+        match result.status() {
+            DeliveryStatus::Failed => Err(NotificationError::PushService(result.error_message().unwrap_or_default())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Email a one-off message to the user, outside the notification feed
+    pub async fn send_email_notification(
+        &self,
+        user_id: Uuid,
+        title: String,
+        message: String,
+    ) -> Result<(), NotificationError> {
+        let email_service = self.email_service.as_ref()
+            .ok_or_else(|| NotificationError::EmailService("Email is not configured".to_string()))?;
+        let (email, name) = self.get_user_contact(user_id).await?;
+
+        let delivery_id = Uuid::new_v4();
+        let result = email_service.send_message_email(&email, &name, &title, &message).await
+            .map_err(|e| NotificationError::EmailService(e.to_string()));
+        let (status, error) = match &result {
+            Ok(()) => (DeliveryStatus::Delivered, None),
+            Err(e) => (DeliveryStatus::Failed, Some(e.to_string())),
+        };
+        self.record_delivery(delivery_id, user_id, DeliveryChannel::Email, status, error.as_deref())
+            .await?;
+
+        result
+    }
+
+    /// Record how a notification fared on one channel; re-sending overwrites the earlier attempt
+    async fn record_delivery(
+        &self,
+        notification_id: Uuid,
+        user_id: Uuid,
+        channel: DeliveryChannel,
+        status: DeliveryStatus,
+        error_message: Option<&str>,
+    ) -> Result<(), NotificationError> {
+        sqlx::query(
+            r#"
+            INSERT INTO notification_deliveries (notification_id, user_id, channel, status, error_message)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (notification_id, channel) DO UPDATE SET
+                status = EXCLUDED.status,
+                error_message = EXCLUDED.error_message,
+                attempted_at = NOW()
+            "#,
+        )
+        .bind(notification_id)
+        .bind(user_id)
+        .bind(channel.as_str())
+        .bind(status.as_str())
+        .bind(error_message)
+        .execute(&self.db)
+        .await?;
+
         Ok(())
     }
 
-    /// Get user notification preferences
+    /// Delivery metrics for a user over a period, from the recorded per-channel outcomes.
+    /// Channels with nowhere to deliver (cancelled) are left out of the counts.
+    pub async fn get_notification_metrics(
+        &self,
+        user_id: Uuid,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<NotificationMetrics, NotificationError> {
+        let channel_counts = sqlx::query_as::<_, ChannelDeliveryCounts>(
+            r#"
+            SELECT channel,
+                   COUNT(*) FILTER (WHERE status <> 'cancelled') AS sent,
+                   COUNT(*) FILTER (WHERE status = 'delivered') AS delivered,
+                   COUNT(*) FILTER (WHERE status = 'failed') AS failed
+            FROM notification_deliveries
+            WHERE user_id = $1 AND attempted_at >= $2 AND attempted_at < $3
+            GROUP BY channel
+            "#,
+        )
+        .bind(user_id)
+        .bind(period_start)
+        .bind(period_end)
+        .fetch_all(&self.db)
+        .await?;
+
+        let (total_sent, total_delivered): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(DISTINCT notification_id) FILTER (WHERE status <> 'cancelled'),
+                   COUNT(DISTINCT notification_id) FILTER (WHERE status = 'delivered')
+            FROM notification_deliveries
+            WHERE user_id = $1 AND attempted_at >= $2 AND attempted_at < $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(period_start)
+        .bind(period_end)
+        .fetch_one(&self.db)
+        .await?;
+
+        let total_read: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND read_at >= $2 AND read_at < $3",
+        )
+        .bind(user_id)
+        .bind(period_start)
+        .bind(period_end)
+        .fetch_one(&self.db)
+        .await?;
+
+        let mut by_channel = HashMap::new();
+        for counts in channel_counts {
+            let channel = match counts.channel.parse::<DeliveryChannel>() {
+                Ok(channel) => channel,
+                Err(e) => {
+                    tracing::warn!("Skipping delivery metrics: {}", e);
+                    continue;
+                }
+            };
+            by_channel.insert(channel, ChannelMetrics {
+                sent: counts.sent,
+                delivered: counts.delivered,
+                failed: counts.failed,
+                delivery_rate: percentage(counts.delivered, counts.sent),
+            });
+        }
+
+        Ok(NotificationMetrics {
+            user_id: Some(user_id),
+            period_start,
+            period_end,
+            total_sent,
+            total_delivered,
+            total_read,
+            delivery_rate: percentage(total_delivered, total_sent),
+            read_rate: percentage(total_read, total_delivered),
+            by_type: HashMap::new(),
+            by_channel,
+        })
+    }
+
+    /// Get user notification preferences; users who never changed them get the defaults
     pub async fn get_user_preferences(&self, user_id: Uuid) -> Result<NotificationPreferences, NotificationError> {
-        // Mock implementation - in real code, this would query the database
-        Ok(NotificationPreferences {
+        let preferences = sqlx::query_as::<_, NotificationPreferences>(&format!(
+            "SELECT {} FROM notification_preferences WHERE user_id = $1",
+            PREFERENCE_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(preferences.unwrap_or_else(|| NotificationPreferences {
             user_id,
             ..Default::default()
-        })
+        }))
     }
 
     /// Update user notification preferences
@@ -415,9 +1091,74 @@ impl NotificationService {
             preferences.batch_interval_minutes = batch_interval_minutes;
         }
 
-        preferences.updated_at = Utc::now();
+        if preferences.batch_interval_minutes < 1 {
+            return Err(NotificationError::InvalidData("batch_interval_minutes must be at least 1".to_string()));
+        }
+        NotificationSchedule::from_preferences(&preferences).map_err(NotificationError::InvalidData)?;
+
+        let preferences = sqlx::query_as::<_, NotificationPreferences>(&format!(
+            r#"
+            INSERT INTO notification_preferences (
+                user_id, workout_reminders, workout_reminder_advance_minutes, rest_day_reminders,
+                ftp_test_reminders, fitness_improvement_alerts, goal_achievement_alerts,
+                performance_decline_alerts, overtraining_risk_alerts, negative_tsb_alerts,
+                injury_risk_alerts, weekly_progress_summaries, achievement_badge_alerts,
+                training_streak_alerts, email_enabled, web_push_enabled, in_app_enabled,
+                quiet_hours_start, quiet_hours_end, timezone, batch_notifications,
+                batch_interval_minutes
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22)
+            ON CONFLICT (user_id) DO UPDATE SET
+                workout_reminders = EXCLUDED.workout_reminders,
+                workout_reminder_advance_minutes = EXCLUDED.workout_reminder_advance_minutes,
+                rest_day_reminders = EXCLUDED.rest_day_reminders,
+                ftp_test_reminders = EXCLUDED.ftp_test_reminders,
+                fitness_improvement_alerts = EXCLUDED.fitness_improvement_alerts,
+                goal_achievement_alerts = EXCLUDED.goal_achievement_alerts,
+                performance_decline_alerts = EXCLUDED.performance_decline_alerts,
+                overtraining_risk_alerts = EXCLUDED.overtraining_risk_alerts,
+                negative_tsb_alerts = EXCLUDED.negative_tsb_alerts,
+                injury_risk_alerts = EXCLUDED.injury_risk_alerts,
+                weekly_progress_summaries = EXCLUDED.weekly_progress_summaries,
+                achievement_badge_alerts = EXCLUDED.achievement_badge_alerts,
+                training_streak_alerts = EXCLUDED.training_streak_alerts,
+                email_enabled = EXCLUDED.email_enabled,
+                web_push_enabled = EXCLUDED.web_push_enabled,
+                in_app_enabled = EXCLUDED.in_app_enabled,
+                quiet_hours_start = EXCLUDED.quiet_hours_start,
+                quiet_hours_end = EXCLUDED.quiet_hours_end,
+                timezone = EXCLUDED.timezone,
+                batch_notifications = EXCLUDED.batch_notifications,
+                batch_interval_minutes = EXCLUDED.batch_interval_minutes
+            RETURNING {}
+            "#,
+            PREFERENCE_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(preferences.workout_reminders)
+        .bind(preferences.workout_reminder_advance_minutes)
+        .bind(preferences.rest_day_reminders)
+        .bind(preferences.ftp_test_reminders)
+        .bind(preferences.fitness_improvement_alerts)
+        .bind(preferences.goal_achievement_alerts)
+        .bind(preferences.performance_decline_alerts)
+        .bind(preferences.overtraining_risk_alerts)
+        .bind(preferences.negative_tsb_alerts)
+        .bind(preferences.injury_risk_alerts)
+        .bind(preferences.weekly_progress_summaries)
+        .bind(preferences.achievement_badge_alerts)
+        .bind(preferences.training_streak_alerts)
+        .bind(preferences.email_enabled)
+        .bind(preferences.web_push_enabled)
+        .bind(preferences.in_app_enabled)
+        .bind(&preferences.quiet_hours_start)
+        .bind(&preferences.quiet_hours_end)
+        .bind(&preferences.timezone)
+        .bind(preferences.batch_notifications)
+        .bind(preferences.batch_interval_minutes)
+        .fetch_one(&self.db)
+        .await?;
 
-        // Mock: Save to database
         tracing::info!("Updated notification preferences for user {}", user_id);
 
         Ok(preferences)
@@ -429,7 +1170,8 @@ impl NotificationService {
         match notification_type {
             NotificationType::WorkoutReminder |
             NotificationType::RestDayReminder |
-            NotificationType::FtpTestSuggestion => NotificationCategory::Training,
+            NotificationType::FtpTestSuggestion |
+            NotificationType::TrainingAdjustment => NotificationCategory::Training,
 
             NotificationType::FitnessImprovement |
             NotificationType::GoalAchievement |
@@ -458,6 +1200,7 @@ impl NotificationService {
             NotificationType::SystemMaintenance => NotificationPriority::High,
 
             NotificationType::WorkoutReminder |
+            NotificationType::TrainingAdjustment |
             NotificationType::FitnessImprovement |
             NotificationType::GoalAchievement |
             NotificationType::PerformanceDecline => NotificationPriority::Medium,
@@ -483,42 +1226,74 @@ impl NotificationService {
             .collect()
     }
 
-    fn is_in_quiet_hours(&self, time: &DateTime<Utc>, preferences: &NotificationPreferences) -> bool {
-        // This is a simplified implementation
-        // In real code, you'd parse the timezone and quiet hours properly
-        let hour = time.hour();
-        let quiet_start: u32 = preferences.quiet_hours_start.split(':').next()
-            .and_then(|h| h.parse().ok()).unwrap_or(22);
-        let quiet_end: u32 = preferences.quiet_hours_end.split(':').next()
-            .and_then(|h| h.parse().ok()).unwrap_or(7);
+    /// Claim due notifications that are sent on their own rather than in a digest, so each is
+    /// sent by one scheduler even with several replicas running. Claims left behind by a
+    /// stopped scheduler are taken over.
+    async fn claim_due_notifications(&self, now: DateTime<Utc>) -> Result<Vec<Notification>, NotificationError> {
+        let notifications = sqlx::query_as::<_, Notification>(&format!(
+            r#"
+            UPDATE notifications SET delivery_status = 'sending', claimed_at = $1
+            WHERE id IN (
+                SELECT id FROM notifications
+                WHERE (delivery_status = 'scheduled' AND batch_id IS NULL AND scheduled_at <= $1)
+                   OR (delivery_status = 'sending' AND claimed_at < $1 - make_interval(secs => $3))
+                ORDER BY scheduled_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(now)
+        .bind(SEND_BATCH_LIMIT)
+        .bind(CLAIM_TIMEOUT_SECONDS)
+        .fetch_all(&self.db)
+        .await?;
 
-        if quiet_start < quiet_end {
-            hour >= quiet_start && hour < quiet_end
-        } else {
-            hour >= quiet_start || hour < quiet_end
-        }
+        Ok(notifications)
     }
 
-    // Mock helper methods (in real implementation, these would query the database)
-
-    async fn get_upcoming_workouts(&self, user_id: Uuid) -> Result<Vec<MockWorkout>, NotificationError> {
-        // Mock implementation
-        Ok(vec![])
+    /// Drop notifications that expired while deferred or waiting for a digest
+    async fn cancel_expired_notifications(&self, now: DateTime<Utc>) -> Result<(), NotificationError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE notifications SET delivery_status = 'cancelled'
+            WHERE delivery_status = 'scheduled' AND expires_at <= $1
+            "#,
+        )
+        .bind(now)
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            tracing::info!("Cancelled {} expired notifications", result.rows_affected());
+        }
+        Ok(())
     }
 
-    async fn get_scheduled_notifications(&self, before: DateTime<Utc>) -> Result<Vec<Notification>, NotificationError> {
-        // Mock implementation
-        Ok(vec![])
-    }
+    async fn mark_notification_sent(
+        &self,
+        notification_id: Uuid,
+        status: DeliveryStatus,
+        sent_at: DateTime<Utc>,
+    ) -> Result<(), NotificationError> {
+        sqlx::query("UPDATE notifications SET delivery_status = $2, sent_at = $3 WHERE id = $1")
+            .bind(notification_id)
+            .bind(status)
+            .bind(sent_at)
+            .execute(&self.db)
+            .await?;
 
-    async fn mark_notification_sent(&self, notification_id: Uuid, sent_at: DateTime<Utc>) -> Result<(), NotificationError> {
-        // Mock implementation
-        tracing::info!("Marked notification {} as sent at {}", notification_id, sent_at);
         Ok(())
     }
 
     async fn mark_notification_failed(&self, notification_id: Uuid) -> Result<(), NotificationError> {
-        // Mock implementation
+        sqlx::query("UPDATE notifications SET delivery_status = 'failed' WHERE id = $1")
+            .bind(notification_id)
+            .execute(&self.db)
+            .await?;
+
         tracing::error!("Marked notification {} as failed", notification_id);
         Ok(())
     }
@@ -547,42 +1322,105 @@ pub enum MotivationNotificationType {
     TrainingStreak,
 }
 
-#[derive(Debug)]
-struct MockWorkout {
-    id: Uuid,
-    name: String,
-    workout_type: String,
-    scheduled_at: DateTime<Utc>,
-    duration_minutes: u32,
+/// A plan's workouts on one day
+#[derive(Debug, Clone)]
+pub struct UpcomingWorkouts {
+    pub plan_id: Uuid,
+    pub plan_name: String,
+    pub date: NaiveDate,
+    pub workouts: Vec<WorkoutDay>,
 }
 
-#[derive(Debug)]
-struct EmailService;
+/// Every workout a plan schedules on `date`, in plan order
+pub fn workouts_on(weeks: &[PlanWeekStructure], start_date: NaiveDate, date: NaiveDate) -> Vec<WorkoutDay> {
+    weeks.iter()
+        .flat_map(|week| {
+            week.workout_days
+                .iter()
+                .filter(move |day| plan_day_date(start_date, week.week_number, day.day_of_week) == date)
+        })
+        .cloned()
+        .collect()
+}
 
-impl EmailService {
-    fn new() -> Self {
-        Self
+fn digest_title(batch_type: BatchType) -> &'static str {
+    match batch_type {
+        BatchType::Hourly => "Your hourly digest",
+        BatchType::Daily => "Your daily digest",
+        BatchType::Weekly => "Your weekly digest",
+        BatchType::Custom => "Your notification digest",
     }
+}
 
-    async fn send_email_notification(&self, notification: &Notification) -> Result<(), NotificationError> {
-        // Mock email sending
-        tracing::info!("Sending email notification {} to user {}", notification.id, notification.user_id);
-        Ok(())
+/// Fill `{{placeholders}}` from the notification data. Returns `None` if any are left
+/// unfilled, so callers can fall back to built-in text.
+fn render_template(template: &str, data: &serde_json::Value) -> Option<String> {
+    let mut rendered = template.to_string();
+
+    if let Some(fields) = data.as_object() {
+        for (key, value) in fields {
+            let text = match value {
+                serde_json::Value::String(text) => text.clone(),
+                serde_json::Value::Number(number) => match number.as_f64() {
+                    Some(n) if n.fract() != 0.0 => format!("{:.1}", n),
+                    _ => number.to_string(),
+                },
+                serde_json::Value::Bool(flag) => flag.to_string(),
+                _ => continue,
+            };
+            rendered = rendered.replace(&format!("{{{{{}}}}}", key), &text);
+        }
     }
+
+    (!rendered.contains("{{")).then_some(rendered)
 }
 
-#[derive(Debug)]
-struct PushNotificationService;
+/// Title and message rendered from an active template
+fn template_text(template: Option<&NotificationTemplate>, data: &serde_json::Value) -> Option<(String, String)> {
+    let template = template?;
+    Some((
+        render_template(&template.title_template, data)?,
+        render_template(&template.message_template, data)?,
+    ))
+}
+
+/// The template's channels, or the alert's defaults without one
+fn template_channels(template: Option<NotificationTemplate>, defaults: &[DeliveryChannel]) -> Vec<DeliveryChannel> {
+    match template {
+        Some(template) if !template.default_channels.is_empty() => template.default_channels,
+        _ => defaults.to_vec(),
+    }
+}
+
+/// Title and one-line summary of a sent digest
+fn digest_summary(batch_type: BatchType, notifications: &[Notification]) -> (String, String) {
+    let titles: Vec<&str> = notifications.iter().map(|n| n.title.as_str()).collect();
+    let summary = match titles.as_slice() {
+        [] => "No new notifications".to_string(),
+        [only] => only.to_string(),
+        [first, second] => format!("{} and {}", first, second),
+        [first, second, rest @ ..] => format!("{}, {} and {} more", first, second, rest.len()),
+    };
+
+    (digest_title(batch_type).to_string(), summary)
+}
 
-impl PushNotificationService {
-    fn new() -> Self {
-        Self
+/// Overall status of a notification from its per-channel outcomes
+fn overall_delivery_status(statuses: &[DeliveryStatus]) -> DeliveryStatus {
+    if statuses.contains(&DeliveryStatus::Delivered) {
+        DeliveryStatus::Delivered
+    } else if statuses.contains(&DeliveryStatus::Failed) {
+        DeliveryStatus::Failed
+    } else {
+        DeliveryStatus::Cancelled
     }
+}
 
-    async fn send_push_notification(&self, notification: &Notification) -> Result<(), NotificationError> {
-        // Mock push notification sending
-        tracing::info!("Sending push notification {} to user {}", notification.id, notification.user_id);
-        Ok(())
+fn percentage(part: i64, total: i64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        (part as f64 / total as f64 * 1000.0).round() / 10.0
     }
 }
 
@@ -600,4 +1438,88 @@ pub enum NotificationError {
     PushService(String),
     #[error("Invalid notification data: {0}")]
     InvalidData(String),
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overall_delivery_status() {
+        use DeliveryStatus::*;
+
+        assert_eq!(overall_delivery_status(&[Failed, Delivered]), Delivered);
+        assert_eq!(overall_delivery_status(&[Cancelled, Failed]), Failed);
+        assert_eq!(overall_delivery_status(&[Cancelled]), Cancelled);
+        assert_eq!(overall_delivery_status(&[]), Cancelled);
+    }
+
+    #[test]
+    fn test_render_template() {
+        let data = json!({
+            "goal_title": "Sub-3 marathon",
+            "improvement_percentage": 12.345,
+            "period_days": 28,
+            "nested": {"ignored": true}
+        });
+
+        assert_eq!(
+            render_template("Goal Achieved: {{goal_title}}", &data).as_deref(),
+            Some("Goal Achieved: Sub-3 marathon")
+        );
+        assert_eq!(
+            render_template("Up {{improvement_percentage}}% over {{period_days}} days", &data).as_deref(),
+            Some("Up 12.3% over 28 days")
+        );
+        // Unfilled placeholders fall back to the built-in text
+        assert_eq!(render_template("Now {{current_tsb}}", &data), None);
+        assert_eq!(render_template("No placeholders", &json!(null)).as_deref(), Some("No placeholders"));
+    }
+
+    #[test]
+    fn test_percentage() {
+        assert_eq!(percentage(42, 45), 93.3);
+        assert_eq!(percentage(0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_workouts_on_date() {
+        use crate::models::{IntensityZone, WorkoutType};
+
+        let workout = |day_of_week, workout_type| WorkoutDay {
+            day_of_week,
+            workout_type,
+            duration_minutes: 60,
+            intensity_zone: IntensityZone::Zone2,
+            workout_description: "Steady".to_string(),
+            power_targets: None,
+            heart_rate_targets: None,
+            pace_targets: None,
+            equipment_needed: vec![],
+            notes: None,
+        };
+        let week = |week_number| PlanWeekStructure {
+            week_number,
+            phase_name: "Base".to_string(),
+            weekly_volume: 6.0,
+            weekly_intensity: 0.7,
+            workout_days: vec![
+                workout(2, WorkoutType::Endurance),
+                workout(2, WorkoutType::Strength),
+                workout(4, WorkoutType::Recovery),
+            ],
+            rest_days: vec![1],
+            week_goals: vec![],
+            key_sessions: vec![],
+        };
+        let weeks = vec![week(1), week(2)];
+        let start = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(); // Monday
+
+        // Both workouts of a double day, from the second week only
+        let tuesday = workouts_on(&weeks, start, NaiveDate::from_ymd_opt(2026, 3, 10).unwrap());
+        let types: Vec<_> = tuesday.iter().map(|w| workout_type_label(&w.workout_type)).collect();
+        assert_eq!(types, vec!["Endurance", "Strength"]);
+
+        assert!(workouts_on(&weeks, start, NaiveDate::from_ymd_opt(2026, 3, 9).unwrap()).is_empty());
+    }
+}
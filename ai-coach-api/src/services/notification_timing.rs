@@ -0,0 +1,407 @@
+/// Notification Timing
+///
+/// Decides when a notification may go out for a user:
+/// - Quiet hours evaluated in the user's IANA timezone, including windows that cross midnight
+/// - Deferral to the end of quiet hours, resolving DST gaps and repeated hours
+/// - Hourly, daily and weekly digest schedules for batched notifications
+///
+/// Everything works on wall-clock time in the user's timezone and converts back to UTC last,
+/// so a 07:00 quiet-hours end stays 07:00 on both sides of a DST change.
+
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc,
+};
+use chrono_tz::Tz;
+
+use crate::models::{
+    BatchType, DeliveryChannel, NotificationPreferences, NotificationPriority, NotificationType,
+};
+
+/// Daily and weekly digests go out when quiet hours end, or at this time without quiet hours
+const DEFAULT_DIGEST_HOUR: u32 = 8;
+
+/// Parse an IANA timezone name such as `Europe/Berlin`
+pub fn parse_timezone(timezone: &str) -> Result<Tz, String> {
+    timezone
+        .parse::<Tz>()
+        .map_err(|_| format!("Unknown timezone: {}", timezone))
+}
+
+/// Parse an `HH:MM` (or `HH:MM:SS`) time of day
+pub fn parse_time_of_day(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M:%S"))
+        .map_err(|_| format!("Invalid time of day (expected HH:MM): {}", value))
+}
+
+/// Daily window, in local time, during which notifications are held back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// `None` when start and end are equal, i.e. no quiet hours
+    pub fn new(start: NaiveTime, end: NaiveTime) -> Option<Self> {
+        (start != end).then_some(Self { start, end })
+    }
+
+    /// Whether a local time falls in the window; the end is exclusive
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            time >= self.start && time < self.end
+        } else {
+            // Window crosses midnight, e.g. 22:00-07:00
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// How a new notification should be delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryPlan {
+    /// Send on its own at this time (later than requested if it fell in quiet hours)
+    At(DateTime<Utc>),
+    /// Roll into the user's digest, which goes out at `send_at`
+    Digest {
+        batch_type: BatchType,
+        send_at: DateTime<Utc>,
+    },
+}
+
+/// A user's delivery schedule, built from their notification preferences
+#[derive(Debug, Clone)]
+pub struct NotificationSchedule {
+    pub timezone: Tz,
+    pub quiet_hours: Option<QuietHours>,
+    /// Digest cadence, or `None` when batching is off
+    pub batch_type: Option<BatchType>,
+    pub batch_interval: Duration,
+}
+
+impl Default for NotificationSchedule {
+    /// UTC, no quiet hours and no batching: everything goes out when requested
+    fn default() -> Self {
+        Self {
+            timezone: Tz::UTC,
+            quiet_hours: None,
+            batch_type: None,
+            batch_interval: Duration::minutes(60),
+        }
+    }
+}
+
+impl NotificationSchedule {
+    pub fn from_preferences(preferences: &NotificationPreferences) -> Result<Self, String> {
+        let timezone = parse_timezone(&preferences.timezone)?;
+        let quiet_hours = QuietHours::new(
+            parse_time_of_day(&preferences.quiet_hours_start)?,
+            parse_time_of_day(&preferences.quiet_hours_end)?,
+        );
+
+        let batch_interval_minutes = preferences.batch_interval_minutes.max(1);
+        let batch_type = preferences
+            .batch_notifications
+            .then(|| batch_type_for_interval(batch_interval_minutes));
+
+        Ok(Self {
+            timezone,
+            quiet_hours,
+            batch_type,
+            batch_interval: Duration::minutes(batch_interval_minutes as i64),
+        })
+    }
+
+    pub fn is_quiet(&self, at: DateTime<Utc>) -> bool {
+        self.quiet_hours
+            .is_some_and(|quiet| quiet.contains(at.with_timezone(&self.timezone).time()))
+    }
+
+    /// Earliest time at or after `at` that is outside quiet hours
+    pub fn defer(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let Some(quiet) = self.quiet_hours.filter(|_| self.is_quiet(at)) else {
+            return at;
+        };
+
+        let local = at.with_timezone(&self.timezone);
+        let mut end_date = local.date_naive();
+        if quiet.start > quiet.end && local.time() >= quiet.start {
+            // In the evening part of a window that ends tomorrow morning
+            end_date += Duration::days(1);
+        }
+
+        self.resolve_local(end_date.and_time(quiet.end))
+    }
+
+    /// When the digest collecting a notification created at `at` goes out
+    pub fn next_digest_at(&self, batch_type: BatchType, at: DateTime<Utc>) -> DateTime<Utc> {
+        let local = at.with_timezone(&self.timezone).naive_local();
+        let digest_time = self
+            .quiet_hours
+            .map(|quiet| quiet.end)
+            .unwrap_or_else(|| NaiveTime::from_hms_opt(DEFAULT_DIGEST_HOUR, 0, 0).unwrap());
+
+        let send_at = match batch_type {
+            BatchType::Hourly => {
+                let hour = local.date().and_hms_opt(local.hour(), 0, 0).unwrap();
+                self.resolve_local(hour + Duration::hours(1))
+            }
+            BatchType::Daily => {
+                let today = self.resolve_local(local.date().and_time(digest_time));
+                if today > at {
+                    today
+                } else {
+                    self.resolve_local((local.date() + Duration::days(1)).and_time(digest_time))
+                }
+            }
+            BatchType::Weekly => {
+                // Monday morning
+                let days_since_monday = local.weekday().num_days_from_monday() as i64;
+                let monday = local.date() - Duration::days(days_since_monday);
+                let this_week = self.resolve_local(monday.and_time(digest_time));
+                if this_week > at {
+                    this_week
+                } else {
+                    self.resolve_local((monday + Duration::weeks(1)).and_time(digest_time))
+                }
+            }
+            BatchType::Custom => at + self.batch_interval,
+        };
+
+        self.defer(send_at)
+    }
+
+    /// Decide how a new notification is delivered. Critical alerts always go out immediately;
+    /// time-sensitive and high priority ones are only held back by quiet hours; everything else
+    /// joins the digest when batching is on.
+    pub fn plan(
+        &self,
+        notification_type: &NotificationType,
+        priority: &NotificationPriority,
+        channels: &[DeliveryChannel],
+        scheduled_at: DateTime<Utc>,
+    ) -> DeliveryPlan {
+        if matches!(priority, NotificationPriority::Critical) {
+            return DeliveryPlan::At(scheduled_at);
+        }
+
+        let batchable = !is_time_sensitive(notification_type)
+            && !matches!(priority, NotificationPriority::High)
+            && channels
+                .iter()
+                .any(|channel| matches!(channel, DeliveryChannel::Email | DeliveryChannel::InApp));
+
+        match self.batch_type {
+            Some(batch_type) if batchable => DeliveryPlan::Digest {
+                batch_type,
+                send_at: self.next_digest_at(batch_type, scheduled_at),
+            },
+            _ => DeliveryPlan::At(self.defer(scheduled_at)),
+        }
+    }
+
+    /// Convert a local wall-clock time to UTC. Times skipped by a DST change resolve to the first
+    /// valid time after the gap; times that occur twice resolve to the earlier one.
+    pub fn resolve_local(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        let mut candidate = local;
+        for _ in 0..16 {
+            match self.timezone.from_local_datetime(&candidate) {
+                LocalResult::Single(time) => return time.with_timezone(&Utc),
+                LocalResult::Ambiguous(earliest, _) => return earliest.with_timezone(&Utc),
+                LocalResult::None => candidate += Duration::minutes(15),
+            }
+        }
+
+        Utc.from_utc_datetime(&local)
+    }
+}
+
+/// Digest cadence for a batch interval; anything other than an hour, day or week is custom
+pub fn batch_type_for_interval(minutes: i32) -> BatchType {
+    match minutes {
+        60 => BatchType::Hourly,
+        1440 => BatchType::Daily,
+        10080 => BatchType::Weekly,
+        _ => BatchType::Custom,
+    }
+}
+
+/// Notifications that are useless once late, so never wait for a digest
+fn is_time_sensitive(notification_type: &NotificationType) -> bool {
+    matches!(
+        notification_type,
+        NotificationType::WorkoutReminder
+            | NotificationType::RestDayReminder
+            | NotificationType::TrainingAdjustment
+            | NotificationType::SecurityAlert
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn schedule(timezone: &str, quiet: (&str, &str), batch_interval_minutes: Option<i32>) -> NotificationSchedule {
+        NotificationSchedule::from_preferences(&NotificationPreferences {
+            timezone: timezone.to_string(),
+            quiet_hours_start: quiet.0.to_string(),
+            quiet_hours_end: quiet.1.to_string(),
+            batch_notifications: batch_interval_minutes.is_some(),
+            batch_interval_minutes: batch_interval_minutes.unwrap_or(60),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_quiet_hours_use_user_timezone() {
+        let schedule = schedule("America/New_York", ("22:00", "07:00"), None);
+
+        // 23:30 EDT
+        assert!(schedule.is_quiet(utc("2024-06-11T03:30:00Z")));
+        // 06:59 EDT
+        assert!(schedule.is_quiet(utc("2024-06-11T10:59:00Z")));
+        // 07:00 EDT, the end is exclusive
+        assert!(!schedule.is_quiet(utc("2024-06-11T11:00:00Z")));
+        // 23:30 UTC is only 19:30 in New York
+        assert!(!schedule.is_quiet(utc("2024-06-10T23:30:00Z")));
+    }
+
+    #[test]
+    fn test_same_day_window() {
+        let quiet = QuietHours::new(
+            parse_time_of_day("13:00").unwrap(),
+            parse_time_of_day("14:30").unwrap(),
+        )
+        .unwrap();
+
+        assert!(quiet.contains(parse_time_of_day("13:45").unwrap()));
+        assert!(!quiet.contains(parse_time_of_day("14:30").unwrap()));
+        assert!(!quiet.contains(parse_time_of_day("23:00").unwrap()));
+        assert!(QuietHours::new(quiet.start, quiet.start).is_none());
+    }
+
+    #[test]
+    fn test_defer_to_end_of_quiet_hours() {
+        let schedule = schedule("Europe/Berlin", ("22:00", "07:00"), None);
+
+        // 23:30 CEST defers to 07:00 CEST the next morning
+        assert_eq!(
+            schedule.defer(utc("2024-06-10T21:30:00Z")),
+            utc("2024-06-11T05:00:00Z")
+        );
+        // 05:00 CEST defers to 07:00 the same day
+        assert_eq!(
+            schedule.defer(utc("2024-06-11T03:00:00Z")),
+            utc("2024-06-11T05:00:00Z")
+        );
+        // Outside quiet hours nothing changes
+        assert_eq!(
+            schedule.defer(utc("2024-06-11T10:00:00Z")),
+            utc("2024-06-11T10:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_defer_across_dst_changes() {
+        // Clocks go back in New York on 2024-11-03; 07:00 is EST (UTC-5) that morning
+        let schedule_ny = schedule("America/New_York", ("22:00", "07:00"), None);
+        assert_eq!(
+            schedule_ny.defer(utc("2024-11-03T03:00:00Z")),
+            utc("2024-11-03T12:00:00Z")
+        );
+
+        // Clocks go forward in Berlin on 2024-03-31 and 02:30 doesn't exist
+        let schedule_berlin = schedule("Europe/Berlin", ("22:00", "02:30"), None);
+        assert_eq!(
+            schedule_berlin.defer(utc("2024-03-31T00:00:00Z")),
+            utc("2024-03-31T01:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_digest_schedule() {
+        let schedule = schedule("Europe/Berlin", ("22:00", "07:00"), Some(60));
+
+        // Hourly digests go out on the next local hour
+        assert_eq!(
+            schedule.next_digest_at(BatchType::Hourly, utc("2024-06-10T10:20:00Z")),
+            utc("2024-06-10T11:00:00Z")
+        );
+        // ...unless that hour is quiet
+        assert_eq!(
+            schedule.next_digest_at(BatchType::Hourly, utc("2024-06-10T19:20:00Z")),
+            utc("2024-06-11T05:00:00Z")
+        );
+        // Daily digests go out when quiet hours end
+        assert_eq!(
+            schedule.next_digest_at(BatchType::Daily, utc("2024-06-10T10:20:00Z")),
+            utc("2024-06-11T05:00:00Z")
+        );
+        assert_eq!(
+            schedule.next_digest_at(BatchType::Daily, utc("2024-06-10T03:00:00Z")),
+            utc("2024-06-10T05:00:00Z")
+        );
+        // Weekly digests go out on Monday morning; 2024-06-12 is a Wednesday
+        assert_eq!(
+            schedule.next_digest_at(BatchType::Weekly, utc("2024-06-12T10:00:00Z")),
+            utc("2024-06-17T05:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_delivery_plan() {
+        let schedule = schedule("UTC", ("22:00", "07:00"), Some(1440));
+        let night = utc("2024-06-10T23:00:00Z");
+        let channels = [DeliveryChannel::InApp, DeliveryChannel::Email];
+
+        assert_eq!(
+            schedule.plan(&NotificationType::InjuryRisk, &NotificationPriority::Critical, &channels, night),
+            DeliveryPlan::At(night)
+        );
+        assert_eq!(
+            schedule.plan(&NotificationType::WorkoutReminder, &NotificationPriority::Medium, &channels, night),
+            DeliveryPlan::At(utc("2024-06-11T07:00:00Z"))
+        );
+        assert_eq!(
+            schedule.plan(&NotificationType::FitnessImprovement, &NotificationPriority::Medium, &channels, night),
+            DeliveryPlan::Digest {
+                batch_type: BatchType::Daily,
+                send_at: utc("2024-06-11T07:00:00Z"),
+            }
+        );
+        // Push-only notifications have no digest to join
+        assert_eq!(
+            schedule.plan(
+                &NotificationType::FitnessImprovement,
+                &NotificationPriority::Medium,
+                &[DeliveryChannel::WebPush],
+                night
+            ),
+            DeliveryPlan::At(utc("2024-06-11T07:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_batch_type_for_interval() {
+        assert_eq!(batch_type_for_interval(60), BatchType::Hourly);
+        assert_eq!(batch_type_for_interval(1440), BatchType::Daily);
+        assert_eq!(batch_type_for_interval(10080), BatchType::Weekly);
+        assert_eq!(batch_type_for_interval(90), BatchType::Custom);
+    }
+
+    #[test]
+    fn test_invalid_preferences() {
+        assert!(parse_timezone("Mars/Olympus_Mons").is_err());
+        assert!(parse_time_of_day("25:00").is_err());
+        assert_eq!(
+            parse_time_of_day("06:30:00").unwrap(),
+            NaiveTime::from_hms_opt(6, 30, 0).unwrap()
+        );
+    }
+}
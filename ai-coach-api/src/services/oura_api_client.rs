@@ -1,9 +1,14 @@
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use chrono::{DateTime, NaiveDate, Utc};
-use reqwest::{Client, StatusCode};
+use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
-use tracing::{error, info, warn};
+
+use crate::models::{
+    CreateHrvReadingRequest, CreateRestingHrRequest, CreateSleepDataRequest, DataSource,
+};
+use crate::services::wearable_provider::{check_response, WearableProvider, WearableTokens};
 
 /// Oura API v2 Client
 ///
@@ -38,6 +43,12 @@ impl OuraApiClient {
         })
     }
 
+    /// Point the client at a different API host (used by tests)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
     // ========================================================================
     // OAuth 2.0 Flow
     // ========================================================================
@@ -73,12 +84,7 @@ impl OuraApiClient {
             .await
             .context("Failed to send token request")?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            error!("Oura token exchange failed: {} - {}", status, error_text);
-            anyhow::bail!("Failed to exchange code for token: {}", status);
-        }
+        let response = check_response(DataSource::Oura, "exchange code for token", response).await?;
 
         let token_response = response
             .json::<OuraTokenResponse>()
@@ -108,12 +114,7 @@ impl OuraApiClient {
             .await
             .context("Failed to send refresh token request")?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            error!("Oura token refresh failed: {} - {}", status, error_text);
-            anyhow::bail!("Failed to refresh token: {}", status);
-        }
+        let response = check_response(DataSource::Oura, "refresh token", response).await?;
 
         let token_response = response
             .json::<OuraTokenResponse>()
@@ -127,7 +128,7 @@ impl OuraApiClient {
     // Data Retrieval Methods
     // ========================================================================
 
-    /// Fetch daily sleep data
+    /// Fetch sleep periods (main sleep, naps and rest periods)
     pub async fn get_sleep_data(
         &self,
         access_token: &str,
@@ -135,7 +136,7 @@ impl OuraApiClient {
         end_date: NaiveDate,
     ) -> Result<Vec<OuraSleepData>> {
         let url = format!(
-            "{}/v2/usercollection/daily_sleep?start_date={}&end_date={}",
+            "{}/v2/usercollection/sleep?start_date={}&end_date={}",
             self.base_url, start_date, end_date
         );
 
@@ -147,14 +148,7 @@ impl OuraApiClient {
             .await
             .context("Failed to fetch sleep data")?;
 
-        self.handle_rate_limit(&response).await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            error!("Oura sleep data request failed: {} - {}", status, error_text);
-            anyhow::bail!("Failed to fetch sleep data: {}", status);
-        }
+        let response = check_response(DataSource::Oura, "fetch sleep data", response).await?;
 
         let sleep_response = response
             .json::<OuraSleepResponse>()
@@ -184,14 +178,7 @@ impl OuraApiClient {
             .await
             .context("Failed to fetch readiness data")?;
 
-        self.handle_rate_limit(&response).await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            error!("Oura readiness data request failed: {} - {}", status, error_text);
-            anyhow::bail!("Failed to fetch readiness data: {}", status);
-        }
+        let response = check_response(DataSource::Oura, "fetch readiness data", response).await?;
 
         let readiness_response = response
             .json::<OuraReadinessResponse>()
@@ -221,14 +208,7 @@ impl OuraApiClient {
             .await
             .context("Failed to fetch heart rate data")?;
 
-        self.handle_rate_limit(&response).await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            error!("Oura heart rate request failed: {} - {}", status, error_text);
-            anyhow::bail!("Failed to fetch heart rate data: {}", status);
-        }
+        let response = check_response(DataSource::Oura, "fetch heart rate data", response).await?;
 
         let hr_response = response
             .json::<OuraHeartRateResponse>()
@@ -237,33 +217,112 @@ impl OuraApiClient {
 
         Ok(hr_response.data)
     }
+}
 
-    // ========================================================================
-    // Rate Limiting & Error Handling
-    // ========================================================================
+#[async_trait]
+impl WearableProvider for OuraApiClient {
+    fn source(&self) -> DataSource {
+        DataSource::Oura
+    }
 
-    async fn handle_rate_limit(&self, response: &reqwest::Response) -> Result<()> {
-        if response.status() == StatusCode::TOO_MANY_REQUESTS {
-            if let Some(retry_after) = response.headers().get("Retry-After") {
-                let retry_seconds = retry_after
-                    .to_str()
-                    .ok()
-                    .and_then(|s| s.parse::<u64>().ok())
-                    .unwrap_or(60);
-
-                warn!(
-                    "Oura API rate limit exceeded. Retry after {} seconds",
-                    retry_seconds
-                );
-
-                anyhow::bail!(
-                    "Rate limit exceeded. Retry after {} seconds",
-                    retry_seconds
-                );
-            }
-        }
-        Ok(())
+    fn authorization_url(&self, state: &str) -> String {
+        self.get_authorization_url(state)
+    }
+
+    async fn exchange_code(&self, code: &str) -> Result<WearableTokens> {
+        Ok(self.exchange_code_for_token(code).await?.into())
+    }
+
+    async fn refresh_token(&self, refresh_token: &str) -> Result<WearableTokens> {
+        Ok(self.refresh_access_token(refresh_token).await?.into())
+    }
+
+    async fn fetch_sleep(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CreateSleepDataRequest>> {
+        let periods = self.get_sleep_data(access_token, start_date, end_date).await?;
+        Ok(periods.iter().filter_map(sleep_request).collect())
+    }
+
+    async fn fetch_hrv(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CreateHrvReadingRequest>> {
+        let periods = self.get_sleep_data(access_token, start_date, end_date).await?;
+        Ok(periods.iter().filter_map(hrv_request).collect())
+    }
+
+    async fn fetch_resting_hr(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CreateRestingHrRequest>> {
+        let periods = self.get_sleep_data(access_token, start_date, end_date).await?;
+        Ok(periods.iter().filter_map(resting_hr_request).collect())
+    }
+}
+
+/// Main sleep period as a sleep record; Oura reports durations in seconds
+fn sleep_request(data: &OuraSleepData) -> Option<CreateSleepDataRequest> {
+    if !data.is_main_sleep() {
+        return None;
     }
+    let hours = |seconds: Option<i32>| seconds.map(|s| s as f64 / 3600.0);
+
+    Some(CreateSleepDataRequest {
+        total_sleep_hours: hours(data.total_sleep_duration)?,
+        deep_sleep_hours: hours(data.deep_sleep_duration),
+        rem_sleep_hours: hours(data.rem_sleep_duration),
+        light_sleep_hours: hours(data.light_sleep_duration),
+        awake_hours: hours(data.awake_time),
+        sleep_efficiency: data.efficiency,
+        sleep_latency_minutes: data.latency.map(|l| l / 60),
+        bedtime: data.bedtime_start,
+        wake_time: data.bedtime_end,
+        sleep_date: Some(data.day),
+        metadata: Some(serde_json::json!({
+            "oura_sleep_id": data.id,
+            "average_hrv": data.average_hrv,
+            "average_heart_rate": data.average_heart_rate
+        })),
+    })
+}
+
+/// Overnight average RMSSD of the main sleep, timestamped at wake-up
+fn hrv_request(data: &OuraSleepData) -> Option<CreateHrvReadingRequest> {
+    if !data.is_main_sleep() {
+        return None;
+    }
+
+    Some(CreateHrvReadingRequest {
+        rmssd: Some(data.average_hrv?),
+        sdnn: None,
+        pnn50: None,
+        measurement_timestamp: Some(data.bedtime_end?),
+        metadata: Some(serde_json::json!({ "oura_sleep_id": data.id })),
+    })
+}
+
+/// Lowest heart rate of the main sleep, timestamped at wake-up
+fn resting_hr_request(data: &OuraSleepData) -> Option<CreateRestingHrRequest> {
+    if !data.is_main_sleep() {
+        return None;
+    }
+
+    Some(CreateRestingHrRequest {
+        resting_hr: data.lowest_heart_rate? as f64,
+        measurement_timestamp: Some(data.bedtime_end?),
+        metadata: Some(serde_json::json!({
+            "oura_sleep_id": data.id,
+            "derived_from_sleep_minimum": true
+        })),
+    })
 }
 
 // ============================================================================
@@ -278,6 +337,18 @@ pub struct OuraTokenResponse {
     pub refresh_token: String,
 }
 
+impl From<OuraTokenResponse> for WearableTokens {
+    fn from(token: OuraTokenResponse) -> Self {
+        Self {
+            access_token: token.access_token,
+            refresh_token: Some(token.refresh_token),
+            expires_in: Some(token.expires_in),
+            provider_user_id: None,
+            scopes: None,
+        }
+    }
+}
+
 // ============================================================================
 // Oura API Data Types
 // ============================================================================
@@ -292,11 +363,14 @@ pub struct OuraSleepResponse {
 pub struct OuraSleepData {
     pub id: String,
     pub day: NaiveDate,
+    #[serde(default)]
     pub score: Option<i32>,
-    pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
 
-    // Sleep contributors
-    pub contributors: OuraSleepContributors,
+    // Sleep contributors (daily summaries only)
+    #[serde(default)]
+    pub contributors: Option<OuraSleepContributors>,
 
     // Sleep periods
     pub average_breath: Option<f64>,
@@ -323,6 +397,13 @@ pub struct OuraSleepData {
     pub r#type: String,
 }
 
+impl OuraSleepData {
+    /// The night's main sleep, as opposed to naps and rest periods
+    pub fn is_main_sleep(&self) -> bool {
+        self.r#type == "long_sleep"
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OuraSleepContributors {
     pub deep_sleep: Option<i32>,
@@ -383,6 +464,46 @@ pub struct OuraHeartRateData {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::wearable_provider::is_auth_error;
+    use serde_json::json;
+    use wiremock::matchers::{body_string_contains, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mock_client() -> (MockServer, OuraApiClient) {
+        let server = MockServer::start().await;
+        let client = OuraApiClient::new(
+            "client_id".to_string(),
+            "client_secret".to_string(),
+            "http://localhost:3000/callback".to_string(),
+        )
+        .unwrap()
+        .with_base_url(server.uri());
+        (server, client)
+    }
+
+    fn sleep_period(id: &str, day: &str, sleep_type: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "day": day,
+            "average_breath": 14.5,
+            "average_heart_rate": 52.0,
+            "average_hrv": 68.0,
+            "awake_time": 1800,
+            "bedtime_start": format!("{}T23:00:00+00:00", day),
+            "bedtime_end": format!("{}T07:00:00+00:00", day),
+            "deep_sleep_duration": 5400,
+            "efficiency": 91,
+            "latency": 600,
+            "light_sleep_duration": 12600,
+            "lowest_heart_rate": 46,
+            "period": 0,
+            "rem_sleep_duration": 7200,
+            "restless_periods": 210,
+            "time_in_bed": 28800,
+            "total_sleep_duration": 25200,
+            "type": sleep_type
+        })
+    }
 
     #[test]
     fn test_authorization_url_generation() {
@@ -414,4 +535,111 @@ mod tests {
         assert_eq!(client.client_id, "client_id");
         assert_eq!(client.base_url, "https://api.ouraring.com");
     }
+
+    #[tokio::test]
+    async fn test_exchange_code_posts_form_and_maps_tokens() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth/token"))
+            .and(body_string_contains("grant_type=authorization_code"))
+            .and(body_string_contains("code=auth_code"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "access_token": "access",
+                "token_type": "bearer",
+                "expires_in": 86400,
+                "refresh_token": "refresh"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let tokens = client.exchange_code("auth_code").await.unwrap();
+
+        assert_eq!(tokens.access_token, "access");
+        assert_eq!(tokens.refresh_token.as_deref(), Some("refresh"));
+        assert_eq!(tokens.expires_in, Some(86400));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_maps_main_sleep_and_skips_naps() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/usercollection/sleep"))
+            .and(query_param("start_date", "2024-03-01"))
+            .and(query_param("end_date", "2024-03-02"))
+            .and(header("authorization", "Bearer access"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": [
+                    sleep_period("night", "2024-03-02", "long_sleep"),
+                    sleep_period("nap", "2024-03-02", "sleep")
+                ],
+                "next_token": null
+            })))
+            .mount(&server)
+            .await;
+
+        let start = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+
+        let sleep = client.fetch_sleep("access", start, end).await.unwrap();
+        assert_eq!(sleep.len(), 1);
+        assert_eq!(sleep[0].total_sleep_hours, 7.0);
+        assert_eq!(sleep[0].deep_sleep_hours, Some(1.5));
+        assert_eq!(sleep[0].sleep_efficiency, Some(91.0));
+        assert_eq!(sleep[0].sleep_latency_minutes, Some(10));
+        assert_eq!(sleep[0].sleep_date, NaiveDate::from_ymd_opt(2024, 3, 2));
+
+        let hrv = client.fetch_hrv("access", start, end).await.unwrap();
+        assert_eq!(hrv.len(), 1);
+        assert_eq!(hrv[0].rmssd, Some(68.0));
+        assert_eq!(hrv[0].measurement_timestamp, sleep[0].wake_time);
+
+        let resting_hr = client.fetch_resting_hr("access", start, end).await.unwrap();
+        assert_eq!(resting_hr.len(), 1);
+        assert_eq!(resting_hr[0].resting_hr, 46.0);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_reports_retry_after() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/usercollection/sleep"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "120"))
+            .mount(&server)
+            .await;
+
+        let day = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let error = client.fetch_sleep("access", day, day).await.unwrap_err();
+
+        assert!(error.to_string().contains("Retry after 120 seconds"));
+    }
+
+    #[tokio::test]
+    async fn test_server_error_is_not_an_auth_error() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/usercollection/sleep"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let day = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let error = client.fetch_sleep("access", day, day).await.unwrap_err();
+
+        assert!(!is_auth_error(&error));
+    }
+
+    #[tokio::test]
+    async fn test_revoked_refresh_token_is_an_auth_error() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth/token"))
+            .and(body_string_contains("grant_type=refresh_token"))
+            .respond_with(ResponseTemplate::new(400).set_body_string(r#"{"error":"invalid_grant"}"#))
+            .mount(&server)
+            .await;
+
+        let error = client.refresh_token("stale").await.unwrap_err();
+        assert!(is_auth_error(&error));
+    }
 }
@@ -0,0 +1,489 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::models::{
+    CreateHrvReadingRequest, CreateRestingHrRequest, CreateSleepDataRequest, DataSource,
+};
+use crate::services::wearable_provider::{
+    check_response, OAuthCredentials, ProviderAuthError, WearableProvider, WearableTokens,
+};
+
+/// Polar AccessLink API v3 Client
+///
+/// Sleep stages come from Sleep Plus Stages; HRV (RMSSD) and heart rate come from Nightly
+/// Recharge. AccessLink only serves the last 28 days and its tokens don't expire, so there
+/// is no refresh flow; users must be registered with the app once after authorizing.
+/// API Documentation: https://www.polar.com/accesslink-api/
+pub struct PolarApiClient {
+    client: Client,
+    credentials: OAuthCredentials,
+    api_base_url: String,
+    auth_base_url: String,
+}
+
+impl PolarApiClient {
+    pub fn new(credentials: OAuthCredentials) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            credentials,
+            api_base_url: "https://www.polaraccesslink.com".to_string(),
+            auth_base_url: "https://polarremote.com".to_string(),
+        })
+    }
+
+    /// Point the client at different API and token hosts (used by tests)
+    pub fn with_base_urls(mut self, api_base_url: impl Into<String>, auth_base_url: impl Into<String>) -> Self {
+        self.api_base_url = api_base_url.into();
+        self.auth_base_url = auth_base_url.into();
+        self
+    }
+
+    pub async fn get_sleep(&self, access_token: &str) -> Result<Vec<PolarNight>> {
+        let response = self
+            .client
+            .get(format!("{}/v3/users/sleep", self.api_base_url))
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .context("Failed to fetch sleep data")?;
+
+        let response = check_response(DataSource::Polar, "fetch sleep data", response).await?;
+        let sleep = response
+            .json::<PolarSleepResponse>()
+            .await
+            .context("Failed to parse sleep data")?;
+
+        Ok(sleep.nights)
+    }
+
+    pub async fn get_nightly_recharge(&self, access_token: &str) -> Result<Vec<PolarNightlyRecharge>> {
+        let response = self
+            .client
+            .get(format!("{}/v3/users/nightly-recharge", self.api_base_url))
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .context("Failed to fetch nightly recharge data")?;
+
+        let response = check_response(DataSource::Polar, "fetch nightly recharge data", response).await?;
+        let recharge = response
+            .json::<PolarNightlyRechargeResponse>()
+            .await
+            .context("Failed to parse nightly recharge data")?;
+
+        Ok(recharge.recharges)
+    }
+}
+
+#[async_trait]
+impl WearableProvider for PolarApiClient {
+    fn source(&self) -> DataSource {
+        DataSource::Polar
+    }
+
+    fn authorization_url(&self, state: &str) -> String {
+        format!(
+            "https://flow.polar.com/oauth2/authorization?response_type=code&client_id={}&redirect_uri={}&scope=accesslink.read_all&state={}",
+            self.credentials.client_id,
+            urlencoding::encode(&self.credentials.redirect_uri),
+            state
+        )
+    }
+
+    async fn exchange_code(&self, code: &str) -> Result<WearableTokens> {
+        let params = [
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.credentials.redirect_uri),
+        ];
+
+        let response = self
+            .client
+            .post(format!("{}/v2/oauth2/token", self.auth_base_url))
+            .basic_auth(&self.credentials.client_id, Some(&self.credentials.client_secret))
+            .header("Accept", "application/json")
+            .form(&params)
+            .send()
+            .await
+            .context("Failed to send token request")?;
+
+        let response = check_response(DataSource::Polar, "token exchange", response).await?;
+        let token = response
+            .json::<PolarTokenResponse>()
+            .await
+            .context("Failed to parse token response")?;
+
+        Ok(token.into())
+    }
+
+    async fn refresh_token(&self, _refresh_token: &str) -> Result<WearableTokens> {
+        let error = ProviderAuthError {
+            provider: DataSource::Polar,
+            status: StatusCode::UNAUTHORIZED,
+        };
+        Err(anyhow::Error::new(error).context("Polar tokens cannot be refreshed; reconnect Polar to continue syncing"))
+    }
+
+    /// Register the user with AccessLink; an already registered user is not an error
+    async fn on_connected(&self, user_id: Uuid, tokens: &WearableTokens) -> Result<()> {
+        let response = self
+            .client
+            .post(format!("{}/v3/users", self.api_base_url))
+            .bearer_auth(&tokens.access_token)
+            .json(&serde_json::json!({ "member-id": user_id.to_string() }))
+            .send()
+            .await
+            .context("Failed to send user registration request")?;
+
+        if response.status() == StatusCode::CONFLICT {
+            return Ok(());
+        }
+
+        check_response(DataSource::Polar, "register user", response).await?;
+        info!("Registered user {} with Polar AccessLink", user_id);
+        Ok(())
+    }
+
+    async fn fetch_sleep(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CreateSleepDataRequest>> {
+        let nights = self.get_sleep(access_token).await?;
+        Ok(nights
+            .iter()
+            .filter(|night| night.date >= start_date && night.date <= end_date)
+            .map(sleep_request)
+            .collect())
+    }
+
+    async fn fetch_hrv(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CreateHrvReadingRequest>> {
+        let recharges = self.get_nightly_recharge(access_token).await?;
+        Ok(recharges
+            .iter()
+            .filter(|recharge| recharge.date >= start_date && recharge.date <= end_date)
+            .filter_map(hrv_request)
+            .collect())
+    }
+
+    async fn fetch_resting_hr(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CreateRestingHrRequest>> {
+        let recharges = self.get_nightly_recharge(access_token).await?;
+        Ok(recharges
+            .iter()
+            .filter(|recharge| recharge.date >= start_date && recharge.date <= end_date)
+            .filter_map(resting_hr_request)
+            .collect())
+    }
+}
+
+/// Polar reports stage durations in seconds; light sleep includes unrecognized stages
+fn sleep_request(night: &PolarNight) -> CreateSleepDataRequest {
+    let hours = |seconds: i64| seconds as f64 / 3600.0;
+    let light = night.light_sleep + night.unrecognized_sleep_stage.unwrap_or(0);
+    let asleep = light + night.deep_sleep + night.rem_sleep;
+    let in_bed = (night.sleep_end_time - night.sleep_start_time).num_seconds();
+
+    CreateSleepDataRequest {
+        total_sleep_hours: hours(asleep),
+        deep_sleep_hours: Some(hours(night.deep_sleep)),
+        rem_sleep_hours: Some(hours(night.rem_sleep)),
+        light_sleep_hours: Some(hours(light)),
+        awake_hours: Some(hours(night.total_interruption_duration.unwrap_or(0))),
+        sleep_efficiency: (in_bed > 0).then(|| (asleep as f64 / in_bed as f64 * 100.0).min(100.0)),
+        sleep_latency_minutes: None,
+        bedtime: Some(night.sleep_start_time),
+        wake_time: Some(night.sleep_end_time),
+        sleep_date: Some(night.date),
+        metadata: Some(serde_json::json!({
+            "polar_sleep_score": night.sleep_score,
+            "continuity": night.continuity
+        })),
+    }
+}
+
+/// Nightly Recharge readings are per night without a time; they're stamped at midnight UTC
+/// of the night's date so re-syncing the same night is idempotent
+fn recharge_timestamp(recharge: &PolarNightlyRecharge) -> DateTime<Utc> {
+    recharge.date.and_hms_opt(0, 0, 0).unwrap().and_utc()
+}
+
+fn hrv_request(recharge: &PolarNightlyRecharge) -> Option<CreateHrvReadingRequest> {
+    Some(CreateHrvReadingRequest {
+        rmssd: Some(recharge.heart_rate_variability_avg?),
+        sdnn: None,
+        pnn50: None,
+        measurement_timestamp: Some(recharge_timestamp(recharge)),
+        metadata: Some(serde_json::json!({
+            "ans_charge": recharge.ans_charge,
+            "nightly_recharge_status": recharge.nightly_recharge_status
+        })),
+    })
+}
+
+/// Average heart rate over the first hours of sleep, Polar's closest equivalent to resting HR
+fn resting_hr_request(recharge: &PolarNightlyRecharge) -> Option<CreateRestingHrRequest> {
+    Some(CreateRestingHrRequest {
+        resting_hr: recharge.heart_rate_avg?,
+        measurement_timestamp: Some(recharge_timestamp(recharge)),
+        metadata: Some(serde_json::json!({ "derived_from_nightly_recharge": true })),
+    })
+}
+
+// ============================================================================
+// OAuth Response Types
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolarTokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: Option<i64>,
+    pub x_user_id: i64,
+}
+
+impl From<PolarTokenResponse> for WearableTokens {
+    fn from(token: PolarTokenResponse) -> Self {
+        Self {
+            access_token: token.access_token,
+            refresh_token: None,
+            expires_in: token.expires_in,
+            provider_user_id: Some(token.x_user_id.to_string()),
+            scopes: Some(vec!["accesslink.read_all".to_string()]),
+        }
+    }
+}
+
+// ============================================================================
+// Polar API Data Types
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolarSleepResponse {
+    #[serde(default)]
+    pub nights: Vec<PolarNight>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolarNight {
+    pub date: NaiveDate,
+    pub sleep_start_time: DateTime<Utc>,
+    pub sleep_end_time: DateTime<Utc>,
+    pub light_sleep: i64,
+    pub deep_sleep: i64,
+    pub rem_sleep: i64,
+    pub unrecognized_sleep_stage: Option<i64>,
+    pub total_interruption_duration: Option<i64>,
+    pub sleep_score: Option<i32>,
+    pub continuity: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolarNightlyRechargeResponse {
+    #[serde(default)]
+    pub recharges: Vec<PolarNightlyRecharge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolarNightlyRecharge {
+    pub date: NaiveDate,
+    pub heart_rate_avg: Option<f64>,
+    pub heart_rate_variability_avg: Option<f64>,
+    pub ans_charge: Option<f64>,
+    pub nightly_recharge_status: Option<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::wearable_provider::is_auth_error;
+    use serde_json::json;
+    use wiremock::matchers::{body_string_contains, header, header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn credentials() -> OAuthCredentials {
+        OAuthCredentials {
+            client_id: "polar_client".to_string(),
+            client_secret: "polar_secret".to_string(),
+            redirect_uri: "http://localhost:3000/callback".to_string(),
+        }
+    }
+
+    async fn mock_client() -> (MockServer, PolarApiClient) {
+        let server = MockServer::start().await;
+        let client = PolarApiClient::new(credentials())
+            .unwrap()
+            .with_base_urls(server.uri(), server.uri());
+        (server, client)
+    }
+
+    fn tokens() -> WearableTokens {
+        WearableTokens {
+            access_token: "access".to_string(),
+            refresh_token: None,
+            expires_in: None,
+            provider_user_id: Some("4711".to_string()),
+            scopes: None,
+        }
+    }
+
+    #[test]
+    fn test_authorization_url() {
+        let client = PolarApiClient::new(credentials()).unwrap();
+        let url = client.authorization_url("state_12345");
+
+        assert!(url.starts_with("https://flow.polar.com/oauth2/authorization?"));
+        assert!(url.contains("client_id=polar_client"));
+        assert!(url.contains("state=state_12345"));
+    }
+
+    #[tokio::test]
+    async fn test_exchange_code_uses_basic_auth_and_keeps_polar_user_id() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/oauth2/token"))
+            .and(header_exists("authorization"))
+            .and(body_string_contains("code=auth_code"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "access_token": "access",
+                "token_type": "bearer",
+                "expires_in": 315_359_999,
+                "x_user_id": 4711
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let tokens = client.exchange_code("auth_code").await.unwrap();
+
+        assert_eq!(tokens.access_token, "access");
+        assert_eq!(tokens.refresh_token, None);
+        assert_eq!(tokens.provider_user_id.as_deref(), Some("4711"));
+    }
+
+    #[tokio::test]
+    async fn test_registration_treats_conflict_as_registered() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("POST"))
+            .and(path("/v3/users"))
+            .and(header("authorization", "Bearer access"))
+            .respond_with(ResponseTemplate::new(409))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        assert!(client.on_connected(Uuid::new_v4(), &tokens()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_is_not_supported() {
+        let (_server, client) = mock_client().await;
+        let error = client.refresh_token("anything").await.unwrap_err();
+        assert!(is_auth_error(&error));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_sleep_filters_to_date_range() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("GET"))
+            .and(path("/v3/users/sleep"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "nights": [
+                    {
+                        "polar_user": "https://www.polaraccesslink.com/v3/users/4711",
+                        "date": "2024-03-02",
+                        "sleep_start_time": "2024-03-01T23:00:00+01:00",
+                        "sleep_end_time": "2024-03-02T07:00:00+01:00",
+                        "device_id": "1111AAAA",
+                        "continuity": 2.8,
+                        "light_sleep": 14400,
+                        "deep_sleep": 5400,
+                        "rem_sleep": 5400,
+                        "unrecognized_sleep_stage": 0,
+                        "sleep_score": 80,
+                        "total_interruption_duration": 1800
+                    },
+                    {
+                        "date": "2024-02-20",
+                        "sleep_start_time": "2024-02-19T23:00:00+01:00",
+                        "sleep_end_time": "2024-02-20T07:00:00+01:00",
+                        "light_sleep": 14400,
+                        "deep_sleep": 5400,
+                        "rem_sleep": 5400
+                    }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let start = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+        let sleep = client.fetch_sleep("access", start, end).await.unwrap();
+
+        assert_eq!(sleep.len(), 1);
+        assert_eq!(sleep[0].total_sleep_hours, 7.0);
+        assert_eq!(sleep[0].awake_hours, Some(0.5));
+        assert_eq!(sleep[0].sleep_efficiency, Some(87.5));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_nightly_recharge() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("GET"))
+            .and(path("/v3/users/nightly-recharge"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "recharges": [
+                    {
+                        "polar_user": "https://www.polaraccesslink.com/v3/users/4711",
+                        "date": "2024-03-02",
+                        "heart_rate_avg": 52.0,
+                        "beat_to_beat_avg": 1150.0,
+                        "heart_rate_variability_avg": 58.0,
+                        "breathing_rate_avg": 14.1,
+                        "nightly_recharge_status": 4,
+                        "ans_charge": 3.2,
+                        "ans_charge_status": 4
+                    },
+                    {
+                        "date": "2024-03-01",
+                        "heart_rate_avg": null,
+                        "heart_rate_variability_avg": null
+                    }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let start = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+
+        let hrv = client.fetch_hrv("access", start, end).await.unwrap();
+        assert_eq!(hrv.len(), 1);
+        assert_eq!(hrv[0].rmssd, Some(58.0));
+
+        let resting_hr = client.fetch_resting_hr("access", start, end).await.unwrap();
+        assert_eq!(resting_hr.len(), 1);
+        assert_eq!(resting_hr[0].resting_hr, 52.0);
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Duration, NaiveDate, Utc};
 use sqlx::PgPool;
+use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::models::{
@@ -8,24 +9,34 @@ use crate::models::{
     RecoveryInsightsResponse, RecoveryPattern, RecoveryScore, RecoveryStatus,
     RecoveryStatusResponse, RecoveryTrendsResponse, Recommendation, RestingHrData, SleepData,
 };
-use crate::services::{NotificationService, RecoveryAlertService};
+use crate::services::{BackgroundJobService, NotificationService, RecoveryAlertService};
 
 const MODEL_VERSION: &str = "1.0.0-simple";
 
 pub struct RecoveryAnalysisService {
     db: PgPool,
     alert_service: Option<RecoveryAlertService>,
+    background_job_service: Arc<BackgroundJobService>,
 }
 
 impl RecoveryAnalysisService {
-    pub fn new(db: PgPool) -> Self {
-        Self { db, alert_service: None }
+    pub fn new(db: PgPool, background_job_service: Arc<BackgroundJobService>) -> Self {
+        Self {
+            db,
+            alert_service: None,
+            background_job_service,
+        }
     }
 
-    pub fn with_alerts(db: PgPool, alert_service: RecoveryAlertService) -> Self {
+    pub fn with_alerts(
+        db: PgPool,
+        alert_service: RecoveryAlertService,
+        background_job_service: Arc<BackgroundJobService>,
+    ) -> Self {
         Self {
             db,
             alert_service: Some(alert_service),
+            background_job_service,
         }
     }
 
@@ -107,6 +118,11 @@ impl RecoveryAnalysisService {
             }
         }
 
+        // Recovery goals are measured from the scores, in the background
+        if let Err(e) = self.background_job_service.queue_goal_evaluation(user_id).await {
+            tracing::error!("Failed to queue goal evaluation for user {}: {:#}", user_id, e);
+        }
+
         Ok(score)
     }
 
@@ -390,11 +406,13 @@ impl RecoveryAnalysisService {
         hrv_data: &[HrvReading],
         baseline: &RecoveryBaseline,
     ) -> (HrvTrend, Option<f64>) {
-        if hrv_data.is_empty() {
+        // Readings without RMSSD (SDNN-only sources) can't be compared with the RMSSD baseline
+        let rmssd: Vec<f64> = hrv_data.iter().filter_map(|h| h.rmssd).collect();
+        if rmssd.is_empty() {
             return (HrvTrend::InsufficientData, None);
         }
 
-        let recent_avg = hrv_data.iter().map(|h| h.rmssd).sum::<f64>() / hrv_data.len() as f64;
+        let recent_avg = rmssd.iter().sum::<f64>() / rmssd.len() as f64;
 
         let deviation = if let Some(baseline_hrv) = baseline.hrv_baseline_rmssd {
             Some(((recent_avg - baseline_hrv) / baseline_hrv) * 100.0)
@@ -403,13 +421,13 @@ impl RecoveryAnalysisService {
         };
 
         // Simple trend detection: compare first half to second half
-        let trend = if hrv_data.len() >= 4 {
-            let mid = hrv_data.len() / 2;
-            let recent_half: Vec<_> = hrv_data[..mid].to_vec();
-            let older_half: Vec<_> = hrv_data[mid..].to_vec();
+        let trend = if rmssd.len() >= 4 {
+            let mid = rmssd.len() / 2;
+            let recent_half = &rmssd[..mid];
+            let older_half = &rmssd[mid..];
 
-            let recent_avg_half = recent_half.iter().map(|h| h.rmssd).sum::<f64>() / recent_half.len() as f64;
-            let older_avg_half = older_half.iter().map(|h| h.rmssd).sum::<f64>() / older_half.len() as f64;
+            let recent_avg_half = recent_half.iter().sum::<f64>() / recent_half.len() as f64;
+            let older_avg_half = older_half.iter().sum::<f64>() / older_half.len() as f64;
 
             let change_percent = ((recent_avg_half - older_avg_half) / older_avg_half) * 100.0;
 
@@ -10,6 +10,7 @@ use crate::models::{
     RestingHrResponse, SleepData, SleepDataListResponse, SleepDataResponse,
 };
 
+#[derive(Clone)]
 pub struct RecoveryDataService {
     db: PgPool,
 }
@@ -458,6 +459,182 @@ impl RecoveryDataService {
         Ok(count.unwrap_or(0))
     }
 
+    // ========================================================================
+    // Bulk Import Operations
+    // ========================================================================
+
+    /// Insert imported HRV readings in one statement. Readings already stored for `source`, and
+    /// readings on days another source already covers, are skipped. Returns the rows inserted.
+    pub async fn import_hrv_readings(
+        &self,
+        user_id: Uuid,
+        source: DataSource,
+        readings: &[CreateHrvReadingRequest],
+    ) -> Result<u64> {
+        let timestamps: Vec<DateTime<Utc>> = readings
+            .iter()
+            .map(|r| r.measurement_timestamp.unwrap_or_else(Utc::now))
+            .collect();
+        let dates: Vec<NaiveDate> = timestamps.iter().map(|t| t.date_naive()).collect();
+        let rmssd: Vec<Option<f64>> = readings.iter().map(|r| r.rmssd).collect();
+        let sdnn: Vec<Option<f64>> = readings.iter().map(|r| r.sdnn).collect();
+        let pnn50: Vec<Option<f64>> = readings.iter().map(|r| r.pnn50).collect();
+        let metadata: Vec<Option<String>> = readings
+            .iter()
+            .map(|r| r.metadata.as_ref().map(|m| m.to_string()))
+            .collect();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO hrv_readings (
+                user_id, measurement_date, measurement_timestamp,
+                rmssd, sdnn, pnn50, source, metadata
+            )
+            SELECT $1, t.measurement_date, t.measurement_timestamp,
+                   t.rmssd, t.sdnn, t.pnn50, $2, t.metadata::jsonb
+            FROM UNNEST($3::date[], $4::timestamptz[], $5::float8[], $6::float8[], $7::float8[], $8::text[])
+                AS t(measurement_date, measurement_timestamp, rmssd, sdnn, pnn50, metadata)
+            WHERE NOT EXISTS (
+                SELECT 1 FROM hrv_readings existing
+                WHERE existing.user_id = $1
+                  AND existing.measurement_date = t.measurement_date
+                  AND existing.source <> $2
+            )
+            ON CONFLICT (user_id, measurement_timestamp, source) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(source.as_str())
+        .bind(&dates)
+        .bind(&timestamps)
+        .bind(&rmssd)
+        .bind(&sdnn)
+        .bind(&pnn50)
+        .bind(&metadata)
+        .execute(&self.db)
+        .await
+        .context("Failed to import HRV readings")?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Insert imported nights of sleep, skipping nights already stored by any source
+    pub async fn import_sleep_data(
+        &self,
+        user_id: Uuid,
+        source: DataSource,
+        nights: &[CreateSleepDataRequest],
+    ) -> Result<u64> {
+        let sleep_dates: Vec<NaiveDate> = nights
+            .iter()
+            .map(|n| n.sleep_date.unwrap_or_else(|| Utc::now().date_naive()))
+            .collect();
+        let total: Vec<f64> = nights.iter().map(|n| n.total_sleep_hours).collect();
+        let deep: Vec<Option<f64>> = nights.iter().map(|n| n.deep_sleep_hours).collect();
+        let rem: Vec<Option<f64>> = nights.iter().map(|n| n.rem_sleep_hours).collect();
+        let light: Vec<Option<f64>> = nights.iter().map(|n| n.light_sleep_hours).collect();
+        let awake: Vec<Option<f64>> = nights.iter().map(|n| n.awake_hours).collect();
+        let efficiency: Vec<Option<f64>> = nights.iter().map(|n| n.sleep_efficiency).collect();
+        let latency: Vec<Option<i32>> = nights.iter().map(|n| n.sleep_latency_minutes).collect();
+        let bedtimes: Vec<Option<DateTime<Utc>>> = nights.iter().map(|n| n.bedtime).collect();
+        let wake_times: Vec<Option<DateTime<Utc>>> = nights.iter().map(|n| n.wake_time).collect();
+        let metadata: Vec<Option<String>> = nights
+            .iter()
+            .map(|n| n.metadata.as_ref().map(|m| m.to_string()))
+            .collect();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO sleep_data (
+                user_id, sleep_date, total_sleep_hours, deep_sleep_hours,
+                rem_sleep_hours, light_sleep_hours, awake_hours,
+                sleep_efficiency, sleep_latency_minutes, bedtime, wake_time,
+                source, metadata
+            )
+            SELECT $1, t.sleep_date, t.total, t.deep, t.rem, t.light, t.awake,
+                   t.efficiency, t.latency, t.bedtime, t.wake_time, $2, t.metadata::jsonb
+            FROM UNNEST(
+                $3::date[], $4::float8[], $5::float8[], $6::float8[], $7::float8[], $8::float8[],
+                $9::float8[], $10::int4[], $11::timestamptz[], $12::timestamptz[], $13::text[]
+            ) AS t(sleep_date, total, deep, rem, light, awake, efficiency, latency, bedtime, wake_time, metadata)
+            WHERE NOT EXISTS (
+                SELECT 1 FROM sleep_data existing
+                WHERE existing.user_id = $1
+                  AND existing.sleep_date = t.sleep_date
+                  AND existing.source <> $2
+            )
+            ON CONFLICT (user_id, sleep_date, source) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(source.as_str())
+        .bind(&sleep_dates)
+        .bind(&total)
+        .bind(&deep)
+        .bind(&rem)
+        .bind(&light)
+        .bind(&awake)
+        .bind(&efficiency)
+        .bind(&latency)
+        .bind(&bedtimes)
+        .bind(&wake_times)
+        .bind(&metadata)
+        .execute(&self.db)
+        .await
+        .context("Failed to import sleep data")?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Insert imported resting HR readings, with the same de-duplication as HRV
+    pub async fn import_resting_hr(
+        &self,
+        user_id: Uuid,
+        source: DataSource,
+        readings: &[CreateRestingHrRequest],
+    ) -> Result<u64> {
+        let timestamps: Vec<DateTime<Utc>> = readings
+            .iter()
+            .map(|r| r.measurement_timestamp.unwrap_or_else(Utc::now))
+            .collect();
+        let dates: Vec<NaiveDate> = timestamps.iter().map(|t| t.date_naive()).collect();
+        let resting_hr: Vec<f64> = readings.iter().map(|r| r.resting_hr).collect();
+        let metadata: Vec<Option<String>> = readings
+            .iter()
+            .map(|r| r.metadata.as_ref().map(|m| m.to_string()))
+            .collect();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO resting_hr_data (
+                user_id, measurement_date, measurement_timestamp,
+                resting_hr, source, metadata
+            )
+            SELECT $1, t.measurement_date, t.measurement_timestamp, t.resting_hr, $2, t.metadata::jsonb
+            FROM UNNEST($3::date[], $4::timestamptz[], $5::float8[], $6::text[])
+                AS t(measurement_date, measurement_timestamp, resting_hr, metadata)
+            WHERE NOT EXISTS (
+                SELECT 1 FROM resting_hr_data existing
+                WHERE existing.user_id = $1
+                  AND existing.measurement_date = t.measurement_date
+                  AND existing.source <> $2
+            )
+            ON CONFLICT (user_id, measurement_timestamp, source) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(source.as_str())
+        .bind(&dates)
+        .bind(&timestamps)
+        .bind(&resting_hr)
+        .bind(&metadata)
+        .execute(&self.db)
+        .await
+        .context("Failed to import resting HR data")?;
+
+        Ok(result.rows_affected())
+    }
+
     // ========================================================================
     // Baseline Operations
     // ========================================================================
@@ -0,0 +1,245 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::interval;
+use uuid::Uuid;
+
+use crate::models::{
+    CreateNotificationRequest, DeliveryChannel, NotificationType, TrainingAdjustment,
+    TrainingRecoverySettings, WorkoutDay,
+};
+use crate::services::notification_timing::NotificationSchedule;
+use crate::services::training_adjustment_service::AutoAdjustmentKind;
+use crate::services::workout_export_service::workout_type_label;
+use crate::services::{NotificationService, TrainingAdjustmentService};
+
+#[derive(Debug, Clone)]
+pub struct TrainingAdjustmentConfig {
+    pub enabled: bool,
+    /// How often to look for users whose recovery score for the day has arrived
+    pub poll_interval: Duration,
+}
+
+impl Default for TrainingAdjustmentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl TrainingAdjustmentConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            enabled: std::env::var("TRAINING_AUTO_ADJUST_ENABLED")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.enabled),
+            poll_interval: std::env::var("TRAINING_AUTO_ADJUST_INTERVAL_MINUTES")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|minutes| *minutes > 0)
+                .map_or(defaults.poll_interval, |minutes| {
+                    Duration::from_secs(minutes * 60)
+                }),
+        }
+    }
+}
+
+/// Applies recovery-driven adjustments to the day's planned workout for every user who opted
+/// in to automatic adjustments. Each user's day is decided once, as soon as that day's
+/// recovery score exists, and the athlete is notified of any change.
+pub struct TrainingAdjustmentScheduler {
+    db: PgPool,
+    adjustment_service: TrainingAdjustmentService,
+    notification_service: NotificationService,
+    config: TrainingAdjustmentConfig,
+}
+
+impl TrainingAdjustmentScheduler {
+    pub fn new(db: PgPool, config: TrainingAdjustmentConfig) -> Self {
+        Self {
+            adjustment_service: TrainingAdjustmentService::new(db.clone()),
+            notification_service: NotificationService::new(db.clone()),
+            db,
+            config,
+        }
+    }
+
+    /// Start the adjustment loop in the background
+    pub fn start(self: Arc<Self>) {
+        if !self.config.enabled {
+            tracing::info!("Automatic training adjustments disabled");
+            return;
+        }
+
+        tokio::spawn(async move {
+            self.run().await;
+        });
+    }
+
+    async fn run(&self) {
+        let mut interval = interval(self.config.poll_interval);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.run_auto_adjustments(Utc::now()).await {
+                tracing::error!("Failed to run automatic training adjustments: {}", e);
+            }
+        }
+    }
+
+    /// Adjust each opted-in user's workout for the day it is at `now` in their timezone
+    pub async fn run_auto_adjustments(&self, now: DateTime<Utc>) -> Result<u32> {
+        let users = sqlx::query_as::<_, TrainingRecoverySettings>(
+            r#"
+            SELECT
+                id, user_id, auto_adjust_enabled, adjustment_aggressiveness,
+                min_rest_days_per_week, max_consecutive_training_days,
+                allow_intensity_reduction, allow_volume_reduction, allow_workout_swap,
+                created_at, updated_at
+            FROM training_recovery_settings
+            WHERE auto_adjust_enabled
+            "#,
+        )
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to fetch users with automatic adjustments")?;
+
+        let mut applied = 0;
+
+        for settings in users {
+            let schedule = self.user_schedule(settings.user_id).await;
+            let date = now.with_timezone(&schedule.timezone).date_naive();
+
+            match self.adjustment_service.auto_adjust_day(&settings, date).await {
+                Ok(Some(adjustment)) if adjustment.adjustment_applied.unwrap_or(false) => {
+                    applied += 1;
+                    self.notify(&adjustment, &schedule).await;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to adjust training for user {} on {}: {:#}",
+                        settings.user_id,
+                        date,
+                        e
+                    );
+                }
+            }
+        }
+
+        if applied > 0 {
+            tracing::info!("Applied {} automatic training adjustments", applied);
+        }
+
+        Ok(applied)
+    }
+
+    /// The user's notification timing, or the defaults (UTC) when it can't be loaded
+    async fn user_schedule(&self, user_id: Uuid) -> NotificationSchedule {
+        self.notification_service.user_schedule(user_id).await.unwrap_or_else(|e| {
+            tracing::warn!("Using UTC for training adjustments of user {}: {}", user_id, e);
+            NotificationSchedule::default()
+        })
+    }
+
+    async fn notify(&self, adjustment: &TrainingAdjustment, schedule: &NotificationSchedule) {
+        let original: Option<WorkoutDay> = adjustment
+            .original_workout
+            .clone()
+            .and_then(|workout| serde_json::from_value(workout).ok());
+        let adjusted: Option<WorkoutDay> = adjustment
+            .adjusted_workout
+            .clone()
+            .and_then(|workout| serde_json::from_value(workout).ok());
+        let kind = adjustment.adjustment_type.as_deref().unwrap_or_default();
+
+        let (title, message) = match original {
+            Some(original) => adjustment_message(kind, &original, adjusted.as_ref()),
+            None => return,
+        };
+
+        let request = CreateNotificationRequest {
+            user_id: adjustment.user_id,
+            notification_type: NotificationType::TrainingAdjustment,
+            title,
+            message,
+            data: Some(json!({
+                "adjustment_id": adjustment.id,
+                "plan_id": adjustment.plan_id,
+                "adjustment_type": kind,
+                "adjustment_date": adjustment.adjustment_date,
+                "reasoning": adjustment.reasoning,
+            })),
+            scheduled_at: None,
+            delivery_channels: vec![DeliveryChannel::InApp, DeliveryChannel::WebPush, DeliveryChannel::Email],
+            expires_at: Some(schedule.resolve_local(
+                (adjustment.adjustment_date + ChronoDuration::days(1)).and_hms_opt(0, 0, 0).unwrap_or_default(),
+            )),
+        };
+
+        if let Err(e) = self.notification_service.create_notification(request).await {
+            tracing::warn!(
+                "Failed to notify user {} of training adjustment {}: {}",
+                adjustment.user_id,
+                adjustment.id,
+                e
+            );
+        }
+    }
+}
+
+/// Title and message telling the athlete what changed
+pub fn adjustment_message(kind: &str, original: &WorkoutDay, adjusted: Option<&WorkoutDay>) -> (String, String) {
+    let original_label = workout_type_label(&original.workout_type);
+    let undo = "You can undo this from your adjustment history.";
+
+    match (kind.parse::<AutoAdjustmentKind>().ok(), adjusted) {
+        (Some(AutoAdjustmentKind::ReduceVolume), Some(adjusted)) => (
+            "Today's workout was shortened".to_string(),
+            format!(
+                "Based on your recovery, today's {} session is now {} min instead of {} min. {}",
+                original_label.to_lowercase(),
+                adjusted.duration_minutes,
+                original.duration_minutes,
+                undo
+            ),
+        ),
+        (Some(AutoAdjustmentKind::ReduceIntensity), Some(adjusted)) => (
+            "Today's workout was eased".to_string(),
+            format!(
+                "Based on your recovery, today's {} session has lower targets and runs {} min. {}",
+                original_label.to_lowercase(),
+                adjusted.duration_minutes,
+                undo
+            ),
+        ),
+        (Some(AutoAdjustmentKind::SwapWorkout), Some(adjusted)) => (
+            "Today's workout was swapped".to_string(),
+            format!(
+                "Based on your recovery, today's {} session was replaced with {} min of {}. {}",
+                original_label.to_lowercase(),
+                adjusted.duration_minutes,
+                workout_type_label(&adjusted.workout_type).to_lowercase(),
+                undo
+            ),
+        ),
+        _ => (
+            "Today is now a rest day".to_string(),
+            format!(
+                "Based on your recovery, today's {} session ({} min) was replaced with rest. {}",
+                original_label.to_lowercase(),
+                original.duration_minutes,
+                undo
+            ),
+        ),
+    }
+}
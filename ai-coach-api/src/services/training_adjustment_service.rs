@@ -1,10 +1,31 @@
 use anyhow::{Context, Result};
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sqlx::{FromRow, PgPool};
+use std::collections::HashSet;
 use uuid::Uuid;
 
+use crate::models::{
+    AdjustmentAggressiveness, HeartRateTargets, IntensityZone, PlanWeekStructure, PowerTargets,
+    TrainingAdjustment, TrainingRecoverySettings, WorkoutDay, WorkoutType,
+};
+use crate::services::calendar_feed_service::plan_day_date;
+use crate::services::workout_export_service::workout_type_label;
+
+const ADJUSTMENT_COLUMNS: &str = r#"
+    id, user_id, adjustment_date, recovery_score_id, plan_id, week_number, day_of_week,
+    original_tss, recommended_tss, adjustment_applied, adjustment_type, original_workout,
+    adjusted_workout, reasoning, automatic, applied_at, reverted_at, created_at
+"#;
+
+/// Adjusted workouts are not shortened below this, unless planned shorter
+const MIN_WORKOUT_MINUTES: i32 = 20;
+
+/// Intensity reductions also trim duration by this factor, as suggested modifications do
+const INTENSITY_DURATION_FACTOR: f64 = 0.9;
+
 /// Training adjustment service for recovery-based workout modifications
+#[derive(Clone)]
 pub struct TrainingAdjustmentService {
     db: PgPool,
 }
@@ -46,6 +67,89 @@ pub struct RestDayRecommendation {
     pub alternative_action: Option<String>,
 }
 
+/// Change the auto-adjust job makes to a planned workout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoAdjustmentKind {
+    NoChange,
+    RestDay,
+    ReduceIntensity,
+    ReduceVolume,
+    SwapWorkout,
+}
+
+impl AutoAdjustmentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AutoAdjustmentKind::NoChange => "no_change",
+            AutoAdjustmentKind::RestDay => "rest_day",
+            AutoAdjustmentKind::ReduceIntensity => "reduce_intensity",
+            AutoAdjustmentKind::ReduceVolume => "reduce_volume",
+            AutoAdjustmentKind::SwapWorkout => "swap_workout",
+        }
+    }
+}
+
+impl std::str::FromStr for AutoAdjustmentKind {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "no_change" => Ok(AutoAdjustmentKind::NoChange),
+            "rest_day" => Ok(AutoAdjustmentKind::RestDay),
+            "reduce_intensity" => Ok(AutoAdjustmentKind::ReduceIntensity),
+            "reduce_volume" => Ok(AutoAdjustmentKind::ReduceVolume),
+            "swap_workout" => Ok(AutoAdjustmentKind::SwapWorkout),
+            other => Err(anyhow::anyhow!("Unknown adjustment type: {}", other)),
+        }
+    }
+}
+
+/// Decision for one planned workout
+#[derive(Debug, Clone)]
+pub struct AutoAdjustment {
+    pub kind: AutoAdjustmentKind,
+    /// The workout after the change; `None` for a rest day or no change
+    pub adjusted_workout: Option<WorkoutDay>,
+    pub planned_tss: f64,
+    pub recommended_tss: f64,
+    pub reasoning: String,
+}
+
+/// Everything the auto-adjust decision looks at besides the workout itself
+#[derive(Debug, Clone)]
+pub struct AutoAdjustContext<'a> {
+    pub settings: &'a TrainingRecoverySettings,
+    pub tss_adjustment: &'a TssAdjustment,
+    pub rest_recommendation: &'a RestDayRecommendation,
+    /// Days trained in a row, up to the day before
+    pub consecutive_training_days: i32,
+    /// Days without a planned workout in the plan week
+    pub planned_rest_days: i32,
+}
+
+/// Why an automatic adjustment could not be undone
+#[derive(Debug, thiserror::Error)]
+pub enum RevertAdjustmentError {
+    #[error("Adjustment not found")]
+    NotFound,
+    #[error("Adjustment is not applied or was already reverted")]
+    NotRevertible,
+    #[error("The workout was changed after it was adjusted")]
+    PlanChanged,
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[derive(Debug, FromRow)]
+struct ActivePlanRow {
+    id: Uuid,
+    start_date: NaiveDate,
+    plan_structure: serde_json::Value,
+    updated_at: DateTime<Utc>,
+}
+
 impl TrainingAdjustmentService {
     pub fn new(db: PgPool) -> Self {
         Self { db }
@@ -182,62 +286,7 @@ impl TrainingAdjustmentService {
             .calculate_daily_tss_adjustment(user_id, target_date, planned_workout.tss)
             .await?;
 
-        // Determine modification type based on adjustment factor
-        let modification = if adjustment.adjustment_factor < 0.5 {
-            // Critical - suggest rest day
-            WorkoutModification {
-                modification_type: "rest_day".to_string(),
-                original_workout: Some(planned_workout.clone()),
-                suggested_workout: None,
-                reasoning: format!(
-                    "Recovery is critical (readiness < 30). Rest is strongly recommended. {}",
-                    adjustment.explanation
-                ),
-            }
-        } else if adjustment.adjustment_factor < 0.8 {
-            // Poor - reduce intensity significantly
-            let mut modified = planned_workout.clone();
-            modified.tss = adjustment.recommended_tss;
-            if let Some(if_val) = modified.intensity_factor {
-                modified.intensity_factor = Some(if_val * 0.8); // Reduce intensity
-            }
-            modified.duration_minutes = (modified.duration_minutes as f64 * 0.9) as i32; // Slight duration reduction
-
-            WorkoutModification {
-                modification_type: "reduce_intensity".to_string(),
-                original_workout: Some(planned_workout),
-                suggested_workout: Some(modified),
-                reasoning: format!(
-                    "Recovery is poor. Reduce intensity to allow for better adaptation. {}",
-                    adjustment.explanation
-                ),
-            }
-        } else if adjustment.adjustment_factor < 1.0 {
-            // Moderate - reduce volume
-            let mut modified = planned_workout.clone();
-            modified.tss = adjustment.recommended_tss;
-            modified.duration_minutes = (modified.duration_minutes as f64 * adjustment.adjustment_factor) as i32;
-
-            WorkoutModification {
-                modification_type: "reduce_volume".to_string(),
-                original_workout: Some(planned_workout),
-                suggested_workout: Some(modified),
-                reasoning: format!(
-                    "Recovery is moderate. Reduce volume to manage training stress. {}",
-                    adjustment.explanation
-                ),
-            }
-        } else {
-            // Good or excellent - no modification or can increase
-            WorkoutModification {
-                modification_type: "no_change".to_string(),
-                original_workout: Some(planned_workout),
-                suggested_workout: None,
-                reasoning: format!("Recovery is good. Proceed as planned. {}", adjustment.explanation),
-            }
-        };
-
-        Ok(modification)
+        Ok(modification_for_adjustment(planned_workout, &adjustment))
     }
 
     /// Determine if a rest day should be scheduled
@@ -358,4 +407,981 @@ impl TrainingAdjustmentService {
             alternative_action,
         })
     }
+
+    /// Decide and apply the day's recovery-driven change to the user's active generated plan.
+    /// Returns `None` when there is nothing to decide: the day was already handled, or there is
+    /// no recovery score, active plan or planned workout for it yet.
+    pub async fn auto_adjust_day(
+        &self,
+        settings: &TrainingRecoverySettings,
+        date: NaiveDate,
+    ) -> Result<Option<TrainingAdjustment>> {
+        let user_id = settings.user_id;
+
+        let decided = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM training_adjustments
+                WHERE user_id = $1 AND adjustment_date = $2 AND automatic
+            )
+            "#,
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_one(&self.db)
+        .await
+        .context("Failed to check earlier adjustments")?;
+
+        if decided {
+            return Ok(None);
+        }
+
+        let recovery_score_id = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM recovery_scores WHERE user_id = $1 AND score_date = $2",
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_optional(&self.db)
+        .await
+        .context("Failed to fetch recovery score")?;
+
+        let recovery_score_id = match recovery_score_id {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        let plan = sqlx::query_as::<_, ActivePlanRow>(
+            r#"
+            SELECT id, start_date, plan_structure, updated_at
+            FROM generated_plans
+            WHERE user_id = $1 AND status = 'active' AND start_date <= $2 AND end_date >= $2
+            ORDER BY updated_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_optional(&self.db)
+        .await
+        .context("Failed to fetch active plan")?;
+
+        let plan = match plan {
+            Some(plan) => plan,
+            None => return Ok(None),
+        };
+
+        let mut weeks: Vec<PlanWeekStructure> = serde_json::from_value(plan.plan_structure.clone())
+            .with_context(|| format!("Invalid structure in plan {}", plan.id))?;
+
+        let (week_index, day_index) = match find_plan_workout(&weeks, plan.start_date, date) {
+            Some(position) => position,
+            None => return Ok(None),
+        };
+
+        let week = &weeks[week_index];
+        let workout = week.workout_days[day_index].clone();
+        let planned_days: HashSet<i32> = week.workout_days.iter().map(|day| day.day_of_week).collect();
+
+        let tss_adjustment = self
+            .calculate_daily_tss_adjustment(user_id, date, estimate_workout_tss(&workout))
+            .await?;
+        let rest_recommendation = self.should_schedule_rest_day(user_id, date).await?;
+        let consecutive_training_days = self
+            .consecutive_training_days(user_id, date, settings.max_consecutive_training_days)
+            .await?;
+
+        let decision = decide_auto_adjustment(
+            &workout,
+            &AutoAdjustContext {
+                settings,
+                tss_adjustment: &tss_adjustment,
+                rest_recommendation: &rest_recommendation,
+                consecutive_training_days,
+                planned_rest_days: 7 - planned_days.len() as i32,
+            },
+        );
+        let applied = decision.kind != AutoAdjustmentKind::NoChange;
+        let week_number = weeks[week_index].week_number;
+
+        let mut tx = self.db.begin().await?;
+
+        if applied {
+            apply_to_week(&mut weeks[week_index], day_index, decision.adjusted_workout.clone());
+
+            // Only write over the plan as it was read; a concurrent edit wins and we retry later
+            let result = sqlx::query(
+                "UPDATE generated_plans SET plan_structure = $2 WHERE id = $1 AND updated_at = $3",
+            )
+            .bind(plan.id)
+            .bind(serde_json::to_value(&weeks)?)
+            .bind(plan.updated_at)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to update plan")?;
+
+            if result.rows_affected() == 0 {
+                anyhow::bail!("Plan {} changed while it was being adjusted", plan.id);
+            }
+        }
+
+        let adjustment = sqlx::query_as::<_, TrainingAdjustment>(&format!(
+            r#"
+            INSERT INTO training_adjustments
+                (user_id, adjustment_date, recovery_score_id, plan_id, week_number, day_of_week,
+                 original_tss, recommended_tss, adjustment_applied, adjustment_type,
+                 original_workout, adjusted_workout, reasoning, automatic, applied_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, TRUE, CASE WHEN $9 THEN NOW() END)
+            ON CONFLICT (user_id, adjustment_date) WHERE automatic DO NOTHING
+            RETURNING {}
+            "#,
+            ADJUSTMENT_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(date)
+        .bind(recovery_score_id)
+        .bind(plan.id)
+        .bind(week_number)
+        .bind(workout.day_of_week)
+        .bind(decision.planned_tss)
+        .bind(decision.recommended_tss)
+        .bind(applied)
+        .bind(decision.kind.as_str())
+        .bind(serde_json::to_value(&workout)?)
+        .bind(decision.adjusted_workout.as_ref().map(serde_json::to_value).transpose()?)
+        .bind(&decision.reasoning)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to record adjustment")?;
+
+        // Another run decided the day first; dropping the transaction undoes our plan change
+        let adjustment = match adjustment {
+            Some(adjustment) => adjustment,
+            None => return Ok(None),
+        };
+
+        tx.commit().await?;
+
+        if applied {
+            tracing::info!(
+                "Applied {} to plan {} for user {} on {}",
+                decision.kind.as_str(),
+                plan.id,
+                user_id,
+                date
+            );
+        }
+
+        Ok(Some(adjustment))
+    }
+
+    /// Undo an applied adjustment, restoring the originally planned workout. Fails if the
+    /// workout was edited since, so the athlete's own changes are never overwritten.
+    pub async fn revert_adjustment(
+        &self,
+        user_id: Uuid,
+        adjustment_id: Uuid,
+    ) -> Result<TrainingAdjustment, RevertAdjustmentError> {
+        let mut tx = self.db.begin().await?;
+
+        let adjustment = sqlx::query_as::<_, TrainingAdjustment>(&format!(
+            "SELECT {} FROM training_adjustments WHERE id = $1 AND user_id = $2 FOR UPDATE",
+            ADJUSTMENT_COLUMNS
+        ))
+        .bind(adjustment_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(RevertAdjustmentError::NotFound)?;
+
+        if !adjustment.can_revert() {
+            return Err(RevertAdjustmentError::NotRevertible);
+        }
+
+        let (plan_id, week_number, day_of_week, original_workout) = match (
+            adjustment.plan_id,
+            adjustment.week_number,
+            adjustment.day_of_week,
+            adjustment.original_workout.clone(),
+        ) {
+            (Some(plan_id), Some(week_number), Some(day_of_week), Some(original)) => {
+                (plan_id, week_number, day_of_week, original)
+            }
+            _ => return Err(RevertAdjustmentError::NotRevertible),
+        };
+
+        let original: WorkoutDay = serde_json::from_value(original_workout)
+            .context("Invalid original workout")?;
+        let adjusted: Option<WorkoutDay> = adjustment
+            .adjusted_workout
+            .clone()
+            .map(serde_json::from_value)
+            .transpose()
+            .context("Invalid adjusted workout")?;
+
+        let plan_structure = sqlx::query_scalar::<_, serde_json::Value>(
+            "SELECT plan_structure FROM generated_plans WHERE id = $1 FOR UPDATE",
+        )
+        .bind(plan_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(RevertAdjustmentError::PlanChanged)?;
+
+        let mut weeks: Vec<PlanWeekStructure> = serde_json::from_value(plan_structure)
+            .context("Invalid plan structure")?;
+        let week = weeks
+            .iter_mut()
+            .find(|week| week.week_number == week_number)
+            .ok_or(RevertAdjustmentError::PlanChanged)?;
+
+        if !restore_workout(week, day_of_week, original, adjusted.as_ref()) {
+            return Err(RevertAdjustmentError::PlanChanged);
+        }
+
+        sqlx::query("UPDATE generated_plans SET plan_structure = $2 WHERE id = $1")
+            .bind(plan_id)
+            .bind(serde_json::to_value(&weeks).context("Failed to serialize plan")?)
+            .execute(&mut *tx)
+            .await?;
+
+        let adjustment = sqlx::query_as::<_, TrainingAdjustment>(&format!(
+            r#"
+            UPDATE training_adjustments
+            SET adjustment_applied = FALSE, reverted_at = NOW()
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            ADJUSTMENT_COLUMNS
+        ))
+        .bind(adjustment_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!("Reverted adjustment {} of plan {} for user {}", adjustment_id, plan_id, user_id);
+        Ok(adjustment)
+    }
+
+    /// Most recent adjustments first
+    pub async fn list_adjustments(&self, user_id: Uuid, limit: i64) -> Result<Vec<TrainingAdjustment>> {
+        let adjustments = sqlx::query_as::<_, TrainingAdjustment>(&format!(
+            r#"
+            SELECT {} FROM training_adjustments
+            WHERE user_id = $1
+            ORDER BY adjustment_date DESC, created_at DESC
+            LIMIT $2
+            "#,
+            ADJUSTMENT_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to fetch adjustments")?;
+
+        Ok(adjustments)
+    }
+
+    /// Days with a training session in a row, ending the day before `date`
+    async fn consecutive_training_days(&self, user_id: Uuid, date: NaiveDate, max_days: i32) -> Result<i32> {
+        let dates = sqlx::query_scalar::<_, NaiveDate>(
+            r#"
+            SELECT DISTINCT date FROM training_sessions
+            WHERE user_id = $1 AND date < $2 AND date >= $3
+            ORDER BY date DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(date)
+        .bind(date - Duration::days(max_days as i64 + 1))
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to fetch recent training days")?;
+
+        Ok(consecutive_days_before(&dates, date))
+    }
+}
+
+/// Modification matching a TSS adjustment: rest below 0.5, intensity reduction below 0.8,
+/// volume reduction below 1.0
+pub fn modification_for_adjustment(planned_workout: WorkoutSummary, adjustment: &TssAdjustment) -> WorkoutModification {
+    // Determine modification type based on adjustment factor
+    if adjustment.adjustment_factor < 0.5 {
+        // Critical - suggest rest day
+        WorkoutModification {
+            modification_type: "rest_day".to_string(),
+            original_workout: Some(planned_workout.clone()),
+            suggested_workout: None,
+            reasoning: format!(
+                "Recovery is critical (readiness < 30). Rest is strongly recommended. {}",
+                adjustment.explanation
+            ),
+        }
+    } else if adjustment.adjustment_factor < 0.8 {
+        // Poor - reduce intensity significantly
+        let mut modified = planned_workout.clone();
+        modified.tss = adjustment.recommended_tss;
+        if let Some(if_val) = modified.intensity_factor {
+            modified.intensity_factor = Some(if_val * 0.8); // Reduce intensity
+        }
+        modified.duration_minutes = (modified.duration_minutes as f64 * 0.9) as i32; // Slight duration reduction
+
+        WorkoutModification {
+            modification_type: "reduce_intensity".to_string(),
+            original_workout: Some(planned_workout),
+            suggested_workout: Some(modified),
+            reasoning: format!(
+                "Recovery is poor. Reduce intensity to allow for better adaptation. {}",
+                adjustment.explanation
+            ),
+        }
+    } else if adjustment.adjustment_factor < 1.0 {
+        // Moderate - reduce volume
+        let mut modified = planned_workout.clone();
+        modified.tss = adjustment.recommended_tss;
+        modified.duration_minutes = (modified.duration_minutes as f64 * adjustment.adjustment_factor) as i32;
+
+        WorkoutModification {
+            modification_type: "reduce_volume".to_string(),
+            original_workout: Some(planned_workout),
+            suggested_workout: Some(modified),
+            reasoning: format!(
+                "Recovery is moderate. Reduce volume to manage training stress. {}",
+                adjustment.explanation
+            ),
+        }
+    } else {
+        // Good or excellent - no modification or can increase
+        WorkoutModification {
+            modification_type: "no_change".to_string(),
+            original_workout: Some(planned_workout),
+            suggested_workout: None,
+            reasoning: format!("Recovery is good. Proceed as planned. {}", adjustment.explanation),
+        }
+    }
+}
+
+/// Decide how to change a planned workout given the day's recovery, within what the athlete's
+/// settings allow. Races and tests are never changed; rest days are forced by the
+/// consecutive-day and weekly rest limits; otherwise the suggested modification is applied,
+/// falling back to another permitted kind of reduction.
+pub fn decide_auto_adjustment(workout: &WorkoutDay, context: &AutoAdjustContext) -> AutoAdjustment {
+    let settings = context.settings;
+    let aggressiveness = settings.aggressiveness();
+    let planned_tss = estimate_workout_tss(workout);
+
+    let decision = |kind, adjusted_workout: Option<WorkoutDay>, reasoning: String| {
+        let recommended_tss = match (&kind, &adjusted_workout) {
+            (AutoAdjustmentKind::RestDay, _) => 0.0,
+            (_, Some(adjusted)) => estimate_workout_tss(adjusted),
+            (_, None) => planned_tss,
+        };
+        AutoAdjustment { kind, adjusted_workout, planned_tss, recommended_tss, reasoning }
+    };
+
+    if matches!(workout.workout_type, WorkoutType::Race | WorkoutType::Test) {
+        return decision(
+            AutoAdjustmentKind::NoChange,
+            None,
+            "Races and tests are not adjusted automatically".to_string(),
+        );
+    }
+
+    if context.consecutive_training_days >= settings.max_consecutive_training_days {
+        return decision(
+            AutoAdjustmentKind::RestDay,
+            None,
+            format!(
+                "{} consecutive training days (your maximum is {})",
+                context.consecutive_training_days, settings.max_consecutive_training_days
+            ),
+        );
+    }
+
+    let rest = context.rest_recommendation;
+    if rest.should_rest && rest.confidence >= rest_confidence_threshold(aggressiveness) {
+        return decision(AutoAdjustmentKind::RestDay, None, rest.reasoning.clone());
+    }
+
+    let factor = scaled_adjustment_factor(aggressiveness, context.tss_adjustment.adjustment_factor);
+    if factor >= 1.0 {
+        return decision(AutoAdjustmentKind::NoChange, None, context.tss_adjustment.explanation.clone());
+    }
+
+    if context.planned_rest_days < settings.min_rest_days_per_week {
+        return decision(
+            AutoAdjustmentKind::RestDay,
+            None,
+            format!(
+                "Recovery is reduced and this week has {} rest days planned (your minimum is {})",
+                context.planned_rest_days, settings.min_rest_days_per_week
+            ),
+        );
+    }
+
+    let scaled = TssAdjustment {
+        original_tss: planned_tss,
+        recommended_tss: planned_tss * factor,
+        adjustment_factor: factor,
+        ..context.tss_adjustment.clone()
+    };
+    let summary = WorkoutSummary {
+        workout_type: workout_type_label(&workout.workout_type).to_string(),
+        tss: planned_tss,
+        duration_minutes: workout.duration_minutes,
+        intensity_factor: Some(zone_intensity_factor(&workout.intensity_zone)),
+    };
+    let modification = modification_for_adjustment(summary, &scaled);
+
+    let swap = settings.allow_workout_swap && is_hard_workout(workout);
+    let candidates: &[AutoAdjustmentKind] = match modification.modification_type.as_str() {
+        "rest_day" => &[AutoAdjustmentKind::RestDay],
+        "reduce_intensity" if swap => &[AutoAdjustmentKind::SwapWorkout],
+        "reduce_intensity" => &[AutoAdjustmentKind::ReduceIntensity, AutoAdjustmentKind::ReduceVolume],
+        "reduce_volume" => &[AutoAdjustmentKind::ReduceVolume, AutoAdjustmentKind::ReduceIntensity],
+        _ => &[],
+    };
+
+    let permitted = candidates.iter().copied().find(|kind| match kind {
+        AutoAdjustmentKind::ReduceIntensity => settings.allow_intensity_reduction,
+        AutoAdjustmentKind::ReduceVolume => settings.allow_volume_reduction,
+        _ => true,
+    });
+
+    let (kind, mut adjusted) = match permitted {
+        Some(AutoAdjustmentKind::RestDay) => {
+            return decision(AutoAdjustmentKind::RestDay, None, modification.reasoning);
+        }
+        Some(kind @ AutoAdjustmentKind::SwapWorkout) => (kind, swap_to_easy(workout, planned_tss * factor, factor)),
+        Some(kind @ AutoAdjustmentKind::ReduceIntensity) => (kind, reduce_intensity(workout, factor)),
+        Some(kind @ AutoAdjustmentKind::ReduceVolume) => (kind, reduce_volume(workout, factor)),
+        _ if candidates.is_empty() => {
+            return decision(AutoAdjustmentKind::NoChange, None, modification.reasoning);
+        }
+        _ => {
+            return decision(
+                AutoAdjustmentKind::NoChange,
+                None,
+                format!("{} Not applied: your settings don't allow this change.", modification.reasoning),
+            );
+        }
+    };
+
+    let note = format!("Adjusted for recovery ({})", kind.as_str().replace('_', " "));
+    adjusted.notes = Some(match adjusted.notes.take() {
+        Some(notes) => format!("{}\n{}", notes, note),
+        None => note,
+    });
+
+    decision(kind, Some(adjusted), modification.reasoning)
+}
+
+/// Conservative settings make half the suggested reduction, aggressive ones one and a half
+/// times it; recovery never raises the planned load automatically
+fn scaled_adjustment_factor(aggressiveness: AdjustmentAggressiveness, factor: f64) -> f64 {
+    if factor >= 1.0 {
+        return 1.0;
+    }
+
+    let scale = match aggressiveness {
+        AdjustmentAggressiveness::Conservative => 0.5,
+        AdjustmentAggressiveness::Moderate => 1.0,
+        AdjustmentAggressiveness::Aggressive => 1.5,
+    };
+    (1.0 - (1.0 - factor) * scale).clamp(0.3, 1.0)
+}
+
+/// Confidence a rest recommendation needs before a workout is replaced by rest
+fn rest_confidence_threshold(aggressiveness: AdjustmentAggressiveness) -> f64 {
+    match aggressiveness {
+        AdjustmentAggressiveness::Conservative => 0.9,
+        AdjustmentAggressiveness::Moderate => 0.8,
+        AdjustmentAggressiveness::Aggressive => 0.7,
+    }
+}
+
+/// Typical intensity factor of a plan zone
+fn zone_intensity_factor(zone: &IntensityZone) -> f64 {
+    match zone {
+        IntensityZone::Zone1 => 0.55,
+        IntensityZone::Zone2 => 0.70,
+        IntensityZone::Zone3 => 0.83,
+        IntensityZone::Zone4 => 0.95,
+        IntensityZone::Zone5 => 1.10,
+        IntensityZone::Zone6 => 1.25,
+        IntensityZone::Mixed => 0.85,
+    }
+}
+
+fn zone_for_intensity_factor(intensity_factor: f64) -> IntensityZone {
+    if intensity_factor >= 1.25 {
+        IntensityZone::Zone6
+    } else if intensity_factor >= 1.10 {
+        IntensityZone::Zone5
+    } else if intensity_factor >= 0.95 {
+        IntensityZone::Zone4
+    } else if intensity_factor >= 0.83 {
+        IntensityZone::Zone3
+    } else if intensity_factor >= 0.70 {
+        IntensityZone::Zone2
+    } else {
+        IntensityZone::Zone1
+    }
+}
+
+/// Estimated TSS of a planned workout: hours x IF^2 x 100
+pub fn estimate_workout_tss(workout: &WorkoutDay) -> f64 {
+    let intensity_factor = zone_intensity_factor(&workout.intensity_zone);
+    workout.duration_minutes as f64 / 60.0 * intensity_factor * intensity_factor * 100.0
+}
+
+/// Tempo or harder
+fn is_hard_workout(workout: &WorkoutDay) -> bool {
+    zone_intensity_factor(&workout.intensity_zone) >= zone_intensity_factor(&IntensityZone::Zone3)
+}
+
+fn scaled_minutes(duration_minutes: i32, factor: f64) -> i32 {
+    ((duration_minutes as f64 * factor).round() as i32).max(MIN_WORKOUT_MINUTES.min(duration_minutes))
+}
+
+/// Same workout, shorter
+fn reduce_volume(workout: &WorkoutDay, factor: f64) -> WorkoutDay {
+    let mut adjusted = workout.clone();
+    adjusted.duration_minutes = scaled_minutes(workout.duration_minutes, factor);
+    adjusted
+}
+
+/// Slightly shorter, with targets lowered so the estimated TSS matches the recommendation
+fn reduce_intensity(workout: &WorkoutDay, factor: f64) -> WorkoutDay {
+    let intensity_scale = (factor / INTENSITY_DURATION_FACTOR).sqrt().min(1.0);
+    let mut adjusted = workout.clone();
+
+    adjusted.duration_minutes = scaled_minutes(workout.duration_minutes, INTENSITY_DURATION_FACTOR);
+    adjusted.intensity_zone =
+        zone_for_intensity_factor(zone_intensity_factor(&workout.intensity_zone) * intensity_scale);
+
+    if let Some(power) = adjusted.power_targets.as_mut() {
+        power.ftp_percentage_low *= intensity_scale;
+        power.ftp_percentage_high *= intensity_scale;
+        power.average_watts = power.average_watts.map(|watts| watts * intensity_scale);
+        power.normalized_power = power.normalized_power.map(|watts| watts * intensity_scale);
+    }
+    if let Some(heart_rate) = adjusted.heart_rate_targets.as_mut() {
+        heart_rate.hr_percentage_low *= intensity_scale;
+        heart_rate.hr_percentage_high *= intensity_scale;
+        heart_rate.average_hr = heart_rate.average_hr.map(|hr| hr * intensity_scale);
+    }
+    // Paces don't scale linearly with effort; the zone and other targets guide the session
+    adjusted.pace_targets = None;
+
+    adjusted
+}
+
+/// Replace a hard session with an endurance ride, or recovery when the reduction is large,
+/// no longer than the recommended TSS allows
+fn swap_to_easy(workout: &WorkoutDay, recommended_tss: f64, factor: f64) -> WorkoutDay {
+    let (workout_type, intensity_zone, power, heart_rate) = if factor < 0.6 {
+        (WorkoutType::Recovery, IntensityZone::Zone1, (40.0, 55.0), (50.0, 68.0))
+    } else {
+        (WorkoutType::Endurance, IntensityZone::Zone2, (56.0, 75.0), (69.0, 83.0))
+    };
+
+    let intensity_factor = zone_intensity_factor(&intensity_zone);
+    let tss_minutes = (recommended_tss / (intensity_factor * intensity_factor * 100.0) * 60.0).round() as i32;
+
+    let mut adjusted = workout.clone();
+    adjusted.duration_minutes = tss_minutes
+        .min(workout.duration_minutes)
+        .max(MIN_WORKOUT_MINUTES.min(workout.duration_minutes));
+    adjusted.workout_description = format!(
+        "{} session in place of {} for recovery",
+        workout_type_label(&workout_type),
+        workout_type_label(&workout.workout_type).to_lowercase()
+    );
+    adjusted.power_targets = workout.power_targets.as_ref().map(|_| PowerTargets {
+        ftp_percentage_low: power.0,
+        ftp_percentage_high: power.1,
+        average_watts: None,
+        normalized_power: None,
+    });
+    adjusted.heart_rate_targets = workout.heart_rate_targets.as_ref().map(|_| HeartRateTargets {
+        hr_percentage_low: heart_rate.0,
+        hr_percentage_high: heart_rate.1,
+        average_hr: None,
+    });
+    adjusted.pace_targets = None;
+    adjusted.workout_type = workout_type;
+    adjusted.intensity_zone = intensity_zone;
+
+    adjusted
+}
+
+/// Week and workout index of the first workout planned on `date`
+fn find_plan_workout(weeks: &[PlanWeekStructure], start_date: NaiveDate, date: NaiveDate) -> Option<(usize, usize)> {
+    weeks.iter().enumerate().find_map(|(week_index, week)| {
+        week.workout_days
+            .iter()
+            .position(|day| plan_day_date(start_date, week.week_number, day.day_of_week) == date)
+            .map(|day_index| (week_index, day_index))
+    })
+}
+
+/// Replace a workout with its adjusted version, or with rest
+fn apply_to_week(week: &mut PlanWeekStructure, day_index: usize, adjusted: Option<WorkoutDay>) {
+    match adjusted {
+        Some(adjusted) => week.workout_days[day_index] = adjusted,
+        None => {
+            let removed = week.workout_days.remove(day_index);
+            let day_of_week = removed.day_of_week;
+            if !week.workout_days.iter().any(|day| day.day_of_week == day_of_week)
+                && !week.rest_days.contains(&day_of_week)
+            {
+                week.rest_days.push(day_of_week);
+                week.rest_days.sort_unstable();
+            }
+        }
+    }
+}
+
+/// Put the original workout back in place of its adjusted version. Returns false when the
+/// adjusted workout is no longer in the plan as it was left, or when a rested day has a
+/// workout on it again, so nothing the athlete planned since is overwritten.
+fn restore_workout(
+    week: &mut PlanWeekStructure,
+    day_of_week: i32,
+    original: WorkoutDay,
+    adjusted: Option<&WorkoutDay>,
+) -> bool {
+    match adjusted {
+        Some(adjusted) => {
+            let position = week.workout_days.iter().position(|day| {
+                day.day_of_week == day_of_week && same_workout(day, adjusted)
+            });
+            match position {
+                Some(index) => {
+                    week.workout_days[index] = original;
+                    true
+                }
+                None => false,
+            }
+        }
+        None => {
+            if week.workout_days.iter().any(|day| day.day_of_week == day_of_week) {
+                return false;
+            }
+            week.rest_days.retain(|day| *day != day_of_week);
+            let index = week.workout_days
+                .iter()
+                .position(|day| day.day_of_week > day_of_week)
+                .unwrap_or(week.workout_days.len());
+            week.workout_days.insert(index, original);
+            true
+        }
+    }
+}
+
+fn same_workout(a: &WorkoutDay, b: &WorkoutDay) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Length of the run of consecutive days ending the day before `date`; `dates` are distinct
+/// and newest first
+fn consecutive_days_before(dates: &[NaiveDate], date: NaiveDate) -> i32 {
+    let mut expected = date - Duration::days(1);
+    let mut count = 0;
+
+    for &day in dates {
+        if day != expected {
+            break;
+        }
+        count += 1;
+        expected -= Duration::days(1);
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn threshold_workout() -> WorkoutDay {
+        WorkoutDay {
+            day_of_week: 3,
+            workout_type: WorkoutType::Threshold,
+            duration_minutes: 90,
+            intensity_zone: IntensityZone::Zone4,
+            workout_description: "3x15 min at threshold".to_string(),
+            power_targets: Some(PowerTargets {
+                ftp_percentage_low: 95.0,
+                ftp_percentage_high: 105.0,
+                average_watts: None,
+                normalized_power: None,
+            }),
+            heart_rate_targets: None,
+            pace_targets: None,
+            equipment_needed: vec![],
+            notes: None,
+        }
+    }
+
+    fn tss_adjustment(factor: f64) -> TssAdjustment {
+        TssAdjustment {
+            original_tss: 100.0,
+            recommended_tss: 100.0 * factor,
+            adjustment_factor: factor,
+            explanation: "explanation".to_string(),
+            reasoning: vec![],
+        }
+    }
+
+    fn no_rest() -> RestDayRecommendation {
+        RestDayRecommendation {
+            should_rest: false,
+            confidence: 0.5,
+            reasoning: "Recovery is adequate".to_string(),
+            alternative_action: None,
+        }
+    }
+
+    fn decide(settings: &TrainingRecoverySettings, factor: f64, rest: &RestDayRecommendation) -> AutoAdjustment {
+        decide_auto_adjustment(
+            &threshold_workout(),
+            &AutoAdjustContext {
+                settings,
+                tss_adjustment: &tss_adjustment(factor),
+                rest_recommendation: rest,
+                consecutive_training_days: 2,
+                planned_rest_days: 2,
+            },
+        )
+    }
+
+    #[test]
+    fn test_good_recovery_keeps_workout() {
+        let decision = decide(&TrainingRecoverySettings::default(), 1.1, &no_rest());
+        assert_eq!(decision.kind, AutoAdjustmentKind::NoChange);
+        assert!(decision.adjusted_workout.is_none());
+    }
+
+    #[test]
+    fn test_moderate_recovery_reduces_volume() {
+        let decision = decide(&TrainingRecoverySettings::default(), 0.85, &no_rest());
+        assert_eq!(decision.kind, AutoAdjustmentKind::ReduceVolume);
+
+        let adjusted = decision.adjusted_workout.unwrap();
+        assert_eq!(adjusted.duration_minutes, 77);
+        assert!(matches!(adjusted.intensity_zone, IntensityZone::Zone4));
+        assert!((decision.recommended_tss - decision.planned_tss * 77.0 / 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_poor_recovery_lowers_targets() {
+        let decision = decide(&TrainingRecoverySettings::default(), 0.7, &no_rest());
+        assert_eq!(decision.kind, AutoAdjustmentKind::ReduceIntensity);
+
+        let adjusted = decision.adjusted_workout.unwrap();
+        assert_eq!(adjusted.duration_minutes, 81);
+        assert!(matches!(adjusted.intensity_zone, IntensityZone::Zone3));
+        let power = adjusted.power_targets.unwrap();
+        assert!(power.ftp_percentage_high < 95.0);
+        assert!((decision.recommended_tss / decision.planned_tss - 0.7).abs() < 0.02);
+        assert!(adjusted.notes.unwrap().contains("reduce intensity"));
+    }
+
+    #[test]
+    fn test_swap_when_allowed() {
+        let settings = TrainingRecoverySettings {
+            allow_workout_swap: true,
+            ..Default::default()
+        };
+        let decision = decide(&settings, 0.7, &no_rest());
+        assert_eq!(decision.kind, AutoAdjustmentKind::SwapWorkout);
+
+        let adjusted = decision.adjusted_workout.unwrap();
+        assert!(matches!(adjusted.workout_type, WorkoutType::Endurance));
+        assert!(matches!(adjusted.intensity_zone, IntensityZone::Zone2));
+        assert!(adjusted.duration_minutes <= 90);
+        assert_eq!(adjusted.power_targets.unwrap().ftp_percentage_high, 75.0);
+    }
+
+    #[test]
+    fn test_falls_back_to_permitted_reduction() {
+        let settings = TrainingRecoverySettings {
+            allow_intensity_reduction: false,
+            ..Default::default()
+        };
+        assert_eq!(decide(&settings, 0.7, &no_rest()).kind, AutoAdjustmentKind::ReduceVolume);
+
+        let settings = TrainingRecoverySettings {
+            allow_intensity_reduction: false,
+            allow_volume_reduction: false,
+            ..Default::default()
+        };
+        let decision = decide(&settings, 0.7, &no_rest());
+        assert_eq!(decision.kind, AutoAdjustmentKind::NoChange);
+        assert!(decision.reasoning.contains("Not applied"));
+    }
+
+    #[test]
+    fn test_aggressiveness_scales_reduction() {
+        let conservative = TrainingRecoverySettings {
+            adjustment_aggressiveness: "conservative".to_string(),
+            ..Default::default()
+        };
+        // 0.7 halved to 0.85: volume instead of intensity
+        assert_eq!(decide(&conservative, 0.7, &no_rest()).kind, AutoAdjustmentKind::ReduceVolume);
+
+        let aggressive = TrainingRecoverySettings {
+            adjustment_aggressiveness: "aggressive".to_string(),
+            ..Default::default()
+        };
+        // 0.85 stretched to 0.775: intensity instead of volume
+        assert_eq!(decide(&aggressive, 0.85, &no_rest()).kind, AutoAdjustmentKind::ReduceIntensity);
+        assert_eq!(scaled_adjustment_factor(AdjustmentAggressiveness::Aggressive, 0.3), 0.3);
+        assert_eq!(scaled_adjustment_factor(AdjustmentAggressiveness::Aggressive, 1.2), 1.0);
+    }
+
+    #[test]
+    fn test_rest_day_rules() {
+        let settings = TrainingRecoverySettings::default();
+        let rest = RestDayRecommendation {
+            should_rest: true,
+            confidence: 0.85,
+            reasoning: "Poor recovery for 3 consecutive days".to_string(),
+            alternative_action: None,
+        };
+        assert_eq!(decide(&settings, 0.9, &rest).kind, AutoAdjustmentKind::RestDay);
+
+        // Conservative settings need more confidence
+        let conservative = TrainingRecoverySettings {
+            adjustment_aggressiveness: "conservative".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(decide(&conservative, 1.0, &rest).kind, AutoAdjustmentKind::NoChange);
+
+        // Critical recovery
+        assert_eq!(decide(&settings, 0.4, &no_rest()).kind, AutoAdjustmentKind::RestDay);
+
+        let context = |consecutive_training_days, planned_rest_days, factor| {
+            decide_auto_adjustment(
+                &threshold_workout(),
+                &AutoAdjustContext {
+                    settings: &settings,
+                    tss_adjustment: &tss_adjustment(factor),
+                    rest_recommendation: &no_rest(),
+                    consecutive_training_days,
+                    planned_rest_days,
+                },
+            )
+        };
+        // Too many days in a row, even when recovered
+        assert_eq!(context(6, 2, 1.1).kind, AutoAdjustmentKind::RestDay);
+        // No rest planned this week and recovery is reduced
+        assert_eq!(context(2, 0, 0.9).kind, AutoAdjustmentKind::RestDay);
+        assert_eq!(context(2, 0, 1.0).kind, AutoAdjustmentKind::NoChange);
+    }
+
+    #[test]
+    fn test_races_are_not_adjusted() {
+        let mut race = threshold_workout();
+        race.workout_type = WorkoutType::Race;
+        let settings = TrainingRecoverySettings::default();
+
+        let decision = decide_auto_adjustment(
+            &race,
+            &AutoAdjustContext {
+                settings: &settings,
+                tss_adjustment: &tss_adjustment(0.4),
+                rest_recommendation: &no_rest(),
+                consecutive_training_days: 10,
+                planned_rest_days: 0,
+            },
+        );
+        assert_eq!(decision.kind, AutoAdjustmentKind::NoChange);
+    }
+
+    #[test]
+    fn test_apply_and_restore_round_trip() {
+        let original = threshold_workout();
+        let mut endurance = threshold_workout();
+        endurance.day_of_week = 5;
+        endurance.workout_type = WorkoutType::Endurance;
+        let week = PlanWeekStructure {
+            week_number: 2,
+            phase_name: "Build".to_string(),
+            weekly_volume: 8.0,
+            weekly_intensity: 0.75,
+            workout_days: vec![original.clone(), endurance],
+            rest_days: vec![1, 7],
+            week_goals: vec![],
+            key_sessions: vec![],
+        };
+
+        // Rest day
+        let mut rested = week.clone();
+        apply_to_week(&mut rested, 0, None);
+        assert_eq!(rested.workout_days.len(), 1);
+        assert_eq!(rested.rest_days, vec![1, 3, 7]);
+
+        // Trained on the rested day since: refuse
+        let mut retrained = rested.clone();
+        let mut added = reduce_volume(&original, 0.5);
+        added.day_of_week = 3;
+        retrained.workout_days.insert(0, added);
+        assert!(!restore_workout(&mut retrained, 3, original.clone(), None));
+
+        assert!(restore_workout(&mut rested, 3, original.clone(), None));
+        assert_eq!(serde_json::to_value(&rested).unwrap(), serde_json::to_value(&week).unwrap());
+
+        // Modified workout
+        let adjusted = reduce_volume(&original, 0.8);
+        let mut shortened = week.clone();
+        apply_to_week(&mut shortened, 0, Some(adjusted.clone()));
+        assert_eq!(shortened.workout_days[0].duration_minutes, 72);
+
+        // Edited by the athlete since: refuse
+        let mut edited = shortened.clone();
+        edited.workout_days[0].duration_minutes = 60;
+        assert!(!restore_workout(&mut edited, 3, original.clone(), Some(&adjusted)));
+
+        assert!(restore_workout(&mut shortened, 3, original, Some(&adjusted)));
+        assert_eq!(serde_json::to_value(&shortened).unwrap(), serde_json::to_value(&week).unwrap());
+    }
+
+    #[test]
+    fn test_find_plan_workout_by_date() {
+        let start = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(); // Monday
+        let week = |week_number| PlanWeekStructure {
+            week_number,
+            phase_name: "Base".to_string(),
+            weekly_volume: 6.0,
+            weekly_intensity: 0.7,
+            workout_days: vec![threshold_workout()],
+            rest_days: vec![],
+            week_goals: vec![],
+            key_sessions: vec![],
+        };
+        let weeks = vec![week(1), week(2)];
+
+        assert_eq!(find_plan_workout(&weeks, start, NaiveDate::from_ymd_opt(2026, 3, 11).unwrap()), Some((1, 0)));
+        assert_eq!(find_plan_workout(&weeks, start, NaiveDate::from_ymd_opt(2026, 3, 12).unwrap()), None);
+    }
+
+    #[test]
+    fn test_consecutive_days_before() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let days = |offsets: &[i64]| offsets.iter().map(|d| date - Duration::days(*d)).collect::<Vec<_>>();
+
+        assert_eq!(consecutive_days_before(&days(&[1, 2, 3, 5]), date), 3);
+        assert_eq!(consecutive_days_before(&days(&[2, 3]), date), 0);
+        assert_eq!(consecutive_days_before(&[], date), 0);
+    }
+
+    #[test]
+    fn test_estimate_workout_tss() {
+        let mut workout = threshold_workout();
+        workout.duration_minutes = 60;
+        assert!((estimate_workout_tss(&workout) - 90.25).abs() < 1e-9);
+    }
 }
@@ -3,7 +3,7 @@ use bytes::Bytes;
 use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use tempfile::NamedTempFile;
 use tokio::fs;
@@ -11,7 +11,11 @@ use tokio::io::AsyncWriteExt;
 use tracing::{info, warn, error};
 use uuid::Uuid;
 
+use crate::models::training_metrics::Sport;
 use crate::models::TrainingSession;
+use crate::services::fit_decoder::FitDecoder;
+use crate::services::training_file_parser::{TrackPoint, TrainingFileParser};
+use crate::services::training_metrics_calculator::{CalculatedMetrics, TrainingMetricsCalculator};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrainingMetrics {
@@ -26,8 +30,12 @@ pub struct TrainingMetrics {
     pub tss: Option<f64>, // Training Stress Score
     pub intensity_factor: Option<f64>,
     pub work: Option<f64>, // Total work in kJ
-    pub power_zones: Option<HashMap<String, f64>>, // Zone distribution
-    pub heart_rate_zones: Option<HashMap<String, f64>>,
+    pub power_zones: Option<HashMap<String, f64>>, // Seconds spent in each zone
+    pub heart_rate_zones: Option<HashMap<String, f64>>, // Seconds spent in each zone
+    #[serde(default)]
+    pub max_core_temperature: Option<f64>, // Peak core body temperature (°C)
+    #[serde(default)]
+    pub peak_powers: Option<BTreeMap<u32, f64>>, // Mean-maximal power (W) by duration in seconds
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +43,8 @@ pub struct PerformanceManagementChart {
     pub ctl: f64, // Chronic Training Load (Fitness)
     pub atl: f64, // Acute Training Load (Fatigue)
     pub tsb: f64, // Training Stress Balance (Form)
+    #[serde(default)]
+    pub tss_daily: f64, // Daily Training Stress Score
     pub date: chrono::NaiveDate,
 }
 
@@ -44,6 +54,8 @@ pub struct ZoneSettings {
     pub heart_rate_zones: Option<Vec<f64>>, // Thresholds for HR zones (bpm)
     pub ftp: Option<f64>, // Functional Threshold Power
     pub lthr: Option<f64>, // Lactate Threshold Heart Rate
+    #[serde(default)]
+    pub threshold_pace: Option<f64>, // Threshold running pace (seconds per meter)
 }
 
 #[derive(Debug, Clone)]
@@ -51,6 +63,7 @@ pub enum FileType {
     Tcx,
     Gpx,
     Csv,
+    Fit,
 }
 
 impl FileType {
@@ -64,6 +77,7 @@ impl FileType {
             Some("tcx") => Ok(FileType::Tcx),
             Some("gpx") => Ok(FileType::Gpx),
             Some("csv") => Ok(FileType::Csv),
+            Some("fit") => Ok(FileType::Fit),
             _ => Err(anyhow!("Unsupported file type: {}", filename)),
         }
     }
@@ -107,6 +121,12 @@ impl TrainingAnalysisService {
         // Validate file type
         let file_type = FileType::from_filename(filename)?;
 
+        // Fall back to the user's stored thresholds so TSS and zones can be computed
+        let zone_settings = match zone_settings {
+            Some(settings) => Some(settings),
+            None => self.get_user_zone_settings(user_id).await?,
+        };
+
         // Save file temporarily for processing
         let temp_file = self.save_temp_file(&file_data, filename).await?;
 
@@ -115,6 +135,7 @@ impl TrainingAnalysisService {
             FileType::Tcx => self.process_tcx_file(&temp_file, zone_settings).await?,
             FileType::Gpx => self.process_gpx_file(&temp_file, zone_settings).await?,
             FileType::Csv => self.process_csv_file(&temp_file, zone_settings).await?,
+            FileType::Fit => self.process_fit_file(&temp_file, zone_settings).await?,
         };
 
         // Clean up temporary file
@@ -126,6 +147,35 @@ impl TrainingAnalysisService {
         Ok(metrics)
     }
 
+    /// Load the user's training thresholds and zone boundaries
+    pub async fn get_user_zone_settings(&self, user_id: Uuid) -> Result<Option<ZoneSettings>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT ftp::float8 AS "ftp?", lthr::float8 AS "lthr?",
+                   threshold_pace::float8 AS "threshold_pace?",
+                   power_zones::text AS "power_zones?", heart_rate_zones::text AS "heart_rate_zones?"
+            FROM zone_settings
+            WHERE user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        // Custom zones are stored as JSON arrays of ascending thresholds
+        let parse_zones = |zones: Option<String>| {
+            zones.and_then(|json| serde_json::from_str::<Vec<f64>>(&json).ok())
+        };
+
+        Ok(row.map(|row| ZoneSettings {
+            power_zones: parse_zones(row.power_zones),
+            heart_rate_zones: parse_zones(row.heart_rate_zones),
+            ftp: row.ftp,
+            lthr: row.lthr,
+            threshold_pace: row.threshold_pace,
+        }))
+    }
+
     /// Calculate Performance Management Chart metrics for a user
     pub async fn calculate_pmc(
         &self,
@@ -253,24 +303,14 @@ impl TrainingAnalysisService {
                 }
             },
             FileType::Csv => {
-                // CSV files should have at least one comma or recognizable header
-                let lines: Vec<&str> = content.lines().collect();
-                if lines.is_empty() {
-                    return Err(anyhow!("Invalid CSV file: no content"));
-                }
-
-                // Check if first line looks like a header
-                let first_line = lines[0].to_lowercase();
-                let has_recognizable_headers = first_line.contains("time") ||
-                    first_line.contains("power") ||
-                    first_line.contains("heart") ||
-                    first_line.contains("speed") ||
-                    first_line.contains("distance") ||
-                    first_line.contains("cadence");
-
-                if !has_recognizable_headers && !first_line.contains(",") {
-                    return Err(anyhow!("Invalid CSV file: no recognizable format"));
-                }
+                // Same delimiter and header detection as the parser, so semicolon or tab
+                // separated exports with a metadata preamble are accepted
+                TrainingFileParser::validate_csv(&content)
+                    .map_err(|e| anyhow!("Invalid CSV file: {}", e))?;
+            }
+            FileType::Fit => {
+                // FIT files are binary, so check the header signature and CRC instead
+                FitDecoder::validate_header(file_data)?;
             }
         }
 
@@ -379,6 +419,29 @@ impl TrainingAnalysisService {
         Ok(metrics)
     }
 
+    async fn process_fit_file(
+        &self,
+        file_path: &str,
+        zone_settings: Option<ZoneSettings>,
+    ) -> Result<TrainingMetrics> {
+        info!("Processing FIT file: {}", file_path);
+
+        // FIT is a binary format, so read raw bytes rather than a string
+        let file_content = fs::read(file_path).await
+            .map_err(|e| self.handle_processing_error(anyhow!("Failed to read FIT file: {}", e), file_path))?;
+
+        // Decode FIT content with error handling
+        let metrics = match self.parse_fit_content(&file_content, zone_settings).await {
+            Ok(metrics) => metrics,
+            Err(e) => return Err(self.handle_processing_error(e, file_path)),
+        };
+
+        info!("Successfully processed FIT file with metrics: duration={:?}s, distance={:?}m",
+              metrics.duration_seconds, metrics.distance_meters);
+
+        Ok(metrics)
+    }
+
     async fn get_training_sessions_for_pmc(
         &self,
         user_id: Uuid,
@@ -453,6 +516,7 @@ impl TrainingAnalysisService {
                 ctl,
                 atl,
                 tsb,
+                tss_daily: daily_tss,
                 date: current_date,
             });
 
@@ -501,15 +565,14 @@ impl TrainingAnalysisService {
         Ok(())
     }
 
-    // File parsing methods (placeholder implementations until trainrs integration)
+    // File parsing methods
 
     async fn parse_tcx_content(
         &self,
         content: &str,
         zone_settings: Option<ZoneSettings>,
     ) -> Result<TrainingMetrics> {
-        // Basic TCX parsing - in production this would use trainrs
-        // For now, extract basic information using regex
+        // Lap summary totals are read directly; detailed channels come from the trackpoints
 
         let duration_regex = regex::Regex::new(r"<TotalTimeSeconds>([^<]+)</TotalTimeSeconds>")
             .map_err(|e| anyhow!("Failed to compile regex: {}", e))?;
@@ -529,7 +592,8 @@ impl TrainingAnalysisService {
 
         // Extract trackpoints for more detailed analysis
         let trackpoints = self.extract_tcx_trackpoints(content)?;
-        let calculated_metrics = self.calculate_metrics_from_trackpoints(&trackpoints, zone_settings)?;
+        let sport = TrainingFileParser::detect_sport(content);
+        let calculated_metrics = self.calculate_metrics_from_trackpoints(&trackpoints, zone_settings, sport)?;
 
         Ok(TrainingMetrics {
             duration_seconds,
@@ -545,6 +609,8 @@ impl TrainingAnalysisService {
             work: calculated_metrics.work,
             power_zones: calculated_metrics.power_zones,
             heart_rate_zones: calculated_metrics.heart_rate_zones,
+            max_core_temperature: calculated_metrics.max_core_temperature,
+            peak_powers: calculated_metrics.peak_powers,
         })
     }
 
@@ -553,11 +619,11 @@ impl TrainingAnalysisService {
         content: &str,
         zone_settings: Option<ZoneSettings>,
     ) -> Result<TrainingMetrics> {
-        // Basic GPX parsing - in production this would use trainrs
         // GPX files typically contain GPS data but may lack power/HR data
 
         let trackpoints = self.extract_gpx_trackpoints(content)?;
-        let calculated_metrics = self.calculate_metrics_from_trackpoints(&trackpoints, zone_settings)?;
+        let sport = TrainingFileParser::detect_sport(content);
+        let calculated_metrics = self.calculate_metrics_from_trackpoints(&trackpoints, zone_settings, sport)?;
 
         Ok(TrainingMetrics {
             duration_seconds: calculated_metrics.duration,
@@ -573,6 +639,8 @@ impl TrainingAnalysisService {
             work: calculated_metrics.work,
             power_zones: calculated_metrics.power_zones,
             heart_rate_zones: calculated_metrics.heart_rate_zones,
+            max_core_temperature: calculated_metrics.max_core_temperature,
+            peak_powers: calculated_metrics.peak_powers,
         })
     }
 
@@ -581,11 +649,11 @@ impl TrainingAnalysisService {
         content: &str,
         zone_settings: Option<ZoneSettings>,
     ) -> Result<TrainingMetrics> {
-        // Basic CSV parsing - in production this would use trainrs
-        // CSV format varies by device, need to detect column structure
+        // CSV format varies by device, the parser detects the column structure
 
         let trackpoints = self.extract_csv_trackpoints(content)?;
-        let calculated_metrics = self.calculate_metrics_from_trackpoints(&trackpoints, zone_settings)?;
+        let sport = TrainingFileParser::detect_sport(content);
+        let calculated_metrics = self.calculate_metrics_from_trackpoints(&trackpoints, zone_settings, sport)?;
 
         Ok(TrainingMetrics {
             duration_seconds: calculated_metrics.duration,
@@ -601,100 +669,74 @@ impl TrainingAnalysisService {
             work: calculated_metrics.work,
             power_zones: calculated_metrics.power_zones,
             heart_rate_zones: calculated_metrics.heart_rate_zones,
+            max_core_temperature: calculated_metrics.max_core_temperature,
+            peak_powers: calculated_metrics.peak_powers,
         })
     }
 
-    fn extract_tcx_trackpoints(&self, _content: &str) -> Result<Vec<TrackPoint>> {
-        // Placeholder implementation - would use proper XML parsing in production
-        let trackpoints = Vec::new();
-
-        // This is a very basic implementation
-        // In production, we would use an XML parser and trainrs
-        warn!("Using placeholder TCX trackpoint extraction");
-
-        Ok(trackpoints)
-    }
+    async fn parse_fit_content(
+        &self,
+        content: &[u8],
+        zone_settings: Option<ZoneSettings>,
+    ) -> Result<TrainingMetrics> {
+        // Session totals are recorded by the device; detailed channels come from the records
 
-    fn extract_gpx_trackpoints(&self, _content: &str) -> Result<Vec<TrackPoint>> {
-        // Placeholder implementation - would use proper XML parsing in production
-        let trackpoints = Vec::new();
+        let activity = FitDecoder::decode(content)?;
+        let sport = activity.sport();
+        let calculated_metrics = self.calculate_metrics_from_trackpoints(&activity.trackpoints, zone_settings, sport)?;
 
-        // This is a very basic implementation
-        // In production, we would use an XML parser and trainrs
-        warn!("Using placeholder GPX trackpoint extraction");
+        let session = activity.sessions.first();
+        let duration_seconds = session
+            .and_then(|s| s.total_timer_time)
+            .map(|d| d as i32)
+            .or(calculated_metrics.duration);
+        let distance_meters = session
+            .and_then(|s| s.total_distance)
+            .or(calculated_metrics.distance);
 
-        Ok(trackpoints)
+        Ok(TrainingMetrics {
+            duration_seconds,
+            distance_meters,
+            elevation_gain_meters: calculated_metrics.elevation_gain,
+            average_power: calculated_metrics.avg_power,
+            normalized_power: calculated_metrics.normalized_power,
+            average_heart_rate: calculated_metrics.avg_heart_rate,
+            average_cadence: calculated_metrics.avg_cadence,
+            average_speed: calculated_metrics.avg_speed,
+            tss: calculated_metrics.tss,
+            intensity_factor: calculated_metrics.intensity_factor,
+            work: calculated_metrics.work,
+            power_zones: calculated_metrics.power_zones,
+            heart_rate_zones: calculated_metrics.heart_rate_zones,
+            max_core_temperature: calculated_metrics.max_core_temperature,
+            peak_powers: calculated_metrics.peak_powers,
+        })
     }
 
-    fn extract_csv_trackpoints(&self, _content: &str) -> Result<Vec<TrackPoint>> {
-        // Placeholder implementation - would use proper CSV parsing in production
-        let trackpoints = Vec::new();
+    fn extract_tcx_trackpoints(&self, content: &str) -> Result<Vec<TrackPoint>> {
+        TrainingFileParser::parse_tcx(content)
+    }
 
-        // This is a very basic implementation
-        // In production, we would parse CSV headers and data properly
-        warn!("Using placeholder CSV trackpoint extraction");
+    fn extract_gpx_trackpoints(&self, content: &str) -> Result<Vec<TrackPoint>> {
+        TrainingFileParser::parse_gpx(content)
+    }
 
-        Ok(trackpoints)
+    fn extract_csv_trackpoints(&self, content: &str) -> Result<Vec<TrackPoint>> {
+        TrainingFileParser::parse_csv(content)
     }
 
     fn calculate_metrics_from_trackpoints(
         &self,
-        _trackpoints: &[TrackPoint],
-        _zone_settings: Option<ZoneSettings>,
+        trackpoints: &[TrackPoint],
+        zone_settings: Option<ZoneSettings>,
+        sport: Option<Sport>,
     ) -> Result<CalculatedMetrics> {
-        // Placeholder implementation for metrics calculation
-        // In production, this would implement proper power analysis,
-        // normalized power calculation, TSS calculation, etc.
-
-        warn!("Using placeholder metrics calculation");
-
-        Ok(CalculatedMetrics {
-            duration: None,
-            distance: None,
-            elevation_gain: None,
-            avg_power: None,
-            normalized_power: None,
-            avg_heart_rate: None,
-            avg_cadence: None,
-            avg_speed: None,
-            tss: None,
-            intensity_factor: None,
-            work: None,
-            power_zones: None,
-            heart_rate_zones: None,
-        })
-    }
-}
-
-// Helper structs for trackpoint data
-#[derive(Debug, Clone)]
-struct TrackPoint {
-    timestamp: chrono::DateTime<chrono::Utc>,
-    latitude: Option<f64>,
-    longitude: Option<f64>,
-    elevation: Option<f64>,
-    heart_rate: Option<f64>,
-    power: Option<f64>,
-    cadence: Option<f64>,
-    speed: Option<f64>,
-    distance: Option<f64>,
-}
+        if trackpoints.is_empty() {
+            warn!("No trackpoints available for metrics calculation");
+        }
 
-#[derive(Debug, Clone)]
-struct CalculatedMetrics {
-    duration: Option<i32>,
-    distance: Option<f64>,
-    elevation_gain: Option<f64>,
-    avg_power: Option<f64>,
-    normalized_power: Option<f64>,
-    avg_heart_rate: Option<f64>,
-    avg_cadence: Option<f64>,
-    avg_speed: Option<f64>,
-    tss: Option<f64>,
-    intensity_factor: Option<f64>,
-    work: Option<f64>,
-    power_zones: Option<HashMap<String, f64>>,
-    heart_rate_zones: Option<HashMap<String, f64>>,
+        Ok(TrainingMetricsCalculator::new(zone_settings, sport).calculate(trackpoints))
+    }
 }
 
 #[cfg(test)]
@@ -727,6 +769,7 @@ mod tests {
         assert!(matches!(FileType::from_filename("test.tcx"), Ok(FileType::Tcx)));
         assert!(matches!(FileType::from_filename("test.gpx"), Ok(FileType::Gpx)));
         assert!(matches!(FileType::from_filename("test.csv"), Ok(FileType::Csv)));
+        assert!(matches!(FileType::from_filename("test.fit"), Ok(FileType::Fit)));
         assert!(matches!(FileType::from_filename("TEST.TCX"), Ok(FileType::Tcx)));
         assert!(FileType::from_filename("test.txt").is_err());
         assert!(FileType::from_filename("test").is_err());
@@ -754,6 +797,11 @@ mod tests {
         let large_bytes = Bytes::from(large_content);
         assert!(service.validate_training_file(&large_bytes, "test.tcx").is_err());
 
+        // Test valid and truncated FIT files
+        let fit_bytes = Bytes::from_static(include_bytes!("../../tests/fixtures/training_files/sample_run.fit"));
+        assert!(service.validate_training_file(&fit_bytes, "test.fit").is_ok());
+        assert!(service.validate_training_file(&tcx_bytes, "test.fit").is_err());
+
         // Test dangerous filename
         assert!(service.validate_training_file(&tcx_bytes, "../test.tcx").is_err());
     }
@@ -790,6 +838,8 @@ mod tests {
             work: Some(800.0),
             power_zones: None,
             heart_rate_zones: None,
+            max_core_temperature: None,
+            peak_powers: None,
         };
 
         // Test power-based TSS calculation
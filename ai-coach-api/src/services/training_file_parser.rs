@@ -0,0 +1,656 @@
+/// Training File Parser
+///
+/// Turns raw workout files into a time series of `TrackPoint`s:
+/// - TCX: streaming XML parse including Garmin `ns3:TPX` extensions (watts, speed, run cadence)
+/// - GPX: streaming XML parse including `gpxtpx:TrackPointExtension` (heart rate, cadence)
+///   and the common `<power>` extension
+/// - CSV: delimiter and header detection with unit-aware column mapping
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use tracing::{debug, warn};
+
+use crate::models::training_metrics::Sport;
+
+/// A single sample recorded by a device during a workout
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackPoint {
+    pub timestamp: DateTime<Utc>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub elevation: Option<f64>, // meters
+    pub heart_rate: Option<f64>, // bpm
+    pub power: Option<f64>, // watts
+    pub cadence: Option<f64>, // rpm / spm
+    pub speed: Option<f64>, // m/s
+    pub distance: Option<f64>, // cumulative meters
+    pub core_temperature: Option<f64>, // °C
+}
+
+/// Trackpoint fields collected while streaming through an XML element
+#[derive(Debug, Default)]
+struct PartialTrackPoint {
+    timestamp: Option<DateTime<Utc>>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    elevation: Option<f64>,
+    heart_rate: Option<f64>,
+    power: Option<f64>,
+    cadence: Option<f64>,
+    speed: Option<f64>,
+    distance: Option<f64>,
+}
+
+impl PartialTrackPoint {
+    fn finish(self) -> Option<TrackPoint> {
+        let timestamp = self.timestamp?;
+        Some(TrackPoint {
+            timestamp,
+            latitude: self.latitude,
+            longitude: self.longitude,
+            elevation: self.elevation,
+            heart_rate: self.heart_rate,
+            power: self.power,
+            cadence: self.cadence,
+            speed: self.speed,
+            distance: self.distance,
+            core_temperature: None,
+        })
+    }
+}
+
+/// Parser for TCX, GPX and CSV training files
+pub struct TrainingFileParser;
+
+impl TrainingFileParser {
+    /// Extract trackpoints from a Garmin Training Center (TCX) document
+    pub fn parse_tcx(content: &str) -> Result<Vec<TrackPoint>> {
+        let mut reader = Reader::from_str(content);
+        reader.trim_text(true);
+
+        let mut trackpoints = Vec::new();
+        let mut path: Vec<String> = Vec::new();
+        let mut current: Option<PartialTrackPoint> = None;
+        let mut skipped = 0;
+
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e)) => {
+                    let name = local_name(&e);
+                    if name == "Trackpoint" {
+                        current = Some(PartialTrackPoint::default());
+                    }
+                    path.push(name);
+                }
+                Ok(Event::End(_)) => {
+                    if path.pop().as_deref() == Some("Trackpoint") {
+                        match current.take().and_then(PartialTrackPoint::finish) {
+                            Some(point) => trackpoints.push(point),
+                            None => skipped += 1,
+                        }
+                    }
+                }
+                Ok(Event::Text(t)) => {
+                    let Some(point) = current.as_mut() else { continue };
+                    let text = t
+                        .unescape()
+                        .map_err(|e| anyhow!("Invalid XML text in TCX file: {}", e))?;
+                    let text = text.trim();
+
+                    match path.last().map(String::as_str) {
+                        Some("Time") => point.timestamp = parse_timestamp(text),
+                        Some("LatitudeDegrees") => point.latitude = parse_number(text),
+                        Some("LongitudeDegrees") => point.longitude = parse_number(text),
+                        Some("AltitudeMeters") => point.elevation = parse_number(text),
+                        Some("DistanceMeters") => point.distance = parse_number(text),
+                        Some("Value") if parent_is(&path, "HeartRateBpm") => {
+                            point.heart_rate = parse_number(text)
+                        }
+                        Some("Cadence") => point.cadence = parse_number(text),
+                        // ns3:TPX extension values
+                        Some("RunCadence") if point.cadence.is_none() => {
+                            point.cadence = parse_number(text)
+                        }
+                        Some("Watts") => point.power = parse_number(text),
+                        Some("Speed") => point.speed = parse_number(text),
+                        _ => {}
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    return Err(anyhow!(
+                        "XML parse error in TCX file at position {}: {}",
+                        reader.buffer_position(),
+                        e
+                    ))
+                }
+            }
+        }
+
+        if skipped > 0 {
+            warn!("Skipped {} TCX trackpoints without a valid timestamp", skipped);
+        }
+        debug!("Extracted {} trackpoints from TCX file", trackpoints.len());
+
+        Ok(trackpoints)
+    }
+
+    /// Extract trackpoints from a GPX document (tracks and routes)
+    pub fn parse_gpx(content: &str) -> Result<Vec<TrackPoint>> {
+        let mut reader = Reader::from_str(content);
+        reader.trim_text(true);
+
+        let mut trackpoints = Vec::new();
+        let mut path: Vec<String> = Vec::new();
+        let mut current: Option<PartialTrackPoint> = None;
+        let mut skipped = 0;
+
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e)) => {
+                    let name = local_name(&e);
+                    if name == "trkpt" || name == "rtept" {
+                        current = Some(Self::gpx_point_from_attributes(&e)?);
+                    }
+                    path.push(name);
+                }
+                Ok(Event::Empty(e)) => {
+                    // Self-closing points carry only a position and never a timestamp
+                    let name = local_name(&e);
+                    if name == "trkpt" || name == "rtept" {
+                        skipped += 1;
+                    }
+                }
+                Ok(Event::End(_)) => {
+                    let name = path.pop();
+                    if matches!(name.as_deref(), Some("trkpt") | Some("rtept")) {
+                        match current.take().and_then(PartialTrackPoint::finish) {
+                            Some(point) => trackpoints.push(point),
+                            None => skipped += 1,
+                        }
+                    }
+                }
+                Ok(Event::Text(t)) => {
+                    let Some(point) = current.as_mut() else { continue };
+                    let text = t
+                        .unescape()
+                        .map_err(|e| anyhow!("Invalid XML text in GPX file: {}", e))?;
+                    let text = text.trim();
+
+                    match path.last().map(String::as_str) {
+                        Some("time") => point.timestamp = parse_timestamp(text),
+                        Some("ele") => point.elevation = parse_number(text),
+                        // gpxtpx:TrackPointExtension values
+                        Some("hr") => point.heart_rate = parse_number(text),
+                        Some("cad") => point.cadence = parse_number(text),
+                        Some("speed") => point.speed = parse_number(text),
+                        Some("power") | Some("PowerInWatts") => point.power = parse_number(text),
+                        _ => {}
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    return Err(anyhow!(
+                        "XML parse error in GPX file at position {}: {}",
+                        reader.buffer_position(),
+                        e
+                    ))
+                }
+            }
+        }
+
+        if skipped > 0 {
+            warn!("Skipped {} GPX points without a valid timestamp", skipped);
+        }
+
+        // GPX has no distance channel, so derive it from the positions
+        let mut cumulative = 0.0;
+        let mut previous: Option<(f64, f64)> = None;
+        for point in trackpoints.iter_mut() {
+            if let (Some(lat), Some(lon)) = (point.latitude, point.longitude) {
+                if let Some((prev_lat, prev_lon)) = previous {
+                    cumulative += haversine_distance(prev_lat, prev_lon, lat, lon);
+                }
+                previous = Some((lat, lon));
+                point.distance = Some(cumulative);
+            }
+        }
+
+        debug!("Extracted {} trackpoints from GPX file", trackpoints.len());
+
+        Ok(trackpoints)
+    }
+
+    /// Extract trackpoints from a CSV export
+    ///
+    /// The delimiter (comma, semicolon or tab) and header row are detected
+    /// automatically, so exports with a metadata preamble are accepted. Files
+    /// without any time column are assumed to be recorded at 1 Hz.
+    pub fn parse_csv(content: &str) -> Result<Vec<TrackPoint>> {
+        let mut reader = csv_reader(content);
+
+        let mut columns: Option<Vec<Option<CsvColumn>>> = None;
+        let mut trackpoints = Vec::new();
+        let mut skipped = 0;
+        let base_time = DateTime::<Utc>::UNIX_EPOCH;
+
+        for (row_index, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| anyhow!("Failed to parse CSV row {}: {}", row_index + 1, e))?;
+
+            let Some(mapping) = columns.as_ref() else {
+                columns = header_mapping(&record);
+                continue;
+            };
+
+            let mut point = PartialTrackPoint::default();
+            let mut has_data = false;
+
+            for (value, column) in record.iter().zip(mapping.iter()) {
+                let Some(column) = column else { continue };
+
+                if *column == CsvColumn::Timestamp {
+                    point.timestamp = parse_timestamp(value);
+                    continue;
+                }
+
+                let Some(number) = parse_number(value) else { continue };
+                has_data = true;
+
+                match column {
+                    CsvColumn::Timestamp => {}
+                    CsvColumn::ElapsedSeconds => point.timestamp = offset_timestamp(base_time, number),
+                    CsvColumn::ElapsedMinutes => point.timestamp = offset_timestamp(base_time, number * 60.0),
+                    CsvColumn::Latitude => point.latitude = Some(number),
+                    CsvColumn::Longitude => point.longitude = Some(number),
+                    CsvColumn::ElevationMeters => point.elevation = Some(number),
+                    CsvColumn::ElevationFeet => point.elevation = Some(number * 0.3048),
+                    CsvColumn::HeartRate => point.heart_rate = Some(number),
+                    CsvColumn::Power => point.power = Some(number),
+                    CsvColumn::Cadence => point.cadence = Some(number),
+                    CsvColumn::SpeedMetersPerSecond => point.speed = Some(number),
+                    CsvColumn::SpeedKph => point.speed = Some(number / 3.6),
+                    CsvColumn::SpeedMph => point.speed = Some(number * 0.44704),
+                    CsvColumn::DistanceMeters => point.distance = Some(number),
+                    CsvColumn::DistanceKm => point.distance = Some(number * 1000.0),
+                    CsvColumn::DistanceMiles => point.distance = Some(number * 1609.344),
+                }
+            }
+
+            let has_time_column = mapping.iter().any(|c| c.is_some_and(CsvColumn::is_time));
+            if !has_time_column {
+                point.timestamp = offset_timestamp(base_time, trackpoints.len() as f64);
+            }
+
+            if !has_data {
+                skipped += 1;
+                continue;
+            }
+
+            match point.finish() {
+                Some(point) => trackpoints.push(point),
+                None => skipped += 1,
+            }
+        }
+
+        if columns.is_none() {
+            return Err(anyhow!("Failed to parse CSV file: no recognizable header row"));
+        }
+
+        if skipped > 0 {
+            warn!("Skipped {} CSV rows without usable data", skipped);
+        }
+        debug!("Extracted {} trackpoints from CSV file", trackpoints.len());
+
+        Ok(trackpoints)
+    }
+
+    /// Check that a CSV export has a recognizable header row, using the same
+    /// delimiter and header detection as `parse_csv`
+    pub fn validate_csv(content: &str) -> Result<()> {
+        for (row_index, record) in csv_reader(content).records().enumerate() {
+            let record = record.map_err(|e| anyhow!("row {}: {}", row_index + 1, e))?;
+            if header_mapping(&record).is_some() {
+                return Ok(());
+            }
+        }
+
+        Err(anyhow!("no recognizable header row"))
+    }
+
+    /// Detect the sport declared by a TCX `Activity Sport` attribute or a GPX track `<type>`
+    pub fn detect_sport(content: &str) -> Option<Sport> {
+        let pattern = regex::Regex::new(r#"<Activity\s+Sport="([^"]+)"|<type>([^<]+)</type>"#).ok()?;
+        let captures = pattern.captures(content)?;
+        let sport = captures.get(1).or_else(|| captures.get(2))?.as_str().trim();
+
+        Some(Sport::from_string(sport))
+    }
+
+    fn gpx_point_from_attributes(element: &BytesStart) -> Result<PartialTrackPoint> {
+        let mut point = PartialTrackPoint::default();
+
+        for attribute in element.attributes() {
+            let attribute = attribute.map_err(|e| anyhow!("Invalid XML attribute in GPX file: {}", e))?;
+            let value = attribute
+                .unescape_value()
+                .map_err(|e| anyhow!("Invalid XML attribute in GPX file: {}", e))?;
+
+            match attribute.key.local_name().as_ref() {
+                b"lat" => point.latitude = parse_number(&value),
+                b"lon" => point.longitude = parse_number(&value),
+                _ => {}
+            }
+        }
+
+        Ok(point)
+    }
+}
+
+/// Known CSV columns and the unit their values are recorded in
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CsvColumn {
+    Timestamp,
+    ElapsedSeconds,
+    ElapsedMinutes,
+    Latitude,
+    Longitude,
+    ElevationMeters,
+    ElevationFeet,
+    HeartRate,
+    Power,
+    Cadence,
+    SpeedMetersPerSecond,
+    SpeedKph,
+    SpeedMph,
+    DistanceMeters,
+    DistanceKm,
+    DistanceMiles,
+}
+
+impl CsvColumn {
+    fn from_header(header: &str) -> Option<Self> {
+        let header = header.trim().trim_start_matches('\u{feff}').to_lowercase();
+
+        // Split "Altitude (m)" style headers into name and unit
+        let (name, unit) = match header.find(['(', '[']) {
+            Some(index) => (
+                header[..index].trim().to_string(),
+                header[index + 1..].trim_end_matches([')', ']']).trim().to_string(),
+            ),
+            None => (header.clone(), String::new()),
+        };
+        let name = name.replace([' ', '-'], "_");
+
+        let column = match name.as_str() {
+            "time" | "timestamp" | "date_time" | "datetime" | "date" => {
+                if unit == "s" || unit == "sec" || unit == "secs" {
+                    Self::ElapsedSeconds
+                } else {
+                    Self::Timestamp
+                }
+            }
+            "secs" | "seconds" | "elapsed" | "elapsed_time" | "elapsed_seconds" | "time_s" => {
+                Self::ElapsedSeconds
+            }
+            "minutes" | "mins" | "elapsed_minutes" => Self::ElapsedMinutes,
+            "lat" | "latitude" | "position_lat" => Self::Latitude,
+            "lon" | "lng" | "long" | "longitude" | "position_long" => Self::Longitude,
+            "alt" | "altitude" | "elevation" | "ele" | "enhanced_altitude" => match unit.as_str() {
+                "ft" | "feet" => Self::ElevationFeet,
+                _ => Self::ElevationMeters,
+            },
+            "hr" | "hrate" | "heart_rate" | "heartrate" | "bpm" => Self::HeartRate,
+            "watts" | "power" | "pwr" => Self::Power,
+            "cad" | "cadence" | "rpm" => Self::Cadence,
+            "kph" | "km/h" => Self::SpeedKph,
+            "mph" => Self::SpeedMph,
+            "speed" | "enhanced_speed" | "velocity" => match unit.as_str() {
+                "km/h" | "kph" | "kmh" => Self::SpeedKph,
+                "mph" => Self::SpeedMph,
+                _ => Self::SpeedMetersPerSecond,
+            },
+            "km" => Self::DistanceKm,
+            "miles" | "mi" => Self::DistanceMiles,
+            "distance" | "dist" | "distance_m" | "meters" => match unit.as_str() {
+                "km" => Self::DistanceKm,
+                "mi" | "miles" => Self::DistanceMiles,
+                _ => Self::DistanceMeters,
+            },
+            _ => return None,
+        };
+
+        Some(column)
+    }
+
+    fn is_time(self) -> bool {
+        matches!(self, Self::Timestamp | Self::ElapsedSeconds | Self::ElapsedMinutes)
+    }
+}
+
+fn local_name(element: &BytesStart) -> String {
+    String::from_utf8_lossy(element.local_name().as_ref()).into_owned()
+}
+
+fn parent_is(path: &[String], name: &str) -> bool {
+    path.len() >= 2 && path[path.len() - 2] == name
+}
+
+fn parse_number(text: &str) -> Option<f64> {
+    text.trim().parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+fn parse_timestamp(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(text) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+
+    // Timestamps without an offset are treated as UTC
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y/%m/%d %H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+fn offset_timestamp(base: DateTime<Utc>, seconds: f64) -> Option<DateTime<Utc>> {
+    if !seconds.is_finite() || seconds < 0.0 {
+        return None;
+    }
+    Some(base + chrono::Duration::milliseconds((seconds * 1000.0).round() as i64))
+}
+
+fn csv_reader(content: &str) -> csv::Reader<&[u8]> {
+    csv::ReaderBuilder::new()
+        .delimiter(detect_delimiter(content))
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes())
+}
+
+/// Column mapping of the header row: the first row naming at least one known column
+fn header_mapping(record: &csv::StringRecord) -> Option<Vec<Option<CsvColumn>>> {
+    let mapping: Vec<Option<CsvColumn>> = record.iter().map(CsvColumn::from_header).collect();
+    mapping.iter().any(Option::is_some).then_some(mapping)
+}
+
+fn detect_delimiter(content: &str) -> u8 {
+    // Pick the candidate that splits the first non-empty lines into the most fields
+    let sample: Vec<&str> = content.lines().filter(|line| !line.trim().is_empty()).take(5).collect();
+
+    [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|delimiter| {
+            sample
+                .iter()
+                .map(|line| line.bytes().filter(|b| b == delimiter).count())
+                .max()
+                .unwrap_or(0)
+        })
+        .unwrap_or(b',')
+}
+
+/// Great-circle distance between two coordinates in meters
+pub(crate) fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lon = (lon2 - lon1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_METERS * a.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_TCX: &str = include_str!("../../tests/fixtures/training_files/sample_ride.tcx");
+    const SAMPLE_GPX: &str = include_str!("../../tests/fixtures/training_files/sample_ride.gpx");
+    const SAMPLE_CSV: &str = include_str!("../../tests/fixtures/training_files/sample_ride.csv");
+
+    #[test]
+    fn test_parse_tcx_with_tpx_extensions() {
+        let points = TrainingFileParser::parse_tcx(SAMPLE_TCX).unwrap();
+
+        assert_eq!(points.len(), 5);
+
+        let first = &points[0];
+        assert_eq!(first.timestamp.to_rfc3339(), "2024-03-02T08:00:00+00:00");
+        assert_eq!(first.latitude, Some(45.5017));
+        assert_eq!(first.longitude, Some(-73.5673));
+        assert_eq!(first.elevation, Some(35.2));
+        assert_eq!(first.distance, Some(0.0));
+        assert_eq!(first.heart_rate, Some(118.0));
+        assert_eq!(first.cadence, Some(85.0));
+        assert_eq!(first.power, Some(180.0));
+        assert_eq!(first.speed, Some(8.1));
+
+        let last = &points[4];
+        assert_eq!(last.power, Some(260.0));
+        assert_eq!(last.distance, Some(34.0));
+    }
+
+    #[test]
+    fn test_parse_tcx_skips_points_without_time() {
+        let tcx = r#"<?xml version="1.0"?>
+<TrainingCenterDatabase><Activities><Activity><Lap><Track>
+<Trackpoint><HeartRateBpm><Value>120</Value></HeartRateBpm></Trackpoint>
+<Trackpoint><Time>2024-03-02T08:00:00Z</Time><HeartRateBpm><Value>121</Value></HeartRateBpm></Trackpoint>
+</Track></Lap></Activity></Activities></TrainingCenterDatabase>"#;
+
+        let points = TrainingFileParser::parse_tcx(tcx).unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].heart_rate, Some(121.0));
+    }
+
+    #[test]
+    fn test_parse_tcx_malformed_xml() {
+        let result = TrainingFileParser::parse_tcx("<TrainingCenterDatabase><Activities></Laps>");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("XML"));
+    }
+
+    #[test]
+    fn test_parse_gpx_with_trackpoint_extension() {
+        let points = TrainingFileParser::parse_gpx(SAMPLE_GPX).unwrap();
+
+        assert_eq!(points.len(), 4);
+
+        let first = &points[0];
+        assert_eq!(first.latitude, Some(45.5017));
+        assert_eq!(first.longitude, Some(-73.5673));
+        assert_eq!(first.elevation, Some(35.0));
+        assert_eq!(first.heart_rate, Some(120.0));
+        assert_eq!(first.cadence, Some(88.0));
+        assert_eq!(first.power, Some(200.0));
+        assert_eq!(first.distance, Some(0.0));
+
+        // Distance is derived from positions: 0.0001° latitude ≈ 11.1 m per step
+        let last_distance = points[3].distance.unwrap();
+        assert!((last_distance - 33.36).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_parse_csv_golden_cheetah_style() {
+        let points = TrainingFileParser::parse_csv(SAMPLE_CSV).unwrap();
+
+        assert_eq!(points.len(), 5);
+
+        let second = &points[1];
+        assert_eq!((second.timestamp - points[0].timestamp).num_seconds(), 1);
+        assert_eq!(second.power, Some(210.0));
+        assert_eq!(second.heart_rate, Some(131.0));
+        assert_eq!(second.cadence, Some(90.0));
+        assert_eq!(second.elevation, Some(36.0));
+        assert!((second.speed.unwrap() - 10.0).abs() < 1e-9); // 36 km/h
+        assert!((second.distance.unwrap() - 10.0).abs() < 1e-9); // 0.01 km
+    }
+
+    #[test]
+    fn test_parse_csv_semicolon_with_preamble_and_timestamps() {
+        let csv = "Device;Trainer X\n\
+                   timestamp;heart_rate;power (W)\n\
+                   2024-03-02T08:00:00Z;120;150\n\
+                   2024-03-02T08:00:05Z;125;\n";
+
+        let points = TrainingFileParser::parse_csv(csv).unwrap();
+
+        assert_eq!(points.len(), 2);
+        assert_eq!((points[1].timestamp - points[0].timestamp).num_seconds(), 5);
+        assert_eq!(points[0].power, Some(150.0));
+        assert_eq!(points[1].power, None);
+        assert_eq!(points[1].heart_rate, Some(125.0));
+    }
+
+    #[test]
+    fn test_parse_csv_without_time_column_assumes_one_hertz() {
+        let csv = "watts,hr\n200,140\n210,141\n220,142\n";
+
+        let points = TrainingFileParser::parse_csv(csv).unwrap();
+
+        assert_eq!(points.len(), 3);
+        assert_eq!((points[2].timestamp - points[0].timestamp).num_seconds(), 2);
+    }
+
+    #[test]
+    fn test_parse_csv_without_header() {
+        let result = TrainingFileParser::parse_csv("1,2,3\n4,5,6\n");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_validate_csv() {
+        let preamble = "Device;Edge 530\nAthlete;Jane Doe\n\nSecs;Watts;HR\n0;200;120\n";
+        assert!(TrainingFileParser::validate_csv(preamble).is_ok());
+        assert!(TrainingFileParser::validate_csv("time\tpower\n0\t180\n").is_ok());
+        assert!(TrainingFileParser::validate_csv("1,2,3\n4,5,6\n").is_err());
+        assert!(TrainingFileParser::validate_csv("").is_err());
+    }
+
+    #[test]
+    fn test_detect_sport() {
+        assert!(matches!(TrainingFileParser::detect_sport(SAMPLE_TCX), Some(Sport::Cycling)));
+        assert!(matches!(TrainingFileParser::detect_sport(SAMPLE_GPX), Some(Sport::Cycling)));
+        assert!(matches!(
+            TrainingFileParser::detect_sport(r#"<Activity Sport="Running"><Id/></Activity>"#),
+            Some(Sport::Running)
+        ));
+        assert!(TrainingFileParser::detect_sport(SAMPLE_CSV).is_none());
+    }
+
+    #[test]
+    fn test_csv_column_units() {
+        assert_eq!(CsvColumn::from_header("Altitude (ft)"), Some(CsvColumn::ElevationFeet));
+        assert_eq!(CsvColumn::from_header("Speed [km/h]"), Some(CsvColumn::SpeedKph));
+        assert_eq!(CsvColumn::from_header("Distance (mi)"), Some(CsvColumn::DistanceMiles));
+        assert_eq!(CsvColumn::from_header("Heart Rate"), Some(CsvColumn::HeartRate));
+        assert_eq!(CsvColumn::from_header("notes"), None);
+    }
+}
@@ -0,0 +1,620 @@
+/// Training Metrics Calculator
+///
+/// Derives session metrics from a trackpoint time series:
+/// - 30-second rolling normalized power, intensity factor and TSS against FTP
+/// - hrTSS against LTHR when no power is recorded
+/// - rTSS from normalized graded speed against threshold pace for runs
+/// - Elevation gain with hysteresis, mechanical work (kJ)
+/// - Time-in-zone maps for power and heart rate
+/// - Mean-maximal power for standard durations (the session's power curve)
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::training_metrics::Sport;
+use crate::services::training_analysis_service::ZoneSettings;
+use crate::services::training_file_parser::{haversine_distance, TrackPoint};
+
+/// Rolling window used for normalized power and normalized graded speed
+const NORMALIZATION_WINDOW_SECONDS: usize = 30;
+/// Gaps longer than this are treated as paused recording and not counted
+const MAX_SAMPLE_GAP_SECONDS: i64 = 30;
+/// Minimum rise or drop before an elevation change is counted
+const ELEVATION_HYSTERESIS_METERS: f64 = 3.0;
+/// Window used to estimate grade for running pace normalization
+const GRADE_WINDOW_SECONDS: usize = 10;
+
+/// Coggan power zone upper bounds as a fraction of FTP
+const POWER_ZONE_FTP_FRACTIONS: [f64; 6] = [0.55, 0.75, 0.90, 1.05, 1.20, 1.50];
+/// Heart rate zone upper bounds as a fraction of LTHR
+const HEART_RATE_ZONE_LTHR_FRACTIONS: [f64; 4] = [0.68, 0.83, 0.94, 1.05];
+/// Durations (seconds) sampled for the mean-maximal power curve
+pub const MEAN_MAX_DURATIONS_SECONDS: [u32; 10] = [5, 15, 30, 60, 120, 300, 600, 1200, 1800, 3600];
+
+/// Metrics derived from a trackpoint series
+#[derive(Debug, Clone, Default)]
+pub struct CalculatedMetrics {
+    pub duration: Option<i32>,
+    pub distance: Option<f64>,
+    pub elevation_gain: Option<f64>,
+    pub avg_power: Option<f64>,
+    pub normalized_power: Option<f64>,
+    pub avg_heart_rate: Option<f64>,
+    pub avg_cadence: Option<f64>,
+    pub avg_speed: Option<f64>,
+    pub tss: Option<f64>,
+    pub intensity_factor: Option<f64>,
+    pub work: Option<f64>,
+    pub power_zones: Option<HashMap<String, f64>>, // seconds per zone
+    pub heart_rate_zones: Option<HashMap<String, f64>>, // seconds per zone
+    pub max_core_temperature: Option<f64>, // °C
+    pub peak_powers: Option<BTreeMap<u32, f64>>, // best average watts by duration in seconds
+}
+
+/// Trackpoint channels resampled to one value per recorded second
+#[derive(Debug, Default)]
+struct SecondSeries {
+    power: Vec<Option<f64>>,
+    heart_rate: Vec<Option<f64>>,
+    cadence: Vec<Option<f64>>,
+    speed: Vec<Option<f64>>,
+    distance: Vec<Option<f64>>,
+    elevation: Vec<Option<f64>>,
+}
+
+impl SecondSeries {
+    fn len(&self) -> usize {
+        self.power.len()
+    }
+}
+
+/// Calculator for training load and intensity metrics
+pub struct TrainingMetricsCalculator {
+    zone_settings: Option<ZoneSettings>,
+    sport: Option<Sport>,
+}
+
+impl TrainingMetricsCalculator {
+    pub fn new(zone_settings: Option<ZoneSettings>, sport: Option<Sport>) -> Self {
+        Self { zone_settings, sport }
+    }
+
+    /// Calculate all available metrics for a session
+    pub fn calculate(&self, trackpoints: &[TrackPoint]) -> CalculatedMetrics {
+        if trackpoints.is_empty() {
+            return CalculatedMetrics::default();
+        }
+
+        let series = Self::resample(trackpoints);
+        let recorded_seconds = series.len() as f64;
+
+        let first = trackpoints.first().map(|p| p.timestamp);
+        let last = trackpoints.last().map(|p| p.timestamp);
+        let duration = match (first, last) {
+            (Some(first), Some(last)) => Some((last - first).num_seconds().max(0) as i32),
+            _ => None,
+        };
+
+        let distance = Self::total_distance(trackpoints);
+        let power = Self::present(&series.power).then(|| Self::fill_dropouts(&series.power));
+        let heart_rate: Vec<f64> = series.heart_rate.iter().flatten().copied().collect();
+        let cadence: Vec<f64> = series.cadence.iter().flatten().copied().filter(|c| *c > 0.0).collect();
+
+        let avg_speed = match distance {
+            Some(distance) if recorded_seconds > 0.0 => Some(distance / recorded_seconds),
+            _ => mean(&series.speed.iter().flatten().copied().collect::<Vec<_>>()),
+        };
+
+        let normalized_power = power.as_deref().and_then(normalized_average);
+        let (tss, intensity_factor) = self.training_stress(&series, power.as_deref(), normalized_power);
+
+        CalculatedMetrics {
+            duration,
+            distance,
+            elevation_gain: Self::elevation_gain(trackpoints),
+            avg_power: power.as_deref().and_then(mean),
+            normalized_power,
+            avg_heart_rate: mean(&heart_rate),
+            avg_cadence: mean(&cadence),
+            avg_speed,
+            tss,
+            intensity_factor,
+            work: power.as_ref().map(|p| p.iter().sum::<f64>() / 1000.0),
+            power_zones: power.as_deref().and_then(|p| {
+                self.power_zone_thresholds().map(|thresholds| time_in_zones(p, &thresholds))
+            }),
+            heart_rate_zones: self
+                .heart_rate_zone_thresholds()
+                .filter(|_| !heart_rate.is_empty())
+                .map(|thresholds| time_in_zones(&heart_rate, &thresholds)),
+            max_core_temperature: trackpoints
+                .iter()
+                .filter_map(|p| p.core_temperature)
+                .reduce(f64::max),
+            peak_powers: power.as_deref().map(mean_maximal_power).filter(|peaks| !peaks.is_empty()),
+        }
+    }
+
+    /// Pick the most accurate stress model the data and thresholds allow:
+    /// power TSS, then rTSS for runs, then hrTSS
+    fn training_stress(
+        &self,
+        series: &SecondSeries,
+        power: Option<&[f64]>,
+        normalized_power: Option<f64>,
+    ) -> (Option<f64>, Option<f64>) {
+        let hours = series.len() as f64 / 3600.0;
+        if hours <= 0.0 {
+            return (None, None);
+        }
+
+        let settings = self.zone_settings.as_ref();
+        let ftp = settings.and_then(|s| s.ftp).filter(|ftp| *ftp > 0.0);
+        let threshold_pace = settings.and_then(|s| s.threshold_pace).filter(|pace| *pace > 0.0);
+        let lthr = settings.and_then(|s| s.lthr).filter(|lthr| *lthr > 0.0);
+
+        // Power-based TSS
+        if let (Some(_), Some(np), Some(ftp)) = (power, normalized_power, ftp) {
+            let intensity_factor = np / ftp;
+            return (Some(hours * intensity_factor.powi(2) * 100.0), Some(intensity_factor));
+        }
+
+        // Running TSS from normalized graded speed (threshold pace is seconds per meter)
+        if let (Some(Sport::Running), Some(threshold_pace)) = (&self.sport, threshold_pace) {
+            if let Some(ngs) = Self::graded_speed(series).as_deref().and_then(normalized_average) {
+                let intensity_factor = ngs * threshold_pace;
+                return (Some(hours * intensity_factor.powi(2) * 100.0), Some(intensity_factor));
+            }
+        }
+
+        // Heart rate TSS, accumulated second by second
+        if let Some(lthr) = lthr {
+            let samples: Vec<f64> = series.heart_rate.iter().flatten().copied().collect();
+            if !samples.is_empty() {
+                let hrtss: f64 = samples.iter().map(|hr| (hr / lthr).powi(2)).sum::<f64>() / 3600.0 * 100.0;
+                let intensity_factor = (hrtss / (samples.len() as f64 / 3600.0) / 100.0).sqrt();
+                return (Some(hrtss), Some(intensity_factor));
+            }
+        }
+
+        (None, None)
+    }
+
+    /// Hold each trackpoint's values until the next sample so every recorded
+    /// second carries one value; long gaps count as a single second
+    fn resample(trackpoints: &[TrackPoint]) -> SecondSeries {
+        let mut series = SecondSeries::default();
+
+        for (index, point) in trackpoints.iter().enumerate() {
+            let seconds = match trackpoints.get(index + 1) {
+                Some(next) => {
+                    let gap = ((next.timestamp - point.timestamp).num_milliseconds() as f64 / 1000.0).round() as i64;
+                    if gap > MAX_SAMPLE_GAP_SECONDS { 1 } else { gap.max(0) }
+                }
+                None => 1,
+            };
+
+            for _ in 0..seconds {
+                series.power.push(point.power);
+                series.heart_rate.push(point.heart_rate);
+                series.cadence.push(point.cadence);
+                series.speed.push(point.speed);
+                series.distance.push(point.distance);
+                series.elevation.push(point.elevation);
+            }
+        }
+
+        series
+    }
+
+    fn present(values: &[Option<f64>]) -> bool {
+        values.iter().any(Option::is_some)
+    }
+
+    /// Power dropouts within a recording with power are treated as zero output
+    fn fill_dropouts(values: &[Option<f64>]) -> Vec<f64> {
+        values.iter().map(|v| v.unwrap_or(0.0).max(0.0)).collect()
+    }
+
+    fn total_distance(trackpoints: &[TrackPoint]) -> Option<f64> {
+        let recorded = trackpoints.iter().filter_map(|p| p.distance).fold(None, |max: Option<f64>, d| {
+            Some(max.map_or(d, |m| m.max(d)))
+        });
+        if recorded.is_some() {
+            return recorded;
+        }
+
+        let positions: Vec<(f64, f64)> = trackpoints
+            .iter()
+            .filter_map(|p| p.latitude.zip(p.longitude))
+            .collect();
+        if positions.len() < 2 {
+            return None;
+        }
+
+        Some(
+            positions
+                .windows(2)
+                .map(|w| haversine_distance(w[0].0, w[0].1, w[1].0, w[1].1))
+                .sum(),
+        )
+    }
+
+    /// Total climbing, counting only rises that exceed the hysteresis band
+    fn elevation_gain(trackpoints: &[TrackPoint]) -> Option<f64> {
+        let mut elevations = trackpoints.iter().filter_map(|p| p.elevation);
+        let first = elevations.next()?;
+
+        let mut gain = 0.0;
+        let mut valley = first;
+        let mut peak = first;
+        let mut climbing = false;
+
+        for elevation in elevations {
+            if climbing {
+                if elevation > peak {
+                    peak = elevation;
+                } else if peak - elevation >= ELEVATION_HYSTERESIS_METERS {
+                    gain += peak - valley;
+                    valley = elevation;
+                    climbing = false;
+                }
+            } else if elevation < valley {
+                valley = elevation;
+            } else if elevation - valley >= ELEVATION_HYSTERESIS_METERS {
+                peak = elevation;
+                climbing = true;
+            }
+        }
+
+        if climbing {
+            gain += peak - valley;
+        }
+
+        Some(gain)
+    }
+
+    /// Per-second speed adjusted for grade using the Minetti energy cost of running
+    fn graded_speed(series: &SecondSeries) -> Option<Vec<f64>> {
+        let len = series.len();
+        let speeds: Vec<Option<f64>> = (0..len)
+            .map(|i| {
+                series.speed[i].or_else(|| {
+                    let current = series.distance[i]?;
+                    let previous = series.distance[i.checked_sub(1)?]?;
+                    Some((current - previous).max(0.0))
+                })
+            })
+            .collect();
+
+        if !Self::present(&speeds) {
+            return None;
+        }
+
+        Some(
+            (0..len)
+                .map(|i| {
+                    let speed = speeds[i].unwrap_or(0.0);
+                    let grade = i
+                        .checked_sub(GRADE_WINDOW_SECONDS)
+                        .and_then(|start| {
+                            let rise = series.elevation[i]? - series.elevation[start]?;
+                            let run = series.distance[i]? - series.distance[start]?;
+                            (run > 5.0).then(|| rise / run)
+                        })
+                        .unwrap_or(0.0)
+                        .clamp(-0.45, 0.45);
+
+                    speed * running_cost_factor(grade)
+                })
+                .collect(),
+        )
+    }
+
+    fn power_zone_thresholds(&self) -> Option<Vec<f64>> {
+        let settings = self.zone_settings.as_ref()?;
+        if let Some(zones) = settings.power_zones.as_ref().filter(|z| !z.is_empty()) {
+            return Some(zones.clone());
+        }
+        let ftp = settings.ftp.filter(|ftp| *ftp > 0.0)?;
+        Some(POWER_ZONE_FTP_FRACTIONS.iter().map(|f| f * ftp).collect())
+    }
+
+    fn heart_rate_zone_thresholds(&self) -> Option<Vec<f64>> {
+        let settings = self.zone_settings.as_ref()?;
+        if let Some(zones) = settings.heart_rate_zones.as_ref().filter(|z| !z.is_empty()) {
+            return Some(zones.clone());
+        }
+        let lthr = settings.lthr.filter(|lthr| *lthr > 0.0)?;
+        Some(HEART_RATE_ZONE_LTHR_FRACTIONS.iter().map(|f| f * lthr).collect())
+    }
+}
+
+/// Energy cost of running on a grade relative to flat ground (Minetti et al. 2002)
+fn running_cost_factor(grade: f64) -> f64 {
+    const FLAT_COST: f64 = 3.6; // J/kg/m
+    let cost = 155.4 * grade.powi(5) - 30.4 * grade.powi(4) - 43.3 * grade.powi(3)
+        + 46.3 * grade.powi(2)
+        + 19.5 * grade
+        + FLAT_COST;
+    (cost / FLAT_COST).max(0.0)
+}
+
+/// Fourth-power mean of a 30-second rolling average (normalized power algorithm)
+fn normalized_average(values: &[f64]) -> Option<f64> {
+    if values.len() < NORMALIZATION_WINDOW_SECONDS {
+        return mean(values);
+    }
+
+    let mut window_sum: f64 = values[..NORMALIZATION_WINDOW_SECONDS].iter().sum();
+    let mut fourth_powers = (window_sum / NORMALIZATION_WINDOW_SECONDS as f64).powi(4);
+    let mut count = 1.0;
+
+    for i in NORMALIZATION_WINDOW_SECONDS..values.len() {
+        window_sum += values[i] - values[i - NORMALIZATION_WINDOW_SECONDS];
+        fourth_powers += (window_sum / NORMALIZATION_WINDOW_SECONDS as f64).powi(4);
+        count += 1.0;
+    }
+
+    Some((fourth_powers / count).powf(0.25))
+}
+
+/// Seconds spent in each zone, keyed `zone_1` to `zone_n` for n-1 ascending thresholds
+fn time_in_zones(samples: &[f64], thresholds: &[f64]) -> HashMap<String, f64> {
+    let mut zones: HashMap<String, f64> = (1..=thresholds.len() + 1)
+        .map(|zone| (format!("zone_{}", zone), 0.0))
+        .collect();
+
+    for sample in samples {
+        let zone = thresholds.iter().take_while(|t| sample >= t).count() + 1;
+        *zones.entry(format!("zone_{}", zone)).or_insert(0.0) += 1.0;
+    }
+
+    zones
+}
+
+/// Best rolling average for each standard duration the series is long enough to cover
+fn mean_maximal_power(power: &[f64]) -> BTreeMap<u32, f64> {
+    MEAN_MAX_DURATIONS_SECONDS
+        .iter()
+        .filter(|&&duration| duration as usize <= power.len())
+        .map(|&duration| {
+            let window = duration as usize;
+            let mut sum: f64 = power[..window].iter().sum();
+            let mut best = sum;
+            for i in window..power.len() {
+                sum += power[i] - power[i - window];
+                best = best.max(sum);
+            }
+            (duration, best / window as f64)
+        })
+        .collect()
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration, Utc};
+
+    fn settings(ftp: Option<f64>, lthr: Option<f64>, threshold_pace: Option<f64>) -> ZoneSettings {
+        ZoneSettings {
+            power_zones: None,
+            heart_rate_zones: None,
+            ftp,
+            lthr,
+            threshold_pace,
+        }
+    }
+
+    fn series(seconds: i64, f: impl Fn(i64) -> TrackPoint) -> Vec<TrackPoint> {
+        (0..seconds).map(f).collect()
+    }
+
+    fn point(second: i64) -> TrackPoint {
+        TrackPoint {
+            timestamp: DateTime::<Utc>::UNIX_EPOCH + Duration::seconds(second),
+            latitude: None,
+            longitude: None,
+            elevation: None,
+            heart_rate: None,
+            power: None,
+            cadence: None,
+            speed: None,
+            distance: None,
+            core_temperature: None,
+        }
+    }
+
+    #[test]
+    fn test_steady_power_at_ftp_is_100_tss() {
+        let points = series(3600, |s| TrackPoint { power: Some(250.0), ..point(s) });
+        let calculator = TrainingMetricsCalculator::new(Some(settings(Some(250.0), None, None)), None);
+
+        let metrics = calculator.calculate(&points);
+
+        assert!((metrics.normalized_power.unwrap() - 250.0).abs() < 1e-6);
+        assert!((metrics.intensity_factor.unwrap() - 1.0).abs() < 1e-6);
+        assert!((metrics.tss.unwrap() - 100.0).abs() < 1e-6);
+        assert!((metrics.work.unwrap() - 900.0).abs() < 1e-6);
+        assert_eq!(metrics.duration, Some(3599));
+    }
+
+    #[test]
+    fn test_normalized_power_exceeds_average_for_variable_efforts() {
+        // Alternating 5 minute blocks at 100 W and 300 W
+        let points = series(3600, |s| {
+            let power = if (s / 300) % 2 == 0 { 100.0 } else { 300.0 };
+            TrackPoint { power: Some(power), ..point(s) }
+        });
+        let calculator = TrainingMetricsCalculator::new(Some(settings(Some(250.0), None, None)), None);
+
+        let metrics = calculator.calculate(&points);
+
+        assert!((metrics.avg_power.unwrap() - 200.0).abs() < 1e-6);
+        assert!(metrics.normalized_power.unwrap() > 230.0);
+    }
+
+    #[test]
+    fn test_mean_maximal_power_finds_best_efforts() {
+        // 20 minutes at 200 W with a 5 minute 320 W effort and a 10 second 800 W sprint
+        let points = series(1200, |s| {
+            let power = match s {
+                100..=109 => 800.0,
+                600..=899 => 320.0,
+                _ => 200.0,
+            };
+            TrackPoint { power: Some(power), ..point(s) }
+        });
+
+        let peaks = TrainingMetricsCalculator::new(None, None).calculate(&points).peak_powers.unwrap();
+
+        assert!((peaks[&5] - 800.0).abs() < 1e-6);
+        assert!((peaks[&300] - 320.0).abs() < 1e-6);
+        assert!(peaks[&1200] < peaks[&300]);
+        assert!(!peaks.contains_key(&1800));
+    }
+
+    #[test]
+    fn test_pauses_are_not_counted() {
+        let mut points = series(1800, |s| TrackPoint { power: Some(200.0), ..point(s) });
+        points.extend(series(1800, |s| TrackPoint { power: Some(200.0), ..point(s + 7200) }));
+
+        let metrics = TrainingMetricsCalculator::new(None, None).calculate(&points);
+
+        assert!((metrics.work.unwrap() - 720.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_heart_rate_tss_without_power() {
+        let points = series(3600, |s| TrackPoint { heart_rate: Some(160.0), ..point(s) });
+        let calculator = TrainingMetricsCalculator::new(Some(settings(Some(250.0), Some(160.0), None)), None);
+
+        let metrics = calculator.calculate(&points);
+
+        assert!(metrics.normalized_power.is_none());
+        assert!((metrics.tss.unwrap() - 100.0).abs() < 1e-6);
+        assert_eq!(metrics.avg_heart_rate, Some(160.0));
+    }
+
+    #[test]
+    fn test_running_tss_from_threshold_pace() {
+        // Threshold pace of 4:00/km is 0.24 s/m; run one hour on the flat at that pace
+        let speed = 1.0 / 0.24;
+        let points = series(3600, |s| TrackPoint {
+            speed: Some(speed),
+            distance: Some(s as f64 * speed),
+            elevation: Some(50.0),
+            heart_rate: Some(150.0),
+            ..point(s)
+        });
+        let calculator = TrainingMetricsCalculator::new(
+            Some(settings(None, Some(170.0), Some(0.24))),
+            Some(Sport::Running),
+        );
+
+        let metrics = calculator.calculate(&points);
+
+        assert!((metrics.intensity_factor.unwrap() - 1.0).abs() < 1e-6);
+        assert!((metrics.tss.unwrap() - 100.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_uphill_running_raises_intensity() {
+        let speed = 1.0 / 0.24;
+        let points = series(3600, |s| TrackPoint {
+            speed: Some(speed),
+            distance: Some(s as f64 * speed),
+            elevation: Some(s as f64 * speed * 0.05),
+            ..point(s)
+        });
+        let calculator = TrainingMetricsCalculator::new(
+            Some(settings(None, None, Some(0.24))),
+            Some(Sport::Running),
+        );
+
+        let metrics = calculator.calculate(&points);
+
+        assert!(metrics.intensity_factor.unwrap() > 1.1);
+    }
+
+    #[test]
+    fn test_elevation_gain_ignores_noise() {
+        let noisy = series(600, |s| TrackPoint {
+            elevation: Some(100.0 + if s % 2 == 0 { 1.0 } else { -1.0 }),
+            ..point(s)
+        });
+        assert_eq!(TrainingMetricsCalculator::elevation_gain(&noisy), Some(0.0));
+
+        // Climb 100 m, descend 50 m, climb 30 m
+        let profile: Vec<f64> = (0..=100).chain((50..100).rev()).chain(51..=80).map(|e| e as f64).collect();
+        let climbing: Vec<TrackPoint> = profile
+            .iter()
+            .enumerate()
+            .map(|(s, e)| TrackPoint { elevation: Some(*e), ..point(s as i64) })
+            .collect();
+        assert_eq!(TrainingMetricsCalculator::elevation_gain(&climbing), Some(130.0));
+    }
+
+    #[test]
+    fn test_time_in_zones() {
+        let points = series(100, |s| TrackPoint {
+            power: Some(if s < 60 { 100.0 } else { 280.0 }),
+            heart_rate: Some(if s < 60 { 100.0 } else { 170.0 }),
+            ..point(s)
+        });
+        let calculator = TrainingMetricsCalculator::new(Some(settings(Some(250.0), Some(160.0), None)), None);
+
+        let metrics = calculator.calculate(&points);
+
+        let power_zones = metrics.power_zones.unwrap();
+        assert_eq!(power_zones.len(), 7);
+        assert_eq!(power_zones["zone_1"], 60.0);
+        assert_eq!(power_zones["zone_5"], 40.0);
+
+        let hr_zones = metrics.heart_rate_zones.unwrap();
+        assert_eq!(hr_zones.len(), 5);
+        assert_eq!(hr_zones["zone_1"], 60.0);
+        assert_eq!(hr_zones["zone_5"], 40.0);
+    }
+
+    #[test]
+    fn test_custom_power_zones_take_precedence() {
+        let points = series(10, |s| TrackPoint { power: Some(150.0), ..point(s) });
+        let mut zone_settings = settings(Some(250.0), None, None);
+        zone_settings.power_zones = Some(vec![100.0, 200.0]);
+
+        let metrics = TrainingMetricsCalculator::new(Some(zone_settings), None).calculate(&points);
+
+        let zones = metrics.power_zones.unwrap();
+        assert_eq!(zones.len(), 3);
+        assert_eq!(zones["zone_2"], 10.0);
+    }
+
+    #[test]
+    fn test_distance_falls_back_to_positions() {
+        let points = series(3, |s| TrackPoint {
+            latitude: Some(45.0 + s as f64 * 0.001),
+            longitude: Some(-73.0),
+            ..point(s)
+        });
+
+        let metrics = TrainingMetricsCalculator::new(None, None).calculate(&points);
+
+        assert!((metrics.distance.unwrap() - 222.4).abs() < 0.5);
+        assert!(metrics.tss.is_none());
+    }
+
+    #[test]
+    fn test_empty_trackpoints() {
+        let metrics = TrainingMetricsCalculator::new(None, None).calculate(&[]);
+        assert!(metrics.duration.is_none());
+        assert!(metrics.tss.is_none());
+    }
+}
@@ -7,7 +7,7 @@ use tracing::{info, warn};
 use serde::{Serialize, Deserialize};
 
 use crate::models::{TrainingFeatures, TrainingLoadPrediction, CreateModelPrediction};
-use crate::services::{FeatureEngineeringService, MLModelService, ModelPredictionService};
+use crate::services::{FeatureEngineeringService, MLModelService, ModelPredictionService, ModelVersioningService};
 
 /// Recommendation request with user preferences
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,6 +73,8 @@ pub struct TrainingRecommendationService {
     db: PgPool,
     feature_service: FeatureEngineeringService,
     prediction_service: ModelPredictionService,
+    ml_service: MLModelService,
+    versioning_service: ModelVersioningService,
     edge_case_config: EdgeCaseConfig,
     // In production, this would be a proper cache like Redis
     cache: std::sync::Arc<tokio::sync::RwLock<HashMap<String, (TrainingRecommendation, chrono::DateTime<chrono::Utc>)>>>,
@@ -83,11 +85,15 @@ impl TrainingRecommendationService {
     pub fn new(db: PgPool) -> Self {
         let feature_service = FeatureEngineeringService::new(db.clone());
         let prediction_service = ModelPredictionService::new(db.clone());
+        let ml_service = MLModelService::new(db.clone());
+        let versioning_service = ModelVersioningService::new(db.clone());
 
         Self {
             db,
             feature_service,
             prediction_service,
+            ml_service,
+            versioning_service,
             edge_case_config: EdgeCaseConfig::default(),
             cache: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
         }
@@ -136,10 +142,8 @@ impl TrainingRecommendationService {
         features: &TrainingFeatures,
         request: &RecommendationRequest,
     ) -> Result<TrainingRecommendation> {
-        let mut ml_service = MLModelService::new(self.db.clone());
-
-        // Get base prediction
-        let base_prediction = ml_service.predict_tss(features).await?;
+        // Get base prediction from the user's stored model, or their A/B test arm's
+        let base_prediction = self.predict_for_user(request, features).await?;
 
         // Apply user preferences and constraints
         let adjusted_prediction = self.apply_user_preferences(&base_prediction, request)?;
@@ -164,6 +168,51 @@ impl TrainingRecommendationService {
         })
     }
 
+    /// Predict with the user's own model of their A/B test arm's configuration when they have one stored,
+    /// otherwise with their active model, and log the prediction for experiment analysis
+    async fn predict_for_user(
+        &self,
+        request: &RecommendationRequest,
+        features: &TrainingFeatures,
+    ) -> Result<TrainingLoadPrediction> {
+        let user_id = request.user_id;
+
+        let assignment = match self.versioning_service.assign_model(user_id).await {
+            Ok(assignment) => assignment,
+            Err(e) => {
+                warn!("Failed to assign A/B test arm for user {}: {}", user_id, e);
+                None
+            }
+        };
+
+        let arm_version = match &assignment {
+            Some(assignment) => self.ml_service
+                .latest_model_version(user_id, &assignment.model_type, assignment.feature_schema_version)
+                .await?,
+            None => None,
+        };
+
+        let started = std::time::Instant::now();
+        let prediction = match &arm_version {
+            Some(version) => self.ml_service.predict_tss_with_version(user_id, version, features).await?,
+            None => self.ml_service.predict_tss_for_user(user_id, features).await?,
+        };
+        let latency_ms = started.elapsed().as_secs_f32() * 1000.0;
+
+        // Only credit the arm if it was actually served
+        let served_assignment = assignment.filter(|_| arm_version.is_some());
+        let prediction_date = request.target_date.unwrap_or_else(|| Utc::now().date_naive());
+
+        if let Err(e) = self.versioning_service
+            .log_prediction(user_id, served_assignment.as_ref(), &prediction, latency_ms, prediction_date)
+            .await
+        {
+            warn!("Failed to log prediction for user {}: {}", user_id, e);
+        }
+
+        Ok(prediction)
+    }
+
     /// Detect edge cases that require special handling
     async fn detect_edge_case(&self, features: &TrainingFeatures, user_id: Uuid) -> Result<Option<String>> {
         // Check if user is new (insufficient data)
@@ -483,6 +532,7 @@ mod tests {
             days_until_goal_event: None,
             preferred_workout_types: vec!["endurance".to_string()],
             seasonal_factors: 1.0,
+            ..TrainingFeatures::default()
         }
     }
 
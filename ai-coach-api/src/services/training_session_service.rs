@@ -1,9 +1,9 @@
 use anyhow::Result;
-use chrono::{Utc, NaiveDate};
+use chrono::{DateTime, Utc, NaiveDate};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::models::{TrainingSession, CreateTrainingSession, UpdateTrainingSession, SessionSummary};
+use crate::models::{TrainingSession, CreateTrainingSession, UpdateTrainingSession, UpsertTrainingSession, SessionSummary};
 
 #[derive(Clone)]
 pub struct TrainingSessionService {
@@ -67,6 +67,25 @@ impl TrainingSessionService {
         Ok(sessions)
     }
 
+    /// Sessions changed after `since`, oldest change first
+    pub async fn get_sessions_updated_since(&self, user_id: Uuid, since: DateTime<Utc>, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<TrainingSession>> {
+        let limit = limit.unwrap_or(50);
+        let offset = offset.unwrap_or(0);
+
+        let sessions = sqlx::query_as!(
+            TrainingSession,
+            "SELECT id, user_id, date, trainrs_data, uploaded_file_path, session_type, duration_seconds, distance_meters, created_at, updated_at FROM training_sessions WHERE user_id = $1 AND updated_at > $2 ORDER BY updated_at ASC, id ASC LIMIT $3 OFFSET $4",
+            user_id,
+            since,
+            limit,
+            offset
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(sessions)
+    }
+
     pub async fn get_sessions_by_date_range(&self, user_id: Uuid, start_date: NaiveDate, end_date: NaiveDate) -> Result<Vec<TrainingSession>> {
         let sessions = sqlx::query_as!(
             TrainingSession,
@@ -113,6 +132,41 @@ impl TrainingSessionService {
         Ok(session)
     }
 
+    /// Insert or update a client-logged session, returning None if the ID belongs to another user
+    pub async fn upsert_session(&self, user_id: Uuid, session_data: UpsertTrainingSession) -> Result<Option<TrainingSession>> {
+        let now = Utc::now();
+
+        // Notes live alongside any file-derived metrics in trainrs_data
+        let session = sqlx::query_as!(
+            TrainingSession,
+            r#"
+            INSERT INTO training_sessions (id, user_id, date, trainrs_data, session_type, duration_seconds, distance_meters, created_at, updated_at)
+            VALUES ($1, $2, $3, jsonb_build_object('notes', $4::text), $5, $6, $7, $8, $8)
+            ON CONFLICT (id) DO UPDATE
+            SET date = EXCLUDED.date,
+                trainrs_data = COALESCE(training_sessions.trainrs_data, '{}'::jsonb) || jsonb_build_object('notes', $4::text),
+                session_type = EXCLUDED.session_type,
+                duration_seconds = EXCLUDED.duration_seconds,
+                distance_meters = EXCLUDED.distance_meters,
+                updated_at = EXCLUDED.updated_at
+            WHERE training_sessions.user_id = EXCLUDED.user_id
+            RETURNING id, user_id, date, trainrs_data, uploaded_file_path, session_type, duration_seconds, distance_meters, created_at, updated_at
+            "#,
+            session_data.id,
+            user_id,
+            session_data.date,
+            session_data.notes,
+            session_data.session_type,
+            session_data.duration_seconds,
+            session_data.distance_meters,
+            now
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(session)
+    }
+
     pub async fn delete_session(&self, session_id: Uuid) -> Result<bool> {
         let result = sqlx::query!(
             "DELETE FROM training_sessions WHERE id = $1",
@@ -1,22 +1,56 @@
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use aws_sdk_s3::{
     primitives::ByteStream,
     types::{Delete, ObjectIdentifier},
     Client as S3Client,
 };
+use std::path::{Component, Path, PathBuf};
 use std::time::Duration;
-use tracing::{error, info};
+use tracing::{info, warn};
 use uuid::Uuid;
 
-/// Service for managing video storage in S3-compatible object storage
-pub struct VideoStorageService {
+/// Storage backend for uploaded exercise videos
+#[async_trait]
+pub trait VideoStorage: Send + Sync {
+    /// Upload video file
+    /// Returns the storage key (path) for the uploaded file
+    async fn upload_video(
+        &self,
+        user_id: Uuid,
+        analysis_id: Uuid,
+        file_data: Vec<u8>,
+        content_type: &str,
+    ) -> Result<String>;
+
+    /// Generate a URL the client can use to access the video
+    async fn generate_presigned_url(&self, storage_key: &str) -> Result<String>;
+
+    /// Delete video from storage
+    async fn delete_video(&self, storage_key: &str) -> Result<()>;
+
+    /// Delete multiple videos in batch
+    async fn delete_videos_batch(&self, storage_keys: Vec<String>) -> Result<()>;
+
+    /// Download video from storage
+    async fn download_video(&self, storage_key: &str) -> Result<Vec<u8>>;
+
+    /// Check if video exists in storage
+    async fn video_exists(&self, storage_key: &str) -> Result<bool>;
+
+    /// Get video metadata (size, content type, etc.)
+    async fn get_video_metadata(&self, storage_key: &str) -> Result<VideoMetadata>;
+}
+
+/// Video storage in S3-compatible object storage
+pub struct S3VideoStorage {
     client: S3Client,
     bucket_name: String,
     url_expiry_seconds: u64,
 }
 
-impl VideoStorageService {
-    /// Create a new VideoStorageService
+impl S3VideoStorage {
+    /// Create a new S3VideoStorage
     pub fn new(client: S3Client, bucket_name: String) -> Self {
         Self {
             client,
@@ -25,16 +59,22 @@ impl VideoStorageService {
         }
     }
 
-    /// Upload video file to S3-compatible storage
-    /// Returns the storage key (path) for the uploaded file
-    pub async fn upload_video(
+    /// Set custom URL expiry time
+    pub fn set_url_expiry_seconds(&mut self, seconds: u64) {
+        self.url_expiry_seconds = seconds;
+    }
+}
+
+#[async_trait]
+impl VideoStorage for S3VideoStorage {
+    async fn upload_video(
         &self,
         user_id: Uuid,
         analysis_id: Uuid,
         file_data: Vec<u8>,
         content_type: &str,
     ) -> Result<String> {
-        let storage_key = self.generate_storage_key(user_id, analysis_id, content_type);
+        let storage_key = generate_storage_key(user_id, analysis_id, content_type);
 
         info!(
             "Uploading video to storage: bucket={}, key={}, size={}",
@@ -60,7 +100,7 @@ impl VideoStorageService {
     }
 
     /// Generate a presigned URL for secure video access
-    pub async fn generate_presigned_url(&self, storage_key: &str) -> Result<String> {
+    async fn generate_presigned_url(&self, storage_key: &str) -> Result<String> {
         let presigning_config = aws_sdk_s3::presigning::PresigningConfig::builder()
             .expires_in(Duration::from_secs(self.url_expiry_seconds))
             .build()
@@ -78,8 +118,7 @@ impl VideoStorageService {
         Ok(presigned_request.uri().to_string())
     }
 
-    /// Delete video from storage
-    pub async fn delete_video(&self, storage_key: &str) -> Result<()> {
+    async fn delete_video(&self, storage_key: &str) -> Result<()> {
         info!("Deleting video from storage: {}", storage_key);
 
         self.client
@@ -94,8 +133,7 @@ impl VideoStorageService {
         Ok(())
     }
 
-    /// Delete multiple videos in batch
-    pub async fn delete_videos_batch(&self, storage_keys: Vec<String>) -> Result<()> {
+    async fn delete_videos_batch(&self, storage_keys: Vec<String>) -> Result<()> {
         if storage_keys.is_empty() {
             return Ok(());
         }
@@ -121,8 +159,7 @@ impl VideoStorageService {
         Ok(())
     }
 
-    /// Download video from storage
-    pub async fn download_video(&self, storage_key: &str) -> Result<Vec<u8>> {
+    async fn download_video(&self, storage_key: &str) -> Result<Vec<u8>> {
         info!("Downloading video from storage: {}", storage_key);
 
         let response = self
@@ -146,8 +183,7 @@ impl VideoStorageService {
         Ok(data)
     }
 
-    /// Check if video exists in storage
-    pub async fn video_exists(&self, storage_key: &str) -> Result<bool> {
+    async fn video_exists(&self, storage_key: &str) -> Result<bool> {
         match self
             .client
             .head_object()
@@ -167,8 +203,7 @@ impl VideoStorageService {
         }
     }
 
-    /// Get video metadata (size, content type, etc.)
-    pub async fn get_video_metadata(&self, storage_key: &str) -> Result<VideoMetadata> {
+    async fn get_video_metadata(&self, storage_key: &str) -> Result<VideoMetadata> {
         let response = self
             .client
             .head_object()
@@ -184,28 +219,159 @@ impl VideoStorageService {
             last_modified: response.last_modified().map(|dt| dt.to_string()),
         })
     }
+}
+
+/// Video storage on the local filesystem, for single-box deployments and development
+pub struct LocalVideoStorage {
+    root: PathBuf,
+    base_url: Option<String>,
+}
 
-    /// Generate storage key based on user and analysis IDs
-    fn generate_storage_key(&self, user_id: Uuid, analysis_id: Uuid, content_type: &str) -> String {
-        let extension = self.extract_file_extension(content_type);
-        format!("videos/{}/{}.{}", user_id, analysis_id, extension)
+impl LocalVideoStorage {
+    /// Create a new LocalVideoStorage rooted at `root`
+    pub fn new<P: AsRef<Path>>(root: P) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        std::fs::create_dir_all(&root)
+            .with_context(|| format!("Failed to create video storage directory {}", root.display()))?;
+
+        Ok(Self { root, base_url: None })
+    }
+
+    /// Serve video URLs from `base_url` instead of `file://` paths
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = Some(base_url.trim_end_matches('/').to_string());
+        self
     }
 
-    /// Extract file extension from content type
-    fn extract_file_extension(&self, content_type: &str) -> &str {
-        match content_type {
-            "video/mp4" => "mp4",
-            "video/quicktime" => "mov",
-            "video/x-msvideo" => "avi",
-            "video/webm" => "webm",
-            "video/x-matroska" => "mkv",
-            _ => "mp4", // Default to mp4
+    /// Resolve a storage key to a path, refusing keys that escape the storage root
+    fn resolve(&self, storage_key: &str) -> Result<PathBuf> {
+        let relative = Path::new(storage_key);
+        if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(anyhow::anyhow!("Invalid storage key: {}", storage_key));
         }
+
+        Ok(self.root.join(relative))
     }
+}
 
-    /// Set custom URL expiry time
-    pub fn set_url_expiry_seconds(&mut self, seconds: u64) {
-        self.url_expiry_seconds = seconds;
+#[async_trait]
+impl VideoStorage for LocalVideoStorage {
+    async fn upload_video(
+        &self,
+        user_id: Uuid,
+        analysis_id: Uuid,
+        file_data: Vec<u8>,
+        content_type: &str,
+    ) -> Result<String> {
+        let storage_key = generate_storage_key(user_id, analysis_id, content_type);
+        let path = self.resolve(&storage_key)?;
+
+        info!(
+            "Writing video to local storage: path={}, size={}",
+            path.display(),
+            file_data.len()
+        );
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .context("Failed to create video directory")?;
+        }
+
+        tokio::fs::write(&path, file_data)
+            .await
+            .context("Failed to write video to local storage")?;
+
+        info!("Successfully stored video: {}", storage_key);
+        Ok(storage_key)
+    }
+
+    async fn generate_presigned_url(&self, storage_key: &str) -> Result<String> {
+        let path = self.resolve(storage_key)?;
+
+        Ok(match &self.base_url {
+            Some(base_url) => format!("{}/{}", base_url, storage_key),
+            None => format!("file://{}", path.display()),
+        })
+    }
+
+    async fn delete_video(&self, storage_key: &str) -> Result<()> {
+        info!("Deleting video from local storage: {}", storage_key);
+
+        match tokio::fs::remove_file(self.resolve(storage_key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                warn!("Video already removed from local storage: {}", storage_key);
+                Ok(())
+            }
+            Err(e) => Err(e).context("Failed to delete video from local storage"),
+        }
+    }
+
+    async fn delete_videos_batch(&self, storage_keys: Vec<String>) -> Result<()> {
+        for storage_key in &storage_keys {
+            self.delete_video(storage_key).await?;
+        }
+        Ok(())
+    }
+
+    async fn download_video(&self, storage_key: &str) -> Result<Vec<u8>> {
+        tokio::fs::read(self.resolve(storage_key)?)
+            .await
+            .context("Failed to read video from local storage")
+    }
+
+    async fn video_exists(&self, storage_key: &str) -> Result<bool> {
+        Ok(tokio::fs::try_exists(self.resolve(storage_key)?).await?)
+    }
+
+    async fn get_video_metadata(&self, storage_key: &str) -> Result<VideoMetadata> {
+        let metadata = tokio::fs::metadata(self.resolve(storage_key)?)
+            .await
+            .context("Failed to get video metadata")?;
+
+        let extension = Path::new(storage_key)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+
+        Ok(VideoMetadata {
+            size_bytes: metadata.len() as i64,
+            content_type: content_type_for_extension(extension).map(|s| s.to_string()),
+            last_modified: metadata
+                .modified()
+                .ok()
+                .map(|time| chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339()),
+        })
+    }
+}
+
+/// Generate storage key based on user and analysis IDs
+fn generate_storage_key(user_id: Uuid, analysis_id: Uuid, content_type: &str) -> String {
+    let extension = extract_file_extension(content_type);
+    format!("videos/{}/{}.{}", user_id, analysis_id, extension)
+}
+
+/// Extract file extension from content type
+fn extract_file_extension(content_type: &str) -> &str {
+    match content_type {
+        "video/mp4" => "mp4",
+        "video/quicktime" => "mov",
+        "video/x-msvideo" => "avi",
+        "video/webm" => "webm",
+        "video/x-matroska" => "mkv",
+        _ => "mp4", // Default to mp4
+    }
+}
+
+fn content_type_for_extension(extension: &str) -> Option<&'static str> {
+    match extension {
+        "mp4" => Some("video/mp4"),
+        "mov" => Some("video/quicktime"),
+        "avi" => Some("video/x-msvideo"),
+        "webm" => Some("video/webm"),
+        "mkv" => Some("video/x-matroska"),
+        _ => None,
     }
 }
 
@@ -223,13 +389,9 @@ mod tests {
 
     #[test]
     fn test_generate_storage_key() {
-        let mock_config = aws_config::SdkConfig::builder().build();
-        let client = S3Client::new(&mock_config);
-        let service = VideoStorageService::new(client, "test-bucket".to_string());
-
         let user_id = Uuid::new_v4();
         let analysis_id = Uuid::new_v4();
-        let key = service.generate_storage_key(user_id, analysis_id, "video/mp4");
+        let key = generate_storage_key(user_id, analysis_id, "video/mp4");
 
         assert!(key.starts_with("videos/"));
         assert!(key.contains(&user_id.to_string()));
@@ -239,13 +401,52 @@ mod tests {
 
     #[test]
     fn test_extract_file_extension() {
-        let mock_config = aws_config::SdkConfig::builder().build();
-        let client = S3Client::new(&mock_config);
-        let service = VideoStorageService::new(client, "test-bucket".to_string());
-
-        assert_eq!(service.extract_file_extension("video/mp4"), "mp4");
-        assert_eq!(service.extract_file_extension("video/quicktime"), "mov");
-        assert_eq!(service.extract_file_extension("video/webm"), "webm");
-        assert_eq!(service.extract_file_extension("unknown"), "mp4");
+        assert_eq!(extract_file_extension("video/mp4"), "mp4");
+        assert_eq!(extract_file_extension("video/quicktime"), "mov");
+        assert_eq!(extract_file_extension("video/webm"), "webm");
+        assert_eq!(extract_file_extension("unknown"), "mp4");
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalVideoStorage::new(dir.path()).unwrap();
+
+        let user_id = Uuid::new_v4();
+        let key = storage
+            .upload_video(user_id, Uuid::new_v4(), b"fake video".to_vec(), "video/webm")
+            .await
+            .unwrap();
+
+        assert!(storage.video_exists(&key).await.unwrap());
+        assert_eq!(storage.download_video(&key).await.unwrap(), b"fake video");
+
+        let metadata = storage.get_video_metadata(&key).await.unwrap();
+        assert_eq!(metadata.size_bytes, 10);
+        assert_eq!(metadata.content_type.as_deref(), Some("video/webm"));
+
+        let url = storage.generate_presigned_url(&key).await.unwrap();
+        assert!(url.starts_with("file://"));
+        assert!(url.ends_with(&key));
+
+        storage.delete_video(&key).await.unwrap();
+        assert!(!storage.video_exists(&key).await.unwrap());
+        // Deleting twice is not an error
+        storage.delete_video(&key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_rejects_escaping_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalVideoStorage::new(dir.path())
+            .unwrap()
+            .with_base_url("http://localhost:3000/media/".to_string());
+
+        assert!(storage.download_video("../secrets.txt").await.is_err());
+        assert!(storage.download_video("/etc/passwd").await.is_err());
+        assert_eq!(
+            storage.generate_presigned_url("videos/a/b.mp4").await.unwrap(),
+            "http://localhost:3000/media/videos/a/b.mp4"
+        );
     }
 }
@@ -106,6 +106,31 @@ impl VisionAnalysisService {
         Ok(())
     }
 
+    /// Record where the uploaded video lives so it can be processed and deleted later
+    pub async fn set_video_location(
+        &self,
+        analysis_id: Uuid,
+        video_url: String,
+        storage_key: String,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE vision_analyses
+            SET video_url = $1,
+                metadata = COALESCE(metadata, '{}'::jsonb) || jsonb_build_object('storage_key', $2::text)
+            WHERE id = $3
+            "#,
+        )
+        .bind(video_url)
+        .bind(storage_key)
+        .bind(analysis_id)
+        .execute(&self.db)
+        .await
+        .context("Failed to update video location")?;
+
+        Ok(())
+    }
+
     /// Update video metadata after processing
     pub async fn update_video_metadata(
         &self,
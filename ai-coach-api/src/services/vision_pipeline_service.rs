@@ -0,0 +1,256 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::models::keypoint::{Keypoint as KeypointData, NormalizationParams, PoseFrame};
+use crate::models::vision_analysis::{AnalysisStatus, ExerciseType, Keypoint};
+use crate::services::keypoint_processor::KeypointProcessor;
+use crate::services::movement_analysis_service::MovementAnalysisService;
+use crate::services::pose_estimation_service::{PersonPose, PoseEstimationService};
+use crate::services::video_processing_service::VideoProcessingService;
+use crate::services::video_storage_service::VideoStorage;
+use crate::services::vision_analysis_service::VisionAnalysisService;
+
+/// Default frame sampling rate for pose estimation
+const DEFAULT_FRAMES_PER_SECOND: u32 = 10;
+
+/// Runs an uploaded video through validation, frame extraction, pose estimation,
+/// keypoint processing and movement scoring, recording progress on the analysis record
+pub struct VisionPipelineService {
+    analysis_service: Arc<VisionAnalysisService>,
+    storage: Arc<dyn VideoStorage>,
+    processing_service: Arc<VideoProcessingService>,
+    pose_service: Arc<PoseEstimationService>,
+    movement_service: MovementAnalysisService,
+    frames_per_second: u32,
+}
+
+impl VisionPipelineService {
+    pub fn new(
+        analysis_service: Arc<VisionAnalysisService>,
+        storage: Arc<dyn VideoStorage>,
+        processing_service: Arc<VideoProcessingService>,
+        pose_service: Arc<PoseEstimationService>,
+    ) -> Self {
+        Self {
+            analysis_service,
+            storage,
+            processing_service,
+            pose_service,
+            movement_service: MovementAnalysisService::new(),
+            frames_per_second: DEFAULT_FRAMES_PER_SECOND,
+        }
+    }
+
+    /// Set the frame sampling rate used for pose estimation
+    pub fn with_frames_per_second(mut self, frames_per_second: u32) -> Self {
+        self.frames_per_second = frames_per_second.clamp(1, 60);
+        self
+    }
+
+    /// Process an uploaded video, marking the analysis completed or failed
+    pub async fn process(&self, analysis_id: Uuid, storage_key: &str) -> Result<Vec<PoseFrame>> {
+        info!("Starting vision analysis: {}", analysis_id);
+
+        self.analysis_service
+            .update_status(analysis_id, AnalysisStatus::Processing, None)
+            .await?;
+
+        match self.run(analysis_id, storage_key).await {
+            Ok(frames) => {
+                self.analysis_service
+                    .update_status(analysis_id, AnalysisStatus::Completed, None)
+                    .await?;
+                info!("Completed vision analysis {} with {} pose frames", analysis_id, frames.len());
+                Ok(frames)
+            }
+            Err(e) => {
+                error!("Vision analysis {} failed: {:#}", analysis_id, e);
+                self.analysis_service
+                    .update_status(analysis_id, AnalysisStatus::Failed, Some(format!("{:#}", e)))
+                    .await?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn run(&self, analysis_id: Uuid, storage_key: &str) -> Result<Vec<PoseFrame>> {
+        // Work in a scratch directory that is removed when processing ends
+        let work_dir = tempfile::tempdir().context("Failed to create working directory")?;
+
+        let extension = Path::new(storage_key)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("mp4");
+        let video_path = work_dir.path().join(format!("input.{}", extension));
+
+        let video_data = self.storage.download_video(storage_key).await?;
+        tokio::fs::write(&video_path, video_data)
+            .await
+            .context("Failed to write video to working directory")?;
+
+        let validation = self.processing_service.validate_video(&video_path).await?;
+        if !validation.is_valid {
+            return Err(anyhow::anyhow!(
+                "Video failed validation: {}",
+                validation.issues.join("; ")
+            ));
+        }
+
+        let info = &validation.metadata;
+        self.analysis_service
+            .update_video_metadata(
+                analysis_id,
+                info.duration_seconds,
+                info.resolution_string(),
+                info.format_name.clone(),
+                info.size_bytes,
+            )
+            .await?;
+
+        let frame_paths = self
+            .processing_service
+            .extract_frames(&video_path, &work_dir.path().join("frames"), self.frames_per_second)
+            .await?;
+        if frame_paths.is_empty() {
+            return Err(anyhow::anyhow!("No frames could be extracted from the video"));
+        }
+
+        // Inference is CPU bound, keep it off the async workers
+        let pose_service = Arc::clone(&self.pose_service);
+        let frame_interval_ms = 1000 / self.frames_per_second as u64;
+        let detections = tokio::task::spawn_blocking(move || {
+            estimate_frames(&pose_service, &frame_paths, frame_interval_ms)
+        })
+        .await
+        .context("Pose estimation task panicked")??;
+
+        if detections.is_empty() {
+            return Err(anyhow::anyhow!("No person detected in the video"));
+        }
+
+        for detection in &detections {
+            self.analysis_service
+                .save_pose_detection(
+                    analysis_id,
+                    detection.frame.frame_number as i32,
+                    detection.frame.timestamp_ms as i32,
+                    detection.image_keypoints.clone(),
+                    detection.confidence,
+                )
+                .await?;
+        }
+
+        let frames: Vec<PoseFrame> = detections.into_iter().map(|detection| detection.frame).collect();
+        self.score_movement(analysis_id, &frames).await?;
+
+        Ok(frames)
+    }
+
+    /// Count reps and score form when the analysis names a supported exercise
+    async fn score_movement(&self, analysis_id: Uuid, frames: &[PoseFrame]) -> Result<()> {
+        let exercise = self
+            .analysis_service
+            .get_analysis(analysis_id)
+            .await?
+            .and_then(|analysis| analysis.exercise_type)
+            .map(|name| ExerciseType::from(name.as_str()));
+
+        let Some(exercise) = exercise.filter(MovementAnalysisService::supports) else {
+            info!("Skipping movement scoring for analysis {}: no supported exercise type", analysis_id);
+            return Ok(());
+        };
+
+        let analysis = self.movement_service.analyze(&exercise, frames)?;
+        let biomechanics_data = analysis.biomechanics_data();
+
+        self.analysis_service
+            .save_movement_score(
+                analysis_id,
+                analysis.overall_score,
+                Some(analysis.form_quality),
+                Some(analysis.injury_risk),
+                analysis.range_of_motion,
+                analysis.tempo_consistency,
+                analysis.rep_count,
+                analysis.issues,
+                analysis.recommendations,
+                biomechanics_data,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Pose detected in a single sampled frame
+struct FrameDetection {
+    /// Processed frame with smoothed keypoints and joint angles
+    frame: PoseFrame,
+    /// Raw keypoints in normalized image coordinates, as stored for overlays
+    image_keypoints: Vec<Keypoint>,
+    confidence: f64,
+}
+
+fn estimate_frames(
+    pose_service: &PoseEstimationService,
+    frame_paths: &[PathBuf],
+    frame_interval_ms: u64,
+) -> Result<Vec<FrameDetection>> {
+    let mut processor = KeypointProcessor::new();
+    let mut detections = Vec::with_capacity(frame_paths.len());
+
+    for (index, path) in frame_paths.iter().enumerate() {
+        let image = image::open(path)
+            .with_context(|| format!("Failed to read frame {}", path.display()))?;
+        let result = pose_service.estimate_pose(&image)?;
+
+        // Analyze the most confident athlete in the frame
+        let Some(person) = result
+            .persons
+            .into_iter()
+            .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+        else {
+            warn!("No person detected in frame {}", index);
+            continue;
+        };
+
+        let image_keypoints = to_stored_keypoints(&person);
+
+        // Scale both axes by the longer side so joint angles are not distorted by the aspect ratio
+        let pixels = person.to_pixel_coords(result.image_width, result.image_height);
+        let side = result.image_width.max(result.image_height) as f32;
+        let keypoints = pixels
+            .keypoints
+            .into_iter()
+            .map(|kp| KeypointData::new(kp.x, kp.y, kp.confidence, kp.name))
+            .collect();
+
+        let frame = PoseFrame::new(index as u64 * frame_interval_ms, index as u32, keypoints);
+        let frame = processor.process_frame(frame, &NormalizationParams::from_image_dimensions(side, side))?;
+
+        detections.push(FrameDetection {
+            frame,
+            image_keypoints,
+            confidence: person.confidence as f64,
+        });
+    }
+
+    Ok(detections)
+}
+
+fn to_stored_keypoints(person: &PersonPose) -> Vec<Keypoint> {
+    person
+        .keypoints
+        .iter()
+        .map(|kp| Keypoint {
+            joint_name: kp.name.clone(),
+            x: kp.x,
+            y: kp.y,
+            z: None,
+            confidence: kp.confidence,
+        })
+        .collect()
+}
@@ -0,0 +1,203 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::models::{
+    CreateHrvReadingRequest, CreateRestingHrRequest, CreateSleepDataRequest, DataSource,
+};
+use crate::services::oura_api_client::OuraApiClient;
+use crate::services::polar_api_client::PolarApiClient;
+use crate::services::whoop_api_client::WhoopApiClient;
+
+/// OAuth client registration for a wearable platform
+#[derive(Debug, Clone)]
+pub struct OAuthCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+}
+
+/// Tokens returned by a provider's token endpoint, normalized across platforms
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WearableTokens {
+    pub access_token: String,
+    /// Absent for providers whose tokens don't expire (Polar)
+    pub refresh_token: Option<String>,
+    /// Seconds until the access token expires, if it does
+    pub expires_in: Option<i64>,
+    pub provider_user_id: Option<String>,
+    pub scopes: Option<Vec<String>>,
+}
+
+/// A wearable platform we can connect to over OAuth and pull recovery data from.
+///
+/// Implementations only talk to the provider's API and map its payloads into the same
+/// request types used for manual entry; storage and token bookkeeping live in
+/// `WearableIntegrationService`.
+#[async_trait]
+pub trait WearableProvider: Send + Sync {
+    /// Which `DataSource` records from this provider are stored under
+    fn source(&self) -> DataSource;
+
+    /// URL to send the user to for authorization
+    fn authorization_url(&self, state: &str) -> String;
+
+    /// Exchange an authorization code from the OAuth callback for tokens
+    async fn exchange_code(&self, code: &str) -> Result<WearableTokens>;
+
+    /// Get a fresh access token
+    async fn refresh_token(&self, refresh_token: &str) -> Result<WearableTokens>;
+
+    /// Called once after a successful token exchange, for providers that need the user
+    /// registered with the app before data can be read
+    async fn on_connected(&self, _user_id: Uuid, _tokens: &WearableTokens) -> Result<()> {
+        Ok(())
+    }
+
+    /// Nightly sleep summaries between the two dates (inclusive)
+    async fn fetch_sleep(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CreateSleepDataRequest>>;
+
+    /// HRV (RMSSD) readings between the two dates (inclusive)
+    async fn fetch_hrv(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CreateHrvReadingRequest>>;
+
+    /// Resting heart rate readings between the two dates (inclusive)
+    async fn fetch_resting_hr(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CreateRestingHrRequest>>;
+}
+
+/// The wearable providers configured for this deployment
+#[derive(Clone, Default)]
+pub struct WearableProviderRegistry {
+    providers: HashMap<DataSource, Arc<dyn WearableProvider>>,
+}
+
+impl WearableProviderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register every provider whose OAuth credentials are present in the config
+    pub fn from_config(config: &AppConfig) -> Result<Self> {
+        let mut registry = Self::new();
+
+        if let Some(credentials) = config.wearable_credentials(DataSource::Oura) {
+            registry.register(Arc::new(OuraApiClient::new(
+                credentials.client_id,
+                credentials.client_secret,
+                credentials.redirect_uri,
+            )?));
+        }
+        if let Some(credentials) = config.wearable_credentials(DataSource::Whoop) {
+            registry.register(Arc::new(WhoopApiClient::new(credentials)?));
+        }
+        if let Some(credentials) = config.wearable_credentials(DataSource::Polar) {
+            registry.register(Arc::new(PolarApiClient::new(credentials)?));
+        }
+
+        for source in [DataSource::Oura, DataSource::Whoop, DataSource::Polar] {
+            if registry.get(&source).is_some() {
+                info!("{} wearable integration enabled", source);
+            } else {
+                warn!("{} wearable integration disabled (missing OAuth credentials)", source);
+            }
+        }
+
+        Ok(registry)
+    }
+
+    pub fn register(&mut self, provider: Arc<dyn WearableProvider>) {
+        self.providers.insert(provider.source(), provider);
+    }
+
+    pub fn get(&self, source: &DataSource) -> Option<Arc<dyn WearableProvider>> {
+        self.providers.get(source).cloned()
+    }
+
+    /// Configured providers, in a stable order
+    pub fn sources(&self) -> Vec<DataSource> {
+        let mut sources: Vec<DataSource> = self.providers.keys().copied().collect();
+        sources.sort_by_key(|source| source.as_str().to_string());
+        sources
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+}
+
+/// The provider rejected our credentials: the access token was refused, or the refresh
+/// token is no longer valid. Only reconnecting the provider fixes this.
+#[derive(Error, Debug)]
+#[error("{provider} rejected the connection's credentials ({status})")]
+pub struct ProviderAuthError {
+    pub provider: DataSource,
+    pub status: StatusCode,
+}
+
+/// Whether an error (or anything in its context chain) is a `ProviderAuthError`
+pub fn is_auth_error(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|cause| cause.downcast_ref::<ProviderAuthError>().is_some())
+}
+
+/// Fail on rate limiting and non-success statuses, logging the provider's error body.
+/// Unauthorized responses and `invalid_grant` token errors become `ProviderAuthError`.
+pub(crate) async fn check_response(provider: DataSource, what: &str, response: Response) -> Result<Response> {
+    let status = response.status();
+
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        let retry_seconds = response
+            .headers()
+            .get("Retry-After")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(60);
+
+        warn!(
+            "{} API rate limit exceeded. Retry after {} seconds",
+            provider, retry_seconds
+        );
+        return Err(anyhow!(
+            "Rate limit exceeded. Retry after {} seconds",
+            retry_seconds
+        ));
+    }
+
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        error!("{} {} failed: {} - {}", provider, what, status, error_text);
+
+        let rejected = matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+            || (status == StatusCode::BAD_REQUEST && error_text.contains("invalid_grant"));
+        if rejected {
+            return Err(anyhow::Error::new(ProviderAuthError { provider, status })
+                .context(format!("Failed to {}", what)));
+        }
+        return Err(anyhow!("Failed to {}: {}", what, status));
+    }
+
+    Ok(response)
+}
@@ -0,0 +1,338 @@
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::interval;
+
+use crate::models::{DataSource, WearableConnection};
+use crate::services::wearable_integration_service::SyncResult;
+use crate::services::wearable_provider::is_auth_error;
+use crate::services::{BackgroundJobService, RecoveryAnalysisService, WearableIntegrationService};
+
+#[derive(Debug, Clone)]
+pub struct WearableSyncConfig {
+    pub enabled: bool,
+    /// How often each connection is synced after a clean run
+    pub sync_interval: Duration,
+    pub poll_interval: Duration,
+    pub batch_size: i64,
+    /// Claimed connections are skipped by other replicas for this long
+    pub lease_timeout: Duration,
+    /// Days before the cursor to re-fetch, so late-scored nights are picked up
+    pub overlap_days: i64,
+    /// Window for a connection's first sync, and the furthest back a stale cursor reaches
+    pub initial_backfill_days: i64,
+    /// Consecutive unauthorized syncs before the connection is marked as needing re-auth
+    pub reauth_after: i32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for WearableSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sync_interval: Duration::from_secs(60 * 60),
+            poll_interval: Duration::from_secs(60),
+            batch_size: 25,
+            lease_timeout: Duration::from_secs(10 * 60),
+            overlap_days: 2,
+            initial_backfill_days: 30,
+            reauth_after: 3,
+            base_backoff: Duration::from_secs(5 * 60),
+            max_backoff: Duration::from_secs(12 * 60 * 60),
+        }
+    }
+}
+
+impl WearableSyncConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            enabled: std::env::var("WEARABLE_SYNC_ENABLED")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.enabled),
+            sync_interval: std::env::var("WEARABLE_SYNC_INTERVAL_MINUTES")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|minutes| *minutes > 0)
+                .map_or(defaults.sync_interval, |minutes| {
+                    Duration::from_secs(minutes * 60)
+                }),
+            batch_size: std::env::var("WEARABLE_SYNC_BATCH_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.batch_size),
+            ..defaults
+        }
+    }
+
+    /// Delay before retrying after `failures` consecutive failed syncs: base * 2^failures, capped
+    pub fn retry_backoff(&self, failures: i32) -> Duration {
+        let factor = 2u32.saturating_pow(failures.clamp(0, 31) as u32);
+        self.base_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+
+    /// Date range to fetch for a connection synced up to `cursor`
+    pub fn sync_window(
+        &self,
+        cursor: Option<NaiveDate>,
+        today: NaiveDate,
+    ) -> (NaiveDate, NaiveDate) {
+        let earliest = today - ChronoDuration::days(self.initial_backfill_days);
+        let start = cursor
+            .map(|cursor| (cursor - ChronoDuration::days(self.overlap_days)).max(earliest))
+            .unwrap_or(earliest);
+
+        (start.min(today), today)
+    }
+}
+
+/// Walks active wearable connections on a schedule, pulling new data incrementally from each
+/// provider and recalculating the day's recovery score once something new arrives.
+pub struct WearableSyncScheduler {
+    integration_service: WearableIntegrationService,
+    recovery_service: RecoveryAnalysisService,
+    config: WearableSyncConfig,
+}
+
+impl WearableSyncScheduler {
+    pub fn new(
+        db: PgPool,
+        integration_service: WearableIntegrationService,
+        config: WearableSyncConfig,
+        background_job_service: Arc<BackgroundJobService>,
+    ) -> Self {
+        Self {
+            integration_service,
+            recovery_service: RecoveryAnalysisService::new(db, background_job_service),
+            config,
+        }
+    }
+
+    /// Start the sync loop in the background
+    pub fn start(self: Arc<Self>) {
+        if !self.config.enabled {
+            tracing::info!("Scheduled wearable sync disabled");
+            return;
+        }
+        if self.integration_service.providers().is_empty() {
+            return;
+        }
+
+        tokio::spawn(async move {
+            self.run().await;
+        });
+    }
+
+    async fn run(&self) {
+        let mut interval = interval(self.config.poll_interval);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.run_due_syncs().await {
+                tracing::error!("Failed to run scheduled wearable sync: {}", e);
+            }
+        }
+    }
+
+    /// Sync every connection that is due, one batch at a time
+    async fn run_due_syncs(&self) -> Result<()> {
+        let lease = ChronoDuration::from_std(self.config.lease_timeout)?;
+
+        loop {
+            let connections = self
+                .integration_service
+                .claim_due_connections(self.config.batch_size, lease)
+                .await?;
+            let claimed = connections.len() as i64;
+
+            for connection in connections {
+                let connection_id = connection.id;
+                if let Err(e) = self.sync_connection(connection).await {
+                    tracing::error!(
+                        "Failed to record wearable sync for connection {}: {}",
+                        connection_id,
+                        e
+                    );
+                }
+            }
+
+            if claimed < self.config.batch_size {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn sync_connection(&self, mut connection: WearableConnection) -> Result<()> {
+        let source: DataSource = connection.provider.parse()?;
+        let provider = self.integration_service.provider(source)?;
+        let today = Utc::now().date_naive();
+        let (start_date, end_date) = self.config.sync_window(connection.sync_cursor, today);
+
+        // Renew tokens that would expire before the next scheduled run
+        let refresh_margin =
+            ChronoDuration::from_std(self.config.sync_interval)? + ChronoDuration::minutes(5);
+
+        let outcome = match self
+            .integration_service
+            .ensure_valid_token(provider.as_ref(), &mut connection, refresh_margin)
+            .await
+        {
+            Ok(()) => {
+                self.integration_service
+                    .sync_range(provider.as_ref(), &connection, start_date, end_date)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        match outcome {
+            Ok(result) if result.errors.is_empty() => {
+                let next_sync_at =
+                    Utc::now() + ChronoDuration::from_std(self.config.sync_interval)?;
+                self.integration_service
+                    .record_sync_success(connection.id, end_date, next_sync_at)
+                    .await?;
+                self.recalculate_recovery(&connection, &result, today).await;
+            }
+            Ok(result) => {
+                self.record_failure(&connection, &result.errors.join("; "), result.auth_failed)
+                    .await?;
+                self.recalculate_recovery(&connection, &result, today).await;
+            }
+            Err(e) => {
+                self.record_failure(&connection, &format!("{:#}", e), is_auth_error(&e))
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn record_failure(
+        &self,
+        connection: &WearableConnection,
+        error: &str,
+        auth_failure: bool,
+    ) -> Result<()> {
+        let backoff = self.config.retry_backoff(connection.consecutive_failures);
+        let next_sync_at = Utc::now() + ChronoDuration::from_std(backoff)?;
+
+        let needs_reauth = self
+            .integration_service
+            .record_sync_failure(
+                connection.id,
+                error,
+                auth_failure,
+                next_sync_at,
+                self.config.reauth_after,
+            )
+            .await?;
+
+        if needs_reauth {
+            tracing::warn!(
+                "{} connection for user {} needs re-authorization: {}",
+                connection.provider,
+                connection.user_id,
+                error
+            );
+        } else {
+            tracing::warn!(
+                "Scheduled {} sync failed for user {}, retrying at {}: {}",
+                connection.provider,
+                connection.user_id,
+                next_sync_at,
+                error
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Refresh today's recovery score once new data has landed
+    async fn recalculate_recovery(
+        &self,
+        connection: &WearableConnection,
+        result: &SyncResult,
+        today: NaiveDate,
+    ) {
+        if result.ingested() == 0 {
+            return;
+        }
+
+        if let Err(e) = self
+            .recovery_service
+            .calculate_daily_recovery(connection.user_id, today)
+            .await
+        {
+            tracing::error!(
+                "Failed to calculate recovery for user {} after {} sync: {}",
+                connection.user_id,
+                connection.provider,
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_first_sync_backfills() {
+        let config = WearableSyncConfig::default();
+        let today = date("2024-03-31");
+
+        assert_eq!(config.sync_window(None, today), (date("2024-03-01"), today));
+    }
+
+    #[test]
+    fn test_incremental_window_overlaps_cursor() {
+        let config = WearableSyncConfig::default();
+        let today = date("2024-03-31");
+
+        assert_eq!(
+            config.sync_window(Some(date("2024-03-30")), today),
+            (date("2024-03-28"), today)
+        );
+        // A cursor of today still re-fetches the overlap
+        assert_eq!(
+            config.sync_window(Some(today), today),
+            (date("2024-03-29"), today)
+        );
+    }
+
+    #[test]
+    fn test_stale_cursor_is_capped_at_backfill() {
+        let config = WearableSyncConfig::default();
+        let today = date("2024-03-31");
+
+        assert_eq!(
+            config.sync_window(Some(date("2023-12-01")), today),
+            (date("2024-03-01"), today)
+        );
+    }
+
+    #[test]
+    fn test_retry_backoff_grows_exponentially_and_caps() {
+        let config = WearableSyncConfig::default();
+
+        assert_eq!(config.retry_backoff(0), Duration::from_secs(5 * 60));
+        assert_eq!(config.retry_backoff(1), Duration::from_secs(10 * 60));
+        assert_eq!(config.retry_backoff(3), Duration::from_secs(40 * 60));
+        assert_eq!(config.retry_backoff(10), config.max_backoff);
+        assert_eq!(config.retry_backoff(100), config.max_backoff);
+    }
+}
@@ -0,0 +1,502 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes128Gcm, Nonce};
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{Duration as ChronoDuration, Utc};
+use hkdf::Hkdf;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::{PublicKey, SecretKey};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use reqwest::{Client, StatusCode, Url};
+use serde::Serialize;
+use sha2::Sha256;
+use std::time::Duration;
+
+/// Record size advertised in the aes128gcm header; the whole payload always fits in one record
+const RECORD_SIZE: u32 = 4096;
+/// Largest plaintext that fits in a single record after the padding delimiter and GCM tag
+pub const MAX_PAYLOAD_SIZE: usize = RECORD_SIZE as usize - 17;
+/// How long VAPID tokens stay valid; push services reject anything over 24 hours
+const VAPID_TOKEN_LIFETIME_HOURS: i64 = 12;
+
+/// Application server key pair used to sign VAPID tokens (RFC 8292)
+#[derive(Clone)]
+pub struct VapidKeys {
+    signing_key: SigningKey,
+    /// Uncompressed public key, base64url encoded; browsers pass this as `applicationServerKey`
+    public_key: String,
+    /// Contact for the push service operator, a `mailto:` or `https:` URL
+    subject: String,
+}
+
+impl std::fmt::Debug for VapidKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VapidKeys")
+            .field("public_key", &self.public_key)
+            .field("subject", &self.subject)
+            .finish_non_exhaustive()
+    }
+}
+
+impl VapidKeys {
+    /// Load keys from a base64url encoded private key, as generated by `web-push generate-vapid-keys`
+    pub fn from_private_key(private_key: &str, subject: impl Into<String>) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(private_key.trim().trim_end_matches('='))
+            .context("VAPID private key is not valid base64url")?;
+        let signing_key =
+            SigningKey::from_slice(&bytes).map_err(|_| anyhow!("Invalid VAPID private key"))?;
+        let public_key = URL_SAFE_NO_PAD.encode(
+            signing_key
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes(),
+        );
+
+        Ok(Self {
+            signing_key,
+            public_key,
+            subject: subject.into(),
+        })
+    }
+
+    /// Read `VAPID_PRIVATE_KEY` and `VAPID_SUBJECT`; `None` if Web Push isn't configured
+    pub fn from_env() -> Option<Self> {
+        let private_key = std::env::var("VAPID_PRIVATE_KEY").ok()?;
+        let subject = std::env::var("VAPID_SUBJECT").ok()?;
+
+        match Self::from_private_key(&private_key, subject) {
+            Ok(keys) => Some(keys),
+            Err(e) => {
+                tracing::error!("Web Push disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// Signed ES256 JWT authorizing requests to the push service at `audience`
+    fn token(&self, audience: &str) -> Result<String> {
+        let header = URL_SAFE_NO_PAD.encode(r#"{"typ":"JWT","alg":"ES256"}"#);
+        let claims = serde_json::json!({
+            "aud": audience,
+            "exp": (Utc::now() + ChronoDuration::hours(VAPID_TOKEN_LIFETIME_HOURS)).timestamp(),
+            "sub": self.subject,
+        });
+        let claims = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims)?);
+
+        let signing_input = format!("{}.{}", header, claims);
+        let signature: Signature = self.signing_key.sign(signing_input.as_bytes());
+
+        Ok(format!(
+            "{}.{}",
+            signing_input,
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        ))
+    }
+}
+
+/// Browser subscription a message is sent to
+#[derive(Debug, Clone)]
+pub struct PushTarget<'a> {
+    pub endpoint: &'a str,
+    /// Subscription public key (`keys.p256dh`), base64url encoded
+    pub p256dh: &'a str,
+    /// Subscription auth secret (`keys.auth`), base64url encoded
+    pub auth: &'a str,
+}
+
+/// Push service `Urgency` header (RFC 8030 section 5.3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushUrgency {
+    VeryLow,
+    Low,
+    Normal,
+    High,
+}
+
+impl PushUrgency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PushUrgency::VeryLow => "very-low",
+            PushUrgency::Low => "low",
+            PushUrgency::Normal => "normal",
+            PushUrgency::High => "high",
+        }
+    }
+}
+
+/// Payload handed to the service worker's `push` event
+#[derive(Debug, Clone, Serialize)]
+pub struct PushMessage {
+    pub title: String,
+    pub body: String,
+    /// Replaces an earlier notification with the same tag on the device
+    pub tag: Option<String>,
+    pub data: Option<serde_json::Value>,
+    #[serde(skip)]
+    pub urgency: PushUrgency,
+    #[serde(skip)]
+    pub ttl_seconds: u32,
+}
+
+/// What the push service did with a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushOutcome {
+    /// Accepted for delivery
+    Delivered,
+    /// The subscription no longer exists (404/410) and should be removed
+    Expired,
+    /// Rejected for another reason
+    Failed(String),
+}
+
+/// Web Push client sending VAPID-signed, aes128gcm-encrypted messages (RFC 8291)
+#[derive(Debug, Clone)]
+pub struct WebPushClient {
+    client: Client,
+    vapid: VapidKeys,
+}
+
+impl WebPushClient {
+    pub fn new(vapid: VapidKeys) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self { client, vapid })
+    }
+
+    pub fn vapid_public_key(&self) -> &str {
+        self.vapid.public_key()
+    }
+
+    /// Encrypt and post a message to a subscription's push service
+    pub async fn send(&self, target: &PushTarget<'_>, message: &PushMessage) -> Result<PushOutcome> {
+        let endpoint = Url::parse(target.endpoint).context("Invalid push endpoint")?;
+        let audience = endpoint.origin().ascii_serialization();
+
+        let payload = serde_json::to_vec(message)?;
+        let body = encrypt_payload(&payload, target.p256dh, target.auth)?;
+        let token = self.vapid.token(&audience)?;
+
+        let response = self
+            .client
+            .post(endpoint)
+            .header(
+                "Authorization",
+                format!("vapid t={}, k={}", token, self.vapid.public_key()),
+            )
+            .header("Content-Encoding", "aes128gcm")
+            .header("Content-Type", "application/octet-stream")
+            .header("TTL", message.ttl_seconds.to_string())
+            .header("Urgency", message.urgency.as_str())
+            .body(body)
+            .send()
+            .await
+            .context("Failed to reach push service")?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(PushOutcome::Delivered);
+        }
+        if status == StatusCode::NOT_FOUND || status == StatusCode::GONE {
+            return Ok(PushOutcome::Expired);
+        }
+
+        let error_text = response.text().await.unwrap_or_default();
+        Ok(PushOutcome::Failed(format!(
+            "Push service returned {}: {}",
+            status,
+            error_text.trim()
+        )))
+    }
+}
+
+/// Encrypt a payload for a subscription with a fresh ephemeral key and salt
+pub fn encrypt_payload(payload: &[u8], p256dh: &str, auth: &str) -> Result<Vec<u8>> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+
+    encrypt_with(payload, p256dh, auth, &SecretKey::random(&mut OsRng), salt)
+}
+
+/// aes128gcm content coding of RFC 8188 keyed as in RFC 8291 section 3
+fn encrypt_with(
+    payload: &[u8],
+    p256dh: &str,
+    auth: &str,
+    server_secret: &SecretKey,
+    salt: [u8; 16],
+) -> Result<Vec<u8>> {
+    if payload.len() > MAX_PAYLOAD_SIZE {
+        return Err(anyhow!(
+            "Push payload is {} bytes, the limit is {}",
+            payload.len(),
+            MAX_PAYLOAD_SIZE
+        ));
+    }
+
+    let ua_public_bytes = URL_SAFE_NO_PAD
+        .decode(p256dh.trim_end_matches('='))
+        .context("Subscription p256dh key is not valid base64url")?;
+    let ua_public = PublicKey::from_sec1_bytes(&ua_public_bytes)
+        .map_err(|_| anyhow!("Subscription p256dh key is not a P-256 point"))?;
+    let auth_secret = URL_SAFE_NO_PAD
+        .decode(auth.trim_end_matches('='))
+        .context("Subscription auth secret is not valid base64url")?;
+
+    let server_public = server_secret.public_key().to_encoded_point(false);
+    let ua_public_point = ua_public.to_encoded_point(false);
+    let shared_secret = p256::ecdh::diffie_hellman(
+        server_secret.to_nonzero_scalar(),
+        ua_public.as_affine(),
+    );
+
+    // Combine the ECDH secret with the subscription's auth secret
+    let mut key_info = b"WebPush: info\0".to_vec();
+    key_info.extend_from_slice(ua_public_point.as_bytes());
+    key_info.extend_from_slice(server_public.as_bytes());
+    let mut ikm = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(&auth_secret), shared_secret.raw_secret_bytes())
+        .expand(&key_info, &mut ikm)
+        .map_err(|_| anyhow!("Failed to derive push input key"))?;
+
+    let hkdf = Hkdf::<Sha256>::new(Some(&salt), &ikm);
+    let mut content_key = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    hkdf.expand(b"Content-Encoding: aes128gcm\0", &mut content_key)
+        .and_then(|_| hkdf.expand(b"Content-Encoding: nonce\0", &mut nonce))
+        .map_err(|_| anyhow!("Failed to derive push content key"))?;
+
+    // Single record, terminated by the last-record padding delimiter
+    let mut plaintext = payload.to_vec();
+    plaintext.push(0x02);
+    let ciphertext = Aes128Gcm::new(&content_key.into())
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|_| anyhow!("Failed to encrypt push payload"))?;
+
+    let key_id = server_public.as_bytes();
+    let mut body = Vec::with_capacity(21 + key_id.len() + ciphertext.len());
+    body.extend_from_slice(&salt);
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(key_id.len() as u8);
+    body.extend_from_slice(key_id);
+    body.extend_from_slice(&ciphertext);
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::signature::Verifier;
+    use p256::ecdsa::VerifyingKey;
+    use wiremock::matchers::{header, header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // Keys and salt from RFC 8291 Appendix A
+    const PLAINTEXT: &str = "When I grow up, I want to be a watermelon";
+    const AS_PRIVATE: &str = "yfWPiYE-n46HLnH0KqZOF1fJJU3MYrct3AELtAQ-oRw";
+    const UA_PRIVATE: &str = "q1dXpw3UpT5VOmu_cf_v6ih07Aems3njxI-JWgLcM94";
+    const UA_PUBLIC: &str =
+        "BCVxsr7N_eNgVRqvHtD0zTZsEc6-VV-JvLexhqUzORcxaOzi6-AYWXvTBHm4bjyPjs7Vd8pZGH6SRpkNtoIAiw4";
+    const AUTH_SECRET: &str = "BTBZMqHH6r4Tts7J_aSIgg";
+    const SALT: &str = "DGv6ra1nlYgDCS1FRnbzlw";
+    const ENCRYPTED: &str = "DGv6ra1nlYgDCS1FRnbzlwAAEABBBP4z9KsN6nGRTbVYI_c7VJSPQTBtkgcy27mlmlMoZIIgDll6e3vCYLocInmYWAmS6TlzAC8wEqKK6PBru3jl7A_yl95bQpu6cVPTpK4Mqgkf1CXztLVBSt2Ks3oZwbuwXPXLWyouBWLVWGNWQexSgSxsj_Qulcy4a-fN";
+
+    fn decode(value: &str) -> Vec<u8> {
+        URL_SAFE_NO_PAD.decode(value).unwrap()
+    }
+
+    /// Decrypt on the user agent side, as a browser would
+    fn decrypt(body: &[u8], ua_private: &SecretKey, auth: &[u8]) -> Vec<u8> {
+        let salt = &body[..16];
+        let key_len = body[20] as usize;
+        let server_public = PublicKey::from_sec1_bytes(&body[21..21 + key_len]).unwrap();
+        let ciphertext = &body[21 + key_len..];
+
+        let shared_secret =
+            p256::ecdh::diffie_hellman(ua_private.to_nonzero_scalar(), server_public.as_affine());
+        let mut key_info = b"WebPush: info\0".to_vec();
+        key_info.extend_from_slice(ua_private.public_key().to_encoded_point(false).as_bytes());
+        key_info.extend_from_slice(&body[21..21 + key_len]);
+        let mut ikm = [0u8; 32];
+        Hkdf::<Sha256>::new(Some(auth), shared_secret.raw_secret_bytes())
+            .expand(&key_info, &mut ikm)
+            .unwrap();
+
+        let hkdf = Hkdf::<Sha256>::new(Some(salt), &ikm);
+        let mut content_key = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        hkdf.expand(b"Content-Encoding: aes128gcm\0", &mut content_key).unwrap();
+        hkdf.expand(b"Content-Encoding: nonce\0", &mut nonce).unwrap();
+
+        let mut plaintext = Aes128Gcm::new(&content_key.into())
+            .decrypt(Nonce::from_slice(&nonce), ciphertext)
+            .unwrap();
+        assert_eq!(plaintext.pop(), Some(0x02));
+        plaintext
+    }
+
+    fn vapid_keys() -> VapidKeys {
+        VapidKeys::from_private_key(AS_PRIVATE, "mailto:ops@example.com").unwrap()
+    }
+
+    fn message() -> PushMessage {
+        PushMessage {
+            title: "Injury Risk Warning".to_string(),
+            body: "Take a recovery day".to_string(),
+            tag: Some("alert".to_string()),
+            data: None,
+            urgency: PushUrgency::High,
+            ttl_seconds: 3600,
+        }
+    }
+
+    #[test]
+    fn test_encryption_matches_rfc8291_vector() {
+        let server_secret = SecretKey::from_slice(&decode(AS_PRIVATE)).unwrap();
+        let salt: [u8; 16] = decode(SALT).try_into().unwrap();
+
+        let body = encrypt_with(
+            PLAINTEXT.as_bytes(),
+            UA_PUBLIC,
+            AUTH_SECRET,
+            &server_secret,
+            salt,
+        )
+        .unwrap();
+
+        assert_eq!(URL_SAFE_NO_PAD.encode(body), ENCRYPTED);
+    }
+
+    #[test]
+    fn test_encrypted_payload_decrypts_on_user_agent() {
+        let ua_private = SecretKey::from_slice(&decode(UA_PRIVATE)).unwrap();
+        let body = encrypt_payload(b"{\"title\":\"Hello\"}", UA_PUBLIC, AUTH_SECRET).unwrap();
+
+        assert_eq!(
+            decrypt(&body, &ua_private, &decode(AUTH_SECRET)),
+            b"{\"title\":\"Hello\"}"
+        );
+        // Fresh salt and ephemeral key on every message
+        assert_ne!(
+            body,
+            encrypt_payload(b"{\"title\":\"Hello\"}", UA_PUBLIC, AUTH_SECRET).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_oversized_payload_is_rejected() {
+        let payload = vec![b'x'; MAX_PAYLOAD_SIZE + 1];
+        assert!(encrypt_payload(&payload, UA_PUBLIC, AUTH_SECRET).is_err());
+    }
+
+    #[test]
+    fn test_vapid_token_is_signed_for_audience() {
+        let keys = vapid_keys();
+        let token = keys.token("https://push.example.net").unwrap();
+        let parts: Vec<&str> = token.split('.').collect();
+        assert_eq!(parts.len(), 3);
+
+        let claims: serde_json::Value = serde_json::from_slice(&decode(parts[1])).unwrap();
+        assert_eq!(claims["aud"], "https://push.example.net");
+        assert_eq!(claims["sub"], "mailto:ops@example.com");
+        assert!(claims["exp"].as_i64().unwrap() > Utc::now().timestamp());
+
+        let verifying_key = VerifyingKey::from_sec1_bytes(&decode(keys.public_key())).unwrap();
+        let signature = Signature::from_slice(&decode(parts[2])).unwrap();
+        let signing_input = format!("{}.{}", parts[0], parts[1]);
+        assert!(verifying_key
+            .verify(signing_input.as_bytes(), &signature)
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_posts_encrypted_message() {
+        let server = MockServer::start().await;
+        let client = WebPushClient::new(vapid_keys()).unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/push/abc"))
+            .and(header("Content-Encoding", "aes128gcm"))
+            .and(header("TTL", "3600"))
+            .and(header("Urgency", "high"))
+            .and(header_exists("Authorization"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let endpoint = format!("{}/push/abc", server.uri());
+        let target = PushTarget {
+            endpoint: &endpoint,
+            p256dh: UA_PUBLIC,
+            auth: AUTH_SECRET,
+        };
+        let outcome = client.send(&target, &message()).await.unwrap();
+        assert_eq!(outcome, PushOutcome::Delivered);
+
+        let request = &server.received_requests().await.unwrap()[0];
+        let authorization = request.headers.get("Authorization").unwrap().to_str().unwrap();
+        assert!(authorization.starts_with("vapid t="));
+        assert!(authorization.ends_with(&format!("k={}", vapid_keys().public_key())));
+
+        let ua_private = SecretKey::from_slice(&decode(UA_PRIVATE)).unwrap();
+        let payload: serde_json::Value =
+            serde_json::from_slice(&decrypt(&request.body, &ua_private, &decode(AUTH_SECRET)))
+                .unwrap();
+        assert_eq!(payload["title"], "Injury Risk Warning");
+        assert_eq!(payload["tag"], "alert");
+    }
+
+    #[tokio::test]
+    async fn test_gone_subscription_is_expired() {
+        let server = MockServer::start().await;
+        let client = WebPushClient::new(vapid_keys()).unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/push/gone"))
+            .respond_with(ResponseTemplate::new(410))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/push/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/push/throttled"))
+            .respond_with(ResponseTemplate::new(429).set_body_string("slow down"))
+            .mount(&server)
+            .await;
+
+        for (endpoint, expected) in [
+            ("gone", PushOutcome::Expired),
+            ("missing", PushOutcome::Expired),
+            (
+                "throttled",
+                PushOutcome::Failed(
+                    "Push service returned 429 Too Many Requests: slow down".to_string(),
+                ),
+            ),
+        ] {
+            let endpoint = format!("{}/push/{}", server.uri(), endpoint);
+            let target = PushTarget {
+                endpoint: &endpoint,
+                p256dh: UA_PUBLIC,
+                auth: AUTH_SECRET,
+            };
+            assert_eq!(client.send(&target, &message()).await.unwrap(), expected);
+        }
+    }
+}
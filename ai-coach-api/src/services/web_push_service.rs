@@ -0,0 +1,458 @@
+/// Web Push Service
+///
+/// Stores browser push subscriptions per device and fans notifications out to them through
+/// `WebPushClient`. Subscriptions the push service reports as gone (404/410) are deleted;
+/// other failures are counted on the subscription and retried with the next notification.
+
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::models::{
+    CreatePushSubscriptionRequest, DeliveryStatus, Notification, NotificationPriority,
+    PushSubscription,
+};
+use crate::services::web_push_client::{
+    PushMessage, PushOutcome, PushTarget, PushUrgency, VapidKeys, WebPushClient,
+};
+
+const PUSH_SUBSCRIPTION_COLUMNS: &str = r#"
+    id, user_id, endpoint, p256dh_key, auth_secret, device_name, user_agent, expires_at,
+    last_success_at, last_failure_at, failure_count, created_at, updated_at
+"#;
+
+/// TTL for notifications without an expiry
+const DEFAULT_TTL_SECONDS: i64 = 24 * 60 * 60;
+/// Push services cap TTL at four weeks
+const MAX_TTL_SECONDS: i64 = 28 * 24 * 60 * 60;
+
+/// Domains of the browser push services (Chrome/Edge via FCM, Firefox, Windows, Safari).
+/// Endpoints are requested from inside the server, so no other host is accepted.
+const PUSH_SERVICE_DOMAINS: &[&str] = &[
+    "fcm.googleapis.com",
+    "android.googleapis.com",
+    "push.services.mozilla.com",
+    "notify.windows.com",
+    "push.apple.com",
+];
+
+/// Result of pushing one notification to all of a user's devices
+#[derive(Debug, Default)]
+pub struct PushDeliveryResult {
+    pub delivered: usize,
+    /// Subscriptions removed because they expired or were unsubscribed
+    pub pruned: usize,
+    pub errors: Vec<String>,
+}
+
+impl PushDeliveryResult {
+    /// Delivered if any device accepted it, cancelled if there was no device to send to
+    pub fn status(&self) -> DeliveryStatus {
+        if self.delivered > 0 {
+            DeliveryStatus::Delivered
+        } else if !self.errors.is_empty() {
+            DeliveryStatus::Failed
+        } else {
+            DeliveryStatus::Cancelled
+        }
+    }
+
+    pub fn error_message(&self) -> Option<String> {
+        if self.errors.is_empty() {
+            None
+        } else {
+            Some(self.errors.join("; "))
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WebPushService {
+    db: PgPool,
+    client: WebPushClient,
+}
+
+impl WebPushService {
+    pub fn new(db: PgPool, client: WebPushClient) -> Self {
+        Self { db, client }
+    }
+
+    /// Build from the `VAPID_*` environment variables; `None` if Web Push isn't configured
+    pub fn from_env(db: PgPool) -> Option<Self> {
+        let vapid = VapidKeys::from_env()?;
+
+        match WebPushClient::new(vapid) {
+            Ok(client) => Some(Self::new(db, client)),
+            Err(e) => {
+                warn!("Web Push disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn vapid_public_key(&self) -> &str {
+        self.client.vapid_public_key()
+    }
+
+    // ========================================================================
+    // Subscriptions
+    // ========================================================================
+
+    /// Store a browser subscription; re-subscribing the same endpoint updates it in place
+    pub async fn subscribe(
+        &self,
+        user_id: Uuid,
+        request: CreatePushSubscriptionRequest,
+        user_agent: Option<&str>,
+    ) -> Result<PushSubscription> {
+        validate_subscription(&request)?;
+
+        let expires_at = request
+            .expiration_time
+            .and_then(DateTime::<Utc>::from_timestamp_millis);
+
+        let subscription = sqlx::query_as::<_, PushSubscription>(&format!(
+            r#"
+            INSERT INTO push_subscriptions
+                (user_id, endpoint, p256dh_key, auth_secret, device_name, user_agent, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (endpoint) DO UPDATE SET
+                user_id = EXCLUDED.user_id,
+                p256dh_key = EXCLUDED.p256dh_key,
+                auth_secret = EXCLUDED.auth_secret,
+                device_name = COALESCE(EXCLUDED.device_name, push_subscriptions.device_name),
+                user_agent = EXCLUDED.user_agent,
+                expires_at = EXCLUDED.expires_at,
+                failure_count = 0
+            RETURNING {}
+            "#,
+            PUSH_SUBSCRIPTION_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(&request.endpoint)
+        .bind(&request.keys.p256dh)
+        .bind(&request.keys.auth)
+        .bind(&request.device_name)
+        .bind(user_agent)
+        .bind(expires_at)
+        .fetch_one(&self.db)
+        .await?;
+
+        info!("Registered push subscription {} for user {}", subscription.id, user_id);
+        Ok(subscription)
+    }
+
+    pub async fn list_subscriptions(&self, user_id: Uuid) -> Result<Vec<PushSubscription>> {
+        let subscriptions = sqlx::query_as::<_, PushSubscription>(&format!(
+            "SELECT {} FROM push_subscriptions WHERE user_id = $1 ORDER BY created_at DESC",
+            PUSH_SUBSCRIPTION_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(subscriptions)
+    }
+
+    /// Remove one of the user's subscriptions; false if it doesn't exist
+    pub async fn unsubscribe(&self, user_id: Uuid, subscription_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM push_subscriptions WHERE id = $1 AND user_id = $2")
+            .bind(subscription_id)
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ========================================================================
+    // Delivery
+    // ========================================================================
+
+    /// Push a notification to every device the user has subscribed
+    pub async fn send_notification(&self, notification: &Notification) -> Result<PushDeliveryResult> {
+        let message = notification_message(notification, Utc::now());
+        self.send_message(notification.user_id, &message).await
+    }
+
+    /// Push a message to every device the user has subscribed
+    pub async fn send_message(&self, user_id: Uuid, message: &PushMessage) -> Result<PushDeliveryResult> {
+        let subscriptions = self.list_subscriptions(user_id).await?;
+        let mut result = PushDeliveryResult::default();
+
+        for subscription in subscriptions {
+            if subscription.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+                self.remove_subscription(subscription.id).await?;
+                result.pruned += 1;
+                continue;
+            }
+
+            // Subscriptions stored before endpoints were restricted to the push services
+            if let Err(e) = validate_push_endpoint(&subscription.endpoint) {
+                warn!("Removing push subscription {}: {}", subscription.id, e);
+                self.remove_subscription(subscription.id).await?;
+                result.pruned += 1;
+                continue;
+            }
+
+            let target = PushTarget {
+                endpoint: &subscription.endpoint,
+                p256dh: &subscription.p256dh_key,
+                auth: &subscription.auth_secret,
+            };
+
+            let outcome = match self.client.send(&target, message).await {
+                Ok(outcome) => outcome,
+                Err(e) => PushOutcome::Failed(format!("{:#}", e)),
+            };
+
+            match outcome {
+                PushOutcome::Delivered => {
+                    self.record_success(subscription.id).await?;
+                    result.delivered += 1;
+                }
+                PushOutcome::Expired => {
+                    info!(
+                        "Removing expired push subscription {} for user {}",
+                        subscription.id, subscription.user_id
+                    );
+                    self.remove_subscription(subscription.id).await?;
+                    result.pruned += 1;
+                }
+                PushOutcome::Failed(error) => {
+                    warn!(
+                        "Push to subscription {} failed for user {}: {}",
+                        subscription.id, user_id, error
+                    );
+                    self.record_failure(subscription.id).await?;
+                    result.errors.push(error);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn record_success(&self, subscription_id: Uuid) -> Result<()> {
+        sqlx::query(
+            "UPDATE push_subscriptions SET last_success_at = NOW(), failure_count = 0 WHERE id = $1",
+        )
+        .bind(subscription_id)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_failure(&self, subscription_id: Uuid) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE push_subscriptions
+            SET last_failure_at = NOW(), failure_count = failure_count + 1
+            WHERE id = $1
+            "#,
+        )
+        .bind(subscription_id)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn remove_subscription(&self, subscription_id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM push_subscriptions WHERE id = $1")
+            .bind(subscription_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Payload for a notification; the TTL runs until the notification expires
+pub fn notification_message(notification: &Notification, now: DateTime<Utc>) -> PushMessage {
+    let ttl_seconds = notification
+        .expires_at
+        .map(|expires_at| (expires_at - now).num_seconds())
+        .unwrap_or(DEFAULT_TTL_SECONDS)
+        .clamp(0, MAX_TTL_SECONDS);
+
+    let urgency = match notification.priority {
+        NotificationPriority::Critical | NotificationPriority::High => PushUrgency::High,
+        NotificationPriority::Medium => PushUrgency::Normal,
+        NotificationPriority::Low => PushUrgency::Low,
+    };
+
+    PushMessage {
+        title: notification.title.clone(),
+        body: notification.message.clone(),
+        tag: Some(notification.id.to_string()),
+        data: Some(serde_json::json!({
+            "notification_id": notification.id,
+            "notification_type": notification.notification_type,
+            "data": notification.data,
+        })),
+        urgency,
+        ttl_seconds: ttl_seconds as u32,
+    }
+}
+
+/// Push endpoints must be HTTPS URLs on a known push service, so deliveries can't be aimed at
+/// internal addresses
+fn validate_push_endpoint(endpoint: &str) -> Result<()> {
+    let endpoint = Url::parse(endpoint).context("Invalid push endpoint")?;
+    if endpoint.scheme() != "https" {
+        return Err(anyhow!("Push endpoint must be an HTTPS URL"));
+    }
+
+    // `domain` is None for IP-literal hosts
+    let Some(host) = endpoint.domain().map(|host| host.trim_end_matches('.').to_ascii_lowercase()) else {
+        return Err(anyhow!("Push endpoint must be a push service domain, not an IP address"));
+    };
+    let known = PUSH_SERVICE_DOMAINS
+        .iter()
+        .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)));
+    if !known || endpoint.port().is_some() {
+        return Err(anyhow!("Push endpoint is not a known push service"));
+    }
+
+    Ok(())
+}
+
+fn validate_subscription(request: &CreatePushSubscriptionRequest) -> Result<()> {
+    validate_push_endpoint(&request.endpoint)?;
+
+    let p256dh = URL_SAFE_NO_PAD
+        .decode(request.keys.p256dh.trim_end_matches('='))
+        .context("p256dh key is not valid base64url")?;
+    if p256dh.len() != 65 || p256dh[0] != 0x04 {
+        return Err(anyhow!("p256dh key must be an uncompressed P-256 public key"));
+    }
+
+    let auth = URL_SAFE_NO_PAD
+        .decode(request.keys.auth.trim_end_matches('='))
+        .context("auth secret is not valid base64url")?;
+    if auth.len() != 16 {
+        return Err(anyhow!("auth secret must be 16 bytes"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DeliveryChannel, NotificationCategory, NotificationType, PushSubscriptionKeys};
+    use chrono::Duration;
+
+    fn notification(priority: NotificationPriority, expires_at: Option<DateTime<Utc>>) -> Notification {
+        let now = Utc::now();
+        Notification {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            notification_type: NotificationType::InjuryRisk,
+            category: NotificationCategory::Health,
+            priority,
+            title: "Injury Risk Warning".to_string(),
+            message: "Elevated injury risk".to_string(),
+            data: None,
+            scheduled_at: now,
+            sent_at: None,
+            read_at: None,
+            delivery_channels: vec![DeliveryChannel::WebPush],
+            delivery_status: DeliveryStatus::Scheduled,
+            expires_at,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn subscription_request(p256dh: &str, auth: &str) -> CreatePushSubscriptionRequest {
+        CreatePushSubscriptionRequest {
+            endpoint: "https://fcm.googleapis.com/fcm/send/abc".to_string(),
+            expiration_time: None,
+            keys: PushSubscriptionKeys {
+                p256dh: p256dh.to_string(),
+                auth: auth.to_string(),
+            },
+            device_name: None,
+        }
+    }
+
+    #[test]
+    fn test_message_ttl_and_urgency() {
+        let now = Utc::now();
+
+        let message = notification_message(
+            &notification(NotificationPriority::Critical, Some(now + Duration::hours(3))),
+            now,
+        );
+        assert_eq!(message.ttl_seconds, 3 * 60 * 60);
+        assert_eq!(message.urgency, PushUrgency::High);
+
+        let message = notification_message(&notification(NotificationPriority::Low, None), now);
+        assert_eq!(message.ttl_seconds, DEFAULT_TTL_SECONDS as u32);
+        assert_eq!(message.urgency, PushUrgency::Low);
+
+        // Already expired
+        let message = notification_message(
+            &notification(NotificationPriority::Medium, Some(now - Duration::hours(1))),
+            now,
+        );
+        assert_eq!(message.ttl_seconds, 0);
+    }
+
+    #[test]
+    fn test_delivery_status() {
+        let mut result = PushDeliveryResult::default();
+        assert_eq!(result.status(), DeliveryStatus::Cancelled);
+
+        result.errors.push("Push service returned 500".to_string());
+        assert_eq!(result.status(), DeliveryStatus::Failed);
+
+        result.delivered = 1;
+        assert_eq!(result.status(), DeliveryStatus::Delivered);
+    }
+
+    #[test]
+    fn test_validate_subscription_keys() {
+        let p256dh = "BCVxsr7N_eNgVRqvHtD0zTZsEc6-VV-JvLexhqUzORcxaOzi6-AYWXvTBHm4bjyPjs7Vd8pZGH6SRpkNtoIAiw4";
+        let auth = "BTBZMqHH6r4Tts7J_aSIgg";
+
+        assert!(validate_subscription(&subscription_request(p256dh, auth)).is_ok());
+        assert!(validate_subscription(&subscription_request(p256dh, "c2hvcnQ")).is_err());
+        assert!(validate_subscription(&subscription_request("c2hvcnQ", auth)).is_err());
+
+        let mut request = subscription_request(p256dh, auth);
+        for endpoint in [
+            "https://updates.push.services.mozilla.com/wpush/v2/abc",
+            "https://wns2-par02p.notify.windows.com/w/?token=abc",
+            "https://web.push.apple.com/abc",
+        ] {
+            request.endpoint = endpoint.to_string();
+            assert!(validate_subscription(&request).is_ok(), "{}", endpoint);
+        }
+
+        for endpoint in [
+            "ftp://push.example.com/abc",
+            "http://fcm.googleapis.com/fcm/send/abc",
+            "https://127.0.0.1:8080/abc",
+            "https://169.254.169.254/latest/meta-data",
+            "https://[::1]/abc",
+            "https://localhost/abc",
+            "https://10.0.0.5/abc",
+            "https://push.example.com/abc",
+            "https://fcm.googleapis.com.evil.example/abc",
+            "https://evilfcm.googleapis.com.example/abc",
+            "https://fcm.googleapis.com:8443/fcm/send/abc",
+        ] {
+            request.endpoint = endpoint.to_string();
+            assert!(validate_subscription(&request).is_err(), "{}", endpoint);
+        }
+    }
+}
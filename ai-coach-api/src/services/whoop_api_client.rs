@@ -0,0 +1,564 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, FixedOffset, NaiveDate, Utc};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::models::{
+    CreateHrvReadingRequest, CreateRestingHrRequest, CreateSleepDataRequest, DataSource,
+};
+use crate::services::wearable_provider::{
+    check_response, OAuthCredentials, WearableProvider, WearableTokens,
+};
+
+/// Upper bound on pages fetched per collection, so a bad `next_token` can't loop forever
+const MAX_PAGES: usize = 50;
+
+/// Whoop Developer API v2 Client
+///
+/// Sleep stages come from sleep activities; HRV (RMSSD) and resting heart rate come from
+/// the daily recovery score.
+/// API Documentation: https://developer.whoop.com/api
+pub struct WhoopApiClient {
+    client: Client,
+    credentials: OAuthCredentials,
+    base_url: String,
+}
+
+impl WhoopApiClient {
+    pub fn new(credentials: OAuthCredentials) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            credentials,
+            base_url: "https://api.prod.whoop.com".to_string(),
+        })
+    }
+
+    /// Point the client at a different API host (used by tests)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    async fn request_token(&self, params: &[(&str, &str)], what: &str) -> Result<WhoopTokenResponse> {
+        let response = self
+            .client
+            .post(format!("{}/oauth/oauth2/token", self.base_url))
+            .form(params)
+            .send()
+            .await
+            .with_context(|| format!("Failed to send {} request", what))?;
+
+        let response = check_response(DataSource::Whoop, what, response).await?;
+
+        response
+            .json::<WhoopTokenResponse>()
+            .await
+            .context("Failed to parse token response")
+    }
+
+    /// Fetch every page of a collection between two dates
+    async fn get_collection<T: DeserializeOwned>(
+        &self,
+        access_token: &str,
+        collection: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<T>> {
+        let start = start_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let end = (end_date + ChronoDuration::days(1)).and_hms_opt(0, 0, 0).unwrap().and_utc();
+
+        let mut records = Vec::new();
+        let mut next_token: Option<String> = None;
+
+        for _ in 0..MAX_PAGES {
+            let mut query = vec![
+                ("start", start.to_rfc3339()),
+                ("end", end.to_rfc3339()),
+                ("limit", "25".to_string()),
+            ];
+            if let Some(token) = &next_token {
+                query.push(("nextToken", token.clone()));
+            }
+
+            let response = self
+                .client
+                .get(format!("{}/developer/v2/{}", self.base_url, collection))
+                .bearer_auth(access_token)
+                .query(&query)
+                .send()
+                .await
+                .with_context(|| format!("Failed to fetch {}", collection))?;
+
+            let response = check_response(DataSource::Whoop, &format!("fetch {}", collection), response).await?;
+            let page = response
+                .json::<WhoopPage<T>>()
+                .await
+                .with_context(|| format!("Failed to parse {}", collection))?;
+
+            records.extend(page.records);
+            match page.next_token.filter(|token| !token.is_empty()) {
+                Some(token) => next_token = Some(token),
+                None => break,
+            }
+        }
+
+        Ok(records)
+    }
+
+    pub async fn get_sleep(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<WhoopSleep>> {
+        self.get_collection(access_token, "activity/sleep", start_date, end_date)
+            .await
+    }
+
+    pub async fn get_recoveries(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<WhoopRecovery>> {
+        self.get_collection(access_token, "recovery", start_date, end_date)
+            .await
+    }
+}
+
+#[async_trait]
+impl WearableProvider for WhoopApiClient {
+    fn source(&self) -> DataSource {
+        DataSource::Whoop
+    }
+
+    fn authorization_url(&self, state: &str) -> String {
+        format!(
+            "{}/oauth/oauth2/auth?response_type=code&client_id={}&redirect_uri={}&scope=offline%20read:recovery%20read:sleep&state={}",
+            self.base_url,
+            self.credentials.client_id,
+            urlencoding::encode(&self.credentials.redirect_uri),
+            state
+        )
+    }
+
+    async fn exchange_code(&self, code: &str) -> Result<WearableTokens> {
+        let params = [
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.credentials.redirect_uri),
+            ("client_id", &self.credentials.client_id),
+            ("client_secret", &self.credentials.client_secret),
+        ];
+
+        Ok(self.request_token(&params, "token exchange").await?.into())
+    }
+
+    async fn refresh_token(&self, refresh_token: &str) -> Result<WearableTokens> {
+        let params = [
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", &self.credentials.client_id),
+            ("client_secret", &self.credentials.client_secret),
+            ("scope", "offline"),
+        ];
+
+        Ok(self.request_token(&params, "token refresh").await?.into())
+    }
+
+    async fn fetch_sleep(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CreateSleepDataRequest>> {
+        let sleeps = self.get_sleep(access_token, start_date, end_date).await?;
+        Ok(sleeps.iter().filter_map(sleep_request).collect())
+    }
+
+    async fn fetch_hrv(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CreateHrvReadingRequest>> {
+        let recoveries = self.get_recoveries(access_token, start_date, end_date).await?;
+        Ok(recoveries.iter().filter_map(hrv_request).collect())
+    }
+
+    async fn fetch_resting_hr(
+        &self,
+        access_token: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CreateRestingHrRequest>> {
+        let recoveries = self.get_recoveries(access_token, start_date, end_date).await?;
+        Ok(recoveries.iter().filter_map(resting_hr_request).collect())
+    }
+}
+
+/// Scored, non-nap sleep as a sleep record; Whoop reports stage durations in milliseconds.
+/// The sleep date is the local date the user woke up.
+fn sleep_request(sleep: &WhoopSleep) -> Option<CreateSleepDataRequest> {
+    if sleep.nap || sleep.score_state != "SCORED" {
+        return None;
+    }
+    let score = sleep.score.as_ref()?;
+    let stages = &score.stage_summary;
+    let hours = |millis: i64| millis as f64 / 3_600_000.0;
+
+    let wake_date = sleep
+        .timezone_offset
+        .as_deref()
+        .and_then(|offset| offset.parse::<FixedOffset>().ok())
+        .map(|offset| sleep.end.with_timezone(&offset).date_naive())
+        .unwrap_or_else(|| sleep.end.date_naive());
+
+    Some(CreateSleepDataRequest {
+        total_sleep_hours: hours(
+            stages.total_light_sleep_time_milli
+                + stages.total_slow_wave_sleep_time_milli
+                + stages.total_rem_sleep_time_milli,
+        ),
+        deep_sleep_hours: Some(hours(stages.total_slow_wave_sleep_time_milli)),
+        rem_sleep_hours: Some(hours(stages.total_rem_sleep_time_milli)),
+        light_sleep_hours: Some(hours(stages.total_light_sleep_time_milli)),
+        awake_hours: Some(hours(stages.total_awake_time_milli)),
+        sleep_efficiency: score.sleep_efficiency_percentage,
+        sleep_latency_minutes: None,
+        bedtime: Some(sleep.start),
+        wake_time: Some(sleep.end),
+        sleep_date: Some(wake_date),
+        metadata: Some(serde_json::json!({
+            "whoop_sleep_id": sleep.id,
+            "sleep_performance_percentage": score.sleep_performance_percentage,
+            "respiratory_rate": score.respiratory_rate
+        })),
+    })
+}
+
+fn hrv_request(recovery: &WhoopRecovery) -> Option<CreateHrvReadingRequest> {
+    let score = recovery.scored()?;
+
+    Some(CreateHrvReadingRequest {
+        rmssd: Some(score.hrv_rmssd_milli?),
+        sdnn: None,
+        pnn50: None,
+        measurement_timestamp: Some(recovery.created_at),
+        metadata: Some(serde_json::json!({
+            "whoop_sleep_id": recovery.sleep_id,
+            "recovery_score": score.recovery_score
+        })),
+    })
+}
+
+fn resting_hr_request(recovery: &WhoopRecovery) -> Option<CreateRestingHrRequest> {
+    let score = recovery.scored()?;
+
+    Some(CreateRestingHrRequest {
+        resting_hr: score.resting_heart_rate?,
+        measurement_timestamp: Some(recovery.created_at),
+        metadata: Some(serde_json::json!({
+            "whoop_sleep_id": recovery.sleep_id,
+            "recovery_score": score.recovery_score
+        })),
+    })
+}
+
+// ============================================================================
+// OAuth Response Types
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhoopTokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+}
+
+impl From<WhoopTokenResponse> for WearableTokens {
+    fn from(token: WhoopTokenResponse) -> Self {
+        Self {
+            access_token: token.access_token,
+            refresh_token: token.refresh_token,
+            expires_in: Some(token.expires_in),
+            provider_user_id: None,
+            scopes: token
+                .scope
+                .map(|scope| scope.split_whitespace().map(str::to_string).collect()),
+        }
+    }
+}
+
+// ============================================================================
+// Whoop API Data Types
+// ============================================================================
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WhoopPage<T> {
+    pub records: Vec<T>,
+    pub next_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhoopSleep {
+    pub id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub timezone_offset: Option<String>,
+    #[serde(default)]
+    pub nap: bool,
+    /// SCORED, PENDING_SCORE or UNSCORABLE
+    pub score_state: String,
+    pub score: Option<WhoopSleepScore>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhoopSleepScore {
+    pub stage_summary: WhoopStageSummary,
+    pub respiratory_rate: Option<f64>,
+    pub sleep_performance_percentage: Option<f64>,
+    pub sleep_efficiency_percentage: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhoopStageSummary {
+    pub total_in_bed_time_milli: i64,
+    pub total_awake_time_milli: i64,
+    pub total_light_sleep_time_milli: i64,
+    pub total_slow_wave_sleep_time_milli: i64,
+    pub total_rem_sleep_time_milli: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhoopRecovery {
+    pub cycle_id: i64,
+    pub sleep_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub score_state: String,
+    pub score: Option<WhoopRecoveryScore>,
+}
+
+impl WhoopRecovery {
+    /// Score, unless Whoop is still calibrating or hasn't scored the recovery
+    fn scored(&self) -> Option<&WhoopRecoveryScore> {
+        self.score
+            .as_ref()
+            .filter(|score| self.score_state == "SCORED" && !score.user_calibrating)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhoopRecoveryScore {
+    #[serde(default)]
+    pub user_calibrating: bool,
+    pub recovery_score: Option<f64>,
+    pub resting_heart_rate: Option<f64>,
+    pub hrv_rmssd_milli: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::wearable_provider::is_auth_error;
+    use serde_json::json;
+    use wiremock::matchers::{body_string_contains, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mock_client() -> (MockServer, WhoopApiClient) {
+        let server = MockServer::start().await;
+        let client = WhoopApiClient::new(OAuthCredentials {
+            client_id: "whoop_client".to_string(),
+            client_secret: "whoop_secret".to_string(),
+            redirect_uri: "http://localhost:3000/callback".to_string(),
+        })
+        .unwrap()
+        .with_base_url(server.uri());
+        (server, client)
+    }
+
+    fn sleep_record(id: &str, nap: bool, score_state: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "user_id": 10129,
+            "created_at": "2024-03-02T07:10:00.000Z",
+            "updated_at": "2024-03-02T07:10:00.000Z",
+            "start": "2024-03-02T03:30:00.000Z",
+            "end": "2024-03-02T11:30:00.000Z",
+            "timezone_offset": "-05:00",
+            "nap": nap,
+            "score_state": score_state,
+            "score": {
+                "stage_summary": {
+                    "total_in_bed_time_milli": 28_800_000,
+                    "total_awake_time_milli": 1_800_000,
+                    "total_no_data_time_milli": 0,
+                    "total_light_sleep_time_milli": 12_600_000,
+                    "total_slow_wave_sleep_time_milli": 7_200_000,
+                    "total_rem_sleep_time_milli": 7_200_000,
+                    "sleep_cycle_count": 4,
+                    "disturbance_count": 12
+                },
+                "respiratory_rate": 15.2,
+                "sleep_performance_percentage": 88.0,
+                "sleep_consistency_percentage": 80.0,
+                "sleep_efficiency_percentage": 93.75
+            }
+        })
+    }
+
+    #[test]
+    fn test_authorization_url_requests_offline_access() {
+        let client = WhoopApiClient::new(OAuthCredentials {
+            client_id: "whoop_client".to_string(),
+            client_secret: "whoop_secret".to_string(),
+            redirect_uri: "http://localhost:3000/callback".to_string(),
+        })
+        .unwrap();
+
+        let url = client.authorization_url("state_12345");
+
+        assert!(url.starts_with("https://api.prod.whoop.com/oauth/oauth2/auth?"));
+        assert!(url.contains("client_id=whoop_client"));
+        assert!(url.contains("offline"));
+        assert!(url.contains("state=state_12345"));
+    }
+
+    #[tokio::test]
+    async fn test_exchange_code_maps_scopes() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth/oauth2/token"))
+            .and(body_string_contains("grant_type=authorization_code"))
+            .and(body_string_contains("client_secret=whoop_secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "access_token": "access",
+                "token_type": "bearer",
+                "expires_in": 3600,
+                "refresh_token": "refresh",
+                "scope": "offline read:recovery read:sleep"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let tokens = client.exchange_code("auth_code").await.unwrap();
+
+        assert_eq!(tokens.refresh_token.as_deref(), Some("refresh"));
+        assert_eq!(tokens.expires_in, Some(3600));
+        assert_eq!(tokens.scopes.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_sleep_follows_pages_and_skips_naps() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("GET"))
+            .and(path("/developer/v2/activity/sleep"))
+            .and(query_param("nextToken", "page2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "records": [sleep_record("pending", false, "PENDING_SCORE")],
+                "next_token": null
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/developer/v2/activity/sleep"))
+            .and(header("authorization", "Bearer access"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "records": [sleep_record("night", false, "SCORED"), sleep_record("nap", true, "SCORED")],
+                "next_token": "page2"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let start = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+        let sleep = client.fetch_sleep("access", start, end).await.unwrap();
+
+        assert_eq!(sleep.len(), 1);
+        assert_eq!(sleep[0].total_sleep_hours, 7.5);
+        assert_eq!(sleep[0].deep_sleep_hours, Some(2.0));
+        assert_eq!(sleep[0].awake_hours, Some(0.5));
+        assert_eq!(sleep[0].sleep_efficiency, Some(93.75));
+        // Woke at 06:30 local time on the 2nd
+        assert_eq!(sleep[0].sleep_date, NaiveDate::from_ymd_opt(2024, 3, 2));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_recovery_skips_calibrating_scores() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("GET"))
+            .and(path("/developer/v2/recovery"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "records": [
+                    {
+                        "cycle_id": 93845,
+                        "sleep_id": "night",
+                        "user_id": 10129,
+                        "created_at": "2024-03-02T11:35:00.000Z",
+                        "updated_at": "2024-03-02T11:35:00.000Z",
+                        "score_state": "SCORED",
+                        "score": {
+                            "user_calibrating": false,
+                            "recovery_score": 72.0,
+                            "resting_heart_rate": 48.0,
+                            "hrv_rmssd_milli": 61.5
+                        }
+                    },
+                    {
+                        "cycle_id": 93846,
+                        "sleep_id": "earlier",
+                        "user_id": 10129,
+                        "created_at": "2024-03-01T11:35:00.000Z",
+                        "updated_at": "2024-03-01T11:35:00.000Z",
+                        "score_state": "SCORED",
+                        "score": {
+                            "user_calibrating": true,
+                            "recovery_score": 50.0,
+                            "resting_heart_rate": 55.0,
+                            "hrv_rmssd_milli": 40.0
+                        }
+                    }
+                ],
+                "next_token": null
+            })))
+            .mount(&server)
+            .await;
+
+        let day = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+
+        let hrv = client.fetch_hrv("access", day, day).await.unwrap();
+        assert_eq!(hrv.len(), 1);
+        assert_eq!(hrv[0].rmssd, Some(61.5));
+
+        let resting_hr = client.fetch_resting_hr("access", day, day).await.unwrap();
+        assert_eq!(resting_hr.len(), 1);
+        assert_eq!(resting_hr[0].resting_hr, 48.0);
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_fetch_is_an_error() {
+        let (server, client) = mock_client().await;
+        Mock::given(method("GET"))
+            .and(path("/developer/v2/recovery"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+
+        let day = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+        let error = client.fetch_hrv("expired", day, day).await.unwrap_err();
+        assert!(is_auth_error(&error));
+    }
+}
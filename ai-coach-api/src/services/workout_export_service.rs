@@ -0,0 +1,1073 @@
+/// Workout File Export
+///
+/// Turns structured workout recommendations and generated plan days into files athletes can load
+/// onto a smart trainer or head unit:
+/// - Zwift `.zwo` (power as a fraction of FTP)
+/// - TrainerRoad/ERG `.erg` (absolute watts) and `.mrc` (percent of FTP)
+/// - Garmin FIT workout files (watts and bpm when thresholds are known)
+///
+/// %FTP and %LTHR targets are resolved against the user's zone settings.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use quick_xml::events::BytesText;
+use quick_xml::Writer;
+use sqlx::PgPool;
+use std::io::Write;
+use uuid::Uuid;
+
+use crate::models::{
+    GeneratedPlan, Interval, PlanWeekStructure, SportType, StructuredWorkoutRecommendation,
+    TestType, TrainingZone, WorkoutDay, WorkoutFileFormat, WorkoutRecommendation, WorkoutType,
+};
+use crate::services::fit_decoder::{fit_crc, FIT_EPOCH_OFFSET};
+use crate::services::training_analysis_service::ZoneSettings;
+use crate::services::TrainingAnalysisService;
+
+const FIT_PROFILE_VERSION: u16 = 2132;
+
+// Global message numbers
+const MESG_FILE_ID: u16 = 0;
+const MESG_WORKOUT: u16 = 26;
+const MESG_WORKOUT_STEP: u16 = 27;
+
+// Base types
+const FIT_ENUM: u8 = 0x00;
+const FIT_STRING: u8 = 0x07;
+const FIT_UINT16: u8 = 0x84;
+const FIT_UINT32: u8 = 0x86;
+
+const EASY_POWER_PCT: f64 = 50.0;
+
+/// Effort of a step, used for the ZWO element and the FIT intensity field
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepIntensity {
+    Warmup,
+    Active,
+    Rest,
+    Cooldown,
+}
+
+/// Step target in percent of FTP or LTHR
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepTarget {
+    /// Hold power within a range of FTP
+    Power { low_pct: f64, high_pct: f64 },
+    /// Ramp power linearly from one percentage of FTP to another
+    Ramp { from_pct: f64, to_pct: f64 },
+    /// Hold heart rate within a range of LTHR
+    HeartRate { low_pct: f64, high_pct: f64 },
+}
+
+impl StepTarget {
+    fn steady(pct: f64) -> Self {
+        StepTarget::Power { low_pct: pct, high_pct: pct }
+    }
+
+    /// Start and end power in percent of FTP; heart rate targets are mapped to the power zone
+    /// with the same heart rate range
+    fn power_pct(&self) -> (f64, f64) {
+        match *self {
+            StepTarget::Power { low_pct, high_pct } => {
+                let mid = (low_pct + high_pct) / 2.0;
+                (mid, mid)
+            }
+            StepTarget::Ramp { from_pct, to_pct } => (from_pct, to_pct),
+            StepTarget::HeartRate { low_pct, high_pct } => {
+                let power = hr_pct_to_power_pct((low_pct + high_pct) / 2.0);
+                (power, power)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkoutStep {
+    pub name: Option<String>,
+    pub duration_seconds: u32,
+    pub intensity: StepIntensity,
+    pub target: StepTarget,
+}
+
+impl WorkoutStep {
+    fn new(name: Option<&str>, duration_seconds: u32, intensity: StepIntensity, target: StepTarget) -> Self {
+        Self {
+            name: name.map(str::to_string),
+            duration_seconds,
+            intensity,
+            target,
+        }
+    }
+}
+
+/// Steps repeated `repetitions` times
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkoutBlock {
+    pub repetitions: u32,
+    pub steps: Vec<WorkoutStep>,
+}
+
+impl WorkoutBlock {
+    fn single(step: WorkoutStep) -> Self {
+        Self { repetitions: 1, steps: vec![step] }
+    }
+}
+
+/// Format-independent structured workout
+#[derive(Debug, Clone)]
+pub struct ExportableWorkout {
+    pub name: String,
+    pub description: String,
+    pub sport: SportType,
+    pub blocks: Vec<WorkoutBlock>,
+}
+
+impl ExportableWorkout {
+    pub fn total_duration_seconds(&self) -> u32 {
+        self.blocks
+            .iter()
+            .map(|block| block.repetitions * block.steps.iter().map(|s| s.duration_seconds).sum::<u32>())
+            .sum()
+    }
+
+    /// Steps in execution order with repeats unrolled
+    fn flattened_steps(&self) -> impl Iterator<Item = &WorkoutStep> {
+        self.blocks
+            .iter()
+            .flat_map(|block| (0..block.repetitions).flat_map(move |_| block.steps.iter()))
+    }
+}
+
+/// Rendered workout file ready to download
+#[derive(Debug, Clone)]
+pub struct WorkoutFile {
+    pub file_name: String,
+    pub content_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct WorkoutExportService {
+    training_analysis_service: TrainingAnalysisService,
+}
+
+impl WorkoutExportService {
+    pub fn new(db: PgPool) -> Result<Self> {
+        Ok(Self {
+            training_analysis_service: TrainingAnalysisService::new(db, None)?,
+        })
+    }
+
+    /// Export a workout recommendation as a single file
+    pub async fn export_recommendation(
+        &self,
+        user_id: Uuid,
+        recommendation: &StructuredWorkoutRecommendation,
+        format: WorkoutFileFormat,
+    ) -> Result<WorkoutFile> {
+        let settings = self.zone_settings(user_id).await?;
+        let workout = workout_from_recommendation(recommendation);
+        let data = render_workout(&workout, format, &settings, Utc::now())?;
+
+        Ok(WorkoutFile {
+            file_name: format!(
+                "ai-coach-{}-{}.{}",
+                file_label(&workout.name),
+                recommendation.created_at.format("%Y-%m-%d"),
+                format.as_str()
+            ),
+            content_type: format.content_type(),
+            data,
+        })
+    }
+
+    /// Export a plan week as a zip with one file per trainer workout, or a single day's file
+    /// when `day_of_week` is given. Returns `None` if the week or day has no such workout.
+    pub async fn export_plan_week(
+        &self,
+        user_id: Uuid,
+        plan: &GeneratedPlan,
+        week_number: i32,
+        day_of_week: Option<i32>,
+        format: WorkoutFileFormat,
+    ) -> Result<Option<WorkoutFile>> {
+        let weeks: Vec<PlanWeekStructure> = serde_json::from_value(plan.plan_structure.clone())?;
+        let Some(week) = weeks.iter().find(|week| week.week_number == week_number) else {
+            return Ok(None);
+        };
+
+        let workouts: Vec<(&WorkoutDay, ExportableWorkout)> = week
+            .workout_days
+            .iter()
+            .filter(|day| day_of_week.is_none_or(|selected| day.day_of_week == selected))
+            .filter_map(|day| workout_from_plan_day(&plan.plan_name, week, day).map(|workout| (day, workout)))
+            .collect();
+
+        if workouts.is_empty() {
+            return Ok(None);
+        }
+
+        let settings = self.zone_settings(user_id).await?;
+        let created_at = Utc::now();
+
+        let mut files = Vec::with_capacity(workouts.len());
+        for (index, (day, workout)) in workouts.iter().enumerate() {
+            // Number the sessions of days with more than one workout
+            let same_day = |(other, _): &&(&WorkoutDay, ExportableWorkout)| other.day_of_week == day.day_of_week;
+            let session = (workouts.iter().filter(same_day).count() > 1)
+                .then(|| workouts[..index].iter().filter(same_day).count() + 1);
+
+            files.push((
+                plan_day_file_name(week.week_number, day, session, format),
+                render_workout(workout, format, &settings, created_at)?,
+            ));
+        }
+
+        // A single day's only workout is sent as is; anything more is zipped
+        if day_of_week.is_some() && files.len() == 1 {
+            let (file_name, data) = files.remove(0);
+            return Ok(Some(WorkoutFile {
+                file_name,
+                content_type: format.content_type(),
+                data,
+            }));
+        }
+
+        let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        for (file_name, data) in &files {
+            archive.start_file(file_name.as_str(), options)?;
+            archive.write_all(data)?;
+        }
+
+        let archive_label = match day_of_week {
+            Some(day) => format!("week-{:02}-{}", week.week_number, weekday_label(day).to_lowercase()),
+            None => format!("week-{:02}", week.week_number),
+        };
+
+        Ok(Some(WorkoutFile {
+            file_name: format!("{}-{}-{}.zip", file_label(&plan.plan_name), archive_label, format.as_str()),
+            content_type: "application/zip",
+            data: archive.finish()?.into_inner(),
+        }))
+    }
+
+    async fn zone_settings(&self, user_id: Uuid) -> Result<ZoneSettings> {
+        Ok(self
+            .training_analysis_service
+            .get_user_zone_settings(user_id)
+            .await?
+            .unwrap_or(ZoneSettings {
+                power_zones: None,
+                heart_rate_zones: None,
+                ftp: None,
+                lthr: None,
+                threshold_pace: None,
+            }))
+    }
+}
+
+/// Build the step structure of a workout recommendation
+pub fn workout_from_recommendation(recommendation: &StructuredWorkoutRecommendation) -> ExportableWorkout {
+    let zones = TrainingZone::cycling_zones();
+    let zone = |number: u8| zones.iter().find(|zone| zone.zone == number);
+
+    let (name, blocks) = match &recommendation.workout {
+        WorkoutRecommendation::Endurance { duration_minutes, target_zones } => {
+            let low = target_zones.iter().filter_map(|&z| zone(z)).map(|z| z.power_pct_min).fold(f32::MAX, f32::min);
+            let high = target_zones.iter().filter_map(|&z| zone(z)).map(|z| z.power_pct_max).fold(f32::MIN, f32::max);
+            let target = if low <= high {
+                StepTarget::Power { low_pct: low as f64, high_pct: high as f64 }
+            } else {
+                StepTarget::Power { low_pct: 56.0, high_pct: 75.0 }
+            };
+            ("Endurance".to_string(), steady_session("Endurance", *duration_minutes, target))
+        }
+        WorkoutRecommendation::Tempo { duration, target_power_pct } => (
+            "Tempo".to_string(),
+            steady_session("Tempo", *duration, StepTarget::steady(*target_power_pct as f64)),
+        ),
+        WorkoutRecommendation::Recovery { duration, max_intensity } => {
+            let ceiling = zone(*max_intensity).map(|z| z.power_pct_max as f64).unwrap_or(55.0).min(55.0);
+            let target = StepTarget::Power { low_pct: 45.0, high_pct: ceiling.max(45.0) };
+            ("Recovery".to_string(), vec![WorkoutBlock::single(WorkoutStep::new(
+                Some("Recovery spin"),
+                duration * 60,
+                StepIntensity::Active,
+                target,
+            ))])
+        }
+        WorkoutRecommendation::Intervals { warmup, intervals, cooldown } => {
+            let mut blocks = Vec::new();
+            if *warmup > 0 {
+                blocks.push(warmup_block(*warmup * 60));
+            }
+            blocks.extend(intervals.iter().map(|interval| interval_block(interval, &zones)));
+            if *cooldown > 0 {
+                blocks.push(cooldown_block(*cooldown * 60));
+            }
+            ("Intervals".to_string(), blocks)
+        }
+        WorkoutRecommendation::Test { test_type, .. } => {
+            (format!("{} test", test_label(test_type)), test_blocks(test_type))
+        }
+    };
+
+    let description = match &recommendation.workout {
+        WorkoutRecommendation::Test { instructions, .. } => instructions.clone(),
+        _ => recommendation.explanation.primary_purpose.clone(),
+    };
+
+    ExportableWorkout {
+        name,
+        description,
+        sport: recommendation.sport_type.clone(),
+        blocks,
+    }
+}
+
+/// Build the step structure of a plan day; sessions without power or heart rate targets
+/// (strength, cross-training) can't be ridden on a trainer and yield `None`
+pub fn workout_from_plan_day(plan_name: &str, week: &PlanWeekStructure, day: &WorkoutDay) -> Option<ExportableWorkout> {
+    if matches!(day.workout_type, WorkoutType::Test) {
+        return Some(ExportableWorkout {
+            name: format!("{} W{} {} - FTP test", plan_name, week.week_number, weekday_label(day.day_of_week)),
+            description: day.workout_description.clone(),
+            sport: SportType::Cycling,
+            blocks: test_blocks(&TestType::FTP),
+        });
+    }
+
+    let target = match (&day.power_targets, &day.heart_rate_targets) {
+        (Some(power), _) => StepTarget::Power {
+            low_pct: power.ftp_percentage_low,
+            high_pct: power.ftp_percentage_high,
+        },
+        (None, Some(hr)) => StepTarget::HeartRate {
+            low_pct: hr.hr_percentage_low,
+            high_pct: hr.hr_percentage_high,
+        },
+        (None, None) => return None,
+    };
+
+    let total_minutes = day.duration_minutes.max(0) as u32;
+    let type_label = workout_type_label(&day.workout_type);
+
+    // Work/recovery pattern for the main set; steady sessions have none
+    let pattern = match day.workout_type {
+        WorkoutType::SweetSpot => Some((900, 300)),
+        WorkoutType::Threshold => Some((600, 300)),
+        WorkoutType::Vo2Max => Some((240, 240)),
+        WorkoutType::Neuromuscular => Some((30, 270)),
+        _ => None,
+    };
+
+    let blocks = match pattern {
+        Some((work, rest)) if total_minutes >= 30 => {
+            let (warmup, cooldown) = (600, 300);
+            let main = total_minutes * 60 - warmup - cooldown;
+            let repetitions = (main / (work + rest)).max(1);
+            let recovery = match target {
+                StepTarget::HeartRate { .. } => StepTarget::HeartRate { low_pct: 60.0, high_pct: 68.0 },
+                _ => StepTarget::steady(EASY_POWER_PCT),
+            };
+
+            vec![
+                warmup_block(warmup),
+                WorkoutBlock {
+                    repetitions,
+                    steps: vec![
+                        WorkoutStep::new(Some(type_label), work, StepIntensity::Active, target),
+                        WorkoutStep::new(Some("Recover"), rest, StepIntensity::Rest, recovery),
+                    ],
+                },
+                cooldown_block(cooldown + main.saturating_sub(repetitions * (work + rest))),
+            ]
+        }
+        _ => steady_session(type_label, total_minutes, target),
+    };
+
+    Some(ExportableWorkout {
+        name: format!("{} W{} {} - {}", plan_name, week.week_number, weekday_label(day.day_of_week), type_label),
+        description: match &day.notes {
+            Some(notes) => format!("{} {}", day.workout_description, notes),
+            None => day.workout_description.clone(),
+        },
+        sport: SportType::Cycling,
+        blocks,
+    })
+}
+
+/// Render a workout in the requested format. ERG needs an FTP; FIT heart rate steps need an LTHR.
+pub fn render_workout(
+    workout: &ExportableWorkout,
+    format: WorkoutFileFormat,
+    settings: &ZoneSettings,
+    created_at: DateTime<Utc>,
+) -> Result<Vec<u8>> {
+    match format {
+        WorkoutFileFormat::Zwo => render_zwo(workout),
+        WorkoutFileFormat::Erg => {
+            let ftp = settings
+                .ftp
+                .filter(|ftp| *ftp > 0.0)
+                .ok_or_else(|| anyhow!("ERG files use absolute watts; set your FTP in zone settings first"))?;
+            Ok(render_course(workout, Some(ftp)).into_bytes())
+        }
+        WorkoutFileFormat::Mrc => Ok(render_course(workout, None).into_bytes()),
+        WorkoutFileFormat::Fit => render_fit(workout, settings, created_at),
+    }
+}
+
+/// Zwift workout XML. Power is written as a fraction of FTP, so Zwift resolves it itself.
+pub fn render_zwo(workout: &ExportableWorkout) -> Result<Vec<u8>> {
+    let sport = match workout.sport {
+        SportType::Running => "run",
+        _ => "bike",
+    };
+
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 4);
+    writer
+        .create_element("workout_file")
+        .write_inner_content(|writer| -> Result<()> {
+            writer.create_element("author").write_text_content(BytesText::new("AI Coach"))?;
+            writer.create_element("name").write_text_content(BytesText::new(&workout.name))?;
+            writer.create_element("description").write_text_content(BytesText::new(&workout.description))?;
+            writer.create_element("sportType").write_text_content(BytesText::new(sport))?;
+            writer.create_element("workout").write_inner_content(|writer| -> Result<()> {
+                for block in &workout.blocks {
+                    write_zwo_block(writer, block)?;
+                }
+                Ok(())
+            })?;
+            Ok(())
+        })?;
+
+    Ok(writer.into_inner())
+}
+
+fn write_zwo_block(writer: &mut Writer<Vec<u8>>, block: &WorkoutBlock) -> Result<()> {
+    // Zwift has a dedicated element for on/off repeats
+    if let [on, off] = block.steps.as_slice() {
+        if block.repetitions > 1 {
+            writer
+                .create_element("IntervalsT")
+                .with_attribute(("Repeat", block.repetitions.to_string().as_str()))
+                .with_attribute(("OnDuration", on.duration_seconds.to_string().as_str()))
+                .with_attribute(("OffDuration", off.duration_seconds.to_string().as_str()))
+                .with_attribute(("OnPower", zwo_power(on.target.power_pct().0).as_str()))
+                .with_attribute(("OffPower", zwo_power(off.target.power_pct().0).as_str()))
+                .write_empty()?;
+            return Ok(());
+        }
+    }
+
+    for _ in 0..block.repetitions {
+        for step in &block.steps {
+            let duration = step.duration_seconds.to_string();
+            let (start, end) = step.target.power_pct();
+            let element = match (step.intensity, step.target) {
+                (StepIntensity::Warmup, StepTarget::Ramp { .. }) => "Warmup",
+                (StepIntensity::Cooldown, StepTarget::Ramp { .. }) => "Cooldown",
+                (_, StepTarget::Ramp { .. }) => "Ramp",
+                _ => "SteadyState",
+            };
+
+            let tag = writer.create_element(element).with_attribute(("Duration", duration.as_str()));
+            if element == "SteadyState" {
+                tag.with_attribute(("Power", zwo_power(start).as_str())).write_empty()?;
+            } else {
+                tag.with_attribute(("PowerLow", zwo_power(start).as_str()))
+                    .with_attribute(("PowerHigh", zwo_power(end).as_str()))
+                    .write_empty()?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn zwo_power(pct: f64) -> String {
+    format!("{:.2}", pct / 100.0)
+}
+
+/// ERG (watts, when `ftp` is given) or MRC (percent of FTP) course file
+pub fn render_course(workout: &ExportableWorkout, ftp: Option<f64>) -> String {
+    let mut lines = vec![
+        "[COURSE HEADER]".to_string(),
+        "VERSION = 2".to_string(),
+        "UNITS = ENGLISH".to_string(),
+        format!("DESCRIPTION = {}", single_line(&workout.description)),
+        format!("FILE NAME = {}", single_line(&workout.name)),
+    ];
+    match ftp {
+        Some(ftp) => {
+            lines.push(format!("FTP = {:.0}", ftp));
+            lines.push("MINUTES WATTS".to_string());
+        }
+        None => lines.push("MINUTES PERCENT".to_string()),
+    }
+    lines.push("[END COURSE HEADER]".to_string());
+    lines.push("[COURSE DATA]".to_string());
+
+    let value = |pct: f64| match ftp {
+        Some(ftp) => format!("{:.0}", pct / 100.0 * ftp),
+        None => format!("{:.0}", pct),
+    };
+
+    // Each step is a line segment from its start to its end power
+    let mut elapsed = 0u32;
+    for step in workout.flattened_steps() {
+        let (start, end) = step.target.power_pct();
+        let end_time = elapsed + step.duration_seconds;
+        lines.push(format!("{:.2}\t{}", elapsed as f64 / 60.0, value(start)));
+        lines.push(format!("{:.2}\t{}", end_time as f64 / 60.0, value(end)));
+        elapsed = end_time;
+    }
+
+    lines.push("[END COURSE DATA]".to_string());
+    lines.join("\r\n") + "\r\n"
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+enum FitValue<'a> {
+    Enum(u8),
+    U16(u16),
+    U32(u32),
+    Str(&'a str),
+}
+
+/// Minimal FIT encoder: every message carries its own definition
+struct FitEncoder {
+    records: Vec<u8>,
+}
+
+impl FitEncoder {
+    fn new() -> Self {
+        Self { records: Vec::new() }
+    }
+
+    fn message(&mut self, global_number: u16, fields: &[(u8, FitValue)]) {
+        let encoded: Vec<(u8, u8, Vec<u8>)> = fields
+            .iter()
+            .map(|(number, value)| match value {
+                FitValue::Enum(v) => (*number, FIT_ENUM, vec![*v]),
+                FitValue::U16(v) => (*number, FIT_UINT16, v.to_le_bytes().to_vec()),
+                FitValue::U32(v) => (*number, FIT_UINT32, v.to_le_bytes().to_vec()),
+                FitValue::Str(s) => {
+                    let mut bytes = truncate_utf8(s, 63).as_bytes().to_vec();
+                    bytes.push(0);
+                    (*number, FIT_STRING, bytes)
+                }
+            })
+            .collect();
+
+        // Definition message for local type 0, little endian
+        self.records.extend_from_slice(&[0x40, 0, 0]);
+        self.records.extend_from_slice(&global_number.to_le_bytes());
+        self.records.push(encoded.len() as u8);
+        for (number, base_type, bytes) in &encoded {
+            self.records.extend_from_slice(&[*number, bytes.len() as u8, *base_type]);
+        }
+
+        self.records.push(0x00);
+        for (_, _, bytes) in &encoded {
+            self.records.extend_from_slice(bytes);
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.records.len() + 16);
+        data.push(14);
+        data.push(0x20);
+        data.extend_from_slice(&FIT_PROFILE_VERSION.to_le_bytes());
+        data.extend_from_slice(&(self.records.len() as u32).to_le_bytes());
+        data.extend_from_slice(b".FIT");
+        let header_crc = fit_crc(&data);
+        data.extend_from_slice(&header_crc.to_le_bytes());
+
+        data.extend_from_slice(&self.records);
+        let crc = fit_crc(&data);
+        data.extend_from_slice(&crc.to_le_bytes());
+        data
+    }
+}
+
+fn truncate_utf8(text: &str, max_bytes: usize) -> &str {
+    let mut end = text.len().min(max_bytes);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Garmin FIT workout file. Power is written in watts when the FTP is known and as %FTP
+/// otherwise; heart rate steps are written in bpm and need the LTHR.
+pub fn render_fit(workout: &ExportableWorkout, settings: &ZoneSettings, created_at: DateTime<Utc>) -> Result<Vec<u8>> {
+    let ftp = settings.ftp.filter(|ftp| *ftp > 0.0);
+    let lthr = settings.lthr.filter(|lthr| *lthr > 0.0);
+
+    let num_steps: usize = workout
+        .blocks
+        .iter()
+        .map(|block| block.steps.len() + usize::from(block.repetitions > 1))
+        .sum();
+
+    let sport = match workout.sport {
+        SportType::Running => 1,
+        SportType::Cycling => 2,
+        SportType::Swimming => 5,
+        SportType::Triathlon => 0,
+    };
+
+    let mut encoder = FitEncoder::new();
+    encoder.message(MESG_FILE_ID, &[
+        (0, FitValue::Enum(5)), // workout file
+        (1, FitValue::U16(255)), // development manufacturer
+        (2, FitValue::U16(0)),
+        (4, FitValue::U32((created_at.timestamp() - FIT_EPOCH_OFFSET).max(0) as u32)),
+    ]);
+    encoder.message(MESG_WORKOUT, &[
+        (4, FitValue::Enum(sport)),
+        (6, FitValue::U16(num_steps as u16)),
+        (8, FitValue::Str(&workout.name)),
+    ]);
+
+    let mut index: u16 = 0;
+    for block in &workout.blocks {
+        let first_index = index;
+
+        for step in &block.steps {
+            let (target_type, low, high) = match step.target {
+                StepTarget::HeartRate { low_pct, high_pct } => {
+                    let lthr = lthr.ok_or_else(|| {
+                        anyhow!("This workout has heart rate targets; set your LTHR in zone settings first")
+                    })?;
+                    (1, bpm_value(low_pct, lthr), bpm_value(high_pct, lthr))
+                }
+                target => {
+                    let (start, end) = target.power_pct();
+                    let (low, high) = (start.min(end), start.max(end));
+                    (4, power_value(low, ftp), power_value(high, ftp))
+                }
+            };
+
+            let intensity = match step.intensity {
+                StepIntensity::Active => 0,
+                StepIntensity::Rest => 1,
+                StepIntensity::Warmup => 2,
+                StepIntensity::Cooldown => 3,
+            };
+
+            let name = step.name.as_deref().unwrap_or("");
+            encoder.message(MESG_WORKOUT_STEP, &[
+                (254, FitValue::U16(index)),
+                (0, FitValue::Str(name)),
+                (1, FitValue::Enum(0)), // duration: time
+                (2, FitValue::U32(step.duration_seconds * 1000)),
+                (3, FitValue::Enum(target_type)),
+                (4, FitValue::U32(0)), // custom range below
+                (5, FitValue::U32(low)),
+                (6, FitValue::U32(high)),
+                (7, FitValue::Enum(intensity)),
+            ]);
+            index += 1;
+        }
+
+        if block.repetitions > 1 {
+            encoder.message(MESG_WORKOUT_STEP, &[
+                (254, FitValue::U16(index)),
+                (1, FitValue::Enum(6)), // duration: repeat until steps complete
+                (2, FitValue::U32(first_index as u32)),
+                (4, FitValue::U32(block.repetitions)),
+            ]);
+            index += 1;
+        }
+    }
+
+    Ok(encoder.finish())
+}
+
+/// FIT custom power value: watts offset by 1000, or percent of FTP below 1000
+fn power_value(pct: f64, ftp: Option<f64>) -> u32 {
+    match ftp {
+        Some(ftp) => (pct / 100.0 * ftp).round() as u32 + 1000,
+        None => pct.round() as u32,
+    }
+}
+
+/// FIT custom heart rate value: bpm offset by 100
+fn bpm_value(pct: f64, lthr: f64) -> u32 {
+    (pct / 100.0 * lthr).round() as u32 + 100
+}
+
+/// Map a heart rate target (%LTHR) to the power (%FTP) of the matching cycling zone,
+/// interpolating within the zone, for formats that only support power
+fn hr_pct_to_power_pct(hr_pct: f64) -> f64 {
+    let zones = TrainingZone::cycling_zones();
+
+    zones
+        .iter()
+        .find_map(|zone| {
+            let (hr_min, hr_max) = (zone.heart_rate_pct_min? as f64, zone.heart_rate_pct_max? as f64);
+            if hr_pct > hr_max || hr_max <= hr_min {
+                return None;
+            }
+            let position = ((hr_pct - hr_min) / (hr_max - hr_min)).clamp(0.0, 1.0);
+            Some(zone.power_pct_min as f64 + position * (zone.power_pct_max - zone.power_pct_min) as f64)
+        })
+        .unwrap_or(120.0)
+}
+
+fn warmup_block(duration_seconds: u32) -> WorkoutBlock {
+    WorkoutBlock::single(WorkoutStep::new(
+        Some("Warm up"),
+        duration_seconds,
+        StepIntensity::Warmup,
+        StepTarget::Ramp { from_pct: 50.0, to_pct: 75.0 },
+    ))
+}
+
+fn cooldown_block(duration_seconds: u32) -> WorkoutBlock {
+    WorkoutBlock::single(WorkoutStep::new(
+        Some("Cool down"),
+        duration_seconds,
+        StepIntensity::Cooldown,
+        StepTarget::Ramp { from_pct: 65.0, to_pct: 45.0 },
+    ))
+}
+
+/// Warm-up, a steady main set and cool-down; short sessions are a single steady step
+fn steady_session(name: &str, total_minutes: u32, target: StepTarget) -> Vec<WorkoutBlock> {
+    if total_minutes < 30 {
+        return vec![WorkoutBlock::single(WorkoutStep::new(Some(name), total_minutes * 60, StepIntensity::Active, target))];
+    }
+
+    let (warmup, cooldown) = (600, 300);
+    vec![
+        warmup_block(warmup),
+        WorkoutBlock::single(WorkoutStep::new(
+            Some(name),
+            total_minutes * 60 - warmup - cooldown,
+            StepIntensity::Active,
+            target,
+        )),
+        cooldown_block(cooldown),
+    ]
+}
+
+fn interval_block(interval: &Interval, zones: &[TrainingZone]) -> WorkoutBlock {
+    let target = match (interval.target_power_pct, interval.target_zone, interval.target_heart_rate_pct) {
+        (Some(power), _, _) => StepTarget::steady(power as f64),
+        (None, Some(number), _) => zones
+            .iter()
+            .find(|zone| zone.zone == number)
+            .map(|zone| StepTarget::Power {
+                low_pct: zone.power_pct_min as f64,
+                high_pct: zone.power_pct_max as f64,
+            })
+            .unwrap_or(StepTarget::steady(75.0)),
+        (None, None, Some(hr)) => StepTarget::HeartRate { low_pct: hr as f64, high_pct: hr as f64 },
+        (None, None, None) => StepTarget::steady(75.0),
+    };
+
+    let mut steps = vec![WorkoutStep::new(
+        interval.description.as_deref().or(Some("Interval")),
+        interval.duration_seconds,
+        StepIntensity::Active,
+        target,
+    )];
+    if let Some(rest) = interval.rest_duration_seconds.filter(|rest| *rest > 0) {
+        steps.push(WorkoutStep::new(Some("Recover"), rest, StepIntensity::Rest, StepTarget::steady(EASY_POWER_PCT)));
+    }
+
+    WorkoutBlock {
+        repetitions: interval.repetitions.max(1),
+        steps,
+    }
+}
+
+/// Warm-up, test effort and cool-down for each test protocol
+fn test_blocks(test_type: &TestType) -> Vec<WorkoutBlock> {
+    let effort = |name: &str, minutes: u32, low_pct: f64, high_pct: f64| {
+        WorkoutBlock::single(WorkoutStep::new(
+            Some(name),
+            minutes * 60,
+            StepIntensity::Active,
+            StepTarget::Power { low_pct, high_pct },
+        ))
+    };
+    let easy = |minutes: u32| {
+        WorkoutBlock::single(WorkoutStep::new(Some("Easy"), minutes * 60, StepIntensity::Rest, StepTarget::steady(EASY_POWER_PCT)))
+    };
+
+    match test_type {
+        TestType::Ramp => {
+            // One-minute steps of 6% FTP until failure
+            let mut blocks = vec![warmup_block(300)];
+            blocks.extend((0..16).map(|step| {
+                let pct = 60.0 + 6.0 * step as f64;
+                WorkoutBlock::single(WorkoutStep::new(Some("Ramp step"), 60, StepIntensity::Active, StepTarget::steady(pct)))
+            }));
+            blocks.push(cooldown_block(300));
+            blocks
+        }
+        TestType::FTP => vec![
+            warmup_block(900),
+            easy(5),
+            effort("20 minute test", 20, 100.0, 105.0),
+            cooldown_block(600),
+        ],
+        TestType::VO2Max => vec![
+            warmup_block(900),
+            easy(5),
+            effort("5 minute test", 5, 115.0, 120.0),
+            cooldown_block(600),
+        ],
+        TestType::LactateThreshold => vec![
+            warmup_block(900),
+            effort("30 minute test", 30, 95.0, 105.0),
+            cooldown_block(600),
+        ],
+        TestType::TimeTrial { .. } => vec![
+            warmup_block(900),
+            easy(5),
+            effort("Time trial", 20, 100.0, 105.0),
+            cooldown_block(600),
+        ],
+    }
+}
+
+fn test_label(test_type: &TestType) -> &'static str {
+    match test_type {
+        TestType::FTP => "FTP",
+        TestType::VO2Max => "VO2max",
+        TestType::LactateThreshold => "Lactate threshold",
+        TestType::Ramp => "Ramp",
+        TestType::TimeTrial { .. } => "Time trial",
+    }
+}
+
+/// Display name of a plan workout type, e.g. `Sweet spot`
+pub fn workout_type_label(workout_type: &WorkoutType) -> &'static str {
+    match workout_type {
+        WorkoutType::Recovery => "Recovery",
+        WorkoutType::Endurance => "Endurance",
+        WorkoutType::Tempo => "Tempo",
+        WorkoutType::SweetSpot => "Sweet spot",
+        WorkoutType::Threshold => "Threshold",
+        WorkoutType::Vo2Max => "VO2max",
+        WorkoutType::Neuromuscular => "Sprints",
+        WorkoutType::Strength => "Strength",
+        WorkoutType::CrossTrain => "Cross-training",
+        WorkoutType::Test => "Test",
+        WorkoutType::Race => "Race",
+    }
+}
+
+fn weekday_label(day_of_week: i32) -> &'static str {
+    match day_of_week {
+        1 => "Mon",
+        2 => "Tue",
+        3 => "Wed",
+        4 => "Thu",
+        5 => "Fri",
+        6 => "Sat",
+        _ => "Sun",
+    }
+}
+
+/// Lowercase, dash-separated label for file names
+fn file_label(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// File name inside a week archive, e.g. `week-03-wed-threshold.zwo`
+/// File name of a plan workout; `session` numbers the workouts of a day with more than one
+fn plan_day_file_name(week_number: i32, day: &WorkoutDay, session: Option<usize>, format: WorkoutFileFormat) -> String {
+    format!(
+        "week-{:02}-{}{}-{}.{}",
+        week_number,
+        weekday_label(day.day_of_week).to_lowercase(),
+        session.map(|session| format!("-{}", session)).unwrap_or_default(),
+        file_label(workout_type_label(&day.workout_type)),
+        format.as_str()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{IntensityZone, HeartRateTargets, PowerTargets};
+    use crate::services::FitDecoder;
+
+    fn settings(ftp: Option<f64>, lthr: Option<f64>) -> ZoneSettings {
+        ZoneSettings {
+            power_zones: None,
+            heart_rate_zones: None,
+            ftp,
+            lthr,
+            threshold_pace: None,
+        }
+    }
+
+    fn interval_workout() -> ExportableWorkout {
+        ExportableWorkout {
+            name: "Threshold & VO2".to_string(),
+            description: "4x5 at 115%".to_string(),
+            sport: SportType::Cycling,
+            blocks: vec![
+                warmup_block(600),
+                WorkoutBlock {
+                    repetitions: 4,
+                    steps: vec![
+                        WorkoutStep::new(Some("VO2max"), 300, StepIntensity::Active, StepTarget::steady(115.0)),
+                        WorkoutStep::new(Some("Recover"), 300, StepIntensity::Rest, StepTarget::steady(50.0)),
+                    ],
+                },
+                cooldown_block(300),
+            ],
+        }
+    }
+
+    fn plan_day(workout_type: WorkoutType, power: Option<(f64, f64)>, hr: Option<(f64, f64)>) -> WorkoutDay {
+        WorkoutDay {
+            day_of_week: 3,
+            workout_type,
+            duration_minutes: 60,
+            intensity_zone: IntensityZone::Zone4,
+            workout_description: "Lactate threshold intervals".to_string(),
+            power_targets: power.map(|(low, high)| PowerTargets {
+                ftp_percentage_low: low,
+                ftp_percentage_high: high,
+                average_watts: None,
+                normalized_power: None,
+            }),
+            heart_rate_targets: hr.map(|(low, high)| HeartRateTargets {
+                hr_percentage_low: low,
+                hr_percentage_high: high,
+                average_hr: None,
+            }),
+            pace_targets: None,
+            equipment_needed: vec![],
+            notes: None,
+        }
+    }
+
+    fn week(days: Vec<WorkoutDay>) -> PlanWeekStructure {
+        PlanWeekStructure {
+            week_number: 3,
+            phase_name: "Build".to_string(),
+            weekly_volume: 6.0,
+            weekly_intensity: 0.7,
+            workout_days: days,
+            rest_days: vec![],
+            week_goals: vec![],
+            key_sessions: vec![],
+        }
+    }
+
+    #[test]
+    fn test_zwo_uses_intervals_and_ramps() {
+        let xml = String::from_utf8(render_zwo(&interval_workout()).unwrap()).unwrap();
+
+        assert!(xml.contains("<name>Threshold &amp; VO2</name>"));
+        assert!(xml.contains(r#"<Warmup Duration="600" PowerLow="0.50" PowerHigh="0.75"/>"#));
+        assert!(xml.contains(r#"<IntervalsT Repeat="4" OnDuration="300" OffDuration="300" OnPower="1.15" OffPower="0.50"/>"#));
+        assert!(xml.contains(r#"<Cooldown Duration="300" PowerLow="0.65" PowerHigh="0.45"/>"#));
+    }
+
+    #[test]
+    fn test_erg_resolves_watts_and_requires_ftp() {
+        let workout = interval_workout();
+        assert!(render_workout(&workout, WorkoutFileFormat::Erg, &settings(None, None), Utc::now()).is_err());
+
+        let erg = String::from_utf8(
+            render_workout(&workout, WorkoutFileFormat::Erg, &settings(Some(200.0), None), Utc::now()).unwrap(),
+        )
+        .unwrap();
+        assert!(erg.contains("FTP = 200"));
+        assert!(erg.contains("MINUTES WATTS"));
+        assert!(erg.contains("0.00\t100\r\n10.00\t150"));
+        assert!(erg.contains("10.00\t230\r\n15.00\t230"));
+        // Warm-up, eight interval steps and cool-down make ten segments ending at 55 minutes
+        assert_eq!(erg.lines().filter(|line| line.contains('\t')).count(), 20);
+        assert!(erg.contains("55.00\t90"));
+
+        let mrc = String::from_utf8(
+            render_workout(&workout, WorkoutFileFormat::Mrc, &settings(None, None), Utc::now()).unwrap(),
+        )
+        .unwrap();
+        assert!(mrc.contains("MINUTES PERCENT"));
+        assert!(mrc.contains("10.00\t115"));
+    }
+
+    #[test]
+    fn test_fit_workout_is_valid_fit() {
+        let data = render_fit(&interval_workout(), &settings(Some(250.0), None), Utc::now()).unwrap();
+
+        let header = FitDecoder::validate_header(&data).unwrap();
+        assert_eq!(header.data_size + 16, data.len());
+        assert!(FitDecoder::decode(&data).is_ok());
+
+        // 115% of 250 W, offset by 1000
+        let watts = (1000u32 + 288).to_le_bytes();
+        assert!(data.windows(4).any(|window| window == watts));
+    }
+
+    #[test]
+    fn test_fit_heart_rate_steps_need_lthr() {
+        let workout = ExportableWorkout {
+            blocks: vec![WorkoutBlock::single(WorkoutStep::new(
+                None,
+                1200,
+                StepIntensity::Active,
+                StepTarget::HeartRate { low_pct: 69.0, high_pct: 83.0 },
+            ))],
+            ..interval_workout()
+        };
+
+        assert!(render_fit(&workout, &settings(Some(250.0), None), Utc::now()).is_err());
+        assert!(render_fit(&workout, &settings(None, Some(170.0)), Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn test_plan_day_structure() {
+        let threshold = plan_day(WorkoutType::Threshold, Some((95.0, 105.0)), Some((95.0, 105.0)));
+        let workout = workout_from_plan_day("Spring Build", &week(vec![]), &threshold).unwrap();
+
+        assert_eq!(workout.name, "Spring Build W3 Wed - Threshold");
+        assert_eq!(workout.total_duration_seconds(), 3600);
+        assert_eq!(workout.blocks[1].repetitions, 3);
+        assert_eq!(workout.blocks[1].steps[0].target, StepTarget::Power { low_pct: 95.0, high_pct: 105.0 });
+
+        let strength = plan_day(WorkoutType::Strength, None, None);
+        assert!(workout_from_plan_day("Spring Build", &week(vec![]), &strength).is_none());
+
+        assert_eq!(plan_day_file_name(3, &threshold, None, WorkoutFileFormat::Zwo), "week-03-wed-threshold.zwo");
+        assert_eq!(plan_day_file_name(3, &threshold, Some(2), WorkoutFileFormat::Zwo), "week-03-wed-2-threshold.zwo");
+    }
+
+    #[test]
+    fn test_heart_rate_targets_map_to_power() {
+        // Endurance heart rate maps into the endurance power zone
+        let power = hr_pct_to_power_pct(76.0);
+        assert!((56.0..=75.0).contains(&power));
+        assert_eq!(hr_pct_to_power_pct(69.0), 56.0);
+    }
+
+    #[test]
+    fn test_format_parsing() {
+        assert_eq!("ZWO".parse::<WorkoutFileFormat>().unwrap(), WorkoutFileFormat::Zwo);
+        assert_eq!("fit".parse::<WorkoutFileFormat>().unwrap().content_type(), "application/vnd.ant.fit");
+        assert!("tcx".parse::<WorkoutFileFormat>().is_err());
+    }
+}
@@ -8,9 +8,11 @@ use crate::config::Config;
 
 mod error;
 mod retry;
+mod sessions;
 
 pub use error::ApiError;
 pub use retry::RetryConfig;
+pub use sessions::SessionUpload;
 
 /// Login request payload
 #[derive(Debug, Serialize)]
@@ -0,0 +1,192 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{ApiClient, ApiError};
+use crate::models::Workout;
+
+const SESSIONS_PATH: &str = "/api/v1/training/sessions";
+
+/// Training session as returned by the API
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteSession {
+    pub id: String,
+    pub date: NaiveDate,
+    pub session_type: Option<String>,
+    pub duration_seconds: Option<i32>,
+    pub distance_meters: Option<f64>,
+    pub trainrs_data: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RemoteSession {
+    /// Notes saved with the session, if any
+    pub fn notes(&self) -> Option<String> {
+        self.trainrs_data
+            .as_ref()
+            .and_then(|data| data.get("notes"))
+            .and_then(|notes| notes.as_str())
+            .map(|notes| notes.to_string())
+    }
+
+    /// Convert to a local workout, keeping the local time of day when the date is unchanged
+    pub fn to_workout(&self, local: Option<&Workout>) -> Workout {
+        let date = match local {
+            Some(workout) if workout.date.date_naive() == self.date => workout.date,
+            _ => self.date.and_time(chrono::NaiveTime::MIN).and_utc(),
+        };
+
+        Workout {
+            id: self.id.clone(),
+            date,
+            exercise_type: self
+                .session_type
+                .clone()
+                .unwrap_or_else(|| "other".to_string()),
+            duration_minutes: self
+                .duration_seconds
+                .map(|seconds| (seconds.max(0) as u32 + 30) / 60),
+            distance_km: self.distance_meters.map(|meters| meters / 1000.0),
+            notes: self.notes(),
+            synced: true,
+            created_at: local.map(|w| w.created_at).unwrap_or(self.created_at),
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+/// Workout upload payload, keyed by the local workout ID
+#[derive(Debug, Serialize)]
+pub struct SessionUpload {
+    pub id: String,
+    pub date: NaiveDate,
+    pub session_type: Option<String>,
+    pub duration_seconds: Option<i32>,
+    pub distance_meters: Option<f64>,
+    pub notes: Option<String>,
+}
+
+impl From<&Workout> for SessionUpload {
+    fn from(workout: &Workout) -> Self {
+        Self {
+            id: workout.id.clone(),
+            date: workout.date.date_naive(),
+            session_type: Some(workout.exercise_type.clone()),
+            duration_seconds: workout.duration_minutes.map(|minutes| minutes as i32 * 60),
+            distance_meters: workout.distance_km.map(|km| km * 1000.0),
+            notes: workout.notes.clone(),
+        }
+    }
+}
+
+impl ApiClient {
+    /// Fetch one page of sessions changed after `since` (all sessions when `None`), oldest change first
+    pub async fn list_sessions_updated_since(
+        &self,
+        since: Option<DateTime<Utc>>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<RemoteSession>> {
+        let mut path = format!("{}?limit={}&offset={}", SESSIONS_PATH, limit, offset);
+        if let Some(since) = since {
+            path.push_str(&format!(
+                "&updated_since={}",
+                since.to_rfc3339_opts(SecondsFormat::Micros, true)
+            ));
+        }
+
+        let response = self.get(&path).await?;
+        let status = response.status();
+
+        if status.is_success() {
+            response
+                .json()
+                .await
+                .context("Failed to parse training sessions response")
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            Err(ApiError::from_status(status, error_text).into())
+        }
+    }
+
+    /// Create or update the server copy of a workout
+    pub async fn upload_session(&self, session: &SessionUpload) -> Result<RemoteSession> {
+        let response = self.post(SESSIONS_PATH, session).await?;
+        let status = response.status();
+
+        if status.is_success() {
+            response
+                .json()
+                .await
+                .context("Failed to parse uploaded session response")
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            Err(ApiError::from_status(status, error_text).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_remote_session() -> RemoteSession {
+        RemoteSession {
+            id: "6f1c1f4e-8d7a-4a55-9a1e-2f4b8c3d9e10".to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+            session_type: Some("running".to_string()),
+            duration_seconds: Some(2730),
+            distance_meters: Some(8500.0),
+            trainrs_data: Some(serde_json::json!({ "notes": "Tempo run", "tss": 55.0 })),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_remote_session_to_workout() {
+        let remote = create_remote_session();
+        let workout = remote.to_workout(None);
+
+        assert_eq!(workout.id, remote.id);
+        assert_eq!(workout.exercise_type, "running");
+        assert_eq!(workout.duration_minutes, Some(46));
+        assert_eq!(workout.distance_km, Some(8.5));
+        assert_eq!(workout.notes.as_deref(), Some("Tempo run"));
+        assert_eq!(workout.date.date_naive(), remote.date);
+        assert_eq!(workout.updated_at, remote.updated_at);
+        assert!(workout.synced);
+    }
+
+    #[test]
+    fn test_to_workout_keeps_local_time_of_day() {
+        let remote = create_remote_session();
+        let mut local = Workout::new("running".to_string(), Some(40), None, None);
+        local.date = remote.date.and_hms_opt(7, 30, 0).unwrap().and_utc();
+
+        let workout = remote.to_workout(Some(&local));
+
+        assert_eq!(workout.date, local.date);
+        assert_eq!(workout.created_at, local.created_at);
+    }
+
+    #[test]
+    fn test_session_upload_from_workout() {
+        let workout = Workout::new(
+            "cycling".to_string(),
+            Some(90),
+            Some(42.2),
+            Some("Group ride".to_string()),
+        );
+
+        let upload = SessionUpload::from(&workout);
+
+        assert_eq!(upload.id, workout.id);
+        assert_eq!(upload.date, workout.date.date_naive());
+        assert_eq!(upload.session_type.as_deref(), Some("cycling"));
+        assert_eq!(upload.duration_seconds, Some(5400));
+        assert_eq!(upload.distance_meters, Some(42200.0));
+        assert_eq!(upload.notes.as_deref(), Some("Group ride"));
+    }
+}
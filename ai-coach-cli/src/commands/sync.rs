@@ -1,17 +1,79 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use clap::Args;
+use comfy_table::{presets::UTF8_FULL, Cell, Color, ContentArrangement, Table};
+use dialoguer::Select;
 use indicatif::{ProgressBar, ProgressStyle};
 use std::time::Duration;
 
-use crate::api::ApiClient;
+use crate::api::{ApiClient, SessionUpload};
 use crate::config::Config;
+use crate::models::{SyncConflict, Workout};
 use crate::storage::Storage;
 
+/// Number of sessions requested per page when downloading changes
+const DOWNLOAD_PAGE_SIZE: u32 = 100;
+
 #[derive(Args)]
 pub struct SyncCommand {
     /// Preview changes without syncing
     #[arg(long)]
     dry_run: bool,
+
+    /// Interactively resolve conflicts left by the manual strategy
+    #[arg(long)]
+    resolve: bool,
+}
+
+/// How to handle workouts changed both locally and on the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    ServerWins,
+    LocalWins,
+    Manual,
+}
+
+impl ConflictStrategy {
+    /// Parse the `sync.conflict_resolution` config value
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "server_wins" => Some(Self::ServerWins),
+            "local_wins" => Some(Self::LocalWins),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+}
+
+/// What a downloaded session means for the local copy of the workout
+#[derive(Debug, PartialEq, Eq)]
+pub enum RemoteChange {
+    /// Not stored locally yet
+    New,
+    /// Changed on the server only
+    Update,
+    /// Already seen at this version (e.g. our own upload)
+    Unchanged,
+    /// Changed on the server while local edits are waiting to upload
+    Conflict,
+}
+
+/// Compare a downloaded session with the local workout using `updated_at`
+pub fn classify_change(
+    local: Option<&Workout>,
+    queued_for_sync: bool,
+    synced_version: Option<DateTime<Utc>>,
+    remote_updated_at: DateTime<Utc>,
+) -> RemoteChange {
+    if local.is_none() {
+        RemoteChange::New
+    } else if synced_version == Some(remote_updated_at) {
+        RemoteChange::Unchanged
+    } else if queued_for_sync {
+        RemoteChange::Conflict
+    } else {
+        RemoteChange::Update
+    }
 }
 
 impl SyncCommand {
@@ -40,12 +102,39 @@ impl SyncCommand {
             return Ok(());
         }
 
+        let strategy = match ConflictStrategy::from_config(&config.sync.conflict_resolution) {
+            Some(strategy) => strategy,
+            None => {
+                println!(
+                    "⚠️  Unknown conflict resolution strategy '{}', defaulting to server_wins",
+                    config.sync.conflict_resolution
+                );
+                ConflictStrategy::ServerWins
+            }
+        };
+
+        let client = ApiClient::new(config.clone()).context("Failed to create API client")?;
         let storage = Storage::init().context("Failed to initialize storage")?;
 
-        // Get unsynced workouts
-        let unsynced_workouts = storage
+        // Download first so server changes are compared before local edits overwrite them
+        self.download_workouts(&client, &storage, strategy).await?;
+        println!();
+
+        if self.resolve {
+            self.resolve_conflicts(&storage)?;
+            println!();
+        }
+
+        // Workouts with unresolved conflicts stay local until resolved
+        let mut unsynced_workouts = Vec::new();
+        for workout in storage
             .get_unsynced_workouts()
-            .context("Failed to get unsynced workouts")?;
+            .context("Failed to get unsynced workouts")?
+        {
+            if !storage.has_conflict(&workout.id)? {
+                unsynced_workouts.push(workout);
+            }
+        }
 
         if unsynced_workouts.is_empty() {
             println!("✓ No pending workouts to sync");
@@ -53,7 +142,7 @@ impl SyncCommand {
             println!("📤 Found {} workout(s) to upload", unsynced_workouts.len());
 
             if !self.dry_run {
-                self.upload_workouts(&config, &storage, &unsynced_workouts)
+                self.upload_workouts(&client, &storage, &unsynced_workouts)
                     .await?;
             } else {
                 for workout in &unsynced_workouts {
@@ -67,13 +156,11 @@ impl SyncCommand {
             }
         }
 
-        println!();
-
-        // Download latest data from server
-        if !self.dry_run {
-            self.download_workouts(&config, &storage).await?;
-        } else {
-            println!("📥 Would download latest workouts from server");
+        let pending_conflicts = storage.list_conflicts()?.len();
+        if pending_conflicts > 0 && !self.resolve {
+            println!();
+            println!("⚠️  {} conflict(s) waiting for resolution", pending_conflicts);
+            println!("   💡 Use 'ai-coach sync --resolve' to resolve conflicts");
         }
 
         println!();
@@ -98,12 +185,10 @@ impl SyncCommand {
 
     async fn upload_workouts(
         &self,
-        config: &Config,
+        client: &ApiClient,
         storage: &Storage,
-        workouts: &[crate::models::Workout],
+        workouts: &[Workout],
     ) -> Result<()> {
-        let client = ApiClient::new(config.clone()).context("Failed to create API client")?;
-
         // Create progress bar
         let pb = ProgressBar::new(workouts.len() as u64);
         pb.set_style(
@@ -119,20 +204,24 @@ impl SyncCommand {
         for workout in workouts {
             pb.set_message(format!("Uploading {}", workout.exercise_type));
 
-            // TODO: Implement actual API endpoint for workout upload
-            // For now, we'll simulate the upload
-            tokio::time::sleep(Duration::from_millis(100)).await;
-
-            // Simulate successful upload for now
-            let upload_success = true;
-
-            if upload_success {
-                storage
-                    .remove_from_sync_queue(&workout.id)
-                    .context("Failed to remove from sync queue")?;
-                uploaded += 1;
-            } else {
-                failed += 1;
+            match client.upload_session(&SessionUpload::from(workout)).await {
+                Ok(remote) => {
+                    let mut synced = workout.clone();
+                    synced.mark_synced();
+                    storage
+                        .save_workout(&synced)
+                        .context("Failed to save synced workout")?;
+                    storage
+                        .remove_from_sync_queue(&workout.id)
+                        .context("Failed to remove from sync queue")?;
+                    // Remember the server version so the next download skips our own change
+                    storage.set_synced_version(&workout.id, remote.updated_at)?;
+                    uploaded += 1;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to upload workout {}: {:#}", workout.id, e);
+                    failed += 1;
+                }
             }
 
             pb.inc(1);
@@ -151,9 +240,12 @@ impl SyncCommand {
         Ok(())
     }
 
-    async fn download_workouts(&self, config: &Config, storage: &Storage) -> Result<()> {
-        let _client = ApiClient::new(config.clone()).context("Failed to create API client")?;
-
+    async fn download_workouts(
+        &self,
+        client: &ApiClient,
+        storage: &Storage,
+        strategy: ConflictStrategy,
+    ) -> Result<()> {
         println!("📥 Downloading latest workouts from server...");
 
         // Create progress spinner
@@ -166,56 +258,227 @@ impl SyncCommand {
         pb.set_message("Fetching data...");
         pb.enable_steady_tick(Duration::from_millis(100));
 
-        // TODO: Implement actual API endpoint for workout download
-        // For now, we'll simulate the download
-        tokio::time::sleep(Duration::from_millis(500)).await;
+        let cursor = storage.get_sync_cursor()?;
+        let mut sessions = Vec::new();
+        loop {
+            let page = client
+                .list_sessions_updated_since(cursor, DOWNLOAD_PAGE_SIZE, sessions.len() as u32)
+                .await
+                .context("Failed to download training sessions")?;
+            let last_page = page.len() < DOWNLOAD_PAGE_SIZE as usize;
+            sessions.extend(page);
+            if last_page {
+                break;
+            }
+        }
 
-        // Simulate no new workouts for now
-        let downloaded = 0;
-        let conflicts = 0;
+        let mut downloaded = 0;
+        let mut conflicts = Vec::new();
+
+        for session in &sessions {
+            let local = storage.get_workout(&session.id)?;
+            let change = classify_change(
+                local.as_ref(),
+                storage.is_queued_for_sync(&session.id)?,
+                storage.get_synced_version(&session.id)?,
+                session.updated_at,
+            );
+
+            match (change, local) {
+                (RemoteChange::New, local) | (RemoteChange::Update, local) => {
+                    if !self.dry_run {
+                        storage.save_workout(&session.to_workout(local.as_ref()))?;
+                        storage.set_synced_version(&session.id, session.updated_at)?;
+                    }
+                    downloaded += 1;
+                }
+                (RemoteChange::Conflict, Some(local)) => {
+                    let server = session.to_workout(Some(&local));
+                    conflicts.push(SyncConflict::new(local, server));
+                }
+                _ => {}
+            }
+        }
 
         pb.finish_with_message(format!(
             "Downloaded: {}, Conflicts: {}",
-            downloaded, conflicts
+            downloaded,
+            conflicts.len()
         ));
 
-        if conflicts > 0 {
+        if !self.dry_run {
+            if let Some(latest) = sessions.iter().map(|s| s.updated_at).max() {
+                storage.set_sync_cursor(latest)?;
+            }
+        }
+
+        if !conflicts.is_empty() {
             println!();
-            self.handle_conflicts(config, storage, conflicts).await?;
+            self.handle_conflicts(strategy, storage, conflicts)?;
         }
 
         Ok(())
     }
 
-    async fn handle_conflicts(
+    fn handle_conflicts(
         &self,
-        config: &Config,
-        _storage: &Storage,
-        conflicts: usize,
+        strategy: ConflictStrategy,
+        storage: &Storage,
+        conflicts: Vec<SyncConflict>,
     ) -> Result<()> {
-        println!("⚠️  Found {} conflict(s)", conflicts);
+        println!("⚠️  Found {} conflict(s)", conflicts.len());
         println!();
 
-        match config.sync.conflict_resolution.as_str() {
-            "server_wins" => {
+        if self.dry_run {
+            println!("   Would resolve using {:?}", strategy);
+            return Ok(());
+        }
+
+        match strategy {
+            ConflictStrategy::ServerWins => {
                 println!("   Using server version (configured: server_wins)");
-                // TODO: Apply server changes
+                for conflict in &conflicts {
+                    apply_server_version(storage, conflict)?;
+                }
             }
-            "local_wins" => {
+            ConflictStrategy::LocalWins => {
                 println!("   Using local version (configured: local_wins)");
-                // TODO: Keep local changes
+                for conflict in &conflicts {
+                    keep_local_version(storage, conflict)?;
+                }
             }
-            "manual" => {
+            ConflictStrategy::Manual => {
                 println!("   Manual resolution required (configured: manual)");
-                println!("   💡 Use 'ai-coach sync --resolve' to resolve conflicts");
-                // TODO: Implement interactive conflict resolution
+                for conflict in &conflicts {
+                    storage.save_conflict(conflict)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Walk through stored conflicts, showing a diff and asking which version to keep
+    fn resolve_conflicts(&self, storage: &Storage) -> Result<()> {
+        let conflicts = storage.list_conflicts()?;
+        if conflicts.is_empty() {
+            println!("✓ No conflicts to resolve");
+            return Ok(());
+        }
+
+        println!("🔀 Resolving {} conflict(s)", conflicts.len());
+
+        for conflict in &conflicts {
+            println!();
+            println!(
+                "  Workout {} ({}, {})",
+                &conflict.workout_id[..8.min(conflict.workout_id.len())],
+                conflict.local.exercise_type,
+                conflict.local.date.format("%Y-%m-%d")
+            );
+            println!("{}", conflict_table(conflict));
+
+            if self.dry_run {
+                println!("   Would ask which version to keep");
+                continue;
             }
-            _ => {
-                println!("   Unknown conflict resolution strategy");
-                println!("   Defaulting to server_wins");
+
+            let selection = Select::new()
+                .with_prompt("Which version do you want to keep?")
+                .items(&["Keep local", "Use server", "Skip for now"])
+                .default(0)
+                .interact()
+                .context("Failed to get selection")?;
+
+            match selection {
+                0 => {
+                    keep_local_version(storage, conflict)?;
+                    println!("   ✓ Keeping local version");
+                }
+                1 => {
+                    apply_server_version(storage, conflict)?;
+                    println!("   ✓ Using server version");
+                }
+                _ => println!("   Skipped"),
             }
         }
 
         Ok(())
     }
 }
+
+/// Replace the local workout with the server copy and drop its pending upload
+fn apply_server_version(storage: &Storage, conflict: &SyncConflict) -> Result<()> {
+    storage.save_workout(&conflict.server)?;
+    storage.remove_from_sync_queue(&conflict.workout_id)?;
+    storage.set_synced_version(&conflict.workout_id, conflict.server.updated_at)?;
+    storage.remove_conflict(&conflict.workout_id)
+}
+
+/// Keep the local workout queued so the next upload overwrites the server copy
+fn keep_local_version(storage: &Storage, conflict: &SyncConflict) -> Result<()> {
+    storage.set_synced_version(&conflict.workout_id, conflict.server.updated_at)?;
+    storage.remove_conflict(&conflict.workout_id)
+}
+
+fn conflict_table(conflict: &SyncConflict) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic);
+
+    table.set_header(vec![
+        Cell::new("Field").fg(Color::Cyan),
+        Cell::new("Local").fg(Color::Cyan),
+        Cell::new("Server").fg(Color::Cyan),
+    ]);
+
+    for diff in conflict.differences() {
+        table.add_row(vec![
+            Cell::new(diff.field),
+            Cell::new(diff.local).fg(Color::Yellow),
+            Cell::new(diff.server).fg(Color::Green),
+        ]);
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflict_strategy_from_config() {
+        assert_eq!(ConflictStrategy::from_config("server_wins"), Some(ConflictStrategy::ServerWins));
+        assert_eq!(ConflictStrategy::from_config("local_wins"), Some(ConflictStrategy::LocalWins));
+        assert_eq!(ConflictStrategy::from_config("manual"), Some(ConflictStrategy::Manual));
+        assert_eq!(ConflictStrategy::from_config("newest"), None);
+    }
+
+    #[test]
+    fn test_classify_change() {
+        let workout = Workout::new("running".to_string(), Some(30), None, None);
+        let synced_at = Utc::now() - chrono::Duration::hours(1);
+        let changed_at = Utc::now();
+
+        assert_eq!(classify_change(None, false, None, changed_at), RemoteChange::New);
+        assert_eq!(
+            classify_change(Some(&workout), false, Some(synced_at), synced_at),
+            RemoteChange::Unchanged
+        );
+        assert_eq!(
+            classify_change(Some(&workout), false, Some(synced_at), changed_at),
+            RemoteChange::Update
+        );
+        assert_eq!(
+            classify_change(Some(&workout), true, Some(synced_at), changed_at),
+            RemoteChange::Conflict
+        );
+        // Local edits still win when the server copy is the one we last synced
+        assert_eq!(
+            classify_change(Some(&workout), true, Some(changed_at), changed_at),
+            RemoteChange::Unchanged
+        );
+    }
+}
@@ -1,5 +1,7 @@
 pub mod goal;
+pub mod sync;
 pub mod workout;
 
 pub use goal::{Goal, GoalType};
+pub use sync::SyncConflict;
 pub use workout::{Workout, WorkoutFilter};
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::Workout;
+
+/// Workout edited both locally and on the server since the last sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub workout_id: String,
+    pub local: Workout,
+    pub server: Workout,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// A field whose local and server values differ
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    pub field: &'static str,
+    pub local: String,
+    pub server: String,
+}
+
+impl SyncConflict {
+    pub fn new(local: Workout, server: Workout) -> Self {
+        Self {
+            workout_id: local.id.clone(),
+            local,
+            server,
+            detected_at: Utc::now(),
+        }
+    }
+
+    /// Fields that differ between the local and server versions
+    pub fn differences(&self) -> Vec<FieldDiff> {
+        let fields = [
+            (
+                "date",
+                self.local.date.format("%Y-%m-%d").to_string(),
+                self.server.date.format("%Y-%m-%d").to_string(),
+            ),
+            (
+                "type",
+                self.local.exercise_type.clone(),
+                self.server.exercise_type.clone(),
+            ),
+            (
+                "duration",
+                display_option(self.local.duration_minutes.map(|m| format!("{} min", m))),
+                display_option(self.server.duration_minutes.map(|m| format!("{} min", m))),
+            ),
+            (
+                "distance",
+                display_option(self.local.distance_km.map(|d| format!("{:.2} km", d))),
+                display_option(self.server.distance_km.map(|d| format!("{:.2} km", d))),
+            ),
+            (
+                "notes",
+                display_option(self.local.notes.clone()),
+                display_option(self.server.notes.clone()),
+            ),
+        ];
+
+        fields
+            .into_iter()
+            .filter(|(_, local, server)| local != server)
+            .map(|(field, local, server)| FieldDiff { field, local, server })
+            .collect()
+    }
+}
+
+fn display_option(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_differences_lists_changed_fields() {
+        let local = Workout::new(
+            "running".to_string(),
+            Some(45),
+            Some(8.0),
+            Some("Easy".to_string()),
+        );
+        let mut server = local.clone();
+        server.duration_minutes = Some(50);
+        server.notes = None;
+
+        let conflict = SyncConflict::new(local, server);
+        let diffs = conflict.differences();
+
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(
+            diffs[0],
+            FieldDiff {
+                field: "duration",
+                local: "45 min".to_string(),
+                server: "50 min".to_string(),
+            }
+        );
+        assert_eq!(diffs[1].field, "notes");
+        assert_eq!(diffs[1].server, "-");
+    }
+}
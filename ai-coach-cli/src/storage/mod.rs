@@ -2,14 +2,20 @@
 // Avoids rusqlite/sqlx libsqlite3-sys conflict
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use sled::Db;
 use std::path::PathBuf;
 
-use crate::models::{Goal, Workout};
+use crate::models::{Goal, SyncConflict, Workout};
 
 const WORKOUTS_TREE: &str = "workouts";
 const GOALS_TREE: &str = "goals";
 const SYNC_QUEUE_TREE: &str = "sync_queue";
+const SYNC_STATE_TREE: &str = "sync_state";
+const SYNC_VERSIONS_TREE: &str = "sync_versions";
+const SYNC_CONFLICTS_TREE: &str = "sync_conflicts";
+
+const WORKOUTS_CURSOR_KEY: &str = "workouts_cursor";
 
 /// Storage manager for local embedded database
 pub struct Storage {
@@ -197,6 +203,135 @@ impl Storage {
         Ok(())
     }
 
+    /// Check whether a workout has local changes waiting to be uploaded
+    pub fn is_queued_for_sync(&self, workout_id: &str) -> Result<bool> {
+        let tree = self
+            .db
+            .open_tree(SYNC_QUEUE_TREE)
+            .context("Failed to open sync queue tree")?;
+
+        tree.contains_key(workout_id.as_bytes())
+            .context("Failed to read sync queue")
+    }
+
+    /// Get the server change time up to which workouts have been downloaded
+    pub fn get_sync_cursor(&self) -> Result<Option<DateTime<Utc>>> {
+        let tree = self
+            .db
+            .open_tree(SYNC_STATE_TREE)
+            .context("Failed to open sync state tree")?;
+
+        read_timestamp(tree.get(WORKOUTS_CURSOR_KEY).context("Failed to get sync cursor")?)
+    }
+
+    /// Save the server change time up to which workouts have been downloaded
+    pub fn set_sync_cursor(&self, cursor: DateTime<Utc>) -> Result<()> {
+        let tree = self
+            .db
+            .open_tree(SYNC_STATE_TREE)
+            .context("Failed to open sync state tree")?;
+
+        tree.insert(WORKOUTS_CURSOR_KEY, cursor.to_rfc3339().as_bytes())
+            .context("Failed to save sync cursor")?;
+
+        self.db.flush().context("Failed to flush database")?;
+
+        tracing::debug!("Advanced sync cursor to {}", cursor);
+        Ok(())
+    }
+
+    /// Get the server `updated_at` of a workout as of its last sync
+    pub fn get_synced_version(&self, workout_id: &str) -> Result<Option<DateTime<Utc>>> {
+        let tree = self
+            .db
+            .open_tree(SYNC_VERSIONS_TREE)
+            .context("Failed to open sync versions tree")?;
+
+        read_timestamp(tree.get(workout_id.as_bytes()).context("Failed to get synced version")?)
+    }
+
+    /// Record the server `updated_at` of a workout after syncing it
+    pub fn set_synced_version(&self, workout_id: &str, updated_at: DateTime<Utc>) -> Result<()> {
+        let tree = self
+            .db
+            .open_tree(SYNC_VERSIONS_TREE)
+            .context("Failed to open sync versions tree")?;
+
+        tree.insert(workout_id.as_bytes(), updated_at.to_rfc3339().as_bytes())
+            .context("Failed to save synced version")?;
+
+        self.db.flush().context("Failed to flush database")?;
+
+        Ok(())
+    }
+
+    /// Save a conflict for manual resolution
+    pub fn save_conflict(&self, conflict: &SyncConflict) -> Result<()> {
+        let tree = self
+            .db
+            .open_tree(SYNC_CONFLICTS_TREE)
+            .context("Failed to open sync conflicts tree")?;
+
+        let key = conflict.workout_id.as_bytes();
+        let value = bincode::serialize(conflict).context("Failed to serialize conflict")?;
+
+        tree.insert(key, value)
+            .context("Failed to insert conflict")?;
+
+        self.db.flush().context("Failed to flush database")?;
+
+        tracing::debug!("Saved sync conflict for workout {}", conflict.workout_id);
+        Ok(())
+    }
+
+    /// List conflicts waiting for manual resolution
+    pub fn list_conflicts(&self) -> Result<Vec<SyncConflict>> {
+        let tree = self
+            .db
+            .open_tree(SYNC_CONFLICTS_TREE)
+            .context("Failed to open sync conflicts tree")?;
+
+        let mut conflicts = Vec::new();
+
+        for item in tree.iter() {
+            let (_key, value) = item.context("Failed to iterate conflicts")?;
+            let conflict: SyncConflict =
+                bincode::deserialize(&value).context("Failed to deserialize conflict")?;
+            conflicts.push(conflict);
+        }
+
+        conflicts.sort_by_key(|conflict| conflict.detected_at);
+
+        Ok(conflicts)
+    }
+
+    /// Check whether a workout has an unresolved conflict
+    pub fn has_conflict(&self, workout_id: &str) -> Result<bool> {
+        let tree = self
+            .db
+            .open_tree(SYNC_CONFLICTS_TREE)
+            .context("Failed to open sync conflicts tree")?;
+
+        tree.contains_key(workout_id.as_bytes())
+            .context("Failed to read sync conflicts")
+    }
+
+    /// Remove a resolved conflict
+    pub fn remove_conflict(&self, workout_id: &str) -> Result<()> {
+        let tree = self
+            .db
+            .open_tree(SYNC_CONFLICTS_TREE)
+            .context("Failed to open sync conflicts tree")?;
+
+        tree.remove(workout_id.as_bytes())
+            .context("Failed to remove conflict")?;
+
+        self.db.flush().context("Failed to flush database")?;
+
+        tracing::debug!("Removed sync conflict for workout {}", workout_id);
+        Ok(())
+    }
+
     // Goal operations
 
     /// Save a goal
@@ -299,6 +434,17 @@ impl Storage {
     }
 }
 
+fn read_timestamp(value: Option<sled::IVec>) -> Result<Option<DateTime<Utc>>> {
+    value
+        .map(|bytes| {
+            let text = std::str::from_utf8(&bytes).context("Invalid timestamp encoding")?;
+            DateTime::parse_from_rfc3339(text)
+                .map(|dt| dt.with_timezone(&Utc))
+                .context("Invalid timestamp")
+        })
+        .transpose()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -371,4 +517,41 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_sync_cursor_and_versions() -> Result<()> {
+        let storage = create_test_storage()?;
+        assert!(storage.get_sync_cursor()?.is_none());
+
+        let cursor = chrono::Utc::now();
+        storage.set_sync_cursor(cursor)?;
+        assert_eq!(storage.get_sync_cursor()?, Some(cursor));
+
+        storage.set_synced_version("workout-1", cursor)?;
+        assert_eq!(storage.get_synced_version("workout-1")?, Some(cursor));
+        assert!(storage.get_synced_version("workout-2")?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_list_and_remove_conflict() -> Result<()> {
+        let storage = create_test_storage()?;
+
+        let local = Workout::new("running".to_string(), Some(45), None, None);
+        let mut server = local.clone();
+        server.duration_minutes = Some(50);
+
+        storage.save_conflict(&SyncConflict::new(local.clone(), server))?;
+        assert!(storage.has_conflict(&local.id)?);
+
+        let conflicts = storage.list_conflicts()?;
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].server.duration_minutes, Some(50));
+
+        storage.remove_conflict(&local.id)?;
+        assert!(!storage.has_conflict(&local.id)?);
+
+        Ok(())
+    }
 }
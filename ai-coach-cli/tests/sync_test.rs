@@ -0,0 +1,117 @@
+use ai_coach_cli::api::{ApiClient, SessionUpload};
+use ai_coach_cli::config::Config;
+use ai_coach_cli::models::Workout;
+use anyhow::Result;
+use chrono::{TimeZone, Utc};
+use mockito::Matcher;
+
+fn create_test_config(base_url: String) -> Config {
+    let mut config = Config::default();
+    config.api.base_url = base_url;
+    config.set_tokens("access".to_string(), "refresh".to_string());
+    config
+}
+
+#[tokio::test]
+async fn test_list_sessions_updated_since_sends_cursor() -> Result<()> {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", "/api/v1/training/sessions")
+        .match_header("authorization", "Bearer access")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("limit".into(), "100".into()),
+            Matcher::UrlEncoded("offset".into(), "0".into()),
+            Matcher::UrlEncoded("updated_since".into(), "2024-03-01T08:00:00.000000Z".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"[{
+                "id": "6f1c1f4e-8d7a-4a55-9a1e-2f4b8c3d9e10",
+                "user_id": "0b7b1c4e-1111-4a55-9a1e-2f4b8c3d9e10",
+                "date": "2024-03-02",
+                "trainrs_data": {"notes": "Hill repeats"},
+                "uploaded_file_path": null,
+                "session_type": "running",
+                "duration_seconds": 3600,
+                "distance_meters": 10000.0,
+                "created_at": "2024-03-02T07:00:00Z",
+                "updated_at": "2024-03-02T09:00:00Z"
+            }]"#,
+        )
+        .create_async()
+        .await;
+
+    let client = ApiClient::new(create_test_config(server.url()))?;
+    let since = Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 0).unwrap();
+    let sessions = client.list_sessions_updated_since(Some(since), 100, 0).await?;
+
+    mock.assert_async().await;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].notes().as_deref(), Some("Hill repeats"));
+
+    let workout = sessions[0].to_workout(None);
+    assert_eq!(workout.exercise_type, "running");
+    assert_eq!(workout.duration_minutes, Some(60));
+    assert_eq!(workout.distance_km, Some(10.0));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upload_session_posts_workout() -> Result<()> {
+    let mut server = mockito::Server::new_async().await;
+    let workout = Workout::new("cycling".to_string(), Some(90), Some(40.0), None);
+
+    let mock = server
+        .mock("POST", "/api/v1/training/sessions")
+        .match_body(Matcher::PartialJson(serde_json::json!({
+            "id": workout.id,
+            "session_type": "cycling",
+            "duration_seconds": 5400,
+            "distance_meters": 40000.0
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(format!(
+            r#"{{
+                "id": "{}",
+                "date": "{}",
+                "trainrs_data": {{"notes": null}},
+                "session_type": "cycling",
+                "duration_seconds": 5400,
+                "distance_meters": 40000.0,
+                "created_at": "2024-03-02T07:00:00Z",
+                "updated_at": "2024-03-02T09:00:00Z"
+            }}"#,
+            workout.id,
+            workout.date.format("%Y-%m-%d")
+        ))
+        .create_async()
+        .await;
+
+    let client = ApiClient::new(create_test_config(server.url()))?;
+    let remote = client.upload_session(&SessionUpload::from(&workout)).await?;
+
+    mock.assert_async().await;
+    assert_eq!(remote.id, workout.id);
+    assert_eq!(remote.updated_at, Utc.with_ymd_and_hms(2024, 3, 2, 9, 0, 0).unwrap());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upload_session_conflict_is_an_error() -> Result<()> {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", "/api/v1/training/sessions")
+        .with_status(409)
+        .create_async()
+        .await;
+
+    let client = ApiClient::new(create_test_config(server.url()))?;
+    let workout = Workout::new("running".to_string(), Some(30), None, None);
+    let result = client.upload_session(&SessionUpload::from(&workout)).await;
+
+    mock.assert_async().await;
+    assert!(result.is_err());
+    Ok(())
+}